@@ -0,0 +1,512 @@
+//! `coolslides lsp`: a language server for deck files, spoken over stdio
+//!
+//! Diagnostics come from the same validation as `coolslides validate`, run on
+//! the deck as it is on disk with the editor's open buffers laid over it.
+//! Completion, hover, and go-to-definition draw on the deck's component
+//! registry and slide ids.
+
+use coolslides_core::fixes::{self, FixEdit};
+use coolslides_core::{catalog, registry, validation, ComponentRegistry, DeckManifest, SlideDoc};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tokio::sync::RwLock;
+use tower_lsp::jsonrpc::Result as RpcResult;
+use tower_lsp::lsp_types::*;
+use tower_lsp::{Client, LanguageServer, LspService, Server};
+
+/// Serve the language server protocol on stdin and stdout until the client exits
+pub async fn run(deck_dir: &Path) {
+    let default_root = deck_dir.canonicalize().unwrap_or_else(|_| deck_dir.to_path_buf());
+    let (service, socket) = LspService::new(|client| Backend {
+        client,
+        documents: RwLock::new(HashMap::new()),
+        default_root,
+    });
+    Server::new(tokio::io::stdin(), tokio::io::stdout(), socket).serve(service).await;
+}
+
+struct Backend {
+    client: Client,
+    /// Contents of open documents, which take precedence over the files on disk
+    documents: RwLock<HashMap<PathBuf, String>>,
+    /// Deck used for files that are not under a `slides.toml`
+    default_root: PathBuf,
+}
+
+struct SlideFile {
+    path: PathBuf,
+    source: String,
+    doc: Result<SlideDoc, toml::de::Error>,
+}
+
+/// A deck's files as the editor currently sees them
+struct DeckSnapshot {
+    root: PathBuf,
+    manifest_source: String,
+    manifest: Result<DeckManifest, toml::de::Error>,
+    slides: Vec<SlideFile>,
+    registry: Option<ComponentRegistry>,
+}
+
+fn is_slide_file(path: &Path) -> bool {
+    path.file_name().and_then(|name| name.to_str()).is_some_and(|name| name.ends_with(".slide.toml"))
+}
+
+fn is_manifest(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| name == "slides.toml")
+}
+
+impl DeckSnapshot {
+    fn load(root: PathBuf, documents: &HashMap<PathBuf, String>) -> Self {
+        let read = |path: &Path| documents.get(path).cloned().or_else(|| std::fs::read_to_string(path).ok());
+        let manifest_source = read(&root.join("slides.toml")).unwrap_or_default();
+        let manifest = toml::from_str::<DeckManifest>(&manifest_source);
+
+        let content_dir = root.join("content");
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(&content_dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| is_slide_file(path))
+            .collect();
+        // Slides created in the editor but not saved yet
+        for path in documents.keys() {
+            if is_slide_file(path) && path.parent() == Some(content_dir.as_path()) && !paths.contains(path) {
+                paths.push(path.clone());
+            }
+        }
+        paths.sort();
+        let slides = paths
+            .into_iter()
+            .filter_map(|path| {
+                let source = read(&path)?;
+                let doc = toml::from_str(&source);
+                Some(SlideFile { path, source, doc })
+            })
+            .collect();
+
+        let registry = manifest.as_ref().ok().and_then(|deck| registry::load_deck_registry(deck, &root).registry);
+        Self { root, manifest_source, manifest, slides, registry }
+    }
+
+    fn source(&self, path: &Path) -> Option<&str> {
+        if path == self.root.join("slides.toml") {
+            return Some(&self.manifest_source);
+        }
+        self.slides.iter().find(|slide| slide.path == path).map(|slide| slide.source.as_str())
+    }
+
+    fn slide(&self, id: &str) -> Option<(&SlideFile, &SlideDoc)> {
+        self.slides.iter().find_map(|file| file.doc.as_ref().ok().filter(|doc| doc.id == id).map(|doc| (file, doc)))
+    }
+
+    fn docs(&self) -> Vec<SlideDoc> {
+        self.slides.iter().filter_map(|file| file.doc.as_ref().ok().cloned()).collect()
+    }
+
+    /// Diagnostics for every file in the deck; files without problems get an
+    /// empty list so stale diagnostics are cleared
+    fn diagnostics(&self) -> HashMap<PathBuf, Vec<Diagnostic>> {
+        let manifest_path = self.root.join("slides.toml");
+        let mut by_file: HashMap<PathBuf, Vec<Diagnostic>> = HashMap::new();
+        by_file.insert(manifest_path.clone(), Vec::new());
+        for slide in &self.slides {
+            let diagnostics = match &slide.doc {
+                Ok(_) => Vec::new(),
+                Err(e) => vec![parse_diagnostic(&slide.source, e)],
+            };
+            by_file.insert(slide.path.clone(), diagnostics);
+        }
+        let manifest = match &self.manifest {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                by_file.insert(manifest_path, vec![parse_diagnostic(&self.manifest_source, e)]);
+                return by_file;
+            }
+        };
+
+        let slides = self.docs();
+        let mut result = validation::validate_deck_with_registry(manifest, &slides, self.registry.as_ref());
+        result.merge(validation::validate_budgets(manifest, &slides, Some(&self.root)));
+        result.merge(validation::validate_narration(&slides, &self.root));
+
+        let errors = result.errors.iter().map(|e| (e, DiagnosticSeverity::ERROR));
+        let warnings = result.warnings.iter().map(|w| (w, DiagnosticSeverity::WARNING));
+        for (error, severity) in errors.chain(warnings) {
+            if matches!(
+                error,
+                validation::ValidationError::TagCollision { .. }
+                    | validation::ValidationError::ShadowedComponent { .. }
+                    | validation::ValidationError::AssetBudgetExceeded { .. }
+            ) {
+                // Registry and asset diagnostics belong to files outside the deck sources
+                continue;
+            }
+            let (path, source) = match crate::extract_slide_id_from_error(error).and_then(|id| self.slide(&id)) {
+                Some((file, _)) => (file.path.clone(), file.source.as_str()),
+                None => (manifest_path.clone(), self.manifest_source.as_str()),
+            };
+            let span = error.span_in(source).unwrap_or(0..0);
+            by_file.entry(path).or_default().push(Diagnostic {
+                range: range_of(source, span),
+                severity: Some(severity),
+                code: Some(NumberOrString::String(error.code().to_string())),
+                source: Some("coolslides".to_string()),
+                message: error.message(),
+                ..Default::default()
+            });
+        }
+        by_file
+    }
+
+    fn completions(&self, path: &Path, source: &str, offset: usize) -> Vec<CompletionItem> {
+        let before = &source[..offset];
+        let line = before.rsplit('\n').next().unwrap_or_default();
+        let in_string = line.matches('"').count() % 2 == 1;
+
+        if is_manifest(path) {
+            // Slide ids in `sequence`, group `slides`, and `ref` entries
+            if in_string && matches!(last_key(before), Some("sequence" | "slides" | "ref")) {
+                return self
+                    .slides
+                    .iter()
+                    .filter_map(|file| file.doc.as_ref().ok())
+                    .map(|doc| CompletionItem {
+                        label: doc.id.clone(),
+                        kind: Some(CompletionItemKind::REFERENCE),
+                        detail: Some(doc.component.name.clone()),
+                        ..Default::default()
+                    })
+                    .collect();
+            }
+            return Vec::new();
+        }
+
+        let Some(registry) = &self.registry else {
+            return Vec::new();
+        };
+        let schema = table_value(source, "component", "name")
+            .and_then(|name| registry.components.get(name))
+            .map(|component| &component.schema);
+        match (current_table(before), assignment(line)) {
+            (Some("component"), Some("name")) if in_string => catalog::sorted(registry)
+                .into_iter()
+                .map(|component| CompletionItem {
+                    label: component.name.clone(),
+                    kind: Some(CompletionItemKind::CLASS),
+                    detail: Some(format!("{} <{}>", component.version, component.tag)),
+                    deprecated: component.deprecated.is_some().then_some(true),
+                    ..Default::default()
+                })
+                .collect(),
+            (Some("props"), Some(key)) if in_string => schema
+                .and_then(|schema| schema.pointer(&format!("/properties/{}/enum", key)))
+                .and_then(serde_json::Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(serde_json::Value::as_str)
+                .map(|value| CompletionItem {
+                    label: value.to_string(),
+                    kind: Some(CompletionItemKind::ENUM_MEMBER),
+                    ..Default::default()
+                })
+                .collect(),
+            (Some("props"), None) if line.trim().chars().all(is_key_char) => schema
+                .map(catalog::prop_docs)
+                .unwrap_or_default()
+                .into_iter()
+                .enumerate()
+                .map(|(index, prop)| CompletionItem {
+                    label: prop.name.clone(),
+                    kind: Some(CompletionItemKind::PROPERTY),
+                    detail: Some(if prop.required { format!("{} (required)", prop.ty) } else { prop.ty.clone() }),
+                    documentation: Some(Documentation::MarkupContent(markdown(catalog::prop_description(&prop)))),
+                    deprecated: prop.deprecated.is_some().then_some(true),
+                    // Required props first, as in the catalog
+                    sort_text: Some(format!("{:04}", index)),
+                    insert_text: Some(format!("{} = ", prop.name)),
+                    ..Default::default()
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn hover(&self, path: &Path, source: &str, offset: usize) -> Option<Hover> {
+        let span = word_at(source, offset)?;
+        let word = &source[span.clone()];
+        let before = &source[..span.start];
+        let line = before.rsplit('\n').next().unwrap_or_default();
+
+        let text = if is_manifest(path) {
+            let (file, doc) = self.slide(word)?;
+            let file = file.path.strip_prefix(&self.root).unwrap_or(&file.path);
+            format!("Slide `{}`: **{}**, {}", doc.id, doc.component.name, file.display())
+        } else {
+            let registry = self.registry.as_ref()?;
+            match current_table(before) {
+                Some("component") if assignment(line) == Some("name") => {
+                    component_summary(registry.components.get(word)?)
+                }
+                Some("props") if assignment(line).is_none() => {
+                    let component = registry.components.get(table_value(source, "component", "name")?)?;
+                    let prop = catalog::prop_docs(&component.schema).into_iter().find(|prop| prop.name == word)?;
+                    let mut text = format!("**{}**: `{}`{}", prop.name, prop.ty, if prop.required { " (required)" } else { "" });
+                    if let Some(default) = &prop.default {
+                        text.push_str(&format!("\n\nDefault: `{}`", default));
+                    }
+                    let description = catalog::prop_description(&prop);
+                    if !description.is_empty() {
+                        text.push_str(&format!("\n\n{}", description));
+                    }
+                    text
+                }
+                _ => return None,
+            }
+        };
+        Some(Hover {
+            contents: HoverContents::Markup(markdown(text)),
+            range: Some(range_of(source, span)),
+        })
+    }
+
+    fn definition(&self, path: &Path, source: &str, offset: usize) -> Option<Location> {
+        let span = word_at(source, offset)?;
+        let word = &source[span.clone()];
+        if is_manifest(path) {
+            // Sequence entry to the slide file, at its `id`
+            let (file, _) = self.slide(word)?;
+            let edit = FixEdit { original: word.to_string(), replacement: String::new(), quoted: true, key: None, safe: false };
+            let target = fixes::locate(&edit, "", &file.source).into_iter().next().map_or(0..0, |fix| fix.span);
+            return Some(Location::new(Url::from_file_path(&file.path).ok()?, range_of(&file.source, target)));
+        }
+        // Component name to the manifest it was loaded from
+        let before = &source[..span.start];
+        let line = before.rsplit('\n').next().unwrap_or_default();
+        if current_table(before) != Some("component") || assignment(line) != Some("name") {
+            return None;
+        }
+        let manifest = PathBuf::from(self.registry.as_ref()?.sources.get(word)?);
+        let manifest = if manifest.is_absolute() { manifest } else { self.root.join(manifest) };
+        Some(Location::new(Url::from_file_path(manifest).ok()?, Range::default()))
+    }
+}
+
+fn markdown(value: String) -> MarkupContent {
+    MarkupContent { kind: MarkupKind::Markdown, value }
+}
+
+fn component_summary(component: &coolslides_core::ComponentManifest) -> String {
+    let mut text = format!("**{}** {} `<{}>`", component.name, component.version, component.tag);
+    if let Some(deprecation) = &component.deprecated {
+        text.push_str(&format!("\n\n**Deprecated:** {}", deprecation));
+    }
+    let props = catalog::prop_docs(&component.schema);
+    if !props.is_empty() {
+        text.push_str("\n\n");
+        for prop in props {
+            text.push_str(&format!("- `{}`: {}{}\n", prop.name, prop.ty, if prop.required { " (required)" } else { "" }));
+        }
+    }
+    text
+}
+
+fn parse_diagnostic(source: &str, error: &toml::de::Error) -> Diagnostic {
+    Diagnostic {
+        range: range_of(source, error.span().unwrap_or(0..0)),
+        severity: Some(DiagnosticSeverity::ERROR),
+        source: Some("coolslides".to_string()),
+        message: error.message().to_string(),
+        ..Default::default()
+    }
+}
+
+fn is_key_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-'
+}
+
+/// Name of the `[table]` header above the end of `before`
+fn current_table(before: &str) -> Option<&str> {
+    let header = before.lines().rev().map(str::trim).find(|line| line.starts_with('['))?;
+    Some(header.trim_start_matches('[').split(']').next().unwrap_or_default().trim())
+}
+
+/// Key being assigned on a line that has reached its `=`
+fn assignment(line: &str) -> Option<&str> {
+    let (key, _) = line.split_once('=')?;
+    let key = key.trim().trim_start_matches('{').trim();
+    let key = key.rsplit([',', '{']).next().unwrap_or(key).trim();
+    (!key.is_empty() && key.chars().all(is_key_char)).then_some(key)
+}
+
+/// The most recently assigned key, which for multi-line arrays is the array's key
+fn last_key(before: &str) -> Option<&str> {
+    let at = before.rfind('=')?;
+    let key = before[..at].trim_end();
+    let start = key.rfind(|c: char| !is_key_char(c)).map_or(0, |i| i + 1);
+    Some(&key[start..]).filter(|key| !key.is_empty())
+}
+
+/// Value of `key` in `[table]`, read from the text so it works while the file
+/// does not parse
+fn table_value<'a>(source: &'a str, table: &str, key: &str) -> Option<&'a str> {
+    let header = format!("[{}]", table);
+    let mut lines = source.lines().map(str::trim).skip_while(|line| *line != header).skip(1);
+    lines.find_map(|line| {
+        if line.starts_with('[') {
+            return Some(None);
+        }
+        let (k, value) = line.split_once('=')?;
+        (k.trim() == key).then(|| Some(value.trim().trim_matches('"')))
+    })?
+}
+
+fn word_at(source: &str, offset: usize) -> Option<std::ops::Range<usize>> {
+    let is_word = |c: char| is_key_char(c) || c == '.';
+    let start = source[..offset].rfind(|c: char| !is_word(c)).map_or(0, |i| i + 1);
+    let end = source[offset..].find(|c: char| !is_word(c)).map_or(source.len(), |i| offset + i);
+    (start < end).then_some(start..end)
+}
+
+/// LSP positions count UTF-16 code units within the line
+fn position_at(source: &str, offset: usize) -> Position {
+    let before = &source[..offset.min(source.len())];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let (line, _) = fixes::line_col(source, offset);
+    Position::new(line as u32 - 1, before[line_start..].encode_utf16().count() as u32)
+}
+
+fn offset_at(source: &str, position: Position) -> usize {
+    let line_start = source.split_inclusive('\n').take(position.line as usize).map(str::len).sum::<usize>();
+    let line = source[line_start..].split('\n').next().unwrap_or_default();
+    let mut units = 0;
+    for (i, c) in line.char_indices() {
+        if units >= position.character as usize {
+            return line_start + i;
+        }
+        units += c.len_utf16();
+    }
+    line_start + line.len()
+}
+
+fn range_of(source: &str, span: std::ops::Range<usize>) -> Range {
+    Range::new(position_at(source, span.start), position_at(source, span.end))
+}
+
+impl Backend {
+    /// Deck directory holding `path`: the nearest ancestor with a `slides.toml`
+    fn deck_root(&self, path: &Path) -> PathBuf {
+        path.ancestors()
+            .skip(1)
+            .find(|dir| dir.join("slides.toml").is_file())
+            .map_or_else(|| self.default_root.clone(), Path::to_path_buf)
+    }
+
+    async fn snapshot(&self, root: PathBuf) -> DeckSnapshot {
+        let documents = self.documents.read().await.clone();
+        // Registry layers may read from disk or the network
+        tokio::task::spawn_blocking(move || DeckSnapshot::load(root, &documents)).await.expect("snapshot task panicked")
+    }
+
+    async fn publish(&self, uri: &Url) {
+        let Ok(path) = uri.to_file_path() else { return };
+        let deck = self.snapshot(self.deck_root(&path)).await;
+        for (path, diagnostics) in deck.diagnostics() {
+            if let Ok(uri) = Url::from_file_path(&path) {
+                self.client.publish_diagnostics(uri, diagnostics, None).await;
+            }
+        }
+    }
+
+    /// Snapshot of the deck holding a document, with the document's path and text
+    async fn open_document(&self, uri: &Url) -> Option<(DeckSnapshot, PathBuf)> {
+        let path = uri.to_file_path().ok()?;
+        let deck = self.snapshot(self.deck_root(&path)).await;
+        deck.source(&path)?;
+        Some((deck, path))
+    }
+}
+
+#[tower_lsp::async_trait]
+impl LanguageServer for Backend {
+    async fn initialize(&self, _: InitializeParams) -> RpcResult<InitializeResult> {
+        Ok(InitializeResult {
+            capabilities: ServerCapabilities {
+                text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+                completion_provider: Some(CompletionOptions {
+                    trigger_characters: Some(vec!["\"".to_string()]),
+                    ..Default::default()
+                }),
+                hover_provider: Some(HoverProviderCapability::Simple(true)),
+                definition_provider: Some(OneOf::Left(true)),
+                ..Default::default()
+            },
+            server_info: Some(ServerInfo {
+                name: "coolslides".to_string(),
+                version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            }),
+        })
+    }
+
+    async fn shutdown(&self) -> RpcResult<()> {
+        Ok(())
+    }
+
+    async fn did_open(&self, params: DidOpenTextDocumentParams) {
+        let document = params.text_document;
+        if let Ok(path) = document.uri.to_file_path() {
+            self.documents.write().await.insert(path, document.text);
+        }
+        self.publish(&document.uri).await;
+    }
+
+    async fn did_change(&self, params: DidChangeTextDocumentParams) {
+        let uri = params.text_document.uri;
+        // Full sync: the last change holds the whole document
+        if let (Ok(path), Some(change)) = (uri.to_file_path(), params.content_changes.into_iter().last()) {
+            self.documents.write().await.insert(path, change.text);
+        }
+        self.publish(&uri).await;
+    }
+
+    async fn did_save(&self, params: DidSaveTextDocumentParams) {
+        self.publish(&params.text_document.uri).await;
+    }
+
+    async fn did_close(&self, params: DidCloseTextDocumentParams) {
+        let uri = params.text_document.uri;
+        if let Ok(path) = uri.to_file_path() {
+            self.documents.write().await.remove(&path);
+        }
+        self.publish(&uri).await;
+    }
+
+    async fn completion(&self, params: CompletionParams) -> RpcResult<Option<CompletionResponse>> {
+        let position = params.text_document_position;
+        let Some((deck, path)) = self.open_document(&position.text_document.uri).await else {
+            return Ok(None);
+        };
+        let source = deck.source(&path).unwrap_or_default();
+        let items = deck.completions(&path, source, offset_at(source, position.position));
+        Ok(Some(CompletionResponse::Array(items)))
+    }
+
+    async fn hover(&self, params: HoverParams) -> RpcResult<Option<Hover>> {
+        let position = params.text_document_position_params;
+        let Some((deck, path)) = self.open_document(&position.text_document.uri).await else {
+            return Ok(None);
+        };
+        let source = deck.source(&path).unwrap_or_default();
+        Ok(deck.hover(&path, source, offset_at(source, position.position)))
+    }
+
+    async fn goto_definition(&self, params: GotoDefinitionParams) -> RpcResult<Option<GotoDefinitionResponse>> {
+        let position = params.text_document_position_params;
+        let Some((deck, path)) = self.open_document(&position.text_document.uri).await else {
+            return Ok(None);
+        };
+        let source = deck.source(&path).unwrap_or_default();
+        Ok(deck.definition(&path, source, offset_at(source, position.position)).map(GotoDefinitionResponse::Scalar))
+    }
+}
@@ -1,5 +1,7 @@
 use clap::{Parser, Subcommand};
-use coolslides_core::{DeckManifest, SlideDoc, ComponentRegistry, components, validation};
+use coolslides_core::{DeckManifest, HooksConfig, SlideDoc, integrity, lint, registry, spelling, validation, vendor};
+use coolslides_core::remote_registry::RemoteRegistry;
+use coolslides_core::{signing, user_config::UserConfig};
 use std::path::Path;
 use anyhow::Result;
 use std::fs;
@@ -7,6 +9,8 @@ use std::io::Write;
 use std::fmt::Write as _;
 use serde::{Deserialize, Serialize};
 
+mod lsp;
+
 #[derive(Parser)]
 #[command(name = "coolslides")]
 #[command(about = "Pro-grade, hackable slide platform")]
@@ -14,6 +18,15 @@ use serde::{Deserialize, Serialize};
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+    /// Skip the deck's [hooks] commands
+    #[arg(long, global = true)]
+    no_hooks: bool,
+    /// Server log level: trace, debug, info, warn, error, or off (RUST_LOG overrides)
+    #[arg(long, global = true, default_value = "info")]
+    log_level: String,
+    /// Server log format on stderr
+    #[arg(long, global = true, value_parser = ["text", "json"], default_value = "text")]
+    log_format: String,
 }
 
 #[derive(Subcommand)]
@@ -62,30 +75,102 @@ enum Commands {
         /// Open browser automatically
         #[arg(long)]
         open: bool,
+        /// Port to run server on (default: coolslides.config.toml, else 5173)
+        #[arg(long)]
+        port: Option<u16>,
+        /// Host to bind to (default: coolslides.config.toml, else 127.0.0.1)
+        #[arg(long)]
+        host: Option<String>,
+        /// Directory to serve deck from; repeat to serve several decks under /decks/<name>/
+        #[arg(long, default_value = ".")]
+        dir: Vec<String>,
+        /// Enable strict mode
+        #[arg(long)]
+        strict: bool,
+        /// Random seed for deterministic behavior
+        #[arg(long)]
+        seed: Option<u64>,
+        /// SQLite database to keep rooms, recordings, analytics, and export history in across restarts
+        #[arg(long)]
+        db: Option<String>,
+        /// Directory to keep rooms, message history, and recordings in across restarts, as JSON files
+        #[arg(long)]
+        state_dir: Option<String>,
+        /// Cross-origin policy: permissive answers any origin, locked only --cors-origin and the server itself
+        #[arg(long, value_parser = ["permissive", "locked"])]
+        cors: Option<String>,
+        /// Origin allowed to call the API and join rooms (repeatable)
+        #[arg(long = "cors-origin")]
+        cors_origins: Vec<String>,
+        /// Methods cross-origin pages may use, comma-separated
+        #[arg(long, value_delimiter = ',')]
+        cors_methods: Vec<String>,
+        /// Let cross-origin pages send credentials
+        #[arg(long)]
+        cors_credentials: bool,
+        /// Share on the local network: listen on 0.0.0.0 and serve a /join page with a QR code for the audience
+        #[arg(long)]
+        lan: bool,
+        /// Token for the presenter page, recording, and presenting in rooms (default: COOLSLIDES_PRESENTER_TOKEN, else a new one each run)
+        #[arg(long)]
+        presenter_token: Option<String>,
+        /// Most clients per room; more audience devices are turned away (default: unlimited)
+        #[arg(long)]
+        max_clients: Option<usize>,
+        /// Redis URL to share rooms with other servers behind a load balancer (default: COOLSLIDES_REDIS_URL); needs the `redis` feature
+        #[arg(long)]
+        redis: Option<String>,
+    },
+    /// Host many uploaded decks, each with its own rooms and access token
+    Hub {
         /// Port to run server on
         #[arg(long, default_value = "5173")]
         port: u16,
         /// Host to bind to
         #[arg(long, default_value = "127.0.0.1")]
         host: String,
-        /// Directory to serve deck from
-        #[arg(long, default_value = ".")]
-        dir: String,
+        /// Directory holding the uploaded decks
+        #[arg(long, default_value = "coolslides-hub")]
+        data: String,
+        /// Token for the /hub/api administration endpoints (default: $COOLSLIDES_HUB_ADMIN_TOKEN, else generated)
+        #[arg(long)]
+        admin_token: Option<String>,
         /// Enable strict mode
         #[arg(long)]
         strict: bool,
-        /// Random seed for deterministic behavior
+    },
+    /// Serve the deck in kiosk mode: looped auto-play without interaction
+    Kiosk {
+        /// Port to run server on
+        #[arg(long, default_value = "5173")]
+        port: u16,
+        /// Host to bind to
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+        /// Directory to serve deck from
+        #[arg(long, default_value = ".")]
+        dir: String,
+        /// Launch a full-screen browser on the kiosk page
         #[arg(long)]
-        seed: Option<u64>,
+        open: bool,
     },
     /// Validate slide deck
     Validate {
-        /// Output format
-        #[arg(long, default_value = "text")]
+        /// Output format (text, json, or sarif)
+        #[arg(long, default_value = "text", value_parser = ["text", "json", "sarif"])]
         format: String,
-        /// Enable strict validation
+        /// Treat warnings as errors (see [lint.strict] for exceptions)
         #[arg(long)]
         strict: bool,
+        /// Report the slowest slides and component schemas
+        #[arg(long)]
+        timings: bool,
+        /// Run the spelling pass even if [lint.spelling] is not enabled
+        #[arg(long)]
+        spellcheck: bool,
+        /// Apply suggested fixes that are marked safe, then validate again
+        #[arg(long)]
+        fix: bool,
     },
     /// Export slide deck
     Export {
@@ -102,10 +187,93 @@ enum Commands {
         #[arg(long, default_value = ".")]
         dir: String,
     },
+    /// Download remote import-map modules into vendor/ for offline use
+    Vendor {
+        /// Deck directory
+        #[arg(long, default_value = ".")]
+        dir: String,
+    },
+    /// Work with the component registry
+    Components {
+        #[command(subcommand)]
+        action: ComponentsAction,
+        /// Deck directory
+        #[arg(long, default_value = ".")]
+        dir: String,
+    },
+    /// Run a lifecycle hook from the deck's [hooks] (e.g. pre-publish)
+    Hook {
+        /// Hook name
+        #[arg(value_parser = HooksConfig::NAMES)]
+        name: String,
+        /// Deck directory
+        #[arg(long, default_value = ".")]
+        dir: String,
+    },
+    /// Sign an exported HTML file, PDF, or export directory
+    Sign {
+        /// Export to sign
+        path: Option<String>,
+        /// Secret key file (defaults to signing.key in the user config)
+        #[arg(long)]
+        key: Option<String>,
+        /// Deck directory the export was made from, for the deck hash
+        #[arg(long, default_value = ".")]
+        dir: String,
+        /// Create a new key at the key path and print its public key
+        #[arg(long)]
+        generate_key: bool,
+    },
+    /// Check the signature of a signed export
+    Verify {
+        /// Signed export
+        path: String,
+        /// Public key (ed25519:<base64>) or a file holding it; defaults to trustedKeys in the user config
+        #[arg(long)]
+        public_key: Option<String>,
+        /// Also check that the export was made from this deck directory
+        #[arg(long)]
+        deck: Option<String>,
+    },
+    /// Explain a diagnostic code (e.g. CS1001)
+    Explain {
+        /// Diagnostic code
+        code: String,
+        /// Output format (text or json)
+        #[arg(long, default_value = "text", value_parser = ["text", "json"])]
+        format: String,
+    },
     /// Run environment diagnostics
     Doctor {
-        /// Specific diagnostic to run
+        /// Specific diagnostic to run (browser or lockfile)
         target: Option<String>,
+        /// Deck directory
+        #[arg(long, default_value = ".")]
+        dir: String,
+    },
+    /// Time deck loading, validation, and rendering
+    Bench {
+        /// Deck directory
+        #[arg(long, default_value = ".")]
+        dir: String,
+        /// Slides to scale the deck to (default: `slides` in `[lint.budgets.time]`)
+        #[arg(long)]
+        slides: Option<usize>,
+        /// Runs of each operation; the median is reported
+        #[arg(long, default_value_t = 10)]
+        iterations: u32,
+        /// Fail when an operation exceeds its time budget
+        #[arg(long)]
+        budget: bool,
+        /// Output format (text or json)
+        #[arg(long, default_value = "text", value_parser = ["text", "json"])]
+        format: String,
+    },
+    /// Run a language server for deck files over stdio
+    Lsp {
+        /// Deck directory for files outside any deck
+        #[arg(long, default_value = ".")]
+        dir: String,
     },
 }
 
@@ -133,6 +301,54 @@ enum ExportFormat {
         #[arg(long, default_value = "30000")]
         timeout: u64,
     },
+    /// Export to an MP4 video, with each slide's narration as its soundtrack (needs ffmpeg)
+    Video {
+        /// Output file
+        file: String,
+        /// Frame width in pixels
+        #[arg(long, default_value = "1920")]
+        width: u32,
+        /// Frame height in pixels
+        #[arg(long, default_value = "1080")]
+        height: u32,
+        /// Seconds on each slide without narration
+        #[arg(long, default_value = "5")]
+        slide_duration: f64,
+        /// Render timeout per slide in milliseconds
+        #[arg(long, default_value = "10000")]
+        timeout: u64,
+    },
+}
+
+#[derive(Subcommand)]
+enum ComponentsAction {
+    /// Generate a catalog of every component: props, slots, tokens, and an example slide
+    Docs {
+        /// Output format (markdown or html)
+        #[arg(long, default_value = "markdown", value_parser = ["markdown", "html"])]
+        format: String,
+        /// Output file (defaults to stdout)
+        #[arg(long)]
+        out: Option<String>,
+    },
+    /// Screenshot each component (or slide) headlessly and compare against committed baselines
+    Test {
+        /// Baseline directory, relative to the deck
+        #[arg(long, default_value = "tests/visual")]
+        baselines: String,
+        /// Largest fraction of differing pixels that still passes
+        #[arg(long, default_value = "0.001")]
+        threshold: f64,
+        /// Capture each slide of the deck instead of each component
+        #[arg(long)]
+        slides: bool,
+        /// Replace the baselines with new screenshots
+        #[arg(long)]
+        update: bool,
+        /// Virtual time budget per page in milliseconds
+        #[arg(long, default_value = "5000")]
+        timeout: u64,
+    },
 }
 
 #[derive(Subcommand)]
@@ -152,6 +368,8 @@ enum AddItem {
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
+    let no_hooks = cli.no_hooks;
+    coolslides_server::logging::init(&cli.log_level, cli.log_format.parse()?)?;
     
     match cli.command {
         Commands::Init { template, dir, no_git, registry, registry_version, open } => {
@@ -191,8 +409,17 @@ async fn main() -> Result<()> {
             println!("Creating new slide: {} with ID: {}", component_name, id);
             new_slide(&dir, &component_name, &id, from_schema.as_deref(), yes).await?;
         }
-        Commands::Dev { open, port, host, dir, strict, seed: _ } => {
-            println!("Starting dev server on {}:{} (dir: {})", host, port, dir);
+        Commands::Dev { open, port, host, dir, strict, seed: _, db, state_dir, cors, cors_origins, cors_methods, cors_credentials, lan, presenter_token, max_clients, redis } => {
+            for dir in &dir {
+                run_hook_or_exit(no_hooks, Path::new(dir), "pre-dev", &[]);
+            }
+
+            // Flags override coolslides.config.toml
+            let mut config = coolslides_server::config::ServerConfig::load(Path::new(&dir[0]))?;
+            let host = host.or(config.host.take()).unwrap_or_else(|| "127.0.0.1".to_string());
+            let port = port.or(config.port).unwrap_or(5173);
+            let strict = strict || config.strict;
+            println!("Starting dev server on {}:{} (dir: {})", host, port, dir.join(", "));
             if strict {
                 println!("Running in strict mode (enhanced HTML sanitization)");
             }
@@ -211,8 +438,38 @@ async fn main() -> Result<()> {
                 });
             }
 
+            if let Some(mode) = cors {
+                config.cors.mode = mode.parse()?;
+            }
+            if !cors_origins.is_empty() {
+                config.cors.allowed_origins = cors_origins;
+            }
+            if !cors_methods.is_empty() {
+                config.cors.allowed_methods = cors_methods;
+            }
+            config.cors.allow_credentials |= cors_credentials;
+            config.lan |= lan;
+            if let Some(state_dir) = state_dir {
+                // The file's path is relative to the deck; the flag's to where it was typed
+                config.state_dir = Some(std::env::current_dir()?.join(state_dir));
+            }
+            config.presenter_token = presenter_token.or_else(|| std::env::var("COOLSLIDES_PRESENTER_TOKEN").ok());
+            if max_clients.is_some() {
+                config.rooms.max_clients = max_clients;
+            }
+            config.redis_url = redis.or_else(|| std::env::var("COOLSLIDES_REDIS_URL").ok());
+            config.validate()?;
+
             // Start the development server
-            match coolslides_server::start_server_with_dir(&host, port, Some(&dir), strict).await {
+            let started = if dir.len() > 1 {
+                if db.is_some() || config.state_dir.is_some() || config.lan || config.redis_url.is_some() {
+                    return Err(anyhow::anyhow!("--db, --state-dir, --lan, and --redis serve a single deck; pass one --dir"));
+                }
+                coolslides_server::decks::start_decks_server(&host, port, &dir, strict, config).await
+            } else {
+                coolslides_server::start_server_with_config(&host, port, Some(&dir[0]), strict, db.as_deref().map(Path::new), config).await
+            };
+            match started {
                 Ok(()) => {
                     println!("Server stopped successfully");
                 }
@@ -222,10 +479,69 @@ async fn main() -> Result<()> {
                 }
             }
         }
-        Commands::Validate { format: _, strict: _ } => {
-            match validate_deck_in_directory(".").await {
-                Ok(()) => {
-                    println!("✓ Deck validation passed");
+        Commands::Hub { port, host, data, admin_token, strict } => {
+            let admin_token = match admin_token.or_else(|| std::env::var("COOLSLIDES_HUB_ADMIN_TOKEN").ok()) {
+                Some(token) => token,
+                None => {
+                    let token = coolslides_server::hub::new_token();
+                    println!("Admin token for this run (set --admin-token or COOLSLIDES_HUB_ADMIN_TOKEN to keep one): {}", token);
+                    token
+                }
+            };
+            if let Err(e) = coolslides_server::hub::start_hub(&host, port, Path::new(&data), &admin_token, strict).await {
+                eprintln!("Error starting hub: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Commands::Kiosk { port, host, dir, open } => {
+            run_hook_or_exit(no_hooks, Path::new(&dir), "pre-dev", &[]);
+            let url = format!("http://{}:{}/kiosk", host, port);
+            println!("Starting kiosk on {} (dir: {})", url, dir);
+            if open {
+                tokio::spawn(async move {
+                    use std::process::Command;
+                    // Give the server a moment to bind before the browser asks for the page
+                    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+                    match coolslides_server::export::check_browser_availability() {
+                        Ok(browser) => {
+                            let _ = Command::new(browser)
+                                .args(["--kiosk", "--noerrdialogs", "--disable-infobars", "--autoplay-policy=no-user-gesture-required", &url])
+                                .spawn();
+                        }
+                        Err(_) => {
+                            eprintln!("No Chrome/Chromium found; opening the default browser (press F11 for full screen)");
+                            #[cfg(target_os = "macos")]
+                            let _ = Command::new("open").arg(&url).spawn();
+                            #[cfg(all(unix, not(target_os = "macos")))]
+                            let _ = Command::new("xdg-open").arg(&url).spawn();
+                            #[cfg(target_os = "windows")]
+                            let _ = Command::new("cmd").args(["/C", "start", &url]).spawn();
+                        }
+                    }
+                });
+            }
+
+            if let Err(e) = coolslides_server::start_server_with_dir(&host, port, Some(&dir), false).await {
+                eprintln!("Error starting server: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Commands::Validate { format, strict, timings, spellcheck, fix } => {
+            let options = ValidateOptions { format, strict, timings, spellcheck, fix };
+            let json = options.format != "text";
+            match validate_deck_in_directory(".", &options).await {
+                Ok(ValidationOutcome::Passed) => {
+                    if !json {
+                        println!("✓ Deck validation passed");
+                    }
+                }
+                Ok(ValidationOutcome::Failed { errors }) => {
+                    eprintln!("✗ Deck validation failed with {} errors", errors);
+                    std::process::exit(1);
+                }
+                Ok(ValidationOutcome::FailedStrict { promoted }) => {
+                    eprintln!("✗ Deck validation failed: {} warnings promoted to errors by --strict", promoted);
+                    std::process::exit(2);
                 }
                 Err(e) => {
                     eprintln!("✗ Deck validation failed: {}", e);
@@ -234,6 +550,12 @@ async fn main() -> Result<()> {
             }
         }
         Commands::Export { format } => {
+            let hook_env = match &format {
+                ExportFormat::Html { dir, .. } => [("COOLSLIDES_EXPORT_FORMAT", "html".to_string()), ("COOLSLIDES_EXPORT_PATH", dir.clone())],
+                ExportFormat::Pdf { file, .. } => [("COOLSLIDES_EXPORT_FORMAT", "pdf".to_string()), ("COOLSLIDES_EXPORT_PATH", file.clone())],
+                ExportFormat::Video { file, .. } => [("COOLSLIDES_EXPORT_FORMAT", "video".to_string()), ("COOLSLIDES_EXPORT_PATH", file.clone())],
+            };
+            run_hook_or_exit(no_hooks, Path::new("."), "pre-export", &hook_env);
             match format {
                 ExportFormat::Html { dir, strict } => {
                     println!("Exporting to HTML: {}", dir);
@@ -262,6 +584,27 @@ async fn main() -> Result<()> {
                             html = html.replace("/packages/", "./packages/");
                             html = html.replace("data-module=\"/packages/", "data-module=\"./packages/");
 
+                            // Resized and re-encoded images, when the deck opts in with [images]
+                            let image_config = fs::read_to_string(cwd.join("slides.toml"))
+                                .ok()
+                                .and_then(|manifest| toml::from_str::<DeckManifest>(&manifest).ok())
+                                .and_then(|deck| deck.images)
+                                .filter(|config| config.optimize);
+                            if let Some(config) = image_config {
+                                match coolslides_server::images::optimize_export(&html, cwd, out_dir, &config) {
+                                    Ok(optimized) => {
+                                        println!(
+                                            "✓ Optimized {} images ({} KB → {} KB at full width)",
+                                            optimized.images,
+                                            optimized.original_bytes / 1024,
+                                            optimized.optimized_bytes / 1024
+                                        );
+                                        html = optimized.html;
+                                    }
+                                    Err(e) => eprintln!("Warning: image optimization failed: {}", e),
+                                }
+                            }
+
                             // Write index.html
                             std::fs::create_dir_all(out_dir).ok();
                             let index_path = out_dir.join("index.html");
@@ -302,13 +645,20 @@ async fn main() -> Result<()> {
                             std::process::exit(1);
                         }
                     };
-                    let slides_html = match coolslides_server::render_slides_html(&deck, &slides, registry.as_ref(), &coolslides_server::SanitizationConfig::new(false)) {
+                    let server_config = coolslides_server::config::ServerConfig::load(cwd)?;
+                    let sanitization = coolslides_server::SanitizationConfig::new(server_config.strict).with_policy(server_config.sanitization);
+                    let slides_html = match coolslides_server::render_slides_html(&deck, &slides, registry.as_ref(), &sanitization.for_deck(&deck)) {
                         Ok(v) => v,
                         Err(e) => {
                             eprintln!("Failed to generate slides HTML: {}", e);
                             std::process::exit(1);
                         }
                     };
+                    let slides_html = if deck.server_plugins.is_empty() {
+                        slides_html
+                    } else {
+                        coolslides_server::server_plugins::PluginHost::for_deck(cwd, &deck.server_plugins).on_export("pdf", slides_html)
+                    };
                     let export_config = coolslides_server::export::ExportConfig {
                         profile: match profile.as_str() {
                             "archival" => coolslides_server::export::ExportProfile::Archival,
@@ -332,8 +682,68 @@ async fn main() -> Result<()> {
                         }
                     }
                 }
+                ExportFormat::Video { file, width, height, slide_duration, timeout } => {
+                    println!("Exporting to video: {} ({}x{})", file, width, height);
+                    let cwd = Path::new(".");
+                    let (deck, slides, registry) = match coolslides_server::load_deck_bundle(cwd) {
+                        Ok(v) => v,
+                        Err(e) => {
+                            eprintln!("Failed to load deck: {}", e);
+                            std::process::exit(1);
+                        }
+                    };
+                    let slide_list: Vec<SlideDoc> = slides.values().cloned().collect();
+                    let missing = validation::validate_narration(&slide_list, cwd);
+                    if !missing.is_valid() {
+                        for error in &missing.errors {
+                            eprintln!("  {}", error);
+                        }
+                        std::process::exit(1);
+                    }
+                    let config = coolslides_server::export::VideoConfig { width, height, slide_duration, timeout };
+                    match coolslides_server::export::export_deck_to_video(&deck, &slides, registry.as_ref(), &config, cwd).await {
+                        Ok(bytes) => {
+                            if let Err(e) = std::fs::write(&file, bytes) {
+                                eprintln!("Failed to write video {}: {}", file, e);
+                                std::process::exit(1);
+                            }
+                            let narrated = slides.values().filter(|s| s.narration.is_some()).count();
+                            println!("✓ Video export written to {} ({} narrated slides)", file, narrated);
+                        }
+                        Err(e) => {
+                            eprintln!("Error exporting video: {}", e);
+                            std::process::exit(1);
+                        }
+                    }
+                }
+            }
+            run_hook_or_exit(no_hooks, Path::new("."), "post-export", &hook_env);
+        }
+        Commands::Hook { name, dir } => {
+            if no_hooks {
+                println!("Skipping {} (--no-hooks)", name);
+            } else {
+                match run_hook(Path::new(&dir), &name, &[]) {
+                    Ok(0) => println!("No commands configured for {} in [hooks]", name),
+                    Ok(_) => {}
+                    Err(e) => {
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+            }
+        }
+        Commands::Sign { path, key, dir, generate_key } => {
+            if let Err(e) = sign_export(path.as_deref(), key.as_deref(), Path::new(&dir), generate_key) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Commands::Verify { path, public_key, deck } => {
+            if let Err(e) = verify_export(Path::new(&path), public_key.as_deref(), deck.as_deref().map(Path::new)) {
+                eprintln!("✗ {}: {}", path, e);
+                std::process::exit(1);
             }
-            // TODO: Implement export
         }
         Commands::Add { item, dir } => {
             match item {
@@ -347,21 +757,112 @@ async fn main() -> Result<()> {
                 }
             }
         }
-        Commands::Doctor { target } => {
+        Commands::Vendor { dir } => {
+            vendor_deck(Path::new(&dir))?;
+        }
+        Commands::Components { action: ComponentsAction::Docs { format, out }, dir } => {
+            let catalog = component_catalog(Path::new(&dir), &format)?;
+            match out {
+                Some(out) => {
+                    fs::write(&out, catalog)?;
+                    println!("✓ Wrote component catalog to {}", out);
+                }
+                None => print!("{}", catalog),
+            }
+        }
+        Commands::Components { action: ComponentsAction::Test { baselines, threshold, slides, update, timeout }, dir } => {
+            use coolslides_server::visual::{self, VisualOptions, VisualStatus};
+            let options = VisualOptions {
+                baselines: Path::new(&dir).join(baselines),
+                threshold,
+                slides,
+                update,
+                timeout,
+                ..Default::default()
+            };
+            let results = visual::run_visual_tests(Path::new(&dir), &options).await?;
+            let mut failed = 0;
+            for result in &results {
+                match result.status {
+                    VisualStatus::Passed { ratio } => println!("✓ {} ({:.3}% changed)", result.name, ratio * 100.0),
+                    VisualStatus::Failed { ratio } => {
+                        failed += 1;
+                        println!(
+                            "✗ {}: {:.3}% of pixels changed; see {}",
+                            result.name,
+                            ratio * 100.0,
+                            result.baseline.with_extension("diff.png").display()
+                        );
+                    }
+                    VisualStatus::Created => println!("+ {}: new baseline {}", result.name, result.baseline.display()),
+                    VisualStatus::Updated => println!("↻ {}: updated {}", result.name, result.baseline.display()),
+                }
+            }
+            if failed > 0 {
+                eprintln!("{} of {} screenshots differ from their baselines", failed, results.len());
+                std::process::exit(1);
+            }
+        }
+        Commands::Explain { code, format } => {
+            match coolslides_core::diagnostics::explain(&code) {
+                Some(info) if format == "json" => {
+                    println!("{}", serde_json::to_string_pretty(info)?);
+                }
+                Some(info) => print_diagnostic_info(info),
+                None => {
+                    eprintln!("Unknown diagnostic code '{}'. Known codes:", code);
+                    for info in coolslides_core::diagnostics::CATALOG {
+                        eprintln!("  {}  {}", info.code, info.title);
+                    }
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Doctor { target, dir } => {
             println!("Running diagnostics");
-            if let Some(target) = target {
-                println!("Target: {}", target);
+            if !run_doctor(Path::new(&dir), target.as_deref())? {
+                std::process::exit(1);
             }
-            // TODO: Implement doctor command
+        }
+        Commands::Bench { dir, slides, iterations, budget, format } => {
+            if !run_bench(Path::new(&dir), slides, iterations, budget, &format)? {
+                std::process::exit(1);
+            }
+        }
+        Commands::Lsp { dir } => {
+            // stdout carries the protocol, so nothing else may print there
+            lsp::run(Path::new(&dir)).await;
         }
     }
     
     Ok(())
 }
 
-/// Validate a deck in the specified directory
-async fn validate_deck_in_directory(deck_dir: &str) -> Result<()> {
-    use std::collections::HashMap;
+/// Result of `coolslides validate`, mapped to the process exit code
+enum ValidationOutcome {
+    /// No errors (exit 0)
+    Passed,
+    /// The deck has real errors (exit 1)
+    Failed { errors: usize },
+    /// Only warnings promoted by `--strict` failed (exit 2)
+    FailedStrict { promoted: usize },
+}
+
+/// Flags for `coolslides validate`
+#[derive(Clone)]
+struct ValidateOptions {
+    /// `text`, `json`, or `sarif`
+    format: String,
+    strict: bool,
+    timings: bool,
+    spellcheck: bool,
+    fix: bool,
+}
+
+/// Validate a deck in the specified directory, reporting as text, JSON, or SARIF
+async fn validate_deck_in_directory(deck_dir: &str, options: &ValidateOptions) -> Result<ValidationOutcome> {
+    use coolslides_core::fixes::{self, SuggestedFix};
+    use std::collections::{BTreeMap, HashMap};
     use std::path::Path;
     use tokio::fs;
     
@@ -376,6 +877,10 @@ async fn validate_deck_in_directory(deck_dir: &str) -> Result<()> {
     let manifest_content = fs::read_to_string(&manifest_path).await?;
     let deck_manifest: DeckManifest = toml::from_str(&manifest_content)?;
     
+    // Sources by displayed file name, used to place suggested fixes
+    let mut file_sources: HashMap<String, String> = HashMap::new();
+    file_sources.insert("slides.toml".to_string(), manifest_content.clone());
+    
     // Load all slide files
     let content_dir = deck_path.join("content");
     let mut slides = Vec::new();
@@ -397,69 +902,208 @@ async fn validate_deck_in_directory(deck_dir: &str) -> Result<()> {
                     }
                 };
                 
+                file_sources.insert(path.display().to_string(), slide_content);
                 slide_file_paths.insert(slide_doc.id.clone(), path);
                 slides.push(slide_doc);
             }
         }
     }
     
-    // Load component registry - try to find components directory
-    let manifests_candidates = [
-        Path::new("packages/components/manifests"),        // From project root
-        Path::new("../../packages/components/manifests"),  // From examples/basic-deck
-        Path::new("../packages/components/manifests"),     // From apps/cli
-    ];
-    let src_candidates = [
-        Path::new("packages/components/src"),        // From project root
-        Path::new("../../packages/components/src"),  // From examples/basic-deck
-        Path::new("../packages/components/src"),     // From apps/cli
-    ];
+    // Load component registry layers: deck components, workspace, node_modules, remote
+    let loaded_registry = registry::load_deck_registry(&deck_manifest, deck_path);
+    for warning in &loaded_registry.warnings {
+        eprintln!("Warning: {}", warning);
+    }
+    let registry = loaded_registry.registry.clone();
+    if options.format == "text" {
+        for rename in registry.iter().flat_map(|r| &r.tag_renames) {
+            println!("Renamed <{}> of {} to <{}>", rename.original, rename.component, rename.tag);
+        }
+    }
     
-    let registry = manifests_candidates
-        .iter()
-        .find(|path| path.exists())
-        .and_then(|dir| components::extract_manifests_from_manifests_dir(dir).ok())
-        .or_else(|| {
-            src_candidates
-                .iter()
-                .find(|path| path.exists())
-                .and_then(|components_dir| {
-                    match components::extract_manifests_from_directory(components_dir) {
-                        Ok(registry) => Some(registry),
-                        Err(e) => {
-                            eprintln!("Warning: Failed to load component manifests from {}: {}", components_dir.display(), e);
-                            eprintln!("Schema validation will be skipped");
-                            None
-                        }
-                    }
-                })
-        });
+    // Import map and lockfile are optional; plugin checks use whichever exist
+    let import_map: Option<coolslides_core::ImportMap> = read_json_file(&deck_path.join("importmap.json"))?;
+    let lockfile: Option<coolslides_core::Lockfile> = read_json_file(&deck_path.join(".coolslides.lock"))?;
     
     // Perform validation
-    let validation_result = validation::validate_deck_with_registry(
+    let (mut validation_result, timings) = validation::validate_deck_timed(
         &deck_manifest,
         &slides,
         registry.as_ref()
     );
+    validation_result.merge(validation::validate_plugins(
+        &deck_manifest,
+        import_map.as_ref(),
+        lockfile.as_ref(),
+        deck_path,
+    ));
+    validation_result.merge(loaded_registry.diagnostics());
+    validation_result.merge(validation::validate_transitions(&deck_manifest, lockfile.as_ref()));
+    validation_result.merge(validation::validate_budgets(&deck_manifest, &slides, Some(deck_path)));
+    validation_result.merge(validation::validate_narration(&slides, deck_path));
+    if options.spellcheck || deck_manifest.lint.as_ref().is_some_and(|l| l.spelling.enabled) {
+        validation_result.merge(spelling::validate_spelling(&deck_manifest, &slides, deck_path));
+    }
+    if let Some(lockfile) = lockfile.as_ref() {
+        validation_result.merge(validation::validate_lockfile_drift(
+            lockfile,
+            import_map.as_ref(),
+            &deck_manifest,
+            &slides,
+            registry.as_ref(),
+        ));
+    }
+    
+    // Try to find which file a diagnostic came from
+    let locate = |error: &validation::ValidationError| -> Option<String> {
+        if error.names_own_file() {
+            // Registry and asset diagnostics already name their files
+            None
+        } else if let Some(slide_id) = extract_slide_id_from_error(error) {
+            slide_file_paths.get(&slide_id).map(|p| p.display().to_string())
+        } else {
+            Some("slides.toml".to_string())
+        }
+    };
+    let fixes_for = |error: &validation::ValidationError| -> Vec<SuggestedFix> {
+        locate(error)
+            .and_then(|file| file_sources.get(&file).map(|source| error.suggested_fixes(&file, source)))
+            .unwrap_or_default()
+    };
+    
+    if options.fix {
+        let mut by_file: BTreeMap<String, Vec<SuggestedFix>> = BTreeMap::new();
+        for fix in validation_result.errors.iter().chain(&validation_result.warnings).flat_map(&fixes_for) {
+            if fix.safe {
+                by_file.entry(fix.file.clone()).or_default().push(fix);
+            }
+        }
+        let mut applied = 0;
+        for (file, file_fixes) in &by_file {
+            let (fixed, count) = fixes::apply(&file_sources[file], file_fixes);
+            let path = if file == "slides.toml" { manifest_path.clone() } else { Path::new(file).to_path_buf() };
+            fs::write(&path, fixed).await?;
+            applied += count;
+        }
+        if applied > 0 {
+            eprintln!("Applied {} safe fixes in {} files", applied, by_file.len());
+            let rerun = ValidateOptions { fix: false, ..options.clone() };
+            return Box::pin(validate_deck_in_directory(deck_dir, &rerun)).await;
+        }
+        eprintln!("No safe fixes to apply");
+    }
+    
+    // Strict mode appends promoted warnings after the original errors
+    let original_errors = validation_result.errors.len();
+    let promoted = if options.strict {
+        let policy = deck_manifest.lint.as_ref().map(|l| l.strict.clone()).unwrap_or_default();
+        lint::apply_strict(&policy, &mut validation_result)
+    } else {
+        0
+    };
+    let is_promoted = |index: usize| index >= original_errors;
+    let outcome = if original_errors > 0 {
+        ValidationOutcome::Failed { errors: original_errors }
+    } else if promoted > 0 {
+        ValidationOutcome::FailedStrict { promoted }
+    } else {
+        ValidationOutcome::Passed
+    };
+    
+    if options.format == "sarif" {
+        let report = sarif_report(&validation_result, original_errors, &locate, &fixes_for, &file_sources);
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(outcome);
+    }
+    
+    if options.format == "json" {
+        let to_json = |error: &validation::ValidationError, severity: &str, promoted: bool| {
+            serde_json::json!({
+                "code": error.code(),
+                "severity": severity,
+                "promoted": promoted,
+                "message": error.message(),
+                "slideId": extract_slide_id_from_error(error),
+                "file": locate(error),
+                "explanation": error.info().map(|info| serde_json::json!({
+                    "title": info.title,
+                    "description": info.description,
+                    "fix": info.fix,
+                })),
+                "fixes": fixes_for(error).iter().map(|fix| {
+                    let source = &file_sources[&fix.file];
+                    let (start_line, start_column) = fixes::line_col(source, fix.span.start);
+                    let (end_line, end_column) = fixes::line_col(source, fix.span.end);
+                    serde_json::json!({
+                        "file": fix.file,
+                        "span": {
+                            "start": fix.span.start,
+                            "end": fix.span.end,
+                            "startLine": start_line,
+                            "startColumn": start_column,
+                            "endLine": end_line,
+                            "endColumn": end_column,
+                        },
+                        "replacement": fix.replacement,
+                        "safe": fix.safe,
+                    })
+                }).collect::<Vec<_>>(),
+            })
+        };
+        let mut report = serde_json::json!({
+            "valid": validation_result.is_valid(),
+            "strict": options.strict,
+            "slides": slides.len(),
+            "components": registry.as_ref().map(|r| r.components.len()),
+            "errors": validation_result.errors.iter().enumerate()
+                .map(|(i, e)| to_json(e, "error", is_promoted(i)))
+                .collect::<Vec<_>>(),
+            "warnings": validation_result.warnings.iter().map(|w| to_json(w, "warning", false)).collect::<Vec<_>>(),
+        });
+        if options.timings {
+            report["timings"] = serde_json::json!({
+                "totalMs": timings.total.as_secs_f64() * 1000.0,
+                "slides": timings.slides.iter().take(SLOWEST_SHOWN).map(|t| serde_json::json!({
+                    "slideId": t.slide_id,
+                    "component": t.component,
+                    "ms": t.duration.as_secs_f64() * 1000.0,
+                })).collect::<Vec<_>>(),
+                "schemas": timings.schemas.iter().take(SLOWEST_SHOWN).map(|t| serde_json::json!({
+                    "component": t.component,
+                    "compileMs": t.compile.as_secs_f64() * 1000.0,
+                    "validateMs": t.validate.as_secs_f64() * 1000.0,
+                    "slides": t.slides,
+                })).collect::<Vec<_>>(),
+            });
+        }
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(outcome);
+    }
+    
+    if options.timings {
+        print_timings(&timings);
+    }
+    let fixable = validation_result.errors.iter().chain(&validation_result.warnings)
+        .filter(|e| fixes_for(e).iter().any(|fix| fix.safe))
+        .count();
     
     // Report results
     if !validation_result.errors.is_empty() {
         eprintln!("Validation errors:");
-        for error in &validation_result.errors {
-            // Try to find which file the error came from
-            let file_context = if let Some(slide_id) = extract_slide_id_from_error(error) {
-                if let Some(file_path) = slide_file_paths.get(&slide_id) {
-                    format!(" in {}", file_path.display())
-                } else {
-                    format!(" in slide '{}'", slide_id)
-                }
-            } else {
-                " in slides.toml".to_string()
+        for (i, error) in validation_result.errors.iter().enumerate() {
+            let file_context = match (locate(error), extract_slide_id_from_error(error)) {
+                (Some(file), _) => format!(" in {}", file),
+                (None, Some(slide_id)) => format!(" in slide '{}'", slide_id),
+                (None, None) => String::new(),
             };
-            
-            eprintln!("  {}{}", error, file_context);
+            let promoted_note = if is_promoted(i) { " (warning, --strict)" } else { "" };
+            eprintln!("  {}{}{}", error, file_context, promoted_note);
+        }
+        eprintln!("Run `coolslides explain <code>` for details on a diagnostic.");
+        if fixable > 0 {
+            eprintln!("{} diagnostics can be fixed with `coolslides validate --fix`.", fixable);
         }
-        return Err(anyhow::anyhow!("Validation failed with {} errors", validation_result.errors.len()));
+        return Ok(outcome);
     }
     
     if !validation_result.warnings.is_empty() {
@@ -467,6 +1111,9 @@ async fn validate_deck_in_directory(deck_dir: &str) -> Result<()> {
         for warning in &validation_result.warnings {
             println!("  {}", warning);
         }
+        if fixable > 0 {
+            println!("{} diagnostics can be fixed with `coolslides validate --fix`.", fixable);
+        }
     }
     
     println!("✓ Validated {} slides successfully", slides.len());
@@ -474,18 +1121,383 @@ async fn validate_deck_in_directory(deck_dir: &str) -> Result<()> {
         println!("✓ Schema validation completed with {} components", registry.components.len());
     }
     
-    Ok(())
+    Ok(outcome)
+}
+
+/// SARIF 2.1.0 log for `validate --format sarif`; errors at or past `original_errors` were promoted by `--strict`
+fn sarif_report(
+    result: &validation::ValidationResult,
+    original_errors: usize,
+    locate: &dyn Fn(&validation::ValidationError) -> Option<String>,
+    fixes_for: &dyn Fn(&validation::ValidationError) -> Vec<coolslides_core::fixes::SuggestedFix>,
+    sources: &std::collections::HashMap<String, String>,
+) -> serde_json::Value {
+    use coolslides_core::fixes::line_col;
+    let uri = |file: &str| file.trim_start_matches("./").to_string();
+    let region = |file: &str, span: &std::ops::Range<usize>| {
+        let source = &sources[file];
+        let (start_line, start_column) = line_col(source, span.start);
+        let (end_line, end_column) = line_col(source, span.end);
+        serde_json::json!({
+            "startLine": start_line,
+            "startColumn": start_column,
+            "endLine": end_line,
+            "endColumn": end_column,
+        })
+    };
+    
+    let diagnostics = result.errors.iter().enumerate()
+        .map(|(i, e)| (e, if i >= original_errors { "warning" } else { "error" }, i >= original_errors))
+        .chain(result.warnings.iter().map(|w| (w, "warning", false)));
+    let mut codes = std::collections::BTreeSet::new();
+    let results: Vec<serde_json::Value> = diagnostics
+        .map(|(error, level, promoted)| {
+            codes.insert(error.code());
+            let fixes = fixes_for(error);
+            let mut location = serde_json::json!({});
+            if let Some(file) = locate(error) {
+                location["physicalLocation"] = serde_json::json!({ "artifactLocation": { "uri": uri(&file) } });
+                if let Some(first) = fixes.first() {
+                    location["physicalLocation"]["region"] = region(&first.file, &first.span);
+                }
+            }
+            serde_json::json!({
+                "ruleId": error.code(),
+                "level": if promoted { "error" } else { level },
+                "message": { "text": error.message() },
+                "locations": [location],
+                "fixes": fixes.iter().map(|fix| serde_json::json!({
+                    "description": { "text": format!("Replace with '{}'", fix.replacement) },
+                    "properties": { "safe": fix.safe },
+                    "artifactChanges": [{
+                        "artifactLocation": { "uri": uri(&fix.file) },
+                        "replacements": [{
+                            "deletedRegion": region(&fix.file, &fix.span),
+                            "insertedContent": { "text": fix.replacement },
+                        }],
+                    }],
+                })).collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+    
+    let rules: Vec<serde_json::Value> = codes.into_iter()
+        .filter_map(coolslides_core::diagnostics::explain)
+        .map(|info| serde_json::json!({
+            "id": info.code,
+            "name": info.title,
+            "shortDescription": { "text": info.title },
+            "fullDescription": { "text": info.description },
+            "help": { "text": info.fix },
+        }))
+        .collect();
+    
+    serde_json::json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "coolslides",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                },
+            },
+            "columnKind": "unicodeCodePoints",
+            "results": results,
+        }],
+    })
+}
+
+/// How many entries `validate --timings` lists per table
+const SLOWEST_SHOWN: usize = 10;
+
+/// Print the slowest slides and schemas for `validate --timings`
+fn print_timings(timings: &validation::ValidationTimings) {
+    let ms = |d: std::time::Duration| d.as_secs_f64() * 1000.0;
+    println!("Validation took {:.1}ms", ms(timings.total));
+    println!("Slowest slides:");
+    for t in timings.slides.iter().take(SLOWEST_SHOWN) {
+        println!("  {:>8.2}ms  {} ({})", ms(t.duration), t.slide_id, t.component);
+    }
+    if !timings.schemas.is_empty() {
+        println!("Slowest schemas:");
+        for t in timings.schemas.iter().take(SLOWEST_SHOWN) {
+            println!(
+                "  {:>8.2}ms  {} (compile {:.2}ms, {} slides)",
+                ms(t.compile + t.validate), t.component, ms(t.compile), t.slides
+            );
+        }
+    }
+}
+
+/// Print a diagnostic catalog entry for `coolslides explain`
+fn print_diagnostic_info(info: &coolslides_core::diagnostics::DiagnosticInfo) {
+    let indent = |text: &str| text.lines().map(|l| format!("    {}", l)).collect::<Vec<_>>().join("\n");
+    println!("{}: {}\n", info.code, info.title);
+    println!("{}\n", info.description);
+    println!("Example:\n{}\n", indent(info.example));
+    println!("Fix:\n{}", indent(info.fix));
+}
+
+/// Run environment diagnostics, returning false if any check failed
+fn run_doctor(deck_dir: &Path, target: Option<&str>) -> Result<bool> {
+    let checks = ["browser", "lockfile"];
+    if let Some(target) = target {
+        if !checks.contains(&target) {
+            return Err(anyhow::anyhow!("Unknown diagnostic '{}' (expected one of: {})", target, checks.join(", ")));
+        }
+    }
+    let mut ok = true;
+
+    if target.is_none_or(|t| t == "browser") {
+        match coolslides_server::export::check_browser_availability() {
+            Ok(browser) => println!("✓ browser: found {} (PDF export available)", browser),
+            Err(e) => {
+                // PDF export is optional; a missing browser is a warning, not a failure
+                println!("! browser: {}", e);
+            }
+        }
+    }
+
+    if target.is_none_or(|t| t == "lockfile") {
+        ok &= doctor_lockfile(deck_dir)?;
+    }
+
+    Ok(ok)
+}
+
+/// Report lockfile drift against importmap.json, slides.toml, and slide components
+/// Time the render path on the deck; returns false when `--budget` is set and a budget was exceeded
+fn run_bench(deck_dir: &Path, slides: Option<usize>, iterations: u32, check_budget: bool, format: &str) -> Result<bool> {
+    use coolslides_server::bench::{self, Fixture};
+
+    let manifest: DeckManifest = toml::from_str(&fs::read_to_string(deck_dir.join("slides.toml"))?)?;
+    let budgets = manifest.lint.map(|lint| lint.budgets.time).unwrap_or_default();
+    let fixture = Fixture::load(deck_dir, Some(slides.unwrap_or(budgets.slides as usize)))?;
+    let measurements = bench::measure(&fixture, iterations, &budgets)?;
+    let over: Vec<&bench::Measurement> = measurements.iter().filter(|m| m.over_budget()).collect();
+    let ms = |duration: std::time::Duration| duration.as_secs_f64() * 1000.0;
+
+    if format == "json" {
+        let report = serde_json::json!({
+            "slides": fixture.slides.len(),
+            "iterations": iterations,
+            "operations": measurements.iter().map(|m| serde_json::json!({
+                "name": m.operation.name(),
+                "medianMs": ms(m.median),
+                "maxMs": ms(m.max),
+                "budgetMs": m.budget.map(ms),
+                "overBudget": m.over_budget(),
+            })).collect::<Vec<_>>(),
+        });
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!("Benchmarked {} slides, median of {} runs:", fixture.slides.len(), iterations.max(1));
+        for m in &measurements {
+            let budget = m.budget.map_or_else(|| "no budget".to_string(), |b| format!("budget {:.0} ms", ms(b)));
+            let mark = if m.over_budget() { "✗" } else { " " };
+            println!("{} {:<9} {:>9.2} ms  (max {:.2} ms, {})", mark, m.operation.name(), ms(m.median), ms(m.max), budget);
+        }
+        if !over.is_empty() {
+            let names: Vec<&str> = over.iter().map(|m| m.operation.name()).collect();
+            eprintln!("Over budget: {}", names.join(", "));
+        }
+    }
+    Ok(!check_budget || over.is_empty())
+}
+
+fn doctor_lockfile(deck_dir: &Path) -> Result<bool> {
+    let lockfile: Option<coolslides_core::Lockfile> = read_json_file(&deck_dir.join(".coolslides.lock"))?;
+    let Some(lockfile) = lockfile else {
+        println!("! lockfile: no .coolslides.lock in {} (run `coolslides add` to create one)", deck_dir.display());
+        return Ok(true);
+    };
+    let import_map: Option<coolslides_core::ImportMap> = read_json_file(&deck_dir.join("importmap.json"))?;
+    let (deck, slides, registry) = coolslides_server::load_deck_bundle(deck_dir)?;
+    let slides: Vec<SlideDoc> = slides.into_values().collect();
+
+    let result = validation::validate_lockfile_drift(&lockfile, import_map.as_ref(), &deck, &slides, registry.as_ref());
+    if result.errors.is_empty() && result.warnings.is_empty() {
+        println!("✓ lockfile: in sync with importmap.json, plugins, and components");
+        return Ok(true);
+    }
+    for error in &result.errors {
+        println!("✗ lockfile: {}", error);
+    }
+    for warning in &result.warnings {
+        println!("! lockfile: {}", warning);
+    }
+    Ok(result.is_valid())
+}
+
+/// Component catalog for the deck's registry (or the workspace's, outside a deck)
+fn component_catalog(deck_dir: &Path, format: &str) -> Result<String> {
+    let manifest_path = deck_dir.join("slides.toml");
+    let loaded = if manifest_path.exists() {
+        let deck: DeckManifest = toml::from_str(&fs::read_to_string(&manifest_path)?)?;
+        registry::load_deck_registry(&deck, deck_dir)
+    } else {
+        registry::load_registry(None, deck_dir)
+    };
+    for warning in &loaded.warnings {
+        eprintln!("Warning: {}", warning);
+    }
+    let registry = loaded.registry.ok_or_else(|| anyhow::anyhow!("No components found for {}", deck_dir.display()))?;
+
+    if format == "html" {
+        let import_map: Option<coolslides_core::ImportMap> = read_json_file(&deck_dir.join("importmap.json"))?;
+        coolslides_server::render_component_catalog_html(&registry, import_map.as_ref(), &coolslides_server::SanitizationConfig::new(false))
+    } else {
+        Ok(coolslides_core::catalog::markdown(&registry))
+    }
+}
+
+/// Read and parse a JSON file, returning None when it does not exist
+fn read_json_file<T: serde::de::DeserializeOwned>(path: &Path) -> Result<Option<T>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(path)?;
+    let value = serde_json::from_str(&content)
+        .map_err(|e| anyhow::anyhow!("Failed to parse {}: {}", path.display(), e))?;
+    Ok(Some(value))
 }
 
 /// Extract slide ID from validation error for file context
 fn extract_slide_id_from_error(error: &validation::ValidationError) -> Option<String> {
-    use validation::ValidationError;
-    match error {
-        ValidationError::UnknownComponent { slide_id, .. } => Some(slide_id.clone()),
-        ValidationError::InvalidComponentProps { slide_id, .. } => Some(slide_id.clone()),
-        ValidationError::MissingRequiredProp { slide_id, .. } => Some(slide_id.clone()),
-        _ => None,
+    error.slide_id().map(str::to_string)
+}
+
+/// Sign an export with the user's key, or create the key with `--generate-key`
+fn sign_export(path: Option<&str>, key: Option<&str>, deck_dir: &Path, generate_key: bool) -> Result<()> {
+    let key_path = match key {
+        Some(key) => std::path::PathBuf::from(key),
+        None => UserConfig::load()?
+            .signing_key_path()
+            .ok_or_else(|| anyhow::anyhow!("No user config directory; pass --key"))?,
+    };
+
+    if generate_key {
+        if key_path.exists() {
+            anyhow::bail!("{} already exists; remove it first to replace the key", key_path.display());
+        }
+        let secret = signing::generate_key()?;
+        signing::write_secret_key(&key_path, &secret)?;
+        println!("✓ Signing key written to {}", key_path.display());
+        println!("Public key (share with recipients): {}", signing::encode_public_key(&secret.verifying_key()));
+        if path.is_none() {
+            return Ok(());
+        }
+    }
+
+    let path = path.ok_or_else(|| anyhow::anyhow!("Nothing to sign; pass the export path"))?;
+    if !key_path.exists() {
+        anyhow::bail!("No signing key at {}; create one with `coolslides sign --generate-key`", key_path.display());
+    }
+    let secret = signing::read_secret_key(&key_path)?;
+    let deck_hash = match signing::deck_hash(deck_dir) {
+        Ok(hash) => Some(hash),
+        Err(e) => {
+            eprintln!("Warning: signing without a deck hash: {}", e);
+            None
+        }
+    };
+    let block = signing::sign_path(Path::new(path), &secret, deck_hash)?;
+    println!("✓ Signed {} with key {}", path, block.key_id);
+    println!("  content {}", block.content_hash);
+    if let Some(hash) = &block.deck_hash {
+        println!("  deck    {}", hash);
+    }
+    Ok(())
+}
+
+/// Check an export's signature against a given or trusted public key
+fn verify_export(path: &Path, public_key: Option<&str>, deck_dir: Option<&Path>) -> Result<()> {
+    let block = signing::verify_path(path)?;
+
+    let trusted = match public_key {
+        Some(key) if Path::new(key).is_file() => vec![fs::read_to_string(key)?],
+        Some(key) => vec![key.to_string()],
+        None => UserConfig::load()?.signing.trusted_keys,
+    };
+    let signer = signing::parse_public_key(&block.public_key)?;
+    let trusted = trusted
+        .iter()
+        .map(|key| signing::parse_public_key(key))
+        .collect::<Result<Vec<_>>>()?;
+    if !trusted.is_empty() && !trusted.contains(&signer) {
+        anyhow::bail!("Signed by untrusted key {} ({})", block.key_id, block.public_key);
+    }
+
+    if let Some(deck_dir) = deck_dir {
+        let expected = signing::deck_hash(deck_dir)?;
+        match &block.deck_hash {
+            Some(hash) if *hash == expected => {}
+            Some(hash) => anyhow::bail!("Made from different deck sources ({} vs {} in {})", hash, expected, deck_dir.display()),
+            None => anyhow::bail!("Signature carries no deck hash"),
+        }
     }
+
+    println!("✓ {} is intact, signed by key {}", path.display(), block.key_id);
+    if trusted.is_empty() {
+        println!("Warning: no --public-key or trustedKeys configured; the signer's key was not checked");
+        println!("  signer {}", block.public_key);
+    }
+    if let Some(hash) = &block.deck_hash {
+        println!("  deck   {}", hash);
+    }
+    Ok(())
+}
+
+/// Run a [hooks] entry of the deck, exiting when one of its commands fails
+fn run_hook_or_exit(no_hooks: bool, deck_dir: &Path, hook: &str, extra_env: &[(&str, String)]) {
+    if no_hooks {
+        return;
+    }
+    if let Err(e) = run_hook(deck_dir, hook, extra_env) {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Run the commands of a [hooks] entry through the shell from the deck directory,
+/// stopping at the first failure. Returns how many commands ran.
+fn run_hook(deck_dir: &Path, hook: &str, extra_env: &[(&str, String)]) -> Result<usize> {
+    let Ok(manifest) = fs::read_to_string(deck_dir.join("slides.toml")) else {
+        return Ok(0);
+    };
+    let deck: DeckManifest = toml::from_str(&manifest)?;
+    let Some(hooks) = &deck.hooks else {
+        return Ok(0);
+    };
+    let deck_dir = fs::canonicalize(deck_dir)?;
+    let commands = hooks.commands(hook);
+    for command in commands {
+        println!("▶ {}: {}", hook, command);
+        #[cfg(not(target_os = "windows"))]
+        let mut process = std::process::Command::new("sh");
+        #[cfg(not(target_os = "windows"))]
+        process.arg("-c").arg(command);
+        #[cfg(target_os = "windows")]
+        let mut process = std::process::Command::new("cmd");
+        #[cfg(target_os = "windows")]
+        process.arg("/C").arg(command);
+        let status = process
+            .current_dir(&deck_dir)
+            .env("COOLSLIDES_HOOK", hook)
+            .env("COOLSLIDES_DECK_DIR", &deck_dir)
+            .env("COOLSLIDES_DECK_TITLE", &deck.title)
+            .env("COOLSLIDES_SLIDE_COUNT", deck.slide_order().len().to_string())
+            .env("COOLSLIDES_VERSION", env!("CARGO_PKG_VERSION"))
+            .envs(extra_env.iter().map(|(key, value)| (*key, value)))
+            .status()
+            .map_err(|e| anyhow::anyhow!("{} hook could not run `{}`: {}", hook, command, e))?;
+        if !status.success() {
+            anyhow::bail!("{} hook failed ({}): {}", hook, status, command);
+        }
+    }
+    Ok(commands.len())
 }
 
 fn copy_dir_all(src: &Path, dst: &Path) -> std::io::Result<()> {
@@ -521,12 +1533,15 @@ enum PackageKind { Component, Plugin }
 struct ImportMap { imports: std::collections::BTreeMap<String, String> }
 
 #[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 struct Lockfile {
-    modelVersion: String,
-    irVersion: String,
+    model_version: String,
+    ir_version: String,
     timestamp: String,
-    importMap: ImportMap,
+    import_map: ImportMap,
     resolved: serde_json::Value,
+    #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+    vendored: serde_json::Map<String, serde_json::Value>,
 }
 
 fn init_project(target_dir: &str, template: &str, registry_flag: &str, registry_version: Option<&str>, do_git: bool) -> Result<()> {
@@ -610,11 +1625,12 @@ title = "Welcome to Coolslides"
 
     // Create lockfile skeleton
     let lock = Lockfile {
-        modelVersion: "1.0".to_string(),
-        irVersion: "1.0".to_string(),
+        model_version: "1.0".to_string(),
+        ir_version: "1.0".to_string(),
         timestamp: chrono::Utc::now().to_rfc3339(),
-        importMap: import_map.clone(),
+        import_map: import_map.clone(),
         resolved: serde_json::json!({ "components": {}, "plugins": {} }),
+        vendored: serde_json::Map::new(),
     };
     fs::write(target.join(".coolslides.lock"), serde_json::to_vec_pretty(&lock)?)?;
 
@@ -685,8 +1701,8 @@ async fn new_slide(deck_dir: &str, component_name: &str, id: &str, from_schema:
     let content_dir = deck_path.join("content");
     fs::create_dir_all(&content_dir)?;
 
-    // Resolve component schema
-    let schema = if let Some(schema_path) = from_schema {
+    // Resolve component schema and declared slots
+    let ComponentScaffold { schema, slots } = if let Some(schema_path) = from_schema {
         load_schema_from_path(Path::new(schema_path))?
     } else {
         load_schema_from_manifests(component_name)?
@@ -722,6 +1738,11 @@ async fn new_slide(deck_dir: &str, component_name: &str, id: &str, from_schema:
         writeln!(toml_str, "{}", line)?;
     }
 
+    // Required slots to fill in, optional ones commented out
+    for slot in &slots {
+        writeln!(toml_str, "\n{}", toml_slot_block(slot))?;
+    }
+
     let file_path = content_dir.join(format!("{}.slide.toml", id));
     fs::write(&file_path, toml_str)?;
     println!("✓ Created {}", file_path.display());
@@ -736,14 +1757,26 @@ struct JsonSchema {
     properties: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
-fn load_schema_from_path(path: &Path) -> Result<JsonSchema> {
+/// What `new` needs from a component manifest
+struct ComponentScaffold {
+    schema: JsonSchema,
+    slots: Vec<coolslides_core::SlotDeclaration>,
+}
+
+/// Read the scaffold from a component manifest, or from a bare JSON Schema (no slots)
+fn scaffold_from_manifest(v: &serde_json::Value) -> Result<ComponentScaffold> {
+    let schema = v.get("schema").unwrap_or(v).clone();
+    let slots = v.get("slots").cloned().map(serde_json::from_value).transpose()?.unwrap_or_default();
+    Ok(ComponentScaffold { schema: serde_json::from_value(schema)?, slots })
+}
+
+fn load_schema_from_path(path: &Path) -> Result<ComponentScaffold> {
     let s = fs::read_to_string(path)?;
     let v: serde_json::Value = serde_json::from_str(&s)?;
-    let schema = v.get("schema").unwrap_or(&v).clone();
-    Ok(serde_json::from_value(schema)?)
+    scaffold_from_manifest(&v)
 }
 
-fn load_schema_from_manifests(component_name: &str) -> Result<JsonSchema> {
+fn load_schema_from_manifests(component_name: &str) -> Result<ComponentScaffold> {
     // Try manifests dir first, then TS extraction via core (manifests fallback is likely enough here)
     let manifests_candidates = [
         Path::new("packages/components/manifests"),
@@ -759,8 +1792,8 @@ fn load_schema_from_manifests(component_name: &str) -> Result<JsonSchema> {
             let v: serde_json::Value = serde_json::from_str(&content)?;
             let name = v.get("name").and_then(|x| x.as_str()).unwrap_or("");
             if name == component_name {
-                let schema = v.get("schema").cloned().ok_or_else(|| anyhow::anyhow!("schema missing in manifest"))?;
-                return Ok(serde_json::from_value(schema)?);
+                if v.get("schema").is_none() { return Err(anyhow::anyhow!("schema missing in manifest")); }
+                return scaffold_from_manifest(&v);
             }
         }
     }
@@ -768,6 +1801,30 @@ fn load_schema_from_manifests(component_name: &str) -> Result<JsonSchema> {
     Err(anyhow::anyhow!("Component manifest for '{}' not found", component_name))
 }
 
+/// `[slots.<name>]` table for a declared slot, commented out unless the slot is required.
+/// Markdown is scaffolded unless the slot only accepts components.
+fn toml_slot_block(slot: &coolslides_core::SlotDeclaration) -> String {
+    use coolslides_core::SlotKind;
+    let mut lines = Vec::new();
+    if let Some(description) = &slot.description {
+        lines.push(format!("# {}", description));
+    }
+    lines.push(format!("[slots.{}]", slot.name));
+    if slot.accepts_kind(SlotKind::Markdown) {
+        lines.push("kind = \"markdown\"".to_string());
+        lines.push("value = \"\"".to_string());
+    } else {
+        lines.push("kind = \"component\"".to_string());
+        lines.push("tag = \"\"".to_string());
+        lines.push("module = \"\"".to_string());
+    }
+    if slot.required {
+        lines.join("\n")
+    } else {
+        lines.iter().map(|l| if l.starts_with('#') { l.clone() } else { format!("# {}", l) }).collect::<Vec<_>>().join("\n")
+    }
+}
+
 fn toml_prop_line(key: &str, prop: &serde_json::Value, commented: bool) -> String {
     let prefix = if commented { "# " } else { "" };
     let default_comment = if let Some(def) = prop.get("default") { format!("  # default: {}", def) } else { String::new() };
@@ -808,9 +1865,9 @@ fn format_json_value(v: &serde_json::Value) -> String {
 }
 
 fn prompt_for_prop_value(key: &str, prop: &serde_json::Value) -> Result<Option<String>> {
-    use std::io::{self, Read};
+    use std::io;
     let ty = prop.get("type").and_then(|x| x.as_str()).unwrap_or("string");
-    let def_str = prop.get("default").map(|d| format_json_value(d));
+    let def_str = prop.get("default").map(format_json_value);
     let enum_opts: Option<Vec<String>> = prop
         .get("enum")
         .and_then(|arr| arr.as_array().map(|a| a.iter().filter_map(|x| x.as_str().map(|s| s.to_string())).collect()));
@@ -865,26 +1922,47 @@ fn add_package(deck_dir: &str, spec: &str, kind: PackageKind) -> Result<()> {
         build_import_map(ImportRegistryMode::Auto, None)?
     };
 
-    let resolved_url = resolve_pkg_url(spec);
+    // Components published to the deck's remote registry resolve to the registry's module URL
+    let published = match kind {
+        PackageKind::Component => find_registry_component(dir, spec)?,
+        PackageKind::Plugin => None,
+    };
+    let (resolved_url, version) = match &published {
+        Some(manifest) => {
+            println!("✓ Found {}@{} in the deck's registry", manifest.name, manifest.version);
+            (manifest.module.clone(), manifest.version.clone())
+        }
+        None => (resolve_pkg_url(spec), spec_version(spec)),
+    };
     import_map.imports.insert(spec.to_string(), resolved_url.clone());
     fs::write(&importmap_path, serde_json::to_vec_pretty(&import_map)?)?;
     println!("✓ Updated {}", importmap_path.display());
 
     // Update lockfile
     let lock_path = dir.join(".coolslides.lock");
-    let mut lock: Lockfile = if lock_path.exists() {
-        serde_json::from_slice(&fs::read(&lock_path)?)?
-    } else {
-        Lockfile {
-            modelVersion: "1.0".into(),
-            irVersion: "1.0".into(),
-            timestamp: chrono::Utc::now().to_rfc3339(),
-            importMap: import_map.clone(),
-            resolved: serde_json::json!({ "components": {}, "plugins": {} }),
-        }
-    };
-    lock.importMap = import_map.clone();
+    let mut lock = read_or_new_lockfile(&lock_path, &import_map)?;
+    lock.import_map = import_map.clone();
     lock.timestamp = chrono::Utc::now().to_rfc3339();
+    let section = match kind {
+        PackageKind::Component => "components",
+        PackageKind::Plugin => "plugins",
+    };
+    if !lock.resolved.get(section).is_some_and(|v| v.is_object()) {
+        lock.resolved[section] = serde_json::json!({});
+    }
+    let mut entry = serde_json::json!({
+        "version": version,
+        "url": resolved_url,
+    });
+    // Pin the module's hash so exported decks can refuse tampered copies
+    match integrity::module_integrity(&resolved_url, dir) {
+        Ok(hash) => {
+            println!("✓ Computed integrity {}", hash);
+            entry["integrity"] = hash.into();
+        }
+        Err(e) => eprintln!("Warning: no integrity hash for {}: {}", spec, e),
+    }
+    lock.resolved[section][spec] = entry;
     fs::write(&lock_path, serde_json::to_vec_pretty(&lock)?)?;
     println!("✓ Updated {}", lock_path.display());
 
@@ -906,6 +1984,115 @@ fn add_package(deck_dir: &str, spec: &str, kind: PackageKind) -> Result<()> {
     Ok(())
 }
 
+/// Existing `.coolslides.lock`, or an empty one for `import_map`
+fn read_or_new_lockfile(lock_path: &Path, import_map: &ImportMap) -> Result<Lockfile> {
+    if lock_path.exists() {
+        return Ok(serde_json::from_slice(&fs::read(lock_path)?)?);
+    }
+    Ok(Lockfile {
+        model_version: "1.0".into(),
+        ir_version: "1.0".into(),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        import_map: import_map.clone(),
+        resolved: serde_json::json!({ "components": {}, "plugins": {} }),
+        vendored: serde_json::Map::new(),
+    })
+}
+
+/// Download every remote import-map module (and what it imports) into vendor/,
+/// point importmap.json and the lockfile at the local copies, and record their hashes
+fn vendor_deck(dir: &Path) -> Result<()> {
+    let importmap_path = dir.join("importmap.json");
+    if !importmap_path.exists() {
+        return Err(anyhow::anyhow!("No importmap.json in {}", dir.display()));
+    }
+    let import_map: coolslides_core::ImportMap = serde_json::from_slice(&fs::read(&importmap_path)?)?;
+    let result = vendor::vendor(&import_map, dir, |url| {
+        println!("  ↓ {}", url);
+        integrity::read_module(url, dir)
+    })?;
+    for (specifier, reason) in &result.skipped {
+        eprintln!("Warning: skipped {}: {}", specifier, reason);
+    }
+    if result.modules.is_empty() {
+        println!("✓ No remote modules to vendor");
+        return Ok(());
+    }
+
+    let import_map = ImportMap { imports: result.import_map.imports.into_iter().collect() };
+    fs::write(&importmap_path, serde_json::to_vec_pretty(&import_map)?)?;
+    println!("✓ Vendored {} modules into {}", result.modules.len(), dir.join(vendor::VENDOR_DIR).display());
+    println!("✓ Updated {}", importmap_path.display());
+
+    let lock_path = dir.join(".coolslides.lock");
+    let mut lock = read_or_new_lockfile(&lock_path, &import_map)?;
+    lock.import_map = import_map;
+    lock.timestamp = chrono::Utc::now().to_rfc3339();
+    for section in ["components", "plugins"] {
+        let Some(entries) = lock.resolved.get_mut(section).and_then(|v| v.as_object_mut()) else {
+            continue;
+        };
+        for entry in entries.values_mut() {
+            let vendored = entry.get("url").and_then(|u| u.as_str()).and_then(|url| result.modules.get(url));
+            if let Some(module) = vendored {
+                entry["url"] = format!("./{}", module.path).into();
+                entry["integrity"] = module.integrity.clone().into();
+            }
+        }
+    }
+    for (url, module) in &result.modules {
+        lock.vendored.insert(url.clone(), serde_json::to_value(module)?);
+    }
+    fs::write(&lock_path, serde_json::to_vec_pretty(&lock)?)?;
+    println!("✓ Updated {}", lock_path.display());
+    Ok(())
+}
+
+/// Look up a component spec (`Name` or `Name@1.2.3`) in the remote registry configured in slides.toml
+fn find_registry_component(deck_dir: &Path, spec: &str) -> Result<Option<coolslides_core::ComponentManifest>> {
+    let manifest_path = deck_dir.join("slides.toml");
+    if !manifest_path.exists() {
+        return Ok(None);
+    }
+    let deck: DeckManifest = toml::from_str(&fs::read_to_string(&manifest_path)?)?;
+    let Some(config) = deck.registry.as_ref() else {
+        return Ok(None);
+    };
+
+    let name = spec.char_indices().skip(1).find(|(_, c)| *c == '@').map_or(spec, |(i, _)| &spec[..i]);
+    let remote = RemoteRegistry::for_deck(config, deck_dir).load()?;
+    if !remote.registry.components.contains_key(name) {
+        return Ok(None);
+    }
+    // `Chart` picks the newest version, `Chart@1.2.0` exactly that one, `Chart@^1` the newest match
+    let requested = spec_version(spec);
+    let req = match requested.as_str() {
+        "latest" => "*".to_string(),
+        version if version.chars().next().is_some_and(|c| c.is_ascii_digit()) => format!("={}", version),
+        range => range.to_string(),
+    };
+    match remote.registry.resolve(name, &req) {
+        Some(manifest) => Ok(Some(manifest.clone())),
+        None => {
+            let available: Vec<&str> = remote.registry.all_versions(name).map(|m| m.version.as_str()).collect();
+            Err(anyhow::anyhow!(
+                "Registry {} has no {} matching {} (available: {})",
+                config.index, name, requested, available.join(", ")
+            ))
+        }
+    }
+}
+
+/// Version pinned in a package spec (`name@1.2.3`, `@scope/name@1.2.3`), or "latest"
+fn spec_version(spec: &str) -> String {
+    spec.char_indices()
+        .skip(1)
+        .find(|(_, c)| *c == '@')
+        .map(|(i, _)| spec[i + 1..].to_string())
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| "latest".to_string())
+}
+
 fn resolve_pkg_url(spec: &str) -> String {
     // Simple heuristic: known first-party packages vs generic CDN
     if spec.starts_with("@coolslides/") {
@@ -0,0 +1,32 @@
+//! Criterion benchmarks for the render path, on the example deck scaled up
+//!
+//! `cargo bench -p coolslides_server`; `coolslides bench` runs the same
+//! operations against a deck and checks them against its time budgets.
+
+use coolslides_server::bench::{Fixture, Operation};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::path::Path;
+
+/// Deck sizes each operation is timed at
+const SLIDE_COUNTS: &[usize] = &[50, 500];
+
+fn render_path(c: &mut Criterion) {
+    let deck_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../examples/basic-deck");
+    let fixtures: Vec<(usize, Fixture)> = SLIDE_COUNTS
+        .iter()
+        .map(|&count| (count, Fixture::load(&deck_dir, Some(count)).expect("example deck loads")))
+        .collect();
+
+    for operation in Operation::ALL {
+        let mut group = c.benchmark_group(operation.name());
+        for (count, fixture) in &fixtures {
+            group.bench_with_input(BenchmarkId::from_parameter(count), fixture, |b, fixture| {
+                b.iter(|| fixture.run(operation).unwrap())
+            });
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, render_path);
+criterion_main!(benches);
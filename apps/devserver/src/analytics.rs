@@ -0,0 +1,290 @@
+/*!
+ * Opt-in view analytics: anonymous events aggregated into `.coolslides/analytics.json`
+ *
+ * Only counters are kept. Events carry no visitor identifiers, and nothing about
+ * the request (address, user agent, referrer) is stored.
+ */
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use crate::storage::Storage;
+
+/// Where the aggregate lives, relative to the deck directory
+pub const ANALYTICS_FILE: &str = ".coolslides/analytics.json";
+
+/// Most events accepted in one request
+pub const MAX_BATCH: usize = 100;
+
+/// Longest time on a slide one event may report; longer reports are clamped
+const MAX_SLIDE_MS: u64 = 60 * 60 * 1000;
+
+/// Export formats counted by `exportDownloaded`
+const EXPORT_FORMATS: &[&str] = &["html", "pdf"];
+
+/// One anonymous view event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum AnalyticsEvent {
+    /// A slide came on screen
+    #[serde(rename_all = "camelCase")]
+    SlideShown { slide_id: String },
+    /// A slide left the screen after `ms` milliseconds
+    #[serde(rename_all = "camelCase")]
+    SlideTime { slide_id: String, ms: u64 },
+    /// An export was downloaded
+    ExportDownloaded { format: String },
+}
+
+/// A single event or a batch, as `POST /api/analytics` accepts
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum EventBatch {
+    One(AnalyticsEvent),
+    Many(Vec<AnalyticsEvent>),
+}
+
+impl EventBatch {
+    pub fn into_events(self) -> Vec<AnalyticsEvent> {
+        match self {
+            EventBatch::One(event) => vec![event],
+            EventBatch::Many(events) => events,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlideStats {
+    pub views: u64,
+    /// Total milliseconds on screen across all views
+    pub total_ms: u64,
+    /// `total_ms` over the number of timed views
+    pub average_ms: u64,
+    #[serde(default)]
+    pub timed_views: u64,
+}
+
+/// The aggregate `GET /api/analytics` returns
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalyticsSummary {
+    /// When the first event was recorded
+    pub since: Option<DateTime<Utc>>,
+    /// When the last event was recorded
+    pub updated: Option<DateTime<Utc>>,
+    /// Events accepted
+    pub events: u64,
+    pub slides: BTreeMap<String, SlideStats>,
+    /// Downloads per export format
+    pub exports: BTreeMap<String, u64>,
+}
+
+impl AnalyticsSummary {
+    /// Fold one event in; events for unknown slides or formats are ignored.
+    /// Returns whether the event counted.
+    pub fn record(&mut self, event: &AnalyticsEvent, known_slides: &HashMap<String, coolslides_core::SlideDoc>) -> bool {
+        match event {
+            AnalyticsEvent::SlideShown { slide_id } if known_slides.contains_key(slide_id) => {
+                self.slides.entry(slide_id.clone()).or_default().views += 1;
+            }
+            AnalyticsEvent::SlideTime { slide_id, ms } if known_slides.contains_key(slide_id) => {
+                let stats = self.slides.entry(slide_id.clone()).or_default();
+                stats.total_ms += (*ms).min(MAX_SLIDE_MS);
+                stats.timed_views += 1;
+                stats.average_ms = stats.total_ms / stats.timed_views;
+            }
+            AnalyticsEvent::ExportDownloaded { format } if EXPORT_FORMATS.contains(&format.as_str()) => {
+                *self.exports.entry(format.clone()).or_default() += 1;
+            }
+            _ => return false,
+        }
+        let now = Utc::now();
+        self.since.get_or_insert(now);
+        self.updated = Some(now);
+        self.events += 1;
+        true
+    }
+}
+
+/// The aggregate of the loaded deck, kept in memory and written through to disk,
+/// or to the database when the server has one
+#[derive(Debug, Default)]
+pub struct Analytics {
+    current: Mutex<Option<(PathBuf, AnalyticsSummary)>>,
+    storage: Option<Arc<Storage>>,
+}
+
+impl Analytics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep aggregates in `storage`, keyed by deck directory, instead of in the deck
+    pub fn with_storage(storage: Arc<Storage>) -> Self {
+        Self { current: Mutex::default(), storage: Some(storage) }
+    }
+
+    /// Record events for the deck in `deck_dir`, persisting the new aggregate.
+    /// Returns how many events counted.
+    pub async fn record(
+        &self,
+        deck_dir: &Path,
+        events: &[AnalyticsEvent],
+        known_slides: &HashMap<String, coolslides_core::SlideDoc>,
+    ) -> anyhow::Result<usize> {
+        self.load(deck_dir).await;
+        let (counted, summary) = {
+            let mut current = self.current.lock().unwrap();
+            let summary = match current.as_mut() {
+                Some((dir, summary)) if dir == deck_dir => summary,
+                // Another deck was loaded meanwhile; its aggregate is not touched
+                _ => return Ok(0),
+            };
+            let counted = events.iter().filter(|event| summary.record(event, known_slides)).count();
+            (counted, summary.clone())
+        };
+        if counted > 0 {
+            match &self.storage {
+                Some(storage) => storage.save_analytics(&deck_dir.to_string_lossy(), &summary).await?,
+                None => write_atomic(&deck_dir.join(ANALYTICS_FILE), &serde_json::to_vec_pretty(&summary)?)?,
+            }
+        }
+        Ok(counted)
+    }
+
+    /// The aggregate for the deck in `deck_dir`
+    pub async fn summary(&self, deck_dir: &Path) -> AnalyticsSummary {
+        self.load(deck_dir).await;
+        match self.current.lock().unwrap().as_ref() {
+            Some((dir, summary)) if dir == deck_dir => summary.clone(),
+            _ => AnalyticsSummary::default(),
+        }
+    }
+
+    /// Switch to the deck's stored aggregate when a different deck is loaded
+    async fn load(&self, deck_dir: &Path) {
+        if self.current.lock().unwrap().as_ref().map(|(dir, _)| dir.as_path()) == Some(deck_dir) {
+            return;
+        }
+        let summary = match &self.storage {
+            Some(storage) => storage.load_analytics(&deck_dir.to_string_lossy()).await.unwrap_or_else(|e| {
+                tracing::warn!(error = %e, "could not read analytics");
+                None
+            }),
+            None => std::fs::read(deck_dir.join(ANALYTICS_FILE)).ok().and_then(|bytes| serde_json::from_slice(&bytes).ok()),
+        };
+        let mut current = self.current.lock().unwrap();
+        if current.as_ref().map(|(dir, _)| dir.as_path()) != Some(deck_dir) {
+            *current = Some((deck_dir.to_path_buf(), summary.unwrap_or_default()));
+        }
+    }
+}
+
+/// Write via a temporary file so a crash never leaves a truncated aggregate
+fn write_atomic(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let temp = path.with_extension("json.tmp");
+    std::fs::write(&temp, bytes)?;
+    std::fs::rename(&temp, path)
+}
+
+/// Reports slide views and time on slide from a deck page
+pub(crate) const TRACKER_SCRIPT: &str = r#"<script type="module">
+  const endpoint = '/api/analytics';
+  let current = null;
+  let shownAt = 0;
+  let queue = [];
+
+  const slideFromHash = () => decodeURIComponent(location.hash.slice(1).split('/')[0] || '');
+  function flush() {
+    if (queue.length === 0) return;
+    const body = new Blob([JSON.stringify(queue)], { type: 'application/json' });
+    queue = [];
+    if (!navigator.sendBeacon(endpoint, body)) {
+      fetch(endpoint, { method: 'POST', body, keepalive: true }).catch(() => {});
+    }
+  }
+  function leave() {
+    if (current) queue.push({ type: 'slideTime', slideId: current, ms: Math.round(performance.now() - shownAt) });
+    current = null;
+  }
+  function enter() {
+    const slide = slideFromHash();
+    if (!slide || slide === current) return;
+    leave();
+    current = slide;
+    shownAt = performance.now();
+    queue.push({ type: 'slideShown', slideId: slide });
+    flush();
+  }
+
+  window.addEventListener('hashchange', enter);
+  document.addEventListener('visibilitychange', () => {
+    if (document.visibilityState === 'hidden') { leave(); flush(); }
+    else enter();
+  });
+  window.addEventListener('pagehide', () => { leave(); flush(); });
+  // The router fills in the hash for the first slide once it has started
+  setTimeout(enter, 0);
+</script>"#;
+
+/// Append the tracker to a rendered deck page
+pub(crate) fn inject(html: &str) -> String {
+    match html.rfind("</body>") {
+        Some(at) => format!("{}{}\n{}", &html[..at], TRACKER_SCRIPT, &html[at..]),
+        None => format!("{}{}\n", html, TRACKER_SCRIPT),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coolslides_core::SlideDoc;
+
+    fn slides(ids: &[&str]) -> HashMap<String, SlideDoc> {
+        ids.iter()
+            .map(|id| {
+                let slide: SlideDoc = toml::from_str(&format!(
+                    "modelVersion = \"1.0\"\nid = \"{}\"\n\n[component]\nname = \"TitleSlide\"\nversionReq = \"^1\"\n\n[props]\ntitle = \"T\"\n",
+                    id
+                ))
+                .unwrap();
+                (id.to_string(), slide)
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_events_aggregate_to_disk() {
+        let deck = tempfile::TempDir::new().unwrap();
+        let known = slides(&["intro", "outro"]);
+        let batch: EventBatch = serde_json::from_str(
+            r#"[
+                {"type": "slideShown", "slideId": "intro"},
+                {"type": "slideTime", "slideId": "intro", "ms": 4000},
+                {"type": "slideTime", "slideId": "intro", "ms": 2000},
+                {"type": "slideShown", "slideId": "not-a-slide"},
+                {"type": "exportDownloaded", "format": "pdf"},
+                {"type": "exportDownloaded", "format": "exe"}
+            ]"#,
+        )
+        .unwrap();
+
+        let analytics = Analytics::new();
+        assert_eq!(analytics.record(deck.path(), &batch.into_events(), &known).await.unwrap(), 4);
+
+        // A fresh instance reads the aggregate back from disk
+        let summary = Analytics::new().summary(deck.path()).await;
+        assert_eq!(summary.events, 4);
+        assert_eq!(summary.slides["intro"].views, 1);
+        assert_eq!(summary.slides["intro"].average_ms, 3000);
+        assert!(!summary.slides.contains_key("not-a-slide"));
+        assert_eq!(summary.exports.get("pdf"), Some(&1));
+        assert_eq!(summary.exports.len(), 1);
+    }
+}
@@ -0,0 +1,123 @@
+/*!
+ * Files a deck references from its own directory
+ *
+ * `/assets/...` serves `<deck>/assets`, so `![](assets/diagram.png)` in a
+ * slide loads in dev the way it does next to an exported deck. Projects that
+ * keep media elsewhere add mounts in `coolslides.config.toml`:
+ *
+ * ```toml
+ * [assets]
+ * "/media" = "../shared/media"
+ * ```
+ *
+ * Directories are relative to the loaded deck, not the directory the server
+ * was started in, and are resolved on each request so a deck switch takes
+ * effect immediately. Responses carry the same cache validators as the
+ * runtime bundles.
+ */
+
+use crate::{safe_relative_path, AppState};
+use axum::{
+    body::Body,
+    extract::{Path as AxumPath, Request, State},
+    http::StatusCode,
+    response::Response,
+    routing::get,
+    Router,
+};
+use std::{collections::BTreeMap, path::PathBuf};
+use tower::ServiceExt;
+
+/// First path segments the server's own routes use
+const RESERVED: &[&str] = &[
+    "api", "rooms", "collab", "ws", "components", "vendor", "narration", "static", "packages", "themes", "assets",
+    "presenter", "audience", "teleprompter", "join", "kiosk", "narrated", "healthz", "test", "proxy", "slide",
+];
+
+/// Check a mount's URL prefix: `/name` or `/a/b`, not shadowing a built-in route
+pub fn validate_prefix(prefix: &str) -> anyhow::Result<()> {
+    let Some(path) = prefix.strip_prefix('/') else {
+        anyhow::bail!("asset mount {:?} must start with '/'", prefix);
+    };
+    let segments: Vec<&str> = path.trim_end_matches('/').split('/').collect();
+    if segments.iter().any(|s| s.is_empty() || s.starts_with([':', '*']) || *s == "." || *s == "..") {
+        anyhow::bail!("asset mount {:?} is not a plain path", prefix);
+    }
+    if RESERVED.contains(&segments[0]) {
+        anyhow::bail!("asset mount {:?} would shadow the server's /{} routes", prefix, segments[0]);
+    }
+    Ok(())
+}
+
+/// Add `/assets` and `mounts` (URL prefix to directory) to `router`
+pub(crate) fn routes(mut router: Router<AppState>, mounts: &BTreeMap<String, PathBuf>) -> Router<AppState> {
+    let builtin = BTreeMap::from([("/assets".to_string(), PathBuf::from("assets"))]);
+    for (prefix, dir) in builtin.iter().chain(mounts) {
+        let dir = dir.clone();
+        let route = format!("{}/*path", prefix.trim_end_matches('/'));
+        router = router.route(
+            &route,
+            get(move |State(state): State<AppState>, AxumPath(path): AxumPath<String>, request: Request| {
+                let dir = dir.clone();
+                async move { serve(&state, dir, &path, request).await }
+            })
+            .layer(axum::middleware::from_fn(crate::caching::static_validators)),
+        );
+    }
+    router
+}
+
+/// Serve `path` from `dir` under the loaded deck, with range requests
+async fn serve(state: &AppState, dir: PathBuf, path: &str, request: Request) -> Result<Response, StatusCode> {
+    let relative = safe_relative_path(path)?;
+    let deck_root = state.deck_root.read().await.clone().ok_or(StatusCode::NOT_FOUND)?;
+    let response = tower_http::services::ServeFile::new(deck_root.join(dir).join(relative))
+        .oneshot(request)
+        .await
+        .unwrap_or_else(|never| match never {});
+    Ok(response.map(Body::new))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::header;
+
+    #[test]
+    fn test_prefixes_must_not_shadow_builtin_routes() {
+        assert!(validate_prefix("/media").is_ok());
+        assert!(validate_prefix("/shared/media/").is_ok());
+        for prefix in ["media", "/", "/api", "/assets", "/a//b", "/:id", "/../up"] {
+            assert!(validate_prefix(prefix).is_err(), "{}", prefix);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_serves_from_the_deck_not_the_working_directory() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("content")).unwrap();
+        std::fs::create_dir_all(dir.path().join("assets/img")).unwrap();
+        std::fs::create_dir_all(dir.path().join("media")).unwrap();
+        std::fs::write(
+            dir.path().join("slides.toml"),
+            "modelVersion = \"1.0\"\ntitle = \"T\"\ntheme = \"theme.css\"\nsequence = []\n\n[transitions]\ndefault = \"none\"\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("assets/img/diagram.svg"), "<svg/>").unwrap();
+        std::fs::write(dir.path().join("media/clip.txt"), "clip").unwrap();
+        let state = AppState::new();
+        state.load_from_directory(dir.path()).await.unwrap();
+
+        let mounts = BTreeMap::from([("/media".to_string(), PathBuf::from("media"))]);
+        let app = routes(Router::new(), &mounts).with_state(state);
+        let get = |uri: &str| app.clone().oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap());
+
+        let svg = get("/assets/img/diagram.svg").await.unwrap();
+        assert_eq!(svg.status(), StatusCode::OK);
+        assert_eq!(svg.headers()[header::CONTENT_TYPE], "image/svg+xml");
+        assert!(svg.headers().contains_key(header::ETAG));
+        assert_eq!(get("/media/clip.txt").await.unwrap().status(), StatusCode::OK);
+        assert_eq!(get("/assets/missing.png").await.unwrap().status(), StatusCode::NOT_FOUND);
+        assert_eq!(get("/assets/../slides.toml").await.unwrap().status(), StatusCode::BAD_REQUEST);
+    }
+}
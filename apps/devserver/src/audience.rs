@@ -0,0 +1,131 @@
+/*!
+ * Audience view: the deck, following the presenter or browsed freely
+ *
+ * The page joins a room (`?room=`, else `default`) as audience and shows the
+ * deck page in a frame, opened offline so the frame itself never joins. In
+ * follow mode the frame moves with every `slide:change` and `fragment:change`
+ * in the room. Browsing hands the frame to the viewer; the presenter's position
+ * is still tracked, so following again jumps straight back to it.
+ */
+
+use crate::html_escape;
+
+const PAGE_STYLE: &str = r#"<style>
+  html, body { margin: 0; height: 100%; background: #000; overflow: hidden; font: 14px/1.4 system-ui, sans-serif; }
+  iframe { position: fixed; inset: 0; width: 100%; height: 100%; border: 0; }
+  body.following iframe { pointer-events: none; }
+  .bar { position: fixed; right: 12px; bottom: 12px; display: flex; gap: 8px; align-items: center; padding: 6px 8px 6px 12px; border-radius: 20px; background: rgba(20, 20, 20, .8); color: #eee; z-index: 1; }
+  .bar button { background: #3a3a3a; color: #eee; border: 0; border-radius: 14px; padding: 4px 12px; font: inherit; cursor: pointer; }
+  body.browsing .bar button { background: #2f6fdf; }
+  #status.offline { color: #ff6b6b; }
+</style>"#;
+
+const PAGE_SCRIPT: &str = r#"<script>
+(function () {
+  const params = new URLSearchParams(location.search);
+  const room = params.get('room') || 'default';
+  const frame = document.getElementById('deck');
+  const deckUrl = new URL('./?offline=1', location.href).href;
+  const status = document.getElementById('status');
+  const toggle = document.getElementById('follow');
+  // Where the presenter is, known even while browsing
+  let position = null;
+  let following = true;
+
+  function hash(p) {
+    return '#' + encodeURIComponent(p.slideId) + (p.fragment > 0 ? '/' + p.fragment : '');
+  }
+
+  function sync() {
+    if (!following || !position) return;
+    const src = deckUrl + hash(position);
+    // Only the hash changes after the first load, so the deck is not reloaded
+    if (frame.src !== src) frame.src = src;
+  }
+
+  function moveTo(slideId, fragment) {
+    if (!slideId) return;
+    position = { slideId: slideId, fragment: Number(fragment) || 0 };
+    status.textContent = following ? 'Following' : 'Browsing · presenter on ' + slideId;
+    sync();
+  }
+
+  function setFollowing(on) {
+    following = on;
+    document.body.classList.toggle('following', on);
+    document.body.classList.toggle('browsing', !on);
+    toggle.textContent = on ? 'Browse' : 'Follow';
+    status.textContent = on ? 'Following' : 'Browsing';
+    if (on) sync(); else frame.focus();
+  }
+
+  function connect() {
+    const url = new URL('rooms/' + encodeURIComponent(room), location.href);
+    url.protocol = location.protocol === 'https:' ? 'wss:' : 'ws:';
+    const ws = new WebSocket(url);
+    ws.onopen = () => status.classList.remove('offline');
+    ws.onmessage = event => {
+      let message;
+      try { message = JSON.parse(event.data); } catch (_) { return; }
+      if (message.type === 'state' && message.data) {
+        const current = message.data.currentSlide;
+        const fragment = message.data.currentFragment;
+        if (current) moveTo(current.slideId, fragment && fragment.slideId === current.slideId ? fragment.fragment : current.fragment);
+      }
+      if (message.type !== 'event' || !message.event) return;
+      const data = message.event.data || {};
+      if (message.event.name === 'slide:change') moveTo(data.slideId, data.fragment);
+      if (message.event.name === 'fragment:change' && position) moveTo(data.slideId || position.slideId, data.fragment);
+    };
+    ws.onclose = () => {
+      status.textContent = 'Reconnecting…';
+      status.classList.add('offline');
+      setTimeout(connect, 2000);
+    };
+  }
+
+  toggle.onclick = () => setFollowing(!following);
+  addEventListener('keydown', event => {
+    if (event.key === 'f') setFollowing(!following);
+  });
+  frame.src = deckUrl;
+  setFollowing(true);
+  connect();
+})();
+</script>"#;
+
+/// The audience view for a deck titled `title`
+pub(crate) fn page(title: &str) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+    <title>{}</title>
+    <meta charset="utf-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1">
+    {}
+</head>
+<body class="following">
+    <iframe id="deck" title="Slides" allow="fullscreen"></iframe>
+    <div class="bar"><span id="status" class="offline">Connecting…</span><button id="follow">Browse</button></div>
+    {}
+</body>
+</html>"#,
+        html_escape(title),
+        PAGE_STYLE,
+        PAGE_SCRIPT
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_frames_the_deck_offline() {
+        let page = page("Q3 <Review>");
+        assert!(page.contains("<title>Q3 &lt;Review&gt;</title>"));
+        assert!(page.contains(r#"<iframe id="deck""#));
+        assert!(page.contains("new URL('./?offline=1', location.href)"));
+    }
+}
@@ -0,0 +1,235 @@
+/*!
+ * Render-path timings behind `coolslides bench` and the criterion benchmarks
+ *
+ * A deck is scaled to a target slide count by repeating its slides under new
+ * ids, so a small deck can stand in for a 500-slide talk. Render caches are
+ * cleared before every render, so the numbers are what a cold load costs.
+ */
+
+use crate::SanitizationConfig;
+use coolslides_core::{registry, validation, ComponentRegistry, DeckItem, DeckManifest, SlideDoc, Slot, TimeBudgets};
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    /// Parsing the manifest and slide files
+    Load,
+    Validate,
+    /// Rendering and sanitizing every markdown slot
+    Markdown,
+    /// Generating the slides HTML
+    Render,
+}
+
+impl Operation {
+    pub const ALL: [Operation; 4] = [Operation::Load, Operation::Validate, Operation::Markdown, Operation::Render];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Operation::Load => "load",
+            Operation::Validate => "validate",
+            Operation::Markdown => "markdown",
+            Operation::Render => "render",
+        }
+    }
+
+    fn budget_ms(self, budgets: &TimeBudgets) -> u64 {
+        match self {
+            Operation::Load => budgets.load_ms,
+            Operation::Validate => budgets.validate_ms,
+            Operation::Markdown => budgets.markdown_ms,
+            Operation::Render => budgets.render_ms,
+        }
+    }
+}
+
+/// A deck's sources and parsed documents, scaled to a slide count
+pub struct Fixture {
+    manifest_source: String,
+    slide_sources: Vec<String>,
+    pub deck: DeckManifest,
+    /// Slides in sequence order
+    pub slides: Vec<SlideDoc>,
+    slide_map: HashMap<String, SlideDoc>,
+    registry: Option<ComponentRegistry>,
+    config: SanitizationConfig,
+}
+
+impl Fixture {
+    /// Read the deck in `deck_dir`, repeating its slides until there are `slides`
+    /// of them; `None` keeps the deck's own size
+    pub fn load(deck_dir: &Path, slides: Option<usize>) -> anyhow::Result<Self> {
+        let manifest_source = std::fs::read_to_string(deck_dir.join("slides.toml"))?;
+        let mut deck: DeckManifest = toml::from_str(&manifest_source)?;
+
+        let content_dir = deck_dir.join("content");
+        let mut originals: Vec<(SlideDoc, String)> = Vec::new();
+        if content_dir.is_dir() {
+            for entry in std::fs::read_dir(&content_dir)? {
+                let path = entry?.path();
+                if path.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.ends_with(".slide.toml")) {
+                    let source = std::fs::read_to_string(&path)?;
+                    originals.push((toml::from_str(&source)?, source));
+                }
+            }
+        }
+        if originals.is_empty() {
+            anyhow::bail!("No slides found in {}", content_dir.display());
+        }
+        // Repeat slides in the order the deck shows them
+        let order = deck.slide_order();
+        let position = |id: &str| order.iter().position(|o| *o == id).unwrap_or(usize::MAX);
+        originals.sort_by(|(a, _), (b, _)| position(&a.id).cmp(&position(&b.id)).then_with(|| a.id.cmp(&b.id)));
+
+        let count = slides.unwrap_or(originals.len()).max(1);
+        let slide_sources: Vec<String> = (0..count)
+            .map(|i| {
+                let (slide, source) = &originals[i % originals.len()];
+                match i / originals.len() {
+                    0 => source.clone(),
+                    copy => rename_slide(source, &slide.id, &format!("{}-{}", slide.id, copy)),
+                }
+            })
+            .collect();
+        let mut slides = parse_slides(&slide_sources)?;
+        // Copies get distinct markdown so a render cannot serve one from another's cache entry
+        for slide in slides.iter_mut().skip(originals.len()) {
+            for slot in slide.slots.values_mut() {
+                if let Slot::Markdown { value } = slot {
+                    value.push_str(&format!("\n\n<!-- {} -->\n", slide.id));
+                }
+            }
+        }
+        deck.sequence = slides.iter().map(|slide| DeckItem::Ref { slide_id: slide.id.clone() }).collect();
+
+        let registry = registry::load_deck_registry(&deck, deck_dir).registry;
+        let config = SanitizationConfig::new(false).for_deck(&deck);
+        let slide_map = slides.iter().map(|slide| (slide.id.clone(), slide.clone())).collect();
+        Ok(Self { manifest_source, slide_sources, deck, slides, slide_map, registry, config })
+    }
+
+    /// Perform one operation on the whole deck
+    pub fn run(&self, operation: Operation) -> anyhow::Result<()> {
+        match operation {
+            Operation::Load => {
+                toml::from_str::<DeckManifest>(&self.manifest_source)?;
+                parse_slides(&self.slide_sources)?;
+            }
+            Operation::Validate => {
+                validation::validate_deck_with_registry(&self.deck, &self.slides, self.registry.as_ref());
+            }
+            Operation::Markdown => {
+                for slot in self.slides.iter().flat_map(|slide| slide.slots.values()) {
+                    if let Slot::Markdown { value } = slot {
+                        crate::sanitize_markdown(value, &self.config);
+                    }
+                }
+            }
+            Operation::Render => {
+                crate::clear_render_caches();
+                crate::render_slides_html(&self.deck, &self.slide_map, self.registry.as_ref(), &self.config)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse_slides(sources: &[String]) -> anyhow::Result<Vec<SlideDoc>> {
+    sources.iter().map(|source| Ok(toml::from_str(source)?)).collect()
+}
+
+/// A slide's source with its top-level `id` changed
+fn rename_slide(source: &str, id: &str, new_id: &str) -> String {
+    let quoted = format!("\"{}\"", id);
+    let mut top_level = true;
+    source
+        .lines()
+        .map(|line| {
+            top_level &= !line.trim_start().starts_with('[');
+            match line.split_once('=') {
+                Some((key, value)) if top_level && key.trim() == "id" && value.trim() == quoted => {
+                    format!("id = \"{}\"", new_id)
+                }
+                _ => line.to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Timings of one operation over several runs
+#[derive(Debug, Clone)]
+pub struct Measurement {
+    pub operation: Operation,
+    pub median: Duration,
+    pub max: Duration,
+    pub budget: Option<Duration>,
+}
+
+impl Measurement {
+    pub fn over_budget(&self) -> bool {
+        self.budget.is_some_and(|budget| self.median > budget)
+    }
+}
+
+/// Run every operation `iterations` times and compare the medians with the budgets
+pub fn measure(fixture: &Fixture, iterations: u32, budgets: &TimeBudgets) -> anyhow::Result<Vec<Measurement>> {
+    Operation::ALL
+        .into_iter()
+        .map(|operation| {
+            let mut samples = Vec::new();
+            for _ in 0..iterations.max(1) {
+                let start = Instant::now();
+                fixture.run(operation)?;
+                samples.push(start.elapsed());
+            }
+            samples.sort();
+            let budget = operation.budget_ms(budgets);
+            Ok(Measurement {
+                operation,
+                median: samples[samples.len() / 2],
+                max: samples[samples.len() - 1],
+                budget: (budget > 0).then(|| Duration::from_millis(budget)),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixture_scales_deck_and_checks_budgets() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("content")).unwrap();
+        std::fs::write(
+            dir.path().join("slides.toml"),
+            "modelVersion = \"1.0\"\ntitle = \"Bench\"\ntheme = \"default\"\nsequence = [\"b\", \"a\"]\n\n[transitions]\ndefault = \"none\"\n",
+        )
+        .unwrap();
+        for id in ["a", "b"] {
+            std::fs::write(
+                dir.path().join(format!("content/{}.slide.toml", id)),
+                format!(
+                    "modelVersion = \"1.0\"\nid = \"{}\"\nprops = {{}}\n\n[component]\nname = \"TitleSlide\"\nversionReq = \"^1\"\n\n[slots.body]\nkind = \"markdown\"\nvalue = \"# Hello\"\n",
+                    id
+                ),
+            )
+            .unwrap();
+        }
+
+        let fixture = Fixture::load(dir.path(), Some(5)).unwrap();
+        let ids: Vec<&str> = fixture.slides.iter().map(|slide| slide.id.as_str()).collect();
+        assert_eq!(ids, ["b", "a", "b-1", "a-1", "b-2"]);
+        assert_eq!(fixture.deck.slide_order(), ids);
+
+        let budgets = TimeBudgets { slides: 5, load_ms: 0, validate_ms: 60_000, markdown_ms: 60_000, render_ms: 60_000 };
+        let measurements = measure(&fixture, 3, &budgets).unwrap();
+        assert_eq!(measurements.len(), Operation::ALL.len());
+        assert_eq!(measurements[0].budget, None);
+        assert!(measurements.iter().all(|m| !m.over_budget() && m.median <= m.max));
+    }
+}
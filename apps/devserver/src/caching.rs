@@ -0,0 +1,112 @@
+/*!
+ * Cache validators for static assets, and no caching for deck content
+ *
+ * The runtime and component bundles and the themes are served from disk with
+ * an ETag derived from the file's size and modification time. Browsers
+ * revalidate them on every load (`no-cache`) and get a `304` while the file
+ * is unchanged, so a reload no longer refetches every bundle. Deck pages and
+ * API responses change with every edit and are never stored.
+ */
+
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+
+/// Add an ETag to static file responses and answer `If-None-Match` with `304`
+pub(crate) async fn static_validators(request: Request, next: Next) -> Response {
+    let if_none_match = request.headers().get(header::IF_NONE_MATCH).cloned();
+    let mut response = next.run(request).await;
+    if matches!(response.status(), StatusCode::OK | StatusCode::NOT_MODIFIED) {
+        response.headers_mut().insert(header::CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+    }
+    if response.status() != StatusCode::OK {
+        return response;
+    }
+    let Some(etag) = file_etag(response.headers()) else {
+        return response;
+    };
+    if if_none_match.as_ref().and_then(|v| v.to_str().ok()).is_some_and(|v| etag_matches(v, &etag)) {
+        let mut not_modified = Response::new(Body::empty());
+        *not_modified.status_mut() = StatusCode::NOT_MODIFIED;
+        for name in [header::CACHE_CONTROL, header::LAST_MODIFIED] {
+            if let Some(value) = response.headers().get(&name) {
+                not_modified.headers_mut().insert(name, value.clone());
+            }
+        }
+        not_modified.headers_mut().insert(header::ETAG, etag);
+        return not_modified;
+    }
+    response.headers_mut().insert(header::ETAG, etag);
+    response
+}
+
+/// Mark responses that carry no validators of their own as not cacheable
+pub(crate) async fn no_store_by_default(request: Request, next: Next) -> Response {
+    let mut response = next.run(request).await;
+    let headers = response.headers();
+    if !headers.contains_key(header::CACHE_CONTROL) && !headers.contains_key(header::ETAG) && !headers.contains_key(header::LAST_MODIFIED) {
+        response.headers_mut().insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    }
+    response
+}
+
+/// Weak ETag from a file response's `Last-Modified` and `Content-Length`
+fn file_etag(headers: &HeaderMap) -> Option<HeaderValue> {
+    let modified = headers.get(header::LAST_MODIFIED)?.to_str().ok()?;
+    let length = headers.get(header::CONTENT_LENGTH)?.to_str().ok()?;
+    let hash = crate::render_cache::key((modified, length));
+    HeaderValue::from_str(&format!("W/\"{:x}-{}\"", hash, length)).ok()
+}
+
+/// Weak comparison of an `If-None-Match` list against `etag`
+fn etag_matches(if_none_match: &str, etag: &HeaderValue) -> bool {
+    let bare = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    let etag = etag.to_str().map(bare).unwrap_or_default();
+    if_none_match.split(',').any(|tag| tag.trim() == "*" || bare(tag) == etag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::Router;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_static_files_revalidate_with_etag_and_deck_content_is_not_stored() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("runtime.js"), "export {}").unwrap();
+        let app = Router::new()
+            .nest_service("/dist", tower_http::services::ServeDir::new(dir.path()))
+            .layer(axum::middleware::from_fn(static_validators))
+            .route("/api/deck", axum::routing::get(|| async { "{}" }))
+            .layer(axum::middleware::from_fn(no_store_by_default));
+        let get = |uri: &str, etag: Option<&HeaderValue>| {
+            let mut request = Request::builder().uri(uri);
+            if let Some(etag) = etag {
+                request = request.header(header::IF_NONE_MATCH, etag);
+            }
+            app.clone().oneshot(request.body(Body::empty()).unwrap())
+        };
+
+        let first = get("/dist/runtime.js", None).await.unwrap();
+        assert_eq!(first.status(), StatusCode::OK);
+        assert_eq!(first.headers()[header::CACHE_CONTROL], "no-cache");
+        let etag = first.headers()[header::ETAG].clone();
+        assert!(etag.to_str().unwrap().starts_with("W/\""));
+
+        let second = get("/dist/runtime.js", Some(&etag)).await.unwrap();
+        assert_eq!(second.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(second.headers()[header::ETAG], etag);
+
+        std::fs::write(dir.path().join("runtime.js"), "export const changed = true;").unwrap();
+        assert_eq!(get("/dist/runtime.js", Some(&etag)).await.unwrap().status(), StatusCode::OK);
+
+        let deck = get("/api/deck", None).await.unwrap();
+        assert_eq!(deck.headers()[header::CACHE_CONTROL], "no-store");
+        assert!(!deck.headers().contains_key(header::ETAG));
+    }
+}
@@ -0,0 +1,390 @@
+/*!
+ * Collaborative editing of deck files over `/collab/<file>`
+ *
+ * Each open TOML file is held as an Automerge text document, so concurrent
+ * edits from several authors merge instead of overwriting each other. The
+ * merged text is written back to the file whenever it parses as TOML, and the
+ * file watcher reloads the deck from there as usual.
+ *
+ * Two kinds of peer can connect:
+ * - `?protocol=automerge`: binary Automerge sync messages, for clients that keep
+ *   their own replica (automerge-js, automerge-repo, another Rust process)
+ * - the default JSON protocol, for light tooling: the server sends the text and
+ *   its heads, and clients send splices made against those heads
+ */
+
+use automerge::sync::{self, SyncDoc};
+use automerge::transaction::Transactable;
+use automerge::{AutoCommit, ChangeHash, ObjId, ObjType, ReadDoc, TextEncoding, ROOT};
+use axum::extract::ws::{Message, WebSocket};
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+
+/// Wire format a peer speaks
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Protocol {
+    Json,
+    Automerge,
+}
+
+/// Message from a JSON peer
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum ClientMessage {
+    /// Delete `delete` UTF-16 code units at `index` and insert `insert`, in the
+    /// text as of `heads`
+    Splice {
+        heads: Vec<String>,
+        index: usize,
+        #[serde(default)]
+        delete: usize,
+        #[serde(default)]
+        insert: String,
+    },
+}
+
+struct Peer {
+    protocol: Protocol,
+    sync: sync::State,
+    tx: mpsc::UnboundedSender<Message>,
+}
+
+/// One deck file being edited
+struct SharedDoc {
+    path: PathBuf,
+    doc: AutoCommit,
+    text: ObjId,
+    peers: HashMap<u64, Peer>,
+    /// Content last read from or written to the file
+    on_disk: String,
+}
+
+impl SharedDoc {
+    fn open(path: &Path) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        // UTF-16 indices match JavaScript strings, which most editors work in
+        let mut doc = AutoCommit::new_with_encoding(TextEncoding::Utf16CodeUnit);
+        let text = doc.put_object(ROOT, "text", ObjType::Text)?;
+        doc.splice_text(&text, 0, 0, &content)?;
+        Ok(Self { path: path.to_path_buf(), doc, text, peers: HashMap::new(), on_disk: content })
+    }
+
+    fn text(&self) -> String {
+        self.doc.text(&self.text).unwrap_or_default()
+    }
+
+    fn snapshot(&mut self) -> Message {
+        let heads: Vec<String> = self.doc.get_heads().iter().map(ChangeHash::to_string).collect();
+        Message::Text(json!({ "type": "text", "heads": heads, "text": self.text() }).to_string())
+    }
+
+    /// Bring every peer up to date and write the merged text back to the file
+    fn broadcast(&mut self) {
+        let snapshot = self.snapshot();
+        for peer in self.peers.values_mut() {
+            match peer.protocol {
+                Protocol::Json => {
+                    let _ = peer.tx.send(snapshot.clone());
+                }
+                Protocol::Automerge => {
+                    while let Some(message) = self.doc.sync().generate_sync_message(&mut peer.sync) {
+                        let _ = peer.tx.send(Message::Binary(message.encode()));
+                    }
+                }
+            }
+        }
+        self.persist();
+    }
+
+    /// Write the text if it changed and is valid TOML; mid-edit states that do not
+    /// parse stay in memory until a later edit fixes them
+    fn persist(&mut self) {
+        let text = self.text();
+        if text == self.on_disk || toml::from_str::<toml::Value>(&text).is_err() {
+            return;
+        }
+        match std::fs::write(&self.path, &text) {
+            Ok(()) => self.on_disk = text,
+            Err(e) => tracing::warn!(path = %self.path.display(), error = %e, "could not save collaborative edit"),
+        }
+    }
+
+    fn apply_splice(&mut self, heads: &[String], index: usize, delete: usize, insert: &str) -> anyhow::Result<()> {
+        let heads = heads.iter().map(|head| head.parse::<ChangeHash>()).collect::<Result<Vec<_>, _>>()?;
+        let mut fork = self.doc.fork_at(&heads)?;
+        fork.splice_text(&self.text, index, delete as isize, insert)?;
+        self.doc.merge(&mut fork)?;
+        Ok(())
+    }
+
+    /// Fold an edit made to the file outside the session into the document, as
+    /// one splice over the changed middle of the text
+    fn apply_disk_change(&mut self, content: String) -> anyhow::Result<()> {
+        let current = self.text();
+        let old: Vec<u16> = current.encode_utf16().collect();
+        let new: Vec<u16> = content.encode_utf16().collect();
+        let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+        let suffix = old[prefix..].iter().rev().zip(new[prefix..].iter().rev()).take_while(|(a, b)| a == b).count();
+        let insert = String::from_utf16_lossy(&new[prefix..new.len() - suffix]);
+        self.doc.splice_text(&self.text, prefix, (old.len() - suffix - prefix) as isize, &insert)?;
+        self.on_disk = content;
+        Ok(())
+    }
+}
+
+/// Open collaborative documents, keyed by canonical file path
+#[derive(Default)]
+pub struct Collab {
+    docs: Mutex<HashMap<PathBuf, Arc<Mutex<SharedDoc>>>>,
+    next_peer: AtomicU64,
+}
+
+impl Collab {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn doc(&self, path: &Path) -> anyhow::Result<Arc<Mutex<SharedDoc>>> {
+        let mut docs = self.docs.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(doc) = docs.get(path) {
+            return Ok(doc.clone());
+        }
+        let doc = Arc::new(Mutex::new(SharedDoc::open(path)?));
+        docs.insert(path.to_path_buf(), doc.clone());
+        Ok(doc)
+    }
+
+    fn join(&self, path: &Path, protocol: Protocol) -> anyhow::Result<(u64, Arc<Mutex<SharedDoc>>, mpsc::UnboundedReceiver<Message>)> {
+        let shared = self.doc(path)?;
+        let id = self.next_peer.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut doc = shared.lock().unwrap_or_else(|e| e.into_inner());
+        let mut peer = Peer { protocol, sync: sync::State::new(), tx };
+        match protocol {
+            Protocol::Json => {
+                let _ = peer.tx.send(doc.snapshot());
+            }
+            Protocol::Automerge => {
+                if let Some(message) = doc.doc.sync().generate_sync_message(&mut peer.sync) {
+                    let _ = peer.tx.send(Message::Binary(message.encode()));
+                }
+            }
+        }
+        doc.peers.insert(id, peer);
+        drop(doc);
+        Ok((id, shared, rx))
+    }
+
+    fn leave(&self, path: &Path, id: u64) {
+        let mut docs = self.docs.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(shared) = docs.get(path) else {
+            return;
+        };
+        let mut doc = shared.lock().unwrap_or_else(|e| e.into_inner());
+        doc.peers.remove(&id);
+        // The file holds the merged result, so an idle document can be dropped
+        if doc.peers.is_empty() {
+            drop(doc);
+            docs.remove(path);
+        }
+    }
+
+    /// Called by the file watcher: merge edits made to an open file from outside
+    pub fn file_changed(&self, path: &Path) {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let Some(shared) = self.docs.lock().unwrap_or_else(|e| e.into_inner()).get(&path).cloned() else {
+            return;
+        };
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            return;
+        };
+        let mut doc = shared.lock().unwrap_or_else(|e| e.into_inner());
+        // Our own writes come back through the watcher too
+        if content == doc.on_disk {
+            return;
+        }
+        match doc.apply_disk_change(content) {
+            Ok(()) => doc.broadcast(),
+            Err(e) => tracing::warn!(path = %path.display(), error = %e, "could not merge outside edit"),
+        }
+    }
+
+    /// Number of peers editing `path`
+    pub fn peers(&self, path: &Path) -> usize {
+        let docs = self.docs.lock().unwrap_or_else(|e| e.into_inner());
+        docs.get(path).map_or(0, |doc| doc.lock().unwrap_or_else(|e| e.into_inner()).peers.len())
+    }
+}
+
+/// Run one peer's session until its socket closes
+pub async fn handle_connection(mut socket: WebSocket, collab: Arc<Collab>, path: PathBuf, protocol: Protocol) {
+    let (id, shared, mut outgoing) = match collab.join(&path, protocol) {
+        Ok(joined) => joined,
+        Err(e) => {
+            let error = json!({ "type": "error", "message": format!("Cannot open {}: {}", path.display(), e) });
+            let _ = socket.send(Message::Text(error.to_string())).await;
+            return;
+        }
+    };
+
+    loop {
+        tokio::select! {
+            incoming = socket.recv() => {
+                let result = match incoming {
+                    Some(Ok(Message::Text(text))) if protocol == Protocol::Json => {
+                        match serde_json::from_str::<ClientMessage>(&text) {
+                            Ok(ClientMessage::Splice { heads, index, delete, insert }) => {
+                                let mut doc = shared.lock().unwrap_or_else(|e| e.into_inner());
+                                doc.apply_splice(&heads, index, delete, &insert).map(|_| doc.broadcast())
+                            }
+                            Err(e) => Err(e.into()),
+                        }
+                    }
+                    Some(Ok(Message::Binary(bytes))) if protocol == Protocol::Automerge => {
+                        let mut doc = shared.lock().unwrap_or_else(|e| e.into_inner());
+                        let before = doc.doc.get_heads();
+                        let received = sync::Message::decode(&bytes).map_err(anyhow::Error::from).and_then(|message| {
+                            let doc = &mut *doc;
+                            let peer = doc.peers.get_mut(&id).expect("peer joined");
+                            Ok(doc.doc.sync().receive_sync_message(&mut peer.sync, message)?)
+                        });
+                        received.map(|_| {
+                            if doc.doc.get_heads() != before {
+                                doc.broadcast();
+                            } else {
+                                // Nothing new, but the sender may still be waiting on our state
+                                let doc = &mut *doc;
+                                let peer = doc.peers.get_mut(&id).expect("peer joined");
+                                while let Some(message) = doc.doc.sync().generate_sync_message(&mut peer.sync) {
+                                    let _ = peer.tx.send(Message::Binary(message.encode()));
+                                }
+                            }
+                        })
+                    }
+                    Some(Ok(Message::Ping(data))) => {
+                        let _ = socket.send(Message::Pong(data)).await;
+                        Ok(())
+                    }
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => Ok(()),
+                };
+                if let Err(e) = result {
+                    let error = json!({ "type": "error", "message": e.to_string() });
+                    if socket.send(Message::Text(error.to_string())).await.is_err() {
+                        break;
+                    }
+                }
+            }
+            message = outgoing.recv() => {
+                let Some(message) = message else { break };
+                if socket.send(message).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+
+    collab.leave(&path, id);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The most recent message waiting for a peer
+    fn latest(rx: &mut mpsc::UnboundedReceiver<Message>) -> Message {
+        let mut last = rx.try_recv().expect("a message");
+        while let Ok(message) = rx.try_recv() {
+            last = message;
+        }
+        last
+    }
+
+    fn heads(message: &Message) -> (Vec<String>, String) {
+        let Message::Text(text) = message else { panic!("expected a text snapshot") };
+        let value: serde_json::Value = serde_json::from_str(text).unwrap();
+        let heads = value["heads"].as_array().unwrap().iter().map(|h| h.as_str().unwrap().to_string()).collect();
+        (heads, value["text"].as_str().unwrap().to_string())
+    }
+
+    #[test]
+    fn test_concurrent_splices_merge_and_persist() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("intro.slide.toml");
+        std::fs::write(&path, "title = \"Hello\"\n").unwrap();
+        let collab = Collab::new();
+
+        let (alice, shared, mut alice_rx) = collab.join(&path, Protocol::Json).unwrap();
+        let (_bob, _, mut bob_rx) = collab.join(&path, Protocol::Json).unwrap();
+        let (base, text) = heads(&alice_rx.try_recv().unwrap());
+        assert_eq!(text, "title = \"Hello\"\n");
+        bob_rx.try_recv().unwrap();
+
+        // Both edit the same version: one appends to the title, the other adds a line
+        {
+            let mut doc = shared.lock().unwrap();
+            doc.apply_splice(&base, 14, 0, ", world").unwrap();
+            doc.broadcast();
+            doc.apply_splice(&base, 16, 0, "subtitle = \"Hi\"\n").unwrap();
+            doc.broadcast();
+        }
+        let merged = "title = \"Hello, world\"\nsubtitle = \"Hi\"\n";
+        assert_eq!(heads(&bob_rx.try_recv().unwrap()).1, "title = \"Hello, world\"\n");
+        assert_eq!(heads(&bob_rx.try_recv().unwrap()).1, merged);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), merged);
+
+        // Text that does not parse is kept in memory but not written
+        let current = heads(&latest(&mut alice_rx)).0;
+        shared.lock().unwrap().apply_splice(&current, 0, 0, "[broken").unwrap();
+        shared.lock().unwrap().broadcast();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), merged);
+
+        // An edit on disk is folded into the open document
+        std::fs::write(&path, "title = \"Edited on disk\"\n").unwrap();
+        collab.file_changed(&path);
+        assert_eq!(shared.lock().unwrap().text(), "title = \"Edited on disk\"\n");
+        assert_eq!(heads(&latest(&mut bob_rx)).1, "title = \"Edited on disk\"\n");
+
+        collab.leave(&path, alice);
+        assert_eq!(collab.peers(&path), 1);
+    }
+
+    #[test]
+    fn test_automerge_peers_sync_with_the_server() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("slides.toml");
+        std::fs::write(&path, "title = \"Deck\"\n").unwrap();
+        let collab = Collab::new();
+        let (id, shared, mut rx) = collab.join(&path, Protocol::Automerge).unwrap();
+
+        // A client replica syncs until both sides have nothing more to say
+        let mut replica = AutoCommit::new();
+        let mut state = sync::State::new();
+        let exchange = |replica: &mut AutoCommit, state: &mut sync::State, rx: &mut mpsc::UnboundedReceiver<Message>| {
+            for _ in 0..10 {
+                while let Ok(Message::Binary(bytes)) = rx.try_recv() {
+                    replica.sync().receive_sync_message(state, sync::Message::decode(&bytes).unwrap()).unwrap();
+                }
+                let Some(message) = replica.sync().generate_sync_message(state) else { break };
+                let mut doc = shared.lock().unwrap();
+                let doc = &mut *doc;
+                let peer = doc.peers.get_mut(&id).unwrap();
+                doc.doc.sync().receive_sync_message(&mut peer.sync, message).unwrap();
+                doc.broadcast();
+            }
+        };
+        exchange(&mut replica, &mut state, &mut rx);
+        let (_, text) = replica.get(ROOT, "text").unwrap().unwrap();
+        assert_eq!(replica.text(&text).unwrap(), "title = \"Deck\"\n");
+
+        replica.splice_text(&text, 0, 0, "# Draft\n").unwrap();
+        exchange(&mut replica, &mut state, &mut rx);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "# Draft\ntitle = \"Deck\"\n");
+    }
+}
@@ -0,0 +1,54 @@
+/*!
+ * Response compression for pages, deck JSON, and bundles
+ *
+ * Audience devices often join over a crowded conference network, where the
+ * runtime bundles and exported HTML are the slowest part of loading a deck.
+ * Text responses are compressed with brotli or gzip, whichever the browser
+ * accepts. Images, audio, video, and archives are already compressed and are
+ * sent as they are, as are event streams and responses under 32 bytes.
+ */
+
+use tower_http::compression::{
+    predicate::{DefaultPredicate, NotForContentType, Predicate},
+    CompressionLayer,
+};
+
+/// Compress text responses with brotli or gzip
+pub(crate) fn layer() -> CompressionLayer<impl Predicate> {
+    // `DefaultPredicate` already skips images other than SVG, event streams, and tiny bodies
+    let predicate = DefaultPredicate::new()
+        .and(NotForContentType::const_new("audio/"))
+        .and(NotForContentType::const_new("video/"))
+        .and(NotForContentType::const_new("font/woff"))
+        .and(NotForContentType::const_new("application/zip"))
+        .and(NotForContentType::const_new("application/gzip"))
+        .and(NotForContentType::const_new("application/pdf"));
+    CompressionLayer::new().compress_when(predicate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, extract::Request, http::header, routing::get, Router};
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_compresses_text_but_not_media() {
+        let app = Router::new()
+            .route("/runtime.js", get(|| async { ([(header::CONTENT_TYPE, "text/javascript")], "export const slides = [];\n".repeat(64)) }))
+            .route("/narration.mp3", get(|| async { ([(header::CONTENT_TYPE, "audio/mpeg")], vec![0u8; 4096]) }))
+            .layer(layer());
+        let get = |uri: &str, encoding: &str| {
+            let request = Request::builder().uri(uri).header(header::ACCEPT_ENCODING, encoding);
+            app.clone().oneshot(request.body(Body::empty()).unwrap())
+        };
+
+        let gzip = get("/runtime.js", "gzip").await.unwrap();
+        assert_eq!(gzip.headers()[header::CONTENT_ENCODING], "gzip");
+        let br = get("/runtime.js", "br").await.unwrap();
+        assert_eq!(br.headers()[header::CONTENT_ENCODING], "br");
+
+        let audio = get("/narration.mp3", "gzip, br").await.unwrap();
+        assert!(!audio.headers().contains_key(header::CONTENT_ENCODING));
+    }
+}
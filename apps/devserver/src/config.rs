@@ -0,0 +1,188 @@
+/*!
+ * Server settings a project commits next to its deck, in `coolslides.config.toml`
+ *
+ * Every setting has a default, so the file and each of its tables are
+ * optional. Command-line flags override what the file says.
+ */
+
+use crate::cors::CorsConfig;
+use coolslides_core::SanitizationPolicy;
+use regex::Regex;
+use serde::Deserialize;
+use std::{collections::BTreeMap, path::{Path, PathBuf}};
+
+pub const CONFIG_FILE: &str = "coolslides.config.toml";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct ServerConfig {
+    /// Address to bind to (`--host`); `127.0.0.1` when neither sets it
+    pub host: Option<String>,
+    /// Port to listen on (`--port`); `5173` when neither sets it
+    pub port: Option<u16>,
+    /// Strict HTML sanitization (`--strict`)
+    pub strict: bool,
+    /// Listen on every interface and serve `/join` for audience devices (`--lan`)
+    pub lan: bool,
+    pub cors: CorsConfig,
+    /// Extra directories to serve, by URL prefix, relative to the deck (`[assets]`)
+    pub assets: BTreeMap<String, PathBuf>,
+    /// Sanitizer additions for every deck served, beneath each deck's own `[sanitization]`
+    pub sanitization: SanitizationPolicy,
+    /// Directory to keep rooms in across restarts, relative to the deck (`--state-dir`);
+    /// `--db` takes precedence
+    pub state_dir: Option<PathBuf>,
+    /// Which file changes reload the deck (`[watch]`)
+    pub watch: WatchConfig,
+    /// Client cap and slow-client handling for every room (`[rooms]`)
+    pub rooms: crate::rooms::RoomLimits,
+    /// Presenter token from `--presenter-token`; never read from the file, which is committed
+    #[serde(skip)]
+    pub presenter_token: Option<String>,
+    /// Redis URL from `--redis`, to share rooms with other servers; not read
+    /// from the file either, since it may hold a password
+    #[serde(skip)]
+    pub redis_url: Option<String>,
+}
+
+impl ServerConfig {
+    /// Read `coolslides.config.toml` from `deck_dir`; the defaults when there is none
+    pub fn load(deck_dir: &Path) -> anyhow::Result<Self> {
+        let path = deck_dir.join(CONFIG_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path)?;
+        let config: Self = toml::from_str(&content).map_err(|e| anyhow::anyhow!("{}: {}", path.display(), e))?;
+        config.validate().map_err(|e| anyhow::anyhow!("{}: {}", path.display(), e))?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        self.cors.validate()?;
+        for prefix in self.assets.keys() {
+            crate::assets::validate_prefix(prefix)?;
+        }
+        self.watch.matcher()?;
+        self.rooms.validate()?;
+        if self.redis_url.is_some() && !cfg!(feature = "redis") {
+            anyhow::bail!("--redis needs a build with the `redis` feature");
+        }
+        Ok(())
+    }
+}
+
+/// `[watch]`: globs relative to the deck directory, with `*`, `?`, and `**`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct WatchConfig {
+    /// Files that reload the deck page besides the deck's own, e.g. `data/*.csv`
+    pub include: Vec<String>,
+    /// Files whose changes are ignored, e.g. `drafts/**`
+    pub ignore: Vec<String>,
+}
+
+impl WatchConfig {
+    pub(crate) fn matcher(&self) -> anyhow::Result<WatchMatcher> {
+        Ok(WatchMatcher { include: glob_set(&self.include)?, ignore: glob_set(&self.ignore)? })
+    }
+}
+
+/// Compiled `[watch]` globs
+pub(crate) struct WatchMatcher {
+    include: Option<Regex>,
+    ignore: Option<Regex>,
+}
+
+impl WatchMatcher {
+    /// Whether a change to `path`, relative to the deck directory, reloads pages
+    /// even though it is not a deck file
+    pub(crate) fn includes(&self, path: &Path) -> bool {
+        matches(&self.include, path)
+    }
+
+    pub(crate) fn ignores(&self, path: &Path) -> bool {
+        matches(&self.ignore, path)
+    }
+}
+
+fn matches(set: &Option<Regex>, path: &Path) -> bool {
+    let path = path.to_string_lossy().replace('\\', "/");
+    set.as_ref().is_some_and(|set| set.is_match(&path))
+}
+
+/// One regex matching any of `globs`
+fn glob_set(globs: &[String]) -> anyhow::Result<Option<Regex>> {
+    if globs.is_empty() {
+        return Ok(None);
+    }
+    let mut alternatives = Vec::new();
+    for glob in globs {
+        if glob.starts_with('/') || glob.split('/').any(|segment| segment == "..") {
+            anyhow::bail!("watch glob {:?} must be relative to the deck directory", glob);
+        }
+        let mut pattern = String::new();
+        let mut chars = glob.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '*' if chars.peek() == Some(&'*') => {
+                    chars.next();
+                    // `**/` also matches no directories at all
+                    if chars.peek() == Some(&'/') {
+                        chars.next();
+                        pattern.push_str("(?:.*/)?");
+                    } else {
+                        pattern.push_str(".*");
+                    }
+                }
+                '*' => pattern.push_str("[^/]*"),
+                '?' => pattern.push_str("[^/]"),
+                c => pattern.push_str(&regex::escape(&c.to_string())),
+            }
+        }
+        alternatives.push(pattern);
+    }
+    Ok(Some(Regex::new(&format!("^(?:{})$", alternatives.join("|")))?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cors::CorsMode;
+
+    #[test]
+    fn test_missing_file_is_default_and_errors_name_the_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        assert_eq!(ServerConfig::load(dir.path()).unwrap(), ServerConfig::default());
+
+        std::fs::write(dir.path().join(CONFIG_FILE), "[cors]\nmode = \"locked\"\nallowedOrigins = [\"https://a.example\"]\n").unwrap();
+        let config = ServerConfig::load(dir.path()).unwrap();
+        assert_eq!(config.cors.mode, CorsMode::Locked);
+        assert_eq!(config.cors.allowed_origins, ["https://a.example"]);
+
+        std::fs::write(dir.path().join(CONFIG_FILE), "[cors]\nallowedOrigin = [\"https://a.example\"]\n").unwrap();
+        let error = ServerConfig::load(dir.path()).unwrap_err().to_string();
+        assert!(error.contains(CONFIG_FILE) && error.contains("allowedOrigin"), "{}", error);
+    }
+
+    #[test]
+    fn test_server_settings_and_watch_globs() {
+        let config: ServerConfig = toml::from_str(
+            "host = \"0.0.0.0\"\nport = 8080\nstrict = true\nstateDir = \".coolslides/state\"\n\n[sanitization]\nallowTags = [\"kbd\"]\n\n[watch]\ninclude = [\"data/*.csv\", \"shared/**/*.svg\"]\nignore = [\"drafts/**\"]\n",
+        )
+        .unwrap();
+        assert_eq!((config.host.as_deref(), config.port, config.strict), (Some("0.0.0.0"), Some(8080), true));
+        assert_eq!(config.state_dir, Some(PathBuf::from(".coolslides/state")));
+        assert_eq!(config.sanitization.rules.allow_tags, ["kbd"]);
+
+        let watch = config.watch.matcher().unwrap();
+        assert!(watch.includes(Path::new("data/sales.csv")));
+        assert!(!watch.includes(Path::new("data/2024/sales.csv")));
+        assert!(watch.includes(Path::new("shared/logo.svg")) && watch.includes(Path::new("shared/a/b/logo.svg")));
+        assert!(watch.ignores(Path::new("drafts/intro.slide.toml")));
+        assert!(!watch.ignores(Path::new("content/intro.slide.toml")));
+
+        let outside = WatchConfig { include: vec!["../other/*.csv".to_string()], ignore: vec![] };
+        assert!(outside.matcher().is_err());
+    }
+}
@@ -0,0 +1,147 @@
+/*!
+ * Cross-origin policy for the dev server's API and rooms
+ *
+ * `permissive` (the default) lets any page call the API, which suits a server
+ * on localhost. `locked` only answers the listed origins, and also refuses
+ * WebSocket upgrades and write requests from any other origin, since browsers
+ * do not apply CORS to those. Use it when the server is reachable on a network.
+ */
+
+use crate::AppState;
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use serde::Deserialize;
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, CorsLayer};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CorsMode {
+    #[default]
+    Permissive,
+    Locked,
+}
+
+impl std::str::FromStr for CorsMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "permissive" => Ok(CorsMode::Permissive),
+            "locked" => Ok(CorsMode::Locked),
+            other => Err(anyhow::anyhow!("unknown CORS mode '{}' (expected permissive or locked)", other)),
+        }
+    }
+}
+
+/// The `[cors]` table of `coolslides.config.toml`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct CorsConfig {
+    pub mode: CorsMode,
+    /// Origins besides the server's own, e.g. `https://slides.example.com`
+    pub allowed_origins: Vec<String>,
+    /// Methods cross-origin pages may use; empty allows the ones the API has
+    pub allowed_methods: Vec<String>,
+    /// Let cross-origin pages send cookies and `Authorization`
+    pub allow_credentials: bool,
+}
+
+const DEFAULT_METHODS: [Method; 6] = [Method::GET, Method::HEAD, Method::POST, Method::PUT, Method::PATCH, Method::DELETE];
+
+impl CorsConfig {
+    /// Check origins and methods, so building the layer cannot fail
+    pub fn validate(&self) -> anyhow::Result<()> {
+        for origin in &self.allowed_origins {
+            if origin == "*" {
+                anyhow::bail!("'*' is not an allowed origin; use the permissive CORS mode instead");
+            }
+            let valid = origin.split_once("://").is_some_and(|(scheme, host)| {
+                matches!(scheme, "http" | "https") && !host.is_empty() && !host.contains('/')
+            });
+            if !valid || HeaderValue::from_str(origin).is_err() {
+                anyhow::bail!("invalid CORS origin '{}' (expected e.g. https://example.com)", origin);
+            }
+        }
+        for method in &self.allowed_methods {
+            method.to_ascii_uppercase().parse::<Method>().map_err(|_| anyhow::anyhow!("invalid CORS method '{}'", method))?;
+        }
+        Ok(())
+    }
+
+    pub fn layer(&self) -> CorsLayer {
+        let methods: Vec<Method> = if self.allowed_methods.is_empty() {
+            DEFAULT_METHODS.to_vec()
+        } else {
+            self.allowed_methods.iter().filter_map(|m| m.to_ascii_uppercase().parse().ok()).collect()
+        };
+        match self.mode {
+            CorsMode::Permissive if self.allow_credentials => {
+                // Credentials rule out `*`, so echo the caller's origin instead
+                CorsLayer::very_permissive().allow_methods(methods)
+            }
+            CorsMode::Permissive if self.allowed_methods.is_empty() => CorsLayer::permissive(),
+            CorsMode::Permissive => CorsLayer::permissive().allow_methods(AllowMethods::list(methods)),
+            CorsMode::Locked => {
+                let origins = self.allowed_origins.iter().filter_map(|o| HeaderValue::from_str(o).ok());
+                CorsLayer::new()
+                    .allow_origin(AllowOrigin::list(origins))
+                    .allow_methods(methods)
+                    .allow_headers(AllowHeaders::mirror_request())
+                    .allow_credentials(self.allow_credentials)
+            }
+        }
+    }
+
+    /// Whether a request from `origin` to the server at `host` may go ahead
+    fn allows(&self, origin: &str, host: Option<&str>) -> bool {
+        self.mode == CorsMode::Permissive
+            || origin.split_once("://").is_some_and(|(_, origin_host)| Some(origin_host) == host)
+            || self.allowed_origins.iter().any(|allowed| allowed == origin)
+    }
+}
+
+/// In locked mode, refuse WebSocket upgrades and writes from origins that are not allowed
+pub(crate) async fn require_allowed_origin(State(state): State<AppState>, request: Request, next: Next) -> Result<Response, StatusCode> {
+    let headers = request.headers();
+    let guarded = headers.contains_key(header::UPGRADE)
+        || !matches!(*request.method(), Method::GET | Method::HEAD | Method::OPTIONS);
+    if let Some(origin) = headers.get(header::ORIGIN).and_then(|o| o.to_str().ok()) {
+        let host = headers.get(header::HOST).and_then(|h| h.to_str().ok());
+        if guarded && !state.cors.allows(origin, host) {
+            return Err(StatusCode::FORBIDDEN);
+        }
+    }
+    Ok(next.run(request).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locked_mode_only_allows_listed_and_same_origins() {
+        let config: CorsConfig = toml::from_str(
+            "mode = \"locked\"\nallowedOrigins = [\"https://slides.example.com\"]\nallowedMethods = [\"get\", \"POST\"]\nallowCredentials = true\n",
+        )
+        .unwrap();
+        config.validate().unwrap();
+        assert!(config.allows("https://slides.example.com", Some("10.0.0.5:5173")));
+        assert!(config.allows("http://10.0.0.5:5173", Some("10.0.0.5:5173")));
+        assert!(!config.allows("https://evil.example", Some("10.0.0.5:5173")));
+        assert!(CorsConfig::default().allows("https://evil.example", None));
+        // Building the layer must not panic for any validated combination
+        let _ = config.layer();
+        let _ = CorsConfig { allow_credentials: true, ..Default::default() }.layer();
+
+        for origins in [vec!["*"], vec!["slides.example.com"], vec!["https://example.com/path"]] {
+            let config = CorsConfig { allowed_origins: origins.iter().map(|o| o.to_string()).collect(), ..config.clone() };
+            assert!(config.validate().is_err(), "{:?}", origins);
+        }
+        assert!(CorsConfig { allowed_methods: vec!["NOT A METHOD".into()], ..Default::default() }.validate().is_err());
+        assert!("open".parse::<CorsMode>().is_err());
+    }
+}
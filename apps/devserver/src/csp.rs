@@ -0,0 +1,136 @@
+/*!
+ * Content-Security-Policy for every HTML page the server sends
+ *
+ * Deck pages build their own policy from the components they use (see
+ * `page_policy`) and hand it over as a response extension. Other pages, such
+ * as the presenter console or a component preview, get the default: the
+ * server's own origin plus the origins of the deck's import map. Either way
+ * the layer generates a fresh nonce per response, stamps it on every inline
+ * `<script>`, the JSON props blocks included, and allows it in `script-src`.
+ *
+ * Exported HTML is a file that cannot carry headers or fresh nonces. It gets
+ * the strict profile in a meta tag instead, with inline scripts allowed by
+ * hash.
+ */
+
+use crate::{import_map_json, read_import_map, read_lockfile, AppState};
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use coolslides_core::policy::SecurityPolicy;
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// Pages larger than this are passed through without a nonce
+const MAX_PAGE_BYTES: usize = 32 * 1024 * 1024;
+
+/// Set the CSP of HTML responses, nonce included
+pub(crate) async fn content_security_policy(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let mut response = next.run(request).await;
+    let is_html = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("text/html"));
+    // Downloads are exports, which carry their own policy
+    if !is_html || response.headers().contains_key(header::CONTENT_DISPOSITION) {
+        return response;
+    }
+    let policy = match response.extensions_mut().remove::<SecurityPolicy>() {
+        Some(policy) => policy,
+        None => default_policy(&state).await,
+    };
+
+    let (mut parts, body) = response.into_parts();
+    let Ok(page) = axum::body::to_bytes(body, MAX_PAGE_BYTES).await else {
+        return Response::builder().status(StatusCode::INTERNAL_SERVER_ERROR).body(Body::empty()).unwrap();
+    };
+    let nonce = uuid::Uuid::new_v4().simple().to_string();
+    let page = stamp_nonce(&String::from_utf8_lossy(&page), &nonce);
+    let mut policy = policy;
+    policy.add_nonce(&nonce);
+    if let Ok(value) = HeaderValue::from_str(&policy.csp()) {
+        parts.headers.insert(header::CONTENT_SECURITY_POLICY, value);
+    }
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(page))
+}
+
+/// The server's own origin plus the deck's import-map and plugin origins
+async fn default_policy(state: &AppState) -> SecurityPolicy {
+    let mut policy = SecurityPolicy::default();
+    let deck_root = state.deck_root.read().await.clone();
+    let import_map = deck_root.as_deref().and_then(read_import_map);
+    let lockfile = deck_root.as_deref().and_then(read_lockfile);
+    let components = state.components.read().await;
+    let mapped = import_map_json(components.as_ref(), import_map.as_ref(), lockfile.as_ref());
+    for url in mapped["imports"].as_object().into_iter().flat_map(|imports| imports.values()) {
+        policy.add_module(url.as_str().unwrap_or_default());
+    }
+    if let Some(deck) = state.deck.read().await.as_ref() {
+        for plugin in &deck.plugins {
+            policy.add_module(plugin);
+        }
+    }
+    policy
+}
+
+/// Add `nonce` to every `<script>` tag that has none
+fn stamp_nonce(html: &str, nonce: &str) -> String {
+    static SCRIPT: OnceLock<Regex> = OnceLock::new();
+    let script = SCRIPT.get_or_init(|| Regex::new(r"<script(\s[^>]*)?>").unwrap());
+    script
+        .replace_all(html, |captures: &regex::Captures| {
+            let attributes = captures.get(1).map_or("", |m| m.as_str());
+            if attributes.contains("nonce=") {
+                captures[0].to_string()
+            } else {
+                format!("<script nonce=\"{}\"{}>", nonce, attributes)
+            }
+        })
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{response::Html, routing::get, Router};
+    use tower::ServiceExt;
+
+    #[test]
+    fn test_every_script_gets_the_nonce_once() {
+        let html = r#"<script type="importmap">{}</script><script>go()</script><script type="application/json" data-props="a">{}</script><script nonce="x">k()</script><scripted>"#;
+        let stamped = stamp_nonce(html, "n1");
+        assert!(stamped.contains(r#"<script nonce="n1" type="importmap">"#));
+        assert!(stamped.contains(r#"<script nonce="n1">go()"#));
+        assert!(stamped.contains(r#"<script nonce="n1" type="application/json" data-props="a">"#));
+        assert!(stamped.contains(r#"<script nonce="x">k()"#));
+        assert!(stamped.contains("<scripted>"));
+    }
+
+    #[tokio::test]
+    async fn test_pages_get_a_fresh_nonce_and_other_responses_are_untouched() {
+        let state = AppState::new();
+        let app = Router::new()
+            .route("/page", get(|| async { Html("<script>go()</script>") }))
+            .route("/api", get(|| async { "<script>go()</script>" }))
+            .layer(axum::middleware::from_fn_with_state(state.clone(), content_security_policy))
+            .with_state(state);
+        let get = |uri: &str| app.clone().oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap());
+
+        let page = get("/page").await.unwrap();
+        let csp = page.headers()[header::CONTENT_SECURITY_POLICY].to_str().unwrap().to_string();
+        let body = String::from_utf8(axum::body::to_bytes(page.into_body(), usize::MAX).await.unwrap().to_vec()).unwrap();
+        let nonce = &body["<script nonce=\"".len()..body.find("\">").unwrap()];
+        assert!(csp.contains(&format!("'nonce-{}'", nonce)), "{}", csp);
+        assert!(csp.contains("default-src 'self'"), "{}", csp);
+
+        let second = get("/page").await.unwrap();
+        assert_ne!(second.headers()[header::CONTENT_SECURITY_POLICY].to_str().unwrap(), csp);
+        assert!(!get("/api").await.unwrap().headers().contains_key(header::CONTENT_SECURITY_POLICY));
+    }
+}
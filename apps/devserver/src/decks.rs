@@ -0,0 +1,287 @@
+/*!
+ * Serving several deck directories from one dev server
+ *
+ * `coolslides dev --dir talks/a --dir talks/b` loads each directory into its
+ * own [`AppState`], with its own rooms, file watcher, and webhooks, and serves
+ * it under `/decks/<name>/` the way the hub does. `<name>` comes from the
+ * directory name. `/` lists the decks, and each deck page gets a switcher for
+ * moving between them.
+ */
+
+use crate::{config, create_router, git, html_escape, hub, static_files, webhooks, AppState};
+use axum::{
+    body::Body,
+    extract::{Path as AxumPath, Request, State},
+    http::{header, StatusCode},
+    response::{Html, IntoResponse, Response},
+    routing::{any, get},
+    Router,
+};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tower::ServiceExt;
+use tower_http::trace::TraceLayer;
+
+/// Largest deck page the switcher is added to
+const MAX_PAGE_BYTES: usize = 64 * 1024 * 1024;
+
+/// The loaded decks, in the order they were given
+#[derive(Clone)]
+pub struct Decks {
+    decks: Arc<Vec<HostedDeck>>,
+}
+
+struct HostedDeck {
+    name: String,
+    state: AppState,
+    router: Router,
+}
+
+impl Decks {
+    /// Serve each `(name, state)` under `/decks/<name>`; the states should be
+    /// mounted there with [`AppState::with_mount_path`]
+    pub fn new(decks: Vec<(String, AppState)>) -> Self {
+        let decks = decks
+            .into_iter()
+            .map(|(name, state)| HostedDeck { name, router: create_router(state.clone()), state })
+            .collect();
+        Self { decks: Arc::new(decks) }
+    }
+
+    fn get(&self, name: &str) -> Option<&HostedDeck> {
+        self.decks.iter().find(|deck| deck.name == name)
+    }
+
+    /// `(name, title)` of every deck; the name stands in while a deck does not load
+    async fn titles(&self) -> Vec<(String, String)> {
+        let mut titles = Vec::new();
+        for deck in self.decks.iter() {
+            let title = deck.state.deck.read().await.as_ref().map(|d| d.title.clone());
+            titles.push((deck.name.clone(), title.unwrap_or_else(|| deck.name.clone())));
+        }
+        titles
+    }
+}
+
+/// URL names for deck directories: the lowercased directory name with other
+/// characters replaced by `-`, and a number added when two would be the same
+pub fn deck_names(dirs: &[impl AsRef<Path>]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    dirs.iter()
+        .map(|dir| {
+            let dir = dir.as_ref();
+            let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+            let base: String = dir
+                .file_name()
+                .map(|name| name.to_string_lossy().to_lowercase())
+                .unwrap_or_default()
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '-' | '_') { c } else { '-' })
+                .collect();
+            let base = match base.trim_matches('-') {
+                "" => "deck".to_string(),
+                trimmed => trimmed.to_string(),
+            };
+            let mut name = base.clone();
+            let mut n = 2;
+            while !seen.insert(name.clone()) {
+                name = format!("{}-{}", base, n);
+                n += 1;
+            }
+            name
+        })
+        .collect()
+}
+
+/// Router for several decks: the landing page, and each deck under `/decks/<name>/`
+pub fn create_decks_router(decks: Decks) -> Router {
+    let routes = Router::new()
+        .route("/", get(index))
+        .route("/decks/:name", get(hub::deck_root_redirect))
+        .route("/decks/:name/", any(deck_request_root))
+        .route("/decks/:name/*rest", any(deck_request));
+    static_files(routes).layer(TraceLayer::new_for_http()).with_state(decks)
+}
+
+/// Start a dev server for several deck directories. `config` supplies CORS and
+/// the presenter token for every deck; `[assets]`, `[sanitization]`, `[watch]`,
+/// and `strict` come from each deck's own `coolslides.config.toml`.
+pub async fn start_decks_server(
+    host: &str,
+    port: u16,
+    deck_dirs: &[String],
+    strict_mode: bool,
+    config: config::ServerConfig,
+) -> anyhow::Result<()> {
+    let presenter_token = config.presenter_token.clone().unwrap_or_else(hub::new_token);
+    let mut decks = Vec::new();
+    for (name, dir) in deck_names(deck_dirs).into_iter().zip(deck_dirs) {
+        let own = config::ServerConfig::load(Path::new(dir))?;
+        let state = AppState::new_with_strict_mode(strict_mode || own.strict)
+            .with_cors(config.cors.clone())
+            .with_presenter_token(presenter_token.clone())
+            .with_asset_mounts(own.assets)
+            .with_sanitization(own.sanitization)
+            .with_watch(own.watch)
+            .with_room_limits(config.rooms)
+            .with_mount_path(format!("/decks/{}", name));
+        if let Err(e) = state.load_from_directory(dir).await {
+            tracing::warn!(deck = %name, dir = %dir, error = %e, "could not load deck");
+        }
+        if let Err(e) = state.start_file_watcher(dir).await {
+            tracing::warn!(deck = %name, error = %e, "could not start file watcher");
+        }
+        git::spawn_head_watcher(state.clone(), PathBuf::from(dir));
+        webhooks::spawn_dispatcher(state.clone());
+        decks.push((name, state));
+    }
+
+    let listener = tokio::net::TcpListener::bind(format!("{}:{}", host, port)).await?;
+    println!("Coolslides dev server running on http://{}:{}", host, port);
+    for ((name, _), dir) in decks.iter().zip(deck_dirs) {
+        println!("  /decks/{}/ ← {}", name, dir);
+    }
+    println!("Presenter token: {} (open /decks/<name>/presenter?token=<token>)", presenter_token);
+    axum::serve(listener, create_decks_router(Decks::new(decks))).await?;
+    Ok(())
+}
+
+/// The decks, with links to each one's slides, presenter console, and audience view
+async fn index(State(decks): State<Decks>) -> Html<String> {
+    let mut items = String::new();
+    for (name, title) in decks.titles().await {
+        let slides = match decks.get(&name) {
+            Some(deck) => deck.state.slides.read().await.len(),
+            None => 0,
+        };
+        items.push_str(&format!(
+            "<li><a href=\"/decks/{name}/\">{title}</a> <small>{slides} slides · <a href=\"/decks/{name}/presenter\">presenter</a> · <a href=\"/decks/{name}/audience\">audience</a></small></li>",
+            name = name,
+            title = html_escape(&title),
+            slides = slides,
+        ));
+    }
+    Html(format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<title>Coolslides</title>\n<meta charset=\"utf-8\">\n<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n<style>body {{ font: 18px/1.6 system-ui, sans-serif; max-width: 40rem; margin: 2rem auto; padding: 0 1rem; }} small {{ color: #666; }}</style>\n</head>\n<body>\n<h1>Presentations</h1>\n<ul>{}</ul>\n</body>\n</html>",
+        items
+    ))
+}
+
+async fn deck_request_root(State(decks): State<Decks>, AxumPath(name): AxumPath<String>, mut request: Request) -> Response {
+    // The page is rewritten below, so ask the deck for it uncompressed
+    request.headers_mut().remove(header::ACCEPT_ENCODING);
+    let response = forward(&decks, &name, "", request).await;
+    if !is_html(&response) {
+        return response;
+    }
+    let (mut parts, body) = response.into_parts();
+    let Ok(page) = axum::body::to_bytes(body, MAX_PAGE_BYTES).await else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    let page = with_switcher(&String::from_utf8_lossy(&page), &name, &decks.titles().await);
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(page))
+}
+
+async fn deck_request(State(decks): State<Decks>, AxumPath((name, rest)): AxumPath<(String, String)>, request: Request) -> Response {
+    forward(&decks, &name, &rest, request).await
+}
+
+/// Hand the request to the deck's own router with the `/decks/<name>` prefix removed
+async fn forward(decks: &Decks, name: &str, rest: &str, mut request: Request) -> Response {
+    let Some(deck) = decks.get(name) else {
+        return (StatusCode::NOT_FOUND, "No such deck").into_response();
+    };
+    let path_and_query = match request.uri().query() {
+        Some(query) => format!("/{}?{}", rest, query),
+        None => format!("/{}", rest),
+    };
+    *request.uri_mut() = match path_and_query.parse() {
+        Ok(uri) => uri,
+        Err(_) => return StatusCode::BAD_REQUEST.into_response(),
+    };
+    deck.router.clone().oneshot(request).await.unwrap_or_else(|never| match never {})
+}
+
+fn is_html(response: &Response) -> bool {
+    response.status() == StatusCode::OK
+        && response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("text/html"))
+}
+
+/// Styles for the deck switcher. It is plain links in a `<details>`, since the
+/// page's CSP only allows the inline scripts it was rendered with.
+const SWITCHER_STYLE: &str = "<style>#coolslides-deck-switcher { position: fixed; top: 8px; right: 8px; z-index: 2147483646; opacity: .15; font: 13px/1.6 system-ui, sans-serif; background: #222; color: #eee; border-radius: 6px; padding: 4px 10px; } #coolslides-deck-switcher:hover, #coolslides-deck-switcher[open] { opacity: 1; } #coolslides-deck-switcher summary { cursor: pointer; } #coolslides-deck-switcher a { display: block; color: inherit; } #coolslides-deck-switcher a[aria-current] { font-weight: 600; }</style>";
+
+/// Add a menu of the decks to the top corner of a deck page. It stays faint
+/// until hovered, so it does not show on the projector.
+fn with_switcher(page: &str, current: &str, decks: &[(String, String)]) -> String {
+    let links: String = decks
+        .iter()
+        .map(|(name, title)| {
+            let marker = if name == current { " aria-current=\"page\"" } else { "" };
+            format!("<a href=\"/decks/{}/\"{}>{}</a>", name, marker, html_escape(title))
+        })
+        .collect();
+    let switcher = format!(
+        "{}<details id=\"coolslides-deck-switcher\"><summary>Decks</summary>{}</details>\n",
+        SWITCHER_STYLE, links
+    );
+    match page.rfind("</body>") {
+        Some(end) => format!("{}{}{}", &page[..end], switcher, &page[end..]),
+        None => format!("{}{}", page, switcher),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deck_names_are_url_safe_and_unique() {
+        let names = deck_names(&["talks/Intro Talk", "other/intro-talk", "a/intro-talk", "/"]);
+        assert_eq!(names, ["intro-talk", "intro-talk-2", "intro-talk-3", "deck"]);
+    }
+
+    #[tokio::test]
+    async fn test_decks_are_served_under_their_names_with_a_switcher() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut decks = Vec::new();
+        for (name, title) in [("a", "First"), ("b", "Second")] {
+            let root = dir.path().join(name);
+            std::fs::create_dir_all(root.join("content")).unwrap();
+            std::fs::write(
+                root.join("slides.toml"),
+                format!("modelVersion = \"1.0\"\ntitle = \"{}\"\ntheme = \"theme.css\"\nsequence = []\n\n[transitions]\ndefault = \"none\"\n", title),
+            )
+            .unwrap();
+            let state = AppState::new().with_mount_path(format!("/decks/{}", name));
+            state.load_from_directory(&root).await.unwrap();
+            decks.push((name.to_string(), state));
+        }
+        let app = create_decks_router(Decks::new(decks));
+        let get = |uri: &str| app.clone().oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap());
+        let text = |response: Response| async move { String::from_utf8(axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap().to_vec()).unwrap() };
+
+        let index = text(get("/").await.unwrap()).await;
+        assert!(index.contains("<a href=\"/decks/a/\">First</a>") && index.contains("<a href=\"/decks/b/\">Second</a>"), "{}", index);
+
+        let deck = get("/decks/b/api/deck").await.unwrap();
+        assert!(text(deck).await.contains("Second"));
+
+        // Asked for compressed, the page still comes back as HTML the switcher was added to
+        let request = Request::builder().uri("/decks/a/").header(header::ACCEPT_ENCODING, "gzip").body(Body::empty()).unwrap();
+        let page = text(app.clone().oneshot(request).await.unwrap()).await;
+        assert!(page.contains("id=\"coolslides-deck-switcher\""));
+        assert!(page.contains("<a href=\"/decks/a/\" aria-current=\"page\">First</a><a href=\"/decks/b/\">Second</a>"));
+
+        assert_eq!(get("/decks/c/").await.unwrap().status(), StatusCode::NOT_FOUND);
+        assert_eq!(get("/decks/a").await.unwrap().status(), StatusCode::PERMANENT_REDIRECT);
+    }
+}
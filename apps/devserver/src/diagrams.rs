@@ -0,0 +1,223 @@
+/*!
+ * Server-side diagrams: ```mermaid and ```dot fences rendered to inline SVG
+ *
+ * Rendering shells out to Graphviz (`dot`) and mermaid-cli (`mmdc`), the way PDF
+ * export shells out to Chromium. When a tool is missing or fails, the fence stays
+ * a code block.
+ */
+
+use crate::render_cache::{self, RenderCache};
+use pulldown_cmark::{CodeBlockKind, Event, Tag};
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+
+/// Rendered SVG, keyed by language and source
+static SVG: RenderCache = RenderCache::new();
+
+/// Marks where a rendered diagram goes; private-use characters survive sanitization
+/// untouched, so `strip_placeholders` removes any the author wrote
+const PLACEHOLDER: char = '\u{E000}';
+
+/// Remove placeholder characters from author markdown, so only `extract` can create them
+pub(crate) fn strip_placeholders(markdown: &str) -> std::borrow::Cow<'_, str> {
+    if markdown.contains(PLACEHOLDER) {
+        std::borrow::Cow::Owned(markdown.replace(PLACEHOLDER, ""))
+    } else {
+        std::borrow::Cow::Borrowed(markdown)
+    }
+}
+
+/// Replace diagram fences in a markdown event stream with placeholders, returning the
+/// events and the SVG for each placeholder
+pub(crate) fn extract<'a>(events: impl Iterator<Item = Event<'a>>) -> (Vec<Event<'a>>, Vec<(String, String)>) {
+    let mut output = Vec::new();
+    let mut diagrams = Vec::new();
+    let mut events = events.peekable();
+    while let Some(event) = events.next() {
+        let language = match &event {
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))) => diagram_language(info),
+            _ => None,
+        };
+        let Some(language) = language else {
+            output.push(event);
+            continue;
+        };
+
+        let mut block = vec![event];
+        let mut source = String::new();
+        for event in events.by_ref() {
+            let end = matches!(event, Event::End(Tag::CodeBlock(_)));
+            if let Event::Text(text) = &event {
+                source.push_str(text);
+            }
+            block.push(event);
+            if end {
+                break;
+            }
+        }
+        match render(language, &source) {
+            Some(svg) => {
+                output.push(Event::Html(format!("{0}diagram:{1}{0}", PLACEHOLDER, diagrams.len()).into()));
+                diagrams.push((language.to_string(), svg));
+            }
+            None => output.extend(block),
+        }
+    }
+    (output, diagrams)
+}
+
+/// Put the rendered diagrams into sanitized HTML
+pub(crate) fn insert(html: &str, diagrams: &[(String, String)]) -> String {
+    diagrams.iter().enumerate().fold(html.to_string(), |html, (i, (language, svg))| {
+        html.replace(
+            &format!("{0}diagram:{1}{0}", PLACEHOLDER, i),
+            &format!(r#"<div class="coolslides-diagram" data-diagram="{}">{}</div>"#, language, svg),
+        )
+    })
+}
+
+/// `mermaid` or `dot` for a fence's info string
+fn diagram_language(info: &str) -> Option<&'static str> {
+    match info.split_whitespace().next()? {
+        "mermaid" => Some("mermaid"),
+        "dot" | "graphviz" => Some("dot"),
+        _ => None,
+    }
+}
+
+fn render(language: &str, source: &str) -> Option<String> {
+    let key = render_cache::key((language, source));
+    if let Some(svg) = SVG.get(key) {
+        return Some(svg);
+    }
+    let result = match language {
+        "dot" => run_dot(source),
+        _ => run_mermaid(source),
+    };
+    match result {
+        Ok(svg) => {
+            let svg = clean_svg(&svg);
+            SVG.insert(key, &svg);
+            Some(svg)
+        }
+        Err(e) => {
+            warn_once(language, &e);
+            None
+        }
+    }
+}
+
+fn run_dot(source: &str) -> anyhow::Result<String> {
+    use std::io::Write;
+    let mut child = Command::new("dot")
+        .arg("-Tsvg")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    child.stdin.take().expect("piped stdin").write_all(source.as_bytes())?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        anyhow::bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+fn run_mermaid(source: &str) -> anyhow::Result<String> {
+    let dir = tempfile::TempDir::new()?;
+    let (input, output_path) = (dir.path().join("diagram.mmd"), dir.path().join("diagram.svg"));
+    std::fs::write(&input, source)?;
+    // Labels as SVG text; the HTML labels mermaid draws in `foreignObject` are not kept
+    let config = dir.path().join("config.json");
+    std::fs::write(&config, r#"{"htmlLabels":false,"flowchart":{"htmlLabels":false}}"#)?;
+    let output = Command::new("mmdc")
+        .arg("--input")
+        .arg(&input)
+        .arg("--configFile")
+        .arg(&config)
+        .arg("--output")
+        .arg(&output_path)
+        .args(["--backgroundColor", "transparent", "--quiet"])
+        .output()?;
+    if !output.status.success() {
+        anyhow::bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(std::fs::read_to_string(&output_path)?)
+}
+
+/// Report a failing renderer once per tool, not once per slide render
+fn warn_once(language: &str, error: &anyhow::Error) {
+    static DOT: OnceLock<()> = OnceLock::new();
+    static MERMAID: OnceLock<()> = OnceLock::new();
+    let (warned, tool) = match language {
+        "dot" => (&DOT, "Graphviz `dot`"),
+        _ => (&MERMAID, "mermaid-cli `mmdc`"),
+    };
+    if warned.set(()).is_ok() {
+        tracing::warn!(language, tool, error = %error, "diagrams are shown as code");
+    }
+}
+
+/// SVG elements a rendered diagram may keep. Nothing that links, embeds, animates,
+/// or carries script or CSS: `a`, `use`, `image`, `foreignObject`, `animate`, `set`,
+/// `script`, and `style` are all dropped.
+const SVG_TAGS: &[&str] = &[
+    "svg", "g", "defs", "title", "desc", "path", "rect", "circle", "ellipse", "line", "polyline", "polygon",
+    "text", "tspan", "marker", "linearGradient", "radialGradient", "stop", "clipPath",
+];
+
+/// Geometry and presentation attributes; no `href`, `style`, or event handlers
+const SVG_ATTRIBUTES: &[&str] = &[
+    "id", "class", "role", "aria-label", "aria-roledescription", "transform", "viewBox", "preserveAspectRatio",
+    "width", "height", "x", "y", "x1", "y1", "x2", "y2", "cx", "cy", "r", "rx", "ry", "dx", "dy", "d", "points",
+    "fill", "fill-opacity", "fill-rule", "stroke", "stroke-width", "stroke-dasharray", "stroke-linecap",
+    "stroke-linejoin", "stroke-miterlimit", "stroke-opacity", "opacity", "clip-path", "font-family", "font-size",
+    "font-weight", "font-style", "text-anchor", "dominant-baseline", "alignment-baseline", "markerWidth",
+    "markerHeight", "markerUnits", "refX", "refY", "orient", "marker-start", "marker-mid", "marker-end", "offset",
+    "stop-color", "stop-opacity", "gradientUnits",
+];
+
+/// Reduce tool output to an allowlist of SVG elements and attributes so it can be
+/// inlined after the page's own sanitization
+fn clean_svg(svg: &str) -> String {
+    static CLEANER: OnceLock<ammonia::Builder<'static>> = OnceLock::new();
+    let cleaner = CLEANER.get_or_init(|| {
+        let mut builder = ammonia::Builder::empty();
+        builder
+            .tags(SVG_TAGS.iter().copied().collect())
+            .generic_attributes(SVG_ATTRIBUTES.iter().copied().collect())
+            .clean_content_tags(["script", "style", "foreignObject"].into_iter().collect())
+            .url_schemes(Default::default())
+            .url_relative(ammonia::UrlRelative::Deny)
+            .strip_comments(true)
+            .link_rel(None);
+        builder
+    });
+    cleaner.clean(svg).to_string().trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_svg_and_placeholders() {
+        let svg = "<?xml version=\"1.0\"?>\n<!DOCTYPE svg>\n<!-- g -->\n<svg onload=\"x()\" viewBox=\"0 0 8 8\"><script>alert(1)</script><a href=\"javascript:go()\"><text>A</text></a></svg>";
+        assert_eq!(clean_svg(svg), "<svg viewBox=\"0 0 8 8\"><text>A</text></svg>");
+
+        let svg = concat!(
+            "<svg><rect onload=alert(1) fill=\"red\"></rect>",
+            "<foreignObject><iframe src=\"javascript:x()\"></iframe></foreignObject><embed src=\"x.swf\">",
+            "<a xlink:href=\"javascript:x()\"><use href=\"data:image/svg+xml,x\"></use></a>",
+            "<animate attributeName=\"href\" values=\"javascript:x()\"></animate><style>p{}</style></svg>",
+        );
+        assert_eq!(clean_svg(svg), "<svg><rect fill=\"red\"></rect></svg>");
+        assert_eq!(strip_placeholders("a\u{E000}diagram:0\u{E000}"), "adiagram:0");
+
+        let html = format!("<p>before</p>{0}diagram:0{0}", PLACEHOLDER);
+        let inserted = insert(&html, &[("dot".to_string(), "<svg></svg>".to_string())]);
+        assert_eq!(inserted, r#"<p>before</p><div class="coolslides-diagram" data-diagram="dot"><svg></svg></div>"#);
+        assert_eq!(diagram_language("graphviz {.wide}"), Some("dot"));
+        assert_eq!(diagram_language("rust"), None);
+    }
+}
@@ -0,0 +1,430 @@
+/*!
+ * Write-back editing of deck files through the running server
+ *
+ * GUI editors and scripts change slides over the API instead of editing TOML.
+ * Edits need the presenter token. A slide is validated against the component
+ * registry before anything is written, then saved over the file it came from
+ * (`content/<id>.slide.toml` for a new slide) through a temporary file. New
+ * speaker notes replace only the `notes` of the slide's file, and a new
+ * sequence only the `sequence` key of `slides.toml`, keeping the rest of each
+ * file as written. The in-memory deck is updated at once; the
+ * file watcher then picks up the write and sends connected pages the usual
+ * hot-reload events.
+ */
+
+use crate::{AppState, TokenQuery};
+use axum::{
+    extract::{Path as AxumPath, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use coolslides_core::{validation, DeckItem, SlideDoc, SpeakerNote};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug)]
+pub(crate) enum EditError {
+    Unauthorized,
+    NotFound,
+    BadRequest(String),
+    /// The edit does not validate; the diagnostics say why
+    Invalid(validation::ValidationResult),
+    Internal(String),
+}
+
+impl EditError {
+    fn internal(e: impl std::fmt::Display) -> Self {
+        EditError::Internal(e.to_string())
+    }
+}
+
+impl IntoResponse for EditError {
+    fn into_response(self) -> Response {
+        let (status, body) = match self {
+            EditError::Unauthorized => (StatusCode::UNAUTHORIZED, serde_json::json!({ "error": "Presenter token required" })),
+            EditError::NotFound => (StatusCode::NOT_FOUND, serde_json::json!({ "error": "No such slide" })),
+            EditError::BadRequest(message) => (StatusCode::BAD_REQUEST, serde_json::json!({ "error": message })),
+            EditError::Invalid(result) => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                serde_json::json!({
+                    "error": "Edit does not validate",
+                    "errors": diagnostics(&result.errors),
+                    "warnings": diagnostics(&result.warnings),
+                }),
+            ),
+            EditError::Internal(message) => (StatusCode::INTERNAL_SERVER_ERROR, serde_json::json!({ "error": message })),
+        };
+        (status, Json(body)).into_response()
+    }
+}
+
+fn diagnostics(errors: &[validation::ValidationError]) -> Vec<serde_json::Value> {
+    errors.iter().map(|e| serde_json::json!({ "code": e.code(), "message": e.message() })).collect()
+}
+
+/// Slide ids become file names, so they are kept to a safe alphabet
+fn valid_slide_id(id: &str) -> bool {
+    !id.is_empty()
+        && !id.starts_with(['-', '.'])
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+pub(crate) fn authorize(state: &AppState, headers: &HeaderMap, query: TokenQuery) -> Result<(), EditError> {
+    if state.presenter_authorized(headers, query.token) {
+        Ok(())
+    } else {
+        Err(EditError::Unauthorized)
+    }
+}
+
+pub(crate) async fn deck_root(state: &AppState) -> Result<PathBuf, EditError> {
+    state.deck_root.read().await.clone().ok_or(EditError::NotFound)
+}
+
+/// Write via a temporary file, so readers and the watcher never see a partial file
+pub(crate) async fn write_atomic(path: &Path, content: &str) -> Result<(), EditError> {
+    let file_name = path.file_name().and_then(|name| name.to_str()).ok_or_else(|| EditError::internal("bad path"))?;
+    let temp = path.with_file_name(format!(".{}.tmp", file_name));
+    tokio::fs::write(&temp, content).await.map_err(EditError::internal)?;
+    tokio::fs::rename(&temp, path).await.map_err(EditError::internal)
+}
+
+/// The file slide `id` was loaded from, or `content/<id>.slide.toml` for a new slide
+async fn slide_path(state: &AppState, root: &Path, id: &str) -> Result<PathBuf, EditError> {
+    match state.slide_file(id).await {
+        Some(path) => Ok(path),
+        None => {
+            let content_dir = root.join("content");
+            tokio::fs::create_dir_all(&content_dir).await.map_err(EditError::internal)?;
+            Ok(content_dir.join(format!("{}.slide.toml", id)))
+        }
+    }
+}
+
+/// Replace or create a slide, writing it back to its TOML file
+pub(crate) async fn put_slide(
+    State(state): State<AppState>,
+    AxumPath(id): AxumPath<String>,
+    Query(query): Query<TokenQuery>,
+    headers: HeaderMap,
+    Json(slide): Json<SlideDoc>,
+) -> Result<Json<SlideDoc>, EditError> {
+    authorize(&state, &headers, query)?;
+    if slide.id != id {
+        return Err(EditError::BadRequest(format!("body is slide '{}', not '{}'", slide.id, id)));
+    }
+    if !valid_slide_id(&id) {
+        return Err(EditError::BadRequest(format!("'{}' is not a usable slide id", id)));
+    }
+    let root = deck_root(&state).await?;
+
+    let result = {
+        let registry = state.components.read().await;
+        validation::validate_slide_with_registry(&slide, registry.as_ref())
+    };
+    if !result.is_valid() {
+        return Err(EditError::Invalid(result));
+    }
+    let content = toml::to_string_pretty(&slide).map_err(|e| EditError::BadRequest(format!("cannot write slide as TOML: {}", e)))?;
+
+    let path = slide_path(&state, &root, &id).await?;
+    write_atomic(&path, &content).await?;
+    state.slides.write().await.insert(id, slide.clone());
+    state.revalidate().await;
+    Ok(Json(slide))
+}
+
+/// Remove a slide and its file. References to it in `slides.toml` are left for
+/// the author to fix; validation reports them.
+pub(crate) async fn delete_slide(
+    State(state): State<AppState>,
+    AxumPath(id): AxumPath<String>,
+    Query(query): Query<TokenQuery>,
+    headers: HeaderMap,
+) -> Result<StatusCode, EditError> {
+    authorize(&state, &headers, query)?;
+    if !state.slides.read().await.contains_key(&id) {
+        return Err(EditError::NotFound);
+    }
+    if let Some(path) = state.slide_file(&id).await {
+        tokio::fs::remove_file(&path).await.map_err(EditError::internal)?;
+    }
+    state.slides.write().await.remove(&id);
+    state.revalidate().await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// A slide's speaker notes
+pub(crate) async fn get_notes(State(state): State<AppState>, AxumPath(id): AxumPath<String>) -> Result<Json<Vec<SpeakerNote>>, EditError> {
+    let slides = state.slides.read().await;
+    let slide = slides.get(&id).ok_or(EditError::NotFound)?;
+    Ok(Json(slide.notes.clone()))
+}
+
+/// Replace a slide's speaker notes, rewriting only `[[notes]]` in its file
+pub(crate) async fn put_notes(
+    State(state): State<AppState>,
+    AxumPath(id): AxumPath<String>,
+    Query(query): Query<TokenQuery>,
+    headers: HeaderMap,
+    Json(notes): Json<Vec<SpeakerNote>>,
+) -> Result<Json<Vec<SpeakerNote>>, EditError> {
+    authorize(&state, &headers, query)?;
+    let root = deck_root(&state).await?;
+    let mut slide = state.slides.read().await.get(&id).cloned().ok_or(EditError::NotFound)?;
+    slide.notes = notes.clone();
+
+    let path = slide_path(&state, &root, &id).await?;
+    let content = tokio::fs::read_to_string(&path).await.map_err(EditError::internal)?;
+    let mut document: toml_edit::DocumentMut = content.parse().map_err(EditError::internal)?;
+    if notes.is_empty() {
+        document.remove("notes");
+    } else {
+        // Serialize through `toml` so the notes are written the way serde reads them
+        let written = toml::to_string(&serde_json::json!({ "notes": notes })).map_err(|e| EditError::BadRequest(format!("cannot write notes as TOML: {}", e)))?;
+        let written: toml_edit::DocumentMut = written.parse().map_err(EditError::internal)?;
+        let item = written.get("notes").cloned().ok_or_else(|| EditError::internal("notes were not written"))?;
+        document["notes"] = item;
+    }
+    write_atomic(&path, &document.to_string()).await?;
+
+    state.slides.write().await.insert(id, slide);
+    state.revalidate().await;
+    Ok(Json(notes))
+}
+
+#[derive(serde::Deserialize)]
+pub(crate) struct SequenceRequest {
+    sequence: Vec<DeckItem>,
+}
+
+/// Reorder the deck: check every slide the new sequence names exists, then
+/// write it to `slides.toml`
+pub(crate) async fn patch_sequence(
+    State(state): State<AppState>,
+    Query(query): Query<TokenQuery>,
+    headers: HeaderMap,
+    Json(request): Json<SequenceRequest>,
+) -> Result<Json<Vec<DeckItem>>, EditError> {
+    authorize(&state, &headers, query)?;
+    let root = deck_root(&state).await?;
+    if state.deck.read().await.is_none() {
+        return Err(EditError::NotFound);
+    }
+
+    let mut result = validation::ValidationResult::new();
+    {
+        let slides = state.slides.read().await;
+        for item in &request.sequence {
+            let ids = match item {
+                DeckItem::Ref { slide_id } => std::slice::from_ref(slide_id),
+                DeckItem::Group { name, slides, .. } => {
+                    if name.trim().is_empty() {
+                        return Err(EditError::BadRequest("groups need a name".to_string()));
+                    }
+                    slides.as_slice()
+                }
+            };
+            for id in ids.iter().filter(|id| !slides.contains_key(*id)) {
+                result.add_error(validation::ValidationError::UnknownSlideReference { id: id.clone() });
+            }
+        }
+    }
+    if !result.is_valid() {
+        return Err(EditError::Invalid(result));
+    }
+
+    let path = root.join("slides.toml");
+    let content = tokio::fs::read_to_string(&path).await.map_err(EditError::internal)?;
+    let mut document: toml_edit::DocumentMut = content.parse().map_err(EditError::internal)?;
+    let inline = document.get("sequence").is_some_and(|item| item.is_array());
+    document["sequence"] = sequence_item(&request.sequence, inline);
+    write_atomic(&path, &document.to_string()).await?;
+
+    if let Some(deck) = state.deck.write().await.as_mut() {
+        deck.sequence = request.sequence.clone();
+    }
+    state.revalidate().await;
+    Ok(Json(request.sequence))
+}
+
+/// `sequence` in the form the file already used: an inline array (slide ids as
+/// strings) or `[[sequence]]` tables
+fn sequence_item(sequence: &[DeckItem], inline: bool) -> toml_edit::Item {
+    use toml_edit::{value, Array, ArrayOfTables, InlineTable, Item, Table, Value};
+
+    let group_slides = |slides: &[String]| slides.iter().map(String::as_str).collect::<Array>();
+    if inline {
+        let mut array = Array::new();
+        for item in sequence {
+            match item {
+                DeckItem::Ref { slide_id } => array.push(slide_id.as_str()),
+                DeckItem::Group { name, transition, slides } => {
+                    let mut table = InlineTable::new();
+                    table.insert("type", "group".into());
+                    table.insert("name", name.as_str().into());
+                    if let Some(transition) = transition {
+                        table.insert("transition", transition.as_str().into());
+                    }
+                    table.insert("slides", Value::Array(group_slides(slides)));
+                    array.push(table);
+                }
+            }
+        }
+        array.set_trailing_comma(true);
+        for element in array.iter_mut() {
+            element.decor_mut().set_prefix("\n  ");
+        }
+        array.set_trailing("\n");
+        return value(array);
+    }
+
+    let mut tables = ArrayOfTables::new();
+    for item in sequence {
+        let mut table = Table::new();
+        match item {
+            DeckItem::Ref { slide_id } => {
+                table["type"] = value("ref");
+                table["ref"] = value(slide_id.as_str());
+            }
+            DeckItem::Group { name, transition, slides } => {
+                table["type"] = value("group");
+                table["name"] = value(name.as_str());
+                if let Some(transition) = transition {
+                    table["transition"] = value(transition.as_str());
+                }
+                table["slides"] = value(group_slides(slides));
+            }
+        }
+        tables.push(table);
+    }
+    Item::ArrayOfTables(tables)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_put_and_delete_write_back_slide_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("content")).unwrap();
+        std::fs::write(dir.path().join("slides.toml"), "modelVersion = \"1.0\"\ntitle = \"T\"\ntheme = \"theme.css\"\nsequence = []\n\n[transitions]\ndefault = \"none\"\n").unwrap();
+        std::fs::write(
+            dir.path().join("content").join("opening.slide.toml"),
+            "modelVersion = \"1.0\"\nid = \"intro\"\n\n[component]\nname = \"TitleSlide\"\nversionReq = \"^1\"\n\n[props]\ntitle = \"Old\"\n",
+        )
+        .unwrap();
+        let state = AppState::new().with_presenter_token("s3cret");
+        state.load_from_directory(dir.path()).await.unwrap();
+        let token = || Query(TokenQuery { token: Some("s3cret".to_string()) });
+        let slide = |id: &str, title: &str| -> SlideDoc {
+            serde_json::from_value(serde_json::json!({
+                "modelVersion": "1.0",
+                "id": id,
+                "component": { "name": "TitleSlide", "versionReq": "^1" },
+                "props": { "title": title },
+            }))
+            .unwrap()
+        };
+
+        let anonymous = Query(TokenQuery { token: None });
+        let denied = put_slide(State(state.clone()), AxumPath("intro".into()), anonymous, HeaderMap::new(), Json(slide("intro", "New"))).await;
+        assert!(matches!(denied, Err(EditError::Unauthorized)));
+        let mismatched = put_slide(State(state.clone()), AxumPath("intro".into()), token(), HeaderMap::new(), Json(slide("other", "New"))).await;
+        assert!(matches!(mismatched, Err(EditError::BadRequest(_))));
+        let unsafe_id = put_slide(State(state.clone()), AxumPath("../x".into()), token(), HeaderMap::new(), Json(slide("../x", "New"))).await;
+        assert!(matches!(unsafe_id, Err(EditError::BadRequest(_))));
+
+        // An existing slide is saved over the file it came from
+        let Json(saved) = put_slide(State(state.clone()), AxumPath("intro".into()), token(), HeaderMap::new(), Json(slide("intro", "New"))).await.expect("saved");
+        assert_eq!((saved.id.as_str(), &saved.props["title"]), ("intro", &serde_json::json!("New")));
+        let written: SlideDoc = toml::from_str(&std::fs::read_to_string(dir.path().join("content/opening.slide.toml")).unwrap()).unwrap();
+        assert_eq!(written.props["title"], "New");
+        assert_eq!(state.slides.read().await["intro"].props["title"], "New");
+
+        // A new slide gets content/<id>.slide.toml
+        let Json(created) = put_slide(State(state.clone()), AxumPath("outro".into()), token(), HeaderMap::new(), Json(slide("outro", "Bye"))).await.expect("created");
+        assert_eq!(created.id, "outro");
+        assert!(dir.path().join("content/outro.slide.toml").exists());
+
+        let deleted = delete_slide(State(state.clone()), AxumPath("intro".into()), token(), HeaderMap::new()).await.ok().unwrap();
+        assert_eq!(deleted, StatusCode::NO_CONTENT);
+        assert!(!dir.path().join("content/opening.slide.toml").exists());
+        assert!(!state.slides.read().await.contains_key("intro"));
+        let missing = delete_slide(State(state.clone()), AxumPath("intro".into()), token(), HeaderMap::new()).await;
+        assert!(matches!(missing, Err(EditError::NotFound)));
+    }
+
+    #[tokio::test]
+    async fn test_put_notes_keeps_the_rest_of_the_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("content")).unwrap();
+        std::fs::write(dir.path().join("slides.toml"), "modelVersion = \"1.0\"\ntitle = \"T\"\ntheme = \"theme.css\"\nsequence = [\"intro\"]\n\n[transitions]\ndefault = \"none\"\n").unwrap();
+        let file = dir.path().join("content").join("intro.slide.toml");
+        std::fs::write(
+            &file,
+            "modelVersion = \"1.0\"\nid = \"intro\"\n\n[component]\nname = \"TitleSlide\"\nversionReq = \"^1\"\n\n# Keep it short\n[props]\ntitle = \"Hi\"\n\n[[notes]]\ncontent = \"Old\"\n",
+        )
+        .unwrap();
+        let state = AppState::new().with_presenter_token("s3cret");
+        state.load_from_directory(dir.path()).await.unwrap();
+        let Json(notes) = get_notes(State(state.clone()), AxumPath("intro".into())).await.ok().unwrap();
+        assert_eq!(notes[0].content, "Old");
+
+        let notes: Vec<SpeakerNote> = serde_json::from_value(serde_json::json!([
+            { "content": "Welcome everyone" },
+            { "content": "Mention the demo", "timestamp": "1:30", "noteType": "timing" },
+        ]))
+        .unwrap();
+        let put = |token: Option<&str>, notes: Vec<SpeakerNote>| {
+            let query = Query(TokenQuery { token: token.map(str::to_string) });
+            put_notes(State(state.clone()), AxumPath("intro".into()), query, HeaderMap::new(), Json(notes))
+        };
+        assert!(matches!(put(None, notes.clone()).await, Err(EditError::Unauthorized)));
+        let Json(saved) = put(Some("s3cret"), notes).await.expect("saved");
+        assert_eq!(saved.iter().map(|note| note.content.as_str()).collect::<Vec<_>>(), ["Welcome everyone", "Mention the demo"]);
+
+        let written = std::fs::read_to_string(&file).unwrap();
+        assert!(written.contains("# Keep it short"), "{}", written);
+        let slide: SlideDoc = toml::from_str(&written).unwrap();
+        assert_eq!(slide.notes.len(), 2);
+        assert_eq!(slide.notes[1].offset_seconds(), Some(90.0));
+        assert_eq!(state.slides.read().await["intro"].notes.len(), 2);
+
+        let Json(cleared) = put(Some("s3cret"), Vec::new()).await.expect("cleared");
+        assert!(cleared.is_empty());
+        let slide: SlideDoc = toml::from_str(&std::fs::read_to_string(&file).unwrap()).unwrap();
+        assert!(slide.notes.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_patch_sequence_rewrites_only_the_sequence() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("content")).unwrap();
+        let manifest = "# My talk\nmodelVersion = \"1.0\"\ntitle = \"T\"\ntheme = \"theme.css\"\nsequence = [\"a\", \"b\"]\n\n[transitions]\ndefault = \"none\"\n";
+        std::fs::write(dir.path().join("slides.toml"), manifest).unwrap();
+        for id in ["a", "b", "c"] {
+            let slide = format!("modelVersion = \"1.0\"\nid = \"{}\"\n\n[component]\nname = \"TitleSlide\"\nversionReq = \"^1\"\n\n[props]\ntitle = \"{}\"\n", id, id);
+            std::fs::write(dir.path().join("content").join(format!("{}.slide.toml", id)), slide).unwrap();
+        }
+        let state = AppState::new();
+        state.load_from_directory(dir.path()).await.unwrap();
+        let patch = |sequence: serde_json::Value| {
+            let request: SequenceRequest = serde_json::from_value(serde_json::json!({ "sequence": sequence })).unwrap();
+            patch_sequence(State(state.clone()), Query(TokenQuery { token: None }), HeaderMap::new(), Json(request))
+        };
+
+        let unknown = patch(serde_json::json!(["b", "zzz"])).await;
+        let Err(EditError::Invalid(result)) = unknown else { panic!("unknown slide accepted") };
+        assert_eq!(result.errors.len(), 1);
+
+        let Json(sequence) = patch(serde_json::json!(["c", { "type": "group", "name": "rest", "slides": ["b", "a"] }])).await.expect("patched");
+        assert_eq!(sequence.len(), 2);
+        assert!(matches!(&sequence[1], DeckItem::Group { name, slides, .. } if name == "rest" && slides == &["b", "a"]));
+        let written = std::fs::read_to_string(dir.path().join("slides.toml")).unwrap();
+        assert!(written.starts_with("# My talk\n"), "{}", written);
+        let deck: coolslides_core::DeckManifest = toml::from_str(&written).unwrap();
+        assert_eq!(deck.slide_order(), ["c", "b", "a"]);
+        assert_eq!(state.deck.read().await.as_ref().unwrap().slide_order(), ["c", "b", "a"]);
+    }
+}
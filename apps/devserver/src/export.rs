@@ -1,8 +1,9 @@
-/**
- * PDF Export functionality using headless Chromium
+/*!
+ * PDF and video export using headless Chromium (and ffmpeg for video)
  */
 
-use coolslides_core::DeckManifest;
+use coolslides_core::{ComponentRegistry, DeckItem, DeckManifest, SlideDoc};
+use std::collections::HashMap;
 use std::path::Path;
 use serde::{Deserialize, Serialize};
 use std::process::Command;
@@ -328,6 +329,167 @@ pub fn check_browser_availability() -> Result<String> {
     exporter.find_browser_path()
 }
 
+/// Settings for a narrated video export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VideoConfig {
+    pub width: u32,
+    pub height: u32,
+    /// Seconds on a slide without narration
+    pub slide_duration: f64,
+    /// Render budget per slide, in milliseconds
+    pub timeout: u64,
+}
+
+/// One slide's stretch of the video
+#[derive(Debug, Clone, PartialEq)]
+pub struct VideoSegment {
+    pub slide_id: String,
+    pub duration: f64,
+    /// Narration file or URL, muxed in as the segment's audio
+    pub audio: Option<String>,
+}
+
+/// Segments in presentation order; narrated slides last as long as their narration
+pub fn video_segments(
+    deck: &DeckManifest,
+    slides: &HashMap<String, SlideDoc>,
+    slide_duration: f64,
+    base_dir: &Path,
+) -> Vec<VideoSegment> {
+    deck.slide_order()
+        .into_iter()
+        .filter_map(|id| slides.get(id))
+        .map(|slide| {
+            let narration = slide.narration.as_ref().filter(|n| n.duration > 0.0);
+            VideoSegment {
+                slide_id: slide.id.clone(),
+                duration: narration.map_or(slide_duration, |n| n.duration),
+                audio: narration.map(|n| match n.is_remote() {
+                    true => n.src.clone(),
+                    false => base_dir.join(n.src.trim_start_matches('/')).to_string_lossy().into_owned(),
+                }),
+            }
+        })
+        .collect()
+}
+
+/// ffmpeg arguments encoding a still image and its narration (or silence) into one segment.
+/// Every segment gets the same codecs and rates so they can be concatenated without re-encoding.
+fn segment_args(image: &Path, segment: &VideoSegment, config: &VideoConfig, output: &Path) -> Vec<String> {
+    let mut args: Vec<String> = ["-y", "-loglevel", "error", "-loop", "1", "-framerate", "30", "-i"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+    args.push(image.to_string_lossy().into_owned());
+    match &segment.audio {
+        Some(audio) => args.extend(["-i".to_string(), audio.clone()]),
+        None => args.extend(["-f", "lavfi", "-i", "anullsrc=channel_layout=stereo:sample_rate=48000"].map(String::from)),
+    }
+    args.extend([
+        "-t".to_string(),
+        format!("{:.3}", segment.duration),
+        "-vf".to_string(),
+        format!(
+            "scale={w}:{h}:force_original_aspect_ratio=decrease,pad={w}:{h}:(ow-iw)/2:(oh-ih)/2,format=yuv420p",
+            w = config.width,
+            h = config.height
+        ),
+    ]);
+    args.extend(
+        ["-c:v", "libx264", "-tune", "stillimage", "-r", "30", "-c:a", "aac", "-b:a", "192k", "-ar", "48000", "-ac", "2"]
+            .map(String::from),
+    );
+    // Narration shorter than the slide is padded with silence rather than ending it early
+    if segment.audio.is_some() {
+        args.extend(["-af".to_string(), "apad".to_string()]);
+    }
+    args.push(output.to_string_lossy().into_owned());
+    args
+}
+
+/// Standalone page showing one slide filling the window, for stills and thumbnails
+pub(crate) fn single_slide_html(
+    deck: &DeckManifest,
+    slides: &HashMap<String, SlideDoc>,
+    components: Option<&ComponentRegistry>,
+    slide_id: &str,
+    base_dir: &Path,
+) -> Result<String> {
+    let mut single = deck.clone();
+    single.sequence = vec![DeckItem::Ref { slide_id: slide_id.to_string() }];
+    let slide_html = crate::render_slides_html(&single, slides, components, &crate::SanitizationConfig::new(false).for_deck(deck))?;
+    Ok(PDFExporter::new()?
+        .generate_export_html(deck, &slide_html, &ExportProfile::Archival, Some(base_dir))?
+        .replace("</head>", "<style>.coolslides-slide { min-height: 100vh !important; }</style>\n</head>"))
+}
+
+/// Screenshot a page from `single_slide_html` at `size` with headless Chromium
+pub(crate) fn screenshot_slide(browser: &str, html_path: &Path, image_path: &Path, size: (u32, u32), timeout: u64) -> Result<()> {
+    let output = Command::new(browser)
+        .args(["--headless", "--no-sandbox", "--disable-gpu", "--disable-dev-shm-usage", "--hide-scrollbars"])
+        .arg(format!("--window-size={},{}", size.0, size.1))
+        .arg(format!("--virtual-time-budget={}", timeout))
+        .arg(format!("--screenshot={}", image_path.to_string_lossy()))
+        .arg(format!("file://{}", html_path.to_string_lossy()))
+        .output()?;
+    if !output.status.success() || !image_path.exists() {
+        return Err(anyhow!("{}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(())
+}
+
+/// Render each slide to a still with headless Chromium and encode the deck as an MP4,
+/// one segment per slide with its narration as the soundtrack. Needs `ffmpeg` on the PATH.
+pub async fn export_deck_to_video(
+    deck: &DeckManifest,
+    slides: &HashMap<String, SlideDoc>,
+    components: Option<&ComponentRegistry>,
+    config: &VideoConfig,
+    base_dir: &Path,
+) -> Result<Vec<u8>> {
+    let exporter = PDFExporter::new()?;
+    let browser_path = exporter.find_browser_path()?;
+    match Command::new("ffmpeg").arg("-version").output() {
+        Ok(output) if output.status.success() => {}
+        _ => return Err(anyhow!("ffmpeg not found. Please install ffmpeg to export video.")),
+    }
+
+    let segments = video_segments(deck, slides, config.slide_duration, base_dir);
+    if segments.is_empty() {
+        return Err(anyhow!("The deck has no slides to export"));
+    }
+    let work = exporter.temp_dir.path();
+    let mut concat_list = String::new();
+    for (i, segment) in segments.iter().enumerate() {
+        let html_path = work.join(format!("slide-{}.html", i));
+        let image_path = work.join(format!("slide-{}.png", i));
+        std::fs::write(&html_path, single_slide_html(deck, slides, components, &segment.slide_id, base_dir)?)?;
+        screenshot_slide(&browser_path, &html_path, &image_path, (config.width, config.height), config.timeout)
+            .map_err(|e| anyhow!("Rendering slide {} failed: {}", segment.slide_id, e))?;
+
+        let segment_path = work.join(format!("segment-{}.mp4", i));
+        let output = Command::new("ffmpeg").args(segment_args(&image_path, segment, config, &segment_path)).output()?;
+        if !output.status.success() {
+            return Err(anyhow!("Encoding slide {} failed: {}", segment.slide_id, String::from_utf8_lossy(&output.stderr)));
+        }
+        concat_list.push_str(&format!("file '{}'\n", segment_path.to_string_lossy().replace('\'', "'\\''")));
+    }
+
+    let list_path = work.join("segments.txt");
+    let video_path = work.join("output.mp4");
+    std::fs::write(&list_path, concat_list)?;
+    let output = Command::new("ffmpeg")
+        .args(["-y", "-loglevel", "error", "-f", "concat", "-safe", "0", "-i"])
+        .arg(&list_path)
+        .args(["-c", "copy", "-movflags", "+faststart"])
+        .arg(&video_path)
+        .output()?;
+    if !output.status.success() {
+        return Err(anyhow!("Joining video segments failed: {}", String::from_utf8_lossy(&output.stderr)));
+    }
+    Ok(std::fs::read(&video_path)?)
+}
+
 fn read_css(base: Option<&Path>, path_str: &str) -> Option<String> {
     use std::fs;
     let p = Path::new(path_str);
@@ -366,6 +528,61 @@ mod tests {
         assert_eq!(config.scale, 1.0);
     }
 
+    #[test]
+    fn test_profile_footer_templates_are_valid() {
+        let exporter = PDFExporter::new().unwrap();
+        for profile in [ExportProfile::Handout, ExportProfile::Archival] {
+            let options = exporter.get_export_options(&profile);
+            if let Some(template) = &options.footer_template {
+                let result = coolslides_core::validation::validate_print_template(
+                    template,
+                    options.page_numbers,
+                    "export footer",
+                );
+                assert!(result.is_valid() && result.warnings.is_empty(), "{:?}", result);
+            }
+        }
+    }
+
+    #[test]
+    fn test_video_segments_follow_narration() {
+        let deck: DeckManifest = toml::from_str(
+            "modelVersion = \"1.0\"\ntitle = \"Async\"\ntheme = \"default\"\nsequence = [\"intro\", \"demo\"]\n\n[transitions]\ndefault = \"none\"",
+        )
+        .unwrap();
+        let slide = |id: &str, narration: &str| -> SlideDoc {
+            toml::from_str(&format!(
+                "modelVersion = \"1.0\"\nid = \"{}\"\nprops = {{}}\n\n[component]\nname = \"TitleSlide\"\nversionReq = \"^1\"\n{}",
+                id, narration
+            ))
+            .unwrap()
+        };
+        let slides: HashMap<String, SlideDoc> = [
+            slide("demo", ""),
+            slide("intro", "\n[narration]\nsrc = \"audio/intro.mp3\"\nduration = 42.5"),
+        ]
+        .into_iter()
+        .map(|slide| (slide.id.clone(), slide))
+        .collect();
+
+        let segments = video_segments(&deck, &slides, 5.0, Path::new("/decks/async"));
+        assert_eq!(
+            segments,
+            vec![
+                VideoSegment { slide_id: "intro".into(), duration: 42.5, audio: Some("/decks/async/audio/intro.mp3".into()) },
+                VideoSegment { slide_id: "demo".into(), duration: 5.0, audio: None },
+            ]
+        );
+
+        let config = VideoConfig { width: 1920, height: 1080, slide_duration: 5.0, timeout: 10000 };
+        let narrated = segment_args(Path::new("0.png"), &segments[0], &config, Path::new("0.mp4"));
+        assert!(narrated.windows(2).any(|w| w == ["-i", "/decks/async/audio/intro.mp3"]));
+        assert!(narrated.windows(2).any(|w| w == ["-t", "42.500"]));
+        let silent = segment_args(Path::new("1.png"), &segments[1], &config, Path::new("1.mp4"));
+        assert!(silent.iter().any(|arg| arg.starts_with("anullsrc")));
+        assert_eq!(silent.last().map(String::as_str), Some("1.mp4"));
+    }
+
     #[tokio::test]
     async fn test_pdf_exporter_creation() {
         let result = PDFExporter::new();
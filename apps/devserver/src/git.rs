@@ -0,0 +1,133 @@
+/*!
+ * Git awareness: which revision of the deck is being served
+ *
+ * Uses the `git` command, so decks outside a repository (or machines without
+ * git) simply report no revision. The dirty flag only covers the deck
+ * directory, so unrelated changes elsewhere in a monorepo do not show up.
+ */
+
+use crate::AppState;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+/// How often HEAD is checked for branch switches, checkouts, and new commits
+const HEAD_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitInfo {
+    /// Top level of the repository holding the deck
+    pub root: PathBuf,
+    /// Checked-out branch; `None` on a detached HEAD
+    pub branch: Option<String>,
+    pub commit: String,
+    pub short_commit: String,
+    /// First line of the commit message
+    pub subject: String,
+    /// Whether files in the deck directory differ from the commit
+    pub dirty: bool,
+}
+
+fn git(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git").arg("-C").arg(dir).args(args).output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).trim_end().to_string())
+}
+
+/// The checked-out commit and branch, or `None` outside a repository or before
+/// the first commit
+pub(crate) fn head(dir: &Path) -> Option<(String, Option<String>)> {
+    let output = git(dir, &["rev-parse", "HEAD", "--abbrev-ref", "HEAD"])?;
+    let mut lines = output.lines();
+    let commit = lines.next()?.to_string();
+    let branch = lines.next().filter(|name| *name != "HEAD").map(str::to_string);
+    Some((commit, branch))
+}
+
+/// Revision details for the repository containing `dir`
+pub fn detect(dir: &Path) -> Option<GitInfo> {
+    let (commit, branch) = head(dir)?;
+    let root = PathBuf::from(git(dir, &["rev-parse", "--show-toplevel"])?);
+    let subject = git(dir, &["log", "-1", "--format=%s"]).unwrap_or_default();
+    let dirty = git(dir, &["status", "--porcelain", "--", "."]).is_some_and(|status| !status.is_empty());
+    Some(GitInfo {
+        root,
+        branch,
+        short_commit: commit.chars().take(7).collect(),
+        commit,
+        subject,
+        dirty,
+    })
+}
+
+/// Reload the deck and every connected page when HEAD moves, e.g. on a branch switch
+pub(crate) fn spawn_head_watcher(state: AppState, deck_dir: PathBuf) {
+    tokio::spawn(async move {
+        let poll = |dir: PathBuf| async move { tokio::task::spawn_blocking(move || head(&dir)).await.ok().flatten() };
+        let mut current = poll(deck_dir.clone()).await;
+        let mut interval = tokio::time::interval(HEAD_POLL_INTERVAL);
+        loop {
+            interval.tick().await;
+            let latest = poll(deck_dir.clone()).await;
+            if latest == current {
+                continue;
+            }
+            if let Some((commit, branch)) = &latest {
+                tracing::info!(commit = &commit[..commit.len().min(7)], branch = branch.as_deref().unwrap_or("detached"), "HEAD moved; reloading");
+            }
+            current = latest;
+            match state.load_from_directory(&deck_dir).await {
+                Ok(()) => state.broadcast_reload().await,
+                Err(e) => tracing::error!(error = %e, "could not reload after HEAD change"),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com", "-c", "init.defaultBranch=main"])
+            .arg("-C")
+            .arg(dir)
+            .args(args)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {:?}", args);
+    }
+
+    #[test]
+    fn test_detect_reports_branch_commit_and_dirty_deck() {
+        if Command::new("git").arg("--version").output().is_err() {
+            return;
+        }
+        let repo = tempfile::TempDir::new().unwrap();
+        let deck = repo.path().join("talk");
+        std::fs::create_dir_all(&deck).unwrap();
+        assert_eq!(detect(&deck), None);
+
+        run(repo.path(), &["init", "-q"]);
+        std::fs::write(deck.join("slides.toml"), "title = \"One\"").unwrap();
+        run(repo.path(), &["add", "-A"]);
+        run(repo.path(), &["commit", "-q", "-m", "First draft"]);
+
+        let info = detect(&deck).unwrap();
+        assert_eq!(info.branch.as_deref(), Some("main"));
+        assert_eq!((info.subject.as_str(), info.dirty), ("First draft", false));
+        assert_eq!(info.short_commit, info.commit[..7]);
+
+        // Changes outside the deck directory do not make it dirty
+        std::fs::write(repo.path().join("README.md"), "notes").unwrap();
+        assert!(!detect(&deck).unwrap().dirty);
+        std::fs::write(deck.join("slides.toml"), "title = \"Two\"").unwrap();
+        assert!(detect(&deck).unwrap().dirty);
+
+        run(repo.path(), &["checkout", "-q", "--detach"]);
+        assert_eq!(head(&deck), Some((info.commit, None)));
+    }
+}
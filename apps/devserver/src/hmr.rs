@@ -0,0 +1,125 @@
+/*!
+ * Structured hot-reload events for open deck pages, served on `/ws/hmr`
+ *
+ * The `__reload` room only tells pages to reload. These events say what
+ * changed: an edited slide comes with its re-rendered markup so the page can
+ * swap that one slide in place and keep the current slide and fragment. Edits
+ * the page cannot patch, such as a new sequence or a different component, ask
+ * for a full reload instead.
+ */
+
+use crate::DeckChanges;
+use axum::extract::ws::{Message, WebSocket};
+use coolslides_core::SlideDoc;
+use serde::Serialize;
+use std::collections::HashMap;
+use tokio::sync::broadcast;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum HmrEvent {
+    /// A slide's content changed; `html` replaces its `[data-slide]` element
+    SlideChanged { id: String, html: String },
+    /// The theme or tokens stylesheet changed; re-request `href` with `?v=<version>`
+    ThemeChanged { href: String, version: i64 },
+    /// The manifest changed, or slides were added, removed, or moved to another component
+    DeckChanged,
+    /// The component registry was rebuilt
+    ComponentsChanged,
+}
+
+pub type HmrSender = broadcast::Sender<HmrEvent>;
+
+pub fn channel() -> HmrSender {
+    broadcast::channel(64).0
+}
+
+/// Events for a deck reload. `components` maps each slide id to its component
+/// name before the reload; a slide that is new or changed component needs the
+/// page's module scripts and sequence rebuilt, so it reloads the deck.
+pub(crate) fn deck_events(
+    changes: &DeckChanges,
+    components: &HashMap<String, String>,
+    slides: &HashMap<String, SlideDoc>,
+    render: impl Fn(&SlideDoc) -> anyhow::Result<String>,
+) -> Vec<HmrEvent> {
+    if changes.manifest || !changes.removed.is_empty() {
+        return vec![HmrEvent::DeckChanged];
+    }
+    let mut events = Vec::new();
+    for id in &changes.slides {
+        let Some(slide) = slides.get(id) else {
+            return vec![HmrEvent::DeckChanged];
+        };
+        if components.get(id) != Some(&slide.component.name) {
+            return vec![HmrEvent::DeckChanged];
+        }
+        match render(slide) {
+            Ok(html) => events.push(HmrEvent::SlideChanged { id: id.clone(), html }),
+            Err(e) => {
+                tracing::warn!(slide = %id, error = %e, "could not render slide for hot reload");
+                return vec![HmrEvent::DeckChanged];
+            }
+        }
+    }
+    events
+}
+
+/// Forward events to one page until it disconnects
+pub async fn handle_connection(mut socket: WebSocket, mut events: broadcast::Receiver<HmrEvent>) {
+    loop {
+        tokio::select! {
+            incoming = socket.recv() => match incoming {
+                Some(Ok(_)) => {}
+                _ => break,
+            },
+            event = events.recv() => {
+                let event = match event {
+                    Ok(event) => event,
+                    // Missed events cannot be patched in, so start over
+                    Err(broadcast::error::RecvError::Lagged(_)) => HmrEvent::DeckChanged,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                let Ok(text) = serde_json::to_string(&event) else { continue };
+                if socket.send(Message::Text(text)).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slide(id: &str, component: &str) -> SlideDoc {
+        toml::from_str(&format!(
+            "modelVersion = \"1.0\"\nid = \"{}\"\n\n[component]\nname = \"{}\"\nversionReq = \"^1\"\n\n[props]\n",
+            id, component
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_only_edited_slides_are_patched() {
+        let slides: HashMap<String, SlideDoc> =
+            [("a", "TitleSlide"), ("b", "TwoCol")].iter().map(|(id, c)| (id.to_string(), slide(id, c))).collect();
+        let components: HashMap<String, String> =
+            [("a", "TitleSlide"), ("b", "TitleSlide")].iter().map(|(id, c)| (id.to_string(), c.to_string())).collect();
+        let render = |slide: &SlideDoc| Ok(format!("<div data-slide=\"{}\"></div>", slide.id));
+        let edited = |ids: &[&str]| DeckChanges { slides: ids.iter().map(|id| id.to_string()).collect(), ..Default::default() };
+
+        let events = deck_events(&edited(&["a"]), &components, &slides, render);
+        assert_eq!(events, [HmrEvent::SlideChanged { id: "a".into(), html: "<div data-slide=\"a\"></div>".into() }]);
+        assert_eq!(
+            serde_json::to_value(&events[0]).unwrap(),
+            serde_json::json!({ "kind": "slide-changed", "id": "a", "html": "<div data-slide=\"a\"></div>" })
+        );
+        // A different component needs its module loaded
+        assert_eq!(deck_events(&edited(&["a", "b"]), &components, &slides, render), [HmrEvent::DeckChanged]);
+        let manifest = DeckChanges { manifest: true, ..Default::default() };
+        assert_eq!(deck_events(&manifest, &components, &slides, render), [HmrEvent::DeckChanged]);
+        assert_eq!(serde_json::to_string(&HmrEvent::DeckChanged).unwrap(), r#"{"kind":"deck-changed"}"#);
+    }
+}
@@ -0,0 +1,658 @@
+/*!
+ * Hub mode: one server hosting many uploaded decks
+ *
+ * Each deck lives in `<data>/decks/<id>` with its own [`AppState`], so rooms,
+ * analytics, webhooks, and server plugins stay per deck. The deck's usual routes
+ * are served under `/decks/<id>/`; [`mount_page`] points the deck page's runtime
+ * at that prefix. `<data>/hub.json` records access tokens, presenter tokens,
+ * and upload times.
+ *
+ * Administration (`/hub/api/...`) takes the hub's admin token as a Bearer token.
+ * Decks with an access token only open with `?access_token=`, which is then kept
+ * in a cookie scoped to the deck, or the `X-Coolslides-Access` header. Each deck
+ * also has its own presenter token, so viewers cannot present, record, or edit.
+ */
+
+use crate::{constant_time_eq, create_router, html_escape, static_files, webhooks, AppState};
+use axum::{
+    body::{Body, Bytes},
+    extract::{DefaultBodyLimit, Path as AxumPath, Query, Request, State},
+    http::{header, HeaderMap, StatusCode, Uri},
+    response::{Html, IntoResponse, Response},
+    routing::{delete, get, post},
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::{Mutex, RwLock};
+use tower::ServiceExt;
+use tower_http::{cors::CorsLayer, trace::TraceLayer};
+
+/// Access tokens and upload times, in the data directory
+pub const INDEX_FILE: &str = "hub.json";
+/// Largest upload accepted, compressed
+const MAX_UPLOAD_BYTES: usize = 100 * 1024 * 1024;
+/// Largest deck an upload may unpack to
+const MAX_UNPACKED_BYTES: u64 = 500 * 1024 * 1024;
+const ACCESS_COOKIE: &str = "coolslides_access";
+const ACCESS_HEADER: &str = "x-coolslides-access";
+
+/// Script that sends the runtime's server requests to the deck's prefix
+const MOUNT_SCRIPT: &str = r#"<script>(function(){var base=__BASE__;var own=/^\/(api|rooms|components|vendor)\//;function fix(u){if(typeof u!=='string'&&!(u instanceof URL))return u;try{var x=new URL(String(u),location.href);if(x.host===location.host&&own.test(x.pathname)){x.pathname=base+x.pathname;return typeof u==='string'?x.toString():x;}}catch(_){}return u;}var f=window.fetch;window.fetch=function(i,o){return f.call(this,fix(i),o);};if(navigator.sendBeacon){var b=navigator.sendBeacon.bind(navigator);navigator.sendBeacon=function(u,d){return b(fix(u),d);};}var W=window.WebSocket;function S(u,p){return p===undefined?new W(fix(u)):new W(fix(u),p);}S.prototype=W.prototype;['CONNECTING','OPEN','CLOSING','CLOSED'].forEach(function(k){S[k]=W[k];});window.WebSocket=S;})();</script>"#;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct HubIndex {
+    decks: BTreeMap<String, DeckRecord>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DeckRecord {
+    /// Required to open the deck; `None` makes it public
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    token: Option<String>,
+    /// Guards the deck's presenter, recording, and editing routes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    presenter_token: Option<String>,
+    uploaded: DateTime<Utc>,
+}
+
+struct HostedDeck {
+    record: DeckRecord,
+    state: AppState,
+    router: Router,
+    dispatcher: tokio::task::JoinHandle<()>,
+}
+
+/// The hub's decks and settings
+#[derive(Clone)]
+pub struct Hub {
+    inner: Arc<HubInner>,
+}
+
+struct HubInner {
+    data_dir: PathBuf,
+    admin_token: String,
+    strict_mode: bool,
+    decks: RwLock<BTreeMap<String, HostedDeck>>,
+    /// Uploads and deletions run one at a time
+    changes: Mutex<()>,
+}
+
+impl Hub {
+    /// Open the data directory and load the decks recorded in it. Decks that no
+    /// longer load are reported and left out.
+    pub async fn open(data_dir: impl Into<PathBuf>, admin_token: impl Into<String>, strict_mode: bool) -> anyhow::Result<Self> {
+        let data_dir = data_dir.into();
+        std::fs::create_dir_all(data_dir.join("decks"))?;
+        let index: HubIndex = match std::fs::read_to_string(data_dir.join(INDEX_FILE)) {
+            Ok(contents) => serde_json::from_str(&contents)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HubIndex::default(),
+            Err(e) => return Err(e.into()),
+        };
+        let hub = Self {
+            inner: Arc::new(HubInner {
+                data_dir,
+                admin_token: admin_token.into(),
+                strict_mode,
+                decks: RwLock::new(BTreeMap::new()),
+                changes: Mutex::new(()),
+            }),
+        };
+        let mut minted = false;
+        for (id, record) in index.decks {
+            minted |= record.presenter_token.is_none();
+            match hub.host(&id, record).await {
+                Ok(deck) => {
+                    hub.inner.decks.write().await.insert(id, deck);
+                }
+                Err(e) => tracing::warn!(deck = %id, error = %e, "hub deck not loaded"),
+            }
+        }
+        if minted {
+            hub.save_index().await?;
+        }
+        Ok(hub)
+    }
+
+    fn deck_dir(&self, id: &str) -> PathBuf {
+        self.inner.data_dir.join("decks").join(id)
+    }
+
+    async fn host(&self, id: &str, mut record: DeckRecord) -> anyhow::Result<HostedDeck> {
+        // Decks recorded before presenter tokens get one now
+        let presenter_token = record.presenter_token.get_or_insert_with(new_token).clone();
+        let state = AppState::new_with_strict_mode(self.inner.strict_mode)
+            .with_mount_path(format!("/decks/{}", id))
+            .with_presenter_token(presenter_token);
+        state.load_from_directory(self.deck_dir(id)).await?;
+        let dispatcher = webhooks::spawn_dispatcher(state.clone());
+        Ok(HostedDeck { record, router: create_router(state.clone()), state, dispatcher })
+    }
+
+    async fn save_index(&self) -> anyhow::Result<()> {
+        let index = HubIndex {
+            decks: self.inner.decks.read().await.iter().map(|(id, deck)| (id.clone(), deck.record.clone())).collect(),
+        };
+        let path = self.inner.data_dir.join(INDEX_FILE);
+        let temp = path.with_extension("json.tmp");
+        tokio::fs::write(&temp, serde_json::to_vec_pretty(&index)?).await?;
+        tokio::fs::rename(&temp, &path).await?;
+        Ok(())
+    }
+
+    /// Unpack an uploaded deck archive and host it as `id`, replacing the current
+    /// upload if there is one. New decks get an access token unless `public`.
+    async fn install(&self, id: &str, archive: Bytes, public: bool) -> Result<(DeckRecord, bool), HubError> {
+        let _guard = self.inner.changes.lock().await;
+        let uploads = self.inner.data_dir.join("uploads");
+        let staging = uploads.join(uuid::Uuid::new_v4().to_string());
+        let unpacked = {
+            let staging = staging.clone();
+            tokio::task::spawn_blocking(move || unpack(&archive, &staging))
+                .await
+                .map_err(|e| HubError::Internal(e.to_string()))?
+        };
+        let result = self.install_unpacked(id, unpacked, &staging, public).await;
+        let _ = tokio::fs::remove_dir_all(&staging).await;
+        result
+    }
+
+    async fn install_unpacked(
+        &self,
+        id: &str,
+        unpacked: anyhow::Result<PathBuf>,
+        staging: &Path,
+        public: bool,
+    ) -> Result<(DeckRecord, bool), HubError> {
+        let root = unpacked.map_err(|e| HubError::BadRequest(format!("Invalid deck archive: {}", e)))?;
+        // Make sure the upload loads before it replaces anything
+        AppState::new_with_strict_mode(self.inner.strict_mode)
+            .load_from_directory(&root)
+            .await
+            .map_err(|e| HubError::BadRequest(format!("Invalid deck: {}", e)))?;
+
+        let target = self.deck_dir(id);
+        let replaced = staging.with_extension("old");
+        if target.exists() {
+            tokio::fs::rename(&target, &replaced).await.map_err(HubError::internal)?;
+        }
+        tokio::fs::rename(&root, &target).await.map_err(HubError::internal)?;
+        let _ = tokio::fs::remove_dir_all(&replaced).await;
+
+        let mut decks = self.inner.decks.write().await;
+        let created = !decks.contains_key(id);
+        let record = match decks.get_mut(id) {
+            // Open pages and rooms carry over to the new upload
+            Some(deck) => {
+                deck.record.uploaded = Utc::now();
+                deck.state.load_from_directory(&target).await.map_err(HubError::internal)?;
+                deck.state.broadcast_reload().await;
+                deck.record.clone()
+            }
+            None => {
+                let token = (!public).then(new_token);
+                let record = DeckRecord { token, presenter_token: Some(new_token()), uploaded: Utc::now() };
+                let deck = self.host(id, record).await.map_err(HubError::internal)?;
+                let record = deck.record.clone();
+                decks.insert(id.to_string(), deck);
+                record
+            }
+        };
+        drop(decks);
+        self.save_index().await.map_err(HubError::internal)?;
+        Ok((record, created))
+    }
+
+    async fn remove(&self, id: &str) -> Result<(), HubError> {
+        let _guard = self.inner.changes.lock().await;
+        let deck = self.inner.decks.write().await.remove(id).ok_or(HubError::NotFound)?;
+        for room in deck.state.room_manager.list_rooms().await {
+            deck.state.room_manager.remove_room(&room.id).await;
+        }
+        // Let the room.closed webhooks go out before the dispatcher stops
+        tokio::task::yield_now().await;
+        deck.dispatcher.abort();
+        let _ = tokio::fs::remove_dir_all(self.deck_dir(id)).await;
+        self.save_index().await.map_err(HubError::internal)
+    }
+
+    async fn set_token(&self, id: &str, token: Option<String>) -> Result<(), HubError> {
+        let mut decks = self.inner.decks.write().await;
+        decks.get_mut(id).ok_or(HubError::NotFound)?.record.token = token;
+        drop(decks);
+        self.save_index().await.map_err(HubError::internal)
+    }
+
+    /// Listing entry for a deck, with its live rooms
+    async fn summary(id: &str, deck: &HostedDeck) -> serde_json::Value {
+        let (title, slides) = match deck.state.deck.read().await.as_ref() {
+            Some(manifest) => (manifest.title.clone(), manifest.slide_order().len()),
+            None => (String::new(), 0),
+        };
+        let mut rooms = Vec::new();
+        for room in deck.state.room_manager.list_rooms().await {
+            // Internal rooms such as `__reload` are not listed
+            if !room.id.starts_with("__") {
+                rooms.push(serde_json::json!({ "id": room.id, "clients": room.clients.read().await.len() }));
+            }
+        }
+        serde_json::json!({
+            "id": id,
+            "title": title,
+            "slides": slides,
+            "url": format!("/decks/{}/", id),
+            "protected": deck.record.token.is_some(),
+            "uploaded": deck.record.uploaded,
+            "rooms": rooms,
+        })
+    }
+}
+
+enum HubError {
+    NotFound,
+    BadRequest(String),
+    Internal(String),
+}
+
+impl HubError {
+    fn internal(e: impl std::fmt::Display) -> Self {
+        HubError::Internal(e.to_string())
+    }
+}
+
+impl IntoResponse for HubError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            HubError::NotFound => (StatusCode::NOT_FOUND, "No such deck".to_string()),
+            HubError::BadRequest(message) => (StatusCode::BAD_REQUEST, message),
+            HubError::Internal(message) => (StatusCode::INTERNAL_SERVER_ERROR, message),
+        };
+        (status, Json(serde_json::json!({ "error": message }))).into_response()
+    }
+}
+
+/// A random token, for access, presenter, and admin tokens
+pub fn new_token() -> String {
+    uuid::Uuid::new_v4().simple().to_string()
+}
+
+/// Deck ids appear in paths and URLs, so they are kept to a safe alphabet
+fn valid_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= 64
+        && !id.starts_with(['-', '.'])
+        && id.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '-' | '_' | '.'))
+}
+
+/// Unpack a tar or tar.gz deck archive into `staging` and return the deck root:
+/// `staging` itself, or its single top-level directory. Only regular files and
+/// directories are unpacked.
+fn unpack(archive: &[u8], staging: &Path) -> anyhow::Result<PathBuf> {
+    std::fs::create_dir_all(staging)?;
+    let reader: Box<dyn Read> = if archive.starts_with(&[0x1f, 0x8b]) {
+        Box::new(flate2::read::GzDecoder::new(archive))
+    } else {
+        Box::new(archive)
+    };
+    let mut archive = tar::Archive::new(reader);
+    let mut total = 0u64;
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !matches!(entry.header().entry_type(), tar::EntryType::Regular | tar::EntryType::Directory) {
+            continue;
+        }
+        total += entry.size();
+        if total > MAX_UNPACKED_BYTES {
+            anyhow::bail!("unpacks to more than {} MB", MAX_UNPACKED_BYTES / 1024 / 1024);
+        }
+        // `unpack_in` refuses paths that would leave `staging`
+        entry.unpack_in(staging)?;
+    }
+
+    if staging.join("slides.toml").is_file() {
+        return Ok(staging.to_path_buf());
+    }
+    let entries: Vec<PathBuf> = std::fs::read_dir(staging)?.filter_map(|e| e.ok().map(|e| e.path())).collect();
+    match entries.as_slice() {
+        [only] if only.join("slides.toml").is_file() => Ok(only.clone()),
+        _ => anyhow::bail!("no slides.toml at the top of the archive"),
+    }
+}
+
+/// Rewrite a deck page served under `prefix`: deck-local modules in the import
+/// map, and the runtime's API, room, and module requests
+pub(crate) fn mount_page(html: &str, prefix: &str) -> String {
+    let script = MOUNT_SCRIPT.replace("__BASE__", &serde_json::to_string(prefix).unwrap_or_default());
+    html.replace("\"/components/", &format!("\"{}/components/", prefix))
+        .replace("\"/vendor/", &format!("\"{}/vendor/", prefix))
+        .replace("\"/proxy/", &format!("\"{}/proxy/", prefix))
+        .replacen("<meta charset=\"utf-8\">", &format!("<meta charset=\"utf-8\">\n    {}", script), 1)
+}
+
+/// Router for hub mode
+pub fn create_hub_router(hub: Hub) -> Router {
+    let decks = Router::new()
+        .route("/decks/:id", get(deck_root_redirect))
+        .route("/decks/:id/", axum::routing::any(deck_request_root))
+        .route("/decks/:id/*rest", axum::routing::any(deck_request));
+    let admin = Router::new()
+        .route("/hub/api/decks", get(list_decks))
+        .route(
+            "/hub/api/decks/:id",
+            get(get_deck).put(upload_deck).delete(delete_deck).layer(DefaultBodyLimit::max(MAX_UPLOAD_BYTES)),
+        )
+        .route("/hub/api/decks/:id/token", post(rotate_token).delete(remove_token))
+        .route("/hub/api/decks/:id/rooms/:room_id", delete(close_room));
+    static_files(Router::new().route("/", get(hub_index)).merge(decks).merge(admin))
+        .layer(CorsLayer::permissive())
+        .layer(TraceLayer::new_for_http())
+        .with_state(hub)
+}
+
+/// Start the hub server
+pub async fn start_hub(host: &str, port: u16, data_dir: &Path, admin_token: &str, strict_mode: bool) -> anyhow::Result<()> {
+    let hub = Hub::open(data_dir, admin_token, strict_mode).await?;
+    let count = hub.inner.decks.read().await.len();
+    let listener = tokio::net::TcpListener::bind(format!("{}:{}", host, port)).await?;
+    println!("Coolslides hub running on http://{}:{}", host, port);
+    println!("Hosting {} deck(s) from {}", count, data_dir.display());
+    axum::serve(listener, create_hub_router(hub)).await?;
+    Ok(())
+}
+
+fn require_admin(hub: &Hub, headers: &HeaderMap) -> Result<(), StatusCode> {
+    let presented = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    match presented {
+        Some(token) if constant_time_eq(token.trim().as_bytes(), hub.inner.admin_token.as_bytes()) => Ok(()),
+        _ => Err(StatusCode::UNAUTHORIZED),
+    }
+}
+
+/// Public decks, for people browsing the hub
+async fn hub_index(State(hub): State<Hub>) -> Html<String> {
+    let decks = hub.inner.decks.read().await;
+    let mut items = String::new();
+    for (id, deck) in decks.iter().filter(|(_, deck)| deck.record.token.is_none()) {
+        let title = deck.state.deck.read().await.as_ref().map(|d| d.title.clone()).unwrap_or_else(|| id.clone());
+        items.push_str(&format!("<li><a href=\"/decks/{}/\">{}</a></li>", id, html_escape(&title)));
+    }
+    if items.is_empty() {
+        items.push_str("<li>No public decks yet</li>");
+    }
+    Html(format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<title>Coolslides Hub</title>\n<meta charset=\"utf-8\">\n<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n</head>\n<body>\n<h1>Presentations</h1>\n<ul>{}</ul>\n</body>\n</html>",
+        items
+    ))
+}
+
+async fn list_decks(State(hub): State<Hub>, headers: HeaderMap) -> Result<Json<Vec<serde_json::Value>>, StatusCode> {
+    require_admin(&hub, &headers)?;
+    let decks = hub.inner.decks.read().await;
+    let mut listing = Vec::new();
+    for (id, deck) in decks.iter() {
+        listing.push(Hub::summary(id, deck).await);
+    }
+    Ok(Json(listing))
+}
+
+async fn get_deck(State(hub): State<Hub>, AxumPath(id): AxumPath<String>, headers: HeaderMap) -> Result<Json<serde_json::Value>, Response> {
+    require_admin(&hub, &headers).map_err(IntoResponse::into_response)?;
+    let decks = hub.inner.decks.read().await;
+    let deck = decks.get(&id).ok_or_else(|| HubError::NotFound.into_response())?;
+    let mut summary = Hub::summary(&id, deck).await;
+    summary["presenterToken"] = deck.record.presenter_token.clone().into();
+    Ok(Json(summary))
+}
+
+#[derive(Deserialize, Default)]
+struct UploadQuery {
+    #[serde(default)]
+    public: bool,
+}
+
+/// Upload a deck as a tar or tar.gz archive. The response includes the access
+/// token, which is only shown here and when it is rotated, and the presenter token.
+async fn upload_deck(
+    State(hub): State<Hub>,
+    AxumPath(id): AxumPath<String>,
+    Query(query): Query<UploadQuery>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, Response> {
+    require_admin(&hub, &headers).map_err(IntoResponse::into_response)?;
+    if !valid_id(&id) {
+        return Err(HubError::BadRequest("Deck ids use a-z, 0-9, '-', '_' and '.'".to_string()).into_response());
+    }
+    let (record, created) = hub.install(&id, body, query.public).await.map_err(IntoResponse::into_response)?;
+    let decks = hub.inner.decks.read().await;
+    let mut summary = Hub::summary(&id, decks.get(&id).ok_or_else(|| HubError::NotFound.into_response())?).await;
+    summary["token"] = record.token.into();
+    summary["presenterToken"] = record.presenter_token.into();
+    let status = if created { StatusCode::CREATED } else { StatusCode::OK };
+    Ok((status, Json(summary)).into_response())
+}
+
+async fn delete_deck(State(hub): State<Hub>, AxumPath(id): AxumPath<String>, headers: HeaderMap) -> Result<StatusCode, Response> {
+    require_admin(&hub, &headers).map_err(IntoResponse::into_response)?;
+    hub.remove(&id).await.map_err(IntoResponse::into_response)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Give the deck a new access token; the old one stops working
+async fn rotate_token(State(hub): State<Hub>, AxumPath(id): AxumPath<String>, headers: HeaderMap) -> Result<Json<serde_json::Value>, Response> {
+    require_admin(&hub, &headers).map_err(IntoResponse::into_response)?;
+    let token = new_token();
+    hub.set_token(&id, Some(token.clone())).await.map_err(IntoResponse::into_response)?;
+    Ok(Json(serde_json::json!({ "id": id, "token": token })))
+}
+
+/// Make the deck public
+async fn remove_token(State(hub): State<Hub>, AxumPath(id): AxumPath<String>, headers: HeaderMap) -> Result<StatusCode, Response> {
+    require_admin(&hub, &headers).map_err(IntoResponse::into_response)?;
+    hub.set_token(&id, None).await.map_err(IntoResponse::into_response)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn close_room(
+    State(hub): State<Hub>,
+    AxumPath((id, room_id)): AxumPath<(String, String)>,
+    headers: HeaderMap,
+) -> Result<StatusCode, Response> {
+    require_admin(&hub, &headers).map_err(IntoResponse::into_response)?;
+    let decks = hub.inner.decks.read().await;
+    let deck = decks.get(&id).ok_or_else(|| HubError::NotFound.into_response())?;
+    deck.state.room_manager.get_room(&room_id).await.ok_or_else(|| StatusCode::NOT_FOUND.into_response())?;
+    deck.state.room_manager.remove_room(&room_id).await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub(crate) async fn deck_root_redirect(AxumPath(id): AxumPath<String>, uri: Uri) -> Response {
+    let location = match uri.query() {
+        Some(query) => format!("/decks/{}/?{}", id, query),
+        None => format!("/decks/{}/", id),
+    };
+    Response::builder()
+        .status(StatusCode::PERMANENT_REDIRECT)
+        .header(header::LOCATION, location)
+        .body(Body::empty())
+        .unwrap()
+}
+
+async fn deck_request_root(State(hub): State<Hub>, AxumPath(id): AxumPath<String>, request: Request) -> Response {
+    forward(&hub, &id, "", request).await
+}
+
+async fn deck_request(State(hub): State<Hub>, AxumPath((id, rest)): AxumPath<(String, String)>, request: Request) -> Response {
+    forward(&hub, &id, &rest, request).await
+}
+
+/// Check access to the deck, then hand the request to the deck's own router
+/// with the `/decks/<id>` prefix removed
+async fn forward(hub: &Hub, id: &str, rest: &str, mut request: Request) -> Response {
+    let (router, token) = {
+        let decks = hub.inner.decks.read().await;
+        match decks.get(id) {
+            Some(deck) => (deck.router.clone(), deck.record.token.clone()),
+            None => return HubError::NotFound.into_response(),
+        }
+    };
+
+    let from_query = request
+        .uri()
+        .query()
+        .and_then(|query| url_param(query, "access_token"));
+    let mut remember = None;
+    if let Some(expected) = &token {
+        let matches = |candidate: &str| constant_time_eq(candidate.as_bytes(), expected.as_bytes());
+        let headers = request.headers();
+        let granted = headers.get(ACCESS_HEADER).and_then(|v| v.to_str().ok()).is_some_and(matches)
+            || cookie(headers, ACCESS_COOKIE).is_some_and(|c| matches(&c))
+            || require_admin(hub, headers).is_ok();
+        if !granted {
+            match from_query {
+                Some(candidate) if matches(&candidate) => remember = Some(candidate),
+                _ => return (StatusCode::UNAUTHORIZED, "This deck needs an access token").into_response(),
+            }
+        }
+    }
+
+    let path_and_query = match request.uri().query() {
+        Some(query) => format!("/{}?{}", rest, query),
+        None => format!("/{}", rest),
+    };
+    *request.uri_mut() = match path_and_query.parse() {
+        Ok(uri) => uri,
+        Err(_) => return StatusCode::BAD_REQUEST.into_response(),
+    };
+    let mut response = router.oneshot(request).await.unwrap_or_else(|never| match never {});
+    if let Some(token) = remember {
+        let cookie = format!("{}={}; Path=/decks/{}; HttpOnly; SameSite=Lax", ACCESS_COOKIE, token, id);
+        if let Ok(value) = cookie.parse() {
+            response.headers_mut().append(header::SET_COOKIE, value);
+        }
+    }
+    response
+}
+
+/// A query parameter, undecoded; tokens never need escaping
+fn url_param(query: &str, name: &str) -> Option<String> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value.to_string())
+}
+
+fn cookie(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deck_archive() -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        let mut add = |path: &str, contents: &str| {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, path, contents.as_bytes()).unwrap();
+        };
+        add(
+            "talk/slides.toml",
+            "modelVersion = \"1.0\"\ntitle = \"Q3 Review\"\ntheme = \"theme.css\"\nsequence = [\"intro\"]\n\n[transitions]\ndefault = \"none\"\n",
+        );
+        add(
+            "talk/content/intro.slide.toml",
+            "modelVersion = \"1.0\"\nid = \"intro\"\n\n[component]\nname = \"TitleSlide\"\nversionReq = \"^1\"\n\n[props]\ntitle = \"Hello\"\n",
+        );
+        builder.into_inner().unwrap()
+    }
+
+    async fn send(router: &Router, request: axum::http::Request<Body>) -> Response {
+        router.clone().oneshot(request).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_hub_upload_access_and_removal() {
+        let data = tempfile::TempDir::new().unwrap();
+        let hub = Hub::open(data.path(), "admin", false).await.unwrap();
+        let router = create_hub_router(hub.clone());
+        let request = |method: &str, uri: &str| axum::http::Request::builder().method(method).uri(uri);
+
+        let anonymous = send(&router, request("PUT", "/hub/api/decks/q3").body(Body::from(deck_archive())).unwrap()).await;
+        assert_eq!(anonymous.status(), StatusCode::UNAUTHORIZED);
+        let upload = request("PUT", "/hub/api/decks/q3").header("authorization", "Bearer admin");
+        let created = send(&router, upload.body(Body::from(deck_archive())).unwrap()).await;
+        assert_eq!(created.status(), StatusCode::CREATED);
+        let body = axum::body::to_bytes(created.into_body(), usize::MAX).await.unwrap();
+        let summary: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!((summary["title"].as_str(), summary["slides"].as_u64()), (Some("Q3 Review"), Some(1)));
+        let token = summary["token"].as_str().unwrap().to_string();
+        let presenter_token = summary["presenterToken"].as_str().unwrap().to_string();
+        assert_ne!(token, presenter_token);
+
+        // New decks are protected
+        let denied = send(&router, request("GET", "/decks/q3/api/deck").body(Body::empty()).unwrap()).await;
+        assert_eq!(denied.status(), StatusCode::UNAUTHORIZED);
+        let page = send(&router, request("GET", &format!("/decks/q3/?access_token={}", token)).body(Body::empty()).unwrap()).await;
+        assert_eq!(page.status(), StatusCode::OK);
+        let cookie = page.headers()[header::SET_COOKIE].to_str().unwrap().split(';').next().unwrap().to_string();
+        let html = String::from_utf8(axum::body::to_bytes(page.into_body(), usize::MAX).await.unwrap().to_vec()).unwrap();
+        assert!(html.contains("var base=\"/decks/q3\""));
+        let api = send(&router, request("GET", "/decks/q3/api/deck").header("cookie", cookie.as_str()).body(Body::empty()).unwrap()).await;
+        assert_eq!(api.status(), StatusCode::OK);
+
+        // Viewers with the access token still cannot present or edit
+        let viewer = |uri: &str| request("GET", uri).header("cookie", cookie.as_str()).body(Body::empty()).unwrap();
+        assert_eq!(send(&router, viewer("/decks/q3/presenter")).await.status(), StatusCode::UNAUTHORIZED);
+        let presenter = send(&router, viewer(&format!("/decks/q3/presenter?token={}", presenter_token))).await;
+        assert_eq!(presenter.status(), StatusCode::OK);
+
+        // Rooms are per deck
+        hub.inner.decks.read().await["q3"].state.room_manager.ensure_room("main".into()).await;
+        let listing = send(&router, request("GET", "/hub/api/decks").header("authorization", "Bearer admin").body(Body::empty()).unwrap()).await;
+        let listing: serde_json::Value = serde_json::from_slice(&axum::body::to_bytes(listing.into_body(), usize::MAX).await.unwrap()).unwrap();
+        assert_eq!(listing[0]["rooms"][0]["id"], "main");
+        assert!(listing[0].get("token").is_none());
+
+        // The index survives a restart
+        let reopened = Hub::open(data.path(), "admin", false).await.unwrap();
+        let record = reopened.inner.decks.read().await["q3"].record.clone();
+        assert_eq!((record.token.as_deref(), record.presenter_token.as_deref()), (Some(token.as_str()), Some(presenter_token.as_str())));
+
+        let removed = send(&router, request("DELETE", "/hub/api/decks/q3").header("authorization", "Bearer admin").body(Body::empty()).unwrap()).await;
+        assert_eq!(removed.status(), StatusCode::NO_CONTENT);
+        assert!(!data.path().join("decks/q3").exists());
+    }
+
+    #[test]
+    fn test_unpack_rejects_archives_without_a_deck_and_bad_ids() {
+        let staging = tempfile::TempDir::new().unwrap();
+        let mut builder = tar::Builder::new(Vec::new());
+        let mut header = tar::Header::new_gnu();
+        header.set_size(2);
+        header.set_cksum();
+        builder.append_data(&mut header, "a/notes.txt", &b"hi"[..]).unwrap();
+        assert!(unpack(&builder.into_inner().unwrap(), &staging.path().join("x")).is_err());
+        assert!(valid_id("q3-review_2024.v2"));
+        assert!(!valid_id("../etc") && !valid_id("Q3") && !valid_id(""));
+    }
+}
@@ -0,0 +1,252 @@
+/*!
+ * Image optimization for HTML exports: resized variants, modern formats, and `srcset`s
+ *
+ * Each local JPEG or PNG an exported page references is resized to the deck's
+ * `[images]` widths and re-encoded. WebP and AVIF copies come from `cwebp` and
+ * `avifenc` when they are installed. Variants are cached under
+ * `.coolslides/images` by source hash, so repeated exports only encode new images.
+ */
+
+use crate::render_cache;
+use anyhow::{anyhow, Result};
+use coolslides_core::ImageConfig;
+use regex::{Captures, Regex};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+
+/// Directory of the export that receives the optimized copies
+pub const EXPORT_IMAGES_DIR: &str = "images";
+
+/// Outcome of optimizing an exported page
+#[derive(Debug, Clone, Default)]
+pub struct OptimizedImages {
+    /// Page with each optimized `<img>` wrapped in a `<picture>`
+    pub html: String,
+    /// Images optimized
+    pub images: usize,
+    /// Total size of the original images, in bytes
+    pub original_bytes: u64,
+    /// Total size of the largest variant of each image in its original format, in bytes
+    pub optimized_bytes: u64,
+}
+
+/// One encoded variant in the deck's cache
+#[derive(Debug, Clone)]
+struct Variant {
+    width: u32,
+    format: &'static str,
+    path: PathBuf,
+}
+
+/// Optimize the local images referenced by `html`, copying the variants into
+/// `<out_dir>/images` and pointing the page at them
+pub fn optimize_export(html: &str, deck_dir: &Path, out_dir: &Path, config: &ImageConfig) -> Result<OptimizedImages> {
+    static IMG: OnceLock<Regex> = OnceLock::new();
+    let img = IMG.get_or_init(|| Regex::new(r#"<img\b[^>]*\bsrc="([^"]+)"[^>]*>"#).unwrap());
+
+    let mut result = OptimizedImages::default();
+    let mut prepared: HashMap<String, Option<Vec<Variant>>> = HashMap::new();
+    for captures in img.captures_iter(html) {
+        let src = captures[1].to_string();
+        if prepared.contains_key(&src) {
+            continue;
+        }
+        let variants = match local_image(&src, deck_dir) {
+            Some(file) => match variants(&file, deck_dir, config) {
+                Ok(variants) => {
+                    result.images += 1;
+                    result.original_bytes += std::fs::metadata(&file)?.len();
+                    let largest = variants.iter().filter(|v| v.format == original_format(&file)).max_by_key(|v| v.width);
+                    result.optimized_bytes += largest.map_or(Ok(0), |v| std::fs::metadata(&v.path).map(|m| m.len()))?;
+                    Some(variants)
+                }
+                Err(e) => {
+                    tracing::warn!(src = %src, error = %e, "could not optimize image");
+                    None
+                }
+            },
+            None => None,
+        };
+        prepared.insert(src, variants);
+    }
+
+    let export_dir = out_dir.join(EXPORT_IMAGES_DIR);
+    for variant in prepared.values().flatten().flatten() {
+        std::fs::create_dir_all(&export_dir)?;
+        std::fs::copy(&variant.path, export_dir.join(variant.path.file_name().unwrap_or_default()))?;
+    }
+
+    result.html = img
+        .replace_all(html, |captures: &Captures| match prepared.get(&captures[1]) {
+            Some(Some(variants)) => picture(&captures[0], &captures[1], variants, &config.sizes),
+            _ => captures[0].to_string(),
+        })
+        .into_owned();
+    Ok(result)
+}
+
+/// The file for a relative JPEG or PNG `src`; remote, data, and root-relative URLs are left alone
+fn local_image(src: &str, deck_dir: &Path) -> Option<PathBuf> {
+    if src.contains("://") || src.starts_with("data:") || src.starts_with('/') || src.contains("..") {
+        return None;
+    }
+    let path = deck_dir.join(src.split(['?', '#']).next()?);
+    matches!(original_format(&path), "jpg" | "png").then_some(path).filter(|path| path.is_file())
+}
+
+fn original_format(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase()).as_deref() {
+        Some("jpg" | "jpeg") => "jpg",
+        Some("png") => "png",
+        _ => "",
+    }
+}
+
+/// Encode (or find in the cache) every width and format of one image
+fn variants(file: &Path, deck_dir: &Path, config: &ImageConfig) -> Result<Vec<Variant>> {
+    let bytes = std::fs::read(file)?;
+    let format = original_format(file);
+    let stem = file.file_stem().and_then(|s| s.to_str()).unwrap_or("image");
+    let hash = render_cache::key((&bytes, &config.widths, &config.formats, config.quality));
+    let cache_dir = deck_dir.join(".coolslides").join("images");
+    std::fs::create_dir_all(&cache_dir)?;
+
+    let image = image::load_from_memory(&bytes)?;
+    let widths = target_widths(image.width(), &config.widths);
+    let mut variants = Vec::new();
+    for width in widths {
+        let name = |format: &str| format!("{}-{:08x}-{}.{}", stem, hash as u32, width, format);
+        let path = cache_dir.join(name(format));
+        if !path.exists() {
+            let height = ((image.height() as u64 * width as u64) / image.width().max(1) as u64).max(1) as u32;
+            let resized = image.resize_exact(width, height, image::imageops::FilterType::Lanczos3);
+            let mut encoded = Vec::new();
+            match format {
+                "jpg" => {
+                    let encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(&mut encoded, config.quality.clamp(1, 100));
+                    resized.to_rgb8().write_with_encoder(encoder)?;
+                }
+                _ => {
+                    let encoder = image::codecs::png::PngEncoder::new_with_quality(
+                        &mut encoded,
+                        image::codecs::png::CompressionType::Best,
+                        image::codecs::png::FilterType::Adaptive,
+                    );
+                    resized.write_with_encoder(encoder)?;
+                }
+            }
+            std::fs::write(&path, encoded)?;
+        }
+        for extra in &config.formats {
+            let Some(extra) = ["webp", "avif"].into_iter().find(|f| f == extra) else {
+                continue;
+            };
+            let extra_path = cache_dir.join(name(extra));
+            if extra_path.exists() || encode_modern(extra, &path, &extra_path, config.quality).is_ok() {
+                variants.push(Variant { width, format: extra, path: extra_path });
+            }
+        }
+        variants.push(Variant { width, format, path });
+    }
+    Ok(variants)
+}
+
+/// Configured widths narrower than the original; the original width is kept when it is
+/// no wider than the largest configured width, so large photos are always scaled down
+fn target_widths(original: u32, configured: &[u32]) -> Vec<u32> {
+    let mut widths: Vec<u32> = configured.iter().copied().filter(|w| *w > 0 && *w < original).collect();
+    match configured.iter().max() {
+        Some(max) if original > *max => {}
+        _ => widths.push(original),
+    }
+    widths.sort_unstable();
+    widths.dedup();
+    widths
+}
+
+/// WebP or AVIF copy via `cwebp` or `avifenc`; a missing tool is reported once
+fn encode_modern(format: &str, input: &Path, output: &Path, quality: u8) -> Result<()> {
+    let quality = quality.clamp(1, 100).to_string();
+    let mut command = match format {
+        "webp" => {
+            let mut command = Command::new("cwebp");
+            command.args(["-quiet", "-q", &quality]).arg(input).arg("-o").arg(output);
+            command
+        }
+        _ => {
+            let mut command = Command::new("avifenc");
+            command.args(["-q", &quality]).arg(input).arg(output);
+            command
+        }
+    };
+    let result = match command.output() {
+        Ok(out) if out.status.success() && output.exists() => Ok(()),
+        Ok(out) => Err(anyhow!("{}", String::from_utf8_lossy(&out.stderr).trim())),
+        Err(e) => Err(anyhow!("{}", e)),
+    };
+    if let Err(e) = &result {
+        static WEBP: OnceLock<()> = OnceLock::new();
+        static AVIF: OnceLock<()> = OnceLock::new();
+        let (warned, tool) = if format == "webp" { (&WEBP, "cwebp") } else { (&AVIF, "avifenc") };
+        if warned.set(()).is_ok() {
+            tracing::warn!(format, tool, error = %e, "skipping image variants");
+        }
+    }
+    result
+}
+
+/// `<picture>` with a `<source>` per modern format and the `<img>` pointed at the variants
+fn picture(img: &str, src: &str, variants: &[Variant], sizes: &str) -> String {
+    let srcset = |format: &str| {
+        variants
+            .iter()
+            .filter(|v| v.format == format)
+            .map(|v| format!("{}/{} {}w", EXPORT_IMAGES_DIR, v.path.file_name().unwrap_or_default().to_string_lossy(), v.width))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let sources: String = ["avif", "webp"]
+        .iter()
+        .filter(|format| variants.iter().any(|v| v.format == **format))
+        .map(|format| format!(r#"<source type="image/{}" srcset="{}" sizes="{}">"#, format, srcset(format), sizes))
+        .collect();
+    let original = variants.iter().filter(|v| v.format == "jpg" || v.format == "png").max_by_key(|v| v.width);
+    let Some(original) = original else {
+        return img.to_string();
+    };
+    let fallback = format!("{}/{}", EXPORT_IMAGES_DIR, original.path.file_name().unwrap_or_default().to_string_lossy());
+    let img = img.replacen(
+        &format!("src=\"{}\"", src),
+        &format!(r#"src="{}" srcset="{}" sizes="{}""#, fallback, srcset(original.format), sizes),
+        1,
+    );
+    format!("<picture>{}{}</picture>", sources, img)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_optimize_export_writes_variants_and_srcset() {
+        let deck = tempfile::TempDir::new().unwrap();
+        let out = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(deck.path().join("img")).unwrap();
+        image::RgbImage::from_pixel(800, 450, image::Rgb([200, 40, 40])).save(deck.path().join("img/photo.jpg")).unwrap();
+
+        let config = ImageConfig { widths: vec![320, 640], formats: vec![], ..Default::default() };
+        let html = r#"<p><img src="img/photo.jpg" alt="Red"><img src="https://example.com/x.png"></p>"#;
+        let result = optimize_export(html, deck.path(), out.path(), &config).unwrap();
+
+        assert_eq!(result.images, 1);
+        assert!(result.html.starts_with("<p><picture><img src=\"images/photo-"), "{}", result.html);
+        assert!(result.html.contains("-320.jpg 320w, images/photo-"), "{}", result.html);
+        assert!(result.html.contains(r#"alt="Red"></picture><img src="https://example.com/x.png">"#), "{}", result.html);
+        let copies: Vec<_> = std::fs::read_dir(out.path().join("images")).unwrap().collect();
+        assert_eq!(copies.len(), 2);
+        assert_eq!(target_widths(800, &[640, 1280]), vec![640, 800]);
+        assert_eq!(target_widths(4000, &[640, 1280]), vec![640, 1280]);
+    }
+}
@@ -0,0 +1,139 @@
+/*!
+ * Kiosk mode: the deck page auto-advancing on the `[kiosk]` timings, for unattended screens
+ *
+ * The page is the same one `/` serves, with a config block and a small player
+ * script appended. The player drives the runtime through `location.hash`, like a
+ * visitor following a deep link would.
+ */
+
+use coolslides_core::{DeckManifest, KioskConfig};
+use serde_json::json;
+
+const KIOSK_STYLE: &str = r#"<style>
+  body.coolslides-kiosk, body.coolslides-kiosk * { cursor: none !important; }
+  body.coolslides-kiosk.locked { user-select: none; }
+  body.coolslides-kiosk.locked * { pointer-events: none !important; }
+</style>"#;
+
+const KIOSK_SCRIPT: &str = r#"<script type="module">
+  const config = JSON.parse(document.getElementById('coolslides-kiosk').textContent);
+  const slides = config.slides;
+  const inputEvents = ['keydown', 'keyup', 'pointerdown', 'pointerup', 'click', 'wheel', 'touchstart', 'touchmove', 'contextmenu'];
+  let index = 0;
+  let timer = null;
+
+  document.body.classList.add('coolslides-kiosk');
+  if (!config.interactive) document.body.classList.add('locked');
+
+  function show(i) {
+    index = i;
+    const target = '#' + slides[i].id;
+    if (location.hash !== target) location.hash = target;
+    clearTimeout(timer);
+    timer = setTimeout(advance, slides[i].duration * 1000);
+  }
+
+  function advance() {
+    if (index + 1 < slides.length) show(index + 1);
+    else if (config.loop) show(0);
+  }
+
+  if (config.interactive) {
+    // A visitor takes over; the loop resumes from the start once they walk away
+    const idle = () => {
+      clearTimeout(timer);
+      timer = setTimeout(() => show(0), config.idleRestart * 1000);
+    };
+    inputEvents.forEach(type => window.addEventListener(type, idle, { capture: true, passive: true }));
+  } else {
+    const block = event => {
+      event.preventDefault();
+      event.stopImmediatePropagation();
+    };
+    inputEvents.forEach(type => window.addEventListener(type, block, { capture: true, passive: false }));
+  }
+
+  if (slides.length > 0) show(0);
+</script>"#;
+
+/// Per-slide schedule and behavior for the player script
+pub(crate) fn config_json(deck: &DeckManifest) -> serde_json::Value {
+    let config = deck.kiosk.clone().unwrap_or_default();
+    json!({
+        "slides": deck
+            .slide_order()
+            .into_iter()
+            .map(|id| json!({ "id": id, "duration": duration(&config, id) }))
+            .collect::<Vec<_>>(),
+        "loop": config.looping,
+        "interactive": config.interactive,
+        "idleRestart": config.idle_restart.max(1.0),
+    })
+}
+
+/// Append the kiosk player to a rendered deck page
+pub(crate) fn inject(html: &str, deck: &DeckManifest) -> String {
+    // `</` would end the data block early
+    let config = config_json(deck).to_string().replace("</", "<\\/");
+    let markup = format!(
+        "{}\n<script type=\"application/json\" id=\"coolslides-kiosk\">{}</script>\n{}\n",
+        KIOSK_STYLE, config, KIOSK_SCRIPT
+    );
+    match html.rfind("</body>") {
+        Some(at) => format!("{}{}{}", &html[..at], markup, &html[at..]),
+        None => format!("{}{}", html, markup),
+    }
+}
+
+/// Seconds on a slide; zero and negative timings fall back to the deck's default, then to ours
+pub(crate) fn duration(config: &KioskConfig, slide_id: &str) -> f64 {
+    [config.duration(slide_id), config.default_duration]
+        .into_iter()
+        .find(|seconds| *seconds > 0.0)
+        .unwrap_or(KioskConfig::default().default_duration)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kiosk_schedule_follows_sequence_and_overrides() {
+        let deck: DeckManifest = toml::from_str(
+            r#"
+modelVersion = "1.0"
+title = "Booth"
+theme = "default"
+sequence = [
+  { ref = "intro" },
+  { name = "Demo", slides = ["demo-1", "demo-2"] },
+]
+
+[transitions]
+default = "none"
+
+[kiosk]
+defaultDuration = 8
+durations = { "demo-2" = 20, "intro" = 0 }
+loop = false
+"#,
+        )
+        .unwrap();
+
+        let config = config_json(&deck);
+        assert_eq!(
+            config["slides"],
+            json!([
+                { "id": "intro", "duration": 8.0 },
+                { "id": "demo-1", "duration": 8.0 },
+                { "id": "demo-2", "duration": 20.0 },
+            ])
+        );
+        assert_eq!(config["loop"], json!(false));
+        assert_eq!(config["interactive"], json!(false));
+
+        let page = inject("<html><body><main></main></body></html>", &deck);
+        assert!(page.ends_with("</script>\n</body></html>"), "{}", page);
+        assert!(page.contains(r#"id="coolslides-kiosk">{"#));
+    }
+}
@@ -0,0 +1,111 @@
+/*!
+ * LAN presentation mode: audience devices join from a QR code
+ *
+ * With `--lan` the server listens on every interface and opens a room for the
+ * talk. `/join` shows a QR code for the audience page of that room, using the
+ * machine's LAN address, so people in the room can scan it from the projector.
+ */
+
+use crate::html_escape;
+use qrcode::{render::svg, QrCode};
+use std::net::{IpAddr, UdpSocket};
+
+/// Where audience devices reach the server, and the room they follow
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LanConfig {
+    /// `http://<lan ip>:<port>`; `None` when no LAN address was found, and
+    /// `/join` uses the address the page was requested on instead
+    pub base_url: Option<String>,
+    pub room: String,
+}
+
+impl LanConfig {
+    pub fn new(port: u16, room: impl Into<String>) -> Self {
+        Self {
+            base_url: detect_ip().map(|ip| base_url(ip, port)),
+            room: room.into(),
+        }
+    }
+}
+
+/// The address other machines on the network reach this one at, if any
+pub fn detect_ip() -> Option<IpAddr> {
+    // Connecting a UDP socket only picks the outgoing interface; nothing is sent
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("192.0.2.1:80").ok()?;
+    let ip = socket.local_addr().ok()?.ip();
+    (!ip.is_loopback() && !ip.is_unspecified()).then_some(ip)
+}
+
+pub fn base_url(ip: IpAddr, port: u16) -> String {
+    match ip {
+        IpAddr::V4(ip) => format!("http://{}:{}", ip, port),
+        IpAddr::V6(ip) => format!("http://[{}]:{}", ip, port),
+    }
+}
+
+/// The audience page following `room`
+pub fn audience_url(base_url: &str, room: &str) -> String {
+    format!("{}/audience?room={}", base_url.trim_end_matches('/'), room)
+}
+
+/// Short id for the talk's room, easy to read out if the QR code will not scan
+pub fn new_room_id() -> String {
+    uuid::Uuid::new_v4().simple().to_string()[..6].to_string()
+}
+
+/// The join page: the deck title, a QR code for `url`, and `url` as text
+pub(crate) fn join_page(title: &str, url: &str) -> anyhow::Result<String> {
+    let code = QrCode::new(url.as_bytes())?;
+    let svg = code
+        .render::<svg::Color>()
+        .min_dimensions(320, 320)
+        .build();
+    // Inline the `<svg>` element without its XML declaration
+    let svg = svg.find("<svg").map_or(svg.as_str(), |start| &svg[start..]);
+    Ok(format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+    <title>Join {title}</title>
+    <meta charset="utf-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1">
+    <style>
+      html, body {{ margin: 0; height: 100%; background: #fff; color: #111; }}
+      body {{ display: flex; flex-direction: column; align-items: center; justify-content: center; gap: 24px; font: 500 24px/1.4 system-ui, sans-serif; text-align: center; }}
+      h1 {{ font-size: 40px; margin: 0; }}
+      .qr svg {{ width: min(60vh, 80vw); height: auto; }}
+      a {{ color: inherit; font-family: ui-monospace, monospace; word-break: break-all; }}
+    </style>
+</head>
+<body>
+    <h1>{title}</h1>
+    <div class="qr">{svg}</div>
+    <p>Scan to follow along, or open <a href="{url}">{url}</a></p>
+</body>
+</html>
+"#,
+        title = html_escape(title),
+        svg = svg,
+        url = html_escape(url),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_join_page_links_the_room_audience_page() {
+        let ip: IpAddr = "192.168.1.20".parse().unwrap();
+        let url = audience_url(&base_url(ip, 5173), "a1b2c3");
+        assert_eq!(url, "http://192.168.1.20:5173/audience?room=a1b2c3");
+        assert_eq!(base_url("fe80::1".parse().unwrap(), 80), "http://[fe80::1]:80");
+
+        let page = join_page("Q3 <Review>", &url).unwrap();
+        assert!(page.contains("<svg"), "{}", page);
+        assert!(!page.contains("<?xml"));
+        assert!(page.contains("Q3 &lt;Review&gt;"));
+        assert!(page.contains(r#"href="http://192.168.1.20:5173/audience?room=a1b2c3""#));
+    }
+}
@@ -1,35 +1,100 @@
 use axum::{
-    extract::{Path as AxumPath, State, WebSocketUpgrade},
-    http::{StatusCode, header},
+    extract::{DefaultBodyLimit, Path as AxumPath, Query, State, WebSocketUpgrade},
+    http::{HeaderMap, StatusCode, header},
     response::{Html, Json, Response},
-    routing::{get, post},
+    routing::{get, patch, post},
     Router,
     body::Body,
 };
-use coolslides_core::{DeckManifest, SlideDoc, components, ComponentRegistry};
+use coolslides_core::{DeckManifest, SlideDoc, ComponentRegistry, SanitizationPolicy, ImportMap, Lockfile, SlotDeclaration, cache::ValidationCache, components, policy::SecurityPolicy, registry, validation, vendor};
 use chrono::Utc;
 use serde::Deserialize;
-use std::{collections::HashMap, path::{Path, PathBuf}, sync::Arc};
+use std::{collections::{BTreeMap, HashMap}, path::{Path, PathBuf}, sync::Arc};
 use tokio::sync::RwLock;
-use tower_http::{cors::CorsLayer, services::ServeDir, trace::TraceLayer};
+use tower_http::{services::ServeDir, trace::TraceLayer};
 use tokio::fs;
 use notify::{RecommendedWatcher, RecursiveMode, Watcher};
 use pulldown_cmark::{Parser, html};
 use maplit::{hashset, hashmap};
 
+pub mod analytics;
+mod assets;
+mod audience;
+pub mod bench;
+mod caching;
+pub mod collab;
+mod compression;
+pub mod config;
+mod csp;
+pub mod decks;
+pub mod cors;
+mod diagrams;
+mod editing;
 pub mod export;
+mod git;
+pub mod hmr;
+pub mod hub;
+pub mod images;
+mod kiosk;
+pub mod lan;
+pub mod logging;
+mod narration;
+mod presenter;
+mod proxy;
+pub mod recordings;
+mod render_cache;
+pub mod room_fanout;
+pub mod room_store;
 pub mod rooms;
+pub mod server_plugins;
+pub mod status;
+pub mod storage;
+mod teleprompter;
+mod thumbnails;
+pub mod visual;
+pub mod webhooks;
 
 /// Configuration for HTML sanitization
 #[derive(Clone)]
 pub struct SanitizationConfig {
     pub strict_mode: bool,
     pub allow_math: bool,
+    /// The deck's `[sanitization]` additions; strict mode ignores them
+    pub policy: SanitizationPolicy,
 }
 
 impl SanitizationConfig {
-    pub fn new(strict_mode: bool) -> Self { Self { strict_mode, allow_math: false } }
+    pub fn new(strict_mode: bool) -> Self { Self { strict_mode, allow_math: false, policy: SanitizationPolicy::default() } }
     pub fn with_math(mut self, allow: bool) -> Self { self.allow_math = allow; self }
+    pub fn with_policy(mut self, policy: SanitizationPolicy) -> Self { self.policy = policy; self }
+
+    /// This config with the math plugin and `[sanitization]` settings of `deck`;
+    /// the deck's rules are added to this config's own
+    pub fn for_deck(&self, deck: &DeckManifest) -> Self {
+        let policy = match &deck.sanitization {
+            Some(extra) => self.policy.merged(extra),
+            None => self.policy.clone(),
+        };
+        Self::new(self.strict_mode).with_math(uses_math_plugin(deck)).with_policy(policy)
+    }
+
+    /// The config for a Markdown slot named `slot`, with its per-slot rules merged in
+    fn for_slot(&self, slot: &str) -> std::borrow::Cow<'_, Self> {
+        if !self.policy.slots.contains_key(slot) {
+            return std::borrow::Cow::Borrowed(self);
+        }
+        let policy = SanitizationPolicy { rules: self.policy.for_slot(slot), slots: Default::default() };
+        std::borrow::Cow::Owned(Self { policy, ..self.clone() })
+    }
+}
+
+/// Whether the deck loads the math plugin, which turns on math rendering and its sanitizer
+fn uses_math_plugin(deck: &DeckManifest) -> bool {
+    deck.plugins.iter().any(|p| p.contains("plugins-math") || p.contains("/math/") || p.ends_with("math"))
+}
+
+fn analytics_enabled(deck: &DeckManifest) -> bool {
+    deck.analytics.as_ref().is_some_and(|config| config.enabled)
 }
 
 /// Development server state
@@ -41,33 +106,328 @@ pub struct AppState {
     pub sanitization_config: SanitizationConfig,
     pub components: Arc<RwLock<Option<ComponentRegistry>>>,
     pub deck_root: Arc<RwLock<Option<PathBuf>>>,
+    /// Per-slide validation results reused across reloads
+    pub validation_cache: Arc<std::sync::Mutex<ValidationCache>>,
+    /// Deck files as of the last load, so reloads only re-parse what changed
+    loaded_files: Arc<RwLock<HashMap<PathBuf, LoadedFile>>>,
+    /// View-event aggregate, when the deck enables `[analytics]`
+    pub analytics: Arc<analytics::Analytics>,
+    /// Room and deck events, delivered to the deck's webhooks
+    pub events: webhooks::EventSender,
+    /// Server plugins: registered ones plus the deck's `[[serverPlugins]]`
+    pub plugins: Arc<server_plugins::PluginHost>,
+    /// Path prefix the deck is served under, e.g. `/decks/q3-review` in a hub
+    pub mount_path: Option<String>,
+    /// SQLite database rooms, analytics, and export jobs persist to (`--db`)
+    pub storage: Option<Arc<storage::Storage>>,
+    /// Deck files open for collaborative editing
+    pub collab: Arc<collab::Collab>,
+    /// What file changes did, for pages connected to `/ws/hmr`
+    pub hmr: hmr::HmrSender,
+    /// Reload, validation, and export progress, for `/api/events`
+    pub status: status::StatusSender,
+    /// Which other origins may use the API and rooms
+    pub cors: cors::CorsConfig,
+    /// The talk's room and LAN address, when sharing on the local network (`--lan`)
+    pub lan: Option<lan::LanConfig>,
+    /// Token for the presenter page, recording, and the presenter room role;
+    /// without one, every client may present
+    pub presenter_token: Option<String>,
+    /// Directories under the deck served by URL prefix, besides `assets/`
+    pub asset_mounts: BTreeMap<String, PathBuf>,
+    /// `[watch]` globs for the file watcher
+    pub watch: config::WatchConfig,
+    /// `[rooms]` limits for rooms the room manager opens
+    pub room_limits: rooms::RoomLimits,
+    /// Other servers the rooms are shared with (`--redis`)
+    pub room_fanout: Option<Arc<room_fanout::Fanout>>,
+}
+
+/// A deck file as it was when last parsed
+#[derive(Clone, PartialEq)]
+struct LoadedFile {
+    modified: Option<std::time::SystemTime>,
+    len: u64,
+    hash: u64,
+    /// Id of the slide the file defines; `None` for the manifest
+    slide_id: Option<String>,
+}
+
+impl LoadedFile {
+    /// Whether the file's metadata still matches, so it need not be read again
+    fn unchanged(&self, metadata: &std::fs::Metadata) -> bool {
+        self.modified.is_some() && self.modified == metadata.modified().ok() && self.len == metadata.len()
+    }
+}
+
+/// Read a deck file unless its metadata matches the last load. Returns the file's new
+/// record, with the content only when it differs from what was last parsed.
+async fn read_if_changed(
+    path: &Path,
+    loaded_files: &HashMap<PathBuf, LoadedFile>,
+) -> anyhow::Result<Option<(LoadedFile, Option<String>)>> {
+    let metadata = fs::metadata(path).await?;
+    let previous = loaded_files.get(path);
+    if previous.is_some_and(|file| file.unchanged(&metadata)) {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(path).await?;
+    let file = LoadedFile {
+        modified: metadata.modified().ok(),
+        len: metadata.len(),
+        hash: content_hash(&content),
+        slide_id: previous.and_then(|file| file.slide_id.clone()),
+    };
+    let content = match previous {
+        Some(previous) if previous.hash == file.hash => None,
+        _ => Some(content),
+    };
+    Ok(Some((file, content)))
+}
+
+fn content_hash(content: &str) -> u64 {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// What a load changed in the in-memory deck
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeckChanges {
+    /// `slides.toml` was parsed again
+    pub manifest: bool,
+    /// Ids of slides that were added or edited
+    pub slides: Vec<String>,
+    /// Ids of slides that no longer exist
+    pub removed: Vec<String>,
+}
+
+impl DeckChanges {
+    pub fn is_empty(&self) -> bool {
+        !self.manifest && self.slides.is_empty() && self.removed.is_empty()
+    }
+}
+
+impl std::fmt::Display for DeckChanges {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts = Vec::new();
+        if self.manifest {
+            parts.push("slides.toml".to_string());
+        }
+        if !self.slides.is_empty() {
+            parts.push(format!("slides {}", self.slides.join(", ")));
+        }
+        if !self.removed.is_empty() {
+            parts.push(format!("removed {}", self.removed.join(", ")));
+        }
+        write!(f, "{}", if parts.is_empty() { "nothing".to_string() } else { parts.join("; ") })
+    }
+}
+
+/// Quiet time after the last file event before a batch of changes is handled
+const WATCH_DEBOUNCE: std::time::Duration = std::time::Duration::from_millis(100);
+/// Longest a batch waits for events to stop, so steady writes are still picked up
+const WATCH_MAX_DELAY: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Paths of `first` and of every event that follows until writes settle, sorted
+/// and without duplicates
+async fn collect_changed_paths(
+    rx: &mut tokio::sync::mpsc::UnboundedReceiver<notify::Event>,
+    first: notify::Event,
+) -> Vec<PathBuf> {
+    let deadline = tokio::time::Instant::now() + WATCH_MAX_DELAY;
+    let mut paths = first.paths;
+    loop {
+        let wait = (tokio::time::Instant::now() + WATCH_DEBOUNCE).min(deadline);
+        match tokio::time::timeout_at(wait, rx.recv()).await {
+            Ok(Some(event)) => paths.extend(event.paths),
+            // Timed out, or the watcher stopped
+            _ => break,
+        }
+    }
+    paths.sort();
+    paths.dedup();
+    paths
+}
+
+impl Default for AppState {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl AppState {
     pub fn new() -> Self {
+        let (events, _) = tokio::sync::broadcast::channel(256);
+        let plugins = Arc::new(server_plugins::PluginHost::new());
         Self {
-            room_manager: Arc::new(rooms::RoomManager::new()),
+            room_manager: Arc::new(rooms::RoomManager::with_events(events.clone()).with_plugins(plugins.clone())),
             deck: Arc::new(RwLock::new(None)),
             slides: Arc::new(RwLock::new(HashMap::new())),
             sanitization_config: SanitizationConfig::new(false), // Default to non-strict
             components: Arc::new(RwLock::new(None)),
             deck_root: Arc::new(RwLock::new(None)),
+            validation_cache: Arc::new(std::sync::Mutex::new(ValidationCache::new())),
+            loaded_files: Arc::new(RwLock::new(HashMap::new())),
+            analytics: Arc::new(analytics::Analytics::new()),
+            events,
+            plugins,
+            mount_path: None,
+            storage: None,
+            collab: Arc::new(collab::Collab::new()),
+            hmr: hmr::channel(),
+            status: status::channel(),
+            cors: cors::CorsConfig::default(),
+            lan: None,
+            presenter_token: None,
+            asset_mounts: BTreeMap::new(),
+            watch: config::WatchConfig::default(),
+            room_limits: rooms::RoomLimits::default(),
+            room_fanout: None,
         }
     }
     
     pub fn new_with_strict_mode(strict_mode: bool) -> Self {
+        let (events, _) = tokio::sync::broadcast::channel(256);
+        let plugins = Arc::new(server_plugins::PluginHost::new());
         Self {
-            room_manager: Arc::new(rooms::RoomManager::new()),
+            room_manager: Arc::new(rooms::RoomManager::with_events(events.clone()).with_plugins(plugins.clone())),
             deck: Arc::new(RwLock::new(None)),
             slides: Arc::new(RwLock::new(HashMap::new())),
             sanitization_config: SanitizationConfig::new(strict_mode),
             components: Arc::new(RwLock::new(None)),
             deck_root: Arc::new(RwLock::new(None)),
+            validation_cache: Arc::new(std::sync::Mutex::new(ValidationCache::new())),
+            loaded_files: Arc::new(RwLock::new(HashMap::new())),
+            analytics: Arc::new(analytics::Analytics::new()),
+            events,
+            plugins,
+            mount_path: None,
+            storage: None,
+            collab: Arc::new(collab::Collab::new()),
+            hmr: hmr::channel(),
+            status: status::channel(),
+            cors: cors::CorsConfig::default(),
+            lan: None,
+            presenter_token: None,
+            asset_mounts: BTreeMap::new(),
+            watch: config::WatchConfig::default(),
+            room_limits: rooms::RoomLimits::default(),
+            room_fanout: None,
+        }
+    }
+
+    /// Answer cross-origin requests as `config` allows
+    pub fn with_cors(mut self, config: cors::CorsConfig) -> Self {
+        self.cors = config;
+        self
+    }
+
+    /// Require `token` for the presenter page, recording, and presenting in rooms
+    pub fn with_presenter_token(mut self, token: impl Into<String>) -> Self {
+        self.presenter_token = Some(token.into());
+        self
+    }
+
+    /// Add `policy` to the sanitizer for every deck, beneath the deck's own `[sanitization]`
+    pub fn with_sanitization(mut self, policy: SanitizationPolicy) -> Self {
+        self.sanitization_config = self.sanitization_config.with_policy(policy);
+        self
+    }
+
+    /// Reload for the files `watch` includes, and not for those it ignores
+    pub fn with_watch(mut self, watch: config::WatchConfig) -> Self {
+        self.watch = watch;
+        self
+    }
+
+    /// Also serve each directory of `mounts`, relative to the deck, under its URL prefix
+    pub fn with_asset_mounts(mut self, mounts: BTreeMap<String, PathBuf>) -> Self {
+        self.asset_mounts = mounts;
+        self
+    }
+
+    /// Whether a request presents the presenter token, as `Authorization: Bearer`
+    /// or `?token=`; any request does when no token is set
+    fn presenter_authorized(&self, headers: &HeaderMap, token: Option<String>) -> bool {
+        match &self.presenter_token {
+            Some(expected) => presented_token(headers, token).is_some_and(|token| constant_time_eq(token.as_bytes(), expected.as_bytes())),
+            None => true,
+        }
+    }
+
+    /// The file slide `id` was last loaded from
+    async fn slide_file(&self, id: &str) -> Option<PathBuf> {
+        let loaded_files = self.loaded_files.read().await;
+        loaded_files.iter().find(|(_, file)| file.slide_id.as_deref() == Some(id)).map(|(path, _)| path.clone())
+    }
+
+    /// Serve `/join` for audience devices on the local network
+    pub fn with_lan(mut self, config: lan::LanConfig) -> Self {
+        self.lan = Some(config);
+        self
+    }
+
+    /// Serve the deck under `prefix` rather than at the root
+    pub fn with_mount_path(mut self, prefix: impl Into<String>) -> Self {
+        self.mount_path = Some(prefix.into());
+        self
+    }
+
+    /// Cap and buffer rooms as `limits` says. Rooms open after this call, and
+    /// [`AppState::with_storage`] and [`AppState::with_room_store`] keep the limits.
+    pub fn with_room_limits(mut self, limits: rooms::RoomLimits) -> Self {
+        self.room_limits = limits;
+        self.room_manager = Arc::new(self.new_room_manager());
+        self
+    }
+
+    /// Share rooms with other servers through `fanout`. Like
+    /// [`AppState::with_room_limits`], this starts a new room manager.
+    pub fn with_room_fanout(mut self, fanout: Arc<room_fanout::Fanout>) -> Self {
+        self.room_fanout = Some(fanout);
+        self.room_manager = Arc::new(self.new_room_manager());
+        self
+    }
+
+    fn new_room_manager(&self) -> rooms::RoomManager {
+        let rooms = rooms::RoomManager::with_events(self.events.clone())
+            .with_plugins(self.plugins.clone())
+            .with_limits(self.room_limits);
+        match &self.room_fanout {
+            Some(fanout) => rooms.with_fanout(fanout.clone()),
+            None => rooms,
         }
     }
 
-    /// Load deck manifest and slides from filesystem
+    /// Persist rooms, analytics, and export jobs in `storage`. Stored rooms are
+    /// restored by [`rooms::RoomManager::restore`].
+    pub fn with_storage(mut self, storage: Arc<storage::Storage>) -> Self {
+        let rooms = self.new_room_manager();
+        let store = room_store::RoomStore::Sqlite(storage.clone());
+        self.room_manager = Arc::new(rooms.with_store(Arc::new(store)));
+        self.analytics = Arc::new(analytics::Analytics::with_storage(storage.clone()));
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Persist rooms, and nothing else, in `store`
+    pub fn with_room_store(mut self, store: room_store::RoomStore) -> Self {
+        let rooms = self.new_room_manager();
+        self.room_manager = Arc::new(rooms.with_store(Arc::new(store)));
+        self
+    }
+
+    /// Load deck manifest and slides from filesystem. Files whose mtime, size, and
+    /// content are unchanged since the last load are not parsed again, and the
+    /// in-memory deck is only touched when something changed.
     pub async fn load_from_directory(&self, deck_dir: impl AsRef<Path>) -> anyhow::Result<()> {
+        self.load_changes(deck_dir).await.map(|_| ())
+    }
+
+    /// Like [`AppState::load_from_directory`], reporting what changed
+    pub async fn load_changes(&self, deck_dir: impl AsRef<Path>) -> anyhow::Result<DeckChanges> {
         let deck_dir = deck_dir.as_ref();
         
         // Load deck manifest from slides.toml
@@ -75,13 +435,27 @@ impl AppState {
         if !manifest_path.exists() {
             return Err(anyhow::anyhow!("No slides.toml found in {:?}", deck_dir));
         }
+
+        // A different deck starts from scratch
+        if self.deck_root.read().await.as_deref() != Some(deck_dir) {
+            self.loaded_files.write().await.clear();
+        }
+        let mut loaded_files = self.loaded_files.write().await;
+        let mut updates: Vec<(PathBuf, LoadedFile)> = Vec::new();
+
+        let mut deck_manifest: Option<DeckManifest> = None;
+        if let Some((file, content)) = read_if_changed(&manifest_path, &loaded_files).await? {
+            if let Some(content) = content {
+                deck_manifest = Some(toml::from_str(&content)?);
+            }
+            updates.push((manifest_path, file));
+        }
         
-        let manifest_content = fs::read_to_string(&manifest_path).await?;
-        let deck_manifest: DeckManifest = toml::from_str(&manifest_content)?;
-        
-        // Load all slide files from content/ directory
+        // Parse slide files in content/ whose contents changed
         let content_dir = deck_dir.join("content");
-        let mut slides_map = HashMap::new();
+        let mut changed_slides = Vec::new();
+        let mut stale_ids = Vec::new();
+        let mut present = std::collections::HashSet::new();
         
         if content_dir.exists() {
             let mut entries = fs::read_dir(&content_dir).await?;
@@ -90,16 +464,51 @@ impl AppState {
                 if path.extension().and_then(|s| s.to_str()) == Some("toml") 
                     && path.file_stem().and_then(|s| s.to_str()).map(|s| s.ends_with(".slide")).unwrap_or(false) {
                     
-                    let slide_content = fs::read_to_string(&path).await?;
-                    let slide_doc: SlideDoc = toml::from_str(&slide_content)?;
-                    
-                    slides_map.insert(slide_doc.id.clone(), slide_doc);
+                    present.insert(path.clone());
+                    let Some((mut file, content)) = read_if_changed(&path, &loaded_files).await? else {
+                        continue;
+                    };
+                    if let Some(slide_content) = content {
+                        let slide_doc: SlideDoc = toml::from_str(&slide_content)?;
+                        // The file may now define a different slide
+                        stale_ids.extend(file.slide_id.take().filter(|id| *id != slide_doc.id));
+                        file.slide_id = Some(slide_doc.id.clone());
+                        changed_slides.push(slide_doc);
+                    }
+                    updates.push((path, file));
                 }
             }
         }
+        let removed: Vec<PathBuf> = loaded_files
+            .iter()
+            .filter(|(path, file)| file.slide_id.is_some() && !present.contains(*path))
+            .map(|(path, _)| path.clone())
+            .collect();
+        for path in &removed {
+            stale_ids.extend(loaded_files.remove(path).and_then(|file| file.slide_id));
+        }
+        // Files that were only touched get their new metadata without counting as changes
+        loaded_files.extend(updates);
+        drop(loaded_files);
+
+        let changed = usize::from(deck_manifest.is_some()) + changed_slides.len() + removed.len();
+        if changed == 0 {
+            return Ok(DeckChanges::default());
+        }
+        let mut changes = DeckChanges {
+            manifest: deck_manifest.is_some(),
+            slides: changed_slides.iter().map(|slide| slide.id.clone()).collect(),
+            removed: Vec::new(),
+        };
+        changes.slides.sort();
+        // A slide moved to another file is changed, not removed
+        changes.removed = stale_ids.iter().filter(|id| !changes.slides.contains(id)).cloned().collect();
+        changes.removed.sort();
+        changes.removed.dedup();
         
         // Update AppState
-        {
+        let manifest_changed = deck_manifest.is_some();
+        if let Some(deck_manifest) = deck_manifest {
             let mut deck = self.deck.write().await;
             *deck = Some(deck_manifest);
         }
@@ -108,173 +517,437 @@ impl AppState {
             *root = Some(deck_dir.to_path_buf());
         }
         
-        let slide_count = slides_map.len();
-        
-        {
+        let slide_count = {
             let mut slides = self.slides.write().await;
-            *slides = slides_map;
-        }
+            for id in &stale_ids {
+                slides.remove(id);
+            }
+            for slide in changed_slides {
+                slides.insert(slide.id.clone(), slide);
+            }
+            slides.len()
+        };
         
-        // Try to load component manifests (prefer generated JSON, fallback to TS source)
-        let manifests_candidates = [
-            Path::new("packages/components/manifests"),        // From project root
-            Path::new("../../packages/components/manifests"),  // From examples/basic-deck
-            Path::new("../packages/components/manifests"),     // From apps/devserver
-        ];
-        let src_candidates = [
-            Path::new("packages/components/src"),        // From project root
-            Path::new("../../packages/components/src"),  // From examples/basic-deck
-            Path::new("../packages/components/src"),     // From apps/devserver
-        ];
-
-        let registry_opt = manifests_candidates
-            .iter()
-            .find(|p| p.exists())
-            .and_then(|dir| components::extract_manifests_from_manifests_dir(dir).ok())
-            .or_else(|| {
-                src_candidates
-                    .iter()
-                    .find(|p| p.exists())
-                    .and_then(|components_dir| components::extract_manifests_from_directory(components_dir).ok())
-            });
+        if manifest_changed {
+            let deck = self.deck.read().await.clone();
+            if let Some(deck) = deck {
+                self.load_registry(&deck, deck_dir).await;
+                self.plugins.load_deck_plugins(deck_dir, &deck.server_plugins);
+            }
+        }
 
-        {
-            let mut comps = self.components.write().await;
-            *comps = registry_opt;
+        tracing::info!(slides = slide_count, changed, "loaded deck");
+        self.revalidate().await;
+        Ok(changes)
+    }
+    
+    /// Load component registry layers: deck components, workspace, node_modules, remote
+    async fn load_registry(&self, deck: &DeckManifest, deck_dir: &Path) {
+        let loaded_registry = registry::load_deck_registry(deck, deck_dir);
+        for warning in &loaded_registry.warnings {
+            tracing::warn!("{}", warning);
         }
+        for conflict in loaded_registry.diagnostics().warnings {
+            tracing::warn!("{}", conflict);
+        }
+        for rename in loaded_registry.registry.iter().flat_map(|r| &r.tag_renames) {
+            tracing::info!(component = %rename.component, original = %rename.original, tag = %rename.tag, "renamed component tag");
+        }
+
+        let mut comps = self.components.write().await;
+        *comps = loaded_registry.registry;
+    }
 
-        println!("Loaded deck manifest and {} slides", slide_count);
+    /// Rebuild the component registry for the loaded deck and revalidate,
+    /// leaving the deck and slides as they are
+    pub async fn reload_components(&self, deck_dir: impl AsRef<Path>) -> anyhow::Result<()> {
+        let deck = self.deck.read().await.clone();
+        let Some(deck) = deck else {
+            return Err(anyhow::anyhow!("No deck loaded"));
+        };
+        self.load_registry(&deck, deck_dir.as_ref()).await;
+        let count = self.components.read().await.as_ref().map_or(0, |r| r.components.len());
+        tracing::info!(components = count, "reloaded components");
+        self.revalidate().await;
         Ok(())
     }
+
+    /// Validate the loaded deck, re-checking only slides that changed since the last load,
+    /// and log any diagnostics
+    pub async fn revalidate(&self) -> validation::ValidationResult {
+        if self.deck.read().await.is_none() {
+            return validation::ValidationResult::new();
+        }
+        let result = self.validate().await;
+        {
+            let cache = self.validation_cache.lock().unwrap_or_else(|e| e.into_inner());
+            tracing::info!(
+                errors = result.errors.len(),
+                warnings = result.warnings.len(),
+                revalidated = cache.misses(),
+                cached = cache.hits(),
+                "validated deck"
+            );
+        }
+        for error in &result.errors {
+            tracing::warn!(slide = error.slide_id(), "{}", error);
+        }
+        if self.status.receiver_count() > 0 {
+            let (errors, warnings) = diagnostics_json(self, &result).await;
+            let _ = self.status.send(status::StatusEvent::Validation { valid: result.is_valid(), errors, warnings });
+        }
+        result
+    }
     
-    /// Watch for file changes and reload using `notify`
+    /// Validate the in-memory deck against the component registry, reusing
+    /// cached results for unchanged slides
+    pub async fn validate(&self) -> validation::ValidationResult {
+        let deck = self.deck.read().await;
+        let Some(deck) = deck.as_ref() else {
+            return validation::ValidationResult::new();
+        };
+        let slides_map = self.slides.read().await;
+        let mut slides: Vec<SlideDoc> = slides_map.values().cloned().collect();
+        slides.sort_by(|a, b| a.id.cmp(&b.id));
+        let registry = self.components.read().await;
+        let deck_root = self.deck_root.read().await.clone();
+        
+        let mut cache = self.validation_cache.lock().unwrap_or_else(|e| e.into_inner());
+        let mut result = validation::validate_deck_cached(deck, &slides, registry.as_ref(), &mut cache);
+        if let Some(deck_root) = deck_root.as_deref() {
+            result.merge(validation::validate_narration(&slides, deck_root));
+        }
+        result
+    }
+
+    /// Watch for file changes and reload using `notify`. Events are batched until
+    /// writes settle; deck files re-parse only what changed, and component sources
+    /// and manifests only rebuild the registry. The `[watch]` globs add files that
+    /// reload pages and drop changes that should not.
     pub async fn start_file_watcher(&self, deck_dir: impl AsRef<Path>) -> anyhow::Result<()> {
-        use tokio::time::{sleep, Duration};
-        use std::time::Instant;
         let deck_dir = deck_dir.as_ref().to_path_buf();
+        let deck_dir_abs = deck_dir.canonicalize().unwrap_or_else(|_| deck_dir.clone());
+        // The deck's components/ may not exist yet; it is under the deck watch either way
+        let mut component_dirs = registry::watch_dirs(&deck_dir);
+        let local_components = deck_dir_abs.join("components");
+        if !component_dirs.contains(&local_components) {
+            component_dirs.push(local_components);
+        }
         let state = self.clone();
+        let watch = self.watch.matcher()?;
 
         // Channel to bridge blocking notify events into async
         let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
 
-        // Spawn blocking watcher thread
-        let watch_path = deck_dir.clone();
+        // Spawn blocking watcher thread; component directories outside the deck get their own watch
+        let mut watch_paths = vec![deck_dir.clone()];
+        watch_paths.extend(component_dirs.iter().filter(|dir| !dir.starts_with(&deck_dir_abs)).cloned());
+        // So do stylesheets linked from elsewhere, such as the shared `themes/`
+        let stylesheets = self.deck.read().await.as_ref().map(|deck| linked_stylesheets(deck, &deck_dir)).unwrap_or_default();
+        for dir in stylesheets.iter().filter_map(|(file, _)| file.parent()) {
+            if !dir.starts_with(&deck_dir_abs) && !watch_paths.iter().any(|path| dir.starts_with(path)) {
+                watch_paths.push(dir.to_path_buf());
+            }
+        }
         tokio::task::spawn_blocking(move || {
             let (wtx, wrx) = std::sync::mpsc::channel();
             let mut watcher: RecommendedWatcher = notify::recommended_watcher(wtx)
                 .expect("failed to create file watcher");
-            let _ = watcher.watch(&watch_path, RecursiveMode::Recursive);
+            for path in &watch_paths {
+                if let Err(e) = watcher.watch(path, RecursiveMode::Recursive) {
+                    tracing::warn!(path = %path.display(), error = %e, "could not watch");
+                }
+            }
             for res in wrx {
                 match res {
                     Ok(event) => {
                         let _ = tx.send(event);
                     }
-                    Err(e) => eprintln!("watch error: {}", e),
+                    Err(e) => tracing::warn!(error = %e, "file watcher error"),
                 }
             }
         });
 
-        // Async task to debounce and reload when relevant files change
+        // Async task to batch events and reload what they touched
         tokio::spawn(async move {
-            let mut last_reload: Option<Instant> = None;
             while let Some(event) = rx.recv().await {
+                // Editors save in several writes; handle them together once they settle
+                let mut paths = collect_changed_paths(&mut rx, event).await;
+                // `[watch]` globs are relative to the deck directory
+                let relative = |path: &PathBuf| -> Option<PathBuf> {
+                    let relative = path.strip_prefix(&deck_dir).or_else(|_| path.strip_prefix(&deck_dir_abs)).ok()?;
+                    Some(relative.to_path_buf())
+                };
+                paths.retain(|p| !relative(p).is_some_and(|rel| watch.ignores(&rel)));
+                let included = paths.iter().any(|p| relative(p).is_some_and(|rel| watch.includes(&rel)));
                 // Filter for relevant extensions
-                let relevant = event.paths.iter().any(|p| {
+                let deck_files: Vec<&PathBuf> = paths.iter().filter(|p| {
                     match p.extension().and_then(|s| s.to_str()) {
                         Some(ext) => matches!(ext, "toml" | "css" | "md"),
                         None => false,
                     }
+                }).collect();
+                let deck_changed = !deck_files.is_empty();
+                let components_changed = paths.iter().any(|p| {
+                    component_dirs.iter().any(|dir| p.starts_with(dir))
+                        && matches!(p.extension().and_then(|s| s.to_str()), Some("js" | "ts" | "json"))
                 });
-                if !relevant { continue; }
-
-                // Basic debounce
-                if let Some(last) = last_reload {
-                    if last.elapsed() < std::time::Duration::from_millis(200) {
+                if !deck_changed && !components_changed && !included { continue; }
+
+                // Theme and token edits swap the stylesheet in place, so pages keep their slide
+                if !components_changed && !included {
+                    let linked = state.deck.read().await.as_ref().map(|deck| linked_stylesheets(deck, &deck_dir)).unwrap_or_default();
+                    if let Some(hrefs) = stylesheet_updates(&linked, &deck_files) {
+                        for href in hrefs {
+                            tracing::info!(source = "file-watcher", href = %href, "swapped stylesheet in place");
+                            state.broadcast_css_update(&href).await;
+                        }
                         continue;
                     }
                 }
 
-                // Short delay to allow file writes to settle
-                sleep(Duration::from_millis(100)).await;
-                if let Err(e) = state.load_from_directory(&deck_dir).await {
-                    eprintln!("Failed to reload files: {}", e);
-                } else {
-                    println!("Reloaded deck files due to change");
-                    // Broadcast a reload message on the special reload room
-                    let reload_room = "__reload".to_string();
-                    let _ = state.room_manager.ensure_room(reload_room.clone()).await;
-                    if let Some(room) = state.room_manager.get_room(&reload_room).await {
-                        // Send prepare event first for overlay UX
-                        let _ = room.broadcast_message(rooms::RoomMessage::Event {
-                            event: rooms::EventData {
-                                name: "reload:prepare".to_string(),
-                                data: serde_json::json!({}),
-                                client_id: "server".to_string(),
-                            },
-                            timestamp: Utc::now(),
-                        }).await;
-                        // Follow with actual reload shortly after
-                        let room_clone = room.clone();
-                        tokio::spawn(async move {
-                            tokio::time::sleep(std::time::Duration::from_millis(150)).await;
-                            let _ = room_clone.broadcast_message(rooms::RoomMessage::Event {
-                                event: rooms::EventData {
-                                    name: "reload".to_string(),
-                                    data: serde_json::json!({}),
-                                    client_id: "server".to_string(),
-                                },
-                                timestamp: Utc::now(),
-                            }).await;
-                        });
+                let started = std::time::Instant::now();
+                let _ = state.status.send(status::StatusEvent::ReloadStarted { files: paths.len() });
+                let mut summary = Vec::new();
+                let mut failure = None;
+                let mut events = Vec::new();
+                if deck_changed {
+                    for path in &deck_files {
+                        state.collab.file_changed(path);
+                    }
+                    let components: HashMap<String, String> = state.slides.read().await.iter()
+                        .map(|(id, slide)| (id.clone(), slide.component.name.clone()))
+                        .collect();
+                    match state.load_changes(&deck_dir).await {
+                        Ok(changes) if changes.is_empty() => {
+                            // Saved without edits; other CSS and markdown are not tracked, so those still reload
+                            if deck_files.iter().any(|p| p.extension().and_then(|s| s.to_str()) != Some("toml")) {
+                                events.push(hmr::HmrEvent::DeckChanged);
+                            }
+                        }
+                        Ok(changes) => {
+                            tracing::info!(source = "file-watcher", files = deck_files.len(), "reloaded {}", changes);
+                            summary.push(changes.to_string());
+                            events.extend(state.deck_events(&changes, &components).await);
+                        }
+                        Err(e) => {
+                            tracing::error!(source = "file-watcher", error = %e, "could not reload deck files");
+                            failure = Some(e.to_string());
+                        }
+                    }
+                }
+                if components_changed {
+                    match state.reload_components(&deck_dir).await {
+                        Ok(()) => {
+                            summary.push("components".to_string());
+                            events.push(hmr::HmrEvent::ComponentsChanged);
+                        }
+                        Err(e) => {
+                            tracing::error!(source = "file-watcher", error = %e, "could not reload components");
+                            failure.get_or_insert_with(|| e.to_string());
+                        }
                     }
                 }
-                last_reload = Some(Instant::now());
+                if included {
+                    summary.push("watched files".to_string());
+                    events.push(hmr::HmrEvent::DeckChanged);
+                }
+                let duration_ms = started.elapsed().as_millis() as u64;
+                let _ = state.status.send(match failure {
+                    Some(error) => status::StatusEvent::ReloadFailed { error, duration_ms },
+                    None => status::StatusEvent::ReloadCompleted { changes: summary.join(", "), duration_ms },
+                });
+                if !events.is_empty() {
+                    state.broadcast_reload().await;
+                }
+                for event in events {
+                    let _ = state.hmr.send(event);
+                }
             }
         });
 
         Ok(())
     }
+
+    /// Hot-reload events for a deck load, rendering edited slides as the deck page does
+    async fn deck_events(&self, changes: &DeckChanges, components: &HashMap<String, String>) -> Vec<hmr::HmrEvent> {
+        let config = match self.deck.read().await.as_ref() {
+            Some(deck) => self.sanitization_config.for_deck(deck),
+            None => self.sanitization_config.clone(),
+        };
+        let registry = self.components.read().await;
+        let slides = self.slides.read().await;
+        hmr::deck_events(changes, components, &slides, |slide| generate_slide_html(slide, registry.as_ref(), &config))
+    }
+
+    /// Tell connected pages to reload the stylesheet linked as `href`, via the
+    /// `__reload` room and `/ws/hmr`
+    async fn broadcast_css_update(&self, href: &str) {
+        // Pages re-request the stylesheet with `?v=<version>` to get past the cache
+        let version = Utc::now().timestamp_millis();
+        let _ = self.hmr.send(hmr::HmrEvent::ThemeChanged { href: href.to_string(), version });
+        let reload_room = "__reload".to_string();
+        let _ = self.room_manager.ensure_room(reload_room.clone()).await;
+        if let Some(room) = self.room_manager.get_room(&reload_room).await {
+            let _ = room.broadcast_message(rooms::RoomMessage::Event {
+                event: rooms::EventData {
+                    name: "css:update".to_string(),
+                    data: serde_json::json!({ "href": href, "version": version }),
+                    client_id: "server".to_string(),
+                },
+                timestamp: Utc::now(),
+            }).await;
+        }
+    }
+
+    /// Tell connected pages to reload, via the special `__reload` room
+    async fn broadcast_reload(&self) {
+        let reload_room = "__reload".to_string();
+        let _ = self.room_manager.ensure_room(reload_room.clone()).await;
+        if let Some(room) = self.room_manager.get_room(&reload_room).await {
+            // Send prepare event first for overlay UX
+            let _ = room.broadcast_message(rooms::RoomMessage::Event {
+                event: rooms::EventData {
+                    name: "reload:prepare".to_string(),
+                    data: serde_json::json!({}),
+                    client_id: "server".to_string(),
+                },
+                timestamp: Utc::now(),
+            }).await;
+            // Follow with actual reload shortly after
+            tokio::spawn(async move {
+                tokio::time::sleep(std::time::Duration::from_millis(150)).await;
+                let _ = room.broadcast_message(rooms::RoomMessage::Event {
+                    event: rooms::EventData {
+                        name: "reload".to_string(),
+                        data: serde_json::json!({}),
+                        client_id: "server".to_string(),
+                    },
+                    timestamp: Utc::now(),
+                }).await;
+            });
+        }
+    }
 }
 
 /// Create the Axum router for the dev server
 pub fn create_router(state: AppState) -> Router {
-    Router::new()
+    assets::routes(static_files(Router::new()), &state.asset_mounts)
         // API routes
         .route("/", get(root_index))
+        .route("/slide/:id", get(slide_index))
+        .route("/kiosk", get(kiosk_index))
+        .route("/narrated", get(narrated_index))
         .route("/api/deck", get(get_deck))
-        .route("/api/slide/:id", get(get_slide))
+        .route("/api/deck/resolved", get(get_resolved_deck))
+        .route("/api/deck/sequence", patch(editing::patch_sequence))
+        .route("/api/slides", get(list_slides))
+        .route("/api/validate", post(validate_deck))
+        .route("/api/slide/:id", get(get_slide).put(editing::put_slide).delete(editing::delete_slide))
+        .route("/api/slide/:id/notes", get(editing::get_notes).put(editing::put_notes))
+        .route("/narration/:slide_id", get(serve_narration))
+        .route("/api/thumbnails/:slide_id", get(get_thumbnail))
+        .route("/api/slide/:id/thumbnail.png", get(get_thumbnail))
+        .route("/api/components", get(list_components))
+        .route("/api/components/:name", get(get_component))
+        .route("/api/components/:name/slots", get(get_component_slots))
         .route("/api/rooms/:room_id/record/start", post(start_recording))
         .route("/api/rooms/:room_id/record/stop", post(stop_recording))
         .route("/api/rooms/:room_id/dump", get(get_room_dump))
+        .route(
+            "/api/rooms/:room_id/recording",
+            post(import_recording).layer(DefaultBodyLimit::max(recordings::MAX_IMPORT_BYTES)),
+        )
+        .route("/api/rooms/:room_id/state", get(get_room_state))
+        .route("/api/rooms/:room_id/replay", post(start_replay).delete(stop_replay))
+        .route("/api/rooms/:room_id/polls", get(list_polls).post(create_poll))
+        .route("/api/rooms/:room_id/polls/:poll_id", get(get_poll))
+        .route("/api/rooms/:room_id/polls/:poll_id/close", post(close_poll))
+        .route("/api/rooms/:room_id/questions", get(list_questions))
+        .route("/api/rooms/:room_id/questions/:question_id/approve", post(approve_question))
+        .route("/api/rooms/:room_id/questions/:question_id/dismiss", post(dismiss_question))
+        .route("/api/rooms/:room_id/next", post(remote_next))
+        .route("/api/rooms/:room_id/prev", post(remote_prev))
+        .route("/api/rooms/:room_id/goto/:slide_id", post(remote_goto))
+        .route("/api/analytics", get(get_analytics).post(post_analytics_events))
+        .route("/api/exports", get(list_export_jobs))
+        .route("/api/events", get(status::events))
         .route("/api/export/pdf", post(export_pdf))
         .route("/api/export/html", post(export_html))
+        .route("/proxy/*path", get(proxy::serve))
         .route("/api/importmap", get(get_import_map))
         .route("/api/code/resolve", post(code_resolve))
+        .route("/components/*path", get(serve_deck_component))
+        .route("/vendor/*path", get(serve_vendored))
         .route("/healthz", get(health_check))
+        .route("/api/version", get(get_version))
         .route("/test/markdown", post(test_markdown_sanitization))
         
         // WebSocket routes
         .route("/rooms/:room_id", get(websocket_handler))
+        .route("/collab/*path", get(collab_handler))
+        .route("/ws/hmr", get(hmr_handler))
         
         // UI routes
         .route("/presenter", get(presenter_ui))
         .route("/audience", get(audience_ui))
+        .route("/teleprompter", get(teleprompter_ui))
+        .route("/join", get(join_ui))
         
-        // Static files
+        .layer(axum::middleware::from_fn_with_state(state.clone(), server_plugin_requests))
+        .layer(axum::middleware::from_fn_with_state(state.clone(), csp::content_security_policy))
+        .layer(axum::middleware::from_fn_with_state(state.clone(), cors::require_allowed_origin))
+        .layer(axum::middleware::from_fn(caching::no_store_by_default))
+        .layer(state.cors.layer())
+        .layer(compression::layer())
+        .layer(TraceLayer::new_for_http())
+        .with_state(state)
+}
+
+/// The Coolslides packages and themes, served from the working directory with
+/// cache validators; everything else defaults to not being stored
+fn static_files<S: Clone + Send + Sync + 'static>(router: Router<S>) -> Router<S> {
+    let assets = Router::new()
         .nest_service("/static", ServeDir::new("static"))
         .nest_service("/packages/runtime/dist", ServeDir::new("packages/runtime/dist"))
         .nest_service("/packages/components/dist", ServeDir::new("packages/components/dist"))
         .nest_service("/packages/component-sdk/dist", ServeDir::new("packages/component-sdk/dist"))
         .nest_service("/packages/plugins-stdlib/dist", ServeDir::new("packages/plugins-stdlib/dist"))
         .nest_service("/themes", ServeDir::new("themes"))
-        
-        .layer(CorsLayer::permissive())
-        .layer(TraceLayer::new_for_http())
-        .with_state(state)
+        .layer(axum::middleware::from_fn(caching::static_validators));
+    router.merge(assets)
 }
 
 /// Root index page serving the current deck
-async fn root_index(State(state): State<AppState>) -> Result<Html<String>, StatusCode> {
+async fn root_index(State(state): State<AppState>) -> Result<Response, StatusCode> {
+    deck_page(&state, DeckPlayer::None, None).await
+}
+
+/// The deck opened on one slide, so a shared link lands on it. Ids the
+/// sequence does not play are a 404.
+async fn slide_index(State(state): State<AppState>, AxumPath(id): AxumPath<String>) -> Result<Response, StatusCode> {
+    deck_page(&state, DeckPlayer::None, Some(&id)).await
+}
+
+/// The deck auto-advancing on its `[kiosk]` timings, for unattended screens
+async fn kiosk_index(State(state): State<AppState>) -> Result<Response, StatusCode> {
+    deck_page(&state, DeckPlayer::Kiosk, None).await
+}
+
+/// The deck playing each slide's narration and advancing when it ends
+async fn narrated_index(State(state): State<AppState>) -> Result<Response, StatusCode> {
+    deck_page(&state, DeckPlayer::Narrated, None).await
+}
+
+/// Script appended to the deck page to drive it without a presenter
+#[derive(Clone, Copy, PartialEq)]
+enum DeckPlayer {
+    None,
+    Kiosk,
+    Narrated,
+}
+
+async fn deck_page(state: &AppState, player: DeckPlayer, initial_slide: Option<&str>) -> Result<Response, StatusCode> {
     let deck = {
         let deck_guard = state.deck.read().await;
         deck_guard.as_ref().ok_or(StatusCode::NOT_FOUND)?.clone()
@@ -283,22 +956,89 @@ async fn root_index(State(state): State<AppState>) -> Result<Html<String>, Statu
         let slides_guard = state.slides.read().await;
         slides_guard.clone()
     };
+    let initial_slide = match initial_slide {
+        Some(id) if deck.slide_order().contains(&id) => Some(slides.get(id).ok_or(StatusCode::NOT_FOUND)?),
+        Some(_) => return Err(StatusCode::NOT_FOUND),
+        None => None,
+    };
     let components_registry = {
         let comps_guard = state.components.read().await;
         comps_guard.clone()
     };
-    let deck_root = {
-        let root_guard = state.deck_root.read().await;
-        root_guard.clone()
-    };
 
     // For dev root, do NOT set a file:// base href; let assets load via http
-    let allow_math = deck.plugins.iter().any(|p| p.contains("plugins-math") || p.contains("/math/") || p.ends_with("math"));
-    let config = SanitizationConfig { strict_mode: state.sanitization_config.strict_mode, allow_math };
-    let html = generate_export_html(&deck, &slides, components_registry.as_ref(), None, &config)
+    let config = state.sanitization_config.for_deck(&deck);
+    let deck_root = state.deck_root.read().await.clone();
+    let lockfile = deck_root.as_deref().and_then(read_lockfile);
+    let import_map = deck_root.as_deref().and_then(read_import_map);
+    let html = generate_export_html(&deck, &slides, components_registry.as_ref(), None, import_map.as_ref(), lockfile.as_ref(), &config)
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let html = match player {
+        DeckPlayer::None => html,
+        DeckPlayer::Kiosk => kiosk::inject(&html, &deck),
+        DeckPlayer::Narrated => narration::inject(&html, &deck, &slides),
+    };
+    let html = match initial_slide {
+        Some(slide) => deep_link(&html, &deck, slide),
+        None => html,
+    };
+    let html = if analytics_enabled(&deck) { analytics::inject(&html) } else { html };
+    let html = match &state.mount_path {
+        Some(prefix) => hub::mount_page(&html, prefix),
+        None => html,
+    };
+    let policy = page_policy(&deck, &slides, components_registry.as_ref(), import_map.as_ref(), lockfile.as_ref(), &html);
+
+    // The CSP layer adds a nonce and sends it
+    Ok(Response::builder()
+        .header(header::CONTENT_TYPE, "text/html; charset=utf-8")
+        .header("Permissions-Policy", policy.permissions_policy())
+        .extension(policy)
+        .body(Body::from(html))
+        .unwrap())
+}
+
+/// Open a deck page on `slide`: the runtime starts there when the URL has no
+/// hash, and the page title names the slide for link previews
+fn deep_link(html: &str, deck: &DeckManifest, slide: &SlideDoc) -> String {
+    let title = match slide.props["title"].as_str() {
+        Some(title) => format!("{} · {}", title, deck.title),
+        None => deck.title.clone(),
+    };
+    let head = format!(
+        "<title>{}</title>\n    <meta name=\"coolslides:initial-slide\" content=\"{}\">\n    <meta property=\"og:title\" content=\"{}\">",
+        html_escape(&title),
+        html_escape(&slide.id),
+        html_escape(&title)
+    );
+    html.replacen(&format!("<title>{}</title>", html_escape(&deck.title)), &head, 1)
+}
 
-    Ok(Html(html))
+/// CSP and Permissions-Policy for a rendered deck page: capabilities of the
+/// components its slides use, module and plugin hosts, and its inline scripts
+fn page_policy(
+    deck: &DeckManifest,
+    slides: &HashMap<String, SlideDoc>,
+    components: Option<&ComponentRegistry>,
+    import_map: Option<&ImportMap>,
+    lockfile: Option<&Lockfile>,
+    html: &str,
+) -> SecurityPolicy {
+    let mut policy = SecurityPolicy::for_slides(slides.values(), components);
+    for plugin in &deck.plugins {
+        policy.add_module(plugin);
+    }
+    let mapped = import_map_json(components, import_map, lockfile);
+    for url in mapped["imports"].as_object().into_iter().flat_map(|imports| imports.values()) {
+        policy.add_module(url.as_str().unwrap_or_default());
+    }
+    if let Some(lockfile) = lockfile {
+        for package in lockfile.resolved.components.values().chain(lockfile.resolved.plugins.values()) {
+            policy.add_module(&package.url);
+        }
+    }
+    policy.add_inline_scripts(html);
+    policy
 }
 
 /// Load deck + slides + component registry from a directory (utility for CLI/exports)
@@ -333,27 +1073,8 @@ pub fn load_deck_bundle(deck_dir: &std::path::Path) -> anyhow::Result<(
         }
     }
 
-    // Components registry (prefer JSON manifests over TS source)
-    let manifests_candidates = [
-        std::path::Path::new("packages/components/manifests"),
-        std::path::Path::new("../../packages/components/manifests"),
-        std::path::Path::new("../packages/components/manifests"),
-    ];
-    let src_candidates = [
-        std::path::Path::new("packages/components/src"),
-        std::path::Path::new("../../packages/components/src"),
-        std::path::Path::new("../packages/components/src"),
-    ];
-    let registry = manifests_candidates
-        .iter()
-        .find(|p| p.exists())
-        .and_then(|dir| components::extract_manifests_from_manifests_dir(dir).ok())
-        .or_else(|| {
-            src_candidates
-                .iter()
-                .find(|p| p.exists())
-                .and_then(|components_dir| components::extract_manifests_from_directory(components_dir).ok())
-        });
+    // Components registry from every layer; conflicts are reported by `coolslides validate`
+    let registry = registry::load_deck_registry(&deck_manifest, deck_dir).registry;
 
     Ok((deck_manifest, slides_map, registry))
 }
@@ -363,9 +1084,18 @@ pub fn export_deck_html_from_dir(deck_dir: &std::path::Path, strict_mode: bool)
     let (deck, mut slides, registry) = load_deck_bundle(deck_dir)?;
     // Embed external code for deterministic export (e.g., CodeSlide with git source)
     if let Err(e) = resolve_codeslide_content(&mut slides, deck_dir) {
-        eprintln!("Warning: failed to resolve external code content: {}", e);
+        tracing::warn!(error = %e, "could not resolve external code content");
+    }
+    let lockfile = read_lockfile(deck_dir);
+    let import_map = read_import_map(deck_dir);
+    // Exports sanitize as the dev server does, with the project's `coolslides.config.toml`
+    let server_config = config::ServerConfig::load(deck_dir)?;
+    let sanitization = SanitizationConfig::new(strict_mode || server_config.strict).with_policy(server_config.sanitization);
+    let html = generate_export_html(&deck, &slides, registry.as_ref(), Some(deck_dir), import_map.as_ref(), lockfile.as_ref(), &sanitization.for_deck(&deck))?;
+    if deck.server_plugins.is_empty() {
+        return Ok(html);
     }
-    generate_export_html(&deck, &slides, registry.as_ref(), Some(deck_dir), &SanitizationConfig::new(strict_mode))
+    Ok(server_plugins::PluginHost::for_deck(deck_dir, &deck.server_plugins).on_export("html", html))
 }
 
 fn resolve_codeslide_content(
@@ -403,6 +1133,39 @@ fn resolve_codeslide_content(
     Ok(())
 }
 
+/// Let server plugins answer a request before the router does
+async fn server_plugin_requests(
+    State(state): State<AppState>,
+    request: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> Response {
+    if state.plugins.is_empty() {
+        return next.run(request).await;
+    }
+    let info = server_plugins::PluginRequest {
+        method: request.method().to_string(),
+        path: request.uri().path().to_string(),
+        query: request.uri().query().map(str::to_string),
+        headers: request
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect(),
+    };
+    let plugins = state.plugins.clone();
+    let answer = tokio::task::spawn_blocking(move || plugins.on_request(&info)).await.unwrap_or(None);
+    let Some(answer) = answer else {
+        return next.run(request).await;
+    };
+    let mut response = Response::builder().status(StatusCode::from_u16(answer.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR));
+    for (name, value) in &answer.headers {
+        response = response.header(name.as_str(), value.as_str());
+    }
+    response
+        .body(Body::from(answer.body))
+        .unwrap_or_else(|_| Response::builder().status(StatusCode::INTERNAL_SERVER_ERROR).body(Body::empty()).unwrap())
+}
+
 /// Health check endpoint
 async fn health_check() -> Json<serde_json::Value> {
     Json(serde_json::json!({ "ok": true }))
@@ -428,6 +1191,7 @@ async fn test_markdown_sanitization(
 
 #[derive(Deserialize)]
 struct CodeResolveRequest {
+    #[allow(dead_code)]
     repo: Option<String>,
     r#ref: String,
     file: String,
@@ -504,8 +1268,21 @@ fn extract_lines(content: &str, spec: &str) -> anyhow::Result<String> {
 }
 
 /// Get import map for package resolution
-async fn get_import_map() -> Json<serde_json::Value> {
-    let import_map = serde_json::json!({
+async fn get_import_map(State(state): State<AppState>) -> Json<serde_json::Value> {
+    let components = state.components.read().await;
+    let deck_root = state.deck_root.read().await.clone();
+    let deck_map = deck_root.as_deref().and_then(read_import_map);
+    let lockfile = deck_root.as_deref().and_then(read_lockfile);
+    let mut import_map = import_map_json(components.as_ref(), deck_map.as_ref(), lockfile.as_ref());
+    proxy::rewrite_import_map(&mut import_map);
+    Json(import_map)
+}
+
+/// Import map for deck pages. The deck's `importmap.json` decides, then the import
+/// map locked in `.coolslides.lock`, then the Coolslides packages served from
+/// `/packages`. Deck-local components are mapped when the deck has any.
+fn import_map_json(registry: Option<&ComponentRegistry>, deck_map: Option<&ImportMap>, lockfile: Option<&Lockfile>) -> serde_json::Value {
+    let mut import_map = serde_json::json!({
         "imports": {
             "@coolslides/runtime": "/packages/runtime/dist/index.js",
             "@coolslides/component-sdk": "/packages/component-sdk/dist/index.js",
@@ -513,70 +1290,769 @@ async fn get_import_map() -> Json<serde_json::Value> {
             "@coolslides/plugins-stdlib": "/packages/plugins-stdlib/dist/index.js"
         }
     });
-    
-    Json(import_map)
+    let has_local = registry.is_some_and(|r| {
+        r.all_manifests().any(|m| m.module.starts_with(components::DECK_COMPONENTS_URL))
+    });
+    if has_local {
+        import_map["imports"][components::DECK_COMPONENTS_SPECIFIER] = components::DECK_COMPONENTS_URL.into();
+    }
+    let locked = lockfile.map(|lock| &lock.import_map);
+    for map in [locked, deck_map].into_iter().flatten() {
+        for (specifier, url) in &map.imports {
+            import_map["imports"][specifier] = url.as_str().into();
+        }
+    }
+    import_map
 }
 
-/// Get the resolved deck manifest
-async fn get_deck(State(state): State<AppState>) -> Result<Json<DeckManifest>, StatusCode> {
-    let deck = state.deck.read().await;
-    match deck.as_ref() {
-        Some(manifest) => Ok(Json(manifest.clone())),
-        None => Err(StatusCode::NOT_FOUND),
-    }
+/// URL a deck page loads `specifier` from, per its import map
+fn mapped_module<'a>(import_map: &'a serde_json::Value, specifier: &str) -> &'a str {
+    import_map["imports"][specifier].as_str().unwrap_or_default()
 }
 
-/// Get a specific slide
-async fn get_slide(
+/// Serve a file from the deck's `components/` directory
+async fn serve_deck_component(
     State(state): State<AppState>,
-    AxumPath(id): AxumPath<String>,
-) -> Result<Json<SlideDoc>, StatusCode> {
-    let slides = state.slides.read().await;
-    match slides.get(&id) {
-        Some(slide) => Ok(Json(slide.clone())),
-        None => Err(StatusCode::NOT_FOUND),
+    AxumPath(path): AxumPath<String>,
+) -> Result<Response, StatusCode> {
+    // `/components/<Name>` is the preview sandbox for a registered component
+    if !path.contains(['/', '.']) {
+        let components = state.components.read().await;
+        if let Some((registry, manifest)) = components.as_ref().and_then(|r| r.components.get(&path).map(|m| (r, m))) {
+            let deck = state.deck.read().await;
+            let html = render_component_preview_html(manifest, registry, deck.as_ref(), &state.sanitization_config)
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+            let mut policy = SecurityPolicy::default();
+            policy.add_module(&manifest.module);
+            policy.add_capabilities(&manifest.capabilities);
+            let mapped = import_map_json(Some(registry), None, None);
+            for url in mapped["imports"].as_object().into_iter().flat_map(|imports| imports.values()) {
+                policy.add_module(url.as_str().unwrap_or_default());
+            }
+            return Ok(Response::builder()
+                .header(header::CONTENT_TYPE, "text/html; charset=utf-8")
+                .extension(policy)
+                .body(Body::from(html))
+                .unwrap());
+        }
     }
+
+    let relative = safe_relative_path(&path)?;
+    let deck_root = state.deck_root.read().await.clone().ok_or(StatusCode::NOT_FOUND)?;
+    let file = deck_root.join("components").join(relative);
+    let content = match fs::read(&file).await {
+        Ok(content) => content,
+        Err(_) if file.extension().is_some_and(|e| e == "js") && file.with_extension("ts").exists() => {
+            let message = format!("{} has not been built; compile components/{} to JavaScript", path, relative.with_extension("ts").display());
+            return Ok(Response::builder().status(StatusCode::NOT_FOUND).body(Body::from(message)).unwrap());
+        }
+        Err(_) => return Err(StatusCode::NOT_FOUND),
+    };
+    Ok(Response::builder()
+        .header(header::CONTENT_TYPE, content_type(&file))
+        .body(Body::from(content))
+        .unwrap())
 }
 
-/// Start recording a room
-async fn start_recording(
-    AxumPath(room_id): AxumPath<String>,
+/// Serve a slide's narration audio from the deck directory, with range requests
+/// for seeking; remote narration redirects to its URL
+async fn serve_narration(
     State(state): State<AppState>,
-) -> StatusCode {
-    if let Some(room) = state.room_manager.get_room(&room_id).await {
-        room.start_recording().await;
-        StatusCode::OK
-    } else {
-        StatusCode::NOT_FOUND
+    AxumPath(slide_id): AxumPath<String>,
+    request: axum::extract::Request,
+) -> Result<Response, StatusCode> {
+    use tower::ServiceExt;
+
+    let narration = {
+        let slides = state.slides.read().await;
+        slides.get(&slide_id).and_then(|slide| slide.narration.clone()).ok_or(StatusCode::NOT_FOUND)?
+    };
+    if narration.is_remote() {
+        return Ok(Response::builder()
+            .status(StatusCode::TEMPORARY_REDIRECT)
+            .header(header::LOCATION, narration.src)
+            .body(Body::empty())
+            .unwrap());
     }
+    let relative = safe_relative_path(narration.src.trim_start_matches('/'))?;
+    let deck_root = state.deck_root.read().await.clone().ok_or(StatusCode::NOT_FOUND)?;
+    let response = tower_http::services::ServeFile::new(deck_root.join(relative))
+        .oneshot(request)
+        .await
+        .unwrap_or_else(|never| match never {});
+    Ok(response.map(Body::new))
 }
 
-/// Stop recording a room
-async fn stop_recording(
-    AxumPath(room_id): AxumPath<String>,
-    State(state): State<AppState>,
-) -> StatusCode {
-    if let Some(room) = state.room_manager.get_room(&room_id).await {
-        room.stop_recording().await;
-        StatusCode::OK
-    } else {
-        StatusCode::NOT_FOUND
-    }
+#[derive(Deserialize)]
+struct ThumbnailQuery {
+    /// Width in pixels; the height follows the slide's 16:9 frame
+    #[serde(alias = "width")]
+    w: Option<u32>,
 }
 
-/// Get room message dump
-async fn get_room_dump(
-    AxumPath(room_id): AxumPath<String>,
+/// PNG preview of one slide, rendered on first request and cached until the slide or theme changes
+async fn get_thumbnail(
     State(state): State<AppState>,
-) -> Result<String, StatusCode> {
-    if let Some(room) = state.room_manager.get_room(&room_id).await {
-        Ok(room.export_recording().await)
-    } else {
-        Err(StatusCode::NOT_FOUND)
+    AxumPath(slide_id): AxumPath<String>,
+    Query(query): Query<ThumbnailQuery>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    let width = thumbnails::snap_width(query.w);
+    let deck_root = state.deck_root.read().await.clone().ok_or(StatusCode::NOT_FOUND)?;
+    let deck = state.deck.read().await.clone().ok_or(StatusCode::NOT_FOUND)?;
+    let slides = state.slides.read().await.clone();
+    let components = state.components.read().await.clone();
+    let slide = slides.get(&slide_id).ok_or(StatusCode::NOT_FOUND)?;
+
+    let hash = thumbnails::content_hash(&deck, slide, components.as_ref(), &deck_root, width);
+    let etag = format!("\"{}\"", hash);
+    if headers.get(header::IF_NONE_MATCH).and_then(|v| v.to_str().ok()) == Some(etag.as_str()) {
+        return Ok(Response::builder().status(StatusCode::NOT_MODIFIED).header(header::ETAG, etag).body(Body::empty()).unwrap());
     }
+    let png = thumbnails::thumbnail(&deck, &slides, components.as_ref(), &deck_root, &slide_id, &hash, width)
+        .await
+        .map_err(|e| {
+            tracing::warn!(slide = %slide_id, error = %e, "thumbnail failed");
+            StatusCode::SERVICE_UNAVAILABLE
+        })?;
+    Ok(Response::builder()
+        .header(header::CONTENT_TYPE, "image/png")
+        // The URL stays the same across edits, so browsers revalidate with the ETag
+        .header(header::CACHE_CONTROL, "no-cache")
+        .header(header::ETAG, etag)
+        .body(Body::from(png))
+        .unwrap())
 }
 
-#[derive(Deserialize)]
+/// Serve a module downloaded by `coolslides vendor` from the deck's `vendor/` directory
+async fn serve_vendored(
+    State(state): State<AppState>,
+    AxumPath(path): AxumPath<String>,
+) -> Result<Response, StatusCode> {
+    let relative = safe_relative_path(&path)?;
+    let deck_root = state.deck_root.read().await.clone().ok_or(StatusCode::NOT_FOUND)?;
+    let file = deck_root.join(vendor::VENDOR_DIR).join(relative);
+    let content = fs::read(&file).await.map_err(|_| StatusCode::NOT_FOUND)?;
+    Ok(Response::builder()
+        .header(header::CONTENT_TYPE, content_type(&file))
+        .body(Body::from(content))
+        .unwrap())
+}
+
+/// Request path as a relative path that cannot leave the directory it is joined to
+fn safe_relative_path(path: &str) -> Result<&Path, StatusCode> {
+    let relative = Path::new(path);
+    if relative.components().any(|c| !matches!(c, std::path::Component::Normal(_))) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    Ok(relative)
+}
+
+fn content_type(file: &Path) -> &'static str {
+    match file.extension().and_then(|e| e.to_str()) {
+        Some("js") | Some("mjs") => "text/javascript",
+        Some("json") => "application/json",
+        Some("css") => "text/css",
+        Some("svg") => "image/svg+xml",
+        Some("png") => "image/png",
+        Some("wasm") => "application/wasm",
+        _ => "application/octet-stream",
+    }
+}
+
+/// The server version and the git revision of the deck being served
+async fn get_version(State(state): State<AppState>) -> Json<serde_json::Value> {
+    let deck_root = state.deck_root.read().await.clone();
+    let git = match deck_root {
+        Some(root) => tokio::task::spawn_blocking(move || git::detect(&root)).await.ok().flatten(),
+        None => None,
+    };
+    Json(serde_json::json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "git": git,
+    }))
+}
+
+/// Get the resolved deck manifest
+async fn get_deck(State(state): State<AppState>) -> Result<Json<DeckManifest>, StatusCode> {
+    let deck = state.deck.read().await;
+    match deck.as_ref() {
+        Some(manifest) => Ok(Json(manifest.clone())),
+        None => Err(StatusCode::NOT_FOUND),
+    }
+}
+
+#[derive(Deserialize)]
+struct ResolvedQuery {
+    profile: Option<String>,
+}
+
+/// The presentation the runtime should play: the sequence with groups
+/// expanded, `[conditions]` (or `[profiles.<profile>]`) applied, and each
+/// slide's transition settled. An unknown profile is a 404.
+async fn get_resolved_deck(
+    State(state): State<AppState>,
+    Query(query): Query<ResolvedQuery>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let deck = state.deck.read().await;
+    let deck = deck.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    let conditions = match &query.profile {
+        Some(name) => Some(deck.profiles.get(name).ok_or(StatusCode::NOT_FOUND)?),
+        None => deck.conditions.as_ref(),
+    };
+    let slides = state.slides.read().await;
+    Ok(Json(serde_json::json!({
+        "title": deck.title,
+        "profile": query.profile,
+        "slides": deck.resolve(&slides, conditions),
+    })))
+}
+
+#[derive(Deserialize)]
+struct SlidesQuery {
+    tag: Option<String>,
+    component: Option<String>,
+}
+
+/// Summaries of the loaded slides, in presentation order and then by id for
+/// slides the sequence does not use. `?tag=` and `?component=` filter them.
+async fn list_slides(State(state): State<AppState>, Query(query): Query<SlidesQuery>) -> Json<Vec<serde_json::Value>> {
+    let deck = state.deck.read().await;
+    let slides = state.slides.read().await;
+    let order = deck.as_ref().map(|deck| deck.slide_order()).unwrap_or_default();
+    let mut seen = std::collections::HashSet::new();
+    let mut ids: Vec<&str> = order.into_iter().filter(|id| slides.contains_key(*id) && seen.insert(*id)).collect();
+    let mut unused: Vec<&str> = slides.keys().map(String::as_str).filter(|id| !seen.contains(id)).collect();
+    unused.sort();
+    ids.extend(unused);
+
+    let summaries = ids
+        .into_iter()
+        .filter_map(|id| slides.get(id))
+        .filter(|slide| query.tag.as_ref().is_none_or(|tag| slide.tags.contains(tag)))
+        .filter(|slide| query.component.as_ref().is_none_or(|name| slide.component.name == *name))
+        .map(|slide| {
+            let deck_note = deck.as_ref().is_some_and(|deck| deck.notes.contains_key(&slide.id));
+            serde_json::json!({
+                "id": slide.id,
+                "component": slide.component.name,
+                "title": slide.props.get("title").and_then(|title| title.as_str()),
+                "tags": slide.tags,
+                "notes": slide.notes.len() + usize::from(deck_note),
+            })
+        })
+        .collect();
+    Json(summaries)
+}
+
+/// Validate the in-memory deck, with each diagnostic's slide and file
+async fn validate_deck(State(state): State<AppState>) -> Result<Json<serde_json::Value>, StatusCode> {
+    if state.deck.read().await.is_none() {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    let result = state.validate().await;
+    let (errors, warnings) = diagnostics_json(&state, &result).await;
+    Ok(Json(serde_json::json!({ "valid": result.is_valid(), "errors": errors, "warnings": warnings })))
+}
+
+/// A validation result's errors and warnings as JSON
+async fn diagnostics_json(
+    state: &AppState,
+    result: &validation::ValidationResult,
+) -> (Vec<serde_json::Value>, Vec<serde_json::Value>) {
+    let mut errors = Vec::new();
+    for error in &result.errors {
+        errors.push(diagnostic_json(state, error, "error").await);
+    }
+    let mut warnings = Vec::new();
+    for warning in &result.warnings {
+        warnings.push(diagnostic_json(state, warning, "warning").await);
+    }
+    (errors, warnings)
+}
+
+/// A diagnostic as JSON, with the deck file it is about relative to the deck
+/// directory: the slide's file, or `slides.toml` for deck-level diagnostics
+async fn diagnostic_json(state: &AppState, error: &validation::ValidationError, severity: &str) -> serde_json::Value {
+    let file = if error.names_own_file() {
+        None
+    } else if let Some(id) = error.slide_id() {
+        let root = state.deck_root.read().await.clone();
+        state.slide_file(id).await.map(|path| {
+            let relative = root.as_deref().and_then(|root| path.strip_prefix(root).ok()).unwrap_or(&path);
+            relative.display().to_string()
+        })
+    } else {
+        Some("slides.toml".to_string())
+    };
+    serde_json::json!({
+        "code": error.code(),
+        "severity": severity,
+        "message": error.message(),
+        "slideId": error.slide_id(),
+        "file": file,
+    })
+}
+
+/// Get a specific slide
+async fn get_slide(
+    State(state): State<AppState>,
+    AxumPath(id): AxumPath<String>,
+) -> Result<Json<SlideDoc>, StatusCode> {
+    let slides = state.slides.read().await;
+    match slides.get(&id) {
+        Some(slide) => Ok(Json(slide.clone())),
+        None => Err(StatusCode::NOT_FOUND),
+    }
+}
+
+/// Every component in the resolved registry, by name
+async fn list_components(State(state): State<AppState>) -> Json<Vec<serde_json::Value>> {
+    let components = state.components.read().await;
+    let Some(registry) = components.as_ref() else {
+        return Json(Vec::new());
+    };
+    let lockfile = deck_lockfile(&state).await;
+    let mut names: Vec<&String> = registry.components.keys().collect();
+    names.sort();
+    Json(
+        names
+            .into_iter()
+            .map(|name| component_json(registry, &registry.components[name], lockfile.as_ref()))
+            .collect(),
+    )
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ComponentQuery {
+    version_req: Option<String>,
+}
+
+/// One component; `?versionReq=^1` picks the newest matching version instead of the default
+async fn get_component(
+    State(state): State<AppState>,
+    AxumPath(name): AxumPath<String>,
+    Query(query): Query<ComponentQuery>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let components = state.components.read().await;
+    let registry = components.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    let manifest = match &query.version_req {
+        Some(req) => registry.resolve(&name, req),
+        None => registry.components.get(&name),
+    }
+    .ok_or(StatusCode::NOT_FOUND)?;
+    let lockfile = deck_lockfile(&state).await;
+    Ok(Json(component_json(registry, manifest, lockfile.as_ref())))
+}
+
+/// Manifest as served by the API: `module` is the URL pages load (the lockfile's
+/// when it locks this version), plus the registered versions, newest first, and
+/// the manifest's source
+fn component_json(registry: &ComponentRegistry, manifest: &coolslides_core::ComponentManifest, lockfile: Option<&Lockfile>) -> serde_json::Value {
+    let mut value = serde_json::to_value(manifest).unwrap_or_default();
+    if let Some(url) = lockfile.and_then(|lock| lock.component_url(&manifest.name, &manifest.version)) {
+        value["module"] = url.into();
+    }
+    let mut versions: Vec<&str> = registry.all_versions(&manifest.name).map(|m| m.version.as_str()).collect();
+    versions.sort_by(|a, b| match (semver::Version::parse(a), semver::Version::parse(b)) {
+        (Ok(a), Ok(b)) => b.cmp(&a),
+        _ => b.cmp(a),
+    });
+    value["versions"] = versions.into();
+    // Sources are tracked for default versions only
+    let is_default = registry.components.get(&manifest.name).is_some_and(|d| d.version == manifest.version);
+    if let Some(source) = registry.sources.get(&manifest.name).filter(|_| is_default) {
+        value["source"] = source.as_str().into();
+    }
+    value
+}
+
+async fn deck_lockfile(state: &AppState) -> Option<Lockfile> {
+    let deck_root = state.deck_root.read().await.clone()?;
+    read_lockfile(&deck_root)
+}
+
+/// Slots a component declares, for editors; an empty list when it declares none
+async fn get_component_slots(
+    State(state): State<AppState>,
+    AxumPath(name): AxumPath<String>,
+) -> Result<Json<Vec<SlotDeclaration>>, StatusCode> {
+    let components = state.components.read().await;
+    let registry = components.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    if !registry.components.contains_key(&name) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    Ok(Json(registry.slots(&name).map(<[SlotDeclaration]>::to_vec).unwrap_or_default()))
+}
+
+/// Start recording a room
+async fn start_recording(
+    AxumPath(room_id): AxumPath<String>,
+    Query(query): Query<TokenQuery>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> StatusCode {
+    if !state.presenter_authorized(&headers, query.token) {
+        return StatusCode::UNAUTHORIZED;
+    }
+    if let Some(room) = state.room_manager.get_room(&room_id).await {
+        room.start_recording().await;
+        StatusCode::OK
+    } else {
+        StatusCode::NOT_FOUND
+    }
+}
+
+/// Stop recording a room
+async fn stop_recording(
+    AxumPath(room_id): AxumPath<String>,
+    Query(query): Query<TokenQuery>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> StatusCode {
+    if !state.presenter_authorized(&headers, query.token) {
+        return StatusCode::UNAUTHORIZED;
+    }
+    if let Some(room) = state.room_manager.get_room(&room_id).await {
+        room.stop_recording().await;
+        StatusCode::OK
+    } else {
+        StatusCode::NOT_FOUND
+    }
+}
+
+#[derive(Deserialize)]
+struct DumpQuery {
+    #[serde(default)]
+    format: recordings::RecordingFormat,
+    token: Option<String>,
+}
+
+/// A room's recording as `json`, `ndjson`, or `csv`. Messages only presenters
+/// were sent, such as speaker notes and the Q&A queue, need the presenter token.
+async fn get_room_dump(
+    AxumPath(room_id): AxumPath<String>,
+    Query(query): Query<DumpQuery>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<([(header::HeaderName, &'static str); 1], String), StatusCode> {
+    let room = state.room_manager.get_room(&room_id).await.ok_or(StatusCode::NOT_FOUND)?;
+    let deck_title = state.deck.read().await.as_ref().map(|deck| deck.title.clone());
+    let mut recording = room.recording(deck_title).await;
+    if !state.presenter_authorized(&headers, query.token) {
+        recording.messages.retain(|recorded| rooms::Recipients::for_message(&recorded.message).includes(rooms::ClientRole::Audience));
+    }
+    Ok(([(header::CONTENT_TYPE, query.format.content_type())], recording.render(query.format)))
+}
+
+#[derive(Deserialize)]
+struct ImportQuery {
+    token: Option<String>,
+    #[serde(default)]
+    format: recordings::RecordingFormat,
+}
+
+/// Store an exported recording on a room, opening the room if needed, for
+/// `POST /api/rooms/:room_id/replay` to play
+async fn import_recording(
+    AxumPath(room_id): AxumPath<String>,
+    Query(query): Query<ImportQuery>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    body: String,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    let refuse = |status: StatusCode, message: &str| (status, Json(serde_json::json!({ "error": message })));
+    if !state.presenter_authorized(&headers, query.token) {
+        return Err(refuse(StatusCode::UNAUTHORIZED, "Presenter token required"));
+    }
+    let recording = recordings::Recording::parse(&body, query.format).map_err(|e| refuse(StatusCode::BAD_REQUEST, &e))?;
+    let count = recording.messages.len();
+    let duration_ms = recordings::span_ms(&recording.messages);
+    state.room_manager.ensure_room(room_id.clone()).await;
+    let room = state.room_manager.get_room(&room_id).await.ok_or_else(|| refuse(StatusCode::NOT_FOUND, "No such room"))?;
+    if !room.import_recording(recording).await {
+        return Err(refuse(StatusCode::CONFLICT, "The room is recording; stop the recording first"));
+    }
+    Ok(Json(serde_json::json!({ "messages": count, "durationMs": duration_ms })))
+}
+
+/// A room's current slide, clients, polls, and questions
+async fn get_room_state(
+    AxumPath(room_id): AxumPath<String>,
+    State(state): State<AppState>,
+) -> Result<Json<rooms::RoomSummary>, StatusCode> {
+    let room = state.room_manager.get_room(&room_id).await.ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(room.summary().await))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReplayRequest {
+    /// Recorded messages, as `/api/rooms/:room_id/dump` lists them
+    messages: Option<Vec<rooms::RecordedMessage>>,
+    /// Room whose recording to replay; the target room's own when neither is given
+    recording: Option<String>,
+    /// Play this many times faster than recorded
+    #[serde(default = "default_time_compression")]
+    time_compression: f64,
+}
+
+fn default_time_compression() -> f64 {
+    1.0
+}
+
+/// Replay recorded messages into a room in the background
+async fn start_replay(
+    AxumPath(room_id): AxumPath<String>,
+    Query(query): Query<TokenQuery>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Json(request): Json<ReplayRequest>,
+) -> Result<(StatusCode, Json<serde_json::Value>), StatusCode> {
+    if !state.presenter_authorized(&headers, query.token) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    if !(request.time_compression.is_finite() && request.time_compression > 0.0) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let room = state.room_manager.get_room(&room_id).await.ok_or(StatusCode::NOT_FOUND)?;
+    let messages = match (request.messages, request.recording) {
+        (Some(_), Some(_)) => return Err(StatusCode::BAD_REQUEST),
+        (Some(messages), None) => messages,
+        (None, source) => {
+            let source = match source {
+                Some(id) => state.room_manager.get_room(&id).await.ok_or(StatusCode::NOT_FOUND)?,
+                None => room.clone(),
+            };
+            source.get_recorded_messages().await
+        }
+    };
+    let duration_ms = (recordings::span_ms(&messages) as f64 / request.time_compression) as u64;
+    let count = messages.len();
+    room.start_replay(messages, request.time_compression);
+    Ok((StatusCode::ACCEPTED, Json(serde_json::json!({ "messages": count, "durationMs": duration_ms }))))
+}
+
+/// Stop a room's replay
+async fn stop_replay(
+    AxumPath(room_id): AxumPath<String>,
+    Query(query): Query<TokenQuery>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> StatusCode {
+    if !state.presenter_authorized(&headers, query.token) {
+        return StatusCode::UNAUTHORIZED;
+    }
+    match state.room_manager.get_room(&room_id).await {
+        Some(room) if room.stop_replay() => StatusCode::NO_CONTENT,
+        _ => StatusCode::NOT_FOUND,
+    }
+}
+
+/// Put a poll to a room
+async fn create_poll(
+    AxumPath(room_id): AxumPath<String>,
+    Query(query): Query<TokenQuery>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Json(poll): Json<rooms::NewPoll>,
+) -> Result<(StatusCode, Json<rooms::PollResults>), StatusCode> {
+    if !state.presenter_authorized(&headers, query.token) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    let room = state.room_manager.get_room(&room_id).await.ok_or(StatusCode::NOT_FOUND)?;
+    let results = room.create_poll(poll).await.map_err(poll_status)?;
+    Ok((StatusCode::CREATED, Json(results)))
+}
+
+/// Tallies of every poll in a room
+async fn list_polls(
+    AxumPath(room_id): AxumPath<String>,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<rooms::PollResults>>, StatusCode> {
+    let room = state.room_manager.get_room(&room_id).await.ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(room.list_polls().await))
+}
+
+/// One poll's live tally
+async fn get_poll(
+    AxumPath((room_id, poll_id)): AxumPath<(String, String)>,
+    State(state): State<AppState>,
+) -> Result<Json<rooms::PollResults>, StatusCode> {
+    let room = state.room_manager.get_room(&room_id).await.ok_or(StatusCode::NOT_FOUND)?;
+    room.poll_results(&poll_id).await.map(Json).ok_or(StatusCode::NOT_FOUND)
+}
+
+/// Stop a poll taking votes
+async fn close_poll(
+    AxumPath((room_id, poll_id)): AxumPath<(String, String)>,
+    Query(query): Query<TokenQuery>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<Json<rooms::PollResults>, StatusCode> {
+    if !state.presenter_authorized(&headers, query.token) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    let room = state.room_manager.get_room(&room_id).await.ok_or(StatusCode::NOT_FOUND)?;
+    room.close_poll(&poll_id).await.map(Json).map_err(poll_status)
+}
+
+fn poll_status(error: rooms::PollError) -> StatusCode {
+    match error {
+        rooms::PollError::Invalid(_) | rooms::PollError::NoSuchOption => StatusCode::BAD_REQUEST,
+        rooms::PollError::NotFound => StatusCode::NOT_FOUND,
+        rooms::PollError::Closed => StatusCode::CONFLICT,
+    }
+}
+
+/// A room's questions, most upvoted first. With the presenter token, pending
+/// and dismissed ones too, for moderation.
+async fn list_questions(
+    AxumPath(room_id): AxumPath<String>,
+    Query(query): Query<TokenQuery>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<rooms::Question>>, StatusCode> {
+    let room = state.room_manager.get_room(&room_id).await.ok_or(StatusCode::NOT_FOUND)?;
+    let presenter = state.presenter_authorized(&headers, query.token);
+    Ok(Json(room.list_questions(presenter).await))
+}
+
+/// Show a question to the room
+async fn approve_question(
+    AxumPath((room_id, question_id)): AxumPath<(String, String)>,
+    Query(query): Query<TokenQuery>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<Json<rooms::Question>, StatusCode> {
+    moderate_question(&state, &room_id, &question_id, &headers, query, rooms::QuestionStatus::Approved).await
+}
+
+/// Take a question off the queue or the board
+async fn dismiss_question(
+    AxumPath((room_id, question_id)): AxumPath<(String, String)>,
+    Query(query): Query<TokenQuery>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<Json<rooms::Question>, StatusCode> {
+    moderate_question(&state, &room_id, &question_id, &headers, query, rooms::QuestionStatus::Dismissed).await
+}
+
+async fn moderate_question(
+    state: &AppState,
+    room_id: &str,
+    question_id: &str,
+    headers: &HeaderMap,
+    query: TokenQuery,
+    status: rooms::QuestionStatus,
+) -> Result<Json<rooms::Question>, StatusCode> {
+    if !state.presenter_authorized(headers, query.token) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    let room = state.room_manager.get_room(room_id).await.ok_or(StatusCode::NOT_FOUND)?;
+    room.moderate_question(question_id, status).await.map(Json).map_err(|_| StatusCode::NOT_FOUND)
+}
+
+/// A token passed as `?token=`, for clients that cannot set headers
+#[derive(Deserialize)]
+struct TokenQuery {
+    token: Option<String>,
+}
+
+/// Where a remote navigation request moves the room
+enum RemoteTarget {
+    Next,
+    Prev,
+    Slide(String),
+}
+
+/// Advance a room to the next slide
+async fn remote_next(
+    AxumPath(room_id): AxumPath<String>,
+    Query(query): Query<TokenQuery>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    remote_navigate(&state, &room_id, &headers, query, RemoteTarget::Next).await
+}
+
+/// Move a room back one slide
+async fn remote_prev(
+    AxumPath(room_id): AxumPath<String>,
+    Query(query): Query<TokenQuery>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    remote_navigate(&state, &room_id, &headers, query, RemoteTarget::Prev).await
+}
+
+/// Jump a room to a slide by id
+async fn remote_goto(
+    AxumPath((room_id, slide_id)): AxumPath<(String, String)>,
+    Query(query): Query<TokenQuery>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    remote_navigate(&state, &room_id, &headers, query, RemoteTarget::Slide(slide_id)).await
+}
+
+/// Check the caller's token, then send the room the same `slide:change` event a
+/// presenter's page sends, so every connected client follows
+async fn remote_navigate(
+    state: &AppState,
+    room_id: &str,
+    headers: &HeaderMap,
+    query: TokenQuery,
+    target: RemoteTarget,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let (expected, order) = {
+        let deck = state.deck.read().await;
+        let deck = deck.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+        // Without a configured token the endpoints do not exist
+        let expected = deck.remote.as_ref().and_then(|remote| remote.token()).ok_or(StatusCode::NOT_FOUND)?;
+        (expected, deck.slide_order().into_iter().map(str::to_string).collect::<Vec<_>>())
+    };
+    if !presented_token(headers, query.token).is_some_and(|token| constant_time_eq(token.as_bytes(), expected.as_bytes())) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    let room = state.room_manager.get_room(room_id).await.ok_or(StatusCode::NOT_FOUND)?;
+    let current = room.state.read().await.get("currentSlide").and_then(|slide| slide.get("slideId")).and_then(|id| id.as_str()).map(str::to_string);
+    let position = current.and_then(|id| order.iter().position(|slide| *slide == id));
+    let index = match target {
+        RemoteTarget::Next => position.map_or(0, |i| (i + 1).min(order.len().saturating_sub(1))),
+        RemoteTarget::Prev => position.map_or(0, |i| i.saturating_sub(1)),
+        RemoteTarget::Slide(id) => order.iter().position(|slide| *slide == id).ok_or(StatusCode::NOT_FOUND)?,
+    };
+    let slide_id = order.get(index).ok_or(StatusCode::NOT_FOUND)?.clone();
+
+    room.handle_event(rooms::EventData {
+        name: "slide:change".to_string(),
+        data: serde_json::json!({ "slideId": slide_id, "fragment": 0 }),
+        client_id: "remote".to_string(),
+    })
+    .await;
+    Ok(Json(serde_json::json!({ "slideId": slide_id, "index": index, "total": order.len() })))
+}
+
+/// The token from `Authorization: Bearer`, or else from `?token=`
+fn presented_token(headers: &HeaderMap, query: Option<String>) -> Option<String> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::to_string)
+        .or(query)
+        .map(|token| token.trim().to_string())
+}
+
+/// Compare secrets without leaking where they differ through timing
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[derive(Deserialize)]
 struct ExportRequest {
     profile: Option<String>,
     scale: Option<f32>,
@@ -604,9 +2080,9 @@ async fn export_pdf(
         let comps_guard = state.components.read().await;
         comps_guard.clone()
     };
-    let allow_math = deck.plugins.iter().any(|p| p.contains("plugins-math") || p.contains("/math/") || p.ends_with("math"));
-    let slides_html = generate_slides_html(&deck, &slides, components_registry.as_ref(), &SanitizationConfig { strict_mode: state.sanitization_config.strict_mode, allow_math })
+    let slides_html = generate_slides_html(&deck, &slides, components_registry.as_ref(), &state.sanitization_config.for_deck(&deck))
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let slides_html = state.plugins.on_export("pdf", slides_html);
 
     // Configure export
     let profile = match request.profile.as_deref() {
@@ -627,9 +2103,14 @@ async fn export_pdf(
         guard.clone()
     };
     // Generate PDF
-    let pdf_data = export::export_deck_to_pdf(&deck, &slides_html, config, deck_root.as_deref())
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let started = Utc::now();
+    let _ = state.status.send(status::StatusEvent::ExportStarted { format: "pdf".to_string() });
+    let pdf_data = export::export_deck_to_pdf(&deck, &slides_html, config, deck_root.as_deref()).await;
+    record_export_job(&state, "pdf", started, pdf_data.as_ref().map(Vec::len).map_err(|e| e.to_string())).await;
+    let pdf_data = pdf_data.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    record_export(&state, &deck, "pdf").await;
+    let _ = state.events.send(webhooks::ServerEvent::new("export.completed", None, serde_json::json!({ "format": "pdf", "bytes": pdf_data.len() })));
 
     // Return PDF response
     Ok(Response::builder()
@@ -640,6 +2121,81 @@ async fn export_pdf(
         .unwrap())
 }
 
+/// Report an export run on `/api/events` and add it to the job history, when
+/// the server has a database
+async fn record_export_job(state: &AppState, format: &str, started: chrono::DateTime<Utc>, result: Result<usize, String>) {
+    let duration_ms = (Utc::now() - started).num_milliseconds().max(0) as u64;
+    let _ = state.status.send(match &result {
+        Ok(bytes) => status::StatusEvent::ExportCompleted { format: format.to_string(), bytes: *bytes, duration_ms },
+        Err(error) => status::StatusEvent::ExportFailed { format: format.to_string(), error: error.clone(), duration_ms },
+    });
+    if let Some(storage) = &state.storage {
+        if let Err(e) = storage.record_export_job(format, started, result).await {
+            tracing::warn!(error = %e, "could not record export job");
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ExportJobsQuery {
+    limit: Option<u32>,
+}
+
+/// Recent export jobs, newest first; only kept with `--db`
+async fn list_export_jobs(
+    State(state): State<AppState>,
+    Query(query): Query<ExportJobsQuery>,
+) -> Result<Json<Vec<storage::ExportJob>>, StatusCode> {
+    let storage = state.storage.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    let jobs = storage.export_jobs(query.limit.unwrap_or(50).min(1000)).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(jobs))
+}
+
+/// Count an export download when the deck collects analytics
+async fn record_export(state: &AppState, deck: &DeckManifest, format: &str) {
+    if !analytics_enabled(deck) {
+        return;
+    }
+    let Some(deck_root) = state.deck_root.read().await.clone() else {
+        return;
+    };
+    let slides = state.slides.read().await;
+    let event = analytics::AnalyticsEvent::ExportDownloaded { format: format.to_string() };
+    if let Err(e) = state.analytics.record(&deck_root, &[event], &slides).await {
+        tracing::warn!(error = %e, "could not record analytics");
+    }
+}
+
+/// Deck directory of a loaded deck that enables `[analytics]`
+async fn analytics_deck_root(state: &AppState) -> Result<PathBuf, StatusCode> {
+    let enabled = state.deck.read().await.as_ref().is_some_and(analytics_enabled);
+    if !enabled {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    state.deck_root.read().await.clone().ok_or(StatusCode::NOT_FOUND)
+}
+
+/// Aggregated view analytics
+async fn get_analytics(State(state): State<AppState>) -> Result<Json<analytics::AnalyticsSummary>, StatusCode> {
+    let deck_root = analytics_deck_root(&state).await?;
+    Ok(Json(state.analytics.summary(&deck_root).await))
+}
+
+/// Ingest one view event or a batch of them
+async fn post_analytics_events(
+    State(state): State<AppState>,
+    Json(batch): Json<analytics::EventBatch>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let deck_root = analytics_deck_root(&state).await?;
+    let events = batch.into_events();
+    if events.len() > analytics::MAX_BATCH {
+        return Err(StatusCode::PAYLOAD_TOO_LARGE);
+    }
+    let slides = state.slides.read().await;
+    let accepted = state.analytics.record(&deck_root, &events, &slides).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(serde_json::json!({ "accepted": accepted })))
+}
+
 /// Export deck to HTML
 async fn export_html(
     State(state): State<AppState>,
@@ -664,8 +2220,16 @@ async fn export_html(
         let guard = state.deck_root.read().await;
         guard.clone()
     };
-    let html_content = generate_export_html(&deck, &slides, components_registry.as_ref(), deck_root.as_deref(), &state.sanitization_config)
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let lockfile = deck_root.as_deref().and_then(read_lockfile);
+    let import_map = deck_root.as_deref().and_then(read_import_map);
+    let started = Utc::now();
+    let _ = state.status.send(status::StatusEvent::ExportStarted { format: "html".to_string() });
+    let html_content = generate_export_html(&deck, &slides, components_registry.as_ref(), deck_root.as_deref(), import_map.as_ref(), lockfile.as_ref(), &state.sanitization_config)
+        .map(|html| state.plugins.on_export("html", html));
+    record_export_job(&state, "html", started, html_content.as_ref().map(String::len).map_err(|e| e.to_string())).await;
+    let html_content = html_content.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    record_export(&state, &deck, "html").await;
+    let _ = state.events.send(webhooks::ServerEvent::new("export.completed", None, serde_json::json!({ "format": "html", "bytes": html_content.len() })));
 
     Ok(Response::builder()
         .status(StatusCode::OK)
@@ -703,30 +2267,333 @@ fn generate_slides_html(
     Ok(html_parts.join("\n"))
 }
 
-/// Public wrapper to generate slides HTML for PDF export and tooling
-pub fn render_slides_html(
-    deck: &DeckManifest,
-    slides: &HashMap<String, SlideDoc>,
-    components: Option<&ComponentRegistry>,
+/// Public wrapper to generate slides HTML for PDF export and tooling
+pub fn render_slides_html(
+    deck: &DeckManifest,
+    slides: &HashMap<String, SlideDoc>,
+    components: Option<&ComponentRegistry>,
+    config: &SanitizationConfig,
+) -> anyhow::Result<String> {
+    generate_slides_html(deck, slides, components, config)
+}
+
+/// HTML catalog of every component in a registry, with a live example slide per component
+pub fn render_component_catalog_html(
+    registry: &ComponentRegistry,
+    import_map: Option<&coolslides_core::ImportMap>,
+    config: &SanitizationConfig,
+) -> anyhow::Result<String> {
+    use coolslides_core::catalog;
+
+    let mut modules = Vec::new();
+    let mut sections = Vec::new();
+    for manifest in catalog::sorted(registry) {
+        if !modules.contains(&manifest.module) {
+            modules.push(manifest.module.clone());
+        }
+
+        let mut section = format!(
+            "<section class=\"component\" id=\"{}\">\n<h2>{}</h2>\n<p><code>&lt;{}&gt;</code> · version {}</p>\n",
+            html_escape(&manifest.name),
+            html_escape(&manifest.name),
+            html_escape(&manifest.tag),
+            html_escape(&manifest.version),
+        );
+        if let Some(deprecation) = &manifest.deprecated {
+            section.push_str(&format!("<p class=\"deprecated\"><strong>Deprecated:</strong> {}</p>\n", html_escape(&deprecation.to_string())));
+        }
+
+        let props = catalog::prop_docs(&manifest.schema);
+        if !props.is_empty() {
+            section.push_str("<h3>Props</h3>\n<table>\n<tr><th>Prop</th><th>Type</th><th>Required</th><th>Default</th><th>Description</th></tr>\n");
+            for prop in props {
+                section.push_str(&format!(
+                    "<tr><td><code>{}</code></td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                    html_escape(&prop.name),
+                    html_escape(&prop.ty),
+                    if prop.required { "yes" } else { "" },
+                    prop.default.map(|d| format!("<code>{}</code>", html_escape(&d))).unwrap_or_default(),
+                    prop.deprecated
+                        .as_ref()
+                        .map(|d| format!("<strong>Deprecated:</strong> {}. ", html_escape(&d.to_string())))
+                        .unwrap_or_default()
+                        + &html_escape(prop.description.as_deref().unwrap_or_default()),
+                ));
+            }
+            section.push_str("</table>\n");
+        }
+
+        if let Some(slots) = manifest.slots.as_ref().filter(|s| !s.is_empty()) {
+            section.push_str("<h3>Slots</h3>\n<ul>\n");
+            for slot in slots {
+                let accepts: Vec<String> = slot.accepts.iter().map(|k| k.to_string()).collect();
+                section.push_str(&format!(
+                    "<li><code>{}</code>{}{}{}</li>\n",
+                    html_escape(&slot.name),
+                    if slot.required { " (required)" } else { "" },
+                    if accepts.is_empty() { String::new() } else { format!(" · accepts {}", accepts.join(", ")) },
+                    slot.description.as_ref().map(|d| format!(": {}", html_escape(d))).unwrap_or_default(),
+                ));
+            }
+            section.push_str("</ul>\n");
+        }
+
+        let code_list = |items: &[String]| items.iter().map(|i| format!("<code>{}</code>", html_escape(i))).collect::<Vec<_>>().join(", ");
+        if !manifest.tokens_used.is_empty() {
+            section.push_str(&format!("<p><strong>Tokens:</strong> {}</p>\n", code_list(&manifest.tokens_used)));
+        }
+        if !manifest.capabilities.is_empty() {
+            section.push_str(&format!("<p><strong>Capabilities:</strong> {}</p>\n", code_list(&manifest.capabilities)));
+        }
+        if let Some(transition) = &manifest.suggested_transition {
+            section.push_str(&format!("<p><strong>Suggested transition:</strong> <code>{}</code></p>\n", html_escape(transition)));
+        }
+
+        let example = catalog::example_slide(manifest);
+        section.push_str(&format!(
+            "<h3>Example</h3>\n<div class=\"example\">{}</div>\n<pre><code>{}</code></pre>\n</section>",
+            generate_slide_html(&example, Some(registry), config)?,
+            html_escape(&catalog::example_toml(&example)),
+        ));
+        sections.push(section);
+    }
+
+    let import_map = match import_map {
+        Some(map) => serde_json::to_string(map)?,
+        None => serde_json::to_string(&serde_json::json!({
+            "imports": { "@coolslides/component-sdk": "/packages/component-sdk/dist/index.js" }
+        }))?,
+    };
+    let module_scripts: Vec<String> = modules
+        .iter()
+        .map(|module| format!("<script type=\"module\" src=\"{}\"></script>", html_escape(module)))
+        .collect();
+
+    Ok(format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="utf-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1">
+    <title>Component Catalog</title>
+    <script type="importmap">{}</script>
+    {}
+    <style>
+        body {{ font-family: system-ui, sans-serif; max-width: 72rem; margin: 0 auto; padding: 2rem; }}
+        table {{ border-collapse: collapse; }}
+        th, td {{ border: 1px solid #ccc; padding: 0.25rem 0.5rem; text-align: left; vertical-align: top; }}
+        .example {{ aspect-ratio: 16 / 9; border: 1px solid #ccc; overflow: hidden; }}
+    </style>
+</head>
+<body>
+    <h1>Component Catalog</h1>
+    {}
+</body>
+</html>"#,
+        import_map,
+        module_scripts.join("\n    "),
+        sections.join("\n"),
+    ))
+}
+
+/// Sandbox page rendering one component with props editable through a form
+/// built from its schema, for `/components/:name`
+pub fn render_component_preview_html(
+    manifest: &coolslides_core::ComponentManifest,
+    registry: &ComponentRegistry,
+    deck: Option<&DeckManifest>,
     config: &SanitizationConfig,
 ) -> anyhow::Result<String> {
-    generate_slides_html(deck, slides, components, config)
+    use coolslides_core::catalog;
+
+    let example = catalog::example_slide(manifest);
+    let slots_html = format_slots(&example.slots, Some(registry), config)?;
+    let stylesheets: String = deck
+        .into_iter()
+        .flat_map(|deck| std::iter::once(&deck.theme).chain(deck.tokens.as_ref()))
+        .map(|href| format!("<link rel=\"stylesheet\" href=\"/{}\">", html_escape(href.trim_start_matches('/'))))
+        .collect();
+    let script_json = |value: &serde_json::Value| value.to_string().replace("</", "<\\/");
+
+    Ok(format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="utf-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1">
+    <title>{name} · Component Preview</title>
+    <script type="importmap">{import_map}</script>
+    {stylesheets}
+    <script type="module" src="{module}"></script>
+    <style>
+        body {{ font-family: system-ui, sans-serif; margin: 0; display: grid; grid-template-columns: 22rem 1fr; min-height: 100vh; }}
+        aside {{ padding: 1rem; border-right: 1px solid #ccc; overflow: auto; }}
+        aside label {{ display: block; margin: 0.75rem 0 0.25rem; font-weight: 600; }}
+        aside small {{ display: block; color: #666; font-weight: normal; }}
+        aside input:not([type=checkbox]), aside select, aside textarea {{ width: 100%; box-sizing: border-box; }}
+        textarea {{ font-family: ui-monospace, monospace; min-height: 8rem; }}
+        #stage {{ aspect-ratio: 16 / 9; margin: 1rem; border: 1px solid #ccc; overflow: hidden; }}
+        #error {{ color: #b00020; }}
+        /* `?bare` shows only the stage, for screenshots */
+        body.bare {{ display: block; }}
+        body.bare aside {{ display: none; }}
+        body.bare #stage {{ margin: 0; border: 0; }}
+    </style>
+</head>
+<body>
+    <aside>
+        <h1>{name}</h1>
+        <p><code>&lt;{tag}&gt;</code> · version {version}</p>
+        <form id="props"></form>
+        <label for="json">Props JSON</label>
+        <textarea id="json" spellcheck="false"></textarea>
+        <p id="error"></p>
+    </aside>
+    <main><div id="stage" class="coolslides-slide"></div></main>
+    <template id="slots">{slots}</template>
+    <script type="application/json" id="schema">{schema}</script>
+    <script type="application/json" id="example">{props}</script>
+    <script type="module">
+        const tag = {tag_json};
+        const schema = JSON.parse(document.getElementById('schema').textContent);
+        let props = JSON.parse(document.getElementById('example').textContent);
+        const form = document.getElementById('props');
+        const json = document.getElementById('json');
+        const error = document.getElementById('error');
+        const kebab = (key) => key.replace(/[A-Z]/g, (c) => '-' + c.toLowerCase());
+        if (new URLSearchParams(location.search).has('bare')) document.body.classList.add('bare');
+
+        function render() {{
+            const el = document.createElement(tag);
+            el.innerHTML = document.getElementById('slots').innerHTML;
+            for (const [key, value] of Object.entries(props)) {{
+                if (key in el) {{ el[key] = value; }}
+                else if (typeof value === 'boolean') {{ if (value) el.setAttribute(kebab(key), ''); }}
+                else if (value !== null && typeof value !== 'object') {{ el.setAttribute(kebab(key), String(value)); }}
+            }}
+            document.getElementById('stage').replaceChildren(el);
+            json.value = JSON.stringify(props, null, 2);
+        }}
+
+        function field(key, prop) {{
+            const label = document.createElement('label');
+            label.textContent = key + ((schema.required || []).includes(key) ? ' *' : '');
+            if (prop.description) {{ const hint = document.createElement('small'); hint.textContent = prop.description; label.append(hint); }}
+            let input;
+            const value = props[key];
+            if (Array.isArray(prop.enum)) {{
+                input = document.createElement('select');
+                input.append(new Option('', ''));
+                prop.enum.forEach((option, i) => input.append(new Option(String(option), String(i), false, option === value)));
+                input.onchange = () => set(key, input.value === '' ? undefined : prop.enum[Number(input.value)]);
+            }} else if (prop.type === 'boolean') {{
+                input = document.createElement('input');
+                input.type = 'checkbox';
+                input.checked = value === true;
+                input.onchange = () => set(key, input.checked);
+            }} else if (prop.type === 'number' || prop.type === 'integer') {{
+                input = document.createElement('input');
+                input.type = 'number';
+                input.value = value ?? '';
+                input.oninput = () => set(key, input.value === '' ? undefined : Number(input.value));
+            }} else if (prop.type === 'object' || prop.type === 'array') {{
+                input = document.createElement('textarea');
+                input.value = value === undefined ? '' : JSON.stringify(value, null, 2);
+                input.oninput = () => {{ try {{ set(key, input.value === '' ? undefined : JSON.parse(input.value)); }} catch (e) {{ error.textContent = key + ': ' + e.message; }} }};
+            }} else {{
+                input = document.createElement('input');
+                input.value = value ?? '';
+                input.oninput = () => set(key, input.value === '' ? undefined : input.value);
+            }}
+            input.name = key;
+            form.append(label, input);
+        }}
+
+        function set(key, value) {{
+            props = {{ ...props }};
+            if (value === undefined) delete props[key]; else props[key] = value;
+            error.textContent = '';
+            render();
+        }}
+
+        function buildForm() {{
+            form.replaceChildren();
+            for (const [key, prop] of Object.entries(schema.properties || {{}})) field(key, prop);
+        }}
+
+        json.oninput = () => {{
+            try {{ props = JSON.parse(json.value); error.textContent = ''; buildForm(); render(); }}
+            catch (e) {{ error.textContent = e.message; }}
+        }};
+
+        buildForm();
+        render();
+        // Re-render once the module defines the element, so props land on its properties
+        customElements.whenDefined(tag).then(render);
+    </script>
+</body>
+</html>"#,
+        name = html_escape(&manifest.name),
+        tag = html_escape(&manifest.tag),
+        version = html_escape(&manifest.version),
+        import_map = script_json(&import_map_json(Some(registry), None, None)),
+        stylesheets = stylesheets,
+        module = html_escape(&manifest.module),
+        slots = slots_html,
+        schema = script_json(&manifest.schema),
+        props = script_json(&example.props),
+        tag_json = script_json(&serde_json::Value::from(manifest.tag.as_str())),
+    ))
 }
 
-fn resolve_component_tag(components: Option<&ComponentRegistry>, component_name: &str) -> String {
+fn resolve_component_tag(components: Option<&ComponentRegistry>, spec: &coolslides_core::ComponentSpec) -> String {
+    let component_name = spec.name.as_str();
     if let Some(registry) = components {
-        if let Some(manifest) = registry.components.get(component_name) {
+        // Unsatisfied versionReqs are reported by validation; render the default version
+        if let Some(manifest) = registry.resolve(component_name, &spec.version_req).or_else(|| registry.components.get(component_name)) {
             return manifest.tag.clone();
         }
-        eprintln!("Warning: component '{}' not found in manifests; falling back to 'cs-unknown-component'", component_name);
+        tracing::warn!(component = %component_name, "component not found in manifests; falling back to 'cs-unknown-component'");
         return "cs-unknown-component".to_string();
     }
-    eprintln!("Warning: component registry not loaded; falling back to 'cs-unknown-component'");
+    tracing::warn!("component registry not loaded; falling back to 'cs-unknown-component'");
     "cs-unknown-component".to_string()
 }
 
+/// Rendered slides, keyed by the slide, its resolved tags, and the sanitization mode
+static SLIDE_HTML: render_cache::RenderCache = render_cache::RenderCache::new();
+/// Sanitized markdown, keyed by the source and the sanitization mode
+static MARKDOWN_HTML: render_cache::RenderCache = render_cache::RenderCache::new();
+
+/// Forget every rendered slide and markdown block, so the next render starts cold
+fn clear_render_caches() {
+    SLIDE_HTML.clear();
+    MARKDOWN_HTML.clear();
+}
+
 fn generate_slide_html(slide: &SlideDoc, components: Option<&ComponentRegistry>, config: &SanitizationConfig) -> anyhow::Result<String> {
-    let tag = resolve_component_tag(components, &slide.component.name);
+    let tag = resolve_component_tag(components, &slide.component);
+    // Registry renames change the tags of component slots without changing the slide
+    let slot_tags: Vec<&str> = slide
+        .slots
+        .values()
+        .filter_map(|slot| match slot {
+            coolslides_core::Slot::Component { tag, module, .. } => {
+                components.and_then(|r| r.renamed_tag(tag, module)).or(Some(tag.as_str()))
+            }
+            _ => None,
+        })
+        .collect();
+    let key = render_cache::key((serde_json::to_string(slide)?, &tag, slot_tags, config.strict_mode, config.allow_math, &config.policy));
+    if let Some(html) = SLIDE_HTML.get(key) {
+        return Ok(html);
+    }
+    let html = render_slide_html(slide, &tag, components, config)?;
+    SLIDE_HTML.insert(key, &html);
+    Ok(html)
+}
+
+fn render_slide_html(slide: &SlideDoc, tag: &str, components: Option<&ComponentRegistry>, config: &SanitizationConfig) -> anyhow::Result<String> {
     let style_attr = if !slide.style_overrides.is_empty() {
         let mut pairs: Vec<String> = slide
             .style_overrides
@@ -748,7 +2615,7 @@ fn generate_slide_html(slide: &SlideDoc, components: Option<&ComponentRegistry>,
         style_attr,
         tag,
         format_props_as_data_id(&slide.id),
-        format_slots(&slide.slots, config)?,
+        format_slots(&slide.slots, components, config)?,
         tag,
         generate_props_script(&slide.id, &slide.props)?
     );
@@ -770,83 +2637,114 @@ fn generate_props_script(slide_id: &str, props: &serde_json::Value) -> anyhow::R
 }
 
 fn render_markdown_to_html(markdown: &str, config: &SanitizationConfig) -> String {
-    let parser = Parser::new(markdown);
+    let key = render_cache::key((markdown, config.strict_mode, config.allow_math, &config.policy.rules));
+    if let Some(html) = MARKDOWN_HTML.get(key) {
+        return html;
+    }
+    let html = sanitize_markdown(markdown, config);
+    MARKDOWN_HTML.insert(key, &html);
+    html
+}
+
+fn sanitize_markdown(markdown: &str, config: &SanitizationConfig) -> String {
+    use coolslides_core::math;
+
+    // `$...$` becomes MathML here, so formulas survive into PDF export without KaTeX
+    let markdown = if config.allow_math && !config.strict_mode {
+        std::borrow::Cow::Owned(math::render_math(markdown))
+    } else {
+        std::borrow::Cow::Borrowed(markdown)
+    };
+    // Diagram fences become SVG, cleaned separately and inserted after sanitization.
+    // Strict mode leaves them as code blocks.
+    let markdown = diagrams::strip_placeholders(&markdown);
+    let (events, diagrams) = if config.strict_mode {
+        (Parser::new(&markdown).collect(), Vec::new())
+    } else {
+        diagrams::extract(Parser::new(&markdown))
+    };
     let mut html_output = String::new();
-    html::push_html(&mut html_output, parser);
+    html::push_html(&mut html_output, events.into_iter());
     
     // Configure sanitization based on strict mode
     let sanitized = if config.strict_mode {
         // Strict mode: very limited HTML tags allowed
         ammonia::Builder::new()
-            .tags(hashset![
-                "p", "br", "strong", "em", "code", "pre",
-                "h1", "h2", "h3", "h4", "h5", "h6",
-                "ul", "ol", "li", "blockquote"
-            ])
+            .tags(coolslides_core::markdown::STRICT_MODE_TAGS.iter().copied().collect())
             .clean_content_tags(hashset!["script", "style"])
             .strip_comments(true)
             .link_rel(None) // Remove all link relations
             .clean(&html_output)
-    } else if config.allow_math {
-        // Math-friendly: allow spans/divs with classes so plugins (KaTeX) can render
-        ammonia::Builder::new()
-            .tags(hashset![
-                "p", "br", "strong", "em", "code", "pre",
-                "h1", "h2", "h3", "h4", "h5", "h6",
-                "ul", "ol", "li", "blockquote", "a", "img",
-                "table", "thead", "tbody", "tr", "td", "th",
-                "span", "div"
-            ])
-            .tag_attributes(hashmap![
-                "a" => hashset!["href", "title"],
-                "img" => hashset!["src", "alt", "title", "width", "height"],
-                "code" => hashset!["class"],
-                "pre" => hashset!["class"],
-                "span" => hashset!["class", "style"],
-                "div" => hashset!["class", "style"]
-            ])
-            .clean_content_tags(hashset!["script", "style"])
-            .strip_comments(true)
-            .link_rel(Some("noopener noreferrer"))
-            .clean(&html_output)
     } else {
         // Default mode: presentation-friendly tags
-        ammonia::Builder::new()
-            .tags(hashset![
-                "p", "br", "strong", "em", "code", "pre", "span", "div",
-                "h1", "h2", "h3", "h4", "h5", "h6",
-                "ul", "ol", "li", "blockquote", "a", "img",
-                "table", "thead", "tbody", "tr", "td", "th"
-            ])
-            .tag_attributes(hashmap![
-                "a" => hashset!["href", "title"],
-                "img" => hashset!["src", "alt", "title", "width", "height"],
-                "code" => hashset!["class"],
-                "pre" => hashset!["class"],
-                "span" => hashset!["class"],
-                "div" => hashset!["class"]
-            ])
+        let mut tags = hashset![
+            "p", "br", "strong", "em", "code", "pre", "span", "div",
+            "h1", "h2", "h3", "h4", "h5", "h6",
+            "ul", "ol", "li", "blockquote", "a", "img",
+            "table", "thead", "tbody", "tr", "td", "th"
+        ];
+        let mut tag_attributes = hashmap![
+            "a" => hashset!["href", "title"],
+            "img" => hashset!["src", "alt", "title", "width", "height"],
+            "code" => hashset!["class"],
+            "pre" => hashset!["class"],
+            "span" => hashset!["class"],
+            "div" => hashset!["class"]
+        ];
+        if config.allow_math {
+            // Math-friendly: styled spans/divs so plugins (KaTeX) can render,
+            // plus the MathML rendered above
+            tags.extend(math::MATHML_TAGS.iter().copied());
+            for tag in ["span", "div"] {
+                tag_attributes.entry(tag).or_default().insert("style");
+            }
+            tag_attributes.extend(math::MATHML_ATTRIBUTES.iter().map(|(tag, attributes)| (*tag, attributes.iter().copied().collect())));
+        }
+
+        // The deck's `[sanitization]` additions. Tags whose content is removed
+        // cannot also be kept, and links always get their own `rel`.
+        let rules = &config.policy.rules;
+        tags.extend(rules.allow_tags.iter().map(String::as_str).filter(|tag| !matches!(*tag, "script" | "style")));
+        let mut generic_attributes = Vec::new();
+        for (tag, attributes) in &rules.allow_attributes {
+            let attributes = attributes.iter().map(String::as_str).filter(|a| !(*a == "rel" && matches!(tag.as_str(), "a" | "*")));
+            match tag.as_str() {
+                "*" => generic_attributes.extend(attributes),
+                tag => tag_attributes.entry(tag).or_default().extend(attributes),
+            }
+        }
+
+        let mut builder = ammonia::Builder::new();
+        builder
+            .tags(tags)
+            .tag_attributes(tag_attributes)
+            .add_generic_attributes(generic_attributes)
             .clean_content_tags(hashset!["script", "style"])
             .strip_comments(true)
-            .link_rel(Some("noopener noreferrer"))
-            .clean(&html_output)
+            .link_rel(Some("noopener noreferrer"));
+        if let Some(schemes) = &rules.url_schemes {
+            builder.url_schemes(schemes.iter().map(String::as_str).collect());
+        }
+        builder.clean(&html_output)
     };
     
-    sanitized.to_string()
+    diagrams::insert(&sanitized.to_string(), &diagrams)
 }
 
 fn format_slots(
     slots: &HashMap<String, coolslides_core::Slot>,
+    components: Option<&ComponentRegistry>,
     config: &SanitizationConfig
 ) -> anyhow::Result<String> {
     let slot_content: Vec<String> = slots.iter()
         .map(|(name, slot)| {
             match slot {
                 coolslides_core::Slot::Markdown { value } => {
-                    let rendered_html = render_markdown_to_html(value, config);
+                    let rendered_html = render_markdown_to_html(value, &config.for_slot(name));
                     format!(r#"<div slot="{}">{}</div>"#, name, rendered_html)
                 }
                 coolslides_core::Slot::Component { tag, module, props, defer, .. } => {
+                    let tag = components.and_then(|r| r.renamed_tag(tag, module)).unwrap_or(tag);
                     let slot_id = format!("{}:{}", name, tag);
                     let props_script = generate_props_script(&slot_id, props).unwrap_or_default();
                     let defer_attr = defer.as_ref().map(|d| format!(" data-defer=\"{}\"", 
@@ -874,9 +2772,19 @@ fn generate_export_html(
     slides: &HashMap<String, SlideDoc>,
     components: Option<&ComponentRegistry>,
     deck_root: Option<&Path>,
+    import_map: Option<&ImportMap>,
+    lockfile: Option<&Lockfile>,
     config: &SanitizationConfig,
 ) -> anyhow::Result<String> {
     let slides_html = generate_slides_html(deck, slides, components, config)?;
+    let version_modules = versioned_module_scripts(slides, components, lockfile);
+    let renamed_modules = tag_rename_script(components);
+    let preloads = integrity_preloads(lockfile);
+    let mut mapped = import_map_json(components, import_map, lockfile);
+    if deck_root.is_none() {
+        // Dev pages load CDN modules through the offline cache
+        proxy::rewrite_import_map(&mut mapped);
+    }
 
     let theme_css = inline_css(deck_root, &deck.theme);
     let tokens_css = deck.tokens.as_ref().and_then(|p| inline_css(deck_root, p));
@@ -890,21 +2798,21 @@ fn generate_export_html(
         )
     } else {
         // In dev, prefer absolute paths so CSS @import resolves reliably
-        let theme_href = if deck.theme.starts_with('/') { deck.theme.clone() } else { format!("/{}", deck.theme) };
-        let tokens_href = deck.tokens.as_ref().map(|t| if t.starts_with('/') { t.clone() } else { format!("/{}", t) });
+        let theme_href = stylesheet_href(&deck.theme);
+        let tokens_href = deck.tokens.as_deref().map(stylesheet_href);
         (
             String::new(),
             format!(
                 "<link rel=\"stylesheet\" href=\"{}\"/>{}",
                 theme_href,
-                tokens_href.map(|t| format!("\n<link rel=\\\"stylesheet\\\" href=\\\"{}\\\"/>", t)).unwrap_or_default()
+                tokens_href.map(|t| format!("\n<link rel=\"stylesheet\" href=\"{}\"/>", t)).unwrap_or_default()
             ),
         )
     };
 
     // In dev mode (no deck_root), inject a tiny WS-based auto-reload client
     let dev_reload_script = if deck_root.is_none() {
-        r#"<script>(function(){try{var p=location.protocol==='https:'?'wss':'ws';var ws=new WebSocket(p+'://'+location.host+'/rooms/__reload');var overlay=null;function show(){if(!overlay){overlay=document.createElement('div');overlay.style.cssText='position:fixed;inset:0;display:flex;align-items:center;justify-content:center;background:rgba(0,0,0,0.35);color:#fff;z-index:2147483647;font:600 16px system-ui,sans-serif';overlay.innerHTML='<div style="padding:12px 16px;background:#111;border-radius:8px;border:1px solid #333;box-shadow:0 2px 8px rgba(0,0,0,.4)">Reloading…</div>';document.addEventListener('DOMContentLoaded',function(){document.body.appendChild(overlay);},{once:true});if(document.readyState!=='loading'){try{if(!overlay.isConnected){document.body.appendChild(overlay);}}catch(_){}}}if(overlay&&overlay.style){overlay.style.display='flex';}}ws.onmessage=function(e){var m;try{m=JSON.parse(e.data);}catch(_){return;}if(m&&m.type==='event'&&m.event){if(m.event.name==='reload:prepare'){show();}if(m.event.name==='reload'){show();setTimeout(function(){location.reload();},10);}}};}catch(_){}})();</script>"#.to_string()
+        r#"<script>(function(){try{var p=location.protocol==='https:'?'wss':'ws';var ws=new WebSocket(p+'://'+location.host+'/ws/hmr');var overlay=null;function show(){if(!overlay){overlay=document.createElement('div');overlay.style.cssText='position:fixed;inset:0;display:flex;align-items:center;justify-content:center;background:rgba(0,0,0,0.35);color:#fff;z-index:2147483647;font:600 16px system-ui,sans-serif';overlay.innerHTML='<div style="padding:12px 16px;background:#111;border-radius:8px;border:1px solid #333;box-shadow:0 2px 8px rgba(0,0,0,.4)">Reloading…</div>';document.addEventListener('DOMContentLoaded',function(){document.body.appendChild(overlay);},{once:true});if(document.readyState!=='loading'){try{if(!overlay.isConnected){document.body.appendChild(overlay);}}catch(_){}}}if(overlay&&overlay.style){overlay.style.display='flex';}}function reload(){show();setTimeout(function(){location.reload();},150);}ws.onmessage=function(e){var m;try{m=JSON.parse(e.data);}catch(_){return;}if(!m)return;if(m.kind==='slide-changed'){patch(m.id,m.html);}else if(m.kind==='theme-changed'){swap(m);}else if(m.kind==='deck-changed'||m.kind==='components-changed'){reload();}};function patch(id,html){var el=document.querySelector('[data-slide="'+CSS.escape(id)+'"]');var t=document.createElement('template');t.innerHTML=html.trim();var n=t.content.firstElementChild;if(!el||!n){reload();return;}if(el.hasAttribute('data-active')){n.setAttribute('data-active','');}el.replaceWith(n);document.dispatchEvent(new CustomEvent('coolslides:slide-updated',{detail:{slideId:id}}));}function swap(d){var links=document.querySelectorAll('link[rel="stylesheet"]');for(var i=0;i<links.length;i++){(function(l){var u=new URL(l.getAttribute('href'),location.href);if(u.pathname!==d.href)return;u.searchParams.set('v',d.version);var n=l.cloneNode();n.href=u.href;n.onload=function(){l.remove();};l.parentNode.insertBefore(n,l.nextSibling);})(links[i]);}}}catch(_){}})();</script>"#.to_string()
     } else { String::new() };
 
     let html = format!(r#"<!DOCTYPE html>
@@ -915,17 +2823,21 @@ fn generate_export_html(
     <title>{}</title>
     {}
     <script type="importmap">{}</script>
+    {}
     <!-- Theme CSS (inline for export; linked in dev) -->
     <style>
         {}
     </style>
     <!-- Tokens CSS (inline for export; linked in dev) -->
     {}
-    <script type="module" src="/packages/runtime/dist/index.js"></script>
-    <script type="module" src="/packages/components/dist/index.js"></script>
+    <script type="module" src="{}"></script>
+    <script type="module" src="{}"></script>
+    {}
+    {}
     {}
 </head>
 <body>
+    {}
     <div class="coolslides-presentation">
         {}
     </div>
@@ -939,47 +2851,216 @@ fn generate_export_html(
     </script>
 </body>
 </html>"#,
-        deck.title,
+        html_escape(&deck.title),
         base_href.as_ref().map(|u| format!("<base href=\"{}\">", u)).unwrap_or_default(),
-        serde_json::to_string(&serde_json::json!({
-            "imports": {
-                "@coolslides/runtime": "/packages/runtime/dist/index.js",
-                "@coolslides/components": "/packages/components/dist/index.js",
-                "@coolslides/component-sdk": "/packages/component-sdk/dist/index.js",
-                "@coolslides/plugins-stdlib": "/packages/plugins-stdlib/dist/index.js"
-            }
-        })).unwrap_or("{}".into()),
+        serde_json::to_string(&mapped).unwrap_or("{}".into()),
+        preloads,
         theme_style_content,
         tokens_block,
+        html_escape(mapped_module(&mapped, "@coolslides/runtime")),
+        html_escape(mapped_module(&mapped, "@coolslides/components")),
+        version_modules,
+        renamed_modules,
         dev_reload_script,
+        slide_links(deck, slides),
         slides_html,
         serde_json::to_string_pretty(deck)?,
         serde_json::to_string_pretty(&slides.values().collect::<Vec<_>>())?
     );
 
+    // Exported files cannot send headers, so the CSP travels in a meta tag;
+    // Permissions-Policy has no meta form and only applies in dev
+    if deck_root.is_some() {
+        let mut policy = page_policy(deck, slides, components, import_map, lockfile, &html).strict();
+        for directive in ["script-src", "style-src", "img-src", "font-src", "media-src"] {
+            policy.add_source(directive, "file:");
+        }
+        let meta = format!(
+            "<meta charset=\"utf-8\">\n    <meta http-equiv=\"Content-Security-Policy\" content=\"{}\">",
+            html_escape(&policy.csp())
+        );
+        return Ok(html.replacen("<meta charset=\"utf-8\">", &meta, 1));
+    }
+
     Ok(html)
 }
 
-fn inline_css(base: Option<&Path>, path_str: &str) -> Option<String> {
-    use std::fs;
-    let mut candidates: Vec<PathBuf> = Vec::new();
+/// Visually hidden links to every slide (`#/<id>`), for screen readers and for
+/// copying a link to one slide
+fn slide_links(deck: &DeckManifest, slides: &HashMap<String, SlideDoc>) -> String {
+    let links: Vec<String> = deck
+        .slide_order()
+        .into_iter()
+        .filter_map(|id| slides.get(id))
+        .map(|slide| {
+            format!(
+                "<li><a href=\"#/{}\">{}</a></li>",
+                html_escape(&slide.id),
+                html_escape(slide.props["title"].as_str().unwrap_or(&slide.id))
+            )
+        })
+        .collect();
+    format!(
+        "<nav class=\"coolslides-slide-links\" aria-label=\"Slides\" style=\"position: absolute; width: 1px; height: 1px; overflow: hidden; clip-path: inset(50%); white-space: nowrap\"><ol>{}</ol></nav>",
+        links.join("")
+    )
+}
+
+/// Module scripts for component versions other than the default, which the
+/// components bundle does not load; URLs come from the lockfile when it locks the version
+fn versioned_module_scripts(
+    slides: &HashMap<String, SlideDoc>,
+    components: Option<&ComponentRegistry>,
+    lockfile: Option<&Lockfile>,
+) -> String {
+    let Some(registry) = components else {
+        return String::new();
+    };
+    let mut modules: Vec<String> = slides
+        .values()
+        .filter_map(|slide| registry.resolve(&slide.component.name, &slide.component.version_req))
+        .filter(|manifest| registry.components.get(&manifest.name).is_some_and(|d| d.version != manifest.version))
+        .map(|manifest| {
+            lockfile
+                .and_then(|lock| lock.component_url(&manifest.name, &manifest.version))
+                .unwrap_or(&manifest.module)
+                .to_string()
+        })
+        .collect();
+    modules.sort();
+    modules.dedup();
+    modules
+        .iter()
+        .map(|module| format!("<script type=\"module\" src=\"{}\"></script>", html_escape(module)))
+        .collect::<Vec<_>>()
+        .join("\n    ")
+}
+
+/// Module script that loads components renamed by `tagCollisions = "prefix"`.
+/// Modules that keep a tag load first; each renamed module then loads with
+/// `customElements.define` mapping its original tag to the prefixed one.
+fn tag_rename_script(components: Option<&ComponentRegistry>) -> String {
+    let Some(registry) = components.filter(|r| !r.tag_renames.is_empty()) else {
+        return String::new();
+    };
+    let mut keepers: Vec<&str> = registry
+        .tag_renames
+        .iter()
+        .filter_map(|rename| registry.tag_to_name.get(&rename.original))
+        .filter_map(|name| registry.components.get(name))
+        .map(|manifest| manifest.module.as_str())
+        .collect();
+    keepers.sort();
+    keepers.dedup();
+
+    let mut renamed: Vec<(&str, serde_json::Map<String, serde_json::Value>)> = Vec::new();
+    for rename in &registry.tag_renames {
+        let index = match renamed.iter().position(|(module, _)| *module == rename.module) {
+            Some(index) => index,
+            None => {
+                renamed.push((&rename.module, serde_json::Map::new()));
+                renamed.len() - 1
+            }
+        };
+        renamed[index].1.insert(rename.original.clone(), rename.tag.clone().into());
+    }
+    let renamed: Vec<serde_json::Value> = renamed
+        .into_iter()
+        .map(|(module, tags)| serde_json::json!({ "module": module, "tags": tags }))
+        .collect();
+
+    format!(
+        r#"<script type="module">const keep={};const renamed={};await Promise.all(keep.map(u=>import(u)));const define=customElements.define.bind(customElements);try{{for(const r of renamed){{customElements.define=(t,c,o)=>define(r.tags[t]||t,c,o);await import(r.module);}}}}finally{{customElements.define=define;}}</script>"#,
+        serde_json::Value::from(keepers).to_string().replace("</", "<\\/"),
+        serde_json::Value::from(renamed).to_string().replace("</", "<\\/"),
+    )
+}
+
+/// Module preloads carrying the lockfile's integrity hashes, so the browser
+/// rejects a locked component, plugin, or vendored module whose contents changed
+fn integrity_preloads(lockfile: Option<&Lockfile>) -> String {
+    let Some(lockfile) = lockfile else {
+        return String::new();
+    };
+    let mut preloads: Vec<(String, &str)> = lockfile
+        .resolved
+        .components
+        .values()
+        .chain(lockfile.resolved.plugins.values())
+        .filter_map(|package| package.integrity.as_deref().map(|integrity| (package.url.clone(), integrity)))
+        .chain(lockfile.vendored.values().map(|module| (format!("./{}", module.path), module.integrity.as_str())))
+        .collect();
+    preloads.sort();
+    preloads.dedup();
+    preloads
+        .iter()
+        .map(|(url, integrity)| {
+            format!(
+                "<link rel=\"modulepreload\" href=\"{}\" integrity=\"{}\" crossorigin=\"anonymous\">",
+                html_escape(url),
+                html_escape(integrity)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n    ")
+}
+
+/// Read the deck's `importmap.json`, if present and valid
+fn read_import_map(deck_root: &Path) -> Option<ImportMap> {
+    let content = std::fs::read(deck_root.join("importmap.json")).ok()?;
+    serde_json::from_slice(&content).ok()
+}
+
+/// Read the deck's `.coolslides.lock`, if present and valid
+fn read_lockfile(deck_root: &Path) -> Option<Lockfile> {
+    let content = std::fs::read(deck_root.join(".coolslides.lock")).ok()?;
+    serde_json::from_slice(&content).ok()
+}
+
+/// Files a stylesheet path may refer to: absolute, relative to the deck, or
+/// relative to the working directory (where `/themes` is served from)
+fn css_candidates(base: Option<&Path>, path_str: &str) -> Vec<PathBuf> {
     let p = PathBuf::from(path_str);
     if p.is_absolute() {
-        candidates.push(p);
-    } else {
-        if let Some(b) = base {
-            candidates.push(b.join(path_str));
-        }
-        candidates.push(PathBuf::from(path_str));
+        return vec![p];
     }
+    let mut candidates: Vec<PathBuf> = base.map(|b| b.join(path_str)).into_iter().collect();
+    candidates.push(p);
+    candidates
+}
 
-    for cand in candidates {
-        if let Ok(content) = fs::read_to_string(&cand) {
-            return Some(content);
-        }
-    }
+fn inline_css(base: Option<&Path>, path_str: &str) -> Option<String> {
+    css_candidates(base, path_str).into_iter().find_map(|cand| std::fs::read_to_string(cand).ok())
+}
+
+/// `href` a deck stylesheet is linked with in dev mode
+fn stylesheet_href(path: &str) -> String {
+    if path.starts_with('/') { path.to_string() } else { format!("/{}", path) }
+}
+
+/// The deck's theme and tokens files, canonicalized, with the `href` each is linked with
+fn linked_stylesheets(deck: &DeckManifest, deck_dir: &Path) -> Vec<(PathBuf, String)> {
+    std::iter::once(&deck.theme)
+        .chain(deck.tokens.as_ref())
+        .filter_map(|path| {
+            let file = css_candidates(Some(deck_dir), path).into_iter().find_map(|cand| cand.canonicalize().ok())?;
+            Some((file, stylesheet_href(path)))
+        })
+        .collect()
+}
 
-    None
+/// The `href`s to hot-swap when every changed file is a linked stylesheet; `None`
+/// when anything else changed and the deck needs a full reload
+fn stylesheet_updates(linked: &[(PathBuf, String)], changed: &[&PathBuf]) -> Option<Vec<String>> {
+    let mut hrefs: Vec<String> = changed
+        .iter()
+        .map(|path| {
+            let path = path.canonicalize().ok()?;
+            linked.iter().find(|(file, _)| *file == path).map(|(_, href)| href.clone())
+        })
+        .collect::<Option<_>>()?;
+    hrefs.dedup();
+    (!hrefs.is_empty()).then_some(hrefs)
 }
 
 fn html_escape(text: &str) -> String {
@@ -991,78 +3072,236 @@ fn html_escape(text: &str) -> String {
 }
 
 /// WebSocket handler for rooms
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RoomQuery {
+    role: Option<rooms::ClientRole>,
+    token: Option<String>,
+    /// Resume token from the client's last `session` message
+    resume: Option<String>,
+    /// Last `seq` the client saw before it lost the connection
+    last_seq: Option<u64>,
+}
+
+/// Join a room. `?role=presenter` needs the presenter token; without a token
+/// configured, every client presents. Clients can also ask for a role with a
+/// `join` message once connected, which keeps the token out of the URL.
+/// `?resume=<token>&lastSeq=<n>` rejoins as the client the token was issued to.
 async fn websocket_handler(
     ws: WebSocketUpgrade,
     AxumPath(room_id): AxumPath<String>,
+    Query(query): Query<RoomQuery>,
+    headers: HeaderMap,
     State(state): State<AppState>,
-) -> axum::response::Response {
+) -> Result<Response, StatusCode> {
+    let role = match (&state.presenter_token, query.role) {
+        (None, _) => rooms::ClientRole::Presenter,
+        (Some(_), Some(rooms::ClientRole::Presenter)) => {
+            if !state.presenter_authorized(&headers, query.token) {
+                return Err(StatusCode::UNAUTHORIZED);
+            }
+            rooms::ClientRole::Presenter
+        }
+        (Some(_), _) => rooms::ClientRole::Audience,
+    };
+
     // Ensure room exists with the provided room_id
     let _ = state.room_manager.ensure_room(room_id.clone()).await;
     
     let room_manager = state.room_manager.clone();
-    ws.on_upgrade(move |socket| {
-        rooms::handle_websocket_connection(socket, room_id, room_manager)
-    })
+    let presenter_token = state.presenter_token.clone();
+    let resume = query.resume.map(|token| rooms::ResumeRequest { token, last_seq: query.last_seq.unwrap_or(0) });
+    Ok(ws.on_upgrade(move |socket| {
+        rooms::handle_websocket_connection(socket, room_id, room_manager, role, presenter_token, resume)
+    }))
+}
+
+#[derive(Deserialize)]
+struct CollabQuery {
+    protocol: Option<collab::Protocol>,
+    token: Option<String>,
+}
+
+/// Join the collaborative editing session of a deck TOML file. Edits are
+/// written to disk, so this takes the presenter token like the edit APIs.
+async fn collab_handler(
+    ws: WebSocketUpgrade,
+    AxumPath(path): AxumPath<String>,
+    Query(query): Query<CollabQuery>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<Response, StatusCode> {
+    if !state.presenter_authorized(&headers, query.token) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    let relative = safe_relative_path(&path)?;
+    if relative.extension().and_then(|e| e.to_str()) != Some("toml") {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let deck_root = state.deck_root.read().await.clone().ok_or(StatusCode::NOT_FOUND)?;
+    let file = deck_root.join(relative).canonicalize().map_err(|_| StatusCode::NOT_FOUND)?;
+    let protocol = query.protocol.unwrap_or(collab::Protocol::Json);
+    Ok(ws.on_upgrade(move |socket| collab::handle_connection(socket, state.collab.clone(), file, protocol)))
+}
+
+/// Structured hot-reload events for deck pages
+async fn hmr_handler(ws: WebSocketUpgrade, State(state): State<AppState>) -> Response {
+    let events = state.hmr.subscribe();
+    ws.on_upgrade(move |socket| hmr::handle_connection(socket, events))
+}
+
+/// Presenter console: previews, speaker notes, timer, and room controls
+async fn presenter_ui(
+    Query(query): Query<TokenQuery>,
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<Html<String>, StatusCode> {
+    if !state.presenter_authorized(&headers, query.token) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    let deck = state.deck.read().await.clone().ok_or(StatusCode::NOT_FOUND)?;
+    let slides = state.slides.read().await;
+    let config = state.sanitization_config.for_deck(&deck);
+    Ok(Html(presenter::page(&deck, &slides, &config)))
+}
+
+/// Audience view following a room (`?room=`), with free browsing
+async fn audience_ui(State(state): State<AppState>) -> Result<Html<String>, StatusCode> {
+    let deck = state.deck.read().await;
+    let deck = deck.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Html(audience::page(&deck.title)))
+}
+
+/// Speaker notes as a scrolling script that follows a room (`?room=`)
+async fn teleprompter_ui(State(state): State<AppState>) -> Result<Html<String>, StatusCode> {
+    let deck = state.deck.read().await.clone().ok_or(StatusCode::NOT_FOUND)?;
+    let slides = state.slides.read().await;
+    let config = state.sanitization_config.for_deck(&deck);
+    Ok(Html(teleprompter::page(&deck, &slides, &config)))
+}
+
+#[derive(Deserialize)]
+struct JoinQuery {
+    room: Option<String>,
 }
 
-/// Presenter UI
-async fn presenter_ui() -> Html<&'static str> {
-    Html(r#"
-    <!DOCTYPE html>
-    <html>
-    <head>
-        <title>Coolslides Presenter</title>
-        <meta charset="utf-8">
-        <meta name="viewport" content="width=device-width, initial-scale=1">
-    </head>
-    <body>
-        <h1>Coolslides Presenter View</h1>
-        <p>Presenter interface will be here</p>
-        <!-- TODO: Implement presenter UI -->
-    </body>
-    </html>
-    "#)
-}
-
-/// Audience UI
-async fn audience_ui() -> Html<&'static str> {
-    Html(r#"
-    <!DOCTYPE html>
-    <html>
-    <head>
-        <title>Coolslides Audience</title>
-        <meta charset="utf-8">
-        <meta name="viewport" content="width=device-width, initial-scale=1">
-    </head>
-    <body>
-        <h1>Coolslides Audience View</h1>
-        <p>Audience interface will be here</p>
-        <!-- TODO: Implement audience UI -->
-    </body>
-    </html>
-    "#)
+/// QR code for the audience page of the talk's room, or of `?room=`, in LAN mode.
+/// The room is created if it does not exist yet.
+async fn join_ui(State(state): State<AppState>, Query(query): Query<JoinQuery>, headers: HeaderMap) -> Result<Html<String>, StatusCode> {
+    let lan = state.lan.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    let room = state.room_manager.ensure_room(query.room.unwrap_or_else(|| lan.room.clone())).await;
+    let base_url = match &lan.base_url {
+        Some(url) => url.clone(),
+        None => {
+            let host = headers.get(header::HOST).and_then(|h| h.to_str().ok()).ok_or(StatusCode::BAD_REQUEST)?;
+            format!("http://{}", host)
+        }
+    };
+    let title = state.deck.read().await.as_ref().map(|deck| deck.title.clone()).unwrap_or_else(|| "Coolslides".to_string());
+    let page = lan::join_page(&title, &lan::audience_url(&base_url, &room)).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Html(page))
 }
 
 /// Start the development server with directory and strict mode
 pub async fn start_server_with_dir(host: &str, port: u16, deck_dir: Option<&str>, strict_mode: bool) -> anyhow::Result<()> {
-    let state = AppState::new_with_strict_mode(strict_mode);
+    start_server_with_db(host, port, deck_dir, strict_mode, None).await
+}
+
+/// Start the development server, persisting rooms, analytics, and export jobs
+/// to the SQLite database at `db` when given
+pub async fn start_server_with_db(host: &str, port: u16, deck_dir: Option<&str>, strict_mode: bool, db: Option<&Path>) -> anyhow::Result<()> {
+    let config = config::ServerConfig::load(Path::new(deck_dir.unwrap_or(".")))?;
+    start_server_with_config(host, port, deck_dir, strict_mode, db, config).await
+}
+
+/// Start the development server with settings from `coolslides.config.toml`,
+/// after any command-line overrides
+pub async fn start_server_with_config(
+    host: &str,
+    port: u16,
+    deck_dir: Option<&str>,
+    strict_mode: bool,
+    db: Option<&Path>,
+    config: config::ServerConfig,
+) -> anyhow::Result<()> {
+    // Privileged routes always need a token; one is made up when none is given
+    let presenter_token = config.presenter_token.unwrap_or_else(hub::new_token);
+    let mut state = AppState::new_with_strict_mode(strict_mode || config.strict)
+        .with_cors(config.cors)
+        .with_presenter_token(presenter_token.clone())
+        .with_asset_mounts(config.assets)
+        .with_sanitization(config.sanitization)
+        .with_watch(config.watch)
+        .with_room_limits(config.rooms);
+    // Shared before the store is set up, so the store's room manager shares too
+    #[cfg(feature = "redis")]
+    let mut outgoing = None;
+    #[cfg(feature = "redis")]
+    if config.redis_url.is_some() {
+        let (fanout, receiver) = room_fanout::Fanout::new();
+        state = state.with_room_fanout(Arc::new(fanout));
+        outgoing = Some(receiver);
+    }
+    // LAN mode listens on every interface so audience devices can connect
+    let host = if config.lan { "0.0.0.0" } else { host };
+    if let Some(db) = db {
+        let storage = storage::Storage::open(db).await?;
+        state = state.with_storage(Arc::new(storage));
+        let restored = state.room_manager.restore().await?;
+        tracing::info!(db = %db.display(), rooms = restored, "restored rooms from database");
+    } else if let Some(dir) = &config.state_dir {
+        // Relative to the deck, like the config file's other paths
+        let dir = Path::new(deck_dir.unwrap_or(".")).join(dir);
+        state = state.with_room_store(room_store::RoomStore::files(&dir));
+        let restored = state.room_manager.restore().await?;
+        tracing::info!(dir = %dir.display(), rooms = restored, "restored rooms from state directory");
+    }
+    // Started once the room manager is final, since the store replaces it
+    #[cfg(feature = "redis")]
+    if let (Some(url), Some(outgoing)) = (&config.redis_url, outgoing) {
+        room_fanout::spawn_redis(url, outgoing, state.room_manager.clone())?;
+    }
     
     // Load deck from directory (default to current directory)
     let deck_path = deck_dir.unwrap_or(".");
     if let Err(e) = state.load_from_directory(deck_path).await {
-        println!("Warning: Failed to load deck from {}: {}", deck_path, e);
-        println!("Server will start but /api/deck and /api/slide endpoints will return 404");
+        tracing::warn!(dir = %deck_path, error = %e, "could not load deck; /api/deck and /api/slide return 404 until it loads");
     }
     
     // Start file watcher for hot reloading
     if let Err(e) = state.start_file_watcher(deck_path).await {
-        println!("Warning: Failed to start file watcher: {}", e);
+        tracing::warn!(error = %e, "could not start file watcher");
     }
+    git::spawn_head_watcher(state.clone(), PathBuf::from(deck_path));
+    webhooks::spawn_dispatcher(state.clone());
     
+    let loopback = host == "localhost" || host.parse::<std::net::IpAddr>().is_ok_and(|ip| ip.is_loopback());
+    if !loopback && state.cors.mode == cors::CorsMode::Permissive {
+        tracing::warn!("any website can call this server's API; use --cors locked when it is reachable on a network");
+    }
+    let lan = if config.lan {
+        let lan = lan::LanConfig::new(port, lan::new_room_id());
+        state.room_manager.ensure_room(lan.room.clone()).await;
+        state = state.with_lan(lan.clone());
+        Some(lan)
+    } else {
+        None
+    };
     let app = create_router(state);
     
     let listener = tokio::net::TcpListener::bind(format!("{}:{}", host, port)).await?;
     println!("Coolslides dev server running on http://{}:{}", host, port);
+    if let Some(lan) = lan {
+        match &lan.base_url {
+            Some(url) => {
+                println!("Audience join page (show it on the projector): {}/join", url);
+                println!("Presenter: {}/presenter?room={}&token={}", url, lan.room, presenter_token);
+            }
+            None => tracing::warn!("no LAN address found; open /join on this machine's network address"),
+        }
+        println!("Room: {}", lan.room);
+    }
+    println!("Presenter token: {} (open /presenter?token=<token>, or /?token=<token> to drive rooms)", presenter_token);
     println!("Serving deck from: {}", std::fs::canonicalize(deck_path).unwrap_or_else(|_| deck_path.into()).display());
     
     axum::serve(listener, app).await?;
@@ -1078,3 +3317,571 @@ pub async fn start_server(host: &str, port: u16) -> anyhow::Result<()> {
 pub async fn start_server_with_strict(host: &str, port: u16, strict_mode: bool) -> anyhow::Result<()> {
     start_server_with_dir(host, port, None, strict_mode).await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_slide(dir: &Path, file: &str, id: &str, title: &str) {
+        let slide = format!("modelVersion = \"1.0\"\nid = \"{}\"\n\n[component]\nname = \"TitleSlide\"\nversionReq = \"^1\"\n\n[props]\ntitle = \"{}\"\n", id, title);
+        std::fs::write(dir.join("content").join(file), slide).unwrap();
+    }
+
+    #[test]
+    fn test_theme_and_tokens_edits_are_swapped_in_place() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("content")).unwrap();
+        for file in ["theme.css", "tokens.css", "print.css"] {
+            std::fs::write(dir.path().join(file), "body {}").unwrap();
+        }
+        let deck: DeckManifest = toml::from_str("modelVersion = \"1.0\"\ntitle = \"T\"\ntheme = \"theme.css\"\ntokens = \"/tokens.css\"\nsequence = []\n\n[transitions]\ndefault = \"none\"\n").unwrap();
+        // An absolute path is taken as is, so the tokens file is not found there
+        let linked = linked_stylesheets(&deck, dir.path());
+        assert_eq!(linked.iter().map(|(_, href)| href.as_str()).collect::<Vec<_>>(), ["/theme.css"]);
+
+        let theme = dir.path().join("theme.css");
+        let print = dir.path().join("print.css");
+        assert_eq!(stylesheet_updates(&linked, &[&theme, &theme]), Some(vec!["/theme.css".to_string()]));
+        assert_eq!(stylesheet_updates(&linked, &[&theme, &print]), None);
+        assert_eq!(stylesheet_updates(&linked, &[]), None);
+    }
+
+    #[test]
+    fn test_deck_import_map_overrides_locked_and_builtin_urls() {
+        let cdn = "https://cdn.jsdelivr.net/npm/@coolslides/runtime@0.3.0/dist/index.js";
+        let deck_map = ImportMap { imports: HashMap::from([("@coolslides/runtime".to_string(), cdn.to_string())]) };
+        let lockfile: Lockfile = serde_json::from_value(serde_json::json!({
+            "modelVersion": "1.0",
+            "resolved": {},
+            "importMap": { "imports": { "@coolslides/runtime": "/stale.js", "chart.js": "./vendor/chart.js" } },
+            "timestamp": "2024-01-01T00:00:00Z"
+        }))
+        .unwrap();
+        let map = import_map_json(None, Some(&deck_map), Some(&lockfile));
+        assert_eq!(map["imports"]["@coolslides/runtime"], cdn);
+        assert_eq!(map["imports"]["chart.js"], "./vendor/chart.js");
+        assert_eq!(map["imports"]["@coolslides/components"], "/packages/components/dist/index.js");
+
+        let deck: DeckManifest = toml::from_str("modelVersion = \"1.0\"\ntitle = \"T\"\ntheme = \"theme.css\"\nsequence = []\n\n[transitions]\ndefault = \"none\"\n").unwrap();
+        let html = generate_export_html(&deck, &HashMap::new(), None, None, Some(&deck_map), None, &SanitizationConfig::new(false)).unwrap();
+        // Dev pages load it through the offline cache
+        assert!(html.contains("<script type=\"module\" src=\"/proxy/cdn.jsdelivr.net/npm/@coolslides/runtime@0.3.0/dist/index.js\"></script>"));
+        assert!(html.contains("<script type=\"module\" src=\"/packages/components/dist/index.js\"></script>"));
+        let policy = page_policy(&deck, &HashMap::new(), None, Some(&deck_map), None, &html);
+        assert!(policy.csp().contains("https://cdn.jsdelivr.net"), "{}", policy.csp());
+    }
+
+    #[test]
+    fn test_export_escapes_the_deck_title() {
+        let deck: DeckManifest = toml::from_str("modelVersion = \"1.0\"\ntitle = \"Q&A </title><script>x()</script>\"\ntheme = \"theme.css\"\nsequence = []\n\n[transitions]\ndefault = \"none\"\n").unwrap();
+        let html = generate_export_html(&deck, &HashMap::new(), None, None, None, None, &SanitizationConfig::new(false)).unwrap();
+        assert!(html.contains("<title>Q&amp;A &lt;/title&gt;&lt;script&gt;x()&lt;/script&gt;</title>"), "{}", html);
+        let head = &html[..html.find("</head>").unwrap()];
+        assert!(!head.contains("<script>x()"), "{}", head);
+    }
+
+    #[tokio::test]
+    async fn test_resolved_deck_applies_profiles_and_transitions() {
+        use tower::ServiceExt;
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("content")).unwrap();
+        std::fs::write(
+            dir.path().join("slides.toml"),
+            r#"modelVersion = "1.0"
+title = "T"
+theme = "theme.css"
+sequence = ["a", { name = "Deep dive", transition = "zoom", slides = ["b", "c"] }]
+
+[transitions]
+default = "fade"
+
+[transitions.overrides]
+c = "none"
+
+[conditions]
+excludeIds = ["a"]
+
+[profiles.short]
+includeTags = ["core"]
+"#,
+        )
+        .unwrap();
+        for id in ["a", "b", "c"] {
+            write_slide(dir.path(), &format!("{}.slide.toml", id), id, id);
+        }
+        let state = AppState::new();
+        state.load_from_directory(dir.path()).await.unwrap();
+        for id in ["a", "c"] {
+            state.slides.write().await.get_mut(id).unwrap().tags.push("core".into());
+        }
+        let app = create_router(state);
+        let get = |uri: &str| app.clone().oneshot(axum::http::Request::builder().uri(uri).body(axum::body::Body::empty()).unwrap());
+        let json = |response: axum::response::Response| async move {
+            serde_json::from_slice::<serde_json::Value>(&axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap()
+        };
+
+        let resolved = json(get("/api/deck/resolved").await.unwrap()).await;
+        assert_eq!(
+            resolved["slides"],
+            serde_json::json!([
+                { "id": "b", "group": "Deep dive", "transition": "zoom" },
+                { "id": "c", "group": "Deep dive", "transition": "none" },
+            ])
+        );
+        let short = json(get("/api/deck/resolved?profile=short").await.unwrap()).await;
+        let ids: Vec<&str> = short["slides"].as_array().unwrap().iter().map(|s| s["id"].as_str().unwrap()).collect();
+        assert_eq!(ids, ["a", "c"]);
+        assert_eq!(short["slides"][0]["transition"], "fade");
+        assert_eq!(get("/api/deck/resolved?profile=long").await.unwrap().status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_slide_links_open_the_deck_on_that_slide() {
+        use tower::ServiceExt;
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("content")).unwrap();
+        std::fs::write(dir.path().join("slides.toml"), "modelVersion = \"1.0\"\ntitle = \"Talk\"\ntheme = \"theme.css\"\nsequence = [\"a\", \"b\"]\n\n[transitions]\ndefault = \"none\"\n").unwrap();
+        write_slide(dir.path(), "a.slide.toml", "a", "Hello");
+        write_slide(dir.path(), "b.slide.toml", "b", "Results");
+        write_slide(dir.path(), "c.slide.toml", "unused", "Unused");
+        let state = AppState::new();
+        state.load_from_directory(dir.path()).await.unwrap();
+        let app = create_router(state);
+        let get = |uri: &str| app.clone().oneshot(axum::http::Request::builder().uri(uri).body(axum::body::Body::empty()).unwrap());
+        let text = |response: axum::response::Response| async move {
+            String::from_utf8(axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap().to_vec()).unwrap()
+        };
+
+        let root = text(get("/").await.unwrap()).await;
+        assert!(root.contains(r##"<li><a href="#/a">Hello</a></li><li><a href="#/b">Results</a></li>"##));
+        assert!(!root.contains("coolslides:initial-slide"));
+
+        let linked = get("/slide/b").await.unwrap();
+        assert_eq!(linked.status(), StatusCode::OK);
+        let linked = text(linked).await;
+        assert!(linked.contains("<title>Results · Talk</title>"));
+        assert!(linked.contains(r#"<meta name="coolslides:initial-slide" content="b">"#));
+        assert_eq!(get("/slide/unused").await.unwrap().status(), StatusCode::NOT_FOUND);
+        assert_eq!(get("/slide/missing").await.unwrap().status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_reload_merges_only_changed_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("content")).unwrap();
+        std::fs::write(dir.path().join("slides.toml"), "modelVersion = \"1.0\"\ntitle = \"T\"\ntheme = \"theme.css\"\nsequence = []\n\n[transitions]\ndefault = \"none\"\n").unwrap();
+        write_slide(dir.path(), "a.slide.toml", "a", "A");
+        write_slide(dir.path(), "b.slide.toml", "b", "B");
+
+        let state = AppState::new();
+        state.load_from_directory(dir.path()).await.unwrap();
+        assert_eq!(state.slides.read().await.len(), 2);
+
+        // Unchanged files keep their parsed slides
+        state.slides.write().await.get_mut("a").unwrap().tags.push("kept".into());
+        write_slide(dir.path(), "b.slide.toml", "renamed", "B2");
+        let changes = state.load_changes(dir.path()).await.unwrap();
+        assert_eq!(changes, DeckChanges { manifest: false, slides: vec!["renamed".into()], removed: vec!["b".into()] });
+        assert_eq!(changes.to_string(), "slides renamed; removed b");
+        {
+            let slides = state.slides.read().await;
+            assert_eq!(slides["a"].tags, vec!["kept".to_string()]);
+            assert!(!slides.contains_key("b"));
+            assert_eq!(slides["renamed"].props["title"], "B2");
+        }
+
+        // Rewriting a file with the same content changes nothing
+        write_slide(dir.path(), "b.slide.toml", "renamed", "B2");
+        assert!(state.load_changes(dir.path()).await.unwrap().is_empty());
+
+        std::fs::remove_file(dir.path().join("content/a.slide.toml")).unwrap();
+        state.load_from_directory(dir.path()).await.unwrap();
+        assert_eq!(state.slides.read().await.keys().collect::<Vec<_>>(), vec!["renamed"]);
+    }
+
+    #[tokio::test]
+    async fn test_file_events_are_batched_until_writes_settle() {
+        let event = |path: &str| notify::Event::new(notify::EventKind::Any).add_path(PathBuf::from(path));
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        tx.send(event("content/b.slide.toml")).unwrap();
+        tx.send(event("slides.toml")).unwrap();
+        let late = tx.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(WATCH_DEBOUNCE / 2).await;
+            late.send(event("content/b.slide.toml")).unwrap();
+        });
+
+        let first = rx.recv().await.unwrap();
+        let paths = collect_changed_paths(&mut rx, first).await;
+        assert_eq!(paths, [PathBuf::from("content/b.slide.toml"), PathBuf::from("slides.toml")]);
+        // Events after the quiet window start the next batch
+        tx.send(event("theme.css")).unwrap();
+        drop(tx);
+        let first = rx.recv().await.unwrap();
+        assert_eq!(collect_changed_paths(&mut rx, first).await, [PathBuf::from("theme.css")]);
+    }
+
+    #[tokio::test]
+    async fn test_remote_navigation_requires_token_and_moves_room() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("content")).unwrap();
+        std::fs::write(
+            dir.path().join("slides.toml"),
+            "modelVersion = \"1.0\"\ntitle = \"T\"\ntheme = \"theme.css\"\nsequence = [\"a\", \"b\"]\n\n[transitions]\ndefault = \"none\"\n\n[remote]\ntoken = \"clicker\"\n",
+        )
+        .unwrap();
+        write_slide(dir.path(), "a.slide.toml", "a", "A");
+        write_slide(dir.path(), "b.slide.toml", "b", "B");
+        let state = AppState::new();
+        state.load_from_directory(dir.path()).await.unwrap();
+        state.room_manager.ensure_room("main".to_string()).await;
+
+        let mut headers = HeaderMap::new();
+        let no_token = TokenQuery { token: None };
+        let result = remote_navigate(&state, "main", &headers, no_token, RemoteTarget::Next).await;
+        assert_eq!(result.unwrap_err(), StatusCode::UNAUTHORIZED);
+
+        headers.insert(header::AUTHORIZATION, "Bearer clicker".parse().unwrap());
+        let query = || TokenQuery { token: None };
+        let Json(first) = remote_navigate(&state, "main", &headers, query(), RemoteTarget::Next).await.unwrap();
+        assert_eq!(first["slideId"], "a");
+        let Json(second) = remote_navigate(&state, "main", &headers, query(), RemoteTarget::Next).await.unwrap();
+        assert_eq!(second["slideId"], "b");
+        // Stays on the last slide
+        let Json(last) = remote_navigate(&state, "main", &headers, query(), RemoteTarget::Next).await.unwrap();
+        assert_eq!((last["slideId"].as_str(), last["index"].as_u64()), (Some("b"), Some(1)));
+        let Json(back) = remote_navigate(&state, "main", &headers, query(), RemoteTarget::Prev).await.unwrap();
+        assert_eq!(back["slideId"], "a");
+        let missing = remote_navigate(&state, "main", &headers, query(), RemoteTarget::Slide("zzz".into())).await;
+        assert_eq!(missing.unwrap_err(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_list_slides_in_order_with_filters() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("content")).unwrap();
+        std::fs::write(
+            dir.path().join("slides.toml"),
+            "modelVersion = \"1.0\"\ntitle = \"T\"\ntheme = \"theme.css\"\nsequence = [\"b\", \"a\"]\n\n[transitions]\ndefault = \"none\"\n\n[notes]\na = \"Say hi\"\n",
+        )
+        .unwrap();
+        write_slide(dir.path(), "a.slide.toml", "a", "A");
+        write_slide(dir.path(), "b.slide.toml", "b", "B");
+        write_slide(dir.path(), "c.slide.toml", "c", "C");
+        std::fs::write(
+            dir.path().join("content").join("d.slide.toml"),
+            "modelVersion = \"1.0\"\nid = \"d\"\ntags = [\"demo\"]\n\n[component]\nname = \"QuoteSlide\"\nversionReq = \"^1\"\n\n[props]\nquote = \"Q\"\n",
+        )
+        .unwrap();
+        let state = AppState::new();
+        state.load_from_directory(dir.path()).await.unwrap();
+        let list = |tag: Option<&str>, component: Option<&str>| {
+            let query = SlidesQuery { tag: tag.map(str::to_string), component: component.map(str::to_string) };
+            list_slides(State(state.clone()), Query(query))
+        };
+
+        let Json(all) = list(None, None).await;
+        let ids: Vec<&str> = all.iter().map(|slide| slide["id"].as_str().unwrap()).collect();
+        assert_eq!(ids, ["b", "a", "c", "d"]);
+        assert_eq!(all[1], serde_json::json!({ "id": "a", "component": "TitleSlide", "title": "A", "tags": [], "notes": 1 }));
+        assert!(all[3]["title"].is_null());
+
+        let Json(tagged) = list(Some("demo"), None).await;
+        assert_eq!(tagged.len(), 1);
+        let Json(titles) = list(None, Some("TitleSlide")).await;
+        assert_eq!(titles.len(), 3);
+        let Json(none) = list(Some("demo"), Some("TitleSlide")).await;
+        assert!(none.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_validate_reports_slide_and_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("content")).unwrap();
+        std::fs::write(
+            dir.path().join("slides.toml"),
+            "modelVersion = \"1.0\"\ntitle = \"T\"\ntheme = \"theme.css\"\nsequence = [\"a\", \"missing\"]\n\n[transitions]\ndefault = \"none\"\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("content").join("a.slide.toml"),
+            "modelVersion = \"1.0\"\nid = \"a\"\n\n[component]\nname = \"TitleSlide\"\nversionReq = \"^1\"\n\n[props]\ntitle = \"A\"\n\n[slots.body]\nkind = \"markdown\"\nvalue = \"\"\n",
+        )
+        .unwrap();
+        let state = AppState::new();
+        assert_eq!(validate_deck(State(state.clone())).await.unwrap_err(), StatusCode::NOT_FOUND);
+        state.load_from_directory(dir.path()).await.unwrap();
+
+        let Json(report) = validate_deck(State(state.clone())).await.unwrap();
+        assert_eq!(report["valid"], false);
+        let errors = report["errors"].as_array().unwrap();
+        let unknown = errors.iter().find(|e| e["code"] == "CS1002").unwrap();
+        assert_eq!((unknown["file"].as_str(), unknown["slideId"].as_str()), (Some("slides.toml"), None));
+        let empty = report["warnings"].as_array().unwrap().iter().find(|e| e["code"] == "CS1005").unwrap();
+        assert_eq!(empty["slideId"], "a");
+        assert_eq!(empty["file"], format!("content{}a.slide.toml", std::path::MAIN_SEPARATOR));
+    }
+
+    #[tokio::test]
+    async fn test_collab_requires_presenter_token() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("slides.toml"), "title = \"T\"\n").unwrap();
+        let state = AppState::new().with_presenter_token("s3cret");
+        *state.deck_root.write().await = Some(dir.path().to_path_buf());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move { axum::serve(listener, create_router(state)).await });
+
+        // Upgrades need a real connection, so speak HTTP over a socket
+        let upgrade = |query: &'static str| async move {
+            let mut stream = tokio::net::TcpStream::connect(address).await.unwrap();
+            let request = format!(
+                "GET /collab/slides.toml{} HTTP/1.1\r\nHost: {}\r\nConnection: Upgrade\r\nUpgrade: websocket\r\nSec-WebSocket-Version: 13\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\r\n",
+                query, address
+            );
+            stream.write_all(request.as_bytes()).await.unwrap();
+            let mut response = [0u8; 12];
+            stream.read_exact(&mut response).await.unwrap();
+            String::from_utf8_lossy(&response).into_owned()
+        };
+
+        assert_eq!(upgrade("").await, "HTTP/1.1 401");
+        assert_eq!(upgrade("?token=wrong").await, "HTTP/1.1 401");
+        assert_eq!(upgrade("?token=s3cret").await, "HTTP/1.1 101");
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn test_recording_requires_presenter_token() {
+        let state = AppState::new().with_presenter_token("s3cret");
+        state.room_manager.ensure_room("main".to_string()).await;
+        let start = |headers: HeaderMap, token: Option<&str>| {
+            let query = TokenQuery { token: token.map(str::to_string) };
+            start_recording(AxumPath("main".to_string()), Query(query), headers, State(state.clone()))
+        };
+
+        assert_eq!(start(HeaderMap::new(), None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(start(HeaderMap::new(), Some("wrong")).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(start(HeaderMap::new(), Some("s3cret")).await, StatusCode::OK);
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, "Bearer s3cret".parse().unwrap());
+        assert_eq!(start(headers, None).await, StatusCode::OK);
+
+        // Audience clients may react, but not move the room
+        assert!(rooms::is_presenter_event("slide:change") && rooms::is_presenter_event("presenter:sync"));
+        assert!(!rooms::is_presenter_event("reaction"));
+    }
+
+    #[tokio::test]
+    async fn test_replay_moves_the_room_as_recorded() {
+        let state = AppState::new().with_presenter_token("s3cret");
+        state.room_manager.ensure_room("rehearsal".to_string()).await;
+        state.room_manager.ensure_room("main".to_string()).await;
+        let rehearsal = state.room_manager.get_room("rehearsal").await.unwrap();
+        rehearsal.start_recording().await;
+        for slide in ["a", "b"] {
+            let data = serde_json::json!({ "slideId": slide });
+            rehearsal.handle_event(rooms::EventData { name: "slide:change".into(), data, client_id: "p".into() }).await;
+        }
+        rehearsal.stop_recording().await;
+
+        let replay = |request: serde_json::Value, token: Option<&str>| {
+            let query = TokenQuery { token: token.map(str::to_string) };
+            let request = serde_json::from_value(request).unwrap();
+            start_replay(AxumPath("main".to_string()), Query(query), HeaderMap::new(), State(state.clone()), Json(request))
+        };
+        let from_rehearsal = serde_json::json!({ "recording": "rehearsal", "timeCompression": 1000.0 });
+        assert_eq!(replay(from_rehearsal.clone(), None).await.unwrap_err(), StatusCode::UNAUTHORIZED);
+        let too_slow = serde_json::json!({ "recording": "rehearsal", "timeCompression": 0.0 });
+        assert_eq!(replay(too_slow, Some("s3cret")).await.unwrap_err(), StatusCode::BAD_REQUEST);
+        let missing = serde_json::json!({ "recording": "nope" });
+        assert_eq!(replay(missing, Some("s3cret")).await.unwrap_err(), StatusCode::NOT_FOUND);
+
+        let (status, Json(body)) = replay(from_rehearsal, Some("s3cret")).await.unwrap();
+        assert_eq!((status, body["messages"].as_u64()), (StatusCode::ACCEPTED, Some(2)));
+        let main = state.room_manager.get_room("main").await.unwrap();
+        for _ in 0..100 {
+            if main.state.read().await["currentSlide"]["slideId"] == "b" {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(main.state.read().await["currentSlide"]["slideId"], "b");
+    }
+
+    #[tokio::test]
+    async fn test_dump_serves_the_recording_in_each_format() {
+        let state = AppState::new().with_presenter_token("s3cret");
+        let dump_as = |format: recordings::RecordingFormat, token: Option<&str>| {
+            let query = DumpQuery { format, token: token.map(str::to_string) };
+            get_room_dump(AxumPath("main".to_string()), Query(query), HeaderMap::new(), State(state.clone()))
+        };
+        let dump = |format| dump_as(format, Some("s3cret"));
+        assert_eq!(dump(recordings::RecordingFormat::Json).await.unwrap_err(), StatusCode::NOT_FOUND);
+
+        state.room_manager.ensure_room("main".to_string()).await;
+        let room = state.room_manager.get_room("main").await.unwrap();
+        room.start_recording().await;
+        let data = serde_json::json!({ "slideId": "a" });
+        room.handle_event(rooms::EventData { name: "slide:change".into(), data, client_id: "p".into() }).await;
+
+        let ([(_, content_type)], body) = dump(recordings::RecordingFormat::Json).await.unwrap();
+        let recording: recordings::Recording = serde_json::from_str(&body).unwrap();
+        assert_eq!(content_type, "application/json");
+        assert_eq!((recording.info.room_id.as_str(), recording.messages.len()), ("main", 1));
+        assert!(recording.info.started_at.is_some() && recording.info.stopped_at.is_none());
+
+        room.stop_recording().await;
+        let ([(_, content_type)], body) = dump(recordings::RecordingFormat::Csv).await.unwrap();
+        assert_eq!(content_type, "text/csv; charset=utf-8");
+        assert_eq!(body.lines().count(), 2);
+        let ([_], body) = dump(recordings::RecordingFormat::Ndjson).await.unwrap();
+        let info: recordings::RecordingInfo = serde_json::from_str(body.lines().next().unwrap()).unwrap();
+        assert!(info.stopped_at.is_some());
+
+        // Presenter-only messages need the token
+        room.start_recording().await;
+        room.handle_event(rooms::EventData { name: "slide:change".into(), data: serde_json::json!({ "slideId": "b" }), client_id: "p".into() }).await;
+        room.handle_event(rooms::EventData { name: "presenter:notes".into(), data: serde_json::json!({ "text": "Pause" }), client_id: "p".into() }).await;
+        let events = |body: String| -> Vec<String> {
+            let recording: recordings::Recording = serde_json::from_str(&body).unwrap();
+            recording
+                .messages
+                .into_iter()
+                .filter_map(|recorded| match recorded.message {
+                    rooms::RoomMessage::Event { event, .. } => Some(event.name),
+                    _ => None,
+                })
+                .collect()
+        };
+        let ([_], public) = dump_as(recordings::RecordingFormat::Json, None).await.unwrap();
+        assert_eq!(events(public), ["slide:change"]);
+        let ([_], full) = dump(recordings::RecordingFormat::Json).await.unwrap();
+        assert_eq!(events(full), ["slide:change", "presenter:notes"]);
+    }
+
+    #[tokio::test]
+    async fn test_imported_recording_replays_into_the_room() {
+        let state = AppState::new().with_presenter_token("s3cret");
+        state.room_manager.ensure_room("rehearsal".to_string()).await;
+        let rehearsal = state.room_manager.get_room("rehearsal").await.unwrap();
+        rehearsal.start_recording().await;
+        let data = serde_json::json!({ "slideId": "b" });
+        rehearsal.handle_event(rooms::EventData { name: "slide:change".into(), data, client_id: "p".into() }).await;
+        rehearsal.stop_recording().await;
+        let exported = rehearsal.recording(None).await.render(recordings::RecordingFormat::Ndjson);
+
+        let import = |body: String, format: recordings::RecordingFormat, token: Option<&str>| {
+            let query = ImportQuery { token: token.map(str::to_string), format };
+            import_recording(AxumPath("demo".to_string()), Query(query), HeaderMap::new(), State(state.clone()), body)
+        };
+        let refused = import(exported.clone(), recordings::RecordingFormat::Ndjson, None).await.unwrap_err();
+        assert_eq!(refused.0, StatusCode::UNAUTHORIZED);
+        let refused = import(exported.clone(), recordings::RecordingFormat::Csv, Some("s3cret")).await.unwrap_err();
+        assert_eq!(refused.0, StatusCode::BAD_REQUEST);
+
+        // The room is opened for the import, and replays what was imported
+        let Json(imported) = import(exported, recordings::RecordingFormat::Ndjson, Some("s3cret")).await.unwrap();
+        assert_eq!(imported["messages"], 1);
+        let demo = state.room_manager.get_room("demo").await.unwrap();
+        assert_eq!(demo.recording(None).await.info.room_id, "demo");
+        demo.replay_recording(demo.get_recorded_messages().await, 1.0).await;
+        assert_eq!(demo.state.read().await["currentSlide"]["slideId"], "b");
+
+        demo.start_recording().await;
+        let refused = import("{\"roomId\":\"x\",\"messages\":[]}".to_string(), recordings::RecordingFormat::Json, Some("s3cret")).await.unwrap_err();
+        assert_eq!(refused.0, StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn test_room_state_summarises_slide_clients_and_polls() {
+        let state = AppState::new();
+        let missing = get_room_state(AxumPath("main".to_string()), State(state.clone())).await;
+        assert_eq!(missing.unwrap_err(), StatusCode::NOT_FOUND);
+
+        state.room_manager.ensure_room("main".to_string()).await;
+        let room = state.room_manager.get_room("main").await.unwrap();
+        room.add_client("p".to_string(), rooms::ClientRole::Presenter).await.unwrap();
+        room.add_client("a".to_string(), rooms::ClientRole::Audience).await.unwrap();
+        for (name, data) in [
+            ("fragment:change", serde_json::json!({ "slideId": "intro", "fragment": 3 })),
+            ("slide:change", serde_json::json!({ "slideId": "demo", "fragment": 0 })),
+            ("fragment:change", serde_json::json!({ "slideId": "demo", "fragment": 2 })),
+        ] {
+            room.handle_event(rooms::EventData { name: name.into(), data, client_id: "p".into() }).await;
+        }
+        let poll = rooms::NewPoll { question: "Ready?".into(), options: vec!["Yes".into(), "No".into()] };
+        room.create_poll(poll).await.unwrap();
+
+        let Json(summary) = get_room_state(AxumPath("main".to_string()), State(state.clone())).await.unwrap();
+        assert_eq!((summary.current_slide.as_deref(), summary.current_fragment), (Some("demo"), Some(2)));
+        assert_eq!((summary.clients.total, summary.clients.presenters, summary.clients.audience), (2, 1, 1));
+        assert_eq!(summary.polls[0].question, "Ready?");
+        assert_eq!(summary.seq, room.last_seq());
+        let json = serde_json::to_value(&summary).unwrap();
+        assert_eq!(json["currentSlide"], "demo");
+        assert_eq!(json["clients"]["presenters"], 1);
+    }
+
+    #[test]
+    fn test_math_renders_to_mathml_when_enabled() {
+        let markdown = "Euler: $e^{i\\pi} + 1 = 0$\n\n$$\\frac{a}{b}$$";
+        let html = render_markdown_to_html(markdown, &SanitizationConfig::new(false).with_math(true));
+        assert!(html.contains(r#"<math xmlns="http://www.w3.org/1998/Math/MathML"><semantics><mrow><msup><mi>e</mi>"#), "{}", html);
+        assert!(html.contains(r#"<math xmlns="http://www.w3.org/1998/Math/MathML" display="block">"#), "{}", html);
+        assert!(html.contains("<mo>+</mo>"), "{}", html);
+
+        let plain = render_markdown_to_html(markdown, &SanitizationConfig::new(false));
+        assert!(!plain.contains("<math") && plain.contains("$e^{i\\pi} + 1 = 0$"), "{}", plain);
+    }
+
+    #[test]
+    fn test_deck_sanitization_policy_extends_the_allowlist_per_slot() {
+        let deck: DeckManifest = toml::from_str(
+            r#"modelVersion = "1.0"
+title = "T"
+theme = "t.css"
+sequence = []
+
+[transitions]
+default = "none"
+
+[sanitization]
+allowTags = ["mark", "script"]
+urlSchemes = ["https"]
+
+[sanitization.allowAttributes]
+"*" = ["id"]
+a = ["target", "rel"]
+
+[sanitization.slots.aside]
+allowTags = ["kbd"]
+"#,
+        )
+        .unwrap();
+        let config = SanitizationConfig::new(false).for_deck(&deck);
+        let markdown = r#"<mark id="m">hi</mark> <kbd>K</kbd> <script>x()</script> [a](https://example.com) [b](ftp://example.com)"#;
+
+        let body = render_markdown_to_html(markdown, &config.for_slot("body"));
+        assert!(body.contains(r#"<mark id="m">hi</mark>"#), "{}", body);
+        assert!(!body.contains("<kbd>") && !body.contains("x()"), "{}", body);
+        assert!(body.contains(r#"href="https://example.com""#) && !body.contains("ftp:"), "{}", body);
+        let aside = render_markdown_to_html(markdown, &config.for_slot("aside"));
+        assert!(aside.contains("<kbd>K</kbd>"), "{}", aside);
+
+        let strict = render_markdown_to_html(markdown, &SanitizationConfig::new(true).for_deck(&deck));
+        assert!(!strict.contains("<mark"), "{}", strict);
+    }
+
+    #[test]
+    fn test_strict_mode_shows_diagrams_as_code_and_authors_cannot_write_placeholders() {
+        let markdown = "\u{E000}diagram:0\u{E000}\n\n```dot\ndigraph { a -> b }\n```\n";
+        let strict = render_markdown_to_html(markdown, &SanitizationConfig::new(true));
+        assert!(strict.contains("<pre><code") && strict.contains("digraph") && !strict.contains("<svg"), "{}", strict);
+        for html in [strict, render_markdown_to_html(markdown, &SanitizationConfig::new(false))] {
+            assert!(!html.contains('\u{E000}') && html.contains("<p>diagram:0</p>"), "{}", html);
+        }
+    }
+}
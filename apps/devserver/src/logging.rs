@@ -0,0 +1,68 @@
+/*!
+ * Server logs: human-readable lines, or JSON for a log collector
+ *
+ * The server reports through `tracing`. Room connections are spans carrying
+ * the room id, client id, and role, so every event a client causes can be
+ * traced back to it; reloads carry the `source` that triggered them. `RUST_LOG`
+ * overrides the level when set.
+ */
+
+use std::str::FromStr;
+use tracing_subscriber::EnvFilter;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// One line per event, for a terminal
+    #[default]
+    Text,
+    /// One JSON object per line, with span fields included
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            other => Err(anyhow::anyhow!("unknown log format {:?}; use text or json", other)),
+        }
+    }
+}
+
+/// Filter for `level`: `trace`, `debug`, `info`, `warn`, `error`, or `off`.
+/// The database driver logs every query at `info`, so it stays at `warn`.
+fn filter(level: &str) -> anyhow::Result<EnvFilter> {
+    if !matches!(level, "trace" | "debug" | "info" | "warn" | "error" | "off") {
+        anyhow::bail!("unknown log level {:?}; use trace, debug, info, warn, error or off", level);
+    }
+    Ok(EnvFilter::try_new(format!("{},sqlx=warn", level))?)
+}
+
+/// Send the process's logs to stderr at `level`, in `format`
+pub fn init(level: &str, format: LogFormat) -> anyhow::Result<()> {
+    let filter = match EnvFilter::try_from_default_env() {
+        Ok(filter) => filter,
+        Err(_) => filter(level)?,
+    };
+    let builder = tracing_subscriber::fmt().with_env_filter(filter).with_writer(std::io::stderr);
+    let installed = match format {
+        LogFormat::Text => builder.with_target(false).try_init(),
+        LogFormat::Json => builder.json().with_current_span(true).with_span_list(false).try_init(),
+    };
+    installed.map_err(|e| anyhow::anyhow!("could not set up logging: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_levels_and_formats_are_checked() {
+        assert_eq!("json".parse::<LogFormat>().unwrap(), LogFormat::Json);
+        assert!("yaml".parse::<LogFormat>().is_err());
+        assert!(filter("debug").unwrap().to_string().contains("sqlx=warn"));
+        assert!(filter("verbose").is_err());
+    }
+}
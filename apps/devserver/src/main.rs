@@ -1,10 +1,10 @@
-use coolslides_server::start_server;
+use coolslides_server::{logging, start_server};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     // Initialize tracing
-    tracing_subscriber::fmt::init();
+    logging::init("info", logging::LogFormat::Text)?;
     
     // Start the server
     start_server("127.0.0.1", 5173).await
-}
\ No newline at end of file
+}
@@ -0,0 +1,192 @@
+/*!
+ * Narrated playback: the deck page playing each slide's `[narration]` and
+ * advancing when it ends, for self-running decks and async viewers
+ *
+ * Like kiosk mode, the page is the one `/` serves with a config block and a
+ * player script appended, and the player drives the runtime through
+ * `location.hash`. Slides without narration stay up for their kiosk timing.
+ */
+
+use coolslides_core::{DeckManifest, SlideDoc};
+use serde_json::json;
+use std::collections::HashMap;
+
+const PLAYER_STYLE: &str = r#"<style>
+  #coolslides-narration-start {
+    position: fixed; inset: 0; z-index: 10000; display: flex; align-items: center; justify-content: center;
+    background: rgba(0, 0, 0, 0.6); color: #fff; font: 600 1.5rem system-ui, sans-serif; border: 0; cursor: pointer;
+  }
+  #coolslides-narration-start[hidden] { display: none; }
+</style>"#;
+
+const PLAYER_SCRIPT: &str = r#"<script type="module">
+  const config = JSON.parse(document.getElementById('coolslides-narration').textContent);
+  const slides = config.slides;
+  const audio = new Audio();
+  const start = document.createElement('button');
+  start.id = 'coolslides-narration-start';
+  start.textContent = '▶ Play narrated deck';
+  document.body.appendChild(start);
+
+  let index = 0;
+  let timer = null;
+  let remaining = 0;
+  let startedAt = 0;
+  let playing = false;
+
+  // Browsers only play audio after a gesture, so playback starts from a click
+  start.addEventListener('click', () => {
+    start.hidden = true;
+    show(0);
+  });
+
+  function schedule(seconds) {
+    clearTimeout(timer);
+    remaining = seconds;
+    startedAt = performance.now();
+    timer = setTimeout(advance, seconds * 1000);
+  }
+
+  function show(i) {
+    index = i;
+    playing = true;
+    const slide = slides[i];
+    const target = '#' + slide.id;
+    if (location.hash !== target) location.hash = target;
+    audio.pause();
+    if (slide.src) {
+      audio.src = slide.src;
+      audio.play().catch(() => {});
+    }
+    schedule(slide.duration);
+  }
+
+  function advance() {
+    if (index + 1 < slides.length) return show(index + 1);
+    // Finished; offer a replay
+    playing = false;
+    audio.pause();
+    start.textContent = '↻ Replay';
+    start.hidden = false;
+  }
+
+  function toggle() {
+    if (!start.hidden) return;
+    if (timer) {
+      clearTimeout(timer);
+      timer = null;
+      remaining -= (performance.now() - startedAt) / 1000;
+      audio.pause();
+    } else {
+      if (slides[index].src) audio.play().catch(() => {});
+      schedule(Math.max(remaining, 0));
+    }
+  }
+
+  window.addEventListener('keydown', event => {
+    if (event.key !== ' ' && event.key !== 'k') return;
+    event.preventDefault();
+    event.stopImmediatePropagation();
+    toggle();
+  }, { capture: true });
+
+  // Navigating by hand jumps the narration to the slide shown
+  window.addEventListener('hashchange', () => {
+    const i = slides.findIndex(slide => '#' + slide.id === location.hash);
+    if (playing && i >= 0 && i !== index) show(i);
+  });
+</script>"#;
+
+/// URL the player loads a slide's narration from, relative to the deck page so it
+/// also resolves under a hub mount
+pub(crate) fn audio_url(slide: &SlideDoc) -> Option<String> {
+    let narration = slide.narration.as_ref()?;
+    Some(match narration.is_remote() {
+        true => narration.src.clone(),
+        false => format!("narration/{}", slide.id),
+    })
+}
+
+/// Per-slide audio and timing for the player script
+pub(crate) fn config_json(deck: &DeckManifest, slides: &HashMap<String, SlideDoc>) -> serde_json::Value {
+    let kiosk = deck.kiosk.clone().unwrap_or_default();
+    json!({
+        "slides": deck
+            .slide_order()
+            .into_iter()
+            .map(|id| {
+                let slide = slides.get(id);
+                let narration = slide.and_then(|s| s.narration.as_ref()).filter(|n| n.duration > 0.0);
+                json!({
+                    "id": id,
+                    "src": slide.and_then(audio_url),
+                    "duration": narration.map_or_else(|| crate::kiosk::duration(&kiosk, id), |n| n.duration),
+                })
+            })
+            .collect::<Vec<_>>(),
+    })
+}
+
+/// Append the narrated player to a rendered deck page
+pub(crate) fn inject(html: &str, deck: &DeckManifest, slides: &HashMap<String, SlideDoc>) -> String {
+    // `</` would end the data block early
+    let config = config_json(deck, slides).to_string().replace("</", "<\\/");
+    let markup = format!(
+        "{}\n<script type=\"application/json\" id=\"coolslides-narration\">{}</script>\n{}\n",
+        PLAYER_STYLE, config, PLAYER_SCRIPT
+    );
+    match html.rfind("</body>") {
+        Some(at) => format!("{}{}{}", &html[..at], markup, &html[at..]),
+        None => format!("{}{}", html, markup),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_narrated_slides_advance_by_narration_length() {
+        let deck: DeckManifest = toml::from_str(
+            r#"
+modelVersion = "1.0"
+title = "Async"
+theme = "default"
+sequence = ["intro", "demo", "outro"]
+
+[transitions]
+default = "none"
+
+[kiosk]
+defaultDuration = 6
+"#,
+        )
+        .unwrap();
+        let slide = |id: &str, narration: &str| -> SlideDoc {
+            toml::from_str(&format!(
+                "modelVersion = \"1.0\"\nid = \"{}\"\nprops = {{}}\n\n[component]\nname = \"TitleSlide\"\nversionReq = \"^1\"\n{}",
+                id, narration
+            ))
+            .unwrap()
+        };
+        let slides: HashMap<String, SlideDoc> = [
+            slide("intro", "\n[narration]\nsrc = \"audio/intro.mp3\"\nduration = 42.5"),
+            slide("demo", ""),
+            slide("outro", "\n[narration]\nsrc = \"https://media.example.com/outro.ogg\"\nduration = 9"),
+        ]
+        .into_iter()
+        .map(|slide| (slide.id.clone(), slide))
+        .collect();
+
+        assert_eq!(
+            config_json(&deck, &slides)["slides"],
+            json!([
+                { "id": "intro", "src": "narration/intro", "duration": 42.5 },
+                { "id": "demo", "src": null, "duration": 6.0 },
+                { "id": "outro", "src": "https://media.example.com/outro.ogg", "duration": 9.0 },
+            ])
+        );
+        let page = inject("<html><body></body></html>", &deck, &slides);
+        assert!(page.contains(r#"id="coolslides-narration">{"#));
+    }
+}
@@ -0,0 +1,309 @@
+/*!
+ * Presenter console: current and next slide, speaker notes, timer, and controls
+ *
+ * The console joins a room (`?room=`, else `default`) in the presenter role,
+ * with the token it was opened with, and drives it: navigating sends the same
+ * `slide:change` event a presenting deck page sends, so every page in the room
+ * follows. It also follows the room, so a clicker on the remote-control API
+ * moves it too. The previews are the deck page itself in frames, opened
+ * offline so they never join the room. The header shows reloads, validation
+ * results, and exports from `/api/events`. Below the notes, audience questions
+ * wait to be approved or dismissed.
+ */
+
+use crate::{html_escape, teleprompter, SanitizationConfig};
+use coolslides_core::{DeckManifest, SlideDoc};
+use std::collections::HashMap;
+
+const PAGE_STYLE: &str = r#"<style>
+  * { box-sizing: border-box; }
+  html, body { margin: 0; height: 100%; background: #111; color: #eee; font: 15px/1.4 system-ui, sans-serif; }
+  body { display: grid; grid-template-columns: 3fr 2fr; grid-template-rows: auto 1fr; height: 100vh; }
+  header { grid-column: 1 / -1; display: flex; gap: 16px; align-items: center; padding: 8px 16px; background: #1b1b1b; border-bottom: 1px solid #2a2a2a; }
+  header .spacer { flex: 1; }
+  header button { background: #333; color: #eee; border: 0; border-radius: 4px; padding: 6px 14px; font: inherit; cursor: pointer; }
+  header button:hover { background: #444; }
+  #clock { font: 600 22px ui-monospace, monospace; }
+  #remaining.over { color: #ff6b6b; }
+  #status.offline { color: #ff6b6b; }
+  #server-status { color: #999; font-size: 13px; }
+  #server-status.failed { color: #ff6b6b; }
+  .previews { display: flex; flex-direction: column; gap: 12px; padding: 16px; min-height: 0; }
+  .frame { position: relative; aspect-ratio: 16 / 9; background: #000; border: 1px solid #333; }
+  .frame iframe { position: absolute; inset: 0; width: 100%; height: 100%; border: 0; pointer-events: none; }
+  .frame .label { position: absolute; left: 8px; top: -1.6em; font-size: 12px; color: #999; text-transform: uppercase; letter-spacing: .06em; }
+  #current-frame { flex: 0 1 auto; margin-top: 1.4em; }
+  #next-frame { width: 55%; margin-top: 1.4em; }
+  #next-frame.end iframe { visibility: hidden; }
+  aside { padding: 16px 20px; overflow-y: auto; border-left: 1px solid #2a2a2a; }
+  aside h2 { margin: 0 0 12px; font-size: 20px; }
+  .note { margin: 0 0 12px; font-size: 20px; line-height: 1.5; }
+  .note p { margin: 0 0 8px; }
+  .note[data-label]::before { content: attr(data-label); display: block; font-size: 12px; color: #8ab4f8; }
+  .note.timing, .note.technical, .note.transition { font-size: 16px; color: #bbb; font-style: italic; }
+  .empty { color: #777; }
+  #questions { margin-top: 24px; padding-top: 12px; border-top: 1px solid #2a2a2a; }
+  #questions h3 { margin: 0 0 8px; font-size: 14px; color: #999; text-transform: uppercase; letter-spacing: .06em; }
+  .question { display: flex; gap: 8px; align-items: baseline; margin: 0 0 8px; }
+  .question .text { flex: 1; }
+  .question.pending .text { color: #bbb; font-style: italic; }
+  .question .votes { color: #8ab4f8; font-variant-numeric: tabular-nums; }
+  .question button { background: #333; color: #eee; border: 0; border-radius: 4px; padding: 2px 8px; font: inherit; font-size: 13px; cursor: pointer; }
+</style>"#;
+
+const PAGE_SCRIPT: &str = r#"<script>
+(function () {
+  const params = new URLSearchParams(location.search);
+  const room = params.get('room') || 'default';
+  const token = params.get('token');
+  const minutes = Number(params.get('minutes')) || 0;
+  const slides = JSON.parse(document.getElementById('coolslides-presenter').textContent);
+  const deckUrl = new URL('./?offline=1', location.href).href;
+  let index = -1;
+  let ws = null;
+  let startedAt = null;
+
+  function preview(frame, slide) {
+    const iframe = frame.querySelector('iframe');
+    frame.classList.toggle('end', !slide);
+    if (!slide) return;
+    const src = deckUrl + '#' + encodeURIComponent(slide.id);
+    // Only the hash changes after the first load, so the deck is not reloaded
+    if (iframe.src !== src) iframe.src = src;
+  }
+
+  function render() {
+    const slide = slides[index];
+    preview(document.getElementById('current-frame'), slide);
+    preview(document.getElementById('next-frame'), slides[index + 1]);
+    document.getElementById('position').textContent = (index + 1) + ' / ' + slides.length;
+    const notes = document.getElementById('notes');
+    notes.innerHTML = '';
+    const title = document.createElement('h2');
+    title.textContent = slide.title;
+    notes.appendChild(title);
+    if (slide.notes.length === 0) {
+      const empty = document.createElement('p');
+      empty.className = 'empty';
+      empty.textContent = 'No notes';
+      notes.appendChild(empty);
+    }
+    for (const note of slide.notes) {
+      const el = document.createElement('div');
+      el.className = 'note ' + note.type;
+      if (note.label) el.dataset.label = note.label;
+      // Rendered and sanitized by the server
+      el.innerHTML = note.html;
+      notes.appendChild(el);
+    }
+  }
+
+  function show(slideId) {
+    const i = slides.findIndex(s => s.id === slideId);
+    if (i < 0 || i === index) return;
+    index = i;
+    render();
+  }
+
+  function go(i) {
+    if (slides.length === 0) return;
+    i = Math.max(0, Math.min(slides.length - 1, i));
+    if (startedAt === null) startedAt = Date.now();
+    show(slides[i].id);
+    if (ws && ws.readyState === WebSocket.OPEN) {
+      ws.send(JSON.stringify({ type: 'event', event: { name: 'slide:change', data: { slideId: slides[i].id, fragment: 0 }, client_id: 'presenter' }, timestamp: Date.now() }));
+    }
+  }
+
+  const format = seconds => {
+    const s = Math.abs(Math.floor(seconds));
+    const h = Math.floor(s / 3600), m = Math.floor(s / 60) % 60;
+    return (seconds < 0 ? '-' : '') + (h ? h + ':' + String(m).padStart(2, '0') : m) + ':' + String(s % 60).padStart(2, '0');
+  };
+  function tick() {
+    const elapsed = startedAt === null ? 0 : (Date.now() - startedAt) / 1000;
+    document.getElementById('clock').textContent = format(elapsed);
+    if (minutes) {
+      const remaining = minutes * 60 - elapsed;
+      const el = document.getElementById('remaining');
+      el.textContent = format(remaining) + ' left';
+      el.classList.toggle('over', remaining < 0);
+    }
+  }
+
+  function connect() {
+    const url = new URL('rooms/' + encodeURIComponent(room), location.href);
+    url.protocol = location.protocol === 'https:' ? 'wss:' : 'ws:';
+    url.searchParams.set('role', 'presenter');
+    if (token) url.searchParams.set('token', token);
+    ws = new WebSocket(url);
+    const status = document.getElementById('status');
+    ws.onopen = () => { status.textContent = 'Room ' + room; status.classList.remove('offline'); loadQuestions(); };
+    ws.onmessage = event => {
+      let message;
+      try { message = JSON.parse(event.data); } catch (_) { return; }
+      if (message.type === 'state' && message.data && message.data.currentSlide) show(message.data.currentSlide.slideId);
+      if (message.type === 'event' && message.event && message.event.name === 'slide:change') show(message.event.data.slideId);
+      if (message.type === 'event' && message.event && message.event.name === 'qa:queue') showQuestions(message.event.data.questions);
+    };
+    ws.onclose = () => {
+      status.textContent = 'Reconnecting…';
+      status.classList.add('offline');
+      setTimeout(connect, 2000);
+    };
+  }
+
+  function moderate(name, questionId) {
+    if (ws && ws.readyState === WebSocket.OPEN) {
+      ws.send(JSON.stringify({ type: 'event', event: { name, data: { questionId }, client_id: 'presenter' }, timestamp: Date.now() }));
+    }
+  }
+
+  // The queue as it stands when the console connects; `qa:queue` events, sent to
+  // presenters only, keep it current
+  async function loadQuestions() {
+    const headers = token ? { Authorization: 'Bearer ' + token } : {};
+    try {
+      const response = await fetch(new URL('api/rooms/' + encodeURIComponent(room) + '/questions', location.href), { headers });
+      if (response.ok) showQuestions(await response.json());
+    } catch (_) {}
+  }
+
+  function showQuestions(questions) {
+    const list = document.getElementById('question-list');
+    list.innerHTML = '';
+    questions = questions.filter(q => q.status !== 'dismissed');
+    document.getElementById('questions').hidden = questions.length === 0;
+    for (const q of questions) {
+      const row = document.createElement('div');
+      row.className = 'question ' + q.status;
+      const votes = document.createElement('span');
+      votes.className = 'votes';
+      votes.textContent = '▲' + q.upvotes;
+      const text = document.createElement('span');
+      text.className = 'text';
+      text.textContent = q.text;
+      row.append(votes, text);
+      if (q.status === 'pending') {
+        const approve = document.createElement('button');
+        approve.textContent = 'Approve';
+        approve.onclick = () => moderate('qa:approve', q.id);
+        row.appendChild(approve);
+      }
+      const dismiss = document.createElement('button');
+      dismiss.textContent = 'Dismiss';
+      dismiss.onclick = () => moderate('qa:dismiss', q.id);
+      row.appendChild(dismiss);
+      list.appendChild(row);
+    }
+  }
+
+  // Reloads, validation, and exports, so a broken save shows up before the next slide does
+  function watchServer() {
+    const el = document.getElementById('server-status');
+    const show = (text, failed) => { el.textContent = text; el.classList.toggle('failed', !!failed); };
+    const events = new EventSource(new URL('api/events', location.href));
+    events.onmessage = event => {
+      let e;
+      try { e = JSON.parse(event.data); } catch (_) { return; }
+      if (e.kind === 'reload-started') show('Reloading…');
+      if (e.kind === 'reload-completed') show('Reloaded');
+      if (e.kind === 'reload-failed') show('Reload failed: ' + e.error, true);
+      if (e.kind === 'validation') show(e.valid ? (e.warnings.length ? e.warnings.length + ' warnings' : 'Deck valid') : e.errors.length + ' errors', !e.valid);
+      if (e.kind === 'export-started') show('Exporting ' + e.format + '…');
+      if (e.kind === 'export-completed') show('Exported ' + e.format);
+      if (e.kind === 'export-failed') show('Export failed: ' + e.error, true);
+    };
+  }
+
+  document.getElementById('prev').onclick = () => go(index - 1);
+  document.getElementById('next').onclick = () => go(index + 1);
+  document.getElementById('reset').onclick = () => { startedAt = Date.now(); tick(); };
+  addEventListener('keydown', event => {
+    const actions = {
+      ArrowRight: () => go(index + 1),
+      ArrowDown: () => go(index + 1),
+      PageDown: () => go(index + 1),
+      ' ': () => go(index + 1),
+      ArrowLeft: () => go(index - 1),
+      ArrowUp: () => go(index - 1),
+      PageUp: () => go(index - 1),
+      Home: () => go(0),
+      End: () => go(slides.length - 1),
+    };
+    if (actions[event.key]) { event.preventDefault(); actions[event.key](); }
+  });
+
+  if (slides.length > 0) { index = 0; render(); }
+  if (!minutes) document.getElementById('remaining').hidden = true;
+  connect();
+  watchServer();
+  tick();
+  setInterval(tick, 500);
+})();
+</script>"#;
+
+/// The presenter console for a deck
+pub(crate) fn page(deck: &DeckManifest, slides: &HashMap<String, SlideDoc>, config: &SanitizationConfig) -> String {
+    // `</` would end the data block early
+    let data = teleprompter::script(deck, slides, config).to_string().replace("</", "<\\/");
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+    <title>{} · Presenter</title>
+    <meta charset="utf-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1">
+    {}
+</head>
+<body>
+    <header>
+        <span id="clock">0:00</span><span id="remaining"></span>
+        <button id="reset">Reset timer</button>
+        <span class="spacer"></span>
+        <span id="position"></span>
+        <button id="prev">◀ Prev</button><button id="next">Next ▶</button>
+        <span id="server-status"></span>
+        <span id="status" class="offline">Connecting…</span>
+    </header>
+    <section class="previews">
+        <div class="frame" id="current-frame"><span class="label">Current</span><iframe title="Current slide"></iframe></div>
+        <div class="frame" id="next-frame"><span class="label">Next</span><iframe title="Next slide"></iframe></div>
+    </section>
+    <aside>
+        <div id="notes"></div>
+        <section id="questions" hidden><h3>Questions</h3><div id="question-list"></div></section>
+    </aside>
+    <script type="application/json" id="coolslides-presenter">{}</script>
+    {}
+</body>
+</html>"#,
+        html_escape(&deck.title),
+        PAGE_STYLE,
+        data,
+        PAGE_SCRIPT
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_embeds_slides_in_order_with_notes() {
+        let deck: DeckManifest = toml::from_str(
+            "modelVersion = \"1.0\"\ntitle = \"Talk <1>\"\ntheme = \"t.css\"\nsequence = [\"intro\", \"outro\"]\n\n[transitions]\ndefault = \"none\"\n\n[notes]\nintro = \"Close the </script> tag\"\n",
+        )
+        .unwrap();
+        let page = page(&deck, &HashMap::new(), &SanitizationConfig::new(false));
+        assert!(page.contains("<title>Talk &lt;1&gt; · Presenter</title>"));
+
+        let start = page.find(r#"id="coolslides-presenter">"#).unwrap();
+        let data = &page[start..];
+        let data = &data[data.find('>').unwrap() + 1..data.find("</script>").unwrap()];
+        let slides: serde_json::Value = serde_json::from_str(data).unwrap();
+        assert_eq!(slides[0]["id"], "intro");
+        assert_eq!(slides[1]["id"], "outro");
+        assert!(slides[0]["notes"][0]["html"].as_str().unwrap().contains("Close the"));
+    }
+}
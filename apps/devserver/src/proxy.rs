@@ -0,0 +1,238 @@
+/*!
+ * Offline copies of CDN modules
+ *
+ * Import maps often point at jsDelivr. In dev, deck pages load those modules
+ * through `/proxy/<host>/<path>` instead: the first request fetches the module
+ * and keeps it in `.coolslides/cache/proxy`, and every later request is served
+ * from there without touching the network. Rehearse once with a connection and
+ * the talk keeps working when the venue's internet drops.
+ *
+ * Only jsDelivr hosts are proxied, so the route cannot reach arbitrary servers.
+ * Imports inside a proxied module that point back at the CDN, by URL or by
+ * root-relative path as jsDelivr's `+esm` bundles do, are rewritten to relative
+ * paths within the proxy, so its dependencies are cached too.
+ */
+
+use crate::{safe_relative_path, AppState};
+use axum::{
+    body::Body,
+    extract::{Path as AxumPath, State},
+    http::{header, StatusCode},
+    response::Response,
+};
+use coolslides_core::cache::project_cache_dir;
+use regex::Regex;
+use sha2::{Digest, Sha256};
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+    time::Duration,
+};
+
+/// CDN hosts the proxy fetches from
+const HOSTS: &[&str] = &["cdn.jsdelivr.net", "fastly.jsdelivr.net", "gcore.jsdelivr.net"];
+const FETCH_TIMEOUT: Duration = Duration::from_secs(20);
+/// Larger responses are refused rather than cached
+const MAX_MODULE_BYTES: u64 = 20 * 1024 * 1024;
+
+/// `/proxy/...` URL for a module on a proxied CDN, or `None` for any other URL
+pub(crate) fn proxied_url(url: &str) -> Option<String> {
+    let (host, path) = url.strip_prefix("https://")?.split_once('/')?;
+    HOSTS.contains(&host).then(|| format!("/proxy/{}/{}", host, path))
+}
+
+/// Point an import map's CDN entries at the proxy
+pub(crate) fn rewrite_import_map(import_map: &mut serde_json::Value) {
+    let Some(imports) = import_map["imports"].as_object_mut() else {
+        return;
+    };
+    for url in imports.values_mut() {
+        if let Some(proxied) = url.as_str().and_then(proxied_url) {
+            *url = proxied.into();
+        }
+    }
+}
+
+/// Serve `/proxy/<host>/<path>` from the cache, fetching it the first time
+pub(crate) async fn serve(State(state): State<AppState>, AxumPath(path): AxumPath<String>) -> Result<Response, StatusCode> {
+    let (host, module) = path.split_once('/').ok_or(StatusCode::NOT_FOUND)?;
+    if !HOSTS.contains(&host) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    safe_relative_path(module)?;
+    let deck_root = state.deck_root.read().await.clone().ok_or(StatusCode::NOT_FOUND)?;
+    let entry = cache_entry(&deck_root, &path);
+
+    let (content_type, body) = match read_cached(&entry).await {
+        Some(cached) => cached,
+        None => {
+            let (content_type, mut body) = fetch(format!("https://{}", path)).await?;
+            if content_type.contains("javascript") {
+                if let Ok(source) = std::str::from_utf8(&body) {
+                    body = rewrite_module(source, &path).into_bytes();
+                }
+            }
+            if let Err(e) = write_cached(&entry, &content_type, &body).await {
+                tracing::warn!(module = %path, error = %e, "could not cache module for offline use");
+            }
+            (content_type, body)
+        }
+    };
+    Ok(Response::builder()
+        .header(header::CONTENT_TYPE, content_type)
+        .header(header::CACHE_CONTROL, "no-cache")
+        .body(Body::from(body))
+        .unwrap())
+}
+
+/// Cache file for `<host>/<path>`. Hashed, since CDN paths such as `pkg@1` and
+/// `pkg@1/+esm` cannot both be files.
+fn cache_entry(deck_root: &Path, path: &str) -> PathBuf {
+    let digest = Sha256::digest(path.as_bytes());
+    let name: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+    project_cache_dir(deck_root).join("proxy").join(name)
+}
+
+/// A cached module and its content type, stored beside it
+async fn read_cached(entry: &Path) -> Option<(String, Vec<u8>)> {
+    let content_type = tokio::fs::read_to_string(entry.with_extension("type")).await.ok()?;
+    let body = tokio::fs::read(entry).await.ok()?;
+    Some((content_type, body))
+}
+
+async fn write_cached(entry: &Path, content_type: &str, body: &[u8]) -> std::io::Result<()> {
+    if let Some(parent) = entry.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(entry, body).await?;
+    // Written last, so an interrupted write leaves no entry behind
+    tokio::fs::write(entry.with_extension("type"), content_type).await
+}
+
+/// Content type and body of `url`; `502` when the CDN cannot be reached
+async fn fetch(url: String) -> Result<(String, Vec<u8>), StatusCode> {
+    tokio::task::spawn_blocking(move || {
+        let response = ureq::AgentBuilder::new()
+            .timeout(FETCH_TIMEOUT)
+            .build()
+            .get(&url)
+            .set("User-Agent", concat!("coolslides/", env!("CARGO_PKG_VERSION")))
+            .call();
+        let response = match response {
+            Ok(response) => response,
+            Err(ureq::Error::Status(404, _)) => return Err(StatusCode::NOT_FOUND),
+            Err(e) => {
+                tracing::warn!(url = %url, error = %e, "could not fetch module and it is not cached");
+                return Err(StatusCode::BAD_GATEWAY);
+            }
+        };
+        let content_type = response.header("Content-Type").unwrap_or("application/octet-stream").to_string();
+        let mut body = Vec::new();
+        response
+            .into_reader()
+            .take(MAX_MODULE_BYTES + 1)
+            .read_to_end(&mut body)
+            .map_err(|_| StatusCode::BAD_GATEWAY)?;
+        if body.len() as u64 > MAX_MODULE_BYTES {
+            return Err(StatusCode::BAD_GATEWAY);
+        }
+        Ok((content_type, body))
+    })
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+}
+
+/// Rewrite the CDN imports of the module at `<host>/<path>` to relative paths
+/// within the proxy. Relative imports already resolve there; bare specifiers are
+/// left for the import map.
+fn rewrite_module(source: &str, path: &str) -> String {
+    static IMPORT: OnceLock<Regex> = OnceLock::new();
+    let import = IMPORT.get_or_init(|| {
+        Regex::new(r#"(?:\bfrom\s*|\bimport\s*\(\s*|\bimport\s*)(?:"([^"\n]+)"|'([^'\n]+)')"#).unwrap()
+    });
+    let host = path.split('/').next().unwrap_or_default();
+    // From the module's directory back up to `/proxy/`
+    let up = "../".repeat(path.matches('/').count());
+
+    let mut output = String::with_capacity(source.len());
+    let mut cursor = 0;
+    for captures in import.captures_iter(source) {
+        let specifier = captures.get(1).or_else(|| captures.get(2)).unwrap();
+        let text = specifier.as_str();
+        let target = if let Some(url) = text.strip_prefix("https://").or_else(|| text.strip_prefix("//")) {
+            url.split_once('/').filter(|(h, _)| HOSTS.contains(h)).map(|_| url.to_string())
+        } else {
+            text.strip_prefix('/').map(|rest| format!("{}/{}", host, rest))
+        };
+        let Some(target) = target else {
+            continue;
+        };
+        output.push_str(&source[cursor..specifier.start()]);
+        output.push_str(&up);
+        output.push_str(&target);
+        cursor = specifier.end();
+    }
+    output.push_str(&source[cursor..]);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::Request, routing::get, Router};
+    use tower::ServiceExt;
+
+    #[test]
+    fn test_only_jsdelivr_modules_are_proxied() {
+        let mut map = serde_json::json!({ "imports": {
+            "chart.js": "https://cdn.jsdelivr.net/npm/chart.js@4/+esm",
+            "lit": "https://unpkg.com/lit@3?module",
+            "@coolslides/runtime": "/packages/runtime/dist/index.js",
+        }});
+        rewrite_import_map(&mut map);
+        assert_eq!(map["imports"]["chart.js"], "/proxy/cdn.jsdelivr.net/npm/chart.js@4/+esm");
+        assert_eq!(map["imports"]["lit"], "https://unpkg.com/lit@3?module");
+        assert_eq!(map["imports"]["@coolslides/runtime"], "/packages/runtime/dist/index.js");
+    }
+
+    #[test]
+    fn test_module_imports_stay_inside_the_proxy() {
+        let source = r#"import a from "/npm/@kurkle/color@0.3/+esm";
+export * from 'https://fastly.jsdelivr.net/npm/b@1/+esm';
+import("./chunk.js"); import "lit"; import x from "https://esm.sh/x";"#;
+        let rewritten = rewrite_module(source, "cdn.jsdelivr.net/npm/chart.js@4/+esm");
+        assert!(rewritten.contains(r#"from "../../../cdn.jsdelivr.net/npm/@kurkle/color@0.3/+esm""#), "{}", rewritten);
+        assert!(rewritten.contains("from '../../../fastly.jsdelivr.net/npm/b@1/+esm'"));
+        assert!(rewritten.contains(r#"import("./chunk.js")"#));
+        assert!(rewritten.contains(r#"import "lit""#));
+        assert!(rewritten.contains(r#""https://esm.sh/x""#));
+    }
+
+    #[tokio::test]
+    async fn test_cached_modules_are_served_without_the_network() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("content")).unwrap();
+        std::fs::write(
+            dir.path().join("slides.toml"),
+            "modelVersion = \"1.0\"\ntitle = \"T\"\ntheme = \"theme.css\"\nsequence = []\n\n[transitions]\ndefault = \"none\"\n",
+        )
+        .unwrap();
+        let state = AppState::new();
+        state.load_from_directory(dir.path()).await.unwrap();
+        let module = "cdn.jsdelivr.net/npm/chart.js@4/+esm";
+        write_cached(&cache_entry(dir.path(), module), "application/javascript; charset=utf-8", b"export default 1;")
+            .await
+            .unwrap();
+
+        let app = Router::new().route("/proxy/*path", get(serve)).with_state(state);
+        let get = |uri: &str| app.clone().oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap());
+        let cached = get(&format!("/proxy/{}", module)).await.unwrap();
+        assert_eq!(cached.status(), StatusCode::OK);
+        assert_eq!(cached.headers()[header::CONTENT_TYPE], "application/javascript; charset=utf-8");
+        let body = axum::body::to_bytes(cached.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&body[..], b"export default 1;");
+        assert_eq!(get("/proxy/example.com/evil.js").await.unwrap().status(), StatusCode::NOT_FOUND);
+        assert_eq!(get("/proxy/cdn.jsdelivr.net/../secret").await.unwrap().status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,171 @@
+/*!
+ * Room recordings as files for analysis tools, from `/api/rooms/:room_id/dump`
+ *
+ * Every format carries the same envelope: the room, the deck's title, and
+ * when the recording started and stopped. `json` is one document with the
+ * messages in it. `ndjson` puts the envelope on the first line and a message
+ * on each line after, so large recordings stream into tools line by line.
+ * `csv` has a row per message, for spreadsheets and dataframes.
+ *
+ * `json` and `ndjson` recordings can be imported back into a room with
+ * `POST /api/rooms/:room_id/recording` and replayed there.
+ */
+
+use crate::rooms::{RecordedMessage, RoomMessage};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Largest recording `POST /api/rooms/:room_id/recording` takes
+pub const MAX_IMPORT_BYTES: usize = 32 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecordingFormat {
+    #[default]
+    Json,
+    Ndjson,
+    Csv,
+}
+
+impl RecordingFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::Ndjson => "application/x-ndjson",
+            Self::Csv => "text/csv; charset=utf-8",
+        }
+    }
+}
+
+/// What a recording is of
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingInfo {
+    pub room_id: String,
+    pub deck_title: Option<String>,
+    #[serde(default, with = "chrono::serde::ts_milliseconds_option")]
+    pub started_at: Option<DateTime<Utc>>,
+    /// `None` while the room is still recording
+    #[serde(default, with = "chrono::serde::ts_milliseconds_option")]
+    pub stopped_at: Option<DateTime<Utc>>,
+}
+
+/// A room's recording in its envelope
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Recording {
+    #[serde(flatten)]
+    pub info: RecordingInfo,
+    pub messages: Vec<RecordedMessage>,
+}
+
+impl Recording {
+    /// Read a recording exported as `format`. CSV drops too much of each
+    /// message to replay, so it is refused.
+    pub fn parse(text: &str, format: RecordingFormat) -> Result<Self, String> {
+        match format {
+            RecordingFormat::Json => serde_json::from_str(text).map_err(|e| format!("Invalid recording: {}", e)),
+            RecordingFormat::Ndjson => {
+                let mut lines = text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+                let (_, first) = lines.next().ok_or("The recording is empty")?;
+                let info = serde_json::from_str(first).map_err(|e| format!("Line 1 is not a recording envelope: {}", e))?;
+                let messages = lines
+                    .map(|(number, line)| serde_json::from_str(line).map_err(|e| format!("Line {}: {}", number + 1, e)))
+                    .collect::<Result<_, _>>()?;
+                Ok(Self { info, messages })
+            }
+            RecordingFormat::Csv => Err("CSV recordings cannot be replayed; export as json or ndjson".to_string()),
+        }
+    }
+
+    pub fn render(&self, format: RecordingFormat) -> String {
+        match format {
+            RecordingFormat::Json => serde_json::to_string_pretty(self).unwrap_or_default(),
+            RecordingFormat::Ndjson => {
+                let mut lines = vec![serde_json::to_string(&self.info).unwrap_or_default()];
+                lines.extend(self.messages.iter().map(|recorded| serde_json::to_string(recorded).unwrap_or_default()));
+                lines.join("\n") + "\n"
+            }
+            RecordingFormat::Csv => self.csv(),
+        }
+    }
+
+    /// One row per message. Events are split into their name, sender, and
+    /// data; other messages keep their JSON in `data`.
+    fn csv(&self) -> String {
+        let mut csv = String::from("roomId,deckTitle,recordedAt,sessionTimeMs,type,event,clientId,data\n");
+        let deck_title = self.info.deck_title.as_deref().unwrap_or_default();
+        for recorded in &self.messages {
+            let (event, client_id, data) = match &recorded.message {
+                RoomMessage::Event { event, .. } => (event.name.as_str(), event.client_id.as_str(), event.data.to_string()),
+                RoomMessage::State { data, .. } => ("", "", data.to_string()),
+                RoomMessage::Join { role, client_id, .. } => ("", client_id.as_str(), serde_json::json!({ "role": role }).to_string()),
+                other => ("", "", serde_json::to_string(other).unwrap_or_default()),
+            };
+            let recorded_at = recorded.recorded_at.timestamp_millis().to_string();
+            let session_time = recorded.session_time.to_string();
+            let row = [self.info.room_id.as_str(), deck_title, recorded_at.as_str(), session_time.as_str(), recorded.message.kind(), event, client_id, data.as_str()];
+            csv.push_str(&row.map(csv_field).join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+/// How long `messages` took to record, first to last
+pub fn span_ms(messages: &[RecordedMessage]) -> u64 {
+    match (messages.first(), messages.last()) {
+        (Some(first), Some(last)) => last.session_time.saturating_sub(first.session_time),
+        _ => 0,
+    }
+}
+
+/// Quote a CSV field when it needs it, doubling its quotes
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rooms::{ClientRole, EventData};
+
+    #[test]
+    fn test_formats_carry_the_envelope() {
+        let at = DateTime::parse_from_rfc3339("2024-05-01T10:00:00Z").unwrap().with_timezone(&Utc);
+        let event = EventData { name: "slide:change".to_string(), data: serde_json::json!({ "slideId": "a,b" }), client_id: "p".to_string() };
+        let recording = Recording {
+            info: RecordingInfo { room_id: "main".to_string(), deck_title: Some("Q3 \"Review\"".to_string()), started_at: Some(at), stopped_at: None },
+            messages: vec![
+                RecordedMessage { message: RoomMessage::Join { role: ClientRole::Presenter, client_id: "p".to_string(), token: None }, recorded_at: at, session_time: 0 },
+                RecordedMessage { message: RoomMessage::Event { event, timestamp: at }, recorded_at: at, session_time: 1500 },
+            ],
+        };
+
+        let json: serde_json::Value = serde_json::from_str(&recording.render(RecordingFormat::Json)).unwrap();
+        assert_eq!((json["roomId"].as_str(), json["startedAt"].as_i64()), (Some("main"), Some(at.timestamp_millis())));
+        assert!(json["stoppedAt"].is_null());
+        assert_eq!(json["messages"].as_array().unwrap().len(), 2);
+        let back = Recording::parse(&recording.render(RecordingFormat::Json), RecordingFormat::Json).unwrap();
+        assert_eq!(back.info, recording.info);
+
+        let ndjson = recording.render(RecordingFormat::Ndjson);
+        let lines: Vec<&str> = ndjson.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(serde_json::from_str::<RecordingInfo>(lines[0]).unwrap(), recording.info);
+        assert_eq!(serde_json::from_str::<RecordedMessage>(lines[2]).unwrap().session_time, 1500);
+        let back = Recording::parse(&ndjson, RecordingFormat::Ndjson).unwrap();
+        assert_eq!((back.info, span_ms(&back.messages)), (recording.info.clone(), 1500));
+        let torn = Recording::parse(&ndjson[..ndjson.len() - 10], RecordingFormat::Ndjson).unwrap_err();
+        assert!(torn.starts_with("Line 3"), "{}", torn);
+
+        let csv = recording.render(RecordingFormat::Csv);
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(rows[0], "roomId,deckTitle,recordedAt,sessionTimeMs,type,event,clientId,data");
+        assert_eq!(rows[1], format!("main,\"Q3 \"\"Review\"\"\",{},0,join,,p,\"{{\"\"role\"\":\"\"presenter\"\"}}\"", at.timestamp_millis()));
+        assert!(rows[2].ends_with(",1500,event,slide:change,p,\"{\"\"slideId\"\":\"\"a,b\"\"}\""), "{}", rows[2]);
+    }
+}
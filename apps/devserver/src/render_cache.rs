@@ -0,0 +1,114 @@
+/*!
+ * Memoized markdown and slide HTML, keyed by a hash of everything the output depends on
+ */
+
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::{Mutex, OnceLock};
+
+/// Entries kept before the cache starts over; bounds memory when slides are edited for hours
+const CAPACITY: usize = 4096;
+
+pub(crate) struct RenderCache {
+    entries: OnceLock<Mutex<HashMap<u64, String>>>,
+}
+
+impl RenderCache {
+    pub(crate) const fn new() -> Self {
+        Self { entries: OnceLock::new() }
+    }
+
+    pub(crate) fn get(&self, key: u64) -> Option<String> {
+        self.entries().get(&key).cloned()
+    }
+
+    pub(crate) fn insert(&self, key: u64, html: &str) {
+        let mut entries = self.entries();
+        if entries.len() >= CAPACITY {
+            entries.clear();
+        }
+        entries.insert(key, html.to_string());
+    }
+
+    pub(crate) fn clear(&self) {
+        self.entries().clear();
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, HashMap<u64, String>> {
+        self.entries.get_or_init(Default::default).lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Cache key for any hashable inputs
+pub(crate) fn key(inputs: impl Hash) -> u64 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    inputs.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SanitizationConfig;
+    use coolslides_core::{ComponentRegistry, SlideDoc, TagRename};
+
+    #[test]
+    fn test_insert_get_and_clear() {
+        let cache = RenderCache::new();
+        let strict = key(("# Hi", true));
+        let loose = key(("# Hi", false));
+        assert_ne!(strict, loose);
+
+        cache.insert(strict, "<h1>Hi</h1>");
+        assert_eq!(cache.get(strict).as_deref(), Some("<h1>Hi</h1>"));
+        assert_eq!(cache.get(loose), None);
+        cache.clear();
+        assert_eq!(cache.get(strict), None);
+    }
+
+    #[test]
+    fn test_markdown_is_cached_per_sanitization_mode() {
+        let markdown = "Area: $\\pi r^2$";
+        let loose = crate::render_markdown_to_html(markdown, &SanitizationConfig::new(false).with_math(true));
+        let strict = crate::render_markdown_to_html(markdown, &SanitizationConfig::new(true).with_math(true));
+        assert!(loose.contains("<math"), "{}", loose);
+        assert!(!strict.contains("<math"), "{}", strict);
+    }
+
+    #[test]
+    fn test_slides_are_cached_per_slot_tag() {
+        let slide: SlideDoc = toml::from_str(
+            r#"modelVersion = "1.0"
+id = "poll"
+
+[component]
+name = "PollSlide"
+versionReq = "^1"
+
+[props]
+
+[slots.main]
+kind = "component"
+tag = "cs-poll"
+module = "/poll.js"
+"#,
+        )
+        .unwrap();
+        let config = SanitizationConfig::new(false);
+        let plain = crate::generate_slide_html(&slide, None, &config).unwrap();
+        assert!(plain.contains("<cs-poll"), "{}", plain);
+
+        // The slide is unchanged, but a collision rename gives its slot a new tag
+        let registry = ComponentRegistry {
+            tag_renames: vec![TagRename {
+                component: "Poll".to_string(),
+                original: "cs-poll".to_string(),
+                tag: "acme-cs-poll".to_string(),
+                module: "/poll.js".to_string(),
+            }],
+            ..Default::default()
+        };
+        let renamed = crate::generate_slide_html(&slide, Some(&registry), &config).unwrap();
+        assert!(renamed.contains("<acme-cs-poll"), "{}", renamed);
+    }
+}
@@ -0,0 +1,222 @@
+/*!
+ * Rooms shared by several servers behind a load balancer
+ *
+ * One process only holds so many sockets. For a larger audience, run several
+ * servers with the same `--redis` URL: a room's clients may then connect to
+ * any of them and still see one room.
+ *
+ * Servers share what changes a room, not what the room sends: client events,
+ * poll and question actions, and reactions. Each server applies them to its
+ * copy of the room and broadcasts to its own clients, so tallies and question
+ * boards come out the same everywhere without being sent twice. A server that
+ * opens a room others already have asks for it, and they answer with its
+ * state, polls, and questions.
+ *
+ * Webhooks, server plugins, recordings, and stores stay with the server an
+ * action reached first; sequence numbers and resume tokens are per server.
+ *
+ * Redis pub/sub is the bus, with the `redis` feature. The bus only carries
+ * [`Envelope`]s, so tests connect two room managers directly.
+ */
+
+use crate::rooms::{EventData, Poll, Question, QuestionStatus};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+
+/// Redis channel every server publishes to and subscribes on
+pub const CHANNEL: &str = "coolslides:rooms";
+
+/// A change to a room, as one server tells the others
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum SharedAction {
+    /// An event from a client, after server plugins saw it
+    Event { event: EventData },
+    /// A state snapshot played back from a recording
+    State { data: serde_json::Value },
+    OpenPoll { poll_id: String, question: String, options: Vec<String> },
+    ClosePoll { poll_id: String },
+    Vote { client_id: String, poll_id: String, option: usize },
+    Ask {
+        client_id: String,
+        question_id: String,
+        text: String,
+        #[serde(with = "chrono::serde::ts_milliseconds")]
+        asked_at: DateTime<Utc>,
+    },
+    Upvote { client_id: String, question_id: String },
+    Moderate { question_id: String, status: QuestionStatus },
+    React { client_id: String, reaction: String },
+    /// A server opened the room and asks the others for it
+    Hello,
+    /// The answer to [`SharedAction::Hello`]
+    Snapshot { state: serde_json::Value, polls: Vec<Poll>, questions: Vec<SharedQuestion> },
+}
+
+/// A question with the clients that upvoted it, which the API never shows
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SharedQuestion {
+    #[serde(flatten)]
+    pub question: Question,
+    pub voters: Vec<String>,
+}
+
+/// What goes over the bus
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Envelope {
+    /// The server that sent it; it ignores its own envelopes when they come back
+    pub origin: String,
+    pub room_id: String,
+    pub action: SharedAction,
+}
+
+/// This server's end of the bus
+#[derive(Debug)]
+pub struct Fanout {
+    instance: String,
+    outgoing: mpsc::UnboundedSender<Envelope>,
+}
+
+impl Fanout {
+    /// A fan-out with a new instance id, and the envelopes it sends, for a transport to publish
+    pub fn new() -> (Self, mpsc::UnboundedReceiver<Envelope>) {
+        let (outgoing, receiver) = mpsc::unbounded_channel();
+        (Self { instance: uuid::Uuid::new_v4().simple().to_string(), outgoing }, receiver)
+    }
+
+    pub fn instance(&self) -> &str {
+        &self.instance
+    }
+
+    /// Tell the other servers about a change to `room_id`
+    pub(crate) fn share(&self, room_id: &str, action: SharedAction) {
+        let envelope = Envelope { origin: self.instance.clone(), room_id: room_id.to_string(), action };
+        // No transport means no other servers to tell
+        let _ = self.outgoing.send(envelope);
+    }
+}
+
+#[cfg(feature = "redis")]
+pub use self::redis_bus::spawn_redis;
+
+#[cfg(feature = "redis")]
+mod redis_bus {
+    use super::{Envelope, CHANNEL};
+    use crate::rooms::RoomManager;
+    use futures_util::StreamExt;
+    use redis::AsyncCommands;
+    use std::{sync::Arc, time::Duration};
+    use tokio::sync::mpsc;
+
+    /// Wait between attempts to reach Redis
+    const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+
+    /// Publish `outgoing` to Redis and deliver what other servers publish to
+    /// `rooms`, reconnecting when Redis goes away. Envelopes sent while it is
+    /// unreachable are lost.
+    pub fn spawn_redis(url: &str, mut outgoing: mpsc::UnboundedReceiver<Envelope>, rooms: Arc<RoomManager>) -> anyhow::Result<()> {
+        let client = redis::Client::open(url)?;
+
+        let publisher = client.clone();
+        tokio::spawn(async move {
+            let mut connection = None;
+            while let Some(envelope) = outgoing.recv().await {
+                let Ok(payload) = serde_json::to_string(&envelope) else {
+                    continue;
+                };
+                if connection.is_none() {
+                    match publisher.get_multiplexed_tokio_connection().await {
+                        Ok(opened) => connection = Some(opened),
+                        Err(e) => tracing::warn!(error = %e, "could not reach Redis; room change not shared"),
+                    }
+                }
+                if let Some(open) = &mut connection {
+                    if let Err(e) = open.publish::<_, _, ()>(CHANNEL, payload).await {
+                        tracing::warn!(error = %e, "could not publish room change to Redis");
+                        connection = None;
+                    }
+                }
+            }
+        });
+
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = subscribe(&client, &rooms).await {
+                    tracing::warn!(error = %e, "lost the Redis room subscription; reconnecting");
+                }
+                tokio::time::sleep(RECONNECT_DELAY).await;
+            }
+        });
+        Ok(())
+    }
+
+    async fn subscribe(client: &redis::Client, rooms: &RoomManager) -> anyhow::Result<()> {
+        let mut pubsub = client.get_async_pubsub().await?;
+        pubsub.subscribe(CHANNEL).await?;
+        tracing::info!(channel = CHANNEL, "sharing rooms through Redis");
+        let mut messages = pubsub.on_message();
+        while let Some(message) = messages.next().await {
+            let payload: String = message.get_payload()?;
+            match serde_json::from_str::<Envelope>(&payload) {
+                Ok(envelope) => rooms.receive(envelope).await,
+                Err(e) => tracing::warn!(error = %e, "ignored unreadable room change from Redis"),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rooms::{NewPoll, RoomManager};
+
+    /// Deliver every envelope `from` sent to `to`, as the bus would
+    async fn deliver(from: &mut mpsc::UnboundedReceiver<Envelope>, to: &RoomManager) {
+        while let Ok(envelope) = from.try_recv() {
+            to.receive(envelope).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_two_servers_share_slides_polls_and_questions() {
+        let (fanout_a, mut out_a) = Fanout::new();
+        let (fanout_b, mut out_b) = Fanout::new();
+        let a = RoomManager::new().with_fanout(std::sync::Arc::new(fanout_a));
+        let b = RoomManager::new().with_fanout(std::sync::Arc::new(fanout_b));
+
+        a.ensure_room("talk".to_string()).await;
+        let room_a = a.get_room("talk").await.unwrap();
+        let event = EventData { name: "slide:change".to_string(), data: serde_json::json!({ "slideId": "intro" }), client_id: "p".to_string() };
+        room_a.handle_event(event).await;
+        let poll = room_a.create_poll(NewPoll { question: "Ready?".to_string(), options: vec!["Yes".to_string(), "No".to_string()] }).await.unwrap();
+        deliver(&mut out_a, &b).await;
+
+        // B opened the room from A's changes and has the same slide and poll
+        let room_b = b.get_room("talk").await.unwrap();
+        assert_eq!(room_b.state.read().await["currentSlide"]["slideId"], "intro");
+        room_b.vote("audience-1", &poll.poll_id, 0).await.unwrap();
+        let question = room_b.ask("audience-1", "Slides online?").await.unwrap();
+        deliver(&mut out_b, &a).await;
+        deliver(&mut out_a, &b).await;
+
+        assert_eq!(room_a.poll_results(&poll.poll_id).await.unwrap().counts, [1, 0]);
+        room_a.moderate_question(&question.id, QuestionStatus::Approved).await.unwrap();
+        deliver(&mut out_a, &b).await;
+        assert_eq!(room_b.list_questions(false).await.len(), 1);
+
+        // A server that opens the room later asks for it
+        let (fanout_c, mut out_c) = Fanout::new();
+        let c = RoomManager::new().with_fanout(std::sync::Arc::new(fanout_c));
+        c.ensure_room("talk".to_string()).await;
+        deliver(&mut out_c, &a).await;
+        deliver(&mut out_a, &c).await;
+        let room_c = c.get_room("talk").await.unwrap();
+        assert_eq!(room_c.poll_results(&poll.poll_id).await.unwrap().counts, [1, 0]);
+        assert_eq!(room_c.list_questions(false).await[0].text, "Slides online?");
+        assert_eq!(room_c.state.read().await["currentSlide"]["slideId"], "intro");
+    }
+}
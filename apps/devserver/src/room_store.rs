@@ -0,0 +1,262 @@
+/*!
+ * Where rooms are kept so they survive a restart
+ *
+ * Two stores are available: the SQLite database of `--db`, which also holds
+ * analytics and export history, or a directory of plain files with
+ * `--state-dir`, one directory per room:
+ *
+ * ```text
+ * <state-dir>/rooms/<room>/room.json         state snapshot
+ * <state-dir>/rooms/<room>/history.jsonl     recent messages, one per line
+ * <state-dir>/rooms/<room>/recording.jsonl   the recording, one message per line
+ * ```
+ *
+ * The files need no database tooling and are easy to inspect or copy to
+ * another machine. Either way, state is written on every change and messages
+ * as they are sent, so a crash loses nothing a client has seen.
+ */
+
+use crate::rooms::{RecordedMessage, RoomMessage};
+use crate::storage::{RoomSnapshot, Storage};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+/// Messages of history kept per room, in memory and in the store
+pub const HISTORY_LIMIT: usize = 1000;
+
+#[derive(Debug)]
+pub enum RoomStore {
+    /// The `--db` database
+    Sqlite(Arc<Storage>),
+    /// JSON and JSONL files under a directory
+    Files(FileStore),
+}
+
+impl RoomStore {
+    /// A store of files under `dir`, created on first write
+    pub fn files(dir: impl Into<PathBuf>) -> Self {
+        Self::Files(FileStore { dir: dir.into(), writes: Mutex::new(()) })
+    }
+
+    pub async fn save_room(&self, room: &RoomSnapshot) -> anyhow::Result<()> {
+        match self {
+            Self::Sqlite(storage) => storage.save_room(room).await,
+            Self::Files(files) => files.save_room(room).await,
+        }
+    }
+
+    pub async fn load_rooms(&self) -> anyhow::Result<Vec<RoomSnapshot>> {
+        match self {
+            Self::Sqlite(storage) => storage.load_rooms().await,
+            Self::Files(files) => files.load_rooms().await,
+        }
+    }
+
+    /// Forget a room, its recording, and its history
+    pub async fn delete_room(&self, room_id: &str) -> anyhow::Result<()> {
+        match self {
+            Self::Sqlite(storage) => storage.delete_room(room_id).await,
+            Self::Files(files) => files.delete_room(room_id).await,
+        }
+    }
+
+    /// Store the `seq`th message of a room's recording
+    pub async fn append_recorded(&self, room_id: &str, seq: usize, message: &RecordedMessage) -> anyhow::Result<()> {
+        match self {
+            Self::Sqlite(storage) => storage.append_recorded(room_id, seq, message).await,
+            Self::Files(files) => files.append(room_id, RECORDING, message).await,
+        }
+    }
+
+    pub async fn clear_recording(&self, room_id: &str) -> anyhow::Result<()> {
+        match self {
+            Self::Sqlite(storage) => storage.clear_recording(room_id).await,
+            Self::Files(files) => files.remove(room_id, RECORDING).await,
+        }
+    }
+
+    pub async fn load_recording(&self, room_id: &str) -> anyhow::Result<Vec<RecordedMessage>> {
+        match self {
+            Self::Sqlite(storage) => storage.load_recording(room_id).await,
+            Self::Files(files) => files.read_lines(room_id, RECORDING).await,
+        }
+    }
+
+    pub async fn append_history(&self, room_id: &str, message: &RoomMessage) -> anyhow::Result<()> {
+        match self {
+            Self::Sqlite(storage) => storage.append_history(room_id, message, HISTORY_LIMIT).await,
+            Self::Files(files) => files.append(room_id, HISTORY, message).await,
+        }
+    }
+
+    /// A room's latest messages, oldest first
+    pub async fn load_history(&self, room_id: &str) -> anyhow::Result<Vec<RoomMessage>> {
+        match self {
+            Self::Sqlite(storage) => storage.load_history(room_id).await,
+            Self::Files(files) => files.load_history(room_id).await,
+        }
+    }
+}
+
+const SNAPSHOT: &str = "room.json";
+const HISTORY: &str = "history.jsonl";
+const RECORDING: &str = "recording.jsonl";
+
+#[derive(Debug)]
+pub struct FileStore {
+    dir: PathBuf,
+    /// Appends from different clients must not interleave within a line
+    writes: Mutex<()>,
+}
+
+impl FileStore {
+    /// Directory of a room. Room ids come from URLs, so anything but letters,
+    /// digits, `-`, and `_` is escaped.
+    fn room_dir(&self, room_id: &str) -> PathBuf {
+        let name: String = room_id
+            .bytes()
+            .map(|b| match b {
+                b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' => (b as char).to_string(),
+                _ => format!("%{:02X}", b),
+            })
+            .collect();
+        self.dir.join("rooms").join(name)
+    }
+
+    async fn save_room(&self, room: &RoomSnapshot) -> anyhow::Result<()> {
+        let dir = self.room_dir(&room.id);
+        tokio::fs::create_dir_all(&dir).await?;
+        // Written beside and renamed over, so a crash never leaves half a snapshot
+        let temp = dir.join(format!("{}.tmp", SNAPSHOT));
+        tokio::fs::write(&temp, serde_json::to_vec_pretty(room)?).await?;
+        tokio::fs::rename(&temp, dir.join(SNAPSHOT)).await?;
+        Ok(())
+    }
+
+    async fn load_rooms(&self) -> anyhow::Result<Vec<RoomSnapshot>> {
+        let mut rooms = Vec::new();
+        let mut entries = match tokio::fs::read_dir(self.dir.join("rooms")).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(rooms),
+            Err(e) => return Err(e.into()),
+        };
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path().join(SNAPSHOT);
+            let Ok(content) = tokio::fs::read(&path).await else {
+                continue;
+            };
+            let room: RoomSnapshot = serde_json::from_slice(&content).map_err(|e| anyhow::anyhow!("{}: {}", path.display(), e))?;
+            rooms.push(room);
+        }
+        rooms.sort_by_key(|room| room.created_at);
+        Ok(rooms)
+    }
+
+    async fn delete_room(&self, room_id: &str) -> anyhow::Result<()> {
+        match tokio::fs::remove_dir_all(self.room_dir(room_id)).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    async fn append(&self, room_id: &str, file: &str, value: &impl Serialize) -> anyhow::Result<()> {
+        let mut line = serde_json::to_vec(value)?;
+        line.push(b'\n');
+        let dir = self.room_dir(room_id);
+        let _guard = self.writes.lock().await;
+        tokio::fs::create_dir_all(&dir).await?;
+        let mut output = tokio::fs::OpenOptions::new().create(true).append(true).open(dir.join(file)).await?;
+        output.write_all(&line).await?;
+        output.flush().await?;
+        Ok(())
+    }
+
+    async fn remove(&self, room_id: &str, file: &str) -> anyhow::Result<()> {
+        let _guard = self.writes.lock().await;
+        match tokio::fs::remove_file(self.room_dir(room_id).join(file)).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Every line of a room's `file`. A torn last line, from a crash mid-write, is skipped.
+    async fn read_lines<T: serde::de::DeserializeOwned>(&self, room_id: &str, file: &str) -> anyhow::Result<Vec<T>> {
+        let path = self.room_dir(room_id).join(file);
+        let content = match tokio::fs::read_to_string(&path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut values = Vec::new();
+        for line in content.lines().filter(|line| !line.trim().is_empty()) {
+            match serde_json::from_str(line) {
+                Ok(value) => values.push(value),
+                Err(e) => tracing::warn!(file = %path.display(), error = %e, "skipped unreadable line"),
+            }
+        }
+        Ok(values)
+    }
+
+    /// The latest messages of a room's history; the file is trimmed to them
+    async fn load_history(&self, room_id: &str) -> anyhow::Result<Vec<RoomMessage>> {
+        let mut history: Vec<RoomMessage> = self.read_lines(room_id, HISTORY).await?;
+        if history.len() > HISTORY_LIMIT {
+            history.drain(..history.len() - HISTORY_LIMIT);
+            let mut content = Vec::new();
+            for message in &history {
+                content.extend(serde_json::to_vec(message)?);
+                content.push(b'\n');
+            }
+            write_replacing(&self.room_dir(room_id).join(HISTORY), &content).await?;
+        }
+        Ok(history)
+    }
+}
+
+async fn write_replacing(path: &Path, content: &[u8]) -> anyhow::Result<()> {
+    let temp = path.with_extension("tmp");
+    tokio::fs::write(&temp, content).await?;
+    tokio::fs::rename(&temp, path).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Utc};
+
+    #[tokio::test]
+    async fn test_file_store_round_trips_rooms_history_and_recordings() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = RoomStore::files(dir.path());
+        let room = RoomSnapshot {
+            id: "talk/1".into(),
+            state: serde_json::json!({ "currentSlide": { "slideId": "b" } }),
+            recording: true,
+            created_at: DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z").unwrap().with_timezone(&Utc),
+        };
+        store.save_room(&room).await.unwrap();
+        store.append_history("talk/1", &RoomMessage::Heartbeat).await.unwrap();
+        let message = RecordedMessage { message: RoomMessage::Heartbeat, recorded_at: Utc::now(), session_time: 5 };
+        store.append_recorded("talk/1", 0, &message).await.unwrap();
+        assert!(dir.path().join("rooms/talk%2F1/recording.jsonl").exists());
+
+        // A crash mid-append leaves a torn line, which is skipped
+        let history = dir.path().join("rooms/talk%2F1/history.jsonl");
+        let mut content = std::fs::read_to_string(&history).unwrap();
+        content.push_str("{\"type\":\"hea");
+        std::fs::write(&history, content).unwrap();
+
+        let store = RoomStore::files(dir.path());
+        assert_eq!(store.load_rooms().await.unwrap(), vec![room]);
+        assert_eq!(store.load_history("talk/1").await.unwrap().len(), 1);
+        assert_eq!(store.load_recording("talk/1").await.unwrap()[0].session_time, 5);
+
+        store.delete_room("talk/1").await.unwrap();
+        assert!(store.load_rooms().await.unwrap().is_empty());
+        assert!(store.load_recording("talk/1").await.unwrap().is_empty());
+    }
+}
@@ -1,23 +1,40 @@
-/**
+/*!
  * WebSocket rooms for presenter/audience interaction with record/replay
  */
 
-use axum::extract::ws::{Message, WebSocket};
+use axum::extract::ws::{close_code, CloseFrame, Message, WebSocket};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::{HashMap, VecDeque},
-    sync::Arc,
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
 };
 use tokio::sync::{RwLock, broadcast};
+use tracing::Instrument;
 use uuid::Uuid;
+use crate::server_plugins::PluginHost;
+use crate::recordings::{Recording, RecordingInfo};
+use crate::room_fanout::{Envelope, Fanout, SharedAction, SharedQuestion};
+use crate::room_store::{RoomStore, HISTORY_LIMIT};
+use crate::storage::RoomSnapshot;
+use crate::webhooks::{EventSender, ServerEvent};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "camelCase")]
 pub enum RoomMessage {
+    /// Sent by the server when a client joins or changes role. A client sends it
+    /// to ask for a role, with the presenter token to present.
     Join {
         role: ClientRole,
+        #[serde(default)]
         client_id: String,
+        /// Never sent back out
+        #[serde(default, skip_serializing)]
+        token: Option<String>,
     },
     Event {
         event: EventData,
@@ -32,16 +49,264 @@ pub enum RoomMessage {
     Ack {
         id: String,
     },
+    /// A client's vote in an open poll; voting again replaces the earlier vote
+    Vote {
+        poll_id: String,
+        /// Index into the poll's options
+        option: usize,
+    },
+    /// A question for the presenter, held until they approve it
+    Ask {
+        text: String,
+    },
+    /// Support for an approved question; once per client
+    Upvote {
+        question_id: String,
+    },
+    /// One of [`REACTIONS`]; counted into the next `reactions` event rather than relayed
+    React {
+        reaction: String,
+    },
+    /// Sent by the server to one client when it connects: who it is in the
+    /// room, and the token to resume as that client after a dropped connection
+    Session {
+        client_id: String,
+        resume_token: String,
+        /// The room's latest sequence number
+        seq: u64,
+        /// Whether the connection resumed an earlier one
+        resumed: bool,
+    },
     Heartbeat,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A room message numbered in the order the room sent it, as clients receive
+/// it: the message with a `seq` field added
+#[derive(Debug, Clone, Serialize)]
+pub struct Sequenced {
+    pub seq: u64,
+    #[serde(flatten)]
+    pub message: RoomMessage,
+    /// Clients that are sent the message; the rest skip its number
+    #[serde(skip)]
+    pub to: Recipients,
+}
+
+/// Which clients a room message is delivered to, checked as each client is sent it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Recipients {
+    #[default]
+    Everyone,
+    Presenters,
+}
+
+impl Recipients {
+    /// `presenter:` events, such as speaker-notes sync and timer alerts, and the
+    /// Q&A moderation queue are for presenters; everything else for everyone
+    pub fn for_message(message: &RoomMessage) -> Self {
+        match message {
+            RoomMessage::Event { event, .. } if event.name.starts_with("presenter:") || event.name == "qa:queue" => Self::Presenters,
+            _ => Self::Everyone,
+        }
+    }
+
+    pub fn includes(self, role: ClientRole) -> bool {
+        match self {
+            Self::Everyone => true,
+            Self::Presenters => role == ClientRole::Presenter,
+        }
+    }
+}
+
+/// How long after a client disconnects it may resume
+pub const RESUME_WINDOW: Duration = Duration::from_secs(5 * 60);
+
+/// A reconnecting client's `?resume=` token and the last `seq` it saw
+#[derive(Debug, Clone)]
+pub struct ResumeRequest {
+    pub token: String,
+    pub last_seq: u64,
+}
+
+/// Who a resume token resumes as
+#[derive(Debug, Clone)]
+struct ResumeTicket {
+    client_id: String,
+    role: ClientRole,
+    /// Set when the client disconnects; `None` while it is connected
+    expires_at: Option<tokio::time::Instant>,
+}
+
+impl RoomMessage {
+    /// The message's `type` on the wire
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Join { .. } => "join",
+            Self::Event { .. } => "event",
+            Self::State { .. } => "state",
+            Self::Ack { .. } => "ack",
+            Self::Vote { .. } => "vote",
+            Self::Ask { .. } => "ask",
+            Self::Upvote { .. } => "upvote",
+            Self::React { .. } => "react",
+            Self::Session { .. } => "session",
+            Self::Heartbeat => "heartbeat",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum ClientRole {
     Presenter,
     Audience,
 }
 
+/// Events that move the room or replace its state, which only presenters may send.
+/// `poll:`, `qa:`, and `reactions` events are included so audience clients cannot
+/// fake a tally or moderate questions.
+pub fn is_presenter_event(name: &str) -> bool {
+    matches!(name, "slide:change" | "fragment:change" | "reactions")
+        || ["presenter:", "poll:", "qa:"].iter().any(|prefix| name.starts_with(prefix))
+}
+
+/// Reactions clients may send
+pub const REACTIONS: &[&str] = &["clap", "heart", "laugh", "wow", "thumbs-up", "fire", "question"];
+
+/// Reactions are counted over this long, then broadcast as one `reactions` event
+pub const REACTION_WINDOW: Duration = Duration::from_secs(1);
+
+/// Reactions counted from one client per window; the rest are dropped
+pub const MAX_REACTIONS_PER_CLIENT: u32 = 10;
+
+/// Reactions counted since the last `reactions` event
+#[derive(Debug, Default)]
+struct ReactionWindow {
+    counts: BTreeMap<String, u32>,
+    /// How many each client sent
+    senders: HashMap<String, u32>,
+}
+
+/// Most options a poll may offer
+pub const MAX_POLL_OPTIONS: usize = 20;
+
+/// A question the presenter puts to the room, from a `poll:create` event or
+/// `POST /api/rooms/:room_id/polls`
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewPoll {
+    pub question: String,
+    pub options: Vec<String>,
+}
+
+/// A poll and its votes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Poll {
+    id: String,
+    question: String,
+    options: Vec<String>,
+    open: bool,
+    /// Each voter's option, by client id
+    votes: HashMap<String, usize>,
+}
+
+impl Poll {
+    fn results(&self) -> PollResults {
+        let mut counts = vec![0; self.options.len()];
+        for &option in self.votes.values() {
+            counts[option] += 1;
+        }
+        PollResults {
+            poll_id: self.id.clone(),
+            question: self.question.clone(),
+            options: self.options.clone(),
+            counts,
+            total_votes: self.votes.len(),
+            open: self.open,
+        }
+    }
+}
+
+/// A poll's tally, as broadcast in `poll:*` events and served by the polls API
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PollResults {
+    pub poll_id: String,
+    pub question: String,
+    pub options: Vec<String>,
+    /// Votes for each option, in the order of `options`
+    pub counts: Vec<usize>,
+    pub total_votes: usize,
+    pub open: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PollError {
+    /// The question or options are unusable
+    Invalid(String),
+    NotFound,
+    Closed,
+    NoSuchOption,
+}
+
+impl std::fmt::Display for PollError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Invalid(reason) => write!(f, "{}", reason),
+            Self::NotFound => write!(f, "No such poll"),
+            Self::Closed => write!(f, "The poll is closed"),
+            Self::NoSuchOption => write!(f, "The poll has no such option"),
+        }
+    }
+}
+
+impl std::error::Error for PollError {}
+
+/// Longest question, in characters
+pub const MAX_QUESTION_CHARS: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QuestionStatus {
+    /// Waiting for the presenter; only presenters see it
+    Pending,
+    /// Shown to the room and open to upvotes
+    Approved,
+    Dismissed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Question {
+    pub id: String,
+    pub text: String,
+    pub status: QuestionStatus,
+    pub upvotes: usize,
+    #[serde(with = "chrono::serde::ts_milliseconds")]
+    pub asked_at: DateTime<Utc>,
+    /// Clients that upvoted, so each counts once
+    #[serde(skip)]
+    voters: HashSet<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QuestionError {
+    /// The question is empty or too long
+    Invalid(String),
+    /// No such question, or not one the client can see
+    NotFound,
+}
+
+impl std::fmt::Display for QuestionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Invalid(reason) => write!(f, "{}", reason),
+            Self::NotFound => write!(f, "No such question"),
+        }
+    }
+}
+
+impl std::error::Error for QuestionError {}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EventData {
     pub name: String,
@@ -49,12 +314,69 @@ pub struct EventData {
     pub client_id: String,
 }
 
+/// Limits on every room, from `[rooms]` in `coolslides.config.toml`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct RoomLimits {
+    /// Most clients in a room at once; unlimited when unset. Presenters are let
+    /// in past it, so a full room cannot lock out the talk.
+    pub max_clients: Option<usize>,
+    /// Messages a client may fall behind the room before it is disconnected
+    pub buffer: usize,
+    /// Longest one message may take to reach a client before it is disconnected, in milliseconds
+    pub send_timeout_ms: u64,
+}
+
+impl Default for RoomLimits {
+    fn default() -> Self {
+        Self { max_clients: None, buffer: 1000, send_timeout_ms: 10_000 }
+    }
+}
+
+impl RoomLimits {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.max_clients == Some(0) {
+            anyhow::bail!("rooms.maxClients must be at least 1");
+        }
+        if self.buffer == 0 || self.send_timeout_ms == 0 {
+            anyhow::bail!("rooms.buffer and rooms.sendTimeoutMs must be at least 1");
+        }
+        Ok(())
+    }
+
+    fn send_timeout(&self) -> Duration {
+        Duration::from_millis(self.send_timeout_ms)
+    }
+}
+
+/// A client was turned away from a room at [`RoomLimits::max_clients`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RoomFull {
+    pub max_clients: usize,
+}
+
+impl std::fmt::Display for RoomFull {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Room is full ({} clients); try again later", self.max_clients)
+    }
+}
+
+impl std::error::Error for RoomFull {}
+
 #[derive(Debug, Clone)]
 pub struct RoomClient {
     pub id: String,
     pub role: ClientRole,
     pub connected_at: DateTime<Utc>,
-    pub sender: broadcast::Sender<RoomMessage>,
+    pub sender: broadcast::Sender<Sequenced>,
+}
+
+/// When the current or last recording started and stopped
+#[derive(Debug, Clone, Copy, Default)]
+struct RecordingTimes {
+    started_at: Option<DateTime<Utc>>,
+    /// `None` while recording
+    stopped_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone)]
@@ -62,11 +384,63 @@ pub struct Room {
     pub id: String,
     pub created_at: DateTime<Utc>,
     pub clients: Arc<RwLock<HashMap<String, RoomClient>>>,
-    pub message_history: Arc<RwLock<VecDeque<RoomMessage>>>,
+    pub message_history: Arc<RwLock<VecDeque<Sequenced>>>,
     pub is_recording: Arc<RwLock<bool>>,
     pub recorded_messages: Arc<RwLock<Vec<RecordedMessage>>>,
     pub state: Arc<RwLock<serde_json::Value>>,
-    pub broadcast_tx: broadcast::Sender<RoomMessage>,
+    pub broadcast_tx: broadcast::Sender<Sequenced>,
+    /// Sequence number of the latest message sent
+    last_seq: Arc<AtomicU64>,
+    /// Resume tokens of connected and recently disconnected clients
+    resume_tickets: Arc<RwLock<HashMap<String, ResumeTicket>>>,
+    /// Server events (slide changes, recording) for webhooks
+    pub events: Option<EventSender>,
+    /// Server plugins that see events before they are broadcast
+    pub plugins: Option<Arc<PluginHost>>,
+    /// Store the room's state, history, and recording are written through to
+    pub store: Option<Arc<RoomStore>>,
+    pub limits: RoomLimits,
+    /// The replay running in the room, if any
+    replay: Arc<std::sync::Mutex<Option<tokio::task::AbortHandle>>>,
+    /// Polls put to the room, oldest first
+    polls: Arc<RwLock<Vec<Poll>>>,
+    /// Audience questions, oldest first
+    questions: Arc<RwLock<Vec<Question>>>,
+    /// Reactions waiting to be broadcast
+    reactions: Arc<std::sync::Mutex<ReactionWindow>>,
+    /// Client messages turned down, by diagnostic code
+    rejections: Arc<std::sync::Mutex<BTreeMap<&'static str, u64>>>,
+    /// Other servers with the same room, with `--redis`
+    pub fanout: Option<Arc<Fanout>>,
+    recording_times: Arc<RwLock<RecordingTimes>>,
+}
+
+/// A room at a glance, for tools that do not hold a WebSocket open
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoomSummary {
+    pub room_id: String,
+    pub current_slide: Option<String>,
+    pub current_fragment: Option<u64>,
+    pub clients: ClientCounts,
+    /// Every poll, oldest first
+    pub polls: Vec<PollResults>,
+    /// Approved questions, most upvoted first
+    pub questions: Vec<Question>,
+    pub recording: bool,
+    /// Sequence number of the latest message, for resuming from this point
+    pub seq: u64,
+    /// Client messages turned down, by diagnostic code
+    pub rejected: BTreeMap<&'static str, u64>,
+    /// The room's state as clients receive it
+    pub state: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ClientCounts {
+    pub total: usize,
+    pub presenters: usize,
+    pub audience: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -79,8 +453,12 @@ pub struct RecordedMessage {
 
 impl Room {
     pub fn new(room_id: String) -> Self {
-        let (broadcast_tx, _) = broadcast::channel(1000);
-        
+        Self::with_limits(room_id, RoomLimits::default())
+    }
+
+    pub fn with_limits(room_id: String, limits: RoomLimits) -> Self {
+        let (broadcast_tx, _) = broadcast::channel(limits.buffer.max(1));
+
         Self {
             id: room_id,
             created_at: Utc::now(),
@@ -90,54 +468,207 @@ impl Room {
             recorded_messages: Arc::new(RwLock::new(Vec::new())),
             state: Arc::new(RwLock::new(serde_json::Value::Null)),
             broadcast_tx,
+            last_seq: Arc::new(AtomicU64::new(0)),
+            resume_tickets: Arc::new(RwLock::new(HashMap::new())),
+            events: None,
+            plugins: None,
+            store: None,
+            limits,
+            replay: Arc::new(std::sync::Mutex::new(None)),
+            polls: Arc::new(RwLock::new(Vec::new())),
+            questions: Arc::new(RwLock::new(Vec::new())),
+            reactions: Arc::new(std::sync::Mutex::new(ReactionWindow::default())),
+            rejections: Arc::new(std::sync::Mutex::new(BTreeMap::new())),
+            fanout: None,
+            recording_times: Arc::new(RwLock::new(RecordingTimes::default())),
+        }
+    }
+
+    /// Tell other servers with this room about a change to it
+    fn share(&self, action: SharedAction) {
+        if let Some(fanout) = &self.fanout {
+            fanout.share(&self.id, action);
+        }
+    }
+
+    fn notify(&self, event: &'static str, data: serde_json::Value) {
+        if let Some(events) = &self.events {
+            let _ = events.send(ServerEvent::new(event, Some(&self.id), data));
         }
     }
 
-    pub async fn add_client(&self, client_id: String, role: ClientRole) -> broadcast::Receiver<RoomMessage> {
+    /// Write the room's state snapshot to the store, if there is one
+    async fn persist(&self) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        let snapshot = RoomSnapshot {
+            id: self.id.clone(),
+            state: self.state.read().await.clone(),
+            recording: *self.is_recording.read().await,
+            created_at: self.created_at,
+        };
+        if let Err(e) = store.save_room(&snapshot).await {
+            tracing::warn!(room = %self.id, error = %e, "could not save room");
+        }
+    }
+
+    /// Let a client in, unless the room is full and it is not presenting
+    pub async fn add_client(&self, client_id: String, role: ClientRole) -> Result<broadcast::Receiver<Sequenced>, RoomFull> {
         let client = RoomClient {
             id: client_id.clone(),
-            role: role.clone(),
+            role,
             connected_at: Utc::now(),
             sender: self.broadcast_tx.clone(),
         };
 
-        let receiver = self.broadcast_tx.subscribe();
-        
-        {
+        let receiver = {
             let mut clients = self.clients.write().await;
+            if let Some(max_clients) = self.limits.max_clients {
+                if role == ClientRole::Audience && clients.len() >= max_clients {
+                    return Err(RoomFull { max_clients });
+                }
+            }
             clients.insert(client_id.clone(), client);
-        }
+            self.broadcast_tx.subscribe()
+        };
 
         // Send join message
         let join_message = RoomMessage::Join {
             role,
             client_id: client_id.clone(),
+            token: None,
         };
         
         self.broadcast_message(join_message).await;
         
-        receiver
+        Ok(receiver)
     }
 
+    /// Give a connected client another role and tell the room
+    pub async fn set_role(&self, client_id: &str, role: ClientRole) {
+        if let Some(client) = self.clients.write().await.get_mut(client_id) {
+            client.role = role;
+        }
+        for ticket in self.resume_tickets.write().await.values_mut().filter(|ticket| ticket.client_id == client_id) {
+            ticket.role = role;
+        }
+        self.broadcast_message(RoomMessage::Join { role, client_id: client_id.to_string(), token: None }).await;
+    }
+
+    /// Forget a client; its resume token stays valid for [`RESUME_WINDOW`]
     pub async fn remove_client(&self, client_id: &str) {
-        let mut clients = self.clients.write().await;
-        clients.remove(client_id);
+        self.clients.write().await.remove(client_id);
+        let expires_at = tokio::time::Instant::now() + RESUME_WINDOW;
+        for ticket in self.resume_tickets.write().await.values_mut().filter(|ticket| ticket.client_id == client_id) {
+            ticket.expires_at = Some(expires_at);
+        }
+    }
+
+    /// A new resume token for a connected client. Expired tokens are dropped here.
+    pub async fn issue_resume_token(&self, client_id: &str, role: ClientRole) -> String {
+        let token = Uuid::new_v4().simple().to_string();
+        let now = tokio::time::Instant::now();
+        let mut tickets = self.resume_tickets.write().await;
+        tickets.retain(|_, ticket| ticket.expires_at.is_none_or(|expires_at| expires_at > now));
+        tickets.insert(token.clone(), ResumeTicket { client_id: client_id.to_string(), role, expires_at: None });
+        token
+    }
+
+    /// Trade a resume token for the client id and role it was issued to. Each
+    /// token works once; `None` when it is unknown or expired.
+    pub async fn redeem_resume_token(&self, token: &str) -> Option<(String, ClientRole)> {
+        let ticket = self.resume_tickets.write().await.remove(token)?;
+        let live = ticket.expires_at.is_none_or(|expires_at| expires_at > tokio::time::Instant::now());
+        live.then_some((ticket.client_id, ticket.role))
+    }
+
+    /// The room's slide, clients, polls, and questions
+    pub async fn summary(&self) -> RoomSummary {
+        let state = self.state.read().await.clone();
+        let slide = state.get("currentSlide");
+        let current_slide = slide.and_then(|slide| slide.get("slideId")).and_then(|id| id.as_str()).map(str::to_string);
+        // A fragment change from a slide shown earlier is stale
+        let fragment = state.get("currentFragment").filter(|fragment| {
+            fragment.get("slideId").and_then(|id| id.as_str()).is_none_or(|id| Some(id) == current_slide.as_deref())
+        });
+        let current_fragment = fragment.or(slide).and_then(|data| data.get("fragment")).and_then(|fragment| fragment.as_u64());
+
+        let mut clients = ClientCounts::default();
+        for client in self.clients.read().await.values() {
+            clients.total += 1;
+            match client.role {
+                ClientRole::Presenter => clients.presenters += 1,
+                ClientRole::Audience => clients.audience += 1,
+            }
+        }
+        RoomSummary {
+            room_id: self.id.clone(),
+            current_slide,
+            current_fragment,
+            clients,
+            polls: self.list_polls().await,
+            questions: self.list_questions(false).await,
+            recording: *self.is_recording.read().await,
+            seq: self.last_seq(),
+            rejected: self.rejections(),
+            state,
+        }
+    }
+
+    /// Note that a client message was turned down with `code`
+    pub fn count_rejection(&self, code: &'static str) {
+        *self.rejections.lock().unwrap_or_else(|e| e.into_inner()).entry(code).or_insert(0) += 1;
+    }
+
+    /// How many client messages were turned down, by diagnostic code
+    pub fn rejections(&self) -> BTreeMap<&'static str, u64> {
+        self.rejections.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn last_seq(&self) -> u64 {
+        self.last_seq.load(Ordering::SeqCst)
+    }
+
+    /// Messages after `seq`, oldest first; `None` when history no longer reaches
+    /// back that far, or `seq` is not one this room sent
+    pub async fn messages_since(&self, seq: u64) -> Option<Vec<Sequenced>> {
+        let history = self.message_history.read().await;
+        let oldest = history.front().map_or(self.last_seq() + 1, |message| message.seq);
+        if seq > self.last_seq() || seq.saturating_add(1) < oldest {
+            return None;
+        }
+        Some(history.iter().filter(|message| message.seq > seq).cloned().collect())
     }
 
+    /// Send `message` to the clients [`Recipients::for_message`] picks
     pub async fn broadcast_message(&self, message: RoomMessage) {
-        // Add to history
+        let to = Recipients::for_message(&message);
+        self.broadcast_to(message, to).await;
+    }
+
+    /// Send `message` to `to`; it is kept in history and recorded either way
+    pub async fn broadcast_to(&self, message: RoomMessage, to: Recipients) {
+        // Numbered, kept, and sent under one lock, so clients get sequence
+        // numbers in order and history never misses a message they were sent
         {
             let mut history = self.message_history.write().await;
-            history.push_back(message.clone());
-            
-            // Keep only last 1000 messages
-            if history.len() > 1000 {
+            let seq = self.last_seq.fetch_add(1, Ordering::SeqCst) + 1;
+            let sequenced = Sequenced { seq, message: message.clone(), to };
+            history.push_back(sequenced.clone());
+            if history.len() > HISTORY_LIMIT {
                 history.pop_front();
             }
+            let _ = self.broadcast_tx.send(sequenced);
+        }
+        if let Some(store) = &self.store {
+            if let Err(e) = store.append_history(&self.id, &message).await {
+                tracing::warn!(room = %self.id, error = %e, "could not save message history");
+            }
         }
 
         // Record if recording is active
-        {
+        let stored = {
             let is_recording = *self.is_recording.read().await;
             if is_recording {
                 let mut recorded = self.recorded_messages.write().await;
@@ -150,26 +681,73 @@ impl Room {
                     recorded_at: Utc::now(),
                     session_time,
                 });
+                recorded.last().cloned().map(|last| (recorded.len() - 1, last))
+            } else {
+                None
+            }
+        };
+        if let (Some(store), Some((seq, recorded))) = (&self.store, stored) {
+            if let Err(e) = store.append_recorded(&self.id, seq, &recorded).await {
+                tracing::warn!(room = %self.id, error = %e, "could not save recording");
             }
         }
-
-        // Broadcast to all clients
-        let _ = self.broadcast_tx.send(message);
     }
 
     pub async fn handle_event(&self, event: EventData) {
-        let message = RoomMessage::Event {
-            event: event.clone(),
-            timestamp: Utc::now(),
+        let event = match &self.plugins {
+            Some(plugins) => match plugins.on_room_message(&self.id, event) {
+                Some(event) => event,
+                None => return,
+            },
+            None => event,
         };
 
         // Handle special events
         match event.name.as_str() {
             "slide:change" => {
-                self.update_state("currentSlide", event.data).await;
+                self.notify("slide.changed", event.data.clone());
+            }
+            // Requests to the server; it announces the outcome itself
+            "poll:create" => {
+                let created = match serde_json::from_value::<NewPoll>(event.data) {
+                    Ok(poll) => self.create_poll(poll).await.map(drop),
+                    Err(e) => Err(PollError::Invalid(e.to_string())),
+                };
+                if let Err(e) = created {
+                    tracing::debug!(room = %self.id, error = %e, "poll not created");
+                }
+                return;
+            }
+            "poll:close" => {
+                let poll_id = event.data["pollId"].as_str().unwrap_or_default();
+                if let Err(e) = self.close_poll(poll_id).await {
+                    tracing::debug!(room = %self.id, poll = %poll_id, error = %e, "poll not closed");
+                }
+                return;
+            }
+            "qa:approve" | "qa:dismiss" => {
+                let status = if event.name == "qa:approve" { QuestionStatus::Approved } else { QuestionStatus::Dismissed };
+                let question_id = event.data["questionId"].as_str().unwrap_or_default();
+                if let Err(e) = self.moderate_question(question_id, status).await {
+                    tracing::debug!(room = %self.id, question = %question_id, error = %e, "question not moderated");
+                }
+                return;
+            }
+            _ => {}
+        }
+
+        self.share(SharedAction::Event { event: event.clone() });
+        self.relay_event(event).await;
+    }
+
+    /// Move the room's state as `event` says and send it to the clients
+    async fn relay_event(&self, event: EventData) {
+        match event.name.as_str() {
+            "slide:change" => {
+                self.update_state("currentSlide", event.data.clone()).await;
             }
             "fragment:change" => {
-                self.update_state("currentFragment", event.data).await;
+                self.update_state("currentFragment", event.data.clone()).await;
             }
             "presenter:sync" => {
                 // Sync presenter state
@@ -177,41 +755,304 @@ impl Room {
                     self.sync_presenter_state(state).await;
                 }
             }
+            // Tallies and boards played back from a recording
+            "poll:start" | "poll:results" | "poll:stop" => {
+                self.update_state("poll", event.data.clone()).await;
+            }
+            "qa:questions" => {
+                self.update_state("questions", event.data.clone()).await;
+            }
             _ => {}
         }
-
-        self.broadcast_message(message).await;
+        self.broadcast_message(RoomMessage::Event { event, timestamp: Utc::now() }).await;
     }
 
     pub async fn update_state(&self, key: &str, value: serde_json::Value) {
-        let mut state = self.state.write().await;
-        
-        if let Some(obj) = state.as_object_mut() {
-            obj.insert(key.to_string(), value);
-        } else {
-            let mut map = serde_json::Map::new();
-            map.insert(key.to_string(), value);
-            *state = serde_json::Value::Object(map);
+        {
+            let mut state = self.state.write().await;
+
+            if let Some(obj) = state.as_object_mut() {
+                obj.insert(key.to_string(), value);
+            } else {
+                let mut map = serde_json::Map::new();
+                map.insert(key.to_string(), value);
+                *state = serde_json::Value::Object(map);
+            }
+        }
+        self.persist().await;
+    }
+
+    /// Put a question to the room; everyone gets a `poll:start` event with the empty tally
+    pub async fn create_poll(&self, poll: NewPoll) -> Result<PollResults, PollError> {
+        let question = poll.question.trim().to_string();
+        let options: Vec<String> = poll.options.iter().map(|option| option.trim().to_string()).collect();
+        if question.is_empty() {
+            return Err(PollError::Invalid("A poll needs a question".to_string()));
         }
+        if !(2..=MAX_POLL_OPTIONS).contains(&options.len()) || options.iter().any(String::is_empty) {
+            return Err(PollError::Invalid(format!("A poll needs 2 to {} non-empty options", MAX_POLL_OPTIONS)));
+        }
+        let poll = Poll { id: Uuid::new_v4().simple().to_string(), question, options, open: true, votes: HashMap::new() };
+        self.share(SharedAction::OpenPoll { poll_id: poll.id.clone(), question: poll.question.clone(), options: poll.options.clone() });
+        let results = self.open_poll(poll).await;
+        self.notify("poll.opened", serde_json::to_value(&results).unwrap_or_default());
+        Ok(results)
+    }
+
+    async fn open_poll(&self, poll: Poll) -> PollResults {
+        let results = poll.results();
+        self.polls.write().await.push(poll);
+        self.publish_poll("poll:start", &results).await;
+        results
+    }
+
+    /// Count `client_id`'s vote and broadcast the new tally as `poll:results`
+    pub async fn vote(&self, client_id: &str, poll_id: &str, option: usize) -> Result<PollResults, PollError> {
+        let results = self.count_vote(client_id, poll_id, option).await?;
+        self.share(SharedAction::Vote { client_id: client_id.to_string(), poll_id: poll_id.to_string(), option });
+        Ok(results)
+    }
+
+    async fn count_vote(&self, client_id: &str, poll_id: &str, option: usize) -> Result<PollResults, PollError> {
+        let results = {
+            let mut polls = self.polls.write().await;
+            let poll = polls.iter_mut().find(|poll| poll.id == poll_id).ok_or(PollError::NotFound)?;
+            if !poll.open {
+                return Err(PollError::Closed);
+            }
+            if option >= poll.options.len() {
+                return Err(PollError::NoSuchOption);
+            }
+            poll.votes.insert(client_id.to_string(), option);
+            poll.results()
+        };
+        self.publish_poll("poll:results", &results).await;
+        Ok(results)
+    }
+
+    /// Stop taking votes; everyone gets a `poll:stop` event with the final tally
+    pub async fn close_poll(&self, poll_id: &str) -> Result<PollResults, PollError> {
+        let results = self.end_poll(poll_id).await?;
+        self.share(SharedAction::ClosePoll { poll_id: poll_id.to_string() });
+        self.notify("poll.closed", serde_json::to_value(&results).unwrap_or_default());
+        Ok(results)
+    }
+
+    async fn end_poll(&self, poll_id: &str) -> Result<PollResults, PollError> {
+        let results = {
+            let mut polls = self.polls.write().await;
+            let poll = polls.iter_mut().find(|poll| poll.id == poll_id).ok_or(PollError::NotFound)?;
+            if !poll.open {
+                return Err(PollError::Closed);
+            }
+            poll.open = false;
+            poll.results()
+        };
+        self.publish_poll("poll:stop", &results).await;
+        Ok(results)
+    }
+
+    pub async fn poll_results(&self, poll_id: &str) -> Option<PollResults> {
+        self.polls.read().await.iter().find(|poll| poll.id == poll_id).map(Poll::results)
+    }
+
+    /// Tallies of every poll put to the room, oldest first
+    pub async fn list_polls(&self) -> Vec<PollResults> {
+        self.polls.read().await.iter().map(Poll::results).collect()
+    }
+
+    /// Broadcast a tally, which records it, and keep it in the state for clients that join later
+    async fn publish_poll(&self, name: &str, results: &PollResults) {
+        let data = serde_json::to_value(results).unwrap_or_default();
+        self.update_state("poll", data.clone()).await;
+        let event = EventData { name: name.to_string(), data, client_id: "system".to_string() };
+        self.broadcast_message(RoomMessage::Event { event, timestamp: Utc::now() }).await;
+    }
+
+    /// Queue `client_id`'s question for the presenter to approve
+    pub async fn ask(&self, client_id: &str, text: &str) -> Result<Question, QuestionError> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Err(QuestionError::Invalid("A question cannot be empty".to_string()));
+        }
+        if text.chars().count() > MAX_QUESTION_CHARS {
+            return Err(QuestionError::Invalid(format!("Questions are at most {} characters", MAX_QUESTION_CHARS)));
+        }
+        let question = Question {
+            id: Uuid::new_v4().simple().to_string(),
+            text: text.to_string(),
+            status: QuestionStatus::Pending,
+            upvotes: 0,
+            asked_at: Utc::now(),
+            voters: HashSet::new(),
+        };
+        tracing::debug!(room = %self.id, client = %client_id, question = %question.id, "question asked");
+        self.share(SharedAction::Ask {
+            client_id: client_id.to_string(),
+            question_id: question.id.clone(),
+            text: question.text.clone(),
+            asked_at: question.asked_at,
+        });
+        self.add_question(question.clone()).await;
+        self.notify("question.asked", serde_json::to_value(&question).unwrap_or_default());
+        Ok(question)
+    }
+
+    async fn add_question(&self, question: Question) {
+        self.questions.write().await.push(question);
+        self.publish_questions().await;
+    }
+
+    /// Count `client_id`'s support for an approved question; upvoting twice changes nothing
+    pub async fn upvote(&self, client_id: &str, question_id: &str) -> Result<Question, QuestionError> {
+        let question = self.count_upvote(client_id, question_id).await?;
+        self.share(SharedAction::Upvote { client_id: client_id.to_string(), question_id: question_id.to_string() });
+        Ok(question)
+    }
+
+    async fn count_upvote(&self, client_id: &str, question_id: &str) -> Result<Question, QuestionError> {
+        let (question, counted) = {
+            let mut questions = self.questions.write().await;
+            let question = questions
+                .iter_mut()
+                .find(|question| question.id == question_id && question.status == QuestionStatus::Approved)
+                .ok_or(QuestionError::NotFound)?;
+            let counted = question.voters.insert(client_id.to_string());
+            question.upvotes = question.voters.len();
+            (question.clone(), counted)
+        };
+        if counted {
+            self.publish_questions().await;
+        }
+        Ok(question)
+    }
+
+    /// Approve or dismiss a question
+    pub async fn moderate_question(&self, question_id: &str, status: QuestionStatus) -> Result<Question, QuestionError> {
+        let question = self.set_question_status(question_id, status).await?;
+        self.share(SharedAction::Moderate { question_id: question_id.to_string(), status });
+        Ok(question)
+    }
+
+    async fn set_question_status(&self, question_id: &str, status: QuestionStatus) -> Result<Question, QuestionError> {
+        let question = {
+            let mut questions = self.questions.write().await;
+            let question = questions.iter_mut().find(|question| question.id == question_id).ok_or(QuestionError::NotFound)?;
+            question.status = status;
+            question.clone()
+        };
+        self.publish_questions().await;
+        Ok(question)
+    }
+
+    /// Questions, most upvoted first: the approved ones, plus pending and
+    /// dismissed ones for the presenter's queue
+    pub async fn list_questions(&self, include_unapproved: bool) -> Vec<Question> {
+        let mut questions: Vec<Question> = self
+            .questions
+            .read()
+            .await
+            .iter()
+            .filter(|question| include_unapproved || question.status == QuestionStatus::Approved)
+            .cloned()
+            .collect();
+        questions.sort_by(|a, b| b.upvotes.cmp(&a.upvotes).then(a.asked_at.cmp(&b.asked_at)));
+        questions
+    }
+
+    /// Broadcast the approved questions and how many wait for moderation, as
+    /// `qa:questions`, and the whole queue to presenters, as `qa:queue`
+    async fn publish_questions(&self) {
+        let approved = self.list_questions(false).await;
+        let pending = self.questions.read().await.iter().filter(|question| question.status == QuestionStatus::Pending).count();
+        let data = serde_json::json!({ "questions": approved, "pending": pending });
+        self.update_state("questions", data.clone()).await;
+        let event = EventData { name: "qa:questions".to_string(), data, client_id: "system".to_string() };
+        self.broadcast_message(RoomMessage::Event { event, timestamp: Utc::now() }).await;
+
+        let queue = serde_json::json!({ "questions": self.list_questions(true).await });
+        let event = EventData { name: "qa:queue".to_string(), data: queue, client_id: "system".to_string() };
+        self.broadcast_to(RoomMessage::Event { event, timestamp: Utc::now() }, Recipients::Presenters).await;
+    }
+
+    /// Count a reaction into the current window; `false` when it is not one of
+    /// [`REACTIONS`]. Large audiences can send thousands a second, so the room
+    /// broadcasts one `reactions` event of counts per [`REACTION_WINDOW`] instead
+    /// of relaying each. A client's reactions past [`MAX_REACTIONS_PER_CLIENT`]
+    /// in a window are dropped.
+    pub fn react(&self, client_id: &str, reaction: &str) -> bool {
+        let counted = self.count_reaction(client_id, reaction);
+        if counted {
+            self.share(SharedAction::React { client_id: client_id.to_string(), reaction: reaction.to_string() });
+        }
+        counted
+    }
+
+    fn count_reaction(&self, client_id: &str, reaction: &str) -> bool {
+        let Some(&reaction) = REACTIONS.iter().find(|&&known| known == reaction) else {
+            return false;
+        };
+        let first_in_window = {
+            let mut window = self.reactions.lock().unwrap_or_else(|e| e.into_inner());
+            let sent = window.senders.entry(client_id.to_string()).or_insert(0);
+            if *sent >= MAX_REACTIONS_PER_CLIENT {
+                return true;
+            }
+            *sent += 1;
+            let first_in_window = window.counts.is_empty();
+            *window.counts.entry(reaction.to_string()).or_insert(0) += 1;
+            first_in_window
+        };
+        if first_in_window {
+            let room = self.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(REACTION_WINDOW).await;
+                room.flush_reactions().await;
+            });
+        }
+        true
+    }
+
+    async fn flush_reactions(&self) {
+        let window = std::mem::take(&mut *self.reactions.lock().unwrap_or_else(|e| e.into_inner()));
+        if window.counts.is_empty() {
+            return;
+        }
+        let data = serde_json::json!({
+            "counts": window.counts,
+            "clients": window.senders.len(),
+            "windowMs": REACTION_WINDOW.as_millis() as u64,
+        });
+        let event = EventData { name: "reactions".to_string(), data, client_id: "system".to_string() };
+        self.broadcast_message(RoomMessage::Event { event, timestamp: Utc::now() }).await;
     }
 
     pub async fn sync_presenter_state(&self, presenter_state: PresenterState) {
-        let mut state = self.state.write().await;
-        *state = serde_json::to_value(presenter_state).unwrap_or(serde_json::Value::Null);
+        *self.state.write().await = serde_json::to_value(presenter_state).unwrap_or(serde_json::Value::Null);
+        self.persist().await;
     }
 
     pub async fn start_recording(&self) {
-        let mut is_recording = self.is_recording.write().await;
-        *is_recording = true;
-        
+        *self.is_recording.write().await = true;
+        *self.recording_times.write().await = RecordingTimes { started_at: Some(Utc::now()), stopped_at: None };
+
         // Clear previous recording
-        let mut recorded = self.recorded_messages.write().await;
-        recorded.clear();
+        self.recorded_messages.write().await.clear();
+        if let Some(store) = &self.store {
+            if let Err(e) = store.clear_recording(&self.id).await {
+                tracing::warn!(room = %self.id, error = %e, "could not clear recording");
+            }
+        }
+        self.persist().await;
+        self.notify("recording.started", serde_json::json!({}));
     }
 
     pub async fn stop_recording(&self) {
-        let mut is_recording = self.is_recording.write().await;
-        *is_recording = false;
+        *self.is_recording.write().await = false;
+        self.recording_times.write().await.stopped_at = Some(Utc::now());
+        let messages = self.recorded_messages.read().await.len();
+        self.persist().await;
+        self.notify("recording.stopped", serde_json::json!({ "messages": messages }));
     }
 
     pub async fn get_recorded_messages(&self) -> Vec<RecordedMessage> {
@@ -219,32 +1060,197 @@ impl Room {
         recorded.clone()
     }
 
-    pub async fn export_recording(&self) -> String {
+    /// Replace the recording with one exported earlier, here or from another
+    /// server, so it can be replayed; `false` while the room is recording
+    pub async fn import_recording(&self, recording: Recording) -> bool {
+        if *self.is_recording.read().await {
+            return false;
+        }
+        let mut messages = recording.messages;
+        // Replays wait for each message in turn, so keep them in session order
+        messages.sort_by_key(|recorded| recorded.session_time);
+        if let Some(store) = &self.store {
+            let saved = async {
+                store.clear_recording(&self.id).await?;
+                for (seq, recorded) in messages.iter().enumerate() {
+                    store.append_recorded(&self.id, seq, recorded).await?;
+                }
+                anyhow::Ok(())
+            };
+            if let Err(e) = saved.await {
+                tracing::warn!(room = %self.id, error = %e, "could not save imported recording");
+            }
+        }
+        *self.recorded_messages.write().await = messages;
+        *self.recording_times.write().await = RecordingTimes { started_at: recording.info.started_at, stopped_at: recording.info.stopped_at };
+        true
+    }
+
+    /// The recording in its envelope, for `/api/rooms/:room_id/dump`
+    pub async fn recording(&self, deck_title: Option<String>) -> Recording {
+        let RecordingTimes { started_at, stopped_at } = *self.recording_times.read().await;
+        let recording = *self.is_recording.read().await;
         let messages = self.get_recorded_messages().await;
-        
-        messages.iter()
-            .map(|recorded| {
-                serde_json::to_string(&recorded).unwrap_or_default()
-            })
-            .collect::<Vec<_>>()
-            .join("\n")
+        // A restored room only knows when its recording ran from the messages
+        let started_at = started_at.or_else(|| messages.first().map(|first| first.recorded_at));
+        let stopped_at = match stopped_at {
+            Some(stopped_at) => Some(stopped_at),
+            None if recording => None,
+            None => messages.last().map(|last| last.recorded_at),
+        };
+        Recording { info: RecordingInfo { room_id: self.id.clone(), deck_title, started_at, stopped_at }, messages }
     }
 
+    /// Play `messages` into the room with their original spacing divided by
+    /// `time_compression`. Events go through [`Room::handle_event`], so they move
+    /// the room as they did when recorded. Client messages such as joins, votes,
+    /// and reactions are skipped; the tallies they produced are events.
     pub async fn replay_recording(&self, messages: Vec<RecordedMessage>, time_compression: f64) {
-        if messages.is_empty() {
+        let Some(first) = messages.first() else {
             return;
-        }
+        };
+        let start_time = first.session_time;
+        let started = tokio::time::Instant::now();
 
-        let start_time = messages[0].session_time;
-        
         for recorded in messages {
-            let delay_ms = ((recorded.session_time - start_time) as f64 / time_compression) as u64;
-            
-            tokio::time::sleep(tokio::time::Duration::from_millis(delay_ms)).await;
-            
-            self.broadcast_message(recorded.message).await;
+            let offset_ms = recorded.session_time.saturating_sub(start_time) as f64 / time_compression;
+            tokio::time::sleep_until(started + tokio::time::Duration::from_millis(offset_ms as u64)).await;
+
+            match recorded.message {
+                RoomMessage::Event { event, .. } => self.handle_event(event).await,
+                RoomMessage::State { data, .. } => {
+                    self.share(SharedAction::State { data: data.clone() });
+                    self.replace_state(data).await;
+                }
+                RoomMessage::Join { .. }
+                | RoomMessage::Ack { .. }
+                | RoomMessage::Vote { .. }
+                | RoomMessage::Ask { .. }
+                | RoomMessage::Upvote { .. }
+                | RoomMessage::React { .. }
+                | RoomMessage::Session { .. }
+                | RoomMessage::Heartbeat => {}
+            }
+        }
+    }
+
+    async fn replace_state(&self, data: serde_json::Value) {
+        *self.state.write().await = data.clone();
+        self.persist().await;
+        self.broadcast_message(RoomMessage::State { data, timestamp: Utc::now() }).await;
+    }
+
+    /// Apply a change another server made to this room. It was checked there,
+    /// so refusals here only mean the servers briefly disagree.
+    pub async fn apply_shared(&self, action: SharedAction) {
+        let refused = match action {
+            SharedAction::Event { event } => {
+                self.relay_event(event).await;
+                None
+            }
+            SharedAction::State { data } => {
+                self.replace_state(data).await;
+                None
+            }
+            SharedAction::OpenPoll { poll_id, question, options } => {
+                self.open_poll(Poll { id: poll_id, question, options, open: true, votes: HashMap::new() }).await;
+                None
+            }
+            SharedAction::ClosePoll { poll_id } => self.end_poll(&poll_id).await.err().map(|e| e.to_string()),
+            SharedAction::Vote { client_id, poll_id, option } => self.count_vote(&client_id, &poll_id, option).await.err().map(|e| e.to_string()),
+            SharedAction::Ask { client_id: _, question_id, text, asked_at } => {
+                let question = Question { id: question_id, text, status: QuestionStatus::Pending, upvotes: 0, asked_at, voters: HashSet::new() };
+                self.add_question(question).await;
+                None
+            }
+            SharedAction::Upvote { client_id, question_id } => self.count_upvote(&client_id, &question_id).await.err().map(|e| e.to_string()),
+            SharedAction::Moderate { question_id, status } => self.set_question_status(&question_id, status).await.err().map(|e| e.to_string()),
+            SharedAction::React { client_id, reaction } => {
+                self.count_reaction(&client_id, &reaction);
+                None
+            }
+            SharedAction::Hello => {
+                self.share(self.snapshot().await);
+                None
+            }
+            SharedAction::Snapshot { state, polls, questions } => {
+                self.merge_snapshot(state, polls, questions).await;
+                None
+            }
+        };
+        if let Some(reason) = refused {
+            tracing::debug!(room = %self.id, reason = %reason, "shared change not applied");
         }
     }
+
+    /// The room as a server that just opened it needs it
+    async fn snapshot(&self) -> SharedAction {
+        let questions = self
+            .questions
+            .read()
+            .await
+            .iter()
+            .map(|question| SharedQuestion { question: question.clone(), voters: question.voters.iter().cloned().collect() })
+            .collect();
+        SharedAction::Snapshot { state: self.state.read().await.clone(), polls: self.polls.read().await.clone(), questions }
+    }
+
+    /// Take what another server has that this one does not: state keys, polls, and questions
+    async fn merge_snapshot(&self, state: serde_json::Value, polls: Vec<Poll>, questions: Vec<SharedQuestion>) {
+        {
+            let mut current = self.state.write().await;
+            if current.is_null() {
+                *current = state;
+            } else if let (Some(current), serde_json::Value::Object(shared)) = (current.as_object_mut(), state) {
+                for (key, value) in shared {
+                    current.entry(key).or_insert(value);
+                }
+            }
+        }
+        self.persist().await;
+        {
+            let mut known = self.polls.write().await;
+            for poll in polls {
+                if !known.iter().any(|known| known.id == poll.id) {
+                    known.push(poll);
+                }
+            }
+        }
+        {
+            let mut known = self.questions.write().await;
+            for SharedQuestion { mut question, voters } in questions {
+                if !known.iter().any(|known| known.id == question.id) {
+                    question.voters = voters.into_iter().collect();
+                    known.push(question);
+                }
+            }
+        }
+    }
+
+    /// Replay `messages` in the background, replacing any replay already running
+    pub fn start_replay(&self, messages: Vec<RecordedMessage>, time_compression: f64) {
+        let room = self.clone();
+        let count = messages.len();
+        let task = tokio::spawn(async move {
+            room.replay_recording(messages, time_compression).await;
+            room.notify("replay.finished", serde_json::json!({ "messages": count }));
+        });
+        let previous = self.replay.lock().unwrap_or_else(|e| e.into_inner()).replace(task.abort_handle());
+        if let Some(previous) = previous {
+            previous.abort();
+        }
+        self.notify("replay.started", serde_json::json!({ "messages": count, "timeCompression": time_compression }));
+    }
+
+    /// Stop the running replay; `false` when none was running
+    pub fn stop_replay(&self) -> bool {
+        let running = self.replay.lock().unwrap_or_else(|e| e.into_inner()).take();
+        running.is_some_and(|task| {
+            let was_running = !task.is_finished();
+            task.abort();
+            was_running
+        })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -257,18 +1263,137 @@ pub struct PresenterState {
 
 pub struct RoomManager {
     rooms: Arc<RwLock<HashMap<String, Room>>>,
+    events: Option<EventSender>,
+    plugins: Option<Arc<PluginHost>>,
+    store: Option<Arc<RoomStore>>,
+    limits: RoomLimits,
+    fanout: Option<Arc<Fanout>>,
+}
+
+impl Default for RoomManager {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl RoomManager {
     pub fn new() -> Self {
         Self {
             rooms: Arc::new(RwLock::new(HashMap::new())),
+            events: None,
+            plugins: None,
+            store: None,
+            limits: RoomLimits::default(),
+            fanout: None,
         }
     }
 
+    /// Rooms that publish their server events on `events`
+    pub fn with_events(events: EventSender) -> Self {
+        Self {
+            rooms: Arc::new(RwLock::new(HashMap::new())),
+            events: Some(events),
+            plugins: None,
+            store: None,
+            limits: RoomLimits::default(),
+            fanout: None,
+        }
+    }
+
+    /// Pass room events through server plugins before broadcasting them
+    pub fn with_plugins(mut self, plugins: Arc<PluginHost>) -> Self {
+        self.plugins = Some(plugins);
+        self
+    }
+
+    /// Write rooms through to `store`; call [`RoomManager::restore`] to bring back stored rooms
+    pub fn with_store(mut self, store: Arc<RoomStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Open rooms with `limits`
+    pub fn with_limits(mut self, limits: RoomLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Share rooms with other servers through `fanout`; hand what they send to
+    /// [`RoomManager::receive`]
+    pub fn with_fanout(mut self, fanout: Arc<Fanout>) -> Self {
+        self.fanout = Some(fanout);
+        self
+    }
+
+    fn new_room(&self, room_id: String) -> Room {
+        // Internal rooms such as `__reload` are not announced or stored, and
+        // every open page joins them
+        let internal = room_id.starts_with("__");
+        let limits = if internal { RoomLimits { max_clients: None, ..self.limits } } else { self.limits };
+        let mut room = Room::with_limits(room_id, limits);
+        room.plugins = self.plugins.clone();
+        if !internal {
+            room.events = self.events.clone();
+            room.store = self.store.clone();
+            room.fanout = self.fanout.clone();
+        }
+        room.notify("room.opened", serde_json::json!({}));
+        room.share(SharedAction::Hello);
+        room
+    }
+
+    /// Apply a room change from another server, opening the room if this
+    /// server does not have it yet
+    pub async fn receive(&self, envelope: Envelope) {
+        let Some(fanout) = &self.fanout else {
+            return;
+        };
+        if envelope.origin == fanout.instance() || envelope.room_id.starts_with("__") {
+            return;
+        }
+        let room = match self.get_room(&envelope.room_id).await {
+            Some(room) => room,
+            // Nothing to answer or take for a room this server never opened
+            None if matches!(envelope.action, SharedAction::Hello | SharedAction::Snapshot { .. }) => return,
+            None => {
+                self.ensure_room(envelope.room_id.clone()).await;
+                let Some(room) = self.get_room(&envelope.room_id).await else {
+                    return;
+                };
+                room
+            }
+        };
+        room.apply_shared(envelope.action).await;
+    }
+
+    /// Recreate the rooms in the store, with their state, history, and recordings.
+    /// Returns how many were restored.
+    pub async fn restore(&self) -> anyhow::Result<usize> {
+        let Some(store) = &self.store else {
+            return Ok(0);
+        };
+        let snapshots = store.load_rooms().await?;
+        let count = snapshots.len();
+        for snapshot in snapshots {
+            let mut room = self.new_room(snapshot.id.clone());
+            room.created_at = snapshot.created_at;
+            *room.state.write().await = snapshot.state;
+            *room.is_recording.write().await = snapshot.recording;
+            // Numbered afresh; resume tokens do not survive a restart anyway
+            let history = store.load_history(&snapshot.id).await?;
+            room.last_seq.store(history.len() as u64, Ordering::SeqCst);
+            *room.message_history.write().await =
+                history.into_iter().zip(1..).map(|(message, seq)| Sequenced { seq, to: Recipients::for_message(&message), message }).collect();
+            *room.recorded_messages.write().await = store.load_recording(&snapshot.id).await?;
+            self.rooms.write().await.insert(snapshot.id, room);
+        }
+        Ok(count)
+    }
+
     pub async fn create_room(&self) -> String {
         let room_id = Uuid::new_v4().to_string();
-        let room = Room::new(room_id.clone());
+        let room = self.new_room(room_id.clone());
+        room.persist().await;
         
         let mut rooms = self.rooms.write().await;
         rooms.insert(room_id.clone(), room);
@@ -283,7 +1408,8 @@ impl RoomManager {
         }
         
         // Create room with the provided ID
-        let room = Room::new(room_id.clone());
+        let room = self.new_room(room_id.clone());
+        room.persist().await;
         
         let mut rooms = self.rooms.write().await;
         rooms.insert(room_id.clone(), room);
@@ -296,9 +1422,16 @@ impl RoomManager {
         rooms.get(room_id).cloned()
     }
 
+    pub async fn list_rooms(&self) -> Vec<Room> {
+        self.rooms.read().await.values().cloned().collect()
+    }
+
     pub async fn remove_room(&self, room_id: &str) {
         let mut rooms = self.rooms.write().await;
-        rooms.remove(room_id);
+        if let Some(room) = rooms.remove(room_id) {
+            room.notify("room.closed", serde_json::json!({}));
+            forget(&room).await;
+        }
     }
 
     pub async fn cleanup_empty_rooms(&self) {
@@ -320,46 +1453,229 @@ impl RoomManager {
         }
 
         for room_id in to_remove {
-            rooms.remove(&room_id);
+            if let Some(room) = rooms.remove(&room_id) {
+                room.notify("room.closed", serde_json::json!({ "reason": "inactive" }));
+                forget(&room).await;
+            }
+        }
+    }
+}
+
+/// Remove a closed room from the store
+async fn forget(room: &Room) {
+    if let Some(store) = &room.store {
+        if let Err(e) = store.delete_room(&room.id).await {
+            tracing::warn!(room = %room.id, error = %e, "could not delete stored room");
+        }
+    }
+}
+
+/// Diagnostic codes of the `error` events a room sends a client
+pub mod codes {
+    /// Not JSON text
+    pub const NOT_JSON: &str = "CS5001";
+    /// No `type`, or one the protocol does not have
+    pub const UNKNOWN_TYPE: &str = "CS5002";
+    /// Fields missing or of the wrong type for the message's `type`
+    pub const INVALID_FIELDS: &str = "CS5003";
+    /// A message only the server sends, such as `state` or `session`
+    pub const SERVER_ONLY: &str = "CS5004";
+    /// A presenter event from an audience client
+    pub const PRESENTER_ONLY: &str = "CS5005";
+    /// A `join` as presenter without the presenter token
+    pub const PRESENTER_TOKEN: &str = "CS5006";
+    /// The room is at its client cap
+    pub const ROOM_FULL: &str = "CS5007";
+    /// The room does not exist
+    pub const ROOM_NOT_FOUND: &str = "CS5008";
+    /// A vote the poll cannot take
+    pub const VOTE_REFUSED: &str = "CS5009";
+    /// A question or upvote that was refused
+    pub const QUESTION_REFUSED: &str = "CS5010";
+    /// A reaction not in [`super::REACTIONS`]
+    pub const UNKNOWN_REACTION: &str = "CS5011";
+}
+
+/// Message types clients may send, as the `type` field names them
+const CLIENT_TYPES: &[&str] = &["join", "event", "ack", "vote", "ask", "upvote", "react", "heartbeat"];
+
+/// Message types only the server sends
+const SERVER_TYPES: &[&str] = &["state", "session"];
+
+/// Something a client sent that the room turned down, and why
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rejection {
+    /// One of [`codes`]
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl Rejection {
+    pub fn new(code: &'static str, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+
+    /// The `error` event the client is sent
+    fn event(&self) -> RoomMessage {
+        system_error(self.code, &self.message)
+    }
+}
+
+/// Read one WebSocket text message, or say precisely what is wrong with it
+pub fn parse_client_message(text: &str) -> Result<RoomMessage, Rejection> {
+    let value: serde_json::Value = serde_json::from_str(text)
+        .map_err(|e| Rejection::new(codes::NOT_JSON, format!("Messages must be JSON: {}", e)))?;
+    let kind = match value.get("type") {
+        Some(serde_json::Value::String(kind)) => kind.clone(),
+        Some(_) => return Err(Rejection::new(codes::UNKNOWN_TYPE, "`type` must be a string")),
+        None => return Err(Rejection::new(codes::UNKNOWN_TYPE, "Messages need a `type`")),
+    };
+    if SERVER_TYPES.contains(&kind.as_str()) {
+        return Err(Rejection::new(codes::SERVER_ONLY, format!("Only the server sends `{}` messages", kind)));
+    }
+    if !CLIENT_TYPES.contains(&kind.as_str()) {
+        let expected = CLIENT_TYPES.join(", ");
+        return Err(Rejection::new(codes::UNKNOWN_TYPE, format!("Unknown message type `{}`; expected one of {}", kind, expected)));
+    }
+    serde_json::from_value(value).map_err(|e| Rejection::new(codes::INVALID_FIELDS, format!("Invalid `{}` message: {}", kind, e)))
+}
+
+/// An `error` event from the server, for one client
+fn system_error(code: &str, message: &str) -> RoomMessage {
+    RoomMessage::Event {
+        event: EventData {
+            name: "error".to_string(),
+            data: serde_json::json!({ "code": code, "message": message }),
+            client_id: "system".to_string(),
+        },
+        timestamp: Utc::now(),
+    }
+}
+
+/// Count a refused message against the room and tell the client why
+async fn reject(socket: &mut WebSocket, room: &Room, rejection: Rejection) {
+    room.count_rejection(rejection.code);
+    tracing::debug!(code = rejection.code, reason = %rejection.message, "rejected message");
+    if let Ok(msg) = serde_json::to_string(&rejection.event()) {
+        let _ = socket.send(Message::Text(msg)).await;
+    }
+}
+
+/// Whether a client may take `role`, given the token it sent and the server's
+/// presenter token. Without a presenter token, anyone may present.
+fn may_take_role(role: ClientRole, token: Option<&str>, presenter_token: Option<&str>) -> bool {
+    match (role, presenter_token) {
+        (ClientRole::Audience, _) | (ClientRole::Presenter, None) => true,
+        (ClientRole::Presenter, Some(expected)) => {
+            token.is_some_and(|token| crate::constant_time_eq(token.as_bytes(), expected.as_bytes()))
         }
     }
 }
 
+/// Serve one client of a room, starting in `role`. A `join` message asks for
+/// another role; presenting takes `presenter_token`. Audience clients'
+/// presenter events are dropped.
 pub async fn handle_websocket_connection(
     mut socket: WebSocket,
     room_id: String,
     room_manager: Arc<RoomManager>,
+    mut role: ClientRole,
+    presenter_token: Option<String>,
+    resume: Option<ResumeRequest>,
 ) {
     let room = match room_manager.get_room(&room_id).await {
         Some(room) => room,
         None => {
-            let _ = socket.send(Message::Text(
-                serde_json::to_string(&RoomMessage::Event {
-                    event: EventData {
-                        name: "error".to_string(),
-                        data: serde_json::json!({"message": "Room not found"}),
-                        client_id: "system".to_string(),
-                    },
-                    timestamp: Utc::now(),
-                }).unwrap()
-            )).await;
+            tracing::debug!(room = %room_id, "connection to unknown room refused");
+            let _ = socket.send(Message::Text(serde_json::to_string(&system_error(codes::ROOM_NOT_FOUND, "Room not found")).unwrap())).await;
             return;
         }
     };
 
-    let client_id = Uuid::new_v4().to_string();
-    let mut receiver = room.add_client(client_id.clone(), ClientRole::Audience).await;
+    let mut resumed_from = None;
+    let client_id = match resume {
+        Some(resume) => match room.redeem_resume_token(&resume.token).await {
+            Some((client_id, resumed_role)) => {
+                // A presenter the URL already let in stays one
+                if role == ClientRole::Audience {
+                    role = resumed_role;
+                }
+                resumed_from = Some(resume.last_seq);
+                client_id
+            }
+            None => {
+                tracing::debug!(room = %room_id, "resume token unknown or expired; joining afresh");
+                Uuid::new_v4().to_string()
+            }
+        },
+        None => Uuid::new_v4().to_string(),
+    };
+    let span = tracing::info_span!("room_client", room = %room_id, client = %client_id, role = ?role);
+    serve_client(socket, room, client_id, role, presenter_token, resumed_from).instrument(span).await;
+}
 
-    // Send current state to new client
-    let state = room.state.read().await.clone();
-    if !state.is_null() {
-        let state_message = RoomMessage::State {
-            data: state,
-            timestamp: Utc::now(),
-        };
-        
-        if let Ok(msg) = serde_json::to_string(&state_message) {
-            let _ = socket.send(Message::Text(msg)).await;
+/// Relay one connected client's messages until it disconnects. A client resuming
+/// from `resumed_from` gets the messages it missed rather than the state.
+async fn serve_client(
+    mut socket: WebSocket,
+    room: Room,
+    client_id: String,
+    mut role: ClientRole,
+    presenter_token: Option<String>,
+    resumed_from: Option<u64>,
+) {
+    let mut receiver = match room.add_client(client_id.clone(), role).await {
+        Ok(receiver) => receiver,
+        Err(full) => {
+            tracing::info!(max_clients = full.max_clients, "refused client; room is full");
+            reject(&mut socket, &room, Rejection::new(codes::ROOM_FULL, full.to_string())).await;
+            let close = CloseFrame { code: close_code::AGAIN, reason: "Room is full".into() };
+            let _ = socket.send(Message::Close(Some(close))).await;
+            return;
+        }
+    };
+    tracing::info!(resumed = resumed_from.is_some(), "client joined");
+
+    let session = RoomMessage::Session {
+        client_id: client_id.clone(),
+        resume_token: room.issue_resume_token(&client_id, role).await,
+        seq: room.last_seq(),
+        resumed: resumed_from.is_some(),
+    };
+    if let Ok(msg) = serde_json::to_string(&session) {
+        let _ = socket.send(Message::Text(msg)).await;
+    }
+
+    // Messages up to here are sent from history, so the live copies are skipped
+    let mut last_sent = 0;
+    let missed = match resumed_from {
+        Some(seq) => room.messages_since(seq).await,
+        None => None,
+    };
+    if let (Some(seq), Some(missed)) = (resumed_from, missed) {
+        tracing::debug!(missed = missed.len(), "backfilling missed messages");
+        last_sent = seq;
+        for message in missed {
+            last_sent = message.seq;
+            if !message.to.includes(role) {
+                continue;
+            }
+            if let Ok(msg) = serde_json::to_string(&message) {
+                let _ = socket.send(Message::Text(msg)).await;
+            }
+        }
+    } else {
+        // New clients, and those away too long to backfill, get the current state
+        let state = room.state.read().await.clone();
+        if !state.is_null() {
+            let state_message = RoomMessage::State {
+                data: state,
+                timestamp: Utc::now(),
+            };
+
+            if let Ok(msg) = serde_json::to_string(&state_message) {
+                let _ = socket.send(Message::Text(msg)).await;
+            }
         }
     }
 
@@ -370,24 +1686,68 @@ pub async fn handle_websocket_connection(
             ws_msg = socket.recv() => {
                 match ws_msg {
                     Some(Ok(Message::Text(text))) => {
-                        if let Ok(room_message) = serde_json::from_str::<RoomMessage>(&text) {
-                            match room_message {
-                                RoomMessage::Event { event, .. } => {
+                        let room_message = match parse_client_message(&text) {
+                            Ok(room_message) => room_message,
+                            Err(rejection) => {
+                                reject(&mut socket, &room, rejection).await;
+                                continue;
+                            }
+                        };
+                        tracing::trace!(message_type = room_message.kind(), "received");
+                        match room_message {
+                            RoomMessage::Event { event, .. } => {
+                                if role == ClientRole::Presenter || !is_presenter_event(&event.name) {
+                                    tracing::debug!(message_type = "event", event = %event.name, "relaying event");
                                     room.handle_event(event).await;
+                                } else {
+                                    let rejection = Rejection::new(codes::PRESENTER_ONLY, format!("Only presenters may send `{}`", event.name));
+                                    reject(&mut socket, &room, rejection).await;
+                                }
+                            }
+                            RoomMessage::Join { role: requested, token, .. } => {
+                                if !may_take_role(requested, token.as_deref(), presenter_token.as_deref()) {
+                                    let rejection = Rejection::new(codes::PRESENTER_TOKEN, "Presenting needs the presenter token");
+                                    reject(&mut socket, &room, rejection).await;
+                                } else if requested != role {
+                                    role = requested;
+                                    tracing::Span::current().record("role", tracing::field::debug(role));
+                                    tracing::info!(role = ?role, "client changed role");
+                                    // Everyone, this client included, hears about it
+                                    room.set_role(&client_id, role).await;
+                                }
+                            }
+                            RoomMessage::Vote { poll_id, option } => {
+                                if let Err(e) = room.vote(&client_id, &poll_id, option).await {
+                                    reject(&mut socket, &room, Rejection::new(codes::VOTE_REFUSED, e.to_string())).await;
+                                }
+                            }
+                            RoomMessage::Ask { text } => {
+                                if let Err(e) = room.ask(&client_id, &text).await {
+                                    reject(&mut socket, &room, Rejection::new(codes::QUESTION_REFUSED, e.to_string())).await;
                                 }
-                                RoomMessage::Heartbeat => {
-                                    // Respond with heartbeat
-                                    let heartbeat = RoomMessage::Heartbeat;
-                                    if let Ok(msg) = serde_json::to_string(&heartbeat) {
-                                        let _ = socket.send(Message::Text(msg)).await;
-                                    }
+                            }
+                            RoomMessage::Upvote { question_id } => {
+                                if let Err(e) = room.upvote(&client_id, &question_id).await {
+                                    reject(&mut socket, &room, Rejection::new(codes::QUESTION_REFUSED, e.to_string())).await;
                                 }
-                                _ => {}
                             }
+                            // Known reactions are counted by `react`; only unknown ones need an answer
+                            RoomMessage::React { reaction } if !room.react(&client_id, &reaction) => {
+                                let rejection = Rejection::new(codes::UNKNOWN_REACTION, format!("Unknown reaction `{}`", reaction));
+                                reject(&mut socket, &room, rejection).await;
+                            }
+                            RoomMessage::Heartbeat => {
+                                // Respond with heartbeat
+                                let heartbeat = RoomMessage::Heartbeat;
+                                if let Ok(msg) = serde_json::to_string(&heartbeat) {
+                                    let _ = socket.send(Message::Text(msg)).await;
+                                }
+                            }
+                            _ => {}
                         }
                     }
                     Some(Ok(Message::Binary(_))) => {
-                        // Ignore binary messages for now
+                        reject(&mut socket, &room, Rejection::new(codes::NOT_JSON, "Binary messages are not supported; send JSON text")).await;
                     }
                     Some(Ok(Message::Ping(data))) => {
                         let _ = socket.send(Message::Pong(data)).await;
@@ -405,12 +1765,30 @@ pub async fn handle_websocket_connection(
             broadcast_msg = receiver.recv() => {
                 match broadcast_msg {
                     Ok(msg) => {
+                        // Already sent in the backfill, or not for this client's role
+                        if msg.seq <= last_sent || !msg.to.includes(role) {
+                            continue;
+                        }
                         if let Ok(json) = serde_json::to_string(&msg) {
-                            if socket.send(Message::Text(json)).await.is_err() {
-                                break;
+                            // A device that stops reading would otherwise hold its
+                            // place in the channel, and its task, forever
+                            match tokio::time::timeout(room.limits.send_timeout(), socket.send(Message::Text(json))).await {
+                                Ok(Ok(())) => {}
+                                Ok(Err(_)) => break,
+                                Err(_) => {
+                                    tracing::warn!(timeout_ms = room.limits.send_timeout_ms, "client stopped reading; disconnecting");
+                                    break;
+                                }
                             }
                         }
                     }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!(skipped, "client fell behind; disconnecting");
+                        // Reconnecting sends the room's current state, which catches it up
+                        let close = CloseFrame { code: close_code::AGAIN, reason: "Fell behind the room; reconnect".into() };
+                        let _ = tokio::time::timeout(room.limits.send_timeout(), socket.send(Message::Close(Some(close)))).await;
+                        break;
+                    }
                     Err(_) => break,
                 }
             }
@@ -419,4 +1797,247 @@ pub async fn handle_websocket_connection(
 
     // Clean up client
     room.remove_client(&client_id).await;
-}
\ No newline at end of file
+    tracing::info!("client left");
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_join_asks_for_a_role_and_never_echoes_the_token() {
+        let join: RoomMessage = serde_json::from_str(r#"{"type":"join","role":"presenter","token":"s3cret"}"#).unwrap();
+        let RoomMessage::Join { role, client_id, token } = &join else {
+            panic!("not a join: {:?}", join);
+        };
+        assert_eq!((*role, client_id.as_str(), token.as_deref()), (ClientRole::Presenter, "", Some("s3cret")));
+        assert!(!serde_json::to_string(&join).unwrap().contains("s3cret"));
+
+        assert!(may_take_role(ClientRole::Presenter, Some("s3cret"), Some("s3cret")));
+        assert!(!may_take_role(ClientRole::Presenter, Some("guess"), Some("s3cret")));
+        assert!(!may_take_role(ClientRole::Presenter, None, Some("s3cret")));
+        assert!(may_take_role(ClientRole::Presenter, None, None));
+        assert!(may_take_role(ClientRole::Audience, None, Some("s3cret")));
+    }
+
+    #[tokio::test]
+    async fn test_role_changes_are_announced() {
+        let room = Room::new("main".to_string());
+        let mut receiver = room.add_client("c1".to_string(), ClientRole::Audience).await.unwrap();
+        assert!(matches!(receiver.recv().await.unwrap().message, RoomMessage::Join { role: ClientRole::Audience, .. }));
+
+        room.set_role("c1", ClientRole::Presenter).await;
+        assert_eq!(room.clients.read().await["c1"].role, ClientRole::Presenter);
+        let RoomMessage::Join { role, client_id, .. } = receiver.recv().await.unwrap().message else {
+            panic!("expected a join");
+        };
+        assert_eq!((role, client_id.as_str()), (ClientRole::Presenter, "c1"));
+    }
+
+    #[tokio::test]
+    async fn test_polls_tally_votes_and_record_results() {
+        let room = Room::new("main".to_string());
+        room.start_recording().await;
+        let mut receiver = room.broadcast_tx.subscribe();
+        let poll = NewPoll { question: "Tabs or spaces?".to_string(), options: vec!["Tabs".to_string(), " Spaces ".to_string()] };
+        let created = room.create_poll(poll).await.unwrap();
+        assert_eq!((created.options[1].as_str(), created.counts.as_slice()), ("Spaces", [0, 0].as_slice()));
+
+        room.vote("c1", &created.poll_id, 1).await.unwrap();
+        room.vote("c2", &created.poll_id, 0).await.unwrap();
+        let changed = room.vote("c1", &created.poll_id, 0).await.unwrap();
+        assert_eq!((changed.counts.as_slice(), changed.total_votes), ([2, 0].as_slice(), 2));
+        assert_eq!(room.vote("c3", &created.poll_id, 2).await, Err(PollError::NoSuchOption));
+        assert_eq!(room.vote("c3", "nope", 0).await, Err(PollError::NotFound));
+
+        let closed = room.close_poll(&created.poll_id).await.unwrap();
+        assert!(!closed.open);
+        assert_eq!(room.vote("c3", &created.poll_id, 0).await, Err(PollError::Closed));
+        assert_eq!(room.state.read().await["poll"]["counts"], serde_json::json!([2, 0]));
+
+        let names: Vec<String> = room
+            .get_recorded_messages()
+            .await
+            .into_iter()
+            .filter_map(|recorded| match recorded.message {
+                RoomMessage::Event { event, .. } => Some(event.name),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(names, ["poll:start", "poll:results", "poll:results", "poll:results", "poll:stop"]);
+        let RoomMessage::Event { event, .. } = receiver.recv().await.unwrap().message else {
+            panic!("expected an event");
+        };
+        assert_eq!(event.name, "poll:start");
+
+        let blank = NewPoll { question: "Pick one".to_string(), options: vec!["Only".to_string()] };
+        assert!(matches!(room.create_poll(blank).await, Err(PollError::Invalid(_))));
+    }
+
+    #[tokio::test]
+    async fn test_questions_wait_for_approval_and_rank_by_upvotes() {
+        let room = Room::new("main".to_string());
+        let first = room.ask("c1", "  What about Windows?  ").await.unwrap();
+        let second = room.ask("c2", "Is it open source?").await.unwrap();
+        assert_eq!((first.text.as_str(), first.status), ("What about Windows?", QuestionStatus::Pending));
+        assert!(room.list_questions(false).await.is_empty());
+        assert_eq!(room.state.read().await["questions"]["pending"], 2);
+        assert_eq!(room.upvote("c3", &first.id).await, Err(QuestionError::NotFound));
+
+        room.moderate_question(&first.id, QuestionStatus::Approved).await.unwrap();
+        room.moderate_question(&second.id, QuestionStatus::Approved).await.unwrap();
+        room.upvote("c1", &second.id).await.unwrap();
+        let upvoted = room.upvote("c1", &second.id).await.unwrap();
+        assert_eq!(upvoted.upvotes, 1);
+        let board: Vec<String> = room.list_questions(false).await.into_iter().map(|question| question.id).collect();
+        assert_eq!(board, [second.id.clone(), first.id.clone()]);
+
+        room.moderate_question(&second.id, QuestionStatus::Dismissed).await.unwrap();
+        assert_eq!(room.list_questions(false).await.len(), 1);
+        assert_eq!(room.list_questions(true).await.len(), 2);
+        assert_eq!(room.state.read().await["questions"]["questions"][0]["id"], first.id.as_str());
+        assert!(!serde_json::to_string(&upvoted).unwrap().contains("voters"));
+
+        assert!(matches!(room.ask("c1", " ").await, Err(QuestionError::Invalid(_))));
+        assert!(matches!(room.ask("c1", &"?".repeat(MAX_QUESTION_CHARS + 1)).await, Err(QuestionError::Invalid(_))));
+    }
+
+    #[tokio::test]
+    async fn test_reactions_are_broadcast_as_counts_per_window() {
+        let room = Room::new("main".to_string());
+        let mut receiver = room.broadcast_tx.subscribe();
+        for _ in 0..(MAX_REACTIONS_PER_CLIENT + 5) {
+            assert!(room.react("c1", "clap"));
+        }
+        assert!(room.react("c2", "heart"));
+        assert!(room.react("c3", "clap"));
+        assert!(!room.react("c3", "<script>"));
+
+        let message = tokio::time::timeout(REACTION_WINDOW * 3, receiver.recv()).await.unwrap().unwrap().message;
+        let RoomMessage::Event { event, .. } = message else {
+            panic!("expected an event");
+        };
+        assert_eq!(event.name, "reactions");
+        assert_eq!(event.data["counts"], serde_json::json!({ "clap": MAX_REACTIONS_PER_CLIENT + 1, "heart": 1 }));
+        assert_eq!(event.data["clients"], 3);
+        assert!(receiver.try_recv().is_err());
+
+        // A new window starts with the next reaction
+        assert!(room.react("c1", "fire"));
+        let message = tokio::time::timeout(REACTION_WINDOW * 3, receiver.recv()).await.unwrap().unwrap().message;
+        assert!(matches!(message, RoomMessage::Event { event, .. } if event.data["counts"] == serde_json::json!({ "fire": 1 })));
+    }
+
+    #[tokio::test]
+    async fn test_full_rooms_turn_audience_away_but_not_presenters() {
+        let limits = RoomLimits { max_clients: Some(2), ..RoomLimits::default() };
+        let room = Room::with_limits("main".to_string(), limits);
+        room.add_client("a1".to_string(), ClientRole::Audience).await.unwrap();
+        room.add_client("a2".to_string(), ClientRole::Audience).await.unwrap();
+        let full = room.add_client("a3".to_string(), ClientRole::Audience).await.unwrap_err();
+        assert_eq!(full.to_string(), "Room is full (2 clients); try again later");
+        assert!(room.add_client("p1".to_string(), ClientRole::Presenter).await.is_ok());
+        assert_eq!(room.clients.read().await.len(), 3);
+
+        room.remove_client("a1").await;
+        room.remove_client("a2").await;
+        assert!(room.add_client("a3".to_string(), ClientRole::Audience).await.is_ok());
+
+        let manager = RoomManager::new().with_limits(limits);
+        manager.ensure_room("__reload".to_string()).await;
+        assert_eq!(manager.get_room("__reload").await.unwrap().limits.max_clients, None);
+        assert!(RoomLimits { buffer: 0, ..limits }.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_resume_tokens_restore_the_client_and_backfill_from_history() {
+        let room = Room::new("main".to_string());
+        room.add_client("c1".to_string(), ClientRole::Presenter).await.unwrap();
+        let token = room.issue_resume_token("c1", ClientRole::Presenter).await;
+        let seen = room.last_seq();
+        room.remove_client("c1").await;
+        for slide in ["b", "c"] {
+            let data = serde_json::json!({ "slideId": slide });
+            room.handle_event(EventData { name: "slide:change".into(), data, client_id: "p".into() }).await;
+        }
+
+        let missed = room.messages_since(seen).await.unwrap();
+        assert_eq!(missed.iter().map(|message| message.seq).collect::<Vec<_>>(), [seen + 1, seen + 2]);
+        let wire = serde_json::to_value(&missed[1]).unwrap();
+        assert_eq!((wire["seq"].as_u64(), wire["type"].as_str()), (Some(seen + 2), Some("event")));
+        assert!(room.messages_since(room.last_seq() + 5).await.is_none());
+
+        assert_eq!(room.redeem_resume_token(&token).await, Some(("c1".to_string(), ClientRole::Presenter)));
+        assert_eq!(room.redeem_resume_token(&token).await, None);
+
+        // History trimmed past the client's last message cannot fill the gap
+        for _ in 0..HISTORY_LIMIT {
+            room.broadcast_message(RoomMessage::Heartbeat).await;
+        }
+        assert!(room.messages_since(seen).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_presenter_messages_are_marked_for_presenters_only() {
+        let room = Room::new("main".to_string());
+        let mut receiver = room.broadcast_tx.subscribe();
+        let notes = EventData { name: "presenter:notes".into(), data: serde_json::json!({ "slideId": "a" }), client_id: "p".into() };
+        room.handle_event(notes).await;
+        assert_eq!(receiver.recv().await.unwrap().to, Recipients::Presenters);
+
+        room.ask("c1", "Slides online?").await.unwrap();
+        let mut sent = Vec::new();
+        while let Ok(message) = receiver.try_recv() {
+            if let RoomMessage::Event { event, .. } = &message.message {
+                sent.push((event.name.clone(), message.to));
+            }
+        }
+        assert_eq!(sent, [("qa:questions".to_string(), Recipients::Everyone), ("qa:queue".to_string(), Recipients::Presenters)]);
+        assert!(!Recipients::Presenters.includes(ClientRole::Audience));
+
+        // Backfills carry the marks too, so resuming does not leak them
+        let history = room.messages_since(0).await.unwrap();
+        assert!(history.iter().any(|message| message.to == Recipients::Presenters));
+    }
+
+    #[test]
+    fn test_bad_messages_are_rejected_with_a_code() {
+        let code = |text: &str| parse_client_message(text).unwrap_err().code;
+        assert_eq!(code("{\"type\":"), codes::NOT_JSON);
+        assert_eq!(code("{\"role\":\"presenter\"}"), codes::UNKNOWN_TYPE);
+        assert_eq!(code("{\"type\":\"shout\"}"), codes::UNKNOWN_TYPE);
+        assert_eq!(code("{\"type\":\"session\",\"client_id\":\"c\",\"resume_token\":\"t\",\"seq\":1,\"resumed\":false}"), codes::SERVER_ONLY);
+
+        let invalid = parse_client_message("{\"type\":\"vote\",\"option\":1}").unwrap_err();
+        assert_eq!(invalid.code, codes::INVALID_FIELDS);
+        assert!(invalid.message.contains("poll_id"), "{}", invalid.message);
+        assert!(matches!(parse_client_message("{\"type\":\"heartbeat\"}"), Ok(RoomMessage::Heartbeat)));
+
+        let RoomMessage::Event { event, .. } = invalid.event() else {
+            panic!("expected an error event");
+        };
+        assert_eq!((event.name.as_str(), event.data["code"].as_str()), ("error", Some(codes::INVALID_FIELDS)));
+
+        let room = Room::new("main".to_string());
+        room.count_rejection(codes::NOT_JSON);
+        room.count_rejection(codes::NOT_JSON);
+        assert_eq!(room.rejections()[codes::NOT_JSON], 2);
+
+        // `coolslides explain` documents every code a room sends
+        let all = [
+            codes::NOT_JSON,
+            codes::UNKNOWN_TYPE,
+            codes::INVALID_FIELDS,
+            codes::SERVER_ONLY,
+            codes::PRESENTER_ONLY,
+            codes::PRESENTER_TOKEN,
+            codes::ROOM_FULL,
+            codes::ROOM_NOT_FOUND,
+            codes::VOTE_REFUSED,
+            codes::QUESTION_REFUSED,
+            codes::UNKNOWN_REACTION,
+        ];
+        for code in all {
+            assert!(coolslides_core::diagnostics::explain(code).is_some(), "{} is not in the diagnostics catalog", code);
+        }
+    }
+}
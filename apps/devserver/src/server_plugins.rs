@@ -0,0 +1,333 @@
+/*!
+ * Server plugins: hooks into request handling, room messages, and exports
+ *
+ * A plugin is anything implementing [`ServerPlugin`]. Embedders can register
+ * Rust implementations directly; decks list WASM modules under `[[serverPlugins]]`,
+ * which run sandboxed in an interpreter with a fuel budget per call.
+ *
+ * WASM modules talk JSON through their linear memory. A module exports `memory`
+ * and `alloc(len: i32) -> i32`; the host writes each hook's input at the returned
+ * pointer and calls the hook with `(ptr, len)`. A hook returns `0` to leave things
+ * as they are, or `(ptr << 32) | len` of its JSON answer. All hooks, and an
+ * `init(ptr, len)` that receives the deck's `config` table, are optional. Modules
+ * may import `coolslides.log(ptr, len)` to print a UTF-8 message.
+ */
+
+use crate::rooms::EventData;
+use coolslides_core::ServerPluginConfig;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex, RwLock};
+use wasmi::{Caller, Config, Engine, Extern, Instance, Linker, Memory, Module, Store, TypedFunc};
+
+/// Instructions a WASM hook may execute per call before it is stopped
+const FUEL_PER_CALL: u64 = 50_000_000;
+
+/// What a plugin sees of an incoming request
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginRequest {
+    pub method: String,
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    /// Header names are lowercase; values that are not UTF-8 are left out
+    pub headers: BTreeMap<String, String>,
+}
+
+/// A response a plugin sends instead of the server's
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginResponse {
+    pub status: u16,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default)]
+    pub body: String,
+}
+
+/// Hooks a server plugin can implement; each defaults to leaving things unchanged
+pub trait ServerPlugin: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Answer a request before the router sees it; `None` lets it through
+    fn on_request(&self, _request: &PluginRequest) -> Option<PluginResponse> {
+        None
+    }
+
+    /// Inspect or rewrite an event sent to a room; `None` drops it
+    fn on_room_message(&self, _room_id: &str, event: EventData) -> Option<EventData> {
+        Some(event)
+    }
+
+    /// Post-process exported HTML (`format` is `html` or `pdf`)
+    fn on_export(&self, _format: &str, html: String) -> String {
+        html
+    }
+}
+
+/// The plugins of the running server, in the order hooks run: registered
+/// plugins first, then the deck's WASM modules
+#[derive(Default)]
+pub struct PluginHost {
+    registered: RwLock<Vec<Arc<dyn ServerPlugin>>>,
+    deck: RwLock<Vec<Arc<dyn ServerPlugin>>>,
+}
+
+impl std::fmt::Debug for PluginHost {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.plugins().iter().map(|plugin| plugin.name().to_string())).finish()
+    }
+}
+
+impl PluginHost {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Host with the deck's WASM plugins loaded, for one-off exports
+    pub fn for_deck(deck_dir: &Path, configs: &[ServerPluginConfig]) -> Self {
+        let host = Self::new();
+        host.load_deck_plugins(deck_dir, configs);
+        host
+    }
+
+    /// Add a plugin that stays loaded across deck reloads
+    pub fn register(&self, plugin: Arc<dyn ServerPlugin>) {
+        self.registered.write().unwrap().push(plugin);
+    }
+
+    /// Replace the deck's WASM plugins; modules that fail to load are reported and skipped
+    pub fn load_deck_plugins(&self, deck_dir: &Path, configs: &[ServerPluginConfig]) {
+        let mut loaded: Vec<Arc<dyn ServerPlugin>> = Vec::new();
+        for config in configs {
+            match WasmPlugin::load(&deck_dir.join(&config.path), &config.config) {
+                Ok(plugin) => {
+                    tracing::info!(plugin = plugin.name(), "loaded server plugin");
+                    loaded.push(Arc::new(plugin));
+                }
+                Err(e) => tracing::warn!(path = %config.path, error = %e, "server plugin not loaded"),
+            }
+        }
+        *self.deck.write().unwrap() = loaded;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.registered.read().unwrap().is_empty() && self.deck.read().unwrap().is_empty()
+    }
+
+    fn plugins(&self) -> Vec<Arc<dyn ServerPlugin>> {
+        let registered = self.registered.read().unwrap();
+        let deck = self.deck.read().unwrap();
+        registered.iter().chain(deck.iter()).cloned().collect()
+    }
+
+    /// The first plugin response for the request, if any plugin answers it
+    pub fn on_request(&self, request: &PluginRequest) -> Option<PluginResponse> {
+        self.plugins().iter().find_map(|plugin| plugin.on_request(request))
+    }
+
+    /// The event after every plugin has seen it; `None` once one drops it
+    pub fn on_room_message(&self, room_id: &str, event: EventData) -> Option<EventData> {
+        self.plugins().iter().try_fold(event, |event, plugin| plugin.on_room_message(room_id, event))
+    }
+
+    pub fn on_export(&self, format: &str, html: String) -> String {
+        self.plugins().iter().fold(html, |html, plugin| plugin.on_export(format, html))
+    }
+}
+
+/// A plugin instantiated from a WASM module
+pub struct WasmPlugin {
+    name: String,
+    instance: Mutex<WasmInstance>,
+}
+
+struct WasmInstance {
+    store: Store<String>,
+    instance: Instance,
+    memory: Memory,
+    alloc: TypedFunc<i32, i32>,
+}
+
+#[derive(Deserialize)]
+struct ExportOutput {
+    html: String,
+}
+
+impl WasmPlugin {
+    pub fn load(path: &Path, config: &serde_json::Value) -> anyhow::Result<Self> {
+        let name = path.file_stem().and_then(|s| s.to_str()).unwrap_or("plugin").to_string();
+        Self::from_bytes(&name, &std::fs::read(path)?, config)
+    }
+
+    pub fn from_bytes(name: &str, wasm: &[u8], config: &serde_json::Value) -> anyhow::Result<Self> {
+        let mut engine_config = Config::default();
+        engine_config.consume_fuel(true);
+        let engine = Engine::new(&engine_config);
+        let module = Module::new(&engine, wasm)?;
+        let mut store = Store::new(&engine, name.to_string());
+        store.set_fuel(FUEL_PER_CALL).map_err(|e| anyhow::anyhow!("{}", e))?;
+
+        let mut linker = Linker::<String>::new(&engine);
+        linker.func_wrap("coolslides", "log", |caller: Caller<'_, String>, ptr: i32, len: i32| {
+            let Some(Extern::Memory(memory)) = caller.get_export("memory") else {
+                return;
+            };
+            let mut buffer = vec![0; len.max(0) as usize];
+            if memory.read(&caller, ptr as usize, &mut buffer).is_ok() {
+                tracing::info!(plugin = %caller.data(), "{}", String::from_utf8_lossy(&buffer));
+            }
+        })?;
+        let instance = linker.instantiate(&mut store, &module)?.start(&mut store)?;
+        let memory = instance
+            .get_memory(&store, "memory")
+            .ok_or_else(|| anyhow::anyhow!("module does not export `memory`"))?;
+        let alloc = instance.get_typed_func::<i32, i32>(&store, "alloc")?;
+
+        let plugin = Self { name: name.to_string(), instance: Mutex::new(WasmInstance { store, instance, memory, alloc }) };
+        if !config.is_null() {
+            plugin.call("init", &serde_json::to_vec(config)?)?;
+        }
+        Ok(plugin)
+    }
+
+    /// Run `hook` on `input`, returning its JSON answer; `None` when the module
+    /// does not export the hook or returns `0`
+    fn call(&self, hook: &str, input: &[u8]) -> anyhow::Result<Option<Vec<u8>>> {
+        let mut guard = self.instance.lock().unwrap();
+        let WasmInstance { store, instance, memory, alloc } = &mut *guard;
+        let answers = instance.get_typed_func::<(i32, i32), i64>(&*store, hook).ok();
+        // `init` has no answer
+        let notified = instance.get_typed_func::<(i32, i32), ()>(&*store, hook).ok();
+        if answers.is_none() && notified.is_none() {
+            return Ok(None);
+        }
+
+        store.set_fuel(FUEL_PER_CALL).map_err(|e| anyhow::anyhow!("{}", e))?;
+        let len = i32::try_from(input.len())?;
+        let ptr = alloc.call(&mut *store, len)?;
+        memory.write(&mut *store, ptr as usize, input).map_err(|e| anyhow::anyhow!("{}", e))?;
+        let Some(function) = answers else {
+            notified.expect("checked above").call(&mut *store, (ptr, len))?;
+            return Ok(None);
+        };
+        let packed = function.call(&mut *store, (ptr, len))? as u64;
+        if packed == 0 {
+            return Ok(None);
+        }
+        let mut output = vec![0; (packed & 0xffff_ffff) as usize];
+        memory.read(&*store, (packed >> 32) as usize, &mut output).map_err(|e| anyhow::anyhow!("{}", e))?;
+        Ok(Some(output))
+    }
+
+    /// Call a hook and parse its answer, reporting failures
+    fn hook<T: serde::de::DeserializeOwned>(&self, hook: &str, input: &impl Serialize) -> Result<Option<T>, ()> {
+        let result = serde_json::to_vec(input)
+            .map_err(anyhow::Error::from)
+            .and_then(|input| self.call(hook, &input))
+            .and_then(|output| output.map(|bytes| serde_json::from_slice(&bytes)).transpose().map_err(Into::into));
+        result.map_err(|e| tracing::warn!(plugin = %self.name, hook, error = %e, "server plugin failed"))
+    }
+}
+
+impl ServerPlugin for WasmPlugin {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn on_request(&self, request: &PluginRequest) -> Option<PluginResponse> {
+        // A failing plugin may be the one guarding access, so its requests fail closed
+        self.hook("on_request", request).unwrap_or_else(|()| {
+            Some(PluginResponse { status: 500, headers: BTreeMap::new(), body: "Server plugin failed".to_string() })
+        })
+    }
+
+    fn on_room_message(&self, room_id: &str, event: EventData) -> Option<EventData> {
+        let input = serde_json::json!({ "roomId": room_id, "event": &event });
+        match self.hook::<Option<EventData>>("on_room_message", &input) {
+            // `null` drops the event
+            Ok(Some(rewritten)) => rewritten,
+            Ok(None) | Err(()) => Some(event),
+        }
+    }
+
+    fn on_export(&self, format: &str, html: String) -> String {
+        let input = serde_json::json!({ "format": format, "html": &html });
+        match self.hook::<ExportOutput>("on_export", &input) {
+            Ok(Some(output)) => output.html,
+            Ok(None) | Err(()) => html,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Denies requests to /private, drops `secret` room events, and appends to exports
+    const TEST_PLUGIN: &str = r#"
+(module
+  (import "coolslides" "log" (func $log (param i32 i32)))
+  (memory (export "memory") 1)
+  (global $next (mut i32) (i32.const 4096))
+  (data (i32.const 1024) "{\"status\":403,\"body\":\"denied\"}")
+  (data (i32.const 1100) "null")
+  (data (i32.const 1200) "{\"html\":\"<p>stamped</p>\"}")
+  (data (i32.const 1300) "/private")
+  (func (export "alloc") (param $len i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr (global.get $next))
+    (global.set $next (i32.add (global.get $next) (local.get $len)))
+    (local.get $ptr))
+  (func (export "init") (param i32 i32)
+    (call $log (local.get 0) (local.get 1)))
+  ;; Input starts {"method":"GET","path":"/private -- the path begins at byte 24
+  (func (export "on_request") (param $ptr i32) (param $len i32) (result i64)
+    (if (result i64)
+      (i64.eq (i64.load (i32.add (local.get $ptr) (i32.const 24))) (i64.load (i32.const 1300)))
+      (then (i64.or (i64.shl (i64.const 1024) (i64.const 32)) (i64.const 30)))
+      (else (i64.const 0))))
+  (func (export "on_room_message") (param i32 i32) (result i64)
+    (i64.or (i64.shl (i64.const 1100) (i64.const 32)) (i64.const 4)))
+  (func (export "on_export") (param i32 i32) (result i64)
+    (i64.or (i64.shl (i64.const 1200) (i64.const 32)) (i64.const 25))))
+"#;
+
+    const SPIN_PLUGIN: &str = r#"
+(module
+  (memory (export "memory") 1)
+  (func (export "alloc") (param i32) (result i32) (i32.const 0))
+  (func (export "on_export") (param i32 i32) (result i64)
+    (loop $forever (br $forever))
+    (i64.const 0)))
+"#;
+
+    fn request(path: &str) -> PluginRequest {
+        PluginRequest { method: "GET".into(), path: path.into(), query: None, headers: BTreeMap::new() }
+    }
+
+    #[test]
+    fn test_wasm_plugin_hooks() {
+        let wasm = wat::parse_str(TEST_PLUGIN).unwrap();
+        let plugin = WasmPlugin::from_bytes("guard", &wasm, &serde_json::json!({ "token": "t" })).unwrap();
+        let host = PluginHost::new();
+        host.register(Arc::new(plugin));
+
+        let denied = host.on_request(&request("/private/x")).unwrap();
+        assert_eq!((denied.status, denied.body.as_str()), (403, "denied"));
+        assert!(host.on_request(&request("/api/deck")).is_none());
+
+        let event = EventData { name: "secret".into(), data: serde_json::json!({}), client_id: "c".into() };
+        assert!(host.on_room_message("main", event).is_none());
+        assert_eq!(host.on_export("html", "<p>deck</p>".into()), "<p>stamped</p>");
+    }
+
+    #[test]
+    fn test_runaway_plugin_is_stopped() {
+        let wasm = wat::parse_str(SPIN_PLUGIN).unwrap();
+        let plugin = WasmPlugin::from_bytes("spin", &wasm, &serde_json::Value::Null).unwrap();
+        assert_eq!(plugin.on_export("html", "<p>deck</p>".into()), "<p>deck</p>");
+    }
+}
@@ -0,0 +1,110 @@
+/*!
+ * Server status for editors and the presenter console, served on `/api/events`
+ *
+ * A Server-Sent Events stream of what the server is busy with: file-watcher
+ * reloads, the validation result each reload produces, and exports. Each event
+ * is named after its `kind` and carries the same JSON as its data, so a page
+ * can `addEventListener('reload-completed', ...)` or read every message from
+ * `onmessage`. Tooling keeps a status bar current without polling
+ * `/api/validate` or `/api/exports`.
+ */
+
+use crate::AppState;
+use axum::{
+    extract::State,
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures_util::stream::Stream;
+use serde::Serialize;
+use std::{convert::Infallible, time::Duration};
+use tokio::sync::broadcast;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case", rename_all_fields = "camelCase")]
+pub enum StatusEvent {
+    /// Deck or component files changed on disk and are being reloaded
+    ReloadStarted { files: usize },
+    /// The reload finished; `changes` summarises what it picked up
+    ReloadCompleted { changes: String, duration_ms: u64 },
+    /// The files could not be loaded; the previous deck stays in place
+    ReloadFailed { error: String, duration_ms: u64 },
+    /// The deck was validated, in the shape `POST /api/validate` returns
+    Validation { valid: bool, errors: Vec<serde_json::Value>, warnings: Vec<serde_json::Value> },
+    /// An export started
+    ExportStarted { format: String },
+    /// An export finished with a file of `bytes` bytes
+    ExportCompleted { format: String, bytes: usize, duration_ms: u64 },
+    /// An export failed
+    ExportFailed { format: String, error: String, duration_ms: u64 },
+}
+
+impl StatusEvent {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::ReloadStarted { .. } => "reload-started",
+            Self::ReloadCompleted { .. } => "reload-completed",
+            Self::ReloadFailed { .. } => "reload-failed",
+            Self::Validation { .. } => "validation",
+            Self::ExportStarted { .. } => "export-started",
+            Self::ExportCompleted { .. } => "export-completed",
+            Self::ExportFailed { .. } => "export-failed",
+        }
+    }
+}
+
+pub type StatusSender = broadcast::Sender<StatusEvent>;
+
+pub fn channel() -> StatusSender {
+    broadcast::channel(64).0
+}
+
+/// Stream status events until the client disconnects
+pub(crate) async fn events(State(state): State<AppState>) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let stream = futures_util::stream::unfold(state.status.subscribe(), |mut events| async move {
+        loop {
+            match events.recv().await {
+                Ok(event) => {
+                    let sse = Event::default().event(event.name()).json_data(&event).unwrap_or_default();
+                    return Some((Ok(sse), events));
+                }
+                // A status bar only needs the latest state, so skip what was missed
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    });
+    Sse::new(stream).keep_alive(KeepAlive::new().interval(Duration::from_secs(15)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::Request, routing::get, Router};
+    use futures_util::StreamExt;
+    use tower::ServiceExt;
+
+    #[test]
+    fn test_events_are_tagged_with_camel_case_fields() {
+        let event = StatusEvent::ExportCompleted { format: "pdf".to_string(), bytes: 2048, duration_ms: 310 };
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::json!({ "kind": "export-completed", "format": "pdf", "bytes": 2048, "durationMs": 310 })
+        );
+        assert_eq!(event.name(), "export-completed");
+    }
+
+    #[tokio::test]
+    async fn test_stream_delivers_events_as_they_happen() {
+        let state = AppState::new();
+        let app = Router::new().route("/api/events", get(events)).with_state(state.clone());
+        let response = app.oneshot(Request::builder().uri("/api/events").body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(response.headers()["content-type"], "text/event-stream");
+
+        state.status.send(StatusEvent::ReloadStarted { files: 2 }).unwrap();
+        let mut body = response.into_body().into_data_stream();
+        let chunk = body.next().await.unwrap().unwrap();
+        let text = String::from_utf8(chunk.to_vec()).unwrap();
+        assert!(text.contains("event: reload-started\n"), "{}", text);
+        assert!(text.contains(r#"data: {"kind":"reload-started","files":2}"#), "{}", text);
+    }
+}
@@ -0,0 +1,291 @@
+/*!
+ * Optional SQLite storage, enabled with `--db <path>`
+ *
+ * Without a database, rooms, recordings, and export history live only in memory
+ * and analytics in `.coolslides/analytics.json`. With one, room state snapshots,
+ * message history, recordings, analytics aggregates, and export jobs are written
+ * through to SQLite and rooms are restored when the server starts again.
+ */
+
+use crate::analytics::AnalyticsSummary;
+use crate::rooms::{RecordedMessage, RoomMessage};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePool, SqlitePoolOptions};
+use sqlx::Row;
+use std::path::Path;
+
+/// Tables are created on open; `IF NOT EXISTS` keeps reopening an existing database cheap
+const SCHEMA: &[&str] = &[
+    "CREATE TABLE IF NOT EXISTS rooms (
+        id TEXT PRIMARY KEY,
+        state TEXT NOT NULL,
+        recording INTEGER NOT NULL,
+        created_at TEXT NOT NULL,
+        updated_at TEXT NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS recorded_messages (
+        room_id TEXT NOT NULL,
+        seq INTEGER NOT NULL,
+        message TEXT NOT NULL,
+        PRIMARY KEY (room_id, seq)
+    )",
+    "CREATE TABLE IF NOT EXISTS room_history (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        room_id TEXT NOT NULL,
+        message TEXT NOT NULL
+    )",
+    "CREATE INDEX IF NOT EXISTS room_history_room ON room_history (room_id, id)",
+    "CREATE TABLE IF NOT EXISTS analytics (
+        deck TEXT PRIMARY KEY,
+        summary TEXT NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS export_jobs (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        format TEXT NOT NULL,
+        status TEXT NOT NULL,
+        started_at TEXT NOT NULL,
+        duration_ms INTEGER NOT NULL,
+        bytes INTEGER,
+        error TEXT
+    )",
+];
+
+/// A room as stored: everything needed to bring it back after a restart
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoomSnapshot {
+    pub id: String,
+    pub state: serde_json::Value,
+    pub recording: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// One run of an export endpoint
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportJob {
+    pub id: i64,
+    pub format: String,
+    /// `completed` or `failed`
+    pub status: String,
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Storage {
+    pool: SqlitePool,
+}
+
+impl Storage {
+    /// Open the database at `path`, creating it and its tables if needed
+    pub async fn open(path: &Path) -> anyhow::Result<Self> {
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let options = SqliteConnectOptions::new()
+            .filename(path)
+            .create_if_missing(true)
+            .journal_mode(SqliteJournalMode::Wal);
+        let pool = SqlitePoolOptions::new().max_connections(4).connect_with(options).await?;
+        for statement in SCHEMA {
+            sqlx::query(statement).execute(&pool).await?;
+        }
+        Ok(Self { pool })
+    }
+
+    pub async fn save_room(&self, room: &RoomSnapshot) -> anyhow::Result<()> {
+        sqlx::query(
+            "INSERT INTO rooms (id, state, recording, created_at, updated_at) VALUES (?, ?, ?, ?, ?)
+             ON CONFLICT(id) DO UPDATE SET state = excluded.state, recording = excluded.recording, updated_at = excluded.updated_at",
+        )
+        .bind(&room.id)
+        .bind(serde_json::to_string(&room.state)?)
+        .bind(room.recording)
+        .bind(room.created_at.to_rfc3339())
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn load_rooms(&self) -> anyhow::Result<Vec<RoomSnapshot>> {
+        let rows = sqlx::query("SELECT id, state, recording, created_at FROM rooms ORDER BY created_at").fetch_all(&self.pool).await?;
+        rows.iter()
+            .map(|row| {
+                Ok(RoomSnapshot {
+                    id: row.try_get("id")?,
+                    state: serde_json::from_str(row.try_get("state")?)?,
+                    recording: row.try_get("recording")?,
+                    created_at: DateTime::parse_from_rfc3339(row.try_get("created_at")?)?.with_timezone(&Utc),
+                })
+            })
+            .collect()
+    }
+
+    /// Forget a room, its recording, and its history
+    pub async fn delete_room(&self, room_id: &str) -> anyhow::Result<()> {
+        let mut transaction = self.pool.begin().await?;
+        sqlx::query("DELETE FROM recorded_messages WHERE room_id = ?").bind(room_id).execute(&mut *transaction).await?;
+        sqlx::query("DELETE FROM room_history WHERE room_id = ?").bind(room_id).execute(&mut *transaction).await?;
+        sqlx::query("DELETE FROM rooms WHERE id = ?").bind(room_id).execute(&mut *transaction).await?;
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    /// Store the `seq`th message of a room's recording
+    pub async fn append_recorded(&self, room_id: &str, seq: usize, message: &RecordedMessage) -> anyhow::Result<()> {
+        sqlx::query("INSERT OR REPLACE INTO recorded_messages (room_id, seq, message) VALUES (?, ?, ?)")
+            .bind(room_id)
+            .bind(seq as i64)
+            .bind(serde_json::to_string(message)?)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn clear_recording(&self, room_id: &str) -> anyhow::Result<()> {
+        sqlx::query("DELETE FROM recorded_messages WHERE room_id = ?").bind(room_id).execute(&self.pool).await?;
+        Ok(())
+    }
+
+    pub async fn load_recording(&self, room_id: &str) -> anyhow::Result<Vec<RecordedMessage>> {
+        let rows = sqlx::query("SELECT message FROM recorded_messages WHERE room_id = ? ORDER BY seq")
+            .bind(room_id)
+            .fetch_all(&self.pool)
+            .await?;
+        rows.iter().map(|row| Ok(serde_json::from_str(row.try_get("message")?)?)).collect()
+    }
+
+    /// Add a message to a room's history, keeping only the latest `keep`
+    pub async fn append_history(&self, room_id: &str, message: &RoomMessage, keep: usize) -> anyhow::Result<()> {
+        let mut transaction = self.pool.begin().await?;
+        sqlx::query("INSERT INTO room_history (room_id, message) VALUES (?, ?)")
+            .bind(room_id)
+            .bind(serde_json::to_string(message)?)
+            .execute(&mut *transaction)
+            .await?;
+        sqlx::query(
+            "DELETE FROM room_history WHERE room_id = ? AND id NOT IN
+             (SELECT id FROM room_history WHERE room_id = ? ORDER BY id DESC LIMIT ?)",
+        )
+        .bind(room_id)
+        .bind(room_id)
+        .bind(keep as i64)
+        .execute(&mut *transaction)
+        .await?;
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    /// A room's history, oldest first
+    pub async fn load_history(&self, room_id: &str) -> anyhow::Result<Vec<RoomMessage>> {
+        let rows = sqlx::query("SELECT message FROM room_history WHERE room_id = ? ORDER BY id")
+            .bind(room_id)
+            .fetch_all(&self.pool)
+            .await?;
+        rows.iter().map(|row| Ok(serde_json::from_str(row.try_get("message")?)?)).collect()
+    }
+
+    /// The analytics aggregate of the deck at `deck`, if any was recorded
+    pub async fn load_analytics(&self, deck: &str) -> anyhow::Result<Option<AnalyticsSummary>> {
+        let row = sqlx::query("SELECT summary FROM analytics WHERE deck = ?").bind(deck).fetch_optional(&self.pool).await?;
+        row.map(|row| Ok(serde_json::from_str(row.try_get("summary")?)?)).transpose()
+    }
+
+    pub async fn save_analytics(&self, deck: &str, summary: &AnalyticsSummary) -> anyhow::Result<()> {
+        sqlx::query("INSERT OR REPLACE INTO analytics (deck, summary) VALUES (?, ?)")
+            .bind(deck)
+            .bind(serde_json::to_string(summary)?)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Record a finished export; `result` is the output size or the error
+    pub async fn record_export_job(&self, format: &str, started_at: DateTime<Utc>, result: Result<usize, String>) -> anyhow::Result<()> {
+        let duration_ms = (Utc::now() - started_at).num_milliseconds().max(0);
+        let (status, bytes, error) = match result {
+            Ok(bytes) => ("completed", Some(bytes as i64), None),
+            Err(error) => ("failed", None, Some(error)),
+        };
+        sqlx::query("INSERT INTO export_jobs (format, status, started_at, duration_ms, bytes, error) VALUES (?, ?, ?, ?, ?, ?)")
+            .bind(format)
+            .bind(status)
+            .bind(started_at.to_rfc3339())
+            .bind(duration_ms)
+            .bind(bytes)
+            .bind(error)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// The most recent export jobs, newest first
+    pub async fn export_jobs(&self, limit: u32) -> anyhow::Result<Vec<ExportJob>> {
+        let rows = sqlx::query("SELECT id, format, status, started_at, duration_ms, bytes, error FROM export_jobs ORDER BY id DESC LIMIT ?")
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await?;
+        rows.iter()
+            .map(|row| {
+                Ok(ExportJob {
+                    id: row.try_get("id")?,
+                    format: row.try_get("format")?,
+                    status: row.try_get("status")?,
+                    started_at: DateTime::parse_from_rfc3339(row.try_get("started_at")?)?.with_timezone(&Utc),
+                    duration_ms: row.try_get::<i64, _>("duration_ms")? as u64,
+                    bytes: row.try_get::<Option<i64>, _>("bytes")?.map(|bytes| bytes as u64),
+                    error: row.try_get("error")?,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_storage_round_trips_rooms_recordings_and_jobs() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("data/coolslides.db");
+        let storage = Storage::open(&path).await.unwrap();
+
+        let room = RoomSnapshot {
+            id: "main".into(),
+            state: serde_json::json!({ "currentSlide": { "slideId": "b" } }),
+            recording: true,
+            created_at: DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z").unwrap().with_timezone(&Utc),
+        };
+        storage.save_room(&room).await.unwrap();
+        let message = RecordedMessage { message: RoomMessage::Heartbeat, recorded_at: Utc::now(), session_time: 5 };
+        storage.append_recorded("main", 0, &message).await.unwrap();
+        for _ in 0..3 {
+            storage.append_history("main", &RoomMessage::Heartbeat, 2).await.unwrap();
+        }
+        storage.record_export_job("pdf", Utc::now(), Err("no browser".into())).await.unwrap();
+        storage.record_export_job("html", Utc::now(), Ok(1200)).await.unwrap();
+        drop(storage);
+
+        // Everything is still there after reopening
+        let storage = Storage::open(&path).await.unwrap();
+        assert_eq!(storage.load_rooms().await.unwrap(), vec![room]);
+        assert_eq!(storage.load_recording("main").await.unwrap()[0].session_time, 5);
+        assert_eq!(storage.load_history("main").await.unwrap().len(), 2);
+        let jobs = storage.export_jobs(10).await.unwrap();
+        assert_eq!((jobs[0].format.as_str(), jobs[0].bytes), ("html", Some(1200)));
+        assert_eq!(jobs[1].error.as_deref(), Some("no browser"));
+
+        storage.delete_room("main").await.unwrap();
+        assert!(storage.load_rooms().await.unwrap().is_empty());
+        assert!(storage.load_recording("main").await.unwrap().is_empty());
+        assert!(storage.load_history("main").await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,314 @@
+/*!
+ * Teleprompter: the deck's speaker notes as one continuously scrolling script
+ *
+ * Notes are resolved per slide in presentation order: the deck's `[notes]` entry
+ * (Markdown) first, then the slide's own `notes`. The page follows a room like an
+ * audience page does, scrolling to the current slide's notes when it changes.
+ * Within a slide, the script scrolls at the reader's speed but never runs past a
+ * note whose `timestamp` has not come yet, and catches up to one whose time has.
+ */
+
+use crate::{html_escape, render_markdown_to_html, SanitizationConfig};
+use coolslides_core::{DeckManifest, NoteType, SlideDoc};
+use serde_json::json;
+use std::collections::HashMap;
+
+const PAGE_STYLE: &str = r#"<style>
+  :root { --size: 44px; --line: 35vh; }
+  * { box-sizing: border-box; }
+  html, body { margin: 0; background: #000; color: #f5f5f5; }
+  body { font: 500 var(--size)/1.45 system-ui, sans-serif; }
+  body.mirror #script { transform: scaleX(-1); }
+  #script { max-width: 32em; margin: 0 auto; padding: var(--line) 1.2em 100vh; }
+  #reading-line { position: fixed; left: 0; right: 0; top: var(--line); border-top: 2px solid rgba(255, 196, 0, .55); pointer-events: none; z-index: 1; }
+  section { margin-bottom: 2.5em; opacity: .45; transition: opacity .3s; }
+  section.current { opacity: 1; }
+  section h2 { font-size: .5em; text-transform: uppercase; letter-spacing: .08em; color: #ffc400; margin: 0 0 .6em; }
+  .note { margin: 0 0 .9em; }
+  .note p { margin: 0 0 .6em; }
+  .note[data-at]::before { content: attr(data-label); display: block; font-size: .4em; color: #8ab4f8; }
+  .note.timing, .note.technical, .note.transition { font-size: .7em; color: #ccc; font-style: italic; }
+  .empty { font-size: .5em; color: #777; }
+  #bar { position: fixed; left: 0; right: 0; bottom: 0; display: flex; gap: 16px; align-items: center; padding: 8px 16px;
+         background: rgba(20, 20, 20, .92); font: 14px system-ui, sans-serif; color: #bbb; z-index: 2; }
+  #bar button { background: #333; color: #eee; border: 0; border-radius: 4px; padding: 4px 10px; font: inherit; cursor: pointer; }
+  #bar .spacer { flex: 1; }
+  body.idle #bar { opacity: 0; transition: opacity .5s; }
+</style>"#;
+
+const PAGE_SCRIPT: &str = r#"<script>
+(function () {
+  const params = new URLSearchParams(location.search);
+  const room = params.get('room') || 'default';
+  const script = document.getElementById('script');
+  const sections = Array.from(script.querySelectorAll('section'));
+  const stored = Number(localStorage.getItem('coolslides-teleprompter-speed'));
+  let speed = Number(params.get('speed')) || stored || 40;
+  let paused = false;
+  let current = null;
+  let shownAt = performance.now();
+  let position = 0;
+  let last = performance.now();
+
+  if (params.has('mirror')) document.body.classList.add('mirror');
+
+  const line = () => parseFloat(getComputedStyle(document.documentElement).getPropertyValue('--line')) / 100 * innerHeight;
+  const top = el => el.getBoundingClientRect().top + scrollY - line();
+
+  function show(slideId) {
+    const section = sections.find(s => s.dataset.slide === slideId);
+    if (!section || section === current) return;
+    if (current) current.classList.remove('current');
+    current = section;
+    current.classList.add('current');
+    shownAt = performance.now();
+    // The animation loop glides there
+    position = top(section);
+    document.getElementById('slide').textContent = (sections.indexOf(section) + 1) + ' / ' + sections.length;
+  }
+
+  // Keep between the last note that is due and the next one that is not
+  function bounds() {
+    if (!current) return [0, Infinity];
+    const elapsed = (performance.now() - shownAt) / 1000;
+    let lower = top(current);
+    let upper = Infinity;
+    const next = sections[sections.indexOf(current) + 1];
+    if (next) upper = top(next);
+    for (const note of current.querySelectorAll('.note[data-at]')) {
+      const at = Number(note.dataset.at);
+      if (at <= elapsed) lower = Math.max(lower, top(note));
+      else { upper = Math.min(upper, top(note)); break; }
+    }
+    return [lower, Math.max(lower, upper)];
+  }
+
+  function tick(now) {
+    const dt = Math.min((now - last) / 1000, 0.25);
+    last = now;
+    if (!paused && current) {
+      const [lower, upper] = bounds();
+      position = Math.min(Math.max(position + speed * dt, lower), upper);
+      // Catching up to a due note is quicker than reading speed
+      if (Math.abs(scrollY - position) > 1) scrollTo(0, scrollY + (position - scrollY) * Math.min(1, dt * 6));
+    }
+    const elapsed = current ? Math.floor((now - shownAt) / 1000) : 0;
+    document.getElementById('elapsed').textContent = Math.floor(elapsed / 60) + ':' + String(elapsed % 60).padStart(2, '0');
+    requestAnimationFrame(tick);
+  }
+
+  function setSpeed(value) {
+    speed = Math.max(0, Math.min(400, Math.round(value)));
+    localStorage.setItem('coolslides-teleprompter-speed', String(speed));
+    document.getElementById('speed').textContent = speed + ' px/s';
+  }
+  function setPaused(value) {
+    paused = value;
+    document.getElementById('pause').textContent = paused ? 'Resume' : 'Pause';
+    position = scrollY;
+  }
+  function setSize(delta) {
+    const size = parseFloat(getComputedStyle(document.documentElement).getPropertyValue('--size')) + delta;
+    document.documentElement.style.setProperty('--size', Math.max(16, Math.min(120, size)) + 'px');
+  }
+
+  document.getElementById('slower').onclick = () => setSpeed(speed - 10);
+  document.getElementById('faster').onclick = () => setSpeed(speed + 10);
+  document.getElementById('pause').onclick = () => setPaused(!paused);
+  document.getElementById('mirror').onclick = () => document.body.classList.toggle('mirror');
+  addEventListener('keydown', event => {
+    const actions = {
+      ' ': () => setPaused(!paused),
+      ArrowUp: () => setSpeed(speed + 10),
+      ArrowDown: () => setSpeed(speed - 10),
+      '+': () => setSize(4),
+      '=': () => setSize(4),
+      '-': () => setSize(-4),
+      m: () => document.body.classList.toggle('mirror'),
+    };
+    if (actions[event.key]) { event.preventDefault(); actions[event.key](); }
+  });
+  // Manual scrolling moves the script; it continues from there
+  addEventListener('wheel', () => { position = scrollY; }, { passive: true });
+
+  let idle;
+  addEventListener('pointermove', () => {
+    document.body.classList.remove('idle');
+    clearTimeout(idle);
+    idle = setTimeout(() => document.body.classList.add('idle'), 3000);
+  });
+
+  function connect() {
+    const url = new URL('rooms/' + encodeURIComponent(room), location.href);
+    url.protocol = location.protocol === 'https:' ? 'wss:' : 'ws:';
+    const ws = new WebSocket(url);
+    ws.onmessage = event => {
+      let message;
+      try { message = JSON.parse(event.data); } catch (_) { return; }
+      if (message.type === 'state' && message.data && message.data.currentSlide) show(message.data.currentSlide.slideId);
+      if (message.type === 'event' && message.event && message.event.name === 'slide:change') show(message.event.data.slideId);
+    };
+    ws.onclose = () => setTimeout(connect, 2000);
+  }
+
+  setSpeed(speed);
+  if (sections.length > 0) show(sections[0].dataset.slide);
+  connect();
+  requestAnimationFrame(tick);
+})();
+</script>"#;
+
+/// The script: per slide in presentation order, its title and resolved notes
+pub(crate) fn script(deck: &DeckManifest, slides: &HashMap<String, SlideDoc>, config: &SanitizationConfig) -> serde_json::Value {
+    let sections: Vec<_> = deck
+        .slide_order()
+        .into_iter()
+        .map(|id| {
+            let slide = slides.get(id);
+            let title = slide.and_then(|s| s.props.get("title")).and_then(|t| t.as_str()).unwrap_or(id);
+            let mut notes = Vec::new();
+            if let Some(markdown) = deck.notes.get(id) {
+                notes.push(json!({ "html": render_markdown_to_html(markdown, config), "type": "general" }));
+            }
+            for note in slide.map(|s| s.notes.as_slice()).unwrap_or_default() {
+                let kind = match note.note_type {
+                    NoteType::General => "general",
+                    NoteType::Timing => "timing",
+                    NoteType::Technical => "technical",
+                    NoteType::Transition => "transition",
+                };
+                notes.push(json!({
+                    "html": html_escape(note.content.trim()).replace('\n', "<br>"),
+                    "type": kind,
+                    "at": note.offset_seconds(),
+                    "label": note.timestamp,
+                }));
+            }
+            json!({ "id": id, "title": title, "notes": notes })
+        })
+        .collect();
+    json!(sections)
+}
+
+/// The teleprompter page for a deck
+pub(crate) fn page(deck: &DeckManifest, slides: &HashMap<String, SlideDoc>, config: &SanitizationConfig) -> String {
+    let mut body = String::new();
+    for section in script(deck, slides, config).as_array().into_iter().flatten() {
+        body.push_str(&format!(
+            "<section data-slide=\"{}\"><h2>{}</h2>",
+            html_escape(section["id"].as_str().unwrap_or_default()),
+            html_escape(section["title"].as_str().unwrap_or_default())
+        ));
+        let notes = section["notes"].as_array().map(Vec::as_slice).unwrap_or_default();
+        if notes.is_empty() {
+            body.push_str("<p class=\"empty\">No notes</p>");
+        }
+        for note in notes {
+            let timing = match note["at"].as_f64() {
+                Some(at) => format!(
+                    " data-at=\"{}\" data-label=\"{}\"",
+                    at,
+                    html_escape(note["label"].as_str().unwrap_or_default())
+                ),
+                None => String::new(),
+            };
+            body.push_str(&format!(
+                "<div class=\"note {}\"{}>{}</div>",
+                note["type"].as_str().unwrap_or("general"),
+                timing,
+                note["html"].as_str().unwrap_or_default()
+            ));
+        }
+        body.push_str("</section>");
+    }
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+    <title>{} · Teleprompter</title>
+    <meta charset="utf-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1">
+    {}
+</head>
+<body>
+    <div id="reading-line"></div>
+    <main id="script">{}</main>
+    <div id="bar">
+        <span id="slide"></span><span id="elapsed">0:00</span>
+        <span class="spacer"></span>
+        <button id="slower">Slower</button><span id="speed"></span><button id="faster">Faster</button>
+        <button id="pause">Pause</button><button id="mirror">Mirror</button>
+    </div>
+    {}
+</body>
+</html>"#,
+        html_escape(&deck.title),
+        PAGE_STYLE,
+        body,
+        PAGE_SCRIPT
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script_resolves_notes_in_presentation_order() {
+        let deck: DeckManifest = toml::from_str(
+            "modelVersion = \"1.0\"\ntitle = \"Talk\"\ntheme = \"t.css\"\nsequence = [\"intro\", \"outro\"]\n\n[transitions]\ndefault = \"none\"\n\n[notes]\nintro = \"**Welcome** everyone\"\n",
+        )
+        .unwrap();
+        let intro: SlideDoc = toml::from_str(
+            r#"
+modelVersion = "1.0"
+id = "intro"
+
+[component]
+name = "TitleSlide"
+versionReq = "^1"
+
+[props]
+title = "Hello"
+
+[[notes]]
+content = "Mention the <demo>"
+timestamp = "1:30"
+noteType = "timing"
+
+[[notes]]
+content = "Wrap up"
+timestamp = "soon"
+"#,
+        )
+        .unwrap();
+        let slides = HashMap::from([("intro".to_string(), intro)]);
+
+        let script = script(&deck, &slides, &SanitizationConfig::new(false));
+        assert_eq!(script[0]["title"], "Hello");
+        assert!(script[0]["notes"][0]["html"].as_str().unwrap().contains("<strong>Welcome</strong>"));
+        assert_eq!(script[0]["notes"][1]["at"], 90.0);
+        assert_eq!(script[0]["notes"][1]["html"], "Mention the &lt;demo&gt;");
+        assert_eq!(script[0]["notes"][2]["at"], serde_json::Value::Null);
+        // Slides without a file still get a section
+        assert_eq!(script[1]["title"], "outro");
+
+        let page = page(&deck, &slides, &SanitizationConfig::new(false));
+        assert!(page.contains(r#"<div class="note timing" data-at="90" data-label="1:30">"#), "{}", page);
+    }
+
+    #[test]
+    fn test_note_timestamps() {
+        let offset = |timestamp: &str| {
+            coolslides_core::SpeakerNote { content: String::new(), timestamp: Some(timestamp.into()), note_type: NoteType::General, style: HashMap::new() }
+                .offset_seconds()
+        };
+        assert_eq!(offset("90"), Some(90.0));
+        assert_eq!(offset("45s"), Some(45.0));
+        assert_eq!(offset("1m30s"), Some(90.0));
+        assert_eq!(offset("2m"), Some(120.0));
+        assert_eq!(offset("1:02:03"), Some(3723.0));
+        assert_eq!(offset("later"), None);
+        assert_eq!(offset(""), None);
+    }
+}
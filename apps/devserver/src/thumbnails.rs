@@ -0,0 +1,156 @@
+/*!
+ * Slide thumbnails for previews, rendered on demand and cached on disk
+ *
+ * A thumbnail is a headless Chromium screenshot of the slide on its own, scaled
+ * down to the requested width. Cached files are named by a hash of everything
+ * that shows in the picture (the slide, its component's version and tag, the
+ * theme and tokens CSS, and the width), so an edit simply misses the cache;
+ * the slide's stale files are removed when it is rendered again.
+ */
+
+use coolslides_core::{cache, ComponentRegistry, DeckManifest, SlideDoc};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+pub(crate) const DEFAULT_WIDTH: u32 = 320;
+/// Widths that get rendered; a request is served the smallest one at least as
+/// wide, so clients cannot start a browser per pixel or fill the cache
+const WIDTHS: [u32; 4] = [160, 320, 640, 1280];
+/// Viewport the slide is laid out in before scaling
+const RENDER_SIZE: (u32, u32) = (1280, 720);
+/// Virtual time budget for the page, in milliseconds
+const RENDER_TIMEOUT: u64 = 5000;
+
+/// One render at a time, since each starts a browser
+static RENDER_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+pub(crate) fn snap_width(width: Option<u32>) -> u32 {
+    let width = width.unwrap_or(DEFAULT_WIDTH);
+    WIDTHS.into_iter().find(|&w| w >= width).unwrap_or(WIDTHS[WIDTHS.len() - 1])
+}
+
+/// Hash of everything a slide's thumbnail depends on; doubles as its ETag
+pub(crate) fn content_hash(
+    deck: &DeckManifest,
+    slide: &SlideDoc,
+    components: Option<&ComponentRegistry>,
+    deck_dir: &Path,
+    width: u32,
+) -> String {
+    let theme = crate::inline_css(Some(deck_dir), &deck.theme);
+    let tokens = deck.tokens.as_ref().and_then(|tokens| crate::inline_css(Some(deck_dir), tokens));
+    let component = components
+        .and_then(|registry| registry.components.get(&slide.component.name))
+        .map(|manifest| (&manifest.version, &manifest.tag));
+    let slide_json = serde_json::to_string(slide).unwrap_or_default();
+    format!("{:016x}", crate::render_cache::key((slide_json, theme, tokens, component, width)))
+}
+
+fn cache_dir(deck_dir: &Path) -> PathBuf {
+    cache::project_cache_dir(deck_dir).join("thumbnails")
+}
+
+/// File name prefix shared by every version of a slide's thumbnail at one width
+fn file_prefix(slide_id: &str, width: u32) -> String {
+    let safe: String = slide_id.chars().map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' }).collect();
+    format!("{}-{}-", safe, width)
+}
+
+/// PNG thumbnail of `slide_id` at `width`, from the cache or freshly rendered
+pub(crate) async fn thumbnail(
+    deck: &DeckManifest,
+    slides: &HashMap<String, SlideDoc>,
+    components: Option<&ComponentRegistry>,
+    deck_dir: &Path,
+    slide_id: &str,
+    hash: &str,
+    width: u32,
+) -> anyhow::Result<Vec<u8>> {
+    let prefix = file_prefix(slide_id, width);
+    let path = cache_dir(deck_dir).join(format!("{}{}.png", prefix, hash));
+    if let Ok(png) = tokio::fs::read(&path).await {
+        return Ok(png);
+    }
+    let _render = RENDER_LOCK.lock().await;
+    // Another request may have rendered it while we waited
+    if let Ok(png) = tokio::fs::read(&path).await {
+        return Ok(png);
+    }
+
+    let html = crate::export::single_slide_html(deck, slides, components, slide_id, deck_dir)?;
+    let png = tokio::task::spawn_blocking(move || render(&html, width)).await??;
+
+    let dir = cache_dir(deck_dir);
+    std::fs::create_dir_all(&dir)?;
+    for entry in std::fs::read_dir(&dir)?.flatten() {
+        if entry.file_name().to_string_lossy().starts_with(&prefix) {
+            let _ = std::fs::remove_file(entry.path());
+        }
+    }
+    std::fs::write(&path, &png)?;
+    Ok(png)
+}
+
+/// Screenshot a single-slide page and scale it to `width`
+fn render(html: &str, width: u32) -> anyhow::Result<Vec<u8>> {
+    let browser = crate::export::check_browser_availability()?;
+    let work = tempfile::TempDir::new()?;
+    let html_path = work.path().join("slide.html");
+    let image_path = work.path().join("slide.png");
+    std::fs::write(&html_path, html)?;
+    crate::export::screenshot_slide(&browser, &html_path, &image_path, RENDER_SIZE, RENDER_TIMEOUT)?;
+    scale_png(&std::fs::read(&image_path)?, width)
+}
+
+fn scale_png(png: &[u8], width: u32) -> anyhow::Result<Vec<u8>> {
+    let image = image::load_from_memory(png)?;
+    let height = ((image.height() as u64 * width as u64) / image.width().max(1) as u64).max(1) as u32;
+    let scaled = image.resize_exact(width, height, image::imageops::FilterType::Triangle);
+    let mut encoded = Vec::new();
+    scaled.write_with_encoder(image::codecs::png::PngEncoder::new(&mut encoded))?;
+    Ok(encoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_follows_slide_and_theme_and_scaling_keeps_aspect() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("theme.css"), "body { color: red }").unwrap();
+        let deck: DeckManifest = toml::from_str(
+            "modelVersion = \"1.0\"\ntitle = \"T\"\ntheme = \"theme.css\"\nsequence = [\"a\"]\n\n[transitions]\ndefault = \"none\"\n",
+        )
+        .unwrap();
+        let mut slide: SlideDoc = toml::from_str(
+            "modelVersion = \"1.0\"\nid = \"a\"\n\n[component]\nname = \"TitleSlide\"\nversionReq = \"^1\"\n\n[props]\ntitle = \"One\"\n",
+        )
+        .unwrap();
+
+        let hash = content_hash(&deck, &slide, None, dir.path(), 320);
+        assert_eq!(hash, content_hash(&deck, &slide, None, dir.path(), 320));
+        assert_ne!(hash, content_hash(&deck, &slide, None, dir.path(), 640));
+        std::fs::write(dir.path().join("theme.css"), "body { color: blue }").unwrap();
+        let rethemed = content_hash(&deck, &slide, None, dir.path(), 320);
+        assert_ne!(hash, rethemed);
+        slide.props["title"] = "Two".into();
+        assert_ne!(rethemed, content_hash(&deck, &slide, None, dir.path(), 320));
+
+        assert_eq!(snap_width(None), DEFAULT_WIDTH);
+        assert_eq!(snap_width(Some(10_000)), 1280);
+        assert_eq!(snap_width(Some(1)), 160);
+        let (near, nearer) = (snap_width(Some(300)), snap_width(Some(301)));
+        assert_eq!(near, 320);
+        assert_eq!(content_hash(&deck, &slide, None, dir.path(), near), content_hash(&deck, &slide, None, dir.path(), nearer));
+        assert_eq!(file_prefix("intro", near), file_prefix("intro", nearer));
+        assert_eq!(file_prefix("intro/../x", 320), "intro____x-320-");
+
+        let mut png = Vec::new();
+        image::RgbImage::from_pixel(1280, 720, image::Rgb([10, 20, 30]))
+            .write_with_encoder(image::codecs::png::PngEncoder::new(&mut png))
+            .unwrap();
+        let scaled = image::load_from_memory(&scale_png(&png, 320).unwrap()).unwrap();
+        assert_eq!((scaled.width(), scaled.height()), (320, 180));
+    }
+}
@@ -0,0 +1,255 @@
+/*!
+ * Visual regression tests: screenshots of components and slides compared against committed baselines
+ */
+
+use crate::{create_router, AppState};
+use anyhow::{anyhow, Result};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// What `coolslides components test` captures
+#[derive(Debug, Clone)]
+pub struct VisualOptions {
+    /// Directory holding `components/<Name>.png` and `slides/<id>.png` baselines
+    pub baselines: PathBuf,
+    /// Largest fraction of differing pixels that still passes
+    pub threshold: f64,
+    /// Per-channel difference below which two pixels count as equal (absorbs anti-aliasing noise)
+    pub tolerance: u8,
+    /// Capture each slide of the deck instead of each component's example slide
+    pub slides: bool,
+    /// Overwrite baselines with the new screenshots
+    pub update: bool,
+    pub width: u32,
+    pub height: u32,
+    /// Virtual time budget per page, in milliseconds
+    pub timeout: u64,
+}
+
+impl Default for VisualOptions {
+    fn default() -> Self {
+        Self {
+            baselines: PathBuf::from("tests/visual"),
+            threshold: 0.001,
+            tolerance: 16,
+            slides: false,
+            update: false,
+            width: 1280,
+            height: 720,
+            timeout: 5000,
+        }
+    }
+}
+
+/// Outcome for one screenshot
+#[derive(Debug, Clone, PartialEq)]
+pub enum VisualStatus {
+    /// Within the threshold
+    Passed { ratio: f64 },
+    /// Over the threshold; `.actual.png` and `.diff.png` were written next to the baseline
+    Failed { ratio: f64 },
+    /// No baseline existed, so the screenshot became the baseline
+    Created,
+    /// The baseline was replaced (`--update`)
+    Updated,
+}
+
+#[derive(Debug, Clone)]
+pub struct VisualResult {
+    /// Component name or slide id
+    pub name: String,
+    pub baseline: PathBuf,
+    pub status: VisualStatus,
+}
+
+/// Pixel comparison of two screenshots
+#[derive(Debug, Clone)]
+pub struct ImageDiff {
+    pub differing: usize,
+    pub total: usize,
+    /// Baseline faded to grey with differing pixels in red; `None` when the sizes differ
+    pub diff_png: Option<Vec<u8>>,
+}
+
+impl ImageDiff {
+    /// Fraction of pixels that differ
+    pub fn ratio(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        self.differing as f64 / self.total as f64
+    }
+}
+
+/// Serve the deck on a local port, screenshot every component (or slide), and compare
+/// each screenshot against its baseline
+pub async fn run_visual_tests(deck_dir: &Path, options: &VisualOptions) -> Result<Vec<VisualResult>> {
+    let browser = crate::export::check_browser_availability()?;
+    let state = AppState::new();
+    state.load_from_directory(deck_dir).await?;
+
+    let targets: Vec<(String, String)> = if options.slides {
+        let deck = state.deck.read().await;
+        let deck = deck.as_ref().ok_or_else(|| anyhow!("No deck loaded from {}", deck_dir.display()))?;
+        deck.slide_order().into_iter().map(|id| (id.to_string(), format!("/#{}", id))).collect()
+    } else {
+        let components = state.components.read().await;
+        let registry = components.as_ref().ok_or_else(|| anyhow!("No components found for {}", deck_dir.display()))?;
+        let mut names: Vec<&String> = registry.components.keys().collect();
+        names.sort();
+        names.into_iter().map(|name| (name.clone(), format!("/components/{}?bare", name))).collect()
+    };
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let address = listener.local_addr()?;
+    let server = tokio::spawn(async move { axum::serve(listener, create_router(state)).await });
+
+    let kind = if options.slides { "slides" } else { "components" };
+    let mut results = Vec::new();
+    for (name, path) in targets {
+        let baseline = options.baselines.join(kind).join(format!("{}.png", file_stem(&name)));
+        let url = format!("http://{}{}", address, path);
+        let (browser, opts) = (browser.clone(), options.clone());
+        let screenshot = tokio::task::spawn_blocking(move || capture_screenshot(&browser, &url, &opts)).await??;
+        let status = record(&baseline, &screenshot, options)?;
+        results.push(VisualResult { name, baseline, status });
+    }
+    server.abort();
+    Ok(results)
+}
+
+/// Compare a screenshot with its baseline, writing the baseline or the failure artifacts
+fn record(baseline: &Path, screenshot: &[u8], options: &VisualOptions) -> Result<VisualStatus> {
+    let actual_path = baseline.with_extension("actual.png");
+    let diff_path = baseline.with_extension("diff.png");
+    if options.update || !baseline.exists() {
+        let existed = baseline.exists();
+        if let Some(parent) = baseline.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(baseline, screenshot)?;
+        let _ = std::fs::remove_file(&actual_path);
+        let _ = std::fs::remove_file(&diff_path);
+        return Ok(if existed { VisualStatus::Updated } else { VisualStatus::Created });
+    }
+
+    let diff = compare_png(&std::fs::read(baseline)?, screenshot, options.tolerance)?;
+    let ratio = diff.ratio();
+    if ratio <= options.threshold {
+        let _ = std::fs::remove_file(&actual_path);
+        let _ = std::fs::remove_file(&diff_path);
+        return Ok(VisualStatus::Passed { ratio });
+    }
+    std::fs::write(&actual_path, screenshot)?;
+    match diff.diff_png {
+        Some(diff_png) => std::fs::write(&diff_path, diff_png)?,
+        None => {
+            let _ = std::fs::remove_file(&diff_path);
+        }
+    }
+    Ok(VisualStatus::Failed { ratio })
+}
+
+/// PNG screenshot of `url` from headless Chrome
+fn capture_screenshot(browser: &str, url: &str, options: &VisualOptions) -> Result<Vec<u8>> {
+    let dir = tempfile::TempDir::new()?;
+    let path = dir.path().join("screenshot.png");
+    let output = Command::new(browser)
+        .args([
+            "--headless",
+            "--no-sandbox",
+            "--disable-gpu",
+            "--disable-dev-shm-usage",
+            "--disable-extensions",
+            "--hide-scrollbars",
+            "--force-device-scale-factor=1",
+            "--run-all-compositor-stages-before-draw",
+            &format!("--virtual-time-budget={}", options.timeout),
+            &format!("--window-size={},{}", options.width, options.height),
+            &format!("--screenshot={}", path.to_string_lossy()),
+            url,
+        ])
+        .output()?;
+    if !output.status.success() {
+        return Err(anyhow!("Browser screenshot of {} failed: {}", url, String::from_utf8_lossy(&output.stderr)));
+    }
+    std::fs::read(&path).map_err(|e| anyhow!("Browser produced no screenshot for {}: {}", url, e))
+}
+
+/// Count the pixels of two PNGs that differ by more than `tolerance` in any channel
+pub fn compare_png(baseline: &[u8], actual: &[u8], tolerance: u8) -> Result<ImageDiff> {
+    let (width, height, expected) = decode_rgba(baseline)?;
+    let (actual_width, actual_height, actual) = decode_rgba(actual)?;
+    if (width, height) != (actual_width, actual_height) {
+        let total = (actual_width as usize * actual_height as usize).max(width as usize * height as usize);
+        return Ok(ImageDiff { differing: total, total, diff_png: None });
+    }
+
+    let mut differing = 0;
+    let mut diff = Vec::with_capacity(expected.len());
+    for (a, b) in expected.chunks_exact(4).zip(actual.chunks_exact(4)) {
+        if a.iter().zip(b).any(|(x, y)| x.abs_diff(*y) > tolerance) {
+            differing += 1;
+            diff.extend_from_slice(&[255, 0, 0, 255]);
+        } else {
+            let grey = ((a[0] as u32 + a[1] as u32 + a[2] as u32) / 3) as u8;
+            let faded = 255 - (255 - grey) / 4;
+            diff.extend_from_slice(&[faded, faded, faded, 255]);
+        }
+    }
+    Ok(ImageDiff { differing, total: expected.len() / 4, diff_png: Some(encode_rgba(width, height, &diff)?) })
+}
+
+/// Decode any 8- or 16-bit PNG to RGBA8
+fn decode_rgba(bytes: &[u8]) -> Result<(u32, u32, Vec<u8>)> {
+    let mut decoder = png::Decoder::new(bytes);
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+    let mut reader = decoder.read_info()?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let frame = reader.next_frame(&mut buffer)?;
+    buffer.truncate(frame.buffer_size());
+    let rgba = match frame.color_type {
+        png::ColorType::Rgba => buffer,
+        png::ColorType::Rgb => buffer.chunks_exact(3).flat_map(|p| [p[0], p[1], p[2], 255]).collect(),
+        png::ColorType::GrayscaleAlpha => buffer.chunks_exact(2).flat_map(|p| [p[0], p[0], p[0], p[1]]).collect(),
+        png::ColorType::Grayscale => buffer.iter().flat_map(|&g| [g, g, g, 255]).collect(),
+        png::ColorType::Indexed => return Err(anyhow!("Unexpanded indexed PNG")),
+    };
+    Ok((frame.width, frame.height, rgba))
+}
+
+fn encode_rgba(width: u32, height: u32, rgba: &[u8]) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    let mut encoder = png::Encoder::new(&mut bytes, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(rgba)?;
+    Ok(bytes)
+}
+
+/// Baseline file name for a component name or slide id
+fn file_stem(name: &str) -> String {
+    name.chars().map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_png_counts_differing_pixels() {
+        let white = vec![255u8; 4 * 4 * 4];
+        let mut changed = white.clone();
+        changed[0..4].copy_from_slice(&[0, 0, 0, 255]);
+        changed[4..8].copy_from_slice(&[250, 250, 250, 255]);
+
+        let baseline = encode_rgba(4, 4, &white).unwrap();
+        let diff = compare_png(&baseline, &encode_rgba(4, 4, &changed).unwrap(), 16).unwrap();
+        assert_eq!((diff.differing, diff.total), (1, 16));
+        assert_eq!(decode_rgba(&diff.diff_png.unwrap()).unwrap().2[0..4], [255, 0, 0, 255]);
+
+        let resized = compare_png(&baseline, &encode_rgba(2, 2, &white[..16]).unwrap(), 16).unwrap();
+        assert_eq!(resized.ratio(), 1.0);
+        assert!(resized.diff_png.is_none());
+    }
+}
@@ -0,0 +1,203 @@
+/*!
+ * Outgoing webhooks: room and deck events POSTed as JSON to the deck's `[[webhooks]]`
+ *
+ * Rooms and export handlers publish [`ServerEvent`]s on a broadcast channel; the
+ * dispatcher started with the server delivers each one to every subscribed
+ * endpoint, signing the body when a secret is configured and retrying failures
+ * with exponential backoff.
+ */
+
+use crate::AppState;
+use chrono::{DateTime, Utc};
+use coolslides_core::WebhookConfig;
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+/// Events a webhook can subscribe to
+pub const WEBHOOK_EVENTS: &[&str] = &[
+    "room.opened",
+    "room.closed",
+    "slide.changed",
+    "recording.started",
+    "recording.stopped",
+    "replay.started",
+    "replay.finished",
+    "poll.opened",
+    "poll.closed",
+    "question.asked",
+    "export.completed",
+];
+
+/// Wait before the first retry; doubled for each further attempt
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Something that happened on the server that webhooks may want to hear about
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerEvent {
+    /// One of [`WEBHOOK_EVENTS`]
+    pub event: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub room_id: Option<String>,
+    pub data: serde_json::Value,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl ServerEvent {
+    pub fn new(event: &'static str, room_id: Option<&str>, data: serde_json::Value) -> Self {
+        Self { event, room_id: room_id.map(str::to_string), data, timestamp: Utc::now() }
+    }
+}
+
+/// Channel the rooms and handlers publish on; sending never blocks and is a no-op
+/// when nothing listens
+pub type EventSender = broadcast::Sender<ServerEvent>;
+
+/// Payload body of one delivery
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Payload<'a> {
+    /// Unique per event; retries of the same delivery reuse it
+    id: String,
+    deck: &'a str,
+    #[serde(flatten)]
+    event: &'a ServerEvent,
+}
+
+/// Deliver server events to the loaded deck's webhooks until the channel closes
+pub fn spawn_dispatcher(state: AppState) -> tokio::task::JoinHandle<()> {
+    let mut receiver = state.events.subscribe();
+    tokio::spawn(async move {
+        loop {
+            let event = match receiver.recv().await {
+                Ok(event) => event,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!(skipped, "webhooks fell behind; events were not delivered");
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+            let (hooks, title) = match state.deck.read().await.as_ref() {
+                Some(deck) => (deck.webhooks.clone(), deck.title.clone()),
+                None => continue,
+            };
+            let id = uuid::Uuid::new_v4().to_string();
+            let body = match serde_json::to_vec(&Payload { id: id.clone(), deck: &title, event: &event }) {
+                Ok(body) => body,
+                Err(_) => continue,
+            };
+            for hook in hooks.into_iter().filter(|hook| hook.wants(event.event)) {
+                let (body, id) = (body.clone(), id.clone());
+                tokio::spawn(async move {
+                    if let Err(e) = deliver(&hook, event.event, &id, &body, RETRY_DELAY).await {
+                        tracing::warn!(url = %hook.url, event = event.event, error = %e, "webhook failed");
+                    }
+                });
+            }
+        }
+    })
+}
+
+/// POST one payload, retrying connection errors, 429s, and 5xx responses
+pub async fn deliver(hook: &WebhookConfig, event: &str, id: &str, body: &[u8], retry_delay: Duration) -> anyhow::Result<()> {
+    let secret = hook.signing_secret();
+    let mut attempt = 0;
+    loop {
+        let timestamp = Utc::now().timestamp().to_string();
+        let mut request = ureq::AgentBuilder::new()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .post(&hook.url)
+            .set("Content-Type", "application/json")
+            .set("User-Agent", concat!("coolslides/", env!("CARGO_PKG_VERSION")))
+            .set("X-Coolslides-Event", event)
+            .set("X-Coolslides-Delivery", id)
+            .set("X-Coolslides-Timestamp", &timestamp);
+        if let Some(secret) = &secret {
+            request = request.set("X-Coolslides-Signature", &signature(secret, &timestamp, body));
+        }
+
+        let body = body.to_vec();
+        let result = tokio::task::spawn_blocking(move || request.send_bytes(&body).map(drop).map_err(Box::new)).await?;
+        let retryable = match result {
+            Ok(()) => return Ok(()),
+            Err(e) => match *e {
+                ureq::Error::Status(status, _) if status != 429 && status < 500 => {
+                    anyhow::bail!("endpoint answered {}", status);
+                }
+                e => e,
+            },
+        };
+        if attempt >= hook.retries {
+            anyhow::bail!("gave up after {} attempts: {}", attempt + 1, retryable);
+        }
+        tokio::time::sleep(retry_delay * 2u32.saturating_pow(attempt)).await;
+        attempt += 1;
+    }
+}
+
+/// `sha256=<hex>` HMAC of `<timestamp>.<body>`, so a captured request cannot be replayed with a new timestamp
+pub fn signature(secret: &str, timestamp: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    let digest = mac.finalize().into_bytes();
+    format!("sha256={}", digest.iter().map(|b| format!("{:02x}", b)).collect::<String>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::StatusCode, routing::post, Router};
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_signature_is_hmac_of_timestamp_and_body() {
+        // HMAC-SHA256("key", "1700000000.{}")
+        assert_eq!(
+            signature("key", "1700000000", b"{}"),
+            "sha256=9d713ed406bb7076d4123f0dc2c39d2df5c654ed4b0cd56b52c8b4c940bd63ae"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_deliver_retries_server_errors() {
+        let calls = Arc::new(AtomicU32::new(0));
+        let counter = calls.clone();
+        let app = Router::new().route(
+            "/hook",
+            post(move || async move {
+                match counter.fetch_add(1, Ordering::SeqCst) {
+                    0 => StatusCode::SERVICE_UNAVAILABLE,
+                    _ => StatusCode::NO_CONTENT,
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let hook = WebhookConfig {
+            url: format!("http://{}/hook", address),
+            events: vec!["slide.changed".to_string()],
+            secret: Some("s3cret".to_string()),
+            secret_env: None,
+            retries: 2,
+        };
+        assert!(hook.wants("slide.changed") && !hook.wants("room.opened"));
+        deliver(&hook, "slide.changed", "d1", b"{}", Duration::from_millis(10)).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        let missing = WebhookConfig { url: format!("http://{}/missing", address), ..hook };
+        assert!(deliver(&missing, "slide.changed", "d2", b"{}", Duration::from_millis(10)).await.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        server.abort();
+    }
+}
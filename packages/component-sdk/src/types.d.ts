@@ -13,7 +13,20 @@ export interface ComponentManifest {
     tokensUsed: string[];
     capabilities?: string[];
     suggestedTransition?: string;
+    slots?: SlotDeclaration[];
+    deprecated?: boolean | Deprecation;
 }
+export interface Deprecation {
+    message?: string;
+    replacement?: string;
+}
+export interface SlotDeclaration {
+    name: string;
+    required?: boolean;
+    description?: string;
+    accepts?: SlotKind[];
+}
+export type SlotKind = 'markdown' | 'component';
 export interface ComponentSchema {
     type: 'object';
     required?: string[];
@@ -27,6 +40,7 @@ export interface SchemaProperty {
     enum?: any[];
     items?: SchemaProperty;
     properties?: Record<string, SchemaProperty>;
+    deprecated?: boolean | Deprecation;
 }
 export interface ComponentEvent<T = any> extends CustomEvent<T> {
     type: 'ready' | 'change' | 'error' | string;
@@ -20,8 +20,29 @@ export interface ComponentManifest {
   tokensUsed: string[];
   capabilities?: string[];
   suggestedTransition?: string;
+  slots?: SlotDeclaration[];
+  deprecated?: boolean | Deprecation;
 }
 
+// Deprecation notice for a component version or prop
+export interface Deprecation {
+  message?: string;
+  // Component or prop to use instead
+  replacement?: string;
+}
+
+// Named slot rendered by a component
+export interface SlotDeclaration {
+  name: string;
+  required?: boolean;
+  description?: string;
+  // Kinds of content the slot takes; omitted accepts any kind
+  accepts?: SlotKind[];
+}
+
+// Kind of content a slot can be filled with
+export type SlotKind = 'markdown' | 'component';
+
 // JSON Schema for component props
 export interface ComponentSchema {
   type: 'object';
@@ -44,6 +65,7 @@ export interface SchemaProperty {
   maxLength?: number;
   pattern?: string;
   format?: string;
+  deprecated?: boolean | Deprecation;
 }
 
 // Component events
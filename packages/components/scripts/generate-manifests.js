@@ -46,6 +46,7 @@ async function generateManifests() {
         tokensUsed: manifestObj.tokensUsed ?? [],
         capabilities: manifestObj.capabilities ?? [],
         suggestedTransition: manifestObj.suggestedTransition ?? undefined,
+        slots: manifestObj.slots ?? undefined,
       };
 
       if (!out.name || !out.tag) continue;
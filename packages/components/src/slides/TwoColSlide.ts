@@ -45,6 +45,10 @@ import { CoolslidesElement, property, component } from '@coolslides/component-sd
     '--background-color',
     '--text-color',
     '--column-gap'
+  ],
+  slots: [
+    { name: 'left', description: 'Left column content' },
+    { name: 'right', description: 'Right column content' }
   ]
 })
 export class TwoColSlide extends CoolslidesElement {
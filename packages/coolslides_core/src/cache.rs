@@ -0,0 +1,92 @@
+//! Incremental validation: per-slide results keyed by content hash.
+
+use crate::ir::{ComponentRegistry, SlideDoc};
+use crate::validation::ValidationResult;
+use serde::Serialize;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+/// Directory for caches that persist across CLI runs and devserver reloads
+pub fn project_cache_dir(deck_root: &Path) -> PathBuf {
+    deck_root.join(".coolslides").join("cache")
+}
+
+/// Per-slide validation results reused while a slide and its component manifest are unchanged
+#[derive(Debug, Default)]
+pub struct ValidationCache {
+    entries: HashMap<String, CachedSlide>,
+    hits: usize,
+    misses: usize,
+}
+
+#[derive(Debug)]
+struct CachedSlide {
+    key: u64,
+    result: ValidationResult,
+}
+
+impl ValidationCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Slides reused from the cache during the last validation run
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// Slides validated from scratch during the last validation run
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+
+    /// Drop every cached result
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub(crate) fn lookup(&self, slide_id: &str, key: u64) -> Option<ValidationResult> {
+        self.entries
+            .get(slide_id)
+            .filter(|entry| entry.key == key)
+            .map(|entry| entry.result.clone())
+    }
+
+    /// Replace the cache contents with the results of a run, forgetting removed slides
+    pub(crate) fn store(&mut self, results: Vec<(String, u64, ValidationResult)>, hits: usize) {
+        self.misses = results.len() - hits;
+        self.hits = hits;
+        self.entries = results
+            .into_iter()
+            .map(|(slide_id, key, result)| (slide_id, CachedSlide { key, result }))
+            .collect();
+    }
+}
+
+/// Cache key for a slide: its content plus the manifest of the component it uses
+pub(crate) fn slide_key(slide: &SlideDoc, registry: Option<&ComponentRegistry>) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    hash_canonical(slide, &mut hasher);
+    match registry {
+        Some(registry) => {
+            true.hash(&mut hasher);
+            match registry.resolve(&slide.component.name, &slide.component.version_req) {
+                Some(component) => hash_canonical(component, &mut hasher),
+                None => 0u8.hash(&mut hasher),
+            }
+        }
+        None => false.hash(&mut hasher),
+    }
+    hasher.finish()
+}
+
+/// Hash a value through `serde_json::Value`, whose object keys are sorted, so that
+/// `HashMap` iteration order does not change the key
+fn hash_canonical<T: Serialize>(value: &T, hasher: &mut impl Hasher) {
+    serde_json::to_value(value)
+        .map(|v| v.to_string())
+        .unwrap_or_default()
+        .hash(hasher);
+}
@@ -0,0 +1,235 @@
+//! Component catalog: reference documentation generated from a registry.
+//!
+//! Each component gets a prop table built from its schema, the tokens and
+//! capabilities it declares, and an example slide whose props come from schema
+//! `examples`, `default`, and `enum` values.
+
+use crate::ir::{ComponentManifest, ComponentRegistry, ComponentSpec, Deprecation, SlideDoc, Slot, SlotKind};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// One row of a component's prop table
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropDoc {
+    pub name: String,
+    /// JSON Schema type, with enum values listed as `"a" | "b"`
+    pub ty: String,
+    pub required: bool,
+    pub default: Option<String>,
+    pub description: Option<String>,
+    pub deprecated: Option<Deprecation>,
+}
+
+/// Prop table rows for a component schema, required props first
+pub fn prop_docs(schema: &Value) -> Vec<PropDoc> {
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|r| r.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+        return Vec::new();
+    };
+
+    let mut docs: Vec<PropDoc> = properties
+        .iter()
+        .map(|(name, prop)| {
+            let ty = match prop.get("enum").and_then(Value::as_array) {
+                Some(values) => values.iter().map(Value::to_string).collect::<Vec<_>>().join(" | "),
+                None => match prop.get("type") {
+                    Some(Value::Array(types)) => types.iter().filter_map(Value::as_str).collect::<Vec<_>>().join(" | "),
+                    Some(ty) => ty.as_str().unwrap_or("any").to_string(),
+                    None => "any".to_string(),
+                },
+            };
+            PropDoc {
+                name: name.clone(),
+                ty,
+                required: required.contains(&name.as_str()),
+                default: prop.get("default").map(Value::to_string),
+                description: prop.get("description").and_then(Value::as_str).map(str::to_string),
+                deprecated: Deprecation::of_prop(prop),
+            }
+        })
+        .collect();
+    docs.sort_by(|a, b| b.required.cmp(&a.required).then_with(|| a.name.cmp(&b.name)));
+    docs
+}
+
+/// Slide showing a component with example props and markdown in each slot that takes it
+pub fn example_slide(manifest: &ComponentManifest) -> SlideDoc {
+    let mut slots = HashMap::new();
+    for slot in manifest.slots.iter().flatten() {
+        if slot.accepts_kind(SlotKind::Markdown) {
+            let value = slot.description.clone().unwrap_or_else(|| format!("Content for the {} slot", slot.name));
+            slots.insert(slot.name.clone(), Slot::Markdown { value });
+        }
+    }
+
+    SlideDoc {
+        model_version: "1.0".to_string(),
+        id: format!("example-{}", manifest.tag),
+        component: ComponentSpec {
+            name: manifest.name.clone(),
+            version_req: format!("^{}", manifest.version),
+        },
+        props: example_props(&manifest.schema),
+        slots,
+        tags: Vec::new(),
+        style_overrides: HashMap::new(),
+        locale: None,
+        dir: None,
+        notes: Vec::new(),
+        narration: None,
+    }
+}
+
+/// Example value for every prop that has one, plus placeholders for required props
+pub fn example_props(schema: &Value) -> Value {
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|r| r.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    let mut props = serde_json::Map::new();
+    for (name, prop) in schema.get("properties").and_then(Value::as_object).into_iter().flatten() {
+        let value = prop
+            .get("examples")
+            .and_then(|e| e.get(0))
+            .or_else(|| prop.get("default"))
+            .or_else(|| prop.get("enum").and_then(|e| e.get(0)))
+            .cloned()
+            .or_else(|| required.contains(&name.as_str()).then(|| placeholder(name, prop)));
+        if let Some(value) = value {
+            props.insert(name.clone(), value);
+        }
+    }
+    Value::Object(props)
+}
+
+fn placeholder(name: &str, prop: &Value) -> Value {
+    match prop.get("type").and_then(Value::as_str) {
+        Some("number") | Some("integer") => Value::from(0),
+        Some("boolean") => Value::Bool(false),
+        Some("array") => Value::Array(Vec::new()),
+        Some("object") => Value::Object(serde_json::Map::new()),
+        _ => Value::String(format!("Example {}", name)),
+    }
+}
+
+/// Markdown catalog of every component, sorted by name
+pub fn markdown(registry: &ComponentRegistry) -> String {
+    let mut out = String::from("# Component Catalog\n");
+    for manifest in sorted(registry) {
+        let _ = write!(out, "\n## {}\n\n`<{}>` · version {}\n", manifest.name, manifest.tag, manifest.version);
+        if let Some(deprecation) = &manifest.deprecated {
+            let _ = writeln!(out, "\n> **Deprecated:** {}", deprecation);
+        }
+
+        let props = prop_docs(&manifest.schema);
+        if !props.is_empty() {
+            out.push_str("\n### Props\n\n| Prop | Type | Required | Default | Description |\n| --- | --- | --- | --- | --- |\n");
+            for prop in props {
+                let _ = writeln!(
+                    out,
+                    "| `{}` | {} | {} | {} | {} |",
+                    prop.name,
+                    table_cell(&prop.ty),
+                    if prop.required { "yes" } else { "" },
+                    prop.default.as_ref().map(|d| format!("`{}`", d)).unwrap_or_default(),
+                    table_cell(&prop_description(&prop)),
+                );
+            }
+        }
+
+        if let Some(slots) = manifest.slots.as_ref().filter(|s| !s.is_empty()) {
+            out.push_str("\n### Slots\n\n");
+            for slot in slots {
+                let accepts: Vec<String> = slot.accepts.iter().map(|k| k.to_string()).collect();
+                let _ = writeln!(
+                    out,
+                    "- `{}`{}{}{}",
+                    slot.name,
+                    if slot.required { " (required)" } else { "" },
+                    if accepts.is_empty() { String::new() } else { format!(" · accepts {}", accepts.join(", ")) },
+                    slot.description.as_ref().map(|d| format!(": {}", d)).unwrap_or_default(),
+                );
+            }
+        }
+
+        if !manifest.tokens_used.is_empty() {
+            let tokens: Vec<String> = manifest.tokens_used.iter().map(|t| format!("`{}`", t)).collect();
+            let _ = writeln!(out, "\n**Tokens:** {}", tokens.join(", "));
+        }
+        if !manifest.capabilities.is_empty() {
+            let capabilities: Vec<String> = manifest.capabilities.iter().map(|c| format!("`{}`", c)).collect();
+            let _ = writeln!(out, "\n**Capabilities:** {}", capabilities.join(", "));
+        }
+        if let Some(transition) = &manifest.suggested_transition {
+            let _ = writeln!(out, "\n**Suggested transition:** `{}`", transition);
+        }
+
+        let _ = write!(out, "\n### Example\n\n```toml\n{}```\n", example_toml(&example_slide(manifest)));
+    }
+    out
+}
+
+/// Components in name order
+pub fn sorted(registry: &ComponentRegistry) -> Vec<&ComponentManifest> {
+    let mut manifests: Vec<&ComponentManifest> = registry.components.values().collect();
+    manifests.sort_by(|a, b| a.name.cmp(&b.name));
+    manifests
+}
+
+/// Slide as it would appear in a `.slide.toml` file, with keys in a stable order
+pub fn example_toml(slide: &SlideDoc) -> String {
+    let Ok(Value::Object(mut fields)) = serde_json::to_value(slide) else {
+        return String::new();
+    };
+    fields.retain(|_, value| !matches!(value, Value::Object(map) if map.is_empty()));
+    toml::to_string(&fields).unwrap_or_default()
+}
+
+/// Description with any deprecation notice first
+pub fn prop_description(prop: &PropDoc) -> String {
+    let description = prop.description.as_deref().unwrap_or_default();
+    match &prop.deprecated {
+        Some(deprecation) => format!("**Deprecated:** {}. {}", deprecation, description).trim_end().to_string(),
+        None => description.to_string(),
+    }
+}
+
+fn table_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_example_props_and_prop_docs() {
+        let schema = serde_json::json!({
+            "type": "object",
+            "required": ["title"],
+            "properties": {
+                "title": { "type": "string", "description": "Slide title" },
+                "align": { "type": "string", "enum": ["left", "center"] },
+                "count": { "type": "number", "default": 3 },
+                "notes": { "type": "string" }
+            }
+        });
+        assert_eq!(
+            example_props(&schema),
+            serde_json::json!({ "title": "Example title", "align": "left", "count": 3 })
+        );
+
+        let docs = prop_docs(&schema);
+        assert_eq!(docs[0].name, "title");
+        assert!(docs[0].required);
+        assert_eq!(docs[1].ty, "\"left\" | \"center\"");
+        assert_eq!(docs[2].default.as_deref(), Some("3"));
+        assert!(docs.iter().all(|doc| doc.deprecated.is_none()));
+    }
+}
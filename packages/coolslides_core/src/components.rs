@@ -1,16 +1,19 @@
-use crate::ir::{ComponentManifest, ComponentRegistry};
+use crate::ir::{ComponentManifest, ComponentRegistry, Deprecation};
 use anyhow::Result;
-use regex::Regex;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::Path;
+use swc_common::{BytePos, Span, Spanned};
+use swc_ecma_ast::{
+    BinaryOp, Callee, Class, Decl, DefaultDecl, ExportDecl, ExportDefaultDecl, Expr, Lit, MemberProp, Module,
+    ModuleDecl, ModuleItem, Pat, Prop, PropName, PropOrSpread, Stmt, UnaryOp, VarDeclKind,
+};
+use swc_ecma_parser::{Parser, StringInput, Syntax, TsSyntax};
 
 /// Extract component manifests from TypeScript source files
 pub fn extract_manifests_from_directory(components_dir: &Path) -> Result<ComponentRegistry> {
-    let mut registry = ComponentRegistry {
-        components: HashMap::new(),
-        tag_to_name: HashMap::new(),
-    };
+    let mut registry = ComponentRegistry::default();
 
     // Walk through all TypeScript files in the components directory
     for entry in walkdir::WalkDir::new(components_dir)
@@ -20,9 +23,10 @@ pub fn extract_manifests_from_directory(components_dir: &Path) -> Result<Compone
         let path = entry.path();
         if path.extension().and_then(|s| s.to_str()) == Some("ts") {
             if let Ok(content) = fs::read_to_string(path) {
-                if let Ok(manifest) = extract_manifest_from_source(&content, path) {
-                    registry.tag_to_name.insert(manifest.tag.clone(), manifest.name.clone());
-                    registry.components.insert(manifest.name.clone(), manifest);
+                if let Ok(manifests) = extract_manifests_from_source(&content, path) {
+                    for manifest in manifests {
+                        registry.insert(manifest, path);
+                    }
                 }
             }
         }
@@ -31,12 +35,89 @@ pub fn extract_manifests_from_directory(components_dir: &Path) -> Result<Compone
     Ok(registry)
 }
 
+/// Bumped when extraction changes so stale cache entries are ignored
+const MANIFEST_CACHE_VERSION: u32 = 1;
+
+/// Manifests extracted from each source file, keyed by path
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ManifestCache {
+    version: u32,
+    files: BTreeMap<String, CachedSource>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedSource {
+    /// Hash of the file contents
+    hash: String,
+    /// Empty for files without a usable `@component` decorator
+    manifests: Vec<ComponentManifest>,
+}
+
+/// Like `extract_manifests_from_directory`, but reuses manifests cached in
+/// `cache_dir` for source files whose contents have not changed
+pub fn extract_manifests_from_directory_cached(components_dir: &Path, cache_dir: &Path) -> Result<ComponentRegistry> {
+    let cache_path = manifest_cache_path(components_dir, cache_dir);
+    let previous: ManifestCache = fs::read_to_string(&cache_path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .filter(|cache: &ManifestCache| cache.version == MANIFEST_CACHE_VERSION)
+        .unwrap_or_default();
+    let mut current = ManifestCache { version: MANIFEST_CACHE_VERSION, files: BTreeMap::new() };
+    let mut registry = ComponentRegistry::default();
+
+    for entry in walkdir::WalkDir::new(components_dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+    {
+        let path = entry.path();
+        if path.extension().and_then(|s| s.to_str()) != Some("ts") {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(path) else {
+            continue;
+        };
+        let key = path.display().to_string();
+        let hash = content_hash(&content);
+        let source = match previous.files.get(&key).filter(|cached| cached.hash == hash) {
+            Some(cached) => cached.clone(),
+            None => CachedSource {
+                hash,
+                manifests: extract_manifests_from_source(&content, path).unwrap_or_default(),
+            },
+        };
+        for manifest in &source.manifests {
+            registry.insert(manifest.clone(), path);
+        }
+        current.files.insert(key, source);
+    }
+
+    // Rewrite only when something changed, dropping entries for deleted files
+    let unchanged = current.files.len() == previous.files.len()
+        && current.files.iter().all(|(key, source)| previous.files.get(key).is_some_and(|p| p.hash == source.hash));
+    if !unchanged {
+        fs::create_dir_all(cache_dir)?;
+        fs::write(&cache_path, serde_json::to_vec(&current)?)?;
+    }
+
+    Ok(registry)
+}
+
+/// One cache file per source directory, so directories do not evict each other's entries
+fn manifest_cache_path(components_dir: &Path, cache_dir: &Path) -> std::path::PathBuf {
+    cache_dir.join(format!("manifests-{}.json", content_hash(&components_dir.display().to_string())))
+}
+
+/// Stable hex digest of file contents (64-bit FNV-1a)
+fn content_hash(content: &str) -> String {
+    let hash = content.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{:016x}", hash)
+}
+
 /// Extract component manifests from pre-generated JSON files in a manifests directory
 pub fn extract_manifests_from_manifests_dir(manifests_dir: &Path) -> Result<ComponentRegistry> {
-    let mut registry = ComponentRegistry {
-        components: HashMap::new(),
-        tag_to_name: HashMap::new(),
-    };
+    let mut registry = ComponentRegistry::default();
 
     if !manifests_dir.exists() {
         return Ok(registry);
@@ -49,8 +130,7 @@ pub fn extract_manifests_from_manifests_dir(manifests_dir: &Path) -> Result<Comp
         }
         if let Ok(content) = fs::read_to_string(path) {
             if let Ok(manifest) = serde_json::from_str::<ComponentManifest>(&content) {
-                registry.tag_to_name.insert(manifest.tag.clone(), manifest.name.clone());
-                registry.components.insert(manifest.name.clone(), manifest);
+                registry.insert(manifest, path);
             }
         }
     }
@@ -58,72 +138,432 @@ pub fn extract_manifests_from_manifests_dir(manifests_dir: &Path) -> Result<Comp
     Ok(registry)
 }
 
-/// Extract a component manifest from TypeScript source code
-fn extract_manifest_from_source(content: &str, file_path: &Path) -> Result<ComponentManifest> {
-    // Regular expression to match the @component decorator
-    let component_regex = Regex::new(r"@component\(\s*(\{[\s\S]*?\})\s*\)")?;
+/// Extract component manifests shipped by npm packages in a deck's `node_modules`
+///
+/// A package opts in with a `coolslides.manifest` field in its package.json (a
+/// manifest file, a directory of manifests, or a list of either), or by shipping
+/// `manifests/*.json`. Relative `module` paths are rewritten to `/node_modules/...`.
+pub fn extract_manifests_from_node_modules(deck_dir: &Path) -> Result<ComponentRegistry> {
+    let mut registry = ComponentRegistry::default();
+    let node_modules = deck_dir.join("node_modules");
+    if !node_modules.is_dir() {
+        return Ok(registry);
+    }
+
+    for package_dir in node_module_packages(&node_modules)? {
+        let Ok(package_json) = fs::read_to_string(package_dir.join("package.json")) else {
+            continue;
+        };
+        let Ok(package_json) = serde_json::from_str::<serde_json::Value>(&package_json) else {
+            continue;
+        };
+        let declared: Vec<&str> = match package_json.pointer("/coolslides/manifest") {
+            Some(serde_json::Value::String(path)) => vec![path.as_str()],
+            Some(serde_json::Value::Array(paths)) => paths.iter().filter_map(|p| p.as_str()).collect(),
+            _ => vec!["manifests"],
+        };
+
+        for manifest_path in declared.into_iter().map(|p| package_dir.join(p)) {
+            let files: Vec<std::path::PathBuf> = if manifest_path.is_dir() {
+                walkdir::WalkDir::new(&manifest_path)
+                    .max_depth(1)
+                    .into_iter()
+                    .filter_map(|e| e.ok())
+                    .map(|e| e.into_path())
+                    .filter(|p| p.extension().and_then(|s| s.to_str()) == Some("json"))
+                    .collect()
+            } else {
+                vec![manifest_path]
+            };
+            for file in files {
+                let Some(mut manifest) = fs::read_to_string(&file)
+                    .ok()
+                    .and_then(|content| serde_json::from_str::<ComponentManifest>(&content).ok())
+                else {
+                    continue;
+                };
+                if is_relative_module(&manifest.module) {
+                    let base = file.parent().unwrap_or(&package_dir);
+                    manifest.module = deck_url(&base.join(&manifest.module), deck_dir);
+                }
+                registry.insert(manifest, &file);
+            }
+        }
+    }
+
+    Ok(registry)
+}
+
+/// URL the dev server serves a deck's `components/` directory from
+pub const DECK_COMPONENTS_URL: &str = "/components/";
+
+/// Import map prefix for deck-local components, e.g. `@deck/components/Chart.js`
+pub const DECK_COMPONENTS_SPECIFIER: &str = "@deck/components/";
+
+/// Extract the components a deck carries in its own `components/` directory, from
+/// manifest JSON files or `@component` sources; relative `module` paths are
+/// rewritten to deck-root URLs. TypeScript sources load from the `.js` their
+/// build writes next to them.
+pub fn extract_manifests_from_local_dir(local_dir: &Path, cache_dir: &Path) -> Result<ComponentRegistry> {
+    let deck_dir = local_dir.parent().unwrap_or(local_dir);
+    let mut registry = ComponentRegistry::default();
+    let sources = extract_manifests_from_directory_cached(local_dir, cache_dir)?;
+    let manifests = extract_manifests_from_manifests_dir(local_dir)?;
+
+    for registry_part in [sources, manifests] {
+        for mut manifest in registry_part.components.into_values().chain(registry_part.versions.into_values().flatten()) {
+            let source = registry_part.sources.get(&manifest.name).cloned().unwrap_or_default();
+            if is_relative_module(&manifest.module) {
+                let base = Path::new(&source).parent().unwrap_or(local_dir);
+                let mut module = base.join(&manifest.module);
+                if module.extension().is_none() {
+                    module.set_extension("js");
+                }
+                manifest.module = deck_url(&module, deck_dir);
+            }
+            registry.insert(manifest, Path::new(&source));
+        }
+    }
+    Ok(registry)
+}
+
+/// Package directories in `node_modules`, including scoped `@scope/name` packages
+fn node_module_packages(node_modules: &Path) -> Result<Vec<std::path::PathBuf>> {
+    let mut packages = Vec::new();
+    for entry in fs::read_dir(node_modules)?.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') || !path.is_dir() {
+            continue;
+        }
+        if name.starts_with('@') {
+            packages.extend(fs::read_dir(&path)?.filter_map(|e| e.ok()).map(|e| e.path()).filter(|p| p.is_dir()));
+        } else {
+            packages.push(path);
+        }
+    }
+    packages.sort();
+    Ok(packages)
+}
+
+fn is_relative_module(module: &str) -> bool {
+    !module.starts_with('/') && !module.contains("://")
+}
+
+/// Root-relative URL for a file inside the deck, with `.` and `..` segments resolved
+fn deck_url(path: &Path, deck_dir: &Path) -> String {
+    let relative = path.strip_prefix(deck_dir).unwrap_or(path);
+    let mut segments: Vec<String> = Vec::new();
+    for component in relative.components() {
+        match component {
+            std::path::Component::Normal(part) => segments.push(part.to_string_lossy().to_string()),
+            std::path::Component::ParentDir => {
+                segments.pop();
+            }
+            _ => {}
+        }
+    }
+    format!("/{}", segments.join("/"))
+}
+
+/// Extract the manifests declared by `@component(...)` decorators in TypeScript source code
+fn extract_manifests_from_source(content: &str, file_path: &Path) -> Result<Vec<ComponentManifest>> {
+    let module = parse_typescript(content)
+        .map_err(|e| anyhow::anyhow!("Failed to parse {:?}: {}", file_path, e))?;
+    let evaluator = Evaluator::new(&module, content);
     
-    if let Some(captures) = component_regex.captures(content) {
-        let manifest_str = &captures[1];
-        
-        // Parse the JavaScript/TypeScript object literal as JSON5
-        let manifest_value: serde_json::Value = json5::from_str(manifest_str)
-            .map_err(|e| anyhow::anyhow!("Failed to parse component manifest in {:?}: {}", file_path, e))?;
-        
-        // Extract the required fields
-        let name = manifest_value.get("name")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| anyhow::anyhow!("Component manifest missing 'name' field in {:?}", file_path))?
-            .to_string();
-            
-        let version = manifest_value.get("version")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| anyhow::anyhow!("Component manifest missing 'version' field in {:?}", file_path))?
-            .to_string();
-            
-        let tag = manifest_value.get("tag")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| anyhow::anyhow!("Component manifest missing 'tag' field in {:?}", file_path))?
-            .to_string();
-            
-        let schema = manifest_value.get("schema")
-            .cloned()
-            .ok_or_else(|| anyhow::anyhow!("Component manifest missing 'schema' field in {:?}", file_path))?;
-        
-        // Optional fields
-        let tokens_used = manifest_value.get("tokensUsed")
-            .and_then(|v| v.as_array())
-            .map(|arr| arr.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect())
-            .unwrap_or_default();
-            
-        let capabilities = manifest_value.get("capabilities")
-            .and_then(|v| v.as_array())
-            .map(|arr| arr.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect())
-            .unwrap_or_default();
-            
-        let suggested_transition = manifest_value.get("suggestedTransition")
-            .and_then(|v| v.as_str())
-            .map(|s| s.to_string());
-        
-        // Generate module path relative to components directory
-        let module = format!("./{}", 
-            file_path.file_stem()
-                .and_then(|s| s.to_str())
-                .unwrap_or("unknown")
-        );
+    let mut manifests = Vec::new();
+    for class in decorated_classes(&module) {
+        for decorator in &class.decorators {
+            let Some(argument) = component_decorator_argument(&decorator.expr) else {
+                continue;
+            };
+            let manifest_value = evaluator.eval(argument)
+                .map_err(|e| anyhow::anyhow!("Failed to evaluate component manifest in {:?}: {}", file_path, e))?;
+            manifests.push(manifest_from_value(&manifest_value, file_path)?);
+        }
+    }
+    
+    if manifests.is_empty() {
+        return Err(anyhow::anyhow!("No @component decorator found in {:?}", file_path));
+    }
+    Ok(manifests)
+}
+
+/// Build a manifest from an evaluated `@component` argument
+fn manifest_from_value(manifest_value: &serde_json::Value, file_path: &Path) -> Result<ComponentManifest> {
+    // Extract the required fields
+    let name = manifest_value.get("name")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Component manifest missing 'name' field in {:?}", file_path))?
+        .to_string();
+        
+    let version = manifest_value.get("version")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Component manifest missing 'version' field in {:?}", file_path))?
+        .to_string();
+        
+    let tag = manifest_value.get("tag")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Component manifest missing 'tag' field in {:?}", file_path))?
+        .to_string();
+        
+    let schema = manifest_value.get("schema")
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("Component manifest missing 'schema' field in {:?}", file_path))?;
+    
+    // Optional fields
+    let tokens_used = manifest_value.get("tokensUsed")
+        .and_then(|v| v.as_array())
+        .map(|arr| arr.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect())
+        .unwrap_or_default();
+        
+    let capabilities = manifest_value.get("capabilities")
+        .and_then(|v| v.as_array())
+        .map(|arr| arr.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect())
+        .unwrap_or_default();
+        
+    let suggested_transition = manifest_value.get("suggestedTransition")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string());
+        
+    let slots = manifest_value.get("slots")
+        .cloned()
+        .map(serde_json::from_value)
+        .transpose()
+        .map_err(|e| anyhow::anyhow!("Invalid 'slots' in component manifest {:?}: {}", file_path, e))?;
+    
+    let deprecated = manifest_value.get("deprecated").and_then(Deprecation::from_value);
+    
+    // Generate module path relative to components directory
+    let module = format!("./{}", 
+        file_path.file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("unknown")
+    );
+    
+    Ok(ComponentManifest {
+        name,
+        version,
+        tag,
+        module,
+        schema,
+        tokens_used,
+        capabilities,
+        suggested_transition,
+        slots,
+        deprecated,
+    })
+}
+
+/// Parse TypeScript with decorators enabled
+fn parse_typescript(content: &str) -> Result<Module, String> {
+    // Positions start at 1; swc reserves 0 for synthesized nodes
+    let input = StringInput::new(content, BytePos(1), BytePos(1 + content.len() as u32));
+    let syntax = Syntax::Typescript(TsSyntax { decorators: true, ..Default::default() });
+    let mut parser = Parser::new(syntax, input, None);
+    let module = parser.parse_module().map_err(|e| {
+        format!("{} at line {}", e.kind().msg(), line_of(content, e.span().lo))
+    })?;
+    Ok(module)
+}
+
+/// Every class declared in the module, exported or not
+fn decorated_classes(module: &Module) -> impl Iterator<Item = &Class> {
+    module.body.iter().filter_map(|item| match item {
+        ModuleItem::Stmt(Stmt::Decl(Decl::Class(decl))) => Some(&*decl.class),
+        ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl { decl: Decl::Class(decl), .. })) => Some(&*decl.class),
+        ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultDecl(ExportDefaultDecl { decl: DefaultDecl::Class(class), .. })) => {
+            Some(&*class.class)
+        }
+        _ => None,
+    })
+}
+
+/// The object passed to a `@component(...)` decorator
+fn component_decorator_argument(expr: &Expr) -> Option<&Expr> {
+    let Expr::Call(call) = expr else { return None };
+    let Callee::Expr(callee) = &call.callee else { return None };
+    let is_component = match &**callee {
+        Expr::Ident(ident) => &*ident.sym == "component",
+        Expr::Member(member) => member.prop.is_ident_with("component"),
+        _ => false,
+    };
+    if !is_component {
+        return None;
+    }
+    call.args.first().filter(|arg| arg.spread.is_none()).map(|arg| &*arg.expr)
+}
+
+/// 1-based line of a parser position
+fn line_of(content: &str, pos: BytePos) -> usize {
+    let offset = (pos.0.saturating_sub(1) as usize).min(content.len());
+    content.as_bytes()[..offset].iter().filter(|b| **b == b'\n').count() + 1
+}
+
+/// Evaluates the constant subset of TypeScript a manifest can use: literals,
+/// objects, arrays, spreads, template strings, `+`, and top-level `const`s
+struct Evaluator<'a> {
+    constants: HashMap<&'a str, &'a Expr>,
+    content: &'a str,
+}
+
+/// How deeply constants may refer to other constants
+const MAX_CONSTANT_DEPTH: usize = 32;
+
+impl<'a> Evaluator<'a> {
+    fn new(module: &'a Module, content: &'a str) -> Self {
+        let mut constants = HashMap::new();
+        for item in &module.body {
+            let var = match item {
+                ModuleItem::Stmt(Stmt::Decl(Decl::Var(var))) => var,
+                ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl { decl: Decl::Var(var), .. })) => var,
+                _ => continue,
+            };
+            if var.kind != VarDeclKind::Const {
+                continue;
+            }
+            for declarator in &var.decls {
+                if let (Pat::Ident(binding), Some(init)) = (&declarator.name, &declarator.init) {
+                    constants.insert(&*binding.id.sym, &**init);
+                }
+            }
+        }
+        Self { constants, content }
+    }
+    
+    fn eval(&self, expr: &Expr) -> Result<serde_json::Value, String> {
+        self.eval_at(expr, 0)
+    }
+    
+    fn eval_at(&self, expr: &Expr, depth: usize) -> Result<serde_json::Value, String> {
+        use serde_json::Value;
         
-        Ok(ComponentManifest {
-            name,
-            version,
-            tag,
-            module,
-            schema,
-            tokens_used,
-            capabilities,
-            suggested_transition,
-        })
+        let unsupported = |what: &str, span: Span| format!("unsupported {} at line {}", what, line_of(self.content, span.lo));
+        match expr {
+            Expr::Lit(Lit::Str(s)) => Ok(Value::String(s.value.to_string_lossy().into_owned())),
+            Expr::Lit(Lit::Bool(b)) => Ok(Value::Bool(b.value)),
+            Expr::Lit(Lit::Null(_)) => Ok(Value::Null),
+            Expr::Lit(Lit::Num(n)) => Ok(number(n.value)),
+            Expr::Paren(e) => self.eval_at(&e.expr, depth),
+            Expr::TsAs(e) => self.eval_at(&e.expr, depth),
+            Expr::TsConstAssertion(e) => self.eval_at(&e.expr, depth),
+            Expr::TsSatisfies(e) => self.eval_at(&e.expr, depth),
+            Expr::TsNonNull(e) => self.eval_at(&e.expr, depth),
+            Expr::Ident(ident) => self.constant(&ident.sym, ident.span, depth),
+            Expr::Unary(unary) if unary.op == UnaryOp::Minus => match self.eval_at(&unary.arg, depth)? {
+                Value::Number(n) => Ok(number(-n.as_f64().unwrap_or_default())),
+                _ => Err(unsupported("negation of a non-number", unary.span)),
+            },
+            Expr::Bin(bin) if bin.op == BinaryOp::Add => {
+                match (self.eval_at(&bin.left, depth)?, self.eval_at(&bin.right, depth)?) {
+                    (Value::Number(a), Value::Number(b)) => {
+                        Ok(number(a.as_f64().unwrap_or_default() + b.as_f64().unwrap_or_default()))
+                    }
+                    (a, b) => Ok(Value::String(format!("{}{}", display(&a), display(&b)))),
+                }
+            }
+            Expr::Tpl(tpl) => {
+                let mut out = String::new();
+                for (i, quasi) in tpl.quasis.iter().enumerate() {
+                    let cooked = quasi.cooked.as_ref().ok_or_else(|| unsupported("template escape", quasi.span))?;
+                    out.push_str(&cooked.to_string_lossy());
+                    if let Some(expr) = tpl.exprs.get(i) {
+                        out.push_str(&display(&self.eval_at(expr, depth)?));
+                    }
+                }
+                Ok(Value::String(out))
+            }
+            Expr::Array(array) => {
+                let mut items = Vec::new();
+                for element in &array.elems {
+                    let Some(element) = element else {
+                        return Err(unsupported("array hole", array.span));
+                    };
+                    let value = self.eval_at(&element.expr, depth)?;
+                    match (element.spread, value) {
+                        (None, value) => items.push(value),
+                        (Some(_), Value::Array(spread)) => items.extend(spread),
+                        (Some(span), _) => return Err(unsupported("spread of a non-array", span)),
+                    }
+                }
+                Ok(Value::Array(items))
+            }
+            Expr::Object(object) => {
+                let mut map = serde_json::Map::new();
+                for prop in &object.props {
+                    match prop {
+                        PropOrSpread::Spread(spread) => match self.eval_at(&spread.expr, depth)? {
+                            Value::Object(fields) => map.extend(fields),
+                            _ => return Err(unsupported("spread of a non-object", spread.dot3_token)),
+                        },
+                        PropOrSpread::Prop(prop) => match &**prop {
+                            Prop::KeyValue(kv) => {
+                                let key = self.prop_name(&kv.key, depth)?;
+                                map.insert(key, self.eval_at(&kv.value, depth)?);
+                            }
+                            Prop::Shorthand(ident) => {
+                                map.insert(ident.sym.to_string(), self.constant(&ident.sym, ident.span, depth)?);
+                            }
+                            _ => return Err(unsupported("method or accessor", object.span)),
+                        },
+                    }
+                }
+                Ok(Value::Object(map))
+            }
+            Expr::Member(member) => {
+                let object = self.eval_at(&member.obj, depth)?;
+                let key = match &member.prop {
+                    MemberProp::Ident(name) => name.sym.to_string(),
+                    MemberProp::Computed(computed) => display(&self.eval_at(&computed.expr, depth)?),
+                    MemberProp::PrivateName(_) => return Err(unsupported("private member", member.span)),
+                };
+                let value = match &object {
+                    Value::Object(map) => map.get(&key),
+                    Value::Array(items) => key.parse::<usize>().ok().and_then(|i| items.get(i)),
+                    _ => None,
+                };
+                value.cloned().ok_or_else(|| unsupported(&format!("member '{}'", key), member.span))
+            }
+            other => Err(unsupported("expression", other.span())),
+        }
+    }
+    
+    fn constant(&self, name: &str, span: Span, depth: usize) -> Result<serde_json::Value, String> {
+        if depth >= MAX_CONSTANT_DEPTH {
+            return Err(format!("constant '{}' at line {} refers to itself", name, line_of(self.content, span.lo)));
+        }
+        let expr = self.constants.get(name).ok_or_else(|| {
+            format!("'{}' at line {} is not a top-level const in this file", name, line_of(self.content, span.lo))
+        })?;
+        self.eval_at(expr, depth + 1)
+    }
+    
+    fn prop_name(&self, key: &PropName, depth: usize) -> Result<String, String> {
+        match key {
+            PropName::Ident(ident) => Ok(ident.sym.to_string()),
+            PropName::Str(s) => Ok(s.value.to_string_lossy().into_owned()),
+            PropName::Num(n) => Ok(display(&number(n.value))),
+            PropName::Computed(computed) => Ok(display(&self.eval_at(&computed.expr, depth)?)),
+            PropName::BigInt(b) => Ok(b.value.to_string()),
+        }
+    }
+}
+
+/// JSON number, keeping integers integral
+fn number(value: f64) -> serde_json::Value {
+    if value.fract() == 0.0 && value.abs() < i64::MAX as f64 {
+        serde_json::Value::from(value as i64)
     } else {
-        Err(anyhow::anyhow!("No @component decorator found in {:?}", file_path))
+        serde_json::Value::from(value)
+    }
+}
+
+/// String form of a value as JavaScript would concatenate it
+fn display(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
     }
 }
 
@@ -164,7 +604,8 @@ export class TitleSlide extends CoolslidesElement {
 }
         "#;
         
-        let manifest = extract_manifest_from_source(source, Path::new("TitleSlide.ts")).unwrap();
+        let manifests = extract_manifests_from_source(source, Path::new("TitleSlide.ts")).unwrap();
+        let manifest = &manifests[0];
         
         assert_eq!(manifest.name, "TitleSlide");
         assert_eq!(manifest.version, "1.0.0");
@@ -172,4 +613,126 @@ export class TitleSlide extends CoolslidesElement {
         assert_eq!(manifest.tokens_used.len(), 2);
         assert!(manifest.schema.is_object());
     }
+    
+    #[test]
+    fn test_extract_handles_comments_constants_and_multiple_decorators() {
+        let source = r#"
+import { component } from '@coolslides/component-sdk';
+
+const VERSION = '2.1.0';
+const PREFIX = 'cs';
+export const baseProps = { title: { type: 'string' } } as const;
+
+@component({
+  name: 'Quote', // inline comment with a } brace
+  version: VERSION,
+  tag: `${PREFIX}-quote`,
+  /* block comment: @component({ nope }) */
+  schema: {
+    type: 'object',
+    properties: { ...baseProps, cite: { type: 'string', default: '})' } }
+  },
+  tokensUsed: ['--quote-' + 'color']
+})
+export class Quote extends HTMLElement {}
+
+@component({ name: 'Aside', version: VERSION, tag: PREFIX + '-aside', schema: { type: 'object', properties: {} } })
+class Aside extends HTMLElement {}
+        "#;
+        
+        let manifests = extract_manifests_from_source(source, Path::new("Quote.ts")).unwrap();
+        assert_eq!(manifests.len(), 2);
+        assert_eq!(manifests[0].version, "2.1.0");
+        assert_eq!(manifests[0].tag, "cs-quote");
+        assert_eq!(manifests[0].tokens_used, vec!["--quote-color"]);
+        assert_eq!(manifests[0].schema["properties"]["cite"]["default"], "})");
+        assert!(manifests[0].schema["properties"]["title"].is_object());
+        assert_eq!(manifests[1].tag, "cs-aside");
+        
+        let unknown = "@component({ name: NAME, version: '1', tag: 'x-y', schema: {} })\nclass A {}";
+        let error = extract_manifests_from_source(unknown, Path::new("A.ts")).unwrap_err().to_string();
+        assert!(error.contains("'NAME' at line 1 is not a top-level const"), "{}", error);
+    }
+    
+    #[test]
+    fn test_extract_manifests_from_node_modules() {
+        let deck = std::env::temp_dir().join(format!("coolslides-node-modules-{}", std::process::id()));
+        let manifest = |name: &str, tag: &str, module: &str| {
+            serde_json::json!({ "name": name, "version": "1.0.0", "tag": tag, "module": module, "schema": {} }).to_string()
+        };
+        let scoped = deck.join("node_modules/@acme/charts");
+        fs::create_dir_all(scoped.join("dist")).unwrap();
+        fs::write(scoped.join("package.json"), r#"{ "name": "@acme/charts", "coolslides": { "manifest": "dist/chart.json" } }"#).unwrap();
+        fs::write(scoped.join("dist/chart.json"), manifest("Chart", "acme-chart", "./chart.js")).unwrap();
+        let plain = deck.join("node_modules/cs-maps");
+        fs::create_dir_all(plain.join("manifests")).unwrap();
+        fs::write(plain.join("package.json"), r#"{ "name": "cs-maps" }"#).unwrap();
+        fs::write(plain.join("manifests/Map.component.json"), manifest("Map", "cs-map", "https://cdn.example.com/map.js")).unwrap();
+        fs::create_dir_all(deck.join("node_modules/lodash")).unwrap();
+        fs::write(deck.join("node_modules/lodash/package.json"), r#"{ "name": "lodash" }"#).unwrap();
+        
+        let registry = extract_manifests_from_node_modules(&deck).unwrap();
+        fs::remove_dir_all(&deck).unwrap();
+        
+        assert_eq!(registry.components.len(), 2);
+        assert_eq!(registry.components["Chart"].module, "/node_modules/@acme/charts/dist/chart.js");
+        assert_eq!(registry.components["Map"].module, "https://cdn.example.com/map.js");
+        assert_eq!(registry.tag_to_name["acme-chart"], "Chart");
+    }
+    
+    #[test]
+    fn test_deck_local_components_load_from_deck_urls() {
+        let deck = std::env::temp_dir().join(format!("coolslides-local-{}", std::process::id()));
+        let local = deck.join("components");
+        fs::create_dir_all(local.join("charts")).unwrap();
+        fs::write(
+            local.join("Callout.ts"),
+            "@component({ name: 'Callout', version: '1.0.0', tag: 'deck-callout', schema: { type: 'object' } })\nexport class Callout {}",
+        )
+        .unwrap();
+        fs::write(
+            local.join("charts/Bar.component.json"),
+            r#"{"name":"Bar","version":"1.0.0","tag":"deck-bar","module":"./dist/bar.js","schema":{}}"#,
+        )
+        .unwrap();
+
+        let registry = extract_manifests_from_local_dir(&local, &deck.join(".coolslides/cache")).unwrap();
+        fs::remove_dir_all(&deck).unwrap();
+        assert_eq!(registry.components["Callout"].module, "/components/Callout.js");
+        assert_eq!(registry.components["Bar"].module, "/components/charts/dist/bar.js");
+    }
+
+    #[test]
+    fn test_manifest_cache_reuses_unchanged_files() {
+        let root = std::env::temp_dir().join(format!("coolslides-manifest-cache-{}", std::process::id()));
+        let (src, cache) = (root.join("src"), root.join("cache"));
+        fs::create_dir_all(&src).unwrap();
+        let source = |version: &str| format!(
+            "@component({{ name: 'Card', version: '{}', tag: 'cs-card', schema: {{}} }})\nexport class Card {{}}",
+            version
+        );
+        fs::write(src.join("Card.ts"), source("1.0.0")).unwrap();
+        fs::write(src.join("util.ts"), "export const x = 1;").unwrap();
+        
+        let first = extract_manifests_from_directory_cached(&src, &cache).unwrap();
+        assert_eq!(first.components["Card"].version, "1.0.0");
+        let cached: ManifestCache = serde_json::from_str(&fs::read_to_string(manifest_cache_path(&src, &cache)).unwrap()).unwrap();
+        assert_eq!(cached.files.len(), 2);
+        
+        // A cache entry is trusted while the file hash matches
+        let mut tampered = cached;
+        for entry in tampered.files.values_mut() {
+            for manifest in &mut entry.manifests {
+                manifest.tag = "cs-cached".to_string();
+            }
+        }
+        fs::write(manifest_cache_path(&src, &cache), serde_json::to_vec(&tampered).unwrap()).unwrap();
+        assert_eq!(extract_manifests_from_directory_cached(&src, &cache).unwrap().components["Card"].tag, "cs-cached");
+        
+        fs::write(src.join("Card.ts"), source("1.1.0")).unwrap();
+        let changed = extract_manifests_from_directory_cached(&src, &cache).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(changed.components["Card"].version, "1.1.0");
+        assert_eq!(changed.components["Card"].tag, "cs-card");
+    }
 }
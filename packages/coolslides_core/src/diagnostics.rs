@@ -0,0 +1,437 @@
+use serde::Serialize;
+
+/// Documentation for a diagnostic code, shown by `coolslides explain` and in JSON output
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct DiagnosticInfo {
+    /// Diagnostic code (e.g. "CS1001")
+    pub code: &'static str,
+    /// One-line summary
+    pub title: &'static str,
+    /// What the diagnostic means and why it matters
+    pub description: &'static str,
+    /// Minimal input that triggers it
+    pub example: &'static str,
+    /// How to resolve it
+    pub fix: &'static str,
+}
+
+/// All documented diagnostic codes, sorted by code
+pub const CATALOG: &[DiagnosticInfo] = &[
+    DiagnosticInfo {
+        code: "CS1001",
+        title: "Duplicate slide id",
+        description: "Two slide files declare the same `id`. Sequence references and room sync address slides by id, so only one of them can ever be shown.",
+        example: "# content/a.slide.toml\nid = \"intro\"\n\n# content/b.slide.toml\nid = \"intro\"",
+        fix: "Give every slide file a unique `id` and update `sequence` in slides.toml to match.",
+    },
+    DiagnosticInfo {
+        code: "CS1002",
+        title: "Unknown slide reference",
+        description: "The deck `sequence` (or a group inside it) references a slide id that no file in content/ declares. The slide is silently skipped when rendering.",
+        example: "[[sequence]]\ntype = \"ref\"\nref = \"intorduction\"",
+        fix: "Correct the id in slides.toml or create the missing content/<id>.slide.toml.",
+    },
+    DiagnosticInfo {
+        code: "CS1003",
+        title: "Invalid style override key",
+        description: "Keys in `styleOverrides` become CSS custom properties on the slide and must start with `--`.",
+        example: "[styleOverrides]\n\"title-color\" = \"#007acc\"",
+        fix: "Prefix the key with `--`, e.g. \"--title-color\".",
+    },
+    DiagnosticInfo {
+        code: "CS1004",
+        title: "Invalid model version",
+        description: "The `modelVersion` of a slide, deck, or lockfile is not one this version of Coolslides understands.",
+        example: "modelVersion = \"2.0\"",
+        fix: "Set `modelVersion = \"1.0\"`.",
+    },
+    DiagnosticInfo {
+        code: "CS1005",
+        title: "Empty markdown slot",
+        description: "A markdown slot has an empty `value`, so it renders nothing.",
+        example: "[slots.body]\nkind = \"markdown\"\nvalue = \"\"",
+        fix: "Fill in the slot content or remove the slot.",
+    },
+    DiagnosticInfo {
+        code: "CS1006",
+        title: "Empty slot component tag or module",
+        description: "A component slot is missing its `tag` or `module`, so the runtime cannot create or load the element.",
+        example: "[slots.right]\nkind = \"component\"\ntag = \"cs-poll\"\nmodule = \"\"",
+        fix: "Set both `tag` and `module` for the slot component.",
+    },
+    DiagnosticInfo {
+        code: "CS1007",
+        title: "Unknown transition",
+        description: "A transition named in `[transitions]` or on a sequence group is neither built in (none, fade, slide, zoom) nor registered by a plugin in the lockfile. The runtime falls back to no transition.",
+        example: "[transitions]\ndefault = \"fde\"",
+        fix: "Fix the name, or add the plugin that provides the transition and list it under `transitions` in its lockfile entry.",
+    },
+    DiagnosticInfo {
+        code: "CS1008",
+        title: "Unknown condition tag",
+        description: "`conditions.includeTags` names a tag that no slide carries. Include filters keep only matching slides, so an unused tag matches nothing, and if every listed tag is unused the deck renders empty.",
+        example: "[conditions]\nincludeTags = [\"advnaced\"]",
+        fix: "Fix the tag name or add it to the `tags` of the slides it should select.",
+    },
+    DiagnosticInfo {
+        code: "CS1009",
+        title: "Unknown condition slide id",
+        description: "`conditions.excludeIds` names a slide id that no slide declares, so the exclusion has no effect.",
+        example: "[conditions]\nexcludeIds = [\"bonus-slid\"]",
+        fix: "Correct the id or remove it from `excludeIds`.",
+    },
+    DiagnosticInfo {
+        code: "CS1010",
+        title: "Unknown print template placeholder",
+        description: "A print header or footer template uses a `{placeholder}` the exporter does not fill in, so it is printed literally on every page. Known placeholders: pageNumber, totalPages, title, date, url.",
+        example: "[print]\nfooterTemplate = \"Page {page} of {totalPages}\"",
+        fix: "Use one of the known placeholders, e.g. `{pageNumber}`.",
+    },
+    DiagnosticInfo {
+        code: "CS1011",
+        title: "Malformed print template",
+        description: "A print header or footer template has an unclosed `{` or a stray `}`.",
+        example: "[print]\nfooterTemplate = \"Page {pageNumber of {totalPages}\"",
+        fix: "Close every placeholder; write `{{` or `}}` for literal braces.",
+    },
+    DiagnosticInfo {
+        code: "CS1012",
+        title: "Page number placeholder with page numbers disabled",
+        description: "The footer template uses `{pageNumber}` or `{totalPages}` while `print.pageNumbers = false`, so the placeholder renders empty.",
+        example: "[print]\npageNumbers = false\nfooterTemplate = \"Page {pageNumber}\"",
+        fix: "Enable `pageNumbers` or remove the placeholder.",
+    },
+    DiagnosticInfo {
+        code: "CS1013",
+        title: "Notes for an unknown slide",
+        description: "A key in the deck-level `[notes]` table does not match any slide id, so those speaker notes never show.",
+        example: "[notes]\nintorduction = \"Welcome everyone\"",
+        fix: "Rename the key to the slide's `id`.",
+    },
+    DiagnosticInfo {
+        code: "CS1014",
+        title: "Duplicate slot id",
+        description: "A component slot's `slot_id` is already used by another slot or by a slide id. The runtime targets slots and slides by id, so every one must be unique across the deck.",
+        example: "[slots.chart]\nkind = \"component\"\ntag = \"cs-chart\"\nmodule = \"/components/chart.js\"\nslot_id = \"intro\"  # same as a slide id",
+        fix: "Rename one of the ids; the message names both locations.",
+    },
+    DiagnosticInfo {
+        code: "CS1015",
+        title: "Invalid narration",
+        description: "A slide's `[narration]` cannot be played: `src` is empty or not an audio file (mp3, m4a, aac, wav, ogg, oga, opus, flac), or `duration` is not a positive number of seconds. The narrated player and video export advance by `duration`.",
+        example: "[narration]\nsrc = \"audio/intro.mp3\"\nduration = 0",
+        fix: "Set `duration` to the length of the recording in seconds and point `src` at an audio file.",
+    },
+    DiagnosticInfo {
+        code: "CS1016",
+        title: "Missing narration file",
+        description: "A slide's narration `src` names a file that is not in the deck directory, so it cannot be served or muxed into the video export.",
+        example: "[narration]\nsrc = \"audio/intro.mp3\"  # with no audio/intro.mp3 in the deck\nduration = 42",
+        fix: "Add the recording at that path or fix `src`. Remote narration must be an absolute http(s) URL.",
+    },
+    DiagnosticInfo {
+        code: "CS1901",
+        title: "Custom lint rule violated",
+        description: "A rule declared under `[[lint.custom]]` in slides.toml matched this slide.",
+        example: "[[lint.custom]]\nrule = \"forbid-tag\"\ntag = \"marquee\"",
+        fix: "Change the slide to satisfy the rule, or adjust the rule's severity in slides.toml.",
+    },
+    DiagnosticInfo {
+        code: "CS2001",
+        title: "Component version conflict",
+        description: "Slides request version ranges of the same component that no single available version satisfies.",
+        example: "# a.slide.toml\nversionReq = \"^1\"\n\n# b.slide.toml\nversionReq = \"^2\"",
+        fix: "Align the `versionReq` of slides using the component, or install a version satisfying all ranges.",
+    },
+    DiagnosticInfo {
+        code: "CS2002",
+        title: "Unresolved plugin",
+        description: "A `plugins` entry in slides.toml is a bare specifier that neither importmap.json nor the lockfile maps, or a relative path that does not exist.",
+        example: "plugins = [\"@acme/confetti\"]",
+        fix: "Run `coolslides add plugin <package>` or fix the path so the plugin resolves.",
+    },
+    DiagnosticInfo {
+        code: "CS2003",
+        title: "Unpinned import",
+        description: "importmap.json maps a specifier that .coolslides.lock does not record (or records with a different URL), so builds are not reproducible.",
+        example: "// importmap.json\n{ \"imports\": { \"@acme/confetti\": \"https://cdn.example.com/confetti.js\" } }",
+        fix: "Re-run `coolslides add` for the package so the lockfile is updated.",
+    },
+    DiagnosticInfo {
+        code: "CS2004",
+        title: "Missing lockfile entry",
+        description: "A plugin or component used by the deck is loaded from a package or URL but has no resolved entry in .coolslides.lock.",
+        example: "plugins = [\"@coolslides/plugins-stdlib\"]  # with an empty resolved.plugins",
+        fix: "Run `coolslides add plugin|component <package>` to pin it.",
+    },
+    DiagnosticInfo {
+        code: "CS2005",
+        title: "Unused plugin",
+        description: "importmap.json maps a plugin that nothing in slides.toml `plugins` loads. Entries count as plugins when the lockfile pins them as plugins or their package name contains `plugin`.",
+        example: "plugins = []  # while importmap.json maps @acme/confetti",
+        fix: "Add the plugin to `plugins` in slides.toml, or remove it from importmap.json and the lockfile.",
+    },
+    DiagnosticInfo {
+        code: "CS2006",
+        title: "Orphaned lockfile entry",
+        description: "The lockfile pins an import, plugin, or component that the deck no longer uses.",
+        example: "// .coolslides.lock\n\"resolved\": { \"components\": { \"OldSlide\": { ... } } }",
+        fix: "Remove the stale entry from .coolslides.lock.",
+    },
+    DiagnosticInfo {
+        code: "CS3001",
+        title: "Unknown component",
+        description: "A slide uses a component name that is not in the component registry. It renders as a placeholder element.",
+        example: "[component]\nname = \"TitelSlide\"",
+        fix: "Fix the component name or install the package that provides it.",
+    },
+    DiagnosticInfo {
+        code: "CS3002",
+        title: "Invalid component props",
+        description: "The slide's `props` do not match the JSON schema declared by the component manifest. String `format`s are checked too: `uri` must be an absolute http(s), data, blob, mailto, or tel URL, `color` a CSS color, and `date-time` an RFC 3339 timestamp.",
+        example: "[props]\nalignment = \"middle\"  # enum allows left|center|right",
+        fix: "Adjust the prop value at the reported JSON path to satisfy the schema.",
+    },
+    DiagnosticInfo {
+        code: "CS3003",
+        title: "Missing required prop",
+        description: "The component schema lists a prop as required, but the slide does not set it.",
+        example: "[component]\nname = \"TitleSlide\"\n\n[props]\nsubtitle = \"No title here\"",
+        fix: "Add the missing prop under `[props]`.",
+    },
+    DiagnosticInfo {
+        code: "CS3004",
+        title: "Custom element tag collision",
+        description: "Two component manifests declare the same custom element tag. Only one can be registered with the browser, so the other component's slides render with the wrong element.",
+        example: "// TitleSlide.component.json and FancyTitle.component.json\n\"tag\": \"cs-title-slide\"",
+        fix: "Rename the tag of one component so every tag is unique, or set `tagCollisions = \"prefix\"` under `[components]` in slides.toml to rename tags from lower-precedence sources automatically.",
+    },
+    DiagnosticInfo {
+        code: "CS3005",
+        title: "Undeclared slot",
+        description: "The slide fills a slot the component does not declare in its manifest `slots`. The component has no matching `<slot>`, so the content never renders.",
+        example: "[component]\nname = \"TwoColSlide\"\n\n[slots.middle]\nkind = \"markdown\"\nvalue = \"Lost content\"",
+        fix: "Rename the slot to one the component declares (listed in the message).",
+    },
+    DiagnosticInfo {
+        code: "CS3006",
+        title: "Empty required slot",
+        description: "The component marks a slot as required, but the slide leaves it out or fills it with nothing.",
+        example: "# component declares { name: 'body', required: true }\n[slots.body]\nkind = \"markdown\"\nvalue = \"\"",
+        fix: "Provide content for the slot.",
+    },
+    DiagnosticInfo {
+        code: "CS3007",
+        title: "Unused style override",
+        description: "A `styleOverrides` key is not in the component's `tokensUsed`, so setting it does not change how the slide looks (the theme may still use it elsewhere).",
+        example: "[component]\nname = \"TitleSlide\"\n\n[styleOverrides]\n\"--heading-color\" = \"red\"  # TitleSlide reads --title-color",
+        fix: "Use one of the tokens listed in the component manifest's `tokensUsed`.",
+    },
+    DiagnosticInfo {
+        code: "CS3008",
+        title: "Shadowed component",
+        description: "Two registry layers define the same version of a component with different tags. The higher layer wins (deck components, then workspace packages, then node_modules, then the remote registry), so the other manifest is ignored. Different versions are not a conflict; they stay available through `versionReq`.",
+        example: "components/Chart.component.json           # Chart 1.0.0, tag acme-chart, used\nnode_modules/charts/manifests/Chart.json  # Chart 1.0.0, tag cs-chart, ignored",
+        fix: "Remove or rename one of the components, or accept the override.",
+    },
+    DiagnosticInfo {
+        code: "CS3009",
+        title: "Slot content kind not accepted",
+        description: "The component's manifest limits which kinds of content a slot takes with `accepts`, and the slide fills it with another kind (for example markdown in a slot that only renders components).",
+        example: "# component declares { name: 'chart', accepts: ['component'] }\n[slots.chart]\nkind = \"markdown\"\nvalue = \"Sales by region\"",
+        fix: "Fill the slot with one of the kinds listed in the message, or move the content to another slot.",
+    },
+    DiagnosticInfo {
+        code: "CS3010",
+        title: "Unsatisfied version requirement",
+        description: "The registry has the component, but none of its registered versions matches the slide's `versionReq` (a semver range such as `^1` or `>=1.2, <2`). An unparseable range matches nothing.",
+        example: "[component]\nname = \"TitleSlide\"\nversionReq = \"^2\"  # only 1.0.0 is registered",
+        fix: "Change `versionReq` to match an available version (listed in the message), or add the required version of the component.",
+    },
+    DiagnosticInfo {
+        code: "CS3011",
+        title: "Deprecated component",
+        description: "The version of the component a slide resolves to is marked `deprecated` in its manifest. It still renders, but it may be removed in a later release.",
+        example: "// Callout.component.json\n\"deprecated\": { \"message\": \"Replaced by Aside\", \"replacement\": \"Aside\" }",
+        fix: "Switch the slide to the replacement component named in the message, or narrow `versionReq` to a version that is not deprecated.",
+    },
+    DiagnosticInfo {
+        code: "CS3012",
+        title: "Deprecated prop",
+        description: "A slide sets a prop whose schema property is marked `deprecated`, either `true` or an object with `message` and `replacement`.",
+        example: "// Schema property\n\"color\": { \"type\": \"string\", \"deprecated\": { \"replacement\": \"tone\" } }",
+        fix: "Move the value to the replacement prop named in the message. `validate --fix` suggests the rename but does not apply it unattended.",
+    },
+    DiagnosticInfo {
+        code: "CS4001",
+        title: "Unclosed code fence",
+        description: "A ``` or ~~~ code fence in a markdown slot is never closed, so everything after it renders as code.",
+        example: "value = \"\"\"\n```rust\nfn main() {}\n\nMore text that is now code\n\"\"\"",
+        fix: "Close the fence with a line of the same fence characters, at least as long as the opening.",
+    },
+    DiagnosticInfo {
+        code: "CS4002",
+        title: "Broken reference link",
+        description: "A `[text][label]` or `[label][]` link has no matching `[label]: url` definition in the slot, so it renders as plain bracketed text.",
+        example: "See the [docs][manual].",
+        fix: "Add a `[manual]: https://...` definition to the same slot or use an inline link.",
+    },
+    DiagnosticInfo {
+        code: "CS4003",
+        title: "Image without alt text",
+        description: "A markdown image has empty alt text. Screen readers and exported handouts have nothing to describe it with.",
+        example: "![](diagram.png)",
+        fix: "Describe the image: `![Request flow diagram](diagram.png)`.",
+    },
+    DiagnosticInfo {
+        code: "CS4004",
+        title: "Raw HTML removed in strict mode",
+        description: "A markdown slot contains a raw HTML tag outside the strict-mode allowlist (p, br, strong, em, code, pre, h1-h6, ul, ol, li, blockquote). `coolslides dev --strict` and strict exports strip it, so embeds silently disappear.",
+        example: "<iframe src=\"https://www.youtube.com/embed/...\"></iframe>",
+        fix: "Use a component slot for embeds, or set `CS4004 = \"off\"` under [lint.rules] if the deck is never presented in strict mode.",
+    },
+    DiagnosticInfo {
+        code: "CS4005",
+        title: "Props over budget",
+        description: "A slide's props serialize to more bytes than `[lint.budgets].maxPropsBytes` (64 KiB by default). Props are inlined into the page, so large ones slow the first render.",
+        example: "[props]\ncode = \"\"\"<thousands of lines>\"\"\"",
+        fix: "Load large content from a file (e.g. CodeSlide `source`), or raise the budget.",
+    },
+    DiagnosticInfo {
+        code: "CS4006",
+        title: "Markdown slot over budget",
+        description: "A markdown slot is longer than `[lint.budgets].maxMarkdownChars` (10,000 by default). Text that long rarely fits a slide and slows rendering.",
+        example: "[slots.body]\nkind = \"markdown\"\nvalue = \"\"\"<an entire article>\"\"\"",
+        fix: "Split the content across slides or move it to speaker notes.",
+    },
+    DiagnosticInfo {
+        code: "CS4007",
+        title: "Asset over budget",
+        description: "A local file the deck loads (theme, tokens, an image in markdown, or a file named by a prop) is larger than `[lint.budgets].maxAssetBytes` (5 MiB by default).",
+        example: "![Team photo](photos/team-raw.png)  # 24 MB",
+        fix: "Compress or resize the asset, or raise the budget.",
+    },
+    DiagnosticInfo {
+        code: "CS4008",
+        title: "Group over budget",
+        description: "A sequence group contains more slides than `[lint.budgets].maxSlidesPerGroup` (30 by default).",
+        example: "[[sequence]]\ntype = \"group\"\nname = \"appendix\"\nslides = [<40 slide ids>]",
+        fix: "Split the group, or raise the budget.",
+    },
+    DiagnosticInfo {
+        code: "CS4009",
+        title: "Possible misspelling",
+        description: "The spelling pass (`[lint.spelling]` or `validate --spellcheck`) found a word in a markdown slot or text prop that is not in the dictionary for the slide's locale.",
+        example: "[slots.body]\nkind = \"markdown\"\nvalue = \"Teh results\"",
+        fix: "Correct the word, or add it to `[lint.spelling].allow`.",
+    },
+    DiagnosticInfo {
+        code: "CS4010",
+        title: "Missing dictionary",
+        description: "No Hunspell `.aff`/`.dic` pair was found for a locale used by the deck, so its slides were not spell-checked. Dictionaries are looked up in the deck's `dictionaries/` directory, `[lint.spelling].dictionaryDirs`, `DICPATH`, and the system Hunspell directories.",
+        example: "locale = \"de-DE\"  # with no de_DE.aff/de_DE.dic available",
+        fix: "Install the dictionary or copy it into `dictionaries/`.",
+    },
+    DiagnosticInfo {
+        code: "CS5001",
+        title: "Room message is not JSON",
+        description: "A client sent a room WebSocket message that is not JSON text, or a binary message. Rooms only speak JSON text, so the message was not acted on.",
+        example: "ws.send(new Blob([\"next\"]))",
+        fix: "Send `JSON.stringify({ type: ..., ... })` as a text message.",
+    },
+    DiagnosticInfo {
+        code: "CS5002",
+        title: "Unknown room message type",
+        description: "A room message has no string `type`, or one the room protocol does not have. Clients may send `join`, `event`, `ack`, `vote`, `ask`, `upvote`, `react`, and `heartbeat`.",
+        example: "{ \"type\": \"shout\" }",
+        fix: "Use one of the client message types listed in docs/remote.md.",
+    },
+    DiagnosticInfo {
+        code: "CS5003",
+        title: "Invalid room message fields",
+        description: "A room message has fields missing, or of the wrong type, for its `type`. The error's message names the field.",
+        example: "{ \"type\": \"vote\", \"option\": 1 }  # no poll_id",
+        fix: "Send every field the message type needs, with the right types.",
+    },
+    DiagnosticInfo {
+        code: "CS5004",
+        title: "Server-only room message",
+        description: "A client sent a message type only the server sends, such as `state` or `session`. Clients change room state through events instead.",
+        example: "{ \"type\": \"state\", \"data\": {} }",
+        fix: "Send an `event` (for example `slide:change`) and let the room broadcast the new state.",
+    },
+    DiagnosticInfo {
+        code: "CS5005",
+        title: "Presenter-only event",
+        description: "An audience client sent an event only presenters may send, such as `presenter:*`, `slide:change`, or `poll:create`. The room ignored it.",
+        example: "{ \"type\": \"event\", \"event\": { \"name\": \"poll:create\", ... } }  # from an audience socket",
+        fix: "Join as presenter with the presenter token to drive the room.",
+    },
+    DiagnosticInfo {
+        code: "CS5006",
+        title: "Presenter token required",
+        description: "A client joined as presenter without the server's presenter token, or with a wrong one. It stays in the room as audience.",
+        example: "{ \"type\": \"join\", \"role\": \"presenter\", \"client_id\": \"p\" }  # no token",
+        fix: "Send the presenter token printed at startup in the `join` message, or connect with `?role=presenter&token=<token>`.",
+    },
+    DiagnosticInfo {
+        code: "CS5007",
+        title: "Room full",
+        description: "The room already has `[rooms] maxClients` clients, so the new audience client was turned away and its socket closed. Presenters are let in past the cap.",
+        example: "[rooms]\nmaxClients = 200  # with 200 clients connected",
+        fix: "Raise `maxClients` (or `--max-clients`), or share rooms between servers with `--redis`.",
+    },
+    DiagnosticInfo {
+        code: "CS5008",
+        title: "Room not found",
+        description: "A client's room was gone by the time its socket opened, for example because the room was closed or the hub deck removed, and the socket was closed.",
+        example: "DELETE /hub/api/decks/q3/rooms/main  # while a client connects to main",
+        fix: "Reconnect; connecting opens the room again.",
+    },
+    DiagnosticInfo {
+        code: "CS5009",
+        title: "Vote refused",
+        description: "A vote named a poll that is unknown or closed, or an option the poll does not have.",
+        example: "{ \"type\": \"vote\", \"poll_id\": \"p1\", \"option\": 9 }  # poll with 3 options",
+        fix: "Vote only in open polls, with an option index below the number of options.",
+    },
+    DiagnosticInfo {
+        code: "CS5010",
+        title: "Question refused",
+        description: "A question was empty or longer than the limit, or an upvote named a question that does not exist or is not approved.",
+        example: "{ \"type\": \"ask\", \"text\": \"  \" }",
+        fix: "Ask a question of 1 to 500 characters, and upvote only questions on the board.",
+    },
+    DiagnosticInfo {
+        code: "CS5011",
+        title: "Unknown reaction",
+        description: "A `react` message named a reaction the room does not count. Rooms take `clap`, `heart`, `laugh`, `wow`, `thumbs-up`, `fire`, and `question`.",
+        example: "{ \"type\": \"react\", \"reaction\": \"confetti\" }",
+        fix: "Send one of the supported reactions listed in docs/reactions.md.",
+    },
+];
+
+/// Look up the documentation for a diagnostic code (case-insensitive)
+pub fn explain(code: &str) -> Option<&'static DiagnosticInfo> {
+    CATALOG.iter().find(|info| info.code.eq_ignore_ascii_case(code.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catalog_codes_are_unique_and_sorted() {
+        let codes: Vec<&str> = CATALOG.iter().map(|info| info.code).collect();
+        let mut sorted = codes.clone();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(codes, sorted);
+    }
+
+    #[test]
+    fn test_explain_is_case_insensitive() {
+        assert_eq!(explain("cs1001").map(|i| i.code), Some("CS1001"));
+        assert!(explain("CS0000").is_none());
+    }
+}
@@ -0,0 +1,236 @@
+//! Suggested fixes: text edits that resolve a diagnostic.
+//!
+//! Diagnostics describe an edit as text to replace (`FixEdit`); `locate` turns it
+//! into byte spans in a specific file, and `apply` rewrites the file.
+
+use crate::validation::ValidationError;
+use std::ops::Range;
+
+/// Replacement text for a diagnostic, independent of where it appears
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixEdit {
+    /// Text to replace
+    pub original: String,
+    /// Text to put in its place
+    pub replacement: String,
+    /// Match only as a quoted string value rather than a whole word
+    pub quoted: bool,
+    /// Match only the value assigned to this TOML key
+    pub key: Option<String>,
+    /// Whether `validate --fix` may apply the edit without review
+    pub safe: bool,
+}
+
+/// Edit located in a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuggestedFix {
+    pub file: String,
+    /// Byte range replaced in the file
+    pub span: Range<usize>,
+    pub replacement: String,
+    pub safe: bool,
+}
+
+impl ValidationError {
+    /// Text edit that would resolve this diagnostic, when one is known
+    pub fn fix(&self) -> Option<FixEdit> {
+        let edit = |original: &str, replacement: &str, quoted: bool, safe: bool| FixEdit {
+            original: original.to_string(),
+            replacement: replacement.to_string(),
+            quoted,
+            key: None,
+            safe,
+        };
+        match self {
+            ValidationError::UnknownTransition { name, location, suggestion: Some(suggestion) } => {
+                Some(FixEdit { key: Some(transition_key(location).to_string()), ..edit(name, suggestion, true, true) })
+            }
+            // Renaming a notes key can collide with an existing key, so it needs review
+            ValidationError::UnknownNotesKey { id, suggestion: Some(suggestion) } => {
+                Some(edit(id, suggestion, false, false))
+            }
+            // The replacement prop may take a different shape of value
+            ValidationError::DeprecatedProp { prop, replacement: Some(replacement), .. } => {
+                Some(edit(prop, replacement, false, false))
+            }
+            ValidationError::Misspelling { word, suggestion: Some(suggestion), .. } => {
+                Some(edit(word, suggestion, false, false))
+            }
+            _ => None,
+        }
+    }
+
+    /// Every place in `source` where this diagnostic's fix applies
+    pub fn suggested_fixes(&self, file: &str, source: &str) -> Vec<SuggestedFix> {
+        self.fix().map(|edit| locate(&edit, file, source)).unwrap_or_default()
+    }
+
+    /// Text in the deck file that the diagnostic is about, and whether it appears
+    /// as a quoted string
+    fn subject(&self) -> Option<(&str, bool)> {
+        use ValidationError::*;
+        match self {
+            DuplicateSlideId { id } | UnknownSlideReference { id } | UnknownConditionId { id } => Some((id, true)),
+            UnknownTransition { name, .. } | UnknownComponent { name, .. } => Some((name, true)),
+            UnsatisfiedVersionReq { version_req, .. } => Some((version_req, true)),
+            DeprecatedComponent { name, .. } => Some((name, true)),
+            UnknownConditionTag { tag } => Some((tag, true)),
+            DuplicateSlotId { slot_id, .. } => Some((slot_id, true)),
+            UnusedStyleOverride { key, .. } | InvalidStyleOverrideKey { key } => Some((key, false)),
+            EmptyMarkdownSlot { slot, .. }
+            | EmptySlotComponent { slot, .. }
+            | UnclosedCodeFence { slot, .. }
+            | MarkdownBudgetExceeded { slot, .. }
+            | UnknownSlot { slot, .. }
+            | SlotKindNotAccepted { slot, .. }
+            | EmptyRequiredSlot { slot, .. } => Some((slot, false)),
+            BrokenReferenceLink { reference, .. } => Some((reference, false)),
+            ImageMissingAlt { src, .. } => Some((src, false)),
+            StrippedHtml { tag, .. } => Some((tag, false)),
+            Misspelling { word, .. } => Some((word, false)),
+            UnknownNotesKey { id, .. } => Some((id, false)),
+            DeprecatedProp { prop, .. } => Some((prop, false)),
+            InvalidComponentProps { json_path: Some(path), .. } => path.rsplit('/').find(|part| !part.is_empty()).map(|prop| (prop, false)),
+            InvalidComponentProps { .. } | MissingRequiredProp { .. } | PropsBudgetExceeded { .. } => Some(("props", false)),
+            InvalidNarration { .. } | MissingNarrationFile { .. } => Some(("narration", false)),
+            InvalidModelVersion { version } => Some((version, true)),
+            _ => None,
+        }
+    }
+
+    /// Byte range of the first place in `source` the diagnostic points at, for
+    /// editors that underline it
+    pub fn span_in(&self, source: &str) -> Option<Range<usize>> {
+        let (original, quoted) = self.subject()?;
+        let edit = FixEdit { original: original.to_string(), replacement: String::new(), quoted, key: None, safe: false };
+        locate(&edit, "", source).into_iter().next().map(|fix| fix.span)
+    }
+}
+
+/// TOML key holding the transition a `UnknownTransition` location refers to
+fn transition_key(location: &str) -> &str {
+    location
+        .strip_prefix("transitions.overrides.")
+        .or_else(|| location.strip_prefix("transitions."))
+        .unwrap_or("transition")
+}
+
+/// Whether the quoted value starting at `quote` is assigned to `key`, as in
+/// `key = "value"` or `"key" = "value"`
+fn assigned_to(source: &str, quote: usize, key: &str) -> bool {
+    let Some(before) = source[..quote].trim_end_matches([' ', '\t']).strip_suffix('=') else {
+        return false;
+    };
+    let before = before.trim_end_matches([' ', '\t']);
+    let before = ["\"", "'"]
+        .iter()
+        .find_map(|q| before.strip_suffix(q).and_then(|b| b.strip_suffix(key)).and_then(|b| b.strip_suffix(q)))
+        .or_else(|| before.strip_suffix(key));
+    before.is_some_and(|rest| !rest.ends_with(|c: char| c.is_alphanumeric() || matches!(c, '_' | '-' | '"' | '\'')))
+}
+
+/// Find each occurrence of an edit's original text in a file
+pub fn locate(edit: &FixEdit, file: &str, source: &str) -> Vec<SuggestedFix> {
+    if edit.original.is_empty() {
+        return Vec::new();
+    }
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_' || c == '-';
+
+    source
+        .match_indices(edit.original.as_str())
+        .map(|(start, text)| start..start + text.len())
+        .filter(|span| {
+            let before = source[..span.start].chars().next_back();
+            let after = source[span.end..].chars().next();
+            if edit.quoted {
+                matches!((before, after), (Some('"'), Some('"')) | (Some('\''), Some('\'')))
+                    && edit.key.as_deref().is_none_or(|key| assigned_to(source, span.start - 1, key))
+            } else {
+                !before.is_some_and(is_word_char) && !after.is_some_and(is_word_char)
+            }
+        })
+        .map(|span| SuggestedFix {
+            file: file.to_string(),
+            span,
+            replacement: edit.replacement.clone(),
+            safe: edit.safe,
+        })
+        .collect()
+}
+
+/// Apply fixes to a file's contents, skipping duplicates and overlapping spans;
+/// returns the new contents and how many fixes were applied
+pub fn apply(source: &str, fixes: &[SuggestedFix]) -> (String, usize) {
+    let mut ordered: Vec<&SuggestedFix> = fixes.iter().collect();
+    ordered.sort_by_key(|fix| (fix.span.start, fix.span.end));
+    ordered.dedup_by(|a, b| a.span == b.span);
+
+    let mut output = String::with_capacity(source.len());
+    let mut cursor = 0;
+    let mut applied = 0;
+    for fix in ordered {
+        if fix.span.start < cursor || fix.span.end > source.len() {
+            continue;
+        }
+        output.push_str(&source[cursor..fix.span.start]);
+        output.push_str(&fix.replacement);
+        cursor = fix.span.end;
+        applied += 1;
+    }
+    output.push_str(&source[cursor..]);
+    (output, applied)
+}
+
+/// 1-based line and column (in characters) of a byte offset
+pub fn line_col(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().map_or(0, |l| l.chars().count()) + 1;
+    (line, column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(original: &str, replacement: &str, quoted: bool) -> FixEdit {
+        FixEdit { original: original.into(), replacement: replacement.into(), quoted, key: None, safe: true }
+    }
+
+    #[test]
+    fn test_locate_quoted_and_words() {
+        let source = "transition = \"fdae\"\n# fdae fdaes\n";
+        let quoted = locate(&edit("fdae", "fade", true), "slides.toml", source);
+        assert_eq!(quoted.iter().map(|f| f.span.clone()).collect::<Vec<_>>(), vec![14..18]);
+        let words = locate(&edit("fdae", "fade", false), "slides.toml", source);
+        assert_eq!(words.len(), 2);
+    }
+
+    #[test]
+    fn test_span_points_at_the_subject() {
+        let source = "id = \"intro\"\n\n[component]\nname = \"TitelSlide\"\n\n[props]\ntitle = 3\n";
+        let unknown = ValidationError::UnknownComponent { name: "TitelSlide".into(), slide_id: "intro".into() };
+        assert_eq!(unknown.span_in(source).map(|span| &source[span]), Some("TitelSlide"));
+        let props = ValidationError::InvalidComponentProps {
+            component: "TitleSlide".into(),
+            slide_id: "intro".into(),
+            error: "3 is not of type string".into(),
+            json_path: Some("/title".into()),
+        };
+        assert_eq!(props.span_in(source).map(|span| line_col(source, span.start)), Some((7, 1)));
+        assert_eq!(ValidationError::VersionConflict { name: "x".into() }.span_in(source), None);
+    }
+
+    #[test]
+    fn test_apply_skips_overlaps() {
+        let fix = |span: Range<usize>, text: &str| SuggestedFix {
+            file: "f".into(),
+            span,
+            replacement: text.into(),
+            safe: true,
+        };
+        let (output, applied) = apply("abcdef", &[fix(4..6, "X"), fix(0..2, "Y"), fix(1..3, "Z"), fix(4..6, "X")]);
+        assert_eq!((output.as_str(), applied), ("YcdX", 2));
+        assert_eq!(line_col("a\nbc", 3), (2, 2));
+    }
+}
@@ -0,0 +1,116 @@
+//! `format` keyword checks used when validating component props.
+//!
+//! The standard JSON Schema formats (`date-time`, `email`, ...) come from the
+//! `jsonschema` crate. `uri` is replaced with a stricter check that only accepts
+//! schemes a browser can load, and `color` accepts CSS color values.
+
+use jsonschema::JSONSchema;
+use serde_json::Value;
+
+/// URI schemes a slide can reference
+const URI_SCHEMES: &[&str] = &["http", "https", "data", "blob", "mailto", "tel"];
+
+/// CSS color keywords besides the named colors
+const COLOR_KEYWORDS: &[&str] = &["transparent", "currentcolor", "inherit", "initial", "unset"];
+
+/// CSS named colors (CSS Color Module Level 4)
+const NAMED_COLORS: &[&str] = &[
+    "aliceblue", "antiquewhite", "aqua", "aquamarine", "azure", "beige", "bisque", "black",
+    "blanchedalmond", "blue", "blueviolet", "brown", "burlywood", "cadetblue", "chartreuse",
+    "chocolate", "coral", "cornflowerblue", "cornsilk", "crimson", "cyan", "darkblue", "darkcyan",
+    "darkgoldenrod", "darkgray", "darkgreen", "darkgrey", "darkkhaki", "darkmagenta",
+    "darkolivegreen", "darkorange", "darkorchid", "darkred", "darksalmon", "darkseagreen",
+    "darkslateblue", "darkslategray", "darkslategrey", "darkturquoise", "darkviolet", "deeppink",
+    "deepskyblue", "dimgray", "dimgrey", "dodgerblue", "firebrick", "floralwhite", "forestgreen",
+    "fuchsia", "gainsboro", "ghostwhite", "gold", "goldenrod", "gray", "green", "greenyellow",
+    "grey", "honeydew", "hotpink", "indianred", "indigo", "ivory", "khaki", "lavender",
+    "lavenderblush", "lawngreen", "lemonchiffon", "lightblue", "lightcoral", "lightcyan",
+    "lightgoldenrodyellow", "lightgray", "lightgreen", "lightgrey", "lightpink", "lightsalmon",
+    "lightseagreen", "lightskyblue", "lightslategray", "lightslategrey", "lightsteelblue",
+    "lightyellow", "lime", "limegreen", "linen", "magenta", "maroon", "mediumaquamarine",
+    "mediumblue", "mediumorchid", "mediumpurple", "mediumseagreen", "mediumslateblue",
+    "mediumspringgreen", "mediumturquoise", "mediumvioletred", "midnightblue", "mintcream",
+    "mistyrose", "moccasin", "navajowhite", "navy", "oldlace", "olive", "olivedrab", "orange",
+    "orangered", "orchid", "palegoldenrod", "palegreen", "paleturquoise", "palevioletred",
+    "papayawhip", "peachpuff", "peru", "pink", "plum", "powderblue", "purple", "rebeccapurple",
+    "red", "rosybrown", "royalblue", "saddlebrown", "salmon", "sandybrown", "seagreen", "seashell",
+    "sienna", "silver", "skyblue", "slateblue", "slategray", "slategrey", "snow", "springgreen",
+    "steelblue", "tan", "teal", "thistle", "tomato", "turquoise", "violet", "wheat", "white",
+    "whitesmoke", "yellow", "yellowgreen",
+];
+
+/// CSS color functions accepted by the `color` format
+const COLOR_FUNCTIONS: &[&str] = &["rgb", "rgba", "hsl", "hsla", "hwb", "lab", "lch", "oklab", "oklch", "color", "var"];
+
+/// Compile a props schema with format validation enabled
+pub fn compile_schema(schema: &Value) -> Result<JSONSchema, String> {
+    JSONSchema::options()
+        .should_validate_formats(true)
+        .with_format("uri", is_uri)
+        .with_format("color", is_color)
+        .compile(schema)
+        .map_err(|e| e.to_string())
+}
+
+/// Absolute URL with a scheme browsers load (catches typos like `htp://`)
+pub fn is_uri(value: &str) -> bool {
+    let Some((scheme, rest)) = value.split_once(':') else {
+        return false;
+    };
+    let scheme = scheme.to_ascii_lowercase();
+    if !URI_SCHEMES.contains(&scheme.as_str()) {
+        return false;
+    }
+    match scheme.as_str() {
+        "http" | "https" => rest
+            .strip_prefix("//")
+            .and_then(|authority| authority.split(['/', '?', '#']).next())
+            .is_some_and(|host| !host.is_empty() && !host.contains(char::is_whitespace)),
+        _ => !rest.is_empty(),
+    }
+}
+
+/// CSS color: hex, named color, keyword, or color function
+pub fn is_color(value: &str) -> bool {
+    let value = value.trim();
+    let lower = value.to_ascii_lowercase();
+
+    if let Some(hex) = lower.strip_prefix('#') {
+        return matches!(hex.len(), 3 | 4 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit());
+    }
+
+    if NAMED_COLORS.contains(&lower.as_str()) || COLOR_KEYWORDS.contains(&lower.as_str()) {
+        return true;
+    }
+
+    lower
+        .split_once('(')
+        .filter(|(_, args)| args.ends_with(')') && args.len() > 1)
+        .is_some_and(|(function, _)| COLOR_FUNCTIONS.contains(&function))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uri_format() {
+        assert!(is_uri("https://example.com/image.png"));
+        assert!(is_uri("data:image/png;base64,AAAA"));
+        assert!(!is_uri("htp://example.com"));
+        assert!(!is_uri("https://"));
+        assert!(!is_uri("example.com"));
+    }
+
+    #[test]
+    fn test_color_format() {
+        assert!(is_color("#007acc"));
+        assert!(is_color("#FFF"));
+        assert!(is_color("RebeccaPurple"));
+        assert!(is_color("rgb(0 0 0 / 50%)"));
+        assert!(is_color("var(--accent-color)"));
+        assert!(!is_color("#12345"));
+        assert!(!is_color("bluish"));
+        assert!(!is_color("rgb("));
+    }
+}
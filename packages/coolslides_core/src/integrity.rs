@@ -0,0 +1,82 @@
+//! Subresource integrity (SRI) hashes for resolved component and plugin modules.
+//!
+//! Hashes are `sha384-<base64>` strings, stored in `.coolslides.lock` and emitted
+//! as `integrity` attributes on module preloads in exported HTML.
+
+use anyhow::Result;
+use base64::Engine;
+use sha2::{Digest, Sha384};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// How long to wait for a CDN when downloading a module to hash
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// SRI hash of a module's bytes
+pub fn sri_hash(bytes: &[u8]) -> String {
+    format!("sha384-{}", base64::engine::general_purpose::STANDARD.encode(Sha384::digest(bytes)))
+}
+
+/// Whether `bytes` match an SRI string; any of its space-separated sha384 hashes may match
+pub fn matches(bytes: &[u8], integrity: &str) -> bool {
+    let actual = sri_hash(bytes);
+    integrity.split_whitespace().any(|hash| hash.split('?').next() == Some(actual.as_str()))
+}
+
+/// Hash the module at `url`: downloaded for http(s) URLs, read from disk otherwise
+pub fn module_integrity(url: &str, deck_dir: &Path) -> Result<String> {
+    Ok(sri_hash(&read_module(url, deck_dir)?))
+}
+
+/// Module contents for a resolved URL
+pub fn read_module(url: &str, deck_dir: &Path) -> Result<Vec<u8>> {
+    if url.starts_with("http://") || url.starts_with("https://") {
+        let agent = ureq::AgentBuilder::new().timeout(REQUEST_TIMEOUT).build();
+        let response = agent
+            .get(url)
+            .call()
+            .map_err(|e| anyhow::anyhow!("Failed to download {}: {}", url, e))?;
+        let mut bytes = Vec::new();
+        response.into_reader().read_to_end(&mut bytes)?;
+        return Ok(bytes);
+    }
+    let path = local_module_path(url, deck_dir).ok_or_else(|| anyhow::anyhow!("Module not found on disk: {}", url))?;
+    Ok(std::fs::read(path)?)
+}
+
+/// File for a local module URL; root-relative URLs (`/packages/...`) are looked up
+/// from the deck directory upwards, since the dev server serves the workspace root
+fn local_module_path(url: &str, deck_dir: &Path) -> Option<PathBuf> {
+    let relative = url.trim_start_matches("./").trim_start_matches('/');
+    if url.starts_with('/') {
+        deck_dir.ancestors().map(|dir| dir.join(relative)).find(|path| path.is_file())
+    } else {
+        Some(deck_dir.join(relative)).filter(|path| path.is_file())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sri_hash_and_local_modules() {
+        // Known value from the SRI spec examples: sha384 of "alert('Hello, world.');"
+        assert_eq!(
+            sri_hash(b"alert('Hello, world.');"),
+            "sha384-H8BRh8j48O9oYatfu5AZzq6A9RINhZO5H16dQZngK7T62em8MUt1FLm52t+eX6xO"
+        );
+
+        let root = std::env::temp_dir().join(format!("coolslides-sri-{}", std::process::id()));
+        let deck_dir = root.join("examples/deck");
+        std::fs::create_dir_all(root.join("packages/x")).unwrap();
+        std::fs::create_dir_all(&deck_dir).unwrap();
+        std::fs::write(root.join("packages/x/index.js"), "export {};").unwrap();
+        let integrity = module_integrity("/packages/x/index.js", &deck_dir).unwrap();
+        assert!(matches(b"export {};", &integrity));
+        assert!(!matches(b"export default 1;", &integrity));
+        assert!(module_integrity("/packages/missing.js", &deck_dir).is_err());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 use schemars::JsonSchema;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use serde_json::Value;
 
 /// SlideDoc represents a single slide in the presentation
@@ -33,6 +33,26 @@ pub struct SlideDoc {
     /// Speaker notes for this slide
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub notes: Vec<SpeakerNote>,
+    /// Recorded narration played while the slide is shown
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub narration: Option<Narration>,
+}
+
+/// Audio narration for a slide, for self-running decks and narrated video export
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Narration {
+    /// Audio file relative to the deck directory, or an absolute http(s) URL
+    pub src: String,
+    /// Length of the audio in seconds; the slide advances after it
+    pub duration: f64,
+}
+
+impl Narration {
+    /// Whether `src` points off the deck rather than at a file in it
+    pub fn is_remote(&self) -> bool {
+        self.src.starts_with("http://") || self.src.starts_with("https://")
+    }
 }
 
 /// Component specification with name and version requirement
@@ -62,11 +82,34 @@ pub struct SpeakerNote {
     pub style: HashMap<String, String>,
 }
 
+impl SpeakerNote {
+    /// `timestamp` as seconds after the slide appears. Accepts `90`, `90s`, `1m30s`,
+    /// `1:30`, and `1:02:30`; `None` when absent or unreadable.
+    pub fn offset_seconds(&self) -> Option<f64> {
+        let text = self.timestamp.as_deref()?.trim();
+        if text.is_empty() {
+            return None;
+        }
+        if text.contains(':') {
+            return text
+                .split(':')
+                .try_fold(0.0, |total, part| part.trim().parse::<f64>().ok().filter(|n| *n >= 0.0).map(|n| total * 60.0 + n));
+        }
+        let (minutes, seconds) = match text.split_once('m') {
+            Some((minutes, rest)) => (minutes.trim().parse::<f64>().ok()?, rest.trim().trim_end_matches('s')),
+            None => (0.0, text.trim_end_matches('s')),
+        };
+        let seconds = if seconds.is_empty() { 0.0 } else { seconds.trim().parse::<f64>().ok()? };
+        Some(minutes * 60.0 + seconds).filter(|total| *total >= 0.0)
+    }
+}
+
 /// Type of speaker note
-#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum NoteType {
     /// General speaking notes
+    #[default]
     General,
     /// Timing information
     Timing,
@@ -76,12 +119,6 @@ pub enum NoteType {
     Transition,
 }
 
-impl Default for NoteType {
-    fn default() -> Self {
-        NoteType::General
-    }
-}
-
 /// Text direction for internationalization
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
@@ -117,9 +154,286 @@ pub struct DeckManifest {
     /// Conditional inclusion/exclusion rules
     #[serde(skip_serializing_if = "Option::is_none")]
     pub conditions: Option<ConditionConfig>,
+    /// Named alternatives to `conditions`, e.g. a short cut of the talk
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub profiles: HashMap<String, ConditionConfig>,
     /// Print/export configuration
     #[serde(skip_serializing_if = "Option::is_none")]
     pub print: Option<PrintConfig>,
+    /// Lint rule configuration
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lint: Option<LintConfig>,
+    /// Remote component registry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub registry: Option<RegistryConfig>,
+    /// How components from the registry layers are combined
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub components: Option<ComponentsConfig>,
+    /// Responsive image variants for exports
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub images: Option<ImageConfig>,
+    /// Auto-play timings for the kiosk view
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kiosk: Option<KioskConfig>,
+    /// Anonymous view analytics; off unless `enabled` is set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub analytics: Option<AnalyticsConfig>,
+    /// Endpoints notified of room and deck events
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub webhooks: Vec<WebhookConfig>,
+    /// REST remote control of rooms; disabled until a token is configured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote: Option<RemoteConfig>,
+    /// WASM modules the dev server loads to extend request handling, rooms, and exports
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub server_plugins: Vec<ServerPluginConfig>,
+    /// Shell commands the CLI runs before and after its own commands
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hooks: Option<HooksConfig>,
+    /// HTML that Markdown slots may keep beyond the built-in allowlist
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sanitization: Option<SanitizationPolicy>,
+}
+
+impl DeckManifest {
+    /// Slide ids in presentation order, with groups expanded
+    pub fn slide_order(&self) -> Vec<&str> {
+        self.sequence
+            .iter()
+            .flat_map(|item| match item {
+                DeckItem::Ref { slide_id } => std::slice::from_ref(slide_id),
+                DeckItem::Group { slides, .. } => slides.as_slice(),
+            })
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// The slides to play, in order: groups expanded, `conditions` applied,
+    /// and each slide's transition settled. A slide's override beats its
+    /// group's transition, which beats the default.
+    pub fn resolve(&self, slides: &HashMap<String, SlideDoc>, conditions: Option<&ConditionConfig>) -> Vec<ResolvedSlide> {
+        let include_tags = conditions.and_then(|c| c.include_tags.as_ref()).filter(|tags| !tags.is_empty());
+        let exclude_ids = conditions.and_then(|c| c.exclude_ids.as_deref()).unwrap_or_default();
+        let included = |id: &String| {
+            let tagged = include_tags.is_none_or(|tags| {
+                slides.get(id).is_some_and(|slide| slide.tags.iter().any(|tag| tags.contains(tag)))
+            });
+            tagged && !exclude_ids.contains(id)
+        };
+
+        let mut resolved = Vec::new();
+        for item in &self.sequence {
+            let (ids, group, group_transition) = match item {
+                DeckItem::Ref { slide_id } => (std::slice::from_ref(slide_id), None, None),
+                DeckItem::Group { name, transition, slides: ids } => (ids.as_slice(), Some(name), transition.as_ref()),
+            };
+            for id in ids.iter().filter(|id| included(id)) {
+                let transition = self.transitions.overrides.get(id).or(group_transition).unwrap_or(&self.transitions.default);
+                resolved.push(ResolvedSlide { id: id.clone(), group: group.cloned(), transition: transition.clone() });
+            }
+        }
+        resolved
+    }
+}
+
+/// One slide of a resolved deck
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolvedSlide {
+    /// Slide ID
+    pub id: String,
+    /// Name of the group the slide was listed in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// Transition into this slide
+    pub transition: String,
+}
+
+/// `[components]` options for the merged component registry
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentsConfig {
+    /// What to do when two components claim the same custom element tag
+    #[serde(default)]
+    pub tag_collisions: TagCollisionMode,
+}
+
+/// Handling of custom element tag collisions between components
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TagCollisionMode {
+    /// Report each collision as CS3004
+    #[default]
+    Error,
+    /// Keep the highest-precedence component's tag and prefix the others with their package name
+    Prefix,
+}
+
+/// `[images]` options: resized and re-encoded copies of local images in HTML exports
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ImageConfig {
+    /// Generate variants on export
+    pub optimize: bool,
+    /// Variant widths in pixels; no variant is wider than the original image
+    pub widths: Vec<u32>,
+    /// Formats offered ahead of the original's format (`webp`, `avif`)
+    pub formats: Vec<String>,
+    /// Encoder quality, 1-100
+    pub quality: u8,
+    /// `sizes` attribute of the generated `srcset`s
+    pub sizes: String,
+}
+
+impl Default for ImageConfig {
+    fn default() -> Self {
+        Self {
+            optimize: true,
+            widths: vec![640, 1280, 1920],
+            formats: vec!["webp".to_string()],
+            quality: 80,
+            sizes: "100vw".to_string(),
+        }
+    }
+}
+
+/// Remote component registry used for validation and `coolslides add component`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistryConfig {
+    /// URL of the registry index
+    pub index: String,
+    /// Seconds a cached index is used before revalidating with the server
+    #[serde(default = "default_registry_max_age")]
+    pub max_age: u64,
+}
+
+fn default_registry_max_age() -> u64 {
+    3600
+}
+
+/// `[[webhooks]]` entry: a URL that receives JSON payloads for server events
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookConfig {
+    /// Endpoint the payloads are POSTed to
+    pub url: String,
+    /// Event names to send (e.g. `slide.changed`); all events when empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<String>,
+    /// HMAC-SHA256 signing secret
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    /// Environment variable holding the signing secret, so it need not be committed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret_env: Option<String>,
+    /// Further attempts after a failed delivery
+    #[serde(default = "default_webhook_retries")]
+    pub retries: u32,
+}
+
+fn default_webhook_retries() -> u32 {
+    3
+}
+
+impl WebhookConfig {
+    /// Whether this endpoint subscribes to `event`
+    pub fn wants(&self, event: &str) -> bool {
+        self.events.is_empty() || self.events.iter().any(|e| e == event || e == "*")
+    }
+
+    /// The signing secret, from `secretEnv` when set, otherwise `secret`
+    pub fn signing_secret(&self) -> Option<String> {
+        match &self.secret_env {
+            Some(var) => std::env::var(var).ok().filter(|s| !s.is_empty()),
+            None => self.secret.clone(),
+        }
+    }
+}
+
+/// `[remote]` options for the navigation endpoints clickers and automation call
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteConfig {
+    /// Bearer token callers must present
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// Environment variable holding the token, so it need not be committed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_env: Option<String>,
+}
+
+impl RemoteConfig {
+    /// The token, from `tokenEnv` when set, otherwise `token`; empty tokens count as unset
+    pub fn token(&self) -> Option<String> {
+        match &self.token_env {
+            Some(var) => std::env::var(var).ok(),
+            None => self.token.clone(),
+        }
+        .filter(|token| !token.is_empty())
+    }
+}
+
+/// `[[serverPlugins]]` entry: a WASM module hooked into the dev server
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerPluginConfig {
+    /// Path to the `.wasm` file, relative to the deck directory
+    pub path: String,
+    /// Passed as JSON to the module's `init` export
+    #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+    pub config: serde_json::Value,
+}
+
+/// `[hooks]`: commands run from the deck directory at points of the CLI's workflow.
+/// Each hook is one command or a list run in order.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct HooksConfig {
+    /// Before `coolslides dev` and `coolslides kiosk` start serving
+    #[serde(deserialize_with = "one_or_many", skip_serializing_if = "Vec::is_empty")]
+    pub pre_dev: Vec<String>,
+    /// Before `coolslides export` renders anything
+    #[serde(deserialize_with = "one_or_many", skip_serializing_if = "Vec::is_empty")]
+    pub pre_export: Vec<String>,
+    /// After `coolslides export` wrote its output
+    #[serde(deserialize_with = "one_or_many", skip_serializing_if = "Vec::is_empty")]
+    pub post_export: Vec<String>,
+    /// Run by `coolslides hook pre-publish` from a publishing workflow
+    #[serde(deserialize_with = "one_or_many", skip_serializing_if = "Vec::is_empty")]
+    pub pre_publish: Vec<String>,
+}
+
+impl HooksConfig {
+    /// Hook names as written in `[hooks]`
+    pub const NAMES: [&'static str; 4] = ["pre-dev", "pre-export", "post-export", "pre-publish"];
+
+    /// Commands of the named hook; empty for unknown names
+    pub fn commands(&self, hook: &str) -> &[String] {
+        match hook {
+            "pre-dev" => &self.pre_dev,
+            "pre-export" => &self.pre_export,
+            "post-export" => &self.post_export,
+            "pre-publish" => &self.pre_publish,
+            _ => &[],
+        }
+    }
+}
+
+fn one_or_many<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(command) => vec![command],
+        OneOrMany::Many(commands) => commands,
+    })
 }
 
 /// Transition configuration for slide animations
@@ -144,6 +458,114 @@ pub struct ConditionConfig {
     pub exclude_ids: Option<Vec<String>>,
 }
 
+/// `[sanitization]` options: additions to the Markdown sanitizer's allowlist.
+/// Servers running in strict mode ignore them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SanitizationPolicy {
+    /// Rules for every Markdown slot
+    #[serde(flatten)]
+    pub rules: SanitizationRules,
+    /// Further rules for slots with these names
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub slots: BTreeMap<String, SanitizationRules>,
+}
+
+impl SanitizationPolicy {
+    /// Rules for the slot named `slot`: the deck-wide rules plus the slot's own.
+    /// A slot's `urlSchemes` replaces the deck-wide list.
+    pub fn for_slot(&self, slot: &str) -> SanitizationRules {
+        let mut rules = self.rules.clone();
+        if let Some(extra) = self.slots.get(slot) {
+            rules.extend(extra);
+        }
+        rules
+    }
+
+    /// These rules with `other`'s added on top, slot by slot
+    pub fn merged(&self, other: &SanitizationPolicy) -> SanitizationPolicy {
+        let mut policy = self.clone();
+        policy.rules.extend(&other.rules);
+        for (slot, rules) in &other.slots {
+            policy.slots.entry(slot.clone()).or_default().extend(rules);
+        }
+        policy
+    }
+}
+
+/// Allowlist additions for Markdown slots
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SanitizationRules {
+    /// Tags to keep besides the built-in ones. `script` and `style` are always removed.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub allow_tags: Vec<String>,
+    /// Attributes to keep, by tag; `"*"` keeps them on every tag
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub allow_attributes: BTreeMap<String, Vec<String>>,
+    /// URL schemes links and images may use, replacing the default list
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url_schemes: Option<Vec<String>>,
+}
+
+impl SanitizationRules {
+    /// Add `other`'s tags and attributes; its `urlSchemes`, when set, replaces ours
+    pub fn extend(&mut self, other: &SanitizationRules) {
+        self.allow_tags.extend(other.allow_tags.iter().cloned());
+        for (tag, attributes) in &other.allow_attributes {
+            self.allow_attributes.entry(tag.clone()).or_default().extend(attributes.iter().cloned());
+        }
+        if other.url_schemes.is_some() {
+            self.url_schemes = other.url_schemes.clone();
+        }
+    }
+}
+
+/// `[kiosk]` options: unattended, looping playback at `/kiosk`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", default)]
+pub struct KioskConfig {
+    /// Seconds each slide is shown
+    pub default_duration: f64,
+    /// Per-slide durations in seconds, keyed by slide id
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub durations: HashMap<String, f64>,
+    /// Start over after the last slide
+    #[serde(rename = "loop")]
+    pub looping: bool,
+    /// Let visitors navigate; otherwise input is ignored
+    pub interactive: bool,
+    /// Seconds without input before an interactive kiosk restarts from the first slide
+    pub idle_restart: f64,
+}
+
+impl Default for KioskConfig {
+    fn default() -> Self {
+        Self {
+            default_duration: 10.0,
+            durations: HashMap::new(),
+            looping: true,
+            interactive: false,
+            idle_restart: 60.0,
+        }
+    }
+}
+
+impl KioskConfig {
+    /// Seconds to show `slide_id`
+    pub fn duration(&self, slide_id: &str) -> f64 {
+        self.durations.get(slide_id).copied().unwrap_or(self.default_duration)
+    }
+}
+
+/// `[analytics]` options for the dev server's view-event collection
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AnalyticsConfig {
+    /// Collect view events; the endpoints answer 404 while this is off
+    pub enabled: bool,
+}
+
 /// Print/export configuration
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
@@ -159,6 +581,169 @@ pub struct PrintConfig {
     pub footer_template: Option<String>,
 }
 
+/// Lint configuration: per-code severities plus declarative custom rules
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LintConfig {
+    /// Severity overrides keyed by diagnostic code (e.g. `CS1005 = "off"`)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub rules: HashMap<String, LintSeverity>,
+    /// Custom rules evaluated during validation
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub custom: Vec<CustomLintRule>,
+    /// Exceptions to warnings-as-errors under `validate --strict`
+    #[serde(default)]
+    pub strict: StrictPolicy,
+    /// Size limits that warn about decks likely to load slowly
+    #[serde(default)]
+    pub budgets: BudgetConfig,
+    /// Opt-in dictionary spelling pass
+    #[serde(default)]
+    pub spelling: SpellingConfig,
+}
+
+/// Performance budgets checked during validation; a limit of 0 disables the check
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BudgetConfig {
+    /// Maximum serialized size of a slide's props, in bytes
+    pub max_props_bytes: u64,
+    /// Maximum length of a markdown slot, in characters
+    pub max_markdown_chars: u64,
+    /// Maximum size of a local asset (theme, tokens, images), in bytes
+    pub max_asset_bytes: u64,
+    /// Maximum number of slides in a sequence group
+    pub max_slides_per_group: u64,
+    /// Render-path time limits checked by `coolslides bench --budget`
+    pub time: TimeBudgets,
+}
+
+impl Default for BudgetConfig {
+    fn default() -> Self {
+        Self {
+            max_props_bytes: 64 * 1024,
+            max_markdown_chars: 10_000,
+            max_asset_bytes: 5 * 1024 * 1024,
+            max_slides_per_group: 30,
+            time: TimeBudgets::default(),
+        }
+    }
+}
+
+/// Median time limits, in milliseconds, for each benchmarked operation on a deck
+/// scaled to `slides` slides; a limit of 0 disables the check
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TimeBudgets {
+    /// Slide count the deck is scaled to before timing
+    pub slides: u64,
+    /// Parsing the manifest and slide files
+    pub load_ms: u64,
+    /// Schema and structural validation
+    pub validate_ms: u64,
+    /// Rendering and sanitizing every markdown slot
+    pub markdown_ms: u64,
+    /// Generating the slides HTML with empty render caches
+    pub render_ms: u64,
+}
+
+impl Default for TimeBudgets {
+    fn default() -> Self {
+        Self {
+            slides: 500,
+            load_ms: 100,
+            validate_ms: 200,
+            markdown_ms: 150,
+            render_ms: 250,
+        }
+    }
+}
+
+/// Spelling check over slide text, using Hunspell dictionaries
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SpellingConfig {
+    /// Run the spelling pass during `validate`
+    pub enabled: bool,
+    /// Locale for slides that do not set one (BCP 47)
+    pub default_locale: String,
+    /// Words accepted in every locale (case-insensitive)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub allow: Vec<String>,
+    /// Extra directories searched for `.aff`/`.dic` files, relative to the deck
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub dictionary_dirs: Vec<String>,
+}
+
+impl Default for SpellingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            default_locale: "en-US".to_string(),
+            allow: Vec::new(),
+            dictionary_dirs: Vec::new(),
+        }
+    }
+}
+
+/// Per-code exceptions applied when warnings are promoted to errors
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StrictPolicy {
+    /// Codes that stay warnings in strict mode
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow: Vec<String>,
+    /// Codes whose warnings are dropped in strict mode
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub silence: Vec<String>,
+}
+
+/// Severity assigned to a diagnostic code or custom rule
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum LintSeverity {
+    /// Suppress the diagnostic
+    Off,
+    /// Report without failing validation
+    Warn,
+    /// Fail validation
+    #[default]
+    Error,
+}
+
+/// A declarative custom lint rule
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomLintRule {
+    /// What the rule checks
+    #[serde(flatten)]
+    pub check: CustomLintCheck,
+    /// Severity of violations
+    #[serde(default)]
+    pub severity: LintSeverity,
+    /// Message to report instead of the generated one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// The check performed by a custom lint rule
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "rule", rename_all = "kebab-case")]
+pub enum CustomLintCheck {
+    /// Slides using `component` must set `prop`
+    RequireProp {
+        /// Component name
+        component: String,
+        /// Prop that must be present
+        prop: String,
+    },
+    /// Element `tag` may not appear as a slide component, slot component, or raw HTML in markdown
+    ForbidTag {
+        /// HTML or custom element tag name
+        tag: String,
+    },
+}
+
 /// DeckItem represents either a slide reference or a group
 #[derive(Debug, Clone, Serialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "camelCase")]
@@ -198,6 +783,7 @@ impl<'de> serde::Deserialize<'de> for DeckItem {
             // Canonical ref: { type = "ref", ref = "..." }
             CanonicalRef {
                 #[serde(rename = "type")]
+                #[allow(dead_code)]
                 r#type: TagRef,
                 #[serde(rename = "ref")]
                 r#ref: String,
@@ -205,6 +791,7 @@ impl<'de> serde::Deserialize<'de> for DeckItem {
             // Canonical group: { type = "group", name = "...", slides = [...] }
             CanonicalGroup {
                 #[serde(rename = "type")]
+                #[allow(dead_code)]
                 r#type: TagGroup,
                 name: String,
                 #[serde(default)]
@@ -300,6 +887,15 @@ pub enum Slot {
     },
 }
 
+impl Slot {
+    pub fn kind(&self) -> SlotKind {
+        match self {
+            Slot::Markdown { .. } => SlotKind::Markdown,
+            Slot::Component { .. } => SlotKind::Component,
+        }
+    }
+}
+
 /// Loading strategy for components
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
@@ -331,6 +927,21 @@ pub struct Lockfile {
     pub import_map: ImportMap,
     /// Timestamp of lockfile generation
     pub timestamp: String,
+    /// Modules downloaded by `coolslides vendor`, by original URL
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub vendored: std::collections::BTreeMap<String, crate::vendor::VendoredModule>,
+}
+
+impl Lockfile {
+    /// Locked module URL for a component version: a `name@version` entry, or the
+    /// `name` entry when it locks that version
+    pub fn component_url(&self, name: &str, version: &str) -> Option<&str> {
+        let components = &self.resolved.components;
+        components
+            .get(&format!("{}@{}", name, version))
+            .or_else(|| components.get(name).filter(|p| p.version == version))
+            .map(|p| p.url.as_str())
+    }
 }
 
 /// Resolved dependencies with integrity hashes
@@ -354,16 +965,34 @@ pub struct ResolvedPackage {
     /// Subresource integrity hash
     #[serde(skip_serializing_if = "Option::is_none")]
     pub integrity: Option<String>,
+    /// Transition names registered by this package (plugins only)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transitions: Vec<String>,
 }
 
 /// Import map for ES module resolution
-#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct ImportMap {
     /// Import specifier mappings
     #[serde(default)]
     pub imports: HashMap<String, String>,
 }
 
+impl ImportMap {
+    /// Resolve a bare specifier the way browsers do: exact match first, then the
+    /// longest trailing-slash prefix mapping
+    pub fn resolve(&self, specifier: &str) -> Option<String> {
+        if let Some(url) = self.imports.get(specifier) {
+            return Some(url.clone());
+        }
+        self.imports
+            .iter()
+            .filter(|(key, _)| key.ends_with('/') && specifier.starts_with(key.as_str()))
+            .max_by_key(|(key, _)| key.len())
+            .map(|(key, url)| format!("{}{}", url, &specifier[key.len()..]))
+    }
+}
+
 /// Component manifest with JSON Schema for validation
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
@@ -387,11 +1016,100 @@ pub struct ComponentManifest {
     /// Suggested transition for this component
     #[serde(skip_serializing_if = "Option::is_none")]
     pub suggested_transition: Option<String>,
+    /// Named slots the component renders; `None` means slots are not declared
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slots: Option<Vec<SlotDeclaration>>,
+    /// Set when this version should no longer be used; `true` or `{ message, replacement }`
+    #[serde(default, deserialize_with = "deserialize_deprecation", skip_serializing_if = "Option::is_none")]
+    pub deprecated: Option<Deprecation>,
 }
 
-/// Registry of all available components and their manifests
+/// Deprecation notice for a component version or a prop
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Deprecation {
+    /// Why it is deprecated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Component or prop to use instead
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replacement: Option<String>,
+}
+
+impl Deprecation {
+    /// Read a `deprecated` value: `true`, or an object with `message` and `replacement`
+    pub fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Bool(true) => Some(Self::default()),
+            Value::Object(_) => serde_json::from_value(value.clone()).ok(),
+            _ => None,
+        }
+    }
+
+    /// Deprecation of a schema property, from its `deprecated` keyword
+    pub fn of_prop(prop: &Value) -> Option<Self> {
+        prop.get("deprecated").and_then(Self::from_value)
+    }
+}
+
+impl std::fmt::Display for Deprecation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.message, &self.replacement) {
+            (Some(message), Some(replacement)) => write!(f, "{}; use {} instead", message, replacement),
+            (Some(message), None) => f.write_str(message),
+            (None, Some(replacement)) => write!(f, "use {} instead", replacement),
+            (None, None) => f.write_str("no replacement given"),
+        }
+    }
+}
+
+fn deserialize_deprecation<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<Deprecation>, D::Error> {
+    Ok(Deprecation::from_value(&Value::deserialize(deserializer)?))
+}
+
+/// A named slot declared by a component
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
+pub struct SlotDeclaration {
+    /// Slot name, matching `<slot name="...">` in the component
+    pub name: String,
+    /// Whether slides should always fill this slot
+    #[serde(default)]
+    pub required: bool,
+    /// What the slot is for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Kinds of content the slot takes; empty accepts any kind
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub accepts: Vec<SlotKind>,
+}
+
+impl SlotDeclaration {
+    /// Whether slides may fill this slot with content of `kind`
+    pub fn accepts_kind(&self, kind: SlotKind) -> bool {
+        self.accepts.is_empty() || self.accepts.contains(&kind)
+    }
+}
+
+/// Kind of slot content, matching the `kind` tag of `Slot`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum SlotKind {
+    Markdown,
+    Component,
+}
+
+impl std::fmt::Display for SlotKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SlotKind::Markdown => "markdown",
+            SlotKind::Component => "component",
+        })
+    }
+}
+
+/// Registry of all available components and their manifests
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
 pub struct ComponentRegistry {
     /// Components indexed by name
     #[serde(default)]
@@ -399,4 +1117,208 @@ pub struct ComponentRegistry {
     /// Tags indexed to component names
     #[serde(default)]
     pub tag_to_name: HashMap<String, String>,
+    /// Source file each component manifest was loaded from, indexed by component name
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub sources: HashMap<String, String>,
+    /// Other versions of components, indexed by name; `components` holds the default version
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub versions: HashMap<String, Vec<ComponentManifest>>,
+    /// Tags rewritten to resolve collisions, applied when the component's module is loaded
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tag_renames: Vec<TagRename>,
+}
+
+/// A component tag rewritten by `tagCollisions = "prefix"`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TagRename {
+    pub component: String,
+    /// Tag the component's module defines
+    pub original: String,
+    /// Tag used in the registry and rendered slides
+    pub tag: String,
+    pub module: String,
+}
+
+impl ComponentRegistry {
+    /// Slots a component declares, or None when the component is unknown or does not declare slots
+    pub fn slots(&self, name: &str) -> Option<&[SlotDeclaration]> {
+        self.components.get(name)?.slots.as_deref()
+    }
+
+    /// Add components from a lower-precedence registry, keeping existing entries on name conflicts
+    pub fn merge_missing(&mut self, other: ComponentRegistry) {
+        let mut names: Vec<String> = other.components.keys().cloned().collect();
+        names.sort();
+        for name in names {
+            if self.components.contains_key(&name) {
+                continue;
+            }
+            let source = other.sources.get(&name).cloned().unwrap_or_default();
+            self.insert(other.components[&name].clone(), std::path::Path::new(&source));
+        }
+    }
+
+    /// Register a manifest, remembering the file it was loaded from. When another
+    /// version of the component is registered, the newer one becomes the default.
+    pub fn insert(&mut self, manifest: ComponentManifest, source: &std::path::Path) {
+        self.tag_to_name.insert(manifest.tag.clone(), manifest.name.clone());
+        let newer = match self.components.get(&manifest.name) {
+            Some(existing) if existing.version != manifest.version => {
+                parse_version(&manifest.version) > parse_version(&existing.version)
+            }
+            _ => true,
+        };
+        if !newer {
+            self.add_version(manifest);
+            return;
+        }
+        self.sources.insert(manifest.name.clone(), source.display().to_string());
+        if let Some(others) = self.versions.get_mut(&manifest.name) {
+            others.retain(|m| m.version != manifest.version);
+        }
+        let version = manifest.version.clone();
+        if let Some(previous) = self.components.insert(manifest.name.clone(), manifest) {
+            if previous.version != version {
+                self.add_version(previous);
+            }
+        }
+    }
+
+    /// Register another version of a component, keeping the current default; returns
+    /// false when that version is already registered
+    pub fn add_version(&mut self, manifest: ComponentManifest) -> bool {
+        let Some(default) = self.components.get(&manifest.name) else {
+            self.tag_to_name.insert(manifest.tag.clone(), manifest.name.clone());
+            self.components.insert(manifest.name.clone(), manifest);
+            return true;
+        };
+        let others = self.versions.entry(manifest.name.clone()).or_default();
+        if default.version == manifest.version || others.iter().any(|m| m.version == manifest.version) {
+            return false;
+        }
+        self.tag_to_name.insert(manifest.tag.clone(), manifest.name.clone());
+        others.push(manifest);
+        true
+    }
+
+    /// Every registered version of a component, default first
+    pub fn all_versions<'a>(&'a self, name: &str) -> impl Iterator<Item = &'a ComponentManifest> + 'a {
+        self.components
+            .get(name)
+            .into_iter()
+            .chain(self.versions.get(name).into_iter().flatten())
+    }
+
+    /// Every registered manifest, all versions included
+    pub fn all_manifests(&self) -> impl Iterator<Item = &ComponentManifest> {
+        self.components.values().chain(self.versions.values().flatten())
+    }
+
+    /// Newest version of a component satisfying a semver requirement (`^1`, `>=1.2, <2`, `*`);
+    /// None when the component is unknown, the requirement does not parse, or no version matches
+    pub fn resolve(&self, name: &str, version_req: &str) -> Option<&ComponentManifest> {
+        let req = match version_req.trim() {
+            "" => semver::VersionReq::STAR,
+            req => semver::VersionReq::parse(req).ok()?,
+        };
+        self.all_versions(name)
+            .filter_map(|m| parse_version(&m.version).filter(|v| req.matches(v)).map(|v| (v, m)))
+            .max_by(|(a, _), (b, _)| a.cmp(b))
+            .map(|(_, m)| m)
+    }
+
+    /// Resolve tag collisions by prefixing every claimant but the first-ranked one
+    /// (lowest `rank`, then name) with its package name, e.g. `acme-charts-cs-chart`
+    pub fn prefix_colliding_tags(&mut self, rank: impl Fn(&str) -> usize) -> Vec<TagRename> {
+        let mut renames = Vec::new();
+        for (tag, mut names) in self.tag_collisions() {
+            names.sort_by_key(|name| (rank(name), name.clone()));
+            for name in &names[1..] {
+                let source = self.sources.get(name).cloned().unwrap_or_default();
+                let base = format!("{}-{}", package_prefix(&source), tag);
+                let mut prefixed = base.clone();
+                let mut n = 2;
+                while self.tag_to_name.contains_key(&prefixed) {
+                    prefixed = format!("{}-{}", base, n);
+                    n += 1;
+                }
+
+                let manifest = self.components.get_mut(name).expect("colliding component is registered");
+                manifest.tag = prefixed.clone();
+                let rename = TagRename {
+                    component: name.clone(),
+                    original: tag.clone(),
+                    tag: prefixed.clone(),
+                    module: manifest.module.clone(),
+                };
+                self.tag_to_name.insert(prefixed, name.clone());
+                self.tag_to_name.insert(tag.clone(), names[0].clone());
+                renames.push(rename);
+            }
+        }
+        self.tag_renames.extend(renames.iter().cloned());
+        renames
+    }
+
+    /// Tag to render for a component element, applying collision renames
+    pub fn renamed_tag(&self, tag: &str, module: &str) -> Option<&str> {
+        self.tag_renames
+            .iter()
+            .find(|r| r.original == tag && r.module == module)
+            .map(|r| r.tag.as_str())
+    }
+
+    /// Find tags declared by more than one component, as (tag, sorted component names)
+    pub fn tag_collisions(&self) -> Vec<(String, Vec<String>)> {
+        let mut by_tag: HashMap<&str, Vec<String>> = HashMap::new();
+        for manifest in self.components.values() {
+            by_tag.entry(manifest.tag.as_str()).or_default().push(manifest.name.clone());
+        }
+
+        let mut collisions: Vec<(String, Vec<String>)> = by_tag
+            .into_iter()
+            .filter(|(_, names)| names.len() > 1)
+            .map(|(tag, mut names)| {
+                names.sort();
+                (tag.to_string(), names)
+            })
+            .collect();
+        collisions.sort();
+        collisions
+    }
+}
+
+fn parse_version(version: &str) -> Option<semver::Version> {
+    semver::Version::parse(version.trim_start_matches('v')).ok()
+}
+
+/// Custom-element-safe prefix naming the package a manifest came from: the
+/// nearest package.json `name`, the host of a registry URL, or the parent directory
+fn package_prefix(source: &str) -> String {
+    let name = if let Some(rest) = source.split("://").nth(1) {
+        rest.split('/').next().unwrap_or(rest).to_string()
+    } else {
+        let path = std::path::Path::new(source);
+        path.ancestors()
+            .skip(1)
+            .find_map(|dir| {
+                let package = std::fs::read_to_string(dir.join("package.json")).ok()?;
+                let package: Value = serde_json::from_str(&package).ok()?;
+                package.get("name")?.as_str().map(str::to_string)
+            })
+            .or_else(|| path.parent()?.file_name().map(|n| n.to_string_lossy().to_string()))
+            .unwrap_or_else(|| "component".to_string())
+    };
+    let prefix: String = name
+        .to_ascii_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let prefix = prefix.trim_matches('-').to_string();
+    // Custom element names must start with a letter
+    match prefix.chars().next() {
+        Some(c) if c.is_ascii_lowercase() => prefix,
+        _ => format!("x-{}", prefix),
+    }
 }
@@ -2,5 +2,21 @@ pub mod ir;
 pub mod schema;
 pub mod validation;
 pub mod components;
+pub mod cache;
+pub mod diagnostics;
+pub mod formats;
+pub mod lint;
+pub mod markdown;
+pub mod math;
+pub mod spelling;
+pub mod fixes;
+pub mod remote_registry;
+pub mod registry;
+pub mod catalog;
+pub mod integrity;
+pub mod policy;
+pub mod signing;
+pub mod user_config;
+pub mod vendor;
 
 pub use ir::*;
\ No newline at end of file
@@ -0,0 +1,133 @@
+use crate::ir::{
+    ComponentRegistry, CustomLintCheck, CustomLintRule, LintSeverity, SlideDoc, Slot, StrictPolicy,
+};
+use crate::validation::{ValidationError, ValidationResult};
+use std::collections::HashMap;
+
+/// Move diagnostics between errors and warnings (or drop them) according to per-code severities
+pub fn apply_severities(rules: &HashMap<String, LintSeverity>, result: &mut ValidationResult) {
+    if rules.is_empty() {
+        return;
+    }
+
+    let diagnostics = std::mem::take(&mut result.errors)
+        .into_iter()
+        .map(|e| (LintSeverity::Error, e))
+        .chain(
+            std::mem::take(&mut result.warnings)
+                .into_iter()
+                .map(|w| (LintSeverity::Warn, w)),
+        );
+
+    for (default_severity, diagnostic) in diagnostics {
+        let severity = rules.get(diagnostic.code()).copied().unwrap_or(default_severity);
+        match severity {
+            LintSeverity::Off => {}
+            LintSeverity::Warn => result.add_warning(diagnostic),
+            LintSeverity::Error => result.add_error(diagnostic),
+        }
+    }
+}
+
+/// Promote warnings to errors for strict validation, honouring the policy's exceptions.
+///
+/// Promoted diagnostics are appended after the existing errors; returns how many were promoted.
+pub fn apply_strict(policy: &StrictPolicy, result: &mut ValidationResult) -> usize {
+    let listed = |codes: &[String], code: &str| codes.iter().any(|c| c.eq_ignore_ascii_case(code));
+    let mut promoted = 0;
+
+    for warning in std::mem::take(&mut result.warnings) {
+        let code = warning.code();
+        if listed(&policy.silence, code) {
+            continue;
+        }
+        if listed(&policy.allow, code) {
+            result.add_warning(warning);
+        } else {
+            result.add_error(warning);
+            promoted += 1;
+        }
+    }
+
+    promoted
+}
+
+/// Evaluate the deck's declarative custom rules against every slide
+pub fn evaluate_custom_rules(
+    rules: &[CustomLintRule],
+    slides: &[SlideDoc],
+    registry: Option<&ComponentRegistry>,
+    result: &mut ValidationResult,
+) {
+    for rule in rules {
+        if rule.severity == LintSeverity::Off {
+            continue;
+        }
+
+        for slide in slides {
+            let violation = match &rule.check {
+                CustomLintCheck::RequireProp { component, prop } => {
+                    let missing = slide.component.name == *component
+                        && slide.props.get(prop).is_none_or(|v| v.is_null());
+                    missing.then(|| format!("{} requires prop '{}'", component, prop))
+                }
+                CustomLintCheck::ForbidTag { tag } => {
+                    slide_uses_tag(slide, tag, registry).then(|| format!("Forbidden tag <{}>", tag))
+                }
+            };
+
+            if let Some(default_message) = violation {
+                let diagnostic = ValidationError::CustomRule {
+                    message: rule.message.clone().unwrap_or(default_message),
+                    slide_id: slide.id.clone(),
+                };
+                match rule.severity {
+                    LintSeverity::Error => result.add_error(diagnostic),
+                    _ => result.add_warning(diagnostic),
+                }
+            }
+        }
+    }
+}
+
+/// Whether a slide renders `tag` through its component, a slot component, or raw markdown HTML
+fn slide_uses_tag(slide: &SlideDoc, tag: &str, registry: Option<&ComponentRegistry>) -> bool {
+    let tag = tag.to_ascii_lowercase();
+
+    let component_tag = registry
+        .and_then(|r| r.resolve(&slide.component.name, &slide.component.version_req))
+        .map(|m| m.tag.to_ascii_lowercase());
+    if component_tag.as_deref() == Some(tag.as_str()) {
+        return true;
+    }
+
+    slide.slots.values().any(|slot| match slot {
+        Slot::Component { tag: slot_tag, .. } => slot_tag.eq_ignore_ascii_case(&tag),
+        Slot::Markdown { value } => markdown_contains_tag(value, &tag),
+    })
+}
+
+/// Detect `<tag` opening elements in raw markdown (tag is expected lowercase)
+fn markdown_contains_tag(markdown: &str, tag: &str) -> bool {
+    let lower = markdown.to_ascii_lowercase();
+    let needle = format!("<{}", tag);
+    lower.match_indices(&needle).any(|(i, _)| {
+        let rest = &lower[i + needle.len()..];
+        rest.chars()
+            .next()
+            .is_none_or(|c| c.is_whitespace() || c == '>' || c == '/')
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markdown_contains_tag() {
+        assert!(markdown_contains_tag("Hello <marquee>hi</marquee>", "marquee"));
+        assert!(markdown_contains_tag("<MARQUEE direction=left>", "marquee"));
+        assert!(!markdown_contains_tag("<marquees>", "marquee"));
+        assert!(!markdown_contains_tag("no html here", "marquee"));
+    }
+}
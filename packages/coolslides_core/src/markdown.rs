@@ -0,0 +1,155 @@
+//! Content lints for markdown slots.
+
+use crate::validation::{ValidationError, ValidationResult};
+use pulldown_cmark::{BrokenLink, Event, LinkType, Options, Parser, Tag};
+use regex::Regex;
+use std::collections::BTreeSet;
+use std::sync::OnceLock;
+
+/// HTML tags that survive sanitization in strict mode; everything else is stripped
+pub const STRICT_MODE_TAGS: &[&str] = &[
+    "p", "br", "strong", "em", "code", "pre",
+    "h1", "h2", "h3", "h4", "h5", "h6",
+    "ul", "ol", "li", "blockquote",
+];
+
+/// Check a markdown slot for content that renders differently than the author expects
+pub fn lint_markdown(markdown: &str, slot: &str, slide_id: &str, result: &mut ValidationResult) {
+    if has_unclosed_fence(markdown) {
+        result.add_warning(ValidationError::UnclosedCodeFence {
+            slot: slot.to_string(),
+            slide_id: slide_id.to_string(),
+        });
+    }
+
+    let mut broken_references = BTreeSet::new();
+    let mut on_broken_link = |link: BrokenLink<'_>| {
+        // Shortcut references (`[text]`) are usually literal brackets, so only explicit
+        // `[text][label]` and `[label][]` forms are reported
+        if matches!(link.link_type, LinkType::Reference | LinkType::Collapsed) {
+            broken_references.insert(link.reference.to_string());
+        }
+        None
+    };
+    let parser = Parser::new_with_broken_link_callback(markdown, Options::empty(), Some(&mut on_broken_link));
+
+    let mut stripped_tags = BTreeSet::new();
+    let mut image: Option<(String, String)> = None;
+    let mut images_without_alt = Vec::new();
+    for event in parser {
+        match event {
+            Event::Start(Tag::Image(_, src, _)) => image = Some((src.to_string(), String::new())),
+            Event::End(Tag::Image(..)) => {
+                if let Some((src, alt)) = image.take() {
+                    if alt.trim().is_empty() {
+                        images_without_alt.push(src);
+                    }
+                }
+            }
+            Event::Text(text) | Event::Code(text) => {
+                if let Some((_, alt)) = image.as_mut() {
+                    alt.push_str(&text);
+                }
+            }
+            Event::Html(html) => {
+                stripped_tags.extend(
+                    html_tags(&html).filter(|tag| !STRICT_MODE_TAGS.contains(&tag.as_str())),
+                );
+            }
+            _ => {}
+        }
+    }
+
+    for reference in broken_references {
+        result.add_warning(ValidationError::BrokenReferenceLink {
+            reference,
+            slot: slot.to_string(),
+            slide_id: slide_id.to_string(),
+        });
+    }
+    for src in images_without_alt {
+        result.add_warning(ValidationError::ImageMissingAlt {
+            src,
+            slot: slot.to_string(),
+            slide_id: slide_id.to_string(),
+        });
+    }
+    for tag in stripped_tags {
+        result.add_warning(ValidationError::StrippedHtml {
+            tag,
+            slot: slot.to_string(),
+            slide_id: slide_id.to_string(),
+        });
+    }
+}
+
+/// Sources of the images a markdown document embeds
+pub fn image_sources(markdown: &str) -> Vec<String> {
+    Parser::new(markdown)
+        .filter_map(|event| match event {
+            Event::Start(Tag::Image(_, src, _)) => Some(src.to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Whether a fenced code block is still open at the end of the document
+fn has_unclosed_fence(markdown: &str) -> bool {
+    let mut open: Option<(char, usize)> = None;
+
+    for line in markdown.lines() {
+        let indent = line.len() - line.trim_start_matches(' ').len();
+        if indent > 3 {
+            continue;
+        }
+        let trimmed = &line[indent..];
+        let Some(fence_char) = trimmed.chars().next().filter(|c| *c == '`' || *c == '~') else {
+            continue;
+        };
+        let fence_len = trimmed.chars().take_while(|c| *c == fence_char).count();
+        if fence_len < 3 {
+            continue;
+        }
+
+        match open {
+            None => open = Some((fence_char, fence_len)),
+            Some((open_char, open_len)) => {
+                // A closing fence uses the same character, is at least as long, and has no info string
+                if fence_char == open_char && fence_len >= open_len && trimmed[fence_len..].trim().is_empty() {
+                    open = None;
+                }
+            }
+        }
+    }
+
+    open.is_some()
+}
+
+/// Lowercased names of the opening tags in a raw HTML fragment
+fn html_tags(html: &str) -> impl Iterator<Item = String> + '_ {
+    static OPENING_TAG: OnceLock<Regex> = OnceLock::new();
+    OPENING_TAG
+        .get_or_init(|| Regex::new(r"<([A-Za-z][A-Za-z0-9-]*)").expect("valid tag regex"))
+        .captures_iter(html)
+        .map(|captures| captures[1].to_ascii_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unclosed_fences() {
+        assert!(has_unclosed_fence("```rust\nfn main() {}\n"));
+        assert!(has_unclosed_fence("````\ncode\n```\n"));
+        assert!(!has_unclosed_fence("```rust\nfn main() {}\n```\n"));
+        assert!(!has_unclosed_fence("~~~\n```\n~~~"));
+        assert!(!has_unclosed_fence("Inline ``` is not a fence"));
+    }
+
+    #[test]
+    fn test_html_tags() {
+        let tags: Vec<String> = html_tags("<iframe src=\"x\"></iframe><BR/>").collect();
+        assert_eq!(tags, vec!["iframe", "br"]);
+    }
+}
@@ -0,0 +1,684 @@
+//! Server-side math: `$...$` and `$$...$$` in markdown rendered to MathML.
+//!
+//! Rendering happens before the markdown is parsed, so formulas reach exported
+//! HTML and PDF without client-side KaTeX. The converter covers the TeX slides
+//! commonly use: scripts, fractions, roots, Greek letters, operators and relations,
+//! accents, `\left`/`\right`, text, styled alphabets, and matrix environments.
+//! Unknown commands are shown as errors (`<merror>`) rather than dropped.
+
+/// Elements produced by [`tex_to_mathml`], for sanitizer allowlists
+pub const MATHML_TAGS: &[&str] = &[
+    "math", "semantics", "annotation", "mrow", "mi", "mn", "mo", "mtext", "mspace",
+    "msup", "msub", "msubsup", "mfrac", "msqrt", "mroot", "mover", "munder", "munderover",
+    "mtable", "mtr", "mtd", "merror",
+];
+
+/// Attributes produced by [`tex_to_mathml`], per element
+pub const MATHML_ATTRIBUTES: &[(&str, &[&str])] = &[
+    ("math", &["xmlns", "display"]),
+    ("annotation", &["encoding"]),
+    ("mi", &["mathvariant"]),
+    ("mo", &["stretchy"]),
+    ("mspace", &["width"]),
+    ("mfrac", &["linethickness"]),
+    ("mover", &["accent"]),
+    ("munder", &["accentunder"]),
+    ("mtable", &["columnalign"]),
+];
+
+/// Replace `$...$` (inline) and `$$...$$` (display) math in markdown with MathML.
+///
+/// Code spans and fenced code blocks are left alone, as is `\$`. Inline math
+/// follows the Pandoc rules so prices such as "$5 and $10" stay text: the opening
+/// `$` must not be followed by whitespace, and the closing `$` must not follow
+/// whitespace or precede a digit.
+pub fn render_math(markdown: &str) -> String {
+    if !markdown.contains('$') {
+        return markdown.to_string();
+    }
+    let mut output = String::with_capacity(markdown.len());
+    for (segment, is_code) in segments(markdown) {
+        if is_code {
+            output.push_str(segment);
+        } else {
+            replace_math(segment, &mut output);
+        }
+    }
+    output
+}
+
+/// Convert a TeX formula to a `<math>` element, keeping the source as an annotation
+pub fn tex_to_mathml(tex: &str, display: bool) -> String {
+    let mut parser = TexParser { tokens: tokenize(tex), pos: 0 };
+    let mut body = String::new();
+    loop {
+        body.push_str(&parser.row());
+        // Stray `}`, `&`, `\\`, or `\right` at the top level
+        if parser.next().is_none() {
+            break;
+        }
+    }
+    format!(
+        r#"<math xmlns="http://www.w3.org/1998/Math/MathML"{}><semantics><mrow>{}</mrow><annotation encoding="application/x-tex">{}</annotation></semantics></math>"#,
+        if display { r#" display="block""# } else { "" },
+        body,
+        escape(tex.trim())
+    )
+}
+
+/// Split markdown into prose and code (fenced blocks and code spans), in order
+fn segments(markdown: &str) -> Vec<(&str, bool)> {
+    let bytes = markdown.as_bytes();
+    let mut segments = Vec::new();
+    let (mut start, mut i) = (0, 0);
+    while i < bytes.len() {
+        if i == 0 || bytes[i - 1] == b'\n' {
+            if let Some(len) = fenced_block_len(&markdown[i..]) {
+                segments.push((&markdown[start..i], false));
+                segments.push((&markdown[i..i + len], true));
+                i += len;
+                start = i;
+                continue;
+            }
+        }
+        if bytes[i] == b'`' {
+            let run = bytes[i..].iter().take_while(|b| **b == b'`').count();
+            if let Some(close) = backtick_run(&markdown[i + run..], run) {
+                let end = i + run + close + run;
+                segments.push((&markdown[start..i], false));
+                segments.push((&markdown[i..end], true));
+                i = end;
+                start = i;
+                continue;
+            }
+            i += run;
+            continue;
+        }
+        i += 1;
+    }
+    segments.push((&markdown[start..], false));
+    segments
+}
+
+/// Length of the fenced code block starting `text`, through its closing fence or the end
+fn fenced_block_len(text: &str) -> Option<usize> {
+    let fence_of = |line: &str| {
+        let trimmed = line.trim_start_matches(' ');
+        let marker = trimmed.chars().next().filter(|c| *c == '`' || *c == '~')?;
+        let run = trimmed.chars().take_while(|c| *c == marker).count();
+        (line.len() - trimmed.len() <= 3 && run >= 3).then_some((marker, run, trimmed[run..].trim().is_empty()))
+    };
+    let first_line = text.split_inclusive('\n').next()?;
+    let (marker, run, _) = fence_of(first_line)?;
+    let mut len = first_line.len();
+    for line in text[len..].split_inclusive('\n') {
+        len += line.len();
+        if fence_of(line).is_some_and(|(m, r, bare)| m == marker && r >= run && bare) {
+            break;
+        }
+    }
+    Some(len)
+}
+
+/// Offset of the next run of exactly `run` backticks
+fn backtick_run(text: &str, run: usize) -> Option<usize> {
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'`' {
+            let len = bytes[i..].iter().take_while(|b| **b == b'`').count();
+            if len == run {
+                return Some(i);
+            }
+            i += len;
+        } else {
+            i += 1;
+        }
+    }
+    None
+}
+
+fn replace_math(prose: &str, output: &mut String) {
+    let mut i = 0;
+    while let Some(offset) = prose[i..].find('$') {
+        let pos = i + offset;
+        if prose[..pos].ends_with('\\') {
+            output.push_str(&prose[i..=pos]);
+            i = pos + 1;
+            continue;
+        }
+        let display = prose[pos + 1..].starts_with('$');
+        let body_start = pos + if display { 2 } else { 1 };
+        let body_len = if display {
+            prose[body_start..].find("$$").filter(|len| !prose[body_start..body_start + len].trim().is_empty())
+        } else {
+            inline_math_len(&prose[body_start..])
+        };
+        let Some(body_len) = body_len else {
+            output.push_str(&prose[i..body_start]);
+            i = body_start;
+            continue;
+        };
+        output.push_str(&prose[i..pos]);
+        output.push_str(&tex_to_mathml(&prose[body_start..body_start + body_len], display));
+        i = body_start + body_len + if display { 2 } else { 1 };
+    }
+    output.push_str(&prose[i..]);
+}
+
+/// Length of inline math starting `text` (just after the opening `$`), if it is closed
+fn inline_math_len(text: &str) -> Option<usize> {
+    if text.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let bytes = text.as_bytes();
+    let close = (0..bytes.len()).find(|&i| bytes[i] == b'$' && (i == 0 || bytes[i - 1] != b'\\'))?;
+    let body = &text[..close];
+    let valid = !body.is_empty()
+        && !body.ends_with(char::is_whitespace)
+        && !body.contains("\n\n")
+        && !text[close + 1..].starts_with(|c: char| c.is_ascii_digit());
+    valid.then_some(close)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Command(String),
+    /// Raw argument of a text command such as `\text{...}`
+    Text(String, String),
+    Open,
+    Close,
+    Sup,
+    Sub,
+    Align,
+    NewRow,
+    Number(String),
+    Char(char),
+}
+
+/// Commands whose argument is text, kept with its spaces
+const TEXT_COMMANDS: &[&str] = &["text", "textrm", "textnormal", "textbf", "textit", "mbox", "operatorname"];
+
+fn tokenize(tex: &str) -> Vec<Token> {
+    let chars: Vec<char> = tex.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        i += 1;
+        match c {
+            c if c.is_whitespace() => {}
+            '{' => tokens.push(Token::Open),
+            '}' => tokens.push(Token::Close),
+            '^' => tokens.push(Token::Sup),
+            '_' => tokens.push(Token::Sub),
+            '&' => tokens.push(Token::Align),
+            '\\' if chars.get(i) == Some(&'\\') => {
+                i += 1;
+                tokens.push(Token::NewRow);
+            }
+            '\\' => {
+                let name: String = chars[i..].iter().take_while(|c| c.is_ascii_alphabetic()).collect();
+                let name = match name.is_empty() {
+                    true => chars.get(i).map(|c| c.to_string()).unwrap_or_default(),
+                    false => name,
+                };
+                i += name.chars().count();
+                if TEXT_COMMANDS.contains(&name.as_str()) {
+                    let start = i + chars[i..].iter().take_while(|c| c.is_whitespace()).count();
+                    if chars.get(start) == Some(&'{') {
+                        let mut depth = 0;
+                        let end = (start..chars.len()).find(|&j| {
+                            match chars[j] {
+                                '{' => depth += 1,
+                                '}' => depth -= 1,
+                                _ => {}
+                            }
+                            depth == 0
+                        });
+                        let end = end.unwrap_or(chars.len());
+                        tokens.push(Token::Text(name, chars[start + 1..end.max(start + 1)].iter().collect()));
+                        i = end + 1;
+                        continue;
+                    }
+                }
+                tokens.push(Token::Command(name));
+            }
+            c if c.is_ascii_digit() || (c == '.' && chars.get(i).is_some_and(|d| d.is_ascii_digit())) => {
+                let mut number = c.to_string();
+                while let Some(&d) = chars.get(i).filter(|d| d.is_ascii_digit() || **d == '.') {
+                    number.push(d);
+                    i += 1;
+                }
+                tokens.push(Token::Number(number));
+            }
+            c => tokens.push(Token::Char(c)),
+        }
+    }
+    tokens
+}
+
+const GREEK: &[(&str, char)] = &[
+    ("alpha", 'α'), ("beta", 'β'), ("gamma", 'γ'), ("delta", 'δ'), ("epsilon", 'ϵ'), ("varepsilon", 'ε'),
+    ("zeta", 'ζ'), ("eta", 'η'), ("theta", 'θ'), ("vartheta", 'ϑ'), ("iota", 'ι'), ("kappa", 'κ'),
+    ("lambda", 'λ'), ("mu", 'μ'), ("nu", 'ν'), ("xi", 'ξ'), ("pi", 'π'), ("varpi", 'ϖ'), ("rho", 'ρ'),
+    ("varrho", 'ϱ'), ("sigma", 'σ'), ("varsigma", 'ς'), ("tau", 'τ'), ("upsilon", 'υ'), ("phi", 'ϕ'),
+    ("varphi", 'φ'), ("chi", 'χ'), ("psi", 'ψ'), ("omega", 'ω'),
+    ("Gamma", 'Γ'), ("Delta", 'Δ'), ("Theta", 'Θ'), ("Lambda", 'Λ'), ("Xi", 'Ξ'), ("Pi", 'Π'),
+    ("Sigma", 'Σ'), ("Upsilon", 'Υ'), ("Phi", 'Φ'), ("Psi", 'Ψ'), ("Omega", 'Ω'),
+    ("infty", '∞'), ("emptyset", '∅'), ("varnothing", '∅'), ("partial", '∂'), ("nabla", '∇'),
+    ("ell", 'ℓ'), ("hbar", 'ℏ'), ("Re", 'ℜ'), ("Im", 'ℑ'), ("aleph", 'ℵ'),
+];
+
+const OPERATORS: &[(&str, &str)] = &[
+    ("cdot", "⋅"), ("times", "×"), ("div", "÷"), ("pm", "±"), ("mp", "∓"), ("ast", "∗"), ("star", "⋆"),
+    ("circ", "∘"), ("bullet", "∙"), ("oplus", "⊕"), ("otimes", "⊗"),
+    ("le", "≤"), ("leq", "≤"), ("ge", "≥"), ("geq", "≥"), ("ne", "≠"), ("neq", "≠"), ("ll", "≪"), ("gg", "≫"),
+    ("approx", "≈"), ("equiv", "≡"), ("sim", "∼"), ("simeq", "≃"), ("cong", "≅"), ("propto", "∝"),
+    ("to", "→"), ("rightarrow", "→"), ("leftarrow", "←"), ("gets", "←"), ("leftrightarrow", "↔"),
+    ("Rightarrow", "⇒"), ("Leftarrow", "⇐"), ("Leftrightarrow", "⇔"), ("implies", "⟹"), ("iff", "⟺"),
+    ("mapsto", "↦"), ("uparrow", "↑"), ("downarrow", "↓"),
+    ("in", "∈"), ("notin", "∉"), ("ni", "∋"), ("subset", "⊂"), ("subseteq", "⊆"), ("supset", "⊃"),
+    ("supseteq", "⊇"), ("cup", "∪"), ("cap", "∩"), ("setminus", "∖"),
+    ("forall", "∀"), ("exists", "∃"), ("neg", "¬"), ("lnot", "¬"), ("land", "∧"), ("lor", "∨"),
+    ("wedge", "∧"), ("vee", "∨"), ("perp", "⊥"), ("parallel", "∥"), ("mid", "∣"),
+    ("ldots", "…"), ("dots", "…"), ("cdots", "⋯"), ("vdots", "⋮"), ("ddots", "⋱"),
+    ("langle", "⟨"), ("rangle", "⟩"), ("lfloor", "⌊"), ("rfloor", "⌋"), ("lceil", "⌈"), ("rceil", "⌉"),
+    ("vert", "|"), ("Vert", "‖"), ("{", "{"), ("}", "}"), ("|", "‖"),
+    ("int", "∫"), ("iint", "∬"), ("oint", "∮"),
+];
+
+/// Operators whose scripts go above and below in display math
+const LARGE_OPERATORS: &[(&str, &str)] = &[
+    ("sum", "∑"), ("prod", "∏"), ("coprod", "∐"), ("bigcup", "⋃"), ("bigcap", "⋂"),
+    ("lim", "lim"), ("max", "max"), ("min", "min"), ("sup", "sup"), ("inf", "inf"),
+    ("limsup", "lim sup"), ("liminf", "lim inf"),
+];
+
+const FUNCTIONS: &[&str] = &[
+    "sin", "cos", "tan", "cot", "sec", "csc", "arcsin", "arccos", "arctan", "sinh", "cosh", "tanh",
+    "log", "ln", "lg", "exp", "det", "dim", "ker", "gcd", "deg", "arg", "Pr", "mod",
+];
+
+const SPACES: &[(&str, &str)] = &[
+    (",", "0.1667em"), (":", "0.2222em"), (">", "0.2222em"), (";", "0.2778em"), (" ", "0.25em"),
+    ("quad", "1em"), ("qquad", "2em"), ("!", "-0.1667em"),
+];
+
+const ACCENTS: &[(&str, &str)] = &[
+    ("hat", "^"), ("widehat", "^"), ("bar", "¯"), ("overline", "‾"), ("vec", "→"), ("dot", "˙"),
+    ("ddot", "¨"), ("tilde", "~"), ("widetilde", "~"), ("overrightarrow", "→"),
+];
+
+/// Commands that only change sizing or spacing, which MathML handles itself
+const IGNORED: &[&str] = &[
+    "displaystyle", "textstyle", "limits", "nolimits", "big", "Big", "bigg", "Bigg",
+    "bigl", "bigr", "Bigl", "Bigr", "biggl", "biggr",
+];
+
+struct TexParser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl TexParser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    /// Nodes up to `}`, `&`, `\\`, `\right`, `\end`, or the end; the terminator is not consumed
+    fn row(&mut self) -> String {
+        let mut row = String::new();
+        while let Some(token) = self.peek() {
+            let ends = matches!(token, Token::Close | Token::Align | Token::NewRow)
+                || matches!(token, Token::Command(name) if name == "right" || name == "end");
+            if ends {
+                break;
+            }
+            row.push_str(&self.scripted());
+        }
+        row
+    }
+
+    /// An atom with its sub- and superscripts
+    fn scripted(&mut self) -> String {
+        let (base, large) = self.atom();
+        let (mut sub, mut sup) = (None, None);
+        loop {
+            match self.peek() {
+                Some(Token::Sub) if sub.is_none() => {
+                    self.next();
+                    sub = Some(self.argument());
+                }
+                Some(Token::Sup) if sup.is_none() => {
+                    self.next();
+                    sup = Some(self.argument());
+                }
+                _ => break,
+            }
+        }
+        let (one_below, one_above, both) = match large {
+            true => ("munder", "mover", "munderover"),
+            false => ("msub", "msup", "msubsup"),
+        };
+        match (sub, sup) {
+            (None, None) => base,
+            (Some(sub), None) => format!("<{0}>{1}{2}</{0}>", one_below, base, sub),
+            (None, Some(sup)) => format!("<{0}>{1}{2}</{0}>", one_above, base, sup),
+            (Some(sub), Some(sup)) => format!("<{0}>{1}{2}{3}</{0}>", both, base, sub, sup),
+        }
+    }
+
+    /// A braced group or a single atom, as one node
+    fn argument(&mut self) -> String {
+        self.atom().0
+    }
+
+    /// One node, and whether it is a large operator
+    fn atom(&mut self) -> (String, bool) {
+        let Some(token) = self.peek().cloned() else {
+            return ("<mrow></mrow>".to_string(), false);
+        };
+        if matches!(token, Token::Sub | Token::Sup) {
+            // A script with no base
+            return ("<mrow></mrow>".to_string(), false);
+        }
+        self.next();
+        let node = match token {
+            Token::Open => {
+                let inner = self.row();
+                if self.peek() == Some(&Token::Close) {
+                    self.next();
+                }
+                format!("<mrow>{}</mrow>", inner)
+            }
+            Token::Number(number) => format!("<mn>{}</mn>", escape(&number)),
+            Token::Char(c) if c.is_alphabetic() => format!("<mi>{}</mi>", escape(&c.to_string())),
+            Token::Char('\'') => "<mo>′</mo>".to_string(),
+            Token::Char('-') => "<mo>−</mo>".to_string(),
+            Token::Char(c) => format!("<mo>{}</mo>", escape(&c.to_string())),
+            Token::Text(name, text) => match name.as_str() {
+                "operatorname" => format!(r#"<mi mathvariant="normal">{}</mi>"#, escape(&text)),
+                _ => format!("<mtext>{}</mtext>", escape(&text)),
+            },
+            Token::Command(name) => return self.command(&name),
+            // Terminators are consumed by their constructs; reaching one here means it was unbalanced
+            Token::Close | Token::Align | Token::NewRow | Token::Sub | Token::Sup => String::new(),
+        };
+        (node, false)
+    }
+
+    fn command(&mut self, name: &str) -> (String, bool) {
+        if let Some((_, c)) = GREEK.iter().find(|(n, _)| *n == name) {
+            let upright = c.is_uppercase() && name.starts_with(char::is_uppercase);
+            let variant = if upright { r#" mathvariant="normal""# } else { "" };
+            return (format!("<mi{}>{}</mi>", variant, c), false);
+        }
+        if let Some((_, op)) = OPERATORS.iter().find(|(n, _)| *n == name) {
+            return (format!("<mo>{}</mo>", escape(op)), false);
+        }
+        if let Some((_, op)) = LARGE_OPERATORS.iter().find(|(n, _)| *n == name) {
+            return (format!("<mo>{}</mo>", op), true);
+        }
+        if FUNCTIONS.contains(&name) {
+            return (format!("<mi>{}</mi>", name), false);
+        }
+        if let Some((_, width)) = SPACES.iter().find(|(n, _)| *n == name) {
+            return (format!(r#"<mspace width="{}"></mspace>"#, width), false);
+        }
+        if let Some((_, accent)) = ACCENTS.iter().find(|(n, _)| *n == name) {
+            let base = self.argument();
+            return (format!(r#"<mover accent="true">{}<mo>{}</mo></mover>"#, base, escape(accent)), false);
+        }
+        if IGNORED.contains(&name) {
+            return (String::new(), false);
+        }
+        let node = match name {
+            "frac" | "dfrac" | "tfrac" => {
+                let (numerator, denominator) = (self.argument(), self.argument());
+                format!("<mfrac>{}{}</mfrac>", numerator, denominator)
+            }
+            "binom" => {
+                let (n, k) = (self.argument(), self.argument());
+                format!(r#"<mrow><mo>(</mo><mfrac linethickness="0">{}{}</mfrac><mo>)</mo></mrow>"#, n, k)
+            }
+            "sqrt" => {
+                let index = (self.peek() == Some(&Token::Char('['))).then(|| {
+                    self.next();
+                    let mut index = String::new();
+                    while self.peek().is_some_and(|t| *t != Token::Char(']')) {
+                        index.push_str(&self.scripted());
+                    }
+                    self.next();
+                    index
+                });
+                let radicand = self.argument();
+                match index {
+                    Some(index) => format!("<mroot>{}<mrow>{}</mrow></mroot>", radicand, index),
+                    None => format!("<msqrt>{}</msqrt>", radicand),
+                }
+            }
+            "underline" => format!(r#"<munder accentunder="true">{}<mo>_</mo></munder>"#, self.argument()),
+            "mathrm" | "mathit" | "mathbf" | "boldsymbol" | "mathbb" | "mathcal" | "mathsf" | "mathtt" => self.styled(name),
+            "left" => {
+                let open = self.delimiter();
+                let inner = self.row();
+                let close = match self.peek() {
+                    Some(Token::Command(name)) if name == "right" => {
+                        self.next();
+                        self.delimiter()
+                    }
+                    _ => String::new(),
+                };
+                format!("<mrow>{}{}{}</mrow>", open, inner, close)
+            }
+            "begin" => self.environment(),
+            "$" | "%" | "#" | "&" | "_" => format!("<mo>{}</mo>", escape(name)),
+            _ => format!("<merror><mtext>{}</mtext></merror>", escape(&format!("\\{}", name))),
+        };
+        (node, false)
+    }
+
+    /// The delimiter after `\left` or `\right`; `.` is none
+    fn delimiter(&mut self) -> String {
+        let symbol = match self.next() {
+            Some(Token::Char('.')) | None => return String::new(),
+            Some(Token::Char(c)) => c.to_string(),
+            Some(Token::Command(name)) => match OPERATORS.iter().find(|(n, _)| *n == name) {
+                Some((_, op)) => op.to_string(),
+                None => return String::new(),
+            },
+            Some(_) => return String::new(),
+        };
+        format!(r#"<mo stretchy="true">{}</mo>"#, escape(&symbol))
+    }
+
+    /// `\mathbb{R}` and friends; MathML Core only styles via Unicode alphanumerics
+    fn styled(&mut self, style: &str) -> String {
+        let start = self.pos;
+        let letters = match self.next() {
+            Some(Token::Char(c)) if c.is_ascii_alphabetic() => Some(c.to_string()),
+            Some(Token::Open) => {
+                let mut letters = String::new();
+                while let Some(Token::Char(c)) = self.peek().cloned().filter(|t| matches!(t, Token::Char(c) if c.is_ascii_alphabetic())) {
+                    letters.push(c);
+                    self.next();
+                }
+                (!letters.is_empty() && self.next() == Some(Token::Close)).then_some(letters)
+            }
+            _ => None,
+        };
+        let Some(letters) = letters else {
+            // Not plain letters: render the argument unstyled
+            self.pos = start;
+            return self.argument();
+        };
+        let styled: String = letters.chars().map(|c| styled_letter(c, style)).collect();
+        match style {
+            "mathrm" if styled.chars().count() == 1 => format!(r#"<mi mathvariant="normal">{}</mi>"#, styled),
+            _ => format!("<mi>{}</mi>", styled),
+        }
+    }
+
+    /// `\begin{name} ... \end{name}` as a table, with fences for the matrix variants
+    fn environment(&mut self) -> String {
+        let name = self.group_name();
+        if name == "array" {
+            // Column spec
+            self.group_name();
+        }
+        let mut rows: Vec<Vec<String>> = Vec::new();
+        loop {
+            let mut cells = vec![self.row()];
+            while self.peek() == Some(&Token::Align) {
+                self.next();
+                cells.push(self.row());
+            }
+            rows.push(cells);
+            match self.next() {
+                Some(Token::NewRow) => continue,
+                Some(Token::Command(end)) if end == "end" => {
+                    self.group_name();
+                    break;
+                }
+                _ => break,
+            }
+        }
+        if rows.len() > 1 && rows.last().is_some_and(|cells| cells.len() == 1 && cells[0].is_empty()) {
+            rows.pop();
+        }
+        let body: String = rows
+            .iter()
+            .map(|cells| format!("<mtr>{}</mtr>", cells.iter().map(|c| format!("<mtd>{}</mtd>", c)).collect::<String>()))
+            .collect();
+        let (open, close, align) = match name.as_str() {
+            "pmatrix" => ("(", ")", None),
+            "bmatrix" => ("[", "]", None),
+            "Bmatrix" => ("{", "}", None),
+            "vmatrix" => ("|", "|", None),
+            "Vmatrix" => ("‖", "‖", None),
+            "cases" => ("{", "", Some("left left")),
+            "aligned" | "align" | "align*" | "split" => ("", "", Some("right left")),
+            _ => ("", "", None),
+        };
+        let align = align.map(|a| format!(r#" columnalign="{}""#, a)).unwrap_or_default();
+        let fence = |f: &str| match f {
+            "" => String::new(),
+            f => format!(r#"<mo stretchy="true">{}</mo>"#, escape(f)),
+        };
+        format!("<mrow>{}<mtable{}>{}</mtable>{}</mrow>", fence(open), align, body, fence(close))
+    }
+
+    /// The plain text of a `{...}` group, such as an environment name
+    fn group_name(&mut self) -> String {
+        let mut name = String::new();
+        if self.peek() != Some(&Token::Open) {
+            return name;
+        }
+        self.next();
+        while let Some(token) = self.next() {
+            match token {
+                Token::Close => break,
+                Token::Char(c) => name.push(c),
+                Token::Number(n) => name.push_str(&n),
+                _ => {}
+            }
+        }
+        name
+    }
+}
+
+fn styled_letter(c: char, style: &str) -> char {
+    let offset = |base: u32, from: char| char::from_u32(base + (c as u32 - from as u32)).unwrap_or(c);
+    match (style, c) {
+        ("mathbb", 'C') => 'ℂ',
+        ("mathbb", 'H') => 'ℍ',
+        ("mathbb", 'N') => 'ℕ',
+        ("mathbb", 'P') => 'ℙ',
+        ("mathbb", 'Q') => 'ℚ',
+        ("mathbb", 'R') => 'ℝ',
+        ("mathbb", 'Z') => 'ℤ',
+        ("mathbb", 'A'..='Z') => offset(0x1D538, 'A'),
+        ("mathbb", 'a'..='z') => offset(0x1D552, 'a'),
+        ("mathbf" | "boldsymbol", 'A'..='Z') => offset(0x1D400, 'A'),
+        ("mathbf" | "boldsymbol", 'a'..='z') => offset(0x1D41A, 'a'),
+        ("mathcal", 'B') => 'ℬ',
+        ("mathcal", 'E') => 'ℰ',
+        ("mathcal", 'F') => 'ℱ',
+        ("mathcal", 'H') => 'ℋ',
+        ("mathcal", 'I') => 'ℐ',
+        ("mathcal", 'L') => 'ℒ',
+        ("mathcal", 'M') => 'ℳ',
+        ("mathcal", 'R') => 'ℛ',
+        ("mathcal", 'A'..='Z') => offset(0x1D49C, 'A'),
+        ("mathit", 'A'..='Z') => offset(0x1D434, 'A'),
+        ("mathit", 'h') => 'ℎ',
+        ("mathit", 'a'..='z') => offset(0x1D44E, 'a'),
+        ("mathsf", 'A'..='Z') => offset(0x1D5A0, 'A'),
+        ("mathsf", 'a'..='z') => offset(0x1D5BA, 'a'),
+        ("mathtt", 'A'..='Z') => offset(0x1D670, 'A'),
+        ("mathtt", 'a'..='z') => offset(0x1D68A, 'a'),
+        _ => c,
+    }
+}
+
+/// Escape text for HTML. ASCII punctuation becomes numeric references as well, so
+/// the markdown parser that later sees the MathML cannot read `*`, `_`, or `\` as markup.
+fn escape(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            c if c.is_ascii_punctuation() => format!("&#{};", c as u32),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn body(tex: &str) -> String {
+        let math = tex_to_mathml(tex, false);
+        let start = math.find("<semantics><mrow>").unwrap() + "<semantics><mrow>".len();
+        let end = math.find("</mrow><annotation").unwrap();
+        math[start..end].to_string()
+    }
+
+    #[test]
+    fn test_tex_to_mathml() {
+        assert_eq!(body("x^2 + 1"), "<msup><mi>x</mi><mn>2</mn></msup><mo>&#43;</mo><mn>1</mn>");
+        assert_eq!(body(r"\frac{a}{b}"), "<mfrac><mrow><mi>a</mi></mrow><mrow><mi>b</mi></mrow></mfrac>");
+        assert_eq!(body(r"\sqrt[3]{x}"), "<mroot><mrow><mi>x</mi></mrow><mrow><mn>3</mn></mrow></mroot>");
+        assert_eq!(body(r"\sum_{i=1}^n i"), "<munderover><mo>∑</mo><mrow><mi>i</mi><mo>&#61;</mo><mn>1</mn></mrow><mi>n</mi></munderover><mi>i</mi>");
+        assert_eq!(body(r"\alpha \le \Omega"), r#"<mi>α</mi><mo>≤</mo><mi mathvariant="normal">Ω</mi>"#);
+        assert_eq!(body(r"x \in \mathbb{R}"), "<mi>x</mi><mo>∈</mo><mi>ℝ</mi>");
+        assert_eq!(body(r"\text{if } x"), "<mtext>if </mtext><mi>x</mi>");
+        assert_eq!(
+            body(r"\left( a \right)"),
+            r#"<mrow><mo stretchy="true">&#40;</mo><mi>a</mi><mo stretchy="true">&#41;</mo></mrow>"#
+        );
+        assert_eq!(
+            body(r"\begin{pmatrix} 1 & 0 \\ 0 & 1 \end{pmatrix}"),
+            r#"<mrow><mo stretchy="true">&#40;</mo><mtable><mtr><mtd><mn>1</mn></mtd><mtd><mn>0</mn></mtd></mtr><mtr><mtd><mn>0</mn></mtd><mtd><mn>1</mn></mtd></mtr></mtable><mo stretchy="true">&#41;</mo></mrow>"#
+        );
+        assert_eq!(body(r"\foo"), "<merror><mtext>&#92;foo</mtext></merror>");
+        assert!(tex_to_mathml("x", true).contains(r#" display="block""#));
+    }
+
+    #[test]
+    fn test_render_math_skips_code_and_prices() {
+        let rendered = render_math("Energy $E = mc^2$ costs $5 and $10.\n\n$$\n\\frac{1}{2}\n$$\n");
+        assert!(rendered.starts_with("Energy <math"));
+        assert!(rendered.contains("costs $5 and $10."));
+        assert!(rendered.contains(r#"<math xmlns="http://www.w3.org/1998/Math/MathML" display="block">"#));
+
+        let code = "Inline `$x$` stays.\n\n```\n$y$\n```\n\\$z$ too";
+        assert_eq!(render_math(code), code);
+    }
+}
@@ -0,0 +1,265 @@
+//! Content-Security-Policy and Permissions-Policy derived from component capabilities.
+//!
+//! Only the components a deck actually uses contribute. A deck whose components
+//! declare no capabilities gets a policy that allows scripts from the deck's
+//! origin and module hosts, and blocks every other network access and browser feature.
+
+use crate::ir::{ComponentRegistry, SlideDoc, Slot};
+use base64::Engine;
+use regex::Regex;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::OnceLock;
+
+/// Sources a capability adds to CSP directives
+const CAPABILITY_SOURCES: &[(&str, &str, &str)] = &[
+    ("network.fetch", "connect-src", "https:"),
+    ("network.fetch", "img-src", "https:"),
+    ("network.fetch", "font-src", "https:"),
+    ("network.fetch", "style-src", "https:"),
+    ("rooms.ws", "connect-src", "ws:"),
+    ("rooms.ws", "connect-src", "wss:"),
+    ("media.remote", "media-src", "https:"),
+];
+
+/// Browser features a capability grants to the deck's origin
+const CAPABILITY_FEATURES: &[(&str, &str)] = &[
+    ("media.camera", "camera"),
+    ("media.microphone", "microphone"),
+    ("media.display", "display-capture"),
+    ("clipboard.read", "clipboard-read"),
+    ("clipboard.write", "clipboard-write"),
+    ("geolocation", "geolocation"),
+];
+
+/// Features listed in Permissions-Policy; denied unless a capability grants them
+const POLICY_FEATURES: &[&str] = &[
+    "camera",
+    "clipboard-read",
+    "clipboard-write",
+    "display-capture",
+    "fullscreen",
+    "geolocation",
+    "microphone",
+    "payment",
+    "usb",
+];
+
+/// CSP directives and Permissions-Policy features for a deck
+#[derive(Debug, Clone, PartialEq)]
+pub struct SecurityPolicy {
+    directives: BTreeMap<&'static str, BTreeSet<String>>,
+    features: BTreeSet<&'static str>,
+    /// Capabilities of the components the policy was built for
+    pub capabilities: BTreeSet<String>,
+}
+
+impl Default for SecurityPolicy {
+    fn default() -> Self {
+        let base: &[(&'static str, &[&str])] = &[
+            ("default-src", &["'self'"]),
+            ("script-src", &["'self'"]),
+            // Style overrides are rendered as inline style attributes
+            ("style-src", &["'self'", "'unsafe-inline'"]),
+            ("img-src", &["'self'", "data:"]),
+            ("font-src", &["'self'", "data:"]),
+            ("connect-src", &["'self'"]),
+            ("media-src", &["'self'"]),
+            ("object-src", &["'none'"]),
+        ];
+        Self {
+            directives: base
+                .iter()
+                .map(|(name, sources)| (*name, sources.iter().map(|s| s.to_string()).collect()))
+                .collect(),
+            // The runtime's presenter view goes fullscreen
+            features: BTreeSet::from(["fullscreen"]),
+            capabilities: BTreeSet::new(),
+        }
+    }
+}
+
+impl SecurityPolicy {
+    /// Policy for the components used by `slides`, including components placed in slots
+    pub fn for_slides<'a>(slides: impl IntoIterator<Item = &'a SlideDoc>, registry: Option<&ComponentRegistry>) -> Self {
+        let mut policy = Self::default();
+        for slide in slides {
+            // Narration hosted elsewhere is played by the narrated player
+            if let Some(origin) = slide.narration.as_ref().and_then(|n| origin(&n.src)) {
+                policy.add_source("media-src", &origin);
+            }
+            let Some(registry) = registry else {
+                continue;
+            };
+            let manifest = registry
+                .resolve(&slide.component.name, &slide.component.version_req)
+                .or_else(|| registry.components.get(&slide.component.name));
+            policy.add_capabilities(manifest.iter().flat_map(|m| &m.capabilities));
+            policy.add_module(manifest.map_or("", |m| m.module.as_str()));
+
+            for slot in slide.slots.values() {
+                if let Slot::Component { tag, module, .. } = slot {
+                    let slotted = registry.tag_to_name.get(tag).and_then(|name| registry.components.get(name));
+                    policy.add_capabilities(slotted.iter().flat_map(|m| &m.capabilities));
+                    policy.add_module(module);
+                }
+            }
+        }
+        policy
+    }
+
+    /// Add the CSP sources and features for each capability; unknown capabilities add nothing
+    pub fn add_capabilities<'a>(&mut self, capabilities: impl IntoIterator<Item = &'a String>) {
+        for capability in capabilities {
+            for (_, directive, source) in CAPABILITY_SOURCES.iter().filter(|(c, _, _)| c == capability) {
+                self.add_source(directive, source);
+            }
+            for (_, feature) in CAPABILITY_FEATURES.iter().filter(|(c, _)| c == capability) {
+                self.features.insert(feature);
+            }
+            self.capabilities.insert(capability.clone());
+        }
+    }
+
+    /// Allow scripts from a module's origin when it is loaded from another host
+    pub fn add_module(&mut self, url: &str) {
+        if let Some(origin) = origin(url) {
+            self.add_source("script-src", &origin);
+        }
+    }
+
+    /// Allow the inline scripts in `html` by hash. JSON data blocks are not executed,
+    /// so they are left out.
+    pub fn add_inline_scripts(&mut self, html: &str) {
+        static SCRIPT: OnceLock<Regex> = OnceLock::new();
+        let script = SCRIPT.get_or_init(|| Regex::new(r"(?s)<script([^>]*)>(.*?)</script>").unwrap());
+        for captures in script.captures_iter(html) {
+            let attributes = &captures[1];
+            if attributes.contains("src=") || attributes.contains("application/json") {
+                continue;
+            }
+            let hash = base64::engine::general_purpose::STANDARD.encode(Sha256::digest(captures[2].as_bytes()));
+            self.add_source("script-src", &format!("'sha256-{}'", hash));
+        }
+    }
+
+    /// Allow inline scripts carrying `nonce="<nonce>"`
+    pub fn add_nonce(&mut self, nonce: &str) {
+        self.add_source("script-src", &format!("'nonce-{}'", nonce));
+    }
+
+    /// Tighten the policy for a standalone exported file: nothing loads that no
+    /// directive names, forms cannot submit anywhere, and `<base>` may only
+    /// point at local files
+    pub fn strict(mut self) -> Self {
+        self.directives.insert("default-src", BTreeSet::from(["'none'".to_string()]));
+        self.add_source("base-uri", "file:");
+        self.add_source("form-action", "'none'");
+        self
+    }
+
+    pub fn add_source(&mut self, directive: &'static str, source: &str) {
+        self.directives.entry(directive).or_default().insert(source.to_string());
+    }
+
+    /// Whether `directive` allows `source` exactly as written
+    pub fn allows(&self, directive: &str, source: &str) -> bool {
+        self.directives.get(directive).is_some_and(|sources| sources.contains(source))
+    }
+
+    /// Value for the `Content-Security-Policy` header or meta tag
+    pub fn csp(&self) -> String {
+        self.directives
+            .iter()
+            .map(|(name, sources)| format!("{} {}", name, sources.iter().cloned().collect::<Vec<_>>().join(" ")))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+
+    /// Value for the `Permissions-Policy` header
+    pub fn permissions_policy(&self) -> String {
+        POLICY_FEATURES
+            .iter()
+            .map(|feature| match self.features.contains(feature) {
+                true => format!("{}=(self)", feature),
+                false => format!("{}=()", feature),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// `scheme://host[:port]` of an absolute http(s) URL
+fn origin(url: &str) -> Option<String> {
+    let (scheme, rest) = url.split_once("://")?;
+    if scheme != "http" && scheme != "https" {
+        return None;
+    }
+    let host = rest.split(['/', '?', '#']).next().filter(|h| !h.is_empty())?;
+    Some(format!("{}://{}", scheme, host))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::{ComponentManifest, ComponentSpec, Narration};
+    use std::collections::HashMap;
+    use std::path::Path;
+
+    fn manifest(name: &str, module: &str, capabilities: &[&str]) -> ComponentManifest {
+        serde_json::from_value(serde_json::json!({
+            "name": name, "version": "1.0.0", "tag": format!("x-{}", name.to_lowercase()),
+            "module": module, "schema": {}, "capabilities": capabilities,
+        }))
+        .unwrap()
+    }
+
+    fn slide(component: &str) -> SlideDoc {
+        SlideDoc {
+            model_version: "1.0".to_string(),
+            id: component.to_lowercase(),
+            component: ComponentSpec { name: component.to_string(), version_req: String::new() },
+            props: serde_json::json!({}),
+            slots: HashMap::new(),
+            tags: Vec::new(),
+            style_overrides: HashMap::new(),
+            locale: None,
+            dir: None,
+            notes: Vec::new(),
+            narration: None,
+        }
+    }
+
+    #[test]
+    fn test_policy_follows_used_capabilities() {
+        let mut registry = ComponentRegistry::default();
+        registry.insert(manifest("Title", "/packages/components/title.js", &[]), Path::new("t.json"));
+        registry.insert(manifest("Code", "https://cdn.example.com/code.js", &["network.fetch"]), Path::new("c.json"));
+        registry.insert(manifest("Cam", "/cam.js", &["media.camera"]), Path::new("m.json"));
+
+        let locked = SecurityPolicy::for_slides(&[slide("Title")], Some(&registry));
+        assert!(locked.csp().contains("connect-src 'self';"));
+        assert!(locked.permissions_policy().contains("camera=()"));
+
+        let open = SecurityPolicy::for_slides(&[slide("Title"), slide("Code")], Some(&registry));
+        assert!(open.allows("connect-src", "https:"));
+        assert!(open.allows("script-src", "https://cdn.example.com"));
+        assert!(!open.permissions_policy().contains("camera=(self)"));
+
+        let mut policy = SecurityPolicy::for_slides(&[slide("Cam")], Some(&registry));
+        assert!(policy.permissions_policy().contains("camera=(self)"));
+        policy.add_inline_scripts("<script type=\"module\">go()</script><script type=\"application/json\">{}</script>");
+        assert_eq!(policy.directives["script-src"].iter().filter(|s| s.starts_with("'sha256-")).count(), 1);
+
+        policy.add_nonce("abc123");
+        assert!(policy.allows("script-src", "'nonce-abc123'"));
+        let strict = policy.strict();
+        assert!(strict.csp().contains("default-src 'none';"), "{}", strict.csp());
+        assert!(strict.allows("form-action", "'none'") && strict.allows("script-src", "'self'"));
+
+        let mut narrated = slide("Title");
+        narrated.narration = Some(Narration { src: "https://media.example.com/intro.mp3".into(), duration: 12.0 });
+        let policy = SecurityPolicy::for_slides(&[narrated], None);
+        assert!(policy.allows("media-src", "https://media.example.com"));
+    }
+}
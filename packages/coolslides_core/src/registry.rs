@@ -0,0 +1,280 @@
+//! Layered component registry loading.
+//!
+//! A deck's registry merges several sources. Higher layers win when two
+//! sources define the same component name:
+//!
+//! 1. deck-local components (`<deck>/components`)
+//! 2. workspace packages (`packages/components`)
+//! 3. the deck's `node_modules`
+//! 4. the remote registry configured in `[registry]`
+//!
+//! The highest layer's manifest is a component's default version; other
+//! versions from lower layers stay available for slides whose `versionReq`
+//! excludes the default. The same version with a different tag in a lower layer
+//! is shadowed and reported as a CS3008 warning.
+//!
+//! With `[components] tagCollisions = "prefix"`, components from lower layers
+//! that claim an already-used tag are renamed with their package name.
+
+use crate::cache;
+use crate::components;
+use crate::ir::{ComponentRegistry, DeckManifest, RegistryConfig, TagCollisionMode};
+use crate::remote_registry::RemoteRegistry;
+use crate::validation::{ValidationError, ValidationResult};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Generated manifests in the workspace, from the project root, a deck in `examples/`, or an app crate
+const WORKSPACE_MANIFEST_DIRS: &[&str] = &[
+    "packages/components/manifests",
+    "../../packages/components/manifests",
+    "../packages/components/manifests",
+];
+
+/// Component sources in the workspace, used when no generated manifests exist
+const WORKSPACE_SOURCE_DIRS: &[&str] = &[
+    "packages/components/src",
+    "../../packages/components/src",
+    "../packages/components/src",
+];
+
+/// Source of components in a layered registry, highest precedence first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RegistryLayer {
+    DeckLocal,
+    Workspace,
+    NodeModules,
+    Remote,
+}
+
+impl fmt::Display for RegistryLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            RegistryLayer::DeckLocal => "deck components",
+            RegistryLayer::Workspace => "workspace packages",
+            RegistryLayer::NodeModules => "node_modules",
+            RegistryLayer::Remote => "remote registry",
+        })
+    }
+}
+
+/// A component defined differently by two layers; the higher layer's manifest is used
+#[derive(Debug, Clone)]
+pub struct RegistryConflict {
+    pub name: String,
+    pub kept: (RegistryLayer, String),
+    pub shadowed: (RegistryLayer, String),
+}
+
+/// Result of loading every layer for a deck
+#[derive(Debug, Clone, Default)]
+pub struct LoadedRegistry {
+    /// Merged registry, or None when no layer provided any components
+    pub registry: Option<ComponentRegistry>,
+    /// Layer each component was taken from
+    pub layers: Vec<(String, RegistryLayer)>,
+    pub conflicts: Vec<RegistryConflict>,
+    /// Layers that failed to load
+    pub warnings: Vec<String>,
+}
+
+impl LoadedRegistry {
+    /// Conflicts as CS3008 warnings
+    pub fn diagnostics(&self) -> ValidationResult {
+        let mut result = ValidationResult::new();
+        for conflict in &self.conflicts {
+            result.add_warning(ValidationError::ShadowedComponent {
+                name: conflict.name.clone(),
+                kept: format!("{} ({})", conflict.kept.0, conflict.kept.1),
+                shadowed: format!("{} ({})", conflict.shadowed.0, conflict.shadowed.1),
+            });
+        }
+        result
+    }
+}
+
+/// Load and merge every registry layer for the deck in `deck_dir`
+pub fn load_deck_registry(deck: &DeckManifest, deck_dir: &Path) -> LoadedRegistry {
+    let mut loaded = load_registry(deck.registry.as_ref(), deck_dir);
+    let mode = deck.components.as_ref().map(|c| c.tag_collisions).unwrap_or_default();
+    if mode == TagCollisionMode::Prefix {
+        let layers = &loaded.layers;
+        if let Some(registry) = loaded.registry.as_mut() {
+            // The higher layer keeps its tag
+            registry.prefix_colliding_tags(|name| {
+                layers.iter().find(|(n, _)| n == name).map_or(usize::MAX, |(_, layer)| *layer as usize)
+            });
+        }
+    }
+    loaded
+}
+
+/// Load and merge the registry layers for `deck_dir`, with an optional remote index;
+/// used when the directory has no `slides.toml`
+pub fn load_registry(remote: Option<&RegistryConfig>, deck_dir: &Path) -> LoadedRegistry {
+    let cache_dir = cache::project_cache_dir(deck_dir);
+    let mut loaded = LoadedRegistry::default();
+    let mut layers: Vec<(RegistryLayer, ComponentRegistry)> = Vec::new();
+
+    let local_dir = deck_dir.join("components");
+    if local_dir.is_dir() {
+        match components::extract_manifests_from_local_dir(&local_dir, &cache_dir) {
+            Ok(registry) => layers.push((RegistryLayer::DeckLocal, registry)),
+            Err(e) => loaded.warnings.push(format!("Failed to load deck components from {}: {}", local_dir.display(), e)),
+        }
+    }
+
+    let workspace = workspace_dir(WORKSPACE_MANIFEST_DIRS, deck_dir)
+        .map(|dir| (components::extract_manifests_from_manifests_dir(&dir), dir))
+        .or_else(|| {
+            workspace_dir(WORKSPACE_SOURCE_DIRS, deck_dir)
+                .map(|dir| (components::extract_manifests_from_directory_cached(&dir, &cache_dir), dir))
+        });
+    match workspace {
+        Some((Ok(registry), _)) => layers.push((RegistryLayer::Workspace, registry)),
+        Some((Err(e), dir)) => loaded.warnings.push(format!("Failed to load component manifests from {}: {}", dir.display(), e)),
+        None => {}
+    }
+
+    match components::extract_manifests_from_node_modules(deck_dir) {
+        Ok(registry) => layers.push((RegistryLayer::NodeModules, registry)),
+        Err(e) => loaded.warnings.push(format!("Failed to scan node_modules: {}", e)),
+    }
+
+    if let Some(config) = remote {
+        match RemoteRegistry::for_deck(config, deck_dir).load() {
+            Ok(remote) => layers.push((RegistryLayer::Remote, remote.registry)),
+            Err(e) => loaded.warnings.push(e.to_string()),
+        }
+    }
+
+    let mut merged = ComponentRegistry::default();
+    for (layer, registry) in layers {
+        let mut names: Vec<&String> = registry.components.keys().collect();
+        names.sort();
+        for name in names {
+            let manifest = &registry.components[name];
+            let source = registry.sources.get(name).cloned().unwrap_or_default();
+            if let Some(existing) = merged.all_versions(name).find(|m| m.version == manifest.version) {
+                if existing.tag != manifest.tag {
+                    let kept_layer = loaded.layers.iter().find(|(n, _)| n == name).map(|(_, l)| *l).unwrap_or(layer);
+                    loaded.conflicts.push(RegistryConflict {
+                        name: name.clone(),
+                        kept: (kept_layer, merged.sources.get(name).cloned().unwrap_or_default()),
+                        shadowed: (layer, source),
+                    });
+                }
+                continue;
+            }
+            if merged.components.contains_key(name) {
+                // Other versions stay available to slides whose versionReq excludes the default
+                merged.add_version(manifest.clone());
+                continue;
+            }
+            merged.insert(manifest.clone(), Path::new(&source));
+            loaded.layers.push((name.clone(), layer));
+        }
+    }
+
+    if !merged.components.is_empty() {
+        loaded.registry = Some(merged);
+    }
+    loaded
+}
+
+/// Directories whose contents feed the registry for `deck_dir`: deck components and
+/// the workspace's manifests and sources. Paths are canonical so watchers can match events.
+pub fn watch_dirs(deck_dir: &Path) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = [deck_dir.join("components")]
+        .into_iter()
+        .chain(workspace_dir(WORKSPACE_MANIFEST_DIRS, deck_dir))
+        .chain(workspace_dir(WORKSPACE_SOURCE_DIRS, deck_dir))
+        .filter(|dir| dir.is_dir())
+        .filter_map(|dir| dir.canonicalize().ok())
+        .collect();
+    dirs.sort();
+    dirs.dedup();
+    dirs
+}
+
+/// First candidate that exists relative to the working directory, else the
+/// first candidate found in the deck directory or one of its ancestors
+fn workspace_dir(candidates: &[&str], deck_dir: &Path) -> Option<PathBuf> {
+    candidates
+        .iter()
+        .map(PathBuf::from)
+        .find(|path| path.exists())
+        .or_else(|| deck_dir.ancestors().map(|dir| dir.join(candidates[0])).find(|path| path.exists()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_layers_merge_by_precedence() {
+        let deck_dir = std::env::temp_dir().join(format!("coolslides-layers-{}", std::process::id()));
+        let manifest = |name: &str, version: &str| {
+            serde_json::json!({ "name": name, "version": version, "tag": format!("x-{}", name.to_lowercase()), "module": "./m.js", "schema": {} })
+                .to_string()
+        };
+        fs::create_dir_all(deck_dir.join("components")).unwrap();
+        fs::write(deck_dir.join("components/Chart.component.json"), manifest("Chart", "2.0.0")).unwrap();
+        let package = deck_dir.join("node_modules/charts");
+        fs::create_dir_all(package.join("manifests")).unwrap();
+        fs::write(package.join("package.json"), "{}").unwrap();
+        fs::write(package.join("manifests/Chart.json"), manifest("Chart", "1.0.0")).unwrap();
+        fs::write(package.join("manifests/Map.json"), manifest("Map", "1.0.0")).unwrap();
+        // Same version as the package's Map, but a different element
+        let mut local_map: serde_json::Value = serde_json::from_str(&manifest("Map", "1.0.0")).unwrap();
+        local_map["tag"] = "x-local-map".into();
+        fs::write(deck_dir.join("components/Map.component.json"), local_map.to_string()).unwrap();
+
+        let deck: DeckManifest = toml::from_str(
+            "modelVersion = \"1.0\"\ntitle = \"T\"\ntheme = \"t.css\"\nsequence = []\n[transitions]\ndefault = \"none\"",
+        )
+        .unwrap();
+        let loaded = load_deck_registry(&deck, &deck_dir);
+        fs::remove_dir_all(&deck_dir).unwrap();
+
+        let registry = loaded.registry.as_ref().unwrap();
+        assert_eq!(registry.components["Chart"].version, "2.0.0");
+        assert_eq!(registry.resolve("Chart", "^1").unwrap().version, "1.0.0");
+        assert_eq!(loaded.conflicts.len(), 1);
+        assert_eq!(loaded.conflicts[0].name, "Map");
+        assert_eq!(loaded.conflicts[0].kept.0, RegistryLayer::DeckLocal);
+        assert_eq!(loaded.conflicts[0].shadowed.0, RegistryLayer::NodeModules);
+        assert_eq!(loaded.diagnostics().warnings[0].code(), "CS3008");
+    }
+
+    #[test]
+    fn test_prefix_mode_renames_lower_layer_tags() {
+        let deck_dir = std::env::temp_dir().join(format!("coolslides-prefix-{}", std::process::id()));
+        let manifest = |name: &str| {
+            serde_json::json!({ "name": name, "version": "1.0.0", "tag": "cs-chart", "module": "./chart.js", "schema": {} }).to_string()
+        };
+        fs::create_dir_all(deck_dir.join("components")).unwrap();
+        fs::write(deck_dir.join("components/Chart.component.json"), manifest("Chart")).unwrap();
+        let package = deck_dir.join("node_modules/@acme/charts");
+        fs::create_dir_all(package.join("manifests")).unwrap();
+        fs::write(package.join("package.json"), r#"{"name":"@acme/charts"}"#).unwrap();
+        fs::write(package.join("manifests/AcmeChart.json"), manifest("AcmeChart")).unwrap();
+
+        let deck: DeckManifest = toml::from_str(
+            "modelVersion = \"1.0\"\ntitle = \"T\"\ntheme = \"t.css\"\nsequence = []\n[transitions]\ndefault = \"none\"\n[components]\ntagCollisions = \"prefix\"",
+        )
+        .unwrap();
+        let loaded = load_deck_registry(&deck, &deck_dir);
+        fs::remove_dir_all(&deck_dir).unwrap();
+
+        let registry = loaded.registry.as_ref().unwrap();
+        assert_eq!(registry.components["Chart"].tag, "cs-chart");
+        assert_eq!(registry.components["AcmeChart"].tag, "acme-charts-cs-chart");
+        assert!(registry.tag_collisions().is_empty());
+        assert_eq!(
+            registry.renamed_tag("cs-chart", "/node_modules/@acme/charts/manifests/chart.js"),
+            Some("acme-charts-cs-chart")
+        );
+    }
+}
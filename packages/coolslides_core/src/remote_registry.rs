@@ -0,0 +1,246 @@
+//! Client for remote component registries.
+//!
+//! A registry index is a JSON document listing component manifests whose
+//! `module` fields are absolute URLs:
+//!
+//! ```json
+//! { "components": [{ "name": "Chart", "version": "1.2.0", "tag": "acme-chart", "module": "https://...", "schema": {} }] }
+//! ```
+//!
+//! Indexes are cached in the project cache directory and revalidated with
+//! `ETag`/`Last-Modified` once they are older than `[registry].maxAge`.
+
+use crate::cache;
+use crate::ir::{ComponentManifest, ComponentRegistry, RegistryConfig};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How long to wait for the registry server
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Component manifests published by a registry
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RegistryIndex {
+    pub components: Vec<ComponentManifest>,
+}
+
+/// Where a loaded index came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexSource {
+    /// Cached copy still within `maxAge`
+    Cached,
+    /// Downloaded from the server
+    Downloaded,
+    /// Cached copy confirmed unchanged by the server (304)
+    Revalidated,
+    /// Cached copy used because the server could not be reached
+    Stale,
+}
+
+/// Registry built from a remote index
+#[derive(Debug, Clone)]
+pub struct RemoteIndex {
+    pub registry: ComponentRegistry,
+    pub source: IndexSource,
+}
+
+/// Index on disk with the validators needed to revalidate it
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CachedIndex {
+    url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    etag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last_modified: Option<String>,
+    /// Seconds since the Unix epoch
+    fetched_at: u64,
+    index: RegistryIndex,
+}
+
+/// Remote registry client with an on-disk cache
+#[derive(Debug, Clone)]
+pub struct RemoteRegistry {
+    index_url: String,
+    cache_dir: PathBuf,
+    max_age: Duration,
+}
+
+impl RemoteRegistry {
+    pub fn new(index_url: impl Into<String>, cache_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            index_url: index_url.into(),
+            cache_dir: cache_dir.into(),
+            max_age: Duration::from_secs(3600),
+        }
+    }
+
+    /// Client for a deck's `[registry]`, caching under the deck's project cache directory
+    pub fn for_deck(config: &RegistryConfig, deck_root: &Path) -> Self {
+        Self::new(config.index.clone(), cache::project_cache_dir(deck_root).join("registry"))
+            .with_max_age(Duration::from_secs(config.max_age))
+    }
+
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    /// Load the index, using the cache while it is fresh and revalidating it afterwards
+    pub fn load(&self) -> Result<RemoteIndex> {
+        let cached = self.read_cache();
+        if let Some(cached) = cached.as_ref().filter(|c| now().saturating_sub(c.fetched_at) < self.max_age.as_secs()) {
+            return Ok(self.index(&cached.index, IndexSource::Cached));
+        }
+        self.fetch(cached)
+    }
+
+    /// Revalidate with the server regardless of the cache age
+    pub fn refresh(&self) -> Result<RemoteIndex> {
+        self.fetch(self.read_cache())
+    }
+
+    fn fetch(&self, cached: Option<CachedIndex>) -> Result<RemoteIndex> {
+        let agent = ureq::AgentBuilder::new().timeout(REQUEST_TIMEOUT).build();
+        let mut request = agent.get(&self.index_url);
+        if let Some(cached) = &cached {
+            if let Some(etag) = &cached.etag {
+                request = request.set("If-None-Match", etag);
+            }
+            if let Some(last_modified) = &cached.last_modified {
+                request = request.set("If-Modified-Since", last_modified);
+            }
+        }
+
+        let response = match request.call() {
+            Ok(response) => response,
+            Err(e) => {
+                return match cached {
+                    Some(cached) => Ok(self.index(&cached.index, IndexSource::Stale)),
+                    None => Err(anyhow::anyhow!("Failed to fetch registry index {}: {}", self.index_url, e)),
+                };
+            }
+        };
+
+        if response.status() == 304 {
+            if let Some(mut cached) = cached {
+                cached.fetched_at = now();
+                self.write_cache(&cached)?;
+                return Ok(self.index(&cached.index, IndexSource::Revalidated));
+            }
+        }
+
+        let etag = response.header("ETag").map(str::to_string);
+        let last_modified = response.header("Last-Modified").map(str::to_string);
+        let body = response.into_string()?;
+        let index: RegistryIndex = serde_json::from_str(&body)
+            .map_err(|e| anyhow::anyhow!("Invalid registry index {}: {}", self.index_url, e))?;
+
+        let entry = CachedIndex {
+            url: self.index_url.clone(),
+            etag,
+            last_modified,
+            fetched_at: now(),
+            index,
+        };
+        self.write_cache(&entry)?;
+        Ok(self.index(&entry.index, IndexSource::Downloaded))
+    }
+
+    fn index(&self, index: &RegistryIndex, source: IndexSource) -> RemoteIndex {
+        let mut registry = ComponentRegistry::default();
+        for manifest in &index.components {
+            registry.insert(manifest.clone(), Path::new(&self.index_url));
+        }
+        RemoteIndex { registry, source }
+    }
+
+    /// Cache file for this index URL
+    fn cache_path(&self) -> PathBuf {
+        let mut hasher = DefaultHasher::new();
+        self.index_url.hash(&mut hasher);
+        self.cache_dir.join(format!("{:016x}.json", hasher.finish()))
+    }
+
+    fn read_cache(&self) -> Option<CachedIndex> {
+        let content = std::fs::read_to_string(self.cache_path()).ok()?;
+        serde_json::from_str::<CachedIndex>(&content)
+            .ok()
+            .filter(|cached| cached.url == self.index_url)
+    }
+
+    fn write_cache(&self, entry: &CachedIndex) -> Result<()> {
+        std::fs::create_dir_all(&self.cache_dir)?;
+        std::fs::write(self.cache_path(), serde_json::to_vec_pretty(entry)?)?;
+        Ok(())
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+
+    /// Serve the index with an ETag, answering 304 when the client sends it back
+    fn serve(requests: usize) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/index.json", listener.local_addr().unwrap());
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = seen.clone();
+        std::thread::spawn(move || {
+            let body = r#"{"components":[{"name":"Chart","version":"1.2.0","tag":"acme-chart","module":"https://cdn.example.com/chart.js","schema":{}}]}"#;
+            for stream in listener.incoming().take(requests) {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut revalidating = false;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    revalidating |= line.to_ascii_lowercase().starts_with("if-none-match: \"v1\"");
+                }
+                let response = if revalidating {
+                    log.lock().unwrap().push("304".to_string());
+                    "HTTP/1.1 304 Not Modified\r\nETag: \"v1\"\r\nContent-Length: 0\r\n\r\n".to_string()
+                } else {
+                    log.lock().unwrap().push("200".to_string());
+                    format!("HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Length: {}\r\n\r\n{}", body.len(), body)
+                };
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        (url, seen)
+    }
+
+    #[test]
+    fn test_remote_registry_caches_and_revalidates() {
+        let cache_dir = std::env::temp_dir().join(format!("coolslides-registry-{}", std::process::id()));
+        let (url, seen) = serve(2);
+
+        let client = RemoteRegistry::new(&url, &cache_dir);
+        let first = client.load().unwrap();
+        assert_eq!(first.source, IndexSource::Downloaded);
+        assert_eq!(first.registry.components["Chart"].module, "https://cdn.example.com/chart.js");
+        assert_eq!(client.load().unwrap().source, IndexSource::Cached);
+
+        let expired = client.clone().with_max_age(Duration::ZERO);
+        assert_eq!(expired.load().unwrap().source, IndexSource::Revalidated);
+        assert_eq!(*seen.lock().unwrap(), vec!["200", "304"]);
+
+        // The server is gone now, so the cached index is used as-is
+        assert_eq!(expired.load().unwrap().source, IndexSource::Stale);
+        std::fs::remove_dir_all(&cache_dir).unwrap();
+    }
+}
@@ -0,0 +1,372 @@
+//! Ed25519 signatures for exported decks.
+//!
+//! `coolslides sign` embeds a [`SignatureBlock`] in what it signs: a JSON script in
+//! an HTML page's `<head>`, a comment after a PDF's final `%%EOF`, or a
+//! `coolslides-signature.json` manifest in an exported directory. The block carries
+//! the signer's public key, the SHA-256 of the content without the block, and a hash
+//! of the deck sources, so a recipient can check that nothing changed since signing.
+
+use anyhow::{anyhow, bail, Context, Result};
+use base64::Engine;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Manifest written into a signed export directory
+pub const SIGNATURE_FILE: &str = "coolslides-signature.json";
+
+const HTML_BLOCK_START: &str = "<script type=\"application/json\" id=\"coolslides-signature\">";
+const HTML_BLOCK_END: &str = "</script>";
+const PDF_BLOCK_START: &[u8] = b"\n%coolslides-signature:";
+const PUBLIC_KEY_PREFIX: &str = "ed25519:";
+
+/// What `coolslides sign` embeds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureBlock {
+    pub version: u32,
+    pub algorithm: String,
+    /// Short fingerprint of `public_key`
+    pub key_id: String,
+    /// `ed25519:<base64>`
+    pub public_key: String,
+    /// `sha256-<hex>` of the signed content without this block; for directories,
+    /// of the `files` table
+    pub content_hash: String,
+    /// [`deck_hash`] of the sources the export was made from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deck_hash: Option<String>,
+    /// Unix seconds
+    pub signed_at: u64,
+    /// Hash of every file of a signed directory, by relative path
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub files: BTreeMap<String, String>,
+    /// Base64 Ed25519 signature over the block with this field empty
+    pub signature: String,
+}
+
+impl SignatureBlock {
+    /// The bytes the signature covers: the block itself, minus the signature
+    fn message(&self) -> Vec<u8> {
+        let unsigned = SignatureBlock { signature: String::new(), ..self.clone() };
+        serde_json::to_vec(&unsigned).expect("signature blocks serialize")
+    }
+
+    fn verifying_key(&self) -> Result<VerifyingKey> {
+        parse_public_key(&self.public_key)
+    }
+}
+
+/// A new random signing key
+pub fn generate_key() -> Result<SigningKey> {
+    let mut seed = [0u8; 32];
+    getrandom::getrandom(&mut seed).map_err(|e| anyhow!("No randomness available: {}", e))?;
+    Ok(SigningKey::from_bytes(&seed))
+}
+
+/// Write a secret key, readable only by its owner where the platform allows
+pub fn write_secret_key(path: &Path, key: &SigningKey) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let contents = format!(
+        "untrusted comment: coolslides signing key {} (keep secret)\n{}\n",
+        key_id(&key.verifying_key()),
+        base64::engine::general_purpose::STANDARD.encode(key.to_bytes())
+    );
+    std::fs::write(path, contents)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(())
+}
+
+pub fn read_secret_key(path: &Path) -> Result<SigningKey> {
+    let contents = std::fs::read_to_string(path).with_context(|| format!("Cannot read signing key {}", path.display()))?;
+    let encoded = contents
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with("untrusted comment:"))
+        .ok_or_else(|| anyhow!("{} holds no key", path.display()))?;
+    let bytes = base64::engine::general_purpose::STANDARD.decode(encoded)?;
+    let seed: [u8; 32] = bytes.try_into().map_err(|_| anyhow!("{} is not an Ed25519 key", path.display()))?;
+    Ok(SigningKey::from_bytes(&seed))
+}
+
+/// `ed25519:<base64>`, the form public keys are shared and configured in
+pub fn encode_public_key(key: &VerifyingKey) -> String {
+    format!("{}{}", PUBLIC_KEY_PREFIX, base64::engine::general_purpose::STANDARD.encode(key.as_bytes()))
+}
+
+/// Parse a public key, with or without the `ed25519:` prefix
+pub fn parse_public_key(text: &str) -> Result<VerifyingKey> {
+    let encoded = text.trim();
+    let encoded = encoded.strip_prefix(PUBLIC_KEY_PREFIX).unwrap_or(encoded);
+    let bytes = base64::engine::general_purpose::STANDARD.decode(encoded).map_err(|_| anyhow!("Invalid public key: {}", text))?;
+    let bytes: [u8; 32] = bytes.try_into().map_err(|_| anyhow!("Invalid public key: {}", text))?;
+    Ok(VerifyingKey::from_bytes(&bytes)?)
+}
+
+/// First 8 bytes of the key's SHA-256, in hex
+pub fn key_id(key: &VerifyingKey) -> String {
+    hex(&Sha256::digest(key.as_bytes())[..8])
+}
+
+/// Hash of a deck's sources: `slides.toml`, `content/`, `importmap.json`, and
+/// `.coolslides.lock`, by relative path
+pub fn deck_hash(deck_dir: &Path) -> Result<String> {
+    let mut files = Vec::new();
+    for name in ["slides.toml", "importmap.json", ".coolslides.lock"] {
+        let path = deck_dir.join(name);
+        if path.is_file() {
+            files.push((name.to_string(), std::fs::read(path)?));
+        }
+    }
+    let content = deck_dir.join("content");
+    if content.is_dir() {
+        for (relative, path) in walk(&content)? {
+            files.push((format!("content/{}", relative), std::fs::read(path)?));
+        }
+    }
+    if files.is_empty() {
+        bail!("No deck found in {}", deck_dir.display());
+    }
+    files.sort();
+    let mut hasher = Sha256::new();
+    for (name, bytes) in files {
+        hasher.update(name.as_bytes());
+        hasher.update([0]);
+        hasher.update((bytes.len() as u64).to_le_bytes());
+        hasher.update(&bytes);
+    }
+    Ok(format!("sha256-{}", hex(&hasher.finalize())))
+}
+
+/// Sign an exported HTML file, PDF, or export directory in place
+pub fn sign_path(path: &Path, key: &SigningKey, deck_hash: Option<String>) -> Result<SignatureBlock> {
+    let mut block = SignatureBlock {
+        version: 1,
+        algorithm: "ed25519".to_string(),
+        key_id: key_id(&key.verifying_key()),
+        public_key: encode_public_key(&key.verifying_key()),
+        content_hash: String::new(),
+        deck_hash,
+        signed_at: std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+        files: BTreeMap::new(),
+        signature: String::new(),
+    };
+    let finish = |block: &mut SignatureBlock| {
+        block.signature = base64::engine::general_purpose::STANDARD.encode(key.sign(&block.message()).to_bytes());
+    };
+
+    match kind(path)? {
+        Kind::Directory => {
+            block.files = directory_hashes(path)?;
+            block.content_hash = files_hash(&block.files);
+            finish(&mut block);
+            std::fs::write(path.join(SIGNATURE_FILE), serde_json::to_vec_pretty(&block)?)?;
+        }
+        Kind::Html => {
+            let html = std::fs::read_to_string(path)?;
+            let (unsigned, _) = split_html(&html)?;
+            block.content_hash = content_hash(unsigned.as_bytes());
+            finish(&mut block);
+            // `</` cannot occur in the JSON, but keep the block safe to inline regardless
+            let json = serde_json::to_string(&block)?.replace("</", "<\\/");
+            let tag = format!("{}{}{}", HTML_BLOCK_START, json, HTML_BLOCK_END);
+            let signed = match unsigned.find("</head>") {
+                Some(at) => format!("{}{}{}", &unsigned[..at], tag, &unsigned[at..]),
+                None => format!("{}{}", tag, unsigned),
+            };
+            std::fs::write(path, signed)?;
+        }
+        Kind::Pdf => {
+            let bytes = std::fs::read(path)?;
+            let (unsigned, _) = split_pdf(&bytes)?;
+            block.content_hash = content_hash(unsigned);
+            finish(&mut block);
+            let mut signed = unsigned.to_vec();
+            signed.extend_from_slice(PDF_BLOCK_START);
+            signed.extend_from_slice(serde_json::to_string(&block)?.as_bytes());
+            signed.push(b'\n');
+            std::fs::write(path, signed)?;
+        }
+    }
+    Ok(block)
+}
+
+/// Check a signed export: the block's signature, then the content against its hash.
+/// Returns the block so the caller can decide whether to trust its key.
+pub fn verify_path(path: &Path) -> Result<SignatureBlock> {
+    let (block, actual_hash) = match kind(path)? {
+        Kind::Directory => {
+            let manifest = std::fs::read(path.join(SIGNATURE_FILE)).map_err(|_| anyhow!("{} is not signed (no {})", path.display(), SIGNATURE_FILE))?;
+            let block: SignatureBlock = serde_json::from_slice(&manifest)?;
+            let actual = directory_hashes(path)?;
+            let changed: Vec<&str> = block
+                .files
+                .keys()
+                .chain(actual.keys())
+                .filter(|name| block.files.get(*name) != actual.get(*name))
+                .map(String::as_str)
+                .collect::<std::collections::BTreeSet<_>>()
+                .into_iter()
+                .collect();
+            if !changed.is_empty() {
+                check_signature(&block)?;
+                bail!("Files changed since signing: {}", changed.join(", "));
+            }
+            let hash = files_hash(&actual);
+            (block, hash)
+        }
+        Kind::Html => {
+            let html = std::fs::read_to_string(path)?;
+            let (unsigned, block) = split_html(&html)?;
+            let block = block.ok_or_else(|| anyhow!("{} is not signed", path.display()))?;
+            (block, content_hash(unsigned.as_bytes()))
+        }
+        Kind::Pdf => {
+            let bytes = std::fs::read(path)?;
+            let (unsigned, block) = split_pdf(&bytes)?;
+            let block = block.ok_or_else(|| anyhow!("{} is not signed", path.display()))?;
+            (block, content_hash(unsigned))
+        }
+    };
+    check_signature(&block)?;
+    if block.content_hash != actual_hash {
+        bail!("Content changed since signing (expected {}, found {})", block.content_hash, actual_hash);
+    }
+    Ok(block)
+}
+
+fn check_signature(block: &SignatureBlock) -> Result<()> {
+    if block.version != 1 || block.algorithm != "ed25519" {
+        bail!("Unsupported signature (version {}, {})", block.version, block.algorithm);
+    }
+    let bytes = base64::engine::general_purpose::STANDARD.decode(&block.signature)?;
+    let signature = Signature::from_slice(&bytes)?;
+    block
+        .verifying_key()?
+        .verify(&block.message(), &signature)
+        .map_err(|_| anyhow!("Signature does not match (the signature block was altered)"))
+}
+
+enum Kind {
+    Directory,
+    Html,
+    Pdf,
+}
+
+fn kind(path: &Path) -> Result<Kind> {
+    if path.is_dir() {
+        return Ok(Kind::Directory);
+    }
+    match path.extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase()).as_deref() {
+        Some("html" | "htm") => Ok(Kind::Html),
+        Some("pdf") => Ok(Kind::Pdf),
+        _ => bail!("Cannot sign {}: expected an .html or .pdf file, or an export directory", path.display()),
+    }
+}
+
+/// The page without its signature block, and the block if there is one
+fn split_html(html: &str) -> Result<(String, Option<SignatureBlock>)> {
+    let Some(start) = html.find(HTML_BLOCK_START) else {
+        return Ok((html.to_string(), None));
+    };
+    let json_start = start + HTML_BLOCK_START.len();
+    let end = html[json_start..].find(HTML_BLOCK_END).ok_or_else(|| anyhow!("Unterminated signature block"))? + json_start;
+    let block = serde_json::from_str(&html[json_start..end].replace("<\\/", "</"))?;
+    Ok((format!("{}{}", &html[..start], &html[end + HTML_BLOCK_END.len()..]), Some(block)))
+}
+
+/// The PDF without its trailing signature comment, and the block if there is one
+fn split_pdf(bytes: &[u8]) -> Result<(&[u8], Option<SignatureBlock>)> {
+    let found = bytes.windows(PDF_BLOCK_START.len()).rposition(|window| window == PDF_BLOCK_START);
+    let Some(start) = found else {
+        return Ok((bytes, None));
+    };
+    let block = serde_json::from_slice(bytes[start + PDF_BLOCK_START.len()..].trim_ascii())?;
+    Ok((&bytes[..start], Some(block)))
+}
+
+fn directory_hashes(dir: &Path) -> Result<BTreeMap<String, String>> {
+    walk(dir)?
+        .into_iter()
+        .filter(|(relative, _)| relative != SIGNATURE_FILE)
+        .map(|(relative, path)| Ok((relative, content_hash(&std::fs::read(path)?))))
+        .collect()
+}
+
+fn files_hash(files: &BTreeMap<String, String>) -> String {
+    content_hash(&serde_json::to_vec(files).expect("file tables serialize"))
+}
+
+/// Files under `dir` with `/`-separated relative paths
+fn walk(dir: &Path) -> Result<Vec<(String, std::path::PathBuf)>> {
+    let mut files = Vec::new();
+    for entry in walkdir::WalkDir::new(dir).follow_links(false) {
+        let entry = entry?;
+        if entry.file_type().is_file() {
+            let relative = entry.path().strip_prefix(dir)?;
+            let relative = relative.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/");
+            files.push((relative, entry.into_path()));
+        }
+    }
+    Ok(files)
+}
+
+fn content_hash(bytes: &[u8]) -> String {
+    format!("sha256-{}", hex(&Sha256::digest(bytes)))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_and_verify_html_pdf_and_directories() {
+        let root = std::env::temp_dir().join(format!("coolslides-signing-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("export/images")).unwrap();
+        let key = generate_key().unwrap();
+
+        let page = root.join("deck.html");
+        std::fs::write(&page, "<html><head><title>T</title></head><body>Hi</body></html>").unwrap();
+        let block = sign_path(&page, &key, Some("sha256-abc".into())).unwrap();
+        assert_eq!(verify_path(&page).unwrap(), block);
+        // Signing again replaces the block rather than nesting it
+        sign_path(&page, &key, None).unwrap();
+        assert_eq!(std::fs::read_to_string(&page).unwrap().matches("coolslides-signature").count(), 1);
+        let tampered = std::fs::read_to_string(&page).unwrap().replace("Hi", "Bye");
+        std::fs::write(&page, tampered).unwrap();
+        assert!(verify_path(&page).unwrap_err().to_string().contains("Content changed"));
+
+        let pdf = root.join("deck.pdf");
+        std::fs::write(&pdf, b"%PDF-1.7\n...\n%%EOF\n").unwrap();
+        sign_path(&pdf, &key, None).unwrap();
+        assert!(verify_path(&pdf).is_ok());
+
+        std::fs::write(root.join("export/index.html"), "<html></html>").unwrap();
+        std::fs::write(root.join("export/images/a.png"), [1, 2, 3]).unwrap();
+        sign_path(&root.join("export"), &key, None).unwrap();
+        assert_eq!(verify_path(&root.join("export")).unwrap().files.len(), 2);
+        std::fs::write(root.join("export/images/b.png"), [4]).unwrap();
+        let error = verify_path(&root.join("export")).unwrap_err().to_string();
+        assert!(error.contains("images/b.png"), "{}", error);
+
+        let key_path = root.join("signing.key");
+        write_secret_key(&key_path, &key).unwrap();
+        assert_eq!(read_secret_key(&key_path).unwrap().to_bytes(), key.to_bytes());
+        let public = encode_public_key(&key.verifying_key());
+        assert_eq!(parse_public_key(&public).unwrap(), key.verifying_key());
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
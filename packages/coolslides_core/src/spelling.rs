@@ -0,0 +1,195 @@
+//! Opt-in spelling pass over slide text.
+//!
+//! Words are checked against Hunspell dictionaries chosen by each slide's
+//! `locale` (falling back to `[lint.spelling].defaultLocale`). Markdown slots are
+//! checked outside code, and string props only when they read like prose.
+
+use crate::ir::{DeckManifest, SlideDoc, Slot, SpellingConfig};
+use crate::validation::{self, ValidationError, ValidationResult};
+use pulldown_cmark::{Event, Parser, Tag};
+use spellbook::Dictionary;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Directories searched for dictionaries after the deck's own
+const SYSTEM_DICTIONARY_DIRS: &[&str] = &[
+    "/usr/share/hunspell",
+    "/usr/share/myspell",
+    "/usr/share/myspell/dicts",
+    "/usr/local/share/hunspell",
+    "/Library/Spelling",
+];
+
+/// Spell-check every slide, reporting misspellings and locales without a dictionary as warnings
+pub fn validate_spelling(manifest: &DeckManifest, slides: &[SlideDoc], deck_root: &Path) -> ValidationResult {
+    let mut result = ValidationResult::new();
+    let config = manifest.lint.as_ref().map(|l| l.spelling.clone()).unwrap_or_default();
+    let allow: HashSet<String> = config.allow.iter().map(|w| w.to_lowercase()).collect();
+    let search_dirs = dictionary_dirs(&config, deck_root);
+
+    let mut dictionaries: HashMap<String, Option<Dictionary>> = HashMap::new();
+    let mut unchecked: BTreeMap<String, usize> = BTreeMap::new();
+
+    for slide in slides {
+        let locale = slide.locale.clone().unwrap_or_else(|| config.default_locale.clone());
+        let dictionary = dictionaries
+            .entry(locale.clone())
+            .or_insert_with(|| load_dictionary(&locale, &search_dirs));
+        let Some(dictionary) = dictionary.as_ref() else {
+            *unchecked.entry(locale).or_default() += 1;
+            continue;
+        };
+
+        for (location, text) in slide_text(slide) {
+            let mut reported = BTreeSet::new();
+            for word in words(&text) {
+                if allow.contains(&word.to_lowercase()) || dictionary.check(word) || !reported.insert(word) {
+                    continue;
+                }
+                let mut suggestions = Vec::new();
+                dictionary.suggest(word, &mut suggestions);
+                result.add_warning(ValidationError::Misspelling {
+                    word: word.to_string(),
+                    location: location.clone(),
+                    slide_id: slide.id.clone(),
+                    suggestion: suggestions.into_iter().next(),
+                });
+            }
+        }
+    }
+
+    for (locale, slides) in unchecked {
+        result.add_warning(ValidationError::MissingDictionary { locale, slides });
+    }
+
+    validation::apply_deck_lint(manifest, &mut result);
+    result
+}
+
+/// Deck dictionaries first, then configured directories, `DICPATH`, and system locations
+fn dictionary_dirs(config: &SpellingConfig, deck_root: &Path) -> Vec<PathBuf> {
+    let mut dirs = vec![deck_root.join("dictionaries")];
+    dirs.extend(config.dictionary_dirs.iter().map(|dir| deck_root.join(dir)));
+    if let Some(dicpath) = std::env::var_os("DICPATH") {
+        dirs.extend(std::env::split_paths(&dicpath));
+    }
+    if let Some(home) = std::env::var_os("HOME") {
+        dirs.push(PathBuf::from(home).join("Library/Spelling"));
+    }
+    dirs.extend(SYSTEM_DICTIONARY_DIRS.iter().map(PathBuf::from));
+    dirs
+}
+
+/// Load the dictionary for a locale, trying `en_US`, `en-US`, then the bare language `en`
+fn load_dictionary(locale: &str, dirs: &[PathBuf]) -> Option<Dictionary> {
+    let mut names = vec![locale.replace('-', "_"), locale.replace('_', "-")];
+    if let Some(language) = locale.split(['-', '_']).next() {
+        names.push(language.to_string());
+    }
+    names.dedup();
+
+    dirs.iter()
+        .flat_map(|dir| names.iter().map(move |name| (dir.join(format!("{name}.aff")), dir.join(format!("{name}.dic")))))
+        .find_map(|(aff, dic)| {
+            let aff = std::fs::read_to_string(aff).ok()?;
+            let dic = std::fs::read_to_string(dic).ok()?;
+            Dictionary::new(&aff, &dic).ok()
+        })
+}
+
+/// Checkable text of a slide, paired with where it came from
+fn slide_text(slide: &SlideDoc) -> Vec<(String, String)> {
+    let mut text = Vec::new();
+    collect_prose_props(&slide.props, "", &mut text);
+
+    let mut slot_names: Vec<&String> = slide.slots.keys().collect();
+    slot_names.sort();
+    for slot_name in slot_names {
+        if let Slot::Markdown { value } = &slide.slots[slot_name] {
+            text.push((format!("slot '{slot_name}'"), markdown_prose(value)));
+        }
+    }
+    text
+}
+
+/// String props that contain whitespace; single tokens are usually ids, enum values, or paths
+fn collect_prose_props(value: &serde_json::Value, path: &str, text: &mut Vec<(String, String)>) {
+    match value {
+        serde_json::Value::String(s) if s.contains(char::is_whitespace) && !s.contains("://") => {
+            text.push((format!("prop '{path}'"), s.clone()));
+        }
+        serde_json::Value::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                collect_prose_props(item, &format!("{path}[{index}]"), text);
+            }
+        }
+        serde_json::Value::Object(map) => {
+            for (key, item) in map {
+                let path = if path.is_empty() { key.clone() } else { format!("{path}.{key}") };
+                collect_prose_props(item, &path, text);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Text of a markdown document outside code blocks, inline code, and link targets
+fn markdown_prose(markdown: &str) -> String {
+    let mut prose = String::new();
+    let mut in_code_block = false;
+    for event in Parser::new(markdown) {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+            Event::End(Tag::CodeBlock(_)) => in_code_block = false,
+            Event::Text(text) if !in_code_block => {
+                prose.push_str(&text);
+                prose.push(' ');
+            }
+            Event::SoftBreak | Event::HardBreak => prose.push(' '),
+            _ => {}
+        }
+    }
+    prose
+}
+
+/// Words worth checking: alphabetic runs (with inner apostrophes), skipping
+/// URLs, acronyms, identifiers, and anything mixed with digits
+fn words(text: &str) -> impl Iterator<Item = &str> {
+    text.split_whitespace()
+        .filter(|token| !token.contains("://") && !token.starts_with('@') && !token.contains(['/', '_', '=']))
+        .filter(|token| !token.chars().any(|c| c.is_ascii_digit()))
+        .flat_map(|token| token.split(|c: char| !(c.is_alphabetic() || c == '\'' || c == '’')))
+        .map(|word| word.trim_matches(['\'', '’']))
+        .filter(|word| word.chars().count() > 1)
+        .filter(|word| !is_acronym(word) && !is_identifier(word))
+}
+
+fn is_acronym(word: &str) -> bool {
+    word.chars().filter(|c| c.is_alphabetic()).all(char::is_uppercase)
+}
+
+/// camelCase or PascalCase with an inner capital, e.g. `TwoColSlide`
+fn is_identifier(word: &str) -> bool {
+    word.chars().skip(1).any(char::is_uppercase)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_words_skip_code_like_tokens() {
+        let found: Vec<&str> = words("Teh API at https://x.io uses camelCase, v2 and don't.").collect();
+        assert_eq!(found, vec!["Teh", "at", "uses", "and", "don't"]);
+    }
+
+    #[test]
+    fn test_markdown_prose_skips_code() {
+        let prose = markdown_prose("Intro `inlnie`\n\n```\nfn mian() {}\n```\n\n[link](https://exmaple.com)");
+        assert!(prose.contains("Intro"));
+        assert!(prose.contains("link"));
+        assert!(!prose.contains("mian"));
+        assert!(!prose.contains("inlnie"));
+        assert!(!prose.contains("exmaple"));
+    }
+}
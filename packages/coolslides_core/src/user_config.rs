@@ -0,0 +1,61 @@
+//! Per-user settings, kept outside any deck.
+//!
+//! Read from `<config dir>/coolslides/config.toml` (`~/.config` on Linux,
+//! `~/Library/Application Support` on macOS, `%APPDATA%` on Windows). A missing file
+//! means defaults.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct UserConfig {
+    pub signing: SigningConfig,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SigningConfig {
+    /// Secret key used by `coolslides sign`; defaults to `signing.key` next to the config
+    pub key: Option<PathBuf>,
+    /// Public keys (`ed25519:<base64>`) `coolslides verify` accepts without `--public-key`
+    pub trusted_keys: Vec<String>,
+}
+
+/// Directory holding the user config, when the platform has one
+pub fn config_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("coolslides"))
+}
+
+impl UserConfig {
+    pub fn load() -> Result<Self> {
+        match config_dir() {
+            Some(dir) => Self::load_from(&dir.join("config.toml")),
+            None => Ok(Self::default()),
+        }
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = std::fs::read_to_string(path)?;
+        toml::from_str(&contents).with_context(|| format!("Invalid user config {}", path.display()))
+    }
+
+    /// The configured signing key path with `~` expanded, or the default location
+    pub fn signing_key_path(&self) -> Option<PathBuf> {
+        match &self.signing.key {
+            Some(path) => Some(expand_home(path)),
+            None => config_dir().map(|dir| dir.join("signing.key")),
+        }
+    }
+}
+
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
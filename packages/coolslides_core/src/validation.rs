@@ -1,9 +1,17 @@
+use crate::cache::{self, ValidationCache};
+use crate::diagnostics::{self, DiagnosticInfo};
+use crate::formats;
 use crate::ir::*;
-use std::collections::HashSet;
+use crate::lint;
+use crate::markdown;
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::time::{Duration, Instant};
 use thiserror::Error;
 
-/// Validation errors with diagnostic codes
-#[derive(Error, Debug)]
+/// Validation diagnostics with codes; each is reported as an error or a warning
+#[derive(Error, Debug, Clone)]
 pub enum ValidationError {
     #[error("CS1001: Slide id duplicated: {id}")]
     DuplicateSlideId { id: String },
@@ -17,9 +25,93 @@ pub enum ValidationError {
     #[error("CS1004: Invalid model version: {version}")]
     InvalidModelVersion { version: String },
     
+    #[error("CS1005: Empty markdown slot '{slot}' in slide {slide_id}")]
+    EmptyMarkdownSlot { slot: String, slide_id: String },
+    
+    #[error("CS1006: Empty component {field} in slot '{slot}' of slide {slide_id}")]
+    EmptySlotComponent { field: String, slot: String, slide_id: String },
+    
+    #[error("CS1007: Unknown transition '{name}' in {location}{}", suggestion.as_ref().map(|s| format!(" (did you mean '{}'?)", s)).unwrap_or_default())]
+    UnknownTransition { name: String, location: String, suggestion: Option<String> },
+    
+    #[error("CS1008: Condition includeTags references tag '{tag}' that no slide carries")]
+    UnknownConditionTag { tag: String },
+    
+    #[error("CS1009: Condition excludeIds references unknown slide id '{id}'")]
+    UnknownConditionId { id: String },
+    
+    #[error("CS4001: Unclosed code fence in slot '{slot}' of slide {slide_id}")]
+    UnclosedCodeFence { slot: String, slide_id: String },
+    
+    #[error("CS4002: Reference link [{reference}] has no definition in slot '{slot}' of slide {slide_id}")]
+    BrokenReferenceLink { reference: String, slot: String, slide_id: String },
+    
+    #[error("CS4003: Image '{src}' has no alt text in slot '{slot}' of slide {slide_id}")]
+    ImageMissingAlt { src: String, slot: String, slide_id: String },
+    
+    #[error("CS4004: Raw HTML <{tag}> in slot '{slot}' of slide {slide_id} is removed in strict mode")]
+    StrippedHtml { tag: String, slot: String, slide_id: String },
+    
+    #[error("CS4005: Props of slide {slide_id} are {bytes} bytes, over the {limit} byte budget")]
+    PropsBudgetExceeded { slide_id: String, bytes: u64, limit: u64 },
+    
+    #[error("CS4006: Markdown slot '{slot}' of slide {slide_id} is {chars} characters, over the {limit} character budget")]
+    MarkdownBudgetExceeded { slot: String, slide_id: String, chars: u64, limit: u64 },
+    
+    #[error("CS4007: Asset {path} is {bytes} bytes, over the {limit} byte budget")]
+    AssetBudgetExceeded { path: String, bytes: u64, limit: u64 },
+    
+    #[error("CS4008: Group '{group}' has {slides} slides, over the budget of {limit}")]
+    GroupBudgetExceeded { group: String, slides: u64, limit: u64 },
+    
+    #[error("CS4009: Possible misspelling '{word}' in {location} of slide {slide_id}{}", suggestion.as_ref().map(|s| format!(" (did you mean '{}'?)", s)).unwrap_or_default())]
+    Misspelling { word: String, location: String, slide_id: String, suggestion: Option<String> },
+    
+    #[error("CS4010: No dictionary found for locale '{locale}'; {slides} slide(s) were not spell-checked")]
+    MissingDictionary { locale: String, slides: usize },
+    
+    #[error("CS1010: Unknown placeholder {{{placeholder}}} in {location} (known: pageNumber, totalPages, title, date, url)")]
+    UnknownPrintPlaceholder { placeholder: String, location: String },
+    
+    #[error("CS1011: Malformed template in {location}: {reason}")]
+    MalformedPrintTemplate { location: String, reason: String },
+    
+    #[error("CS1012: {location} uses {{{placeholder}}} but page numbers are disabled")]
+    PageNumbersDisabled { placeholder: String, location: String },
+    
+    #[error("CS1013: Deck notes key '{id}' does not match any slide{}", suggestion.as_ref().map(|s| format!(" (did you mean '{}'?)", s)).unwrap_or_default())]
+    UnknownNotesKey { id: String, suggestion: Option<String> },
+    
+    #[error("CS1014: Slot id '{slot_id}' in slot '{slot}' of slide {slide_id} is already used by {first}")]
+    DuplicateSlotId { slot_id: String, slot: String, slide_id: String, first: String },
+    
+    #[error("CS1015: Invalid narration in slide {slide_id}: {reason}")]
+    InvalidNarration { slide_id: String, reason: String },
+    
+    #[error("CS1016: Narration file '{src}' of slide {slide_id} does not exist")]
+    MissingNarrationFile { src: String, slide_id: String },
+    
+    #[error("CS1901: {message} in slide {slide_id}")]
+    CustomRule { message: String, slide_id: String },
+    
     #[error("CS2001: Component version ranges cannot converge for {name}")]
     VersionConflict { name: String },
     
+    #[error("CS2002: Plugin '{plugin}' does not resolve through importmap.json, the lockfile, or a local path")]
+    UnresolvedPlugin { plugin: String },
+    
+    #[error("CS2003: Import '{specifier}' in importmap.json is not pinned in .coolslides.lock")]
+    UnpinnedImport { specifier: String },
+    
+    #[error("CS2004: {kind} '{name}' is used by the deck but missing from .coolslides.lock")]
+    MissingLockEntry { kind: String, name: String },
+    
+    #[error("CS2005: Plugin '{plugin}' is in importmap.json but not listed in slides.toml plugins")]
+    UnusedPlugin { plugin: String },
+    
+    #[error("CS2006: Orphaned lockfile {kind} '{name}' is no longer used by the deck")]
+    OrphanedLockEntry { kind: String, name: String },
+    
     #[error("CS3001: Unknown component: {name} in slide {slide_id}")]
     UnknownComponent { name: String, slide_id: String },
     
@@ -37,13 +129,151 @@ pub enum ValidationError {
         slide_id: String, 
         prop: String 
     },
+    
+    #[error("CS3004: Custom element tag <{tag}> declared by both {first} ({first_source}) and {second} ({second_source})")]
+    TagCollision {
+        tag: String,
+        first: String,
+        first_source: String,
+        second: String,
+        second_source: String,
+    },
+    
+    #[error("CS3008: Component '{name}' from {shadowed} is shadowed by a different manifest from {kept}")]
+    ShadowedComponent { name: String, kept: String, shadowed: String },
+    
+    #[error("CS3005: Slot '{slot}' is not declared by component {component} in slide {slide_id} (declared: {declared})")]
+    UnknownSlot { slot: String, component: String, slide_id: String, declared: String },
+    
+    #[error("CS3009: Slot '{slot}' of component {component} in slide {slide_id} is {kind}, but the slot accepts {accepts}")]
+    SlotKindNotAccepted { slot: String, component: String, slide_id: String, kind: String, accepts: String },
+    
+    #[error("CS3010: No version of component {name} satisfies versionReq '{version_req}' in slide {slide_id} (available: {available})")]
+    UnsatisfiedVersionReq { name: String, version_req: String, slide_id: String, available: String },
+    
+    #[error("CS3011: Component {name} {version} used in slide {slide_id} is deprecated: {note}")]
+    DeprecatedComponent { name: String, version: String, slide_id: String, note: String },
+    
+    #[error("CS3012: Prop '{prop}' of component {component} in slide {slide_id} is deprecated: {note}")]
+    DeprecatedProp { prop: String, component: String, slide_id: String, note: String, replacement: Option<String> },
+    
+    #[error("CS3007: Style override '{key}' in slide {slide_id} is not a token {component} reads")]
+    UnusedStyleOverride { key: String, component: String, slide_id: String },
+    
+    #[error("CS3006: Required slot '{slot}' of component {component} is empty in slide {slide_id}")]
+    EmptyRequiredSlot { slot: String, component: String, slide_id: String },
+}
+
+impl ValidationError {
+    /// Diagnostic code (e.g. "CS1001") used for lint configuration and documentation
+    pub fn code(&self) -> &'static str {
+        match self {
+            ValidationError::DuplicateSlideId { .. } => "CS1001",
+            ValidationError::UnknownSlideReference { .. } => "CS1002",
+            ValidationError::InvalidStyleOverrideKey { .. } => "CS1003",
+            ValidationError::InvalidModelVersion { .. } => "CS1004",
+            ValidationError::EmptyMarkdownSlot { .. } => "CS1005",
+            ValidationError::EmptySlotComponent { .. } => "CS1006",
+            ValidationError::UnknownTransition { .. } => "CS1007",
+            ValidationError::UnknownConditionTag { .. } => "CS1008",
+            ValidationError::UnknownConditionId { .. } => "CS1009",
+            ValidationError::UnknownPrintPlaceholder { .. } => "CS1010",
+            ValidationError::MalformedPrintTemplate { .. } => "CS1011",
+            ValidationError::PageNumbersDisabled { .. } => "CS1012",
+            ValidationError::UnknownNotesKey { .. } => "CS1013",
+            ValidationError::DuplicateSlotId { .. } => "CS1014",
+            ValidationError::InvalidNarration { .. } => "CS1015",
+            ValidationError::MissingNarrationFile { .. } => "CS1016",
+            ValidationError::CustomRule { .. } => "CS1901",
+            ValidationError::VersionConflict { .. } => "CS2001",
+            ValidationError::UnresolvedPlugin { .. } => "CS2002",
+            ValidationError::UnpinnedImport { .. } => "CS2003",
+            ValidationError::MissingLockEntry { .. } => "CS2004",
+            ValidationError::UnusedPlugin { .. } => "CS2005",
+            ValidationError::OrphanedLockEntry { .. } => "CS2006",
+            ValidationError::UnknownComponent { .. } => "CS3001",
+            ValidationError::InvalidComponentProps { .. } => "CS3002",
+            ValidationError::MissingRequiredProp { .. } => "CS3003",
+            ValidationError::TagCollision { .. } => "CS3004",
+            ValidationError::ShadowedComponent { .. } => "CS3008",
+            ValidationError::UnknownSlot { .. } => "CS3005",
+            ValidationError::EmptyRequiredSlot { .. } => "CS3006",
+            ValidationError::UnusedStyleOverride { .. } => "CS3007",
+            ValidationError::SlotKindNotAccepted { .. } => "CS3009",
+            ValidationError::UnsatisfiedVersionReq { .. } => "CS3010",
+            ValidationError::DeprecatedComponent { .. } => "CS3011",
+            ValidationError::DeprecatedProp { .. } => "CS3012",
+            ValidationError::UnclosedCodeFence { .. } => "CS4001",
+            ValidationError::BrokenReferenceLink { .. } => "CS4002",
+            ValidationError::ImageMissingAlt { .. } => "CS4003",
+            ValidationError::StrippedHtml { .. } => "CS4004",
+            ValidationError::PropsBudgetExceeded { .. } => "CS4005",
+            ValidationError::MarkdownBudgetExceeded { .. } => "CS4006",
+            ValidationError::AssetBudgetExceeded { .. } => "CS4007",
+            ValidationError::GroupBudgetExceeded { .. } => "CS4008",
+            ValidationError::Misspelling { .. } => "CS4009",
+            ValidationError::MissingDictionary { .. } => "CS4010",
+        }
+    }
+    
+    /// Message without the leading diagnostic code
+    pub fn message(&self) -> String {
+        let full = self.to_string();
+        full.strip_prefix(self.code())
+            .and_then(|rest| rest.strip_prefix(": "))
+            .map(str::to_string)
+            .unwrap_or(full)
+    }
+    
+    /// Catalog entry describing this diagnostic
+    pub fn info(&self) -> Option<&'static DiagnosticInfo> {
+        diagnostics::explain(self.code())
+    }
+
+    /// The slide the diagnostic is about, for diagnostics that concern one slide
+    pub fn slide_id(&self) -> Option<&str> {
+        match self {
+            ValidationError::UnknownComponent { slide_id, .. }
+            | ValidationError::InvalidComponentProps { slide_id, .. }
+            | ValidationError::MissingRequiredProp { slide_id, .. }
+            | ValidationError::EmptyMarkdownSlot { slide_id, .. }
+            | ValidationError::EmptySlotComponent { slide_id, .. }
+            | ValidationError::CustomRule { slide_id, .. }
+            | ValidationError::UnknownSlot { slide_id, .. }
+            | ValidationError::EmptyRequiredSlot { slide_id, .. }
+            | ValidationError::SlotKindNotAccepted { slide_id, .. }
+            | ValidationError::UnsatisfiedVersionReq { slide_id, .. }
+            | ValidationError::DeprecatedComponent { slide_id, .. }
+            | ValidationError::DeprecatedProp { slide_id, .. }
+            | ValidationError::UnusedStyleOverride { slide_id, .. }
+            | ValidationError::UnclosedCodeFence { slide_id, .. }
+            | ValidationError::BrokenReferenceLink { slide_id, .. }
+            | ValidationError::ImageMissingAlt { slide_id, .. }
+            | ValidationError::StrippedHtml { slide_id, .. }
+            | ValidationError::PropsBudgetExceeded { slide_id, .. }
+            | ValidationError::MarkdownBudgetExceeded { slide_id, .. }
+            | ValidationError::Misspelling { slide_id, .. }
+            | ValidationError::DuplicateSlotId { slide_id, .. }
+            | ValidationError::InvalidNarration { slide_id, .. }
+            | ValidationError::MissingNarrationFile { slide_id, .. } => Some(slide_id),
+            _ => None,
+        }
+    }
+
+    /// Registry and asset diagnostics name their own files rather than a deck file
+    pub fn names_own_file(&self) -> bool {
+        matches!(
+            self,
+            ValidationError::TagCollision { .. } | ValidationError::ShadowedComponent { .. } | ValidationError::AssetBudgetExceeded { .. }
+        )
+    }
 }
 
 /// Validation context and results
-#[derive(Debug)]
+#[derive(Debug, Clone, Default)]
 pub struct ValidationResult {
     pub errors: Vec<ValidationError>,
-    pub warnings: Vec<String>,
+    pub warnings: Vec<ValidationError>,
 }
 
 impl ValidationResult {
@@ -62,9 +292,15 @@ impl ValidationResult {
         self.errors.push(error);
     }
     
-    pub fn add_warning(&mut self, warning: String) {
+    pub fn add_warning(&mut self, warning: ValidationError) {
         self.warnings.push(warning);
     }
+    
+    /// Append the errors and warnings of another validation pass
+    pub fn merge(&mut self, other: ValidationResult) {
+        self.errors.extend(other.errors);
+        self.warnings.extend(other.warnings);
+    }
 }
 
 /// Validate a complete deck (manifest + slides) with optional component registry for schema validation
@@ -78,7 +314,74 @@ pub fn validate_deck_with_registry(
     slides: &[SlideDoc],
     registry: Option<&ComponentRegistry>
 ) -> ValidationResult {
+    validate_deck_timed(manifest, slides, registry).0
+}
+
+/// Time spent validating one slide
+#[derive(Debug, Clone)]
+pub struct SlideTiming {
+    pub slide_id: String,
+    pub component: String,
+    pub duration: Duration,
+}
+
+/// Time spent on one component's props schema
+#[derive(Debug, Clone)]
+pub struct SchemaTiming {
+    pub component: String,
+    /// Time to compile the schema
+    pub compile: Duration,
+    /// Total time validating slides that use the component
+    pub validate: Duration,
+    /// Number of slides using the component
+    pub slides: usize,
+}
+
+/// Where deck validation spent its time, slowest first
+#[derive(Debug, Clone, Default)]
+pub struct ValidationTimings {
+    pub total: Duration,
+    pub slides: Vec<SlideTiming>,
+    pub schemas: Vec<SchemaTiming>,
+}
+
+/// Validate a complete deck like [`validate_deck_with_registry`], also reporting timings.
+///
+/// Each component schema is compiled once and slides are validated in parallel; diagnostics
+/// are reported in slide order regardless of scheduling.
+pub fn validate_deck_timed(
+    manifest: &DeckManifest,
+    slides: &[SlideDoc],
+    registry: Option<&ComponentRegistry>,
+) -> (ValidationResult, ValidationTimings) {
+    validate_deck_inner(manifest, slides, registry, None)
+}
+
+/// Validate a complete deck, reusing cached results for slides whose content and
+/// component manifest have not changed since the previous run
+pub fn validate_deck_cached(
+    manifest: &DeckManifest,
+    slides: &[SlideDoc],
+    registry: Option<&ComponentRegistry>,
+    cache: &mut ValidationCache,
+) -> ValidationResult {
+    validate_deck_inner(manifest, slides, registry, Some(cache)).0
+}
+
+fn validate_deck_inner(
+    manifest: &DeckManifest,
+    slides: &[SlideDoc],
+    registry: Option<&ComponentRegistry>,
+    mut cache: Option<&mut ValidationCache>,
+) -> (ValidationResult, ValidationTimings) {
+    let started = Instant::now();
     let mut result = ValidationResult::new();
+    let mut timings = ValidationTimings::default();
+    
+    // Check the registry itself before using it to validate slides
+    if let Some(registry) = registry {
+        validate_registry(registry, &mut result);
+    }
     
     // Validate model version
     if manifest.model_version != "1.0" {
@@ -87,24 +390,113 @@ pub fn validate_deck_with_registry(
         });
     }
     
+    // Look up slides whose content hash matches the cache
+    let keys: Vec<u64> = match cache {
+        Some(_) => slides.iter().map(|slide| cache::slide_key(slide, registry)).collect(),
+        None => Vec::new(),
+    };
+    let cached: Vec<Option<ValidationResult>> = slides
+        .iter()
+        .enumerate()
+        .map(|(i, slide)| cache.as_ref().and_then(|c| c.lookup(&slide.id, keys[i])))
+        .collect();
+    
+    // Resolve each slide's versionReq once
+    let resolved: Vec<Option<&ComponentManifest>> = slides
+        .iter()
+        .map(|s| registry.and_then(|r| r.resolve(&s.component.name, &s.component.version_req)))
+        .collect();
+    
+    // Compile each component schema needed by uncached slides once, in parallel
+    let mut used_components: Vec<&ComponentManifest> = resolved
+        .iter()
+        .zip(&cached)
+        .filter(|(_, cached)| cached.is_none())
+        .filter_map(|(component, _)| *component)
+        .collect();
+    used_components.sort_by(|a, b| (&a.name, &a.version).cmp(&(&b.name, &b.version)));
+    used_components.dedup_by(|a, b| a.name == b.name && a.version == b.version);
+    let compiled: HashMap<(&str, &str), (CompiledSchema, Duration)> = used_components
+        .par_iter()
+        .map(|component| {
+            let started = Instant::now();
+            let schema = formats::compile_schema(&component.schema);
+            ((component.name.as_str(), component.version.as_str()), (schema, started.elapsed()))
+        })
+        .collect();
+    
+    // Validate slides in parallel, each into its own result
+    let hits = cached.iter().filter(|c| c.is_some()).count();
+    let per_slide: Vec<(ValidationResult, Duration)> = slides
+        .par_iter()
+        .zip(cached)
+        .zip(resolved.par_iter())
+        .map(|((slide, cached), component)| {
+            if let Some(slide_result) = cached {
+                return (slide_result, Duration::ZERO);
+            }
+            let started = Instant::now();
+            let mut slide_result = ValidationResult::new();
+            validate_slide_internal(slide, &mut slide_result);
+            if let Some(registry) = registry {
+                let schema = component
+                    .and_then(|c| compiled.get(&(c.name.as_str(), c.version.as_str())))
+                    .map(|(schema, _)| schema);
+                validate_component_schema(slide, registry, schema, &mut slide_result);
+            }
+            (slide_result, started.elapsed())
+        })
+        .collect();
+    
+    if let Some(cache) = cache.as_mut() {
+        let results = slides
+            .iter()
+            .zip(&keys)
+            .zip(&per_slide)
+            .map(|((slide, key), (slide_result, _))| (slide.id.clone(), *key, slide_result.clone()))
+            .collect();
+        cache.store(results, hits);
+    }
+    
     // Check for duplicate slide IDs
     let mut slide_ids = HashSet::new();
-    for slide in slides {
+    for (slide, (slide_result, duration)) in slides.iter().zip(per_slide) {
         if !slide_ids.insert(&slide.id) {
             result.add_error(ValidationError::DuplicateSlideId {
                 id: slide.id.clone(),
             });
         }
-        
-        // Validate individual slide
-        validate_slide_internal(slide, &mut result);
-        
-        // Validate component schema if registry is provided
-        if let Some(registry) = registry {
-            validate_component_schema(slide, registry, &mut result);
-        }
+        result.merge(slide_result);
+        timings.slides.push(SlideTiming {
+            slide_id: slide.id.clone(),
+            component: slide.component.name.clone(),
+            duration,
+        });
     }
     
+    timings.schemas = compiled
+        .iter()
+        .map(|(&(name, version), (_, compile))| {
+            // Slide timings are still in slide order here, matching `resolved`
+            let using: Vec<&SlideTiming> = timings
+                .slides
+                .iter()
+                .zip(&resolved)
+                .filter(|(_, c)| c.is_some_and(|c| c.name == name && c.version == version))
+                .map(|(t, _)| t)
+                .collect();
+            let versions_used = compiled.keys().filter(|(n, _)| *n == name).count();
+            SchemaTiming {
+                component: if versions_used > 1 { format!("{}@{}", name, version) } else { name.to_string() },
+                compile: *compile,
+                validate: using.iter().map(|t| t.duration).sum(),
+                slides: using.len(),
+            }
+        })
+        .collect();
+    timings.slides.sort_by_key(|t| std::cmp::Reverse(t.duration));
+    timings.schemas.sort_by_key(|t| std::cmp::Reverse(t.compile + t.validate));
+    
     // Validate sequence references
     for item in &manifest.sequence {
         match item {
@@ -127,9 +519,166 @@ pub fn validate_deck_with_registry(
         }
     }
     
+    if let Some(conditions) = &manifest.conditions {
+        validate_conditions(conditions, slides, &slide_ids, &mut result);
+    }
+    
+    // Deck-level notes are keyed by slide id
+    let mut note_keys: Vec<&String> = manifest.notes.keys().filter(|id| !slide_ids.contains(id)).collect();
+    note_keys.sort();
+    let known_ids: Vec<&str> = slides.iter().map(|s| s.id.as_str()).collect();
+    for id in note_keys {
+        result.add_error(ValidationError::UnknownNotesKey {
+            id: id.clone(),
+            suggestion: closest_match(id, &known_ids).map(str::to_string),
+        });
+    }
+    
+    validate_slot_ids(slides, &mut result);
+    
+    if let Some(template) = manifest.print.as_ref().and_then(|p| p.footer_template.as_deref()) {
+        let page_numbers = manifest.print.as_ref().and_then(|p| p.page_numbers).unwrap_or(true);
+        result.merge(validate_print_template(template, page_numbers, "print.footerTemplate"));
+    }
+    
+    if let Some(lint) = &manifest.lint {
+        lint::evaluate_custom_rules(&lint.custom, slides, registry, &mut result);
+    }
+    
+    apply_deck_lint(manifest, &mut result);
+    timings.total = started.elapsed();
+    (result, timings)
+}
+
+/// Slot ids share the runtime's targeting namespace with slide ids, so each must be unique deck-wide
+fn validate_slot_ids(slides: &[SlideDoc], result: &mut ValidationResult) {
+    let mut seen: HashMap<&str, String> = HashMap::new();
+    for slide in slides {
+        seen.entry(slide.id.as_str()).or_insert_with(|| format!("slide {}", slide.id));
+    }
+    
+    for slide in slides {
+        let mut slot_names: Vec<&String> = slide.slots.keys().collect();
+        slot_names.sort();
+        for slot_name in slot_names {
+            let Slot::Component { slot_id: Some(slot_id), .. } = &slide.slots[slot_name] else {
+                continue;
+            };
+            let location = format!("slot '{}' of slide {}", slot_name, slide.id);
+            match seen.get(slot_id.as_str()) {
+                Some(first) => result.add_error(ValidationError::DuplicateSlotId {
+                    slot_id: slot_id.clone(),
+                    slot: slot_name.clone(),
+                    slide_id: slide.id.clone(),
+                    first: first.clone(),
+                }),
+                None => {
+                    seen.insert(slot_id, location);
+                }
+            }
+        }
+    }
+}
+
+/// Placeholders print header and footer templates can use
+pub const PRINT_TEMPLATE_PLACEHOLDERS: &[&str] = &["pageNumber", "totalPages", "title", "date", "url"];
+
+/// Placeholders that only render when page numbers are enabled
+const PAGE_NUMBER_PLACEHOLDERS: &[&str] = &["pageNumber", "totalPages"];
+
+/// Placeholder names used by a print template (`{name}`; `{{` and `}}` are literal braces)
+pub fn template_placeholders(template: &str) -> Result<Vec<String>, String> {
+    let mut placeholders = Vec::new();
+    let mut chars = template.char_indices().peekable();
+    
+    while let Some((offset, c)) = chars.next() {
+        match c {
+            '{' if chars.peek().map(|(_, c)| *c) == Some('{') => {
+                chars.next();
+            }
+            '}' if chars.peek().map(|(_, c)| *c) == Some('}') => {
+                chars.next();
+            }
+            '{' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '}')) => break,
+                        Some((_, '{')) | None => return Err(format!("unclosed '{{' at offset {}", offset)),
+                        Some((_, c)) => name.push(c),
+                    }
+                }
+                placeholders.push(name.trim().to_string());
+            }
+            '}' => return Err(format!("unmatched '}}' at offset {}", offset)),
+            _ => {}
+        }
+    }
+    
+    Ok(placeholders)
+}
+
+/// Check a print header/footer template for unknown placeholders and for page-number
+/// placeholders used while page numbers are disabled
+pub fn validate_print_template(template: &str, page_numbers: bool, location: &str) -> ValidationResult {
+    let mut result = ValidationResult::new();
+    
+    let placeholders = match template_placeholders(template) {
+        Ok(placeholders) => placeholders,
+        Err(reason) => {
+            result.add_error(ValidationError::MalformedPrintTemplate {
+                location: location.to_string(),
+                reason,
+            });
+            return result;
+        }
+    };
+    
+    for placeholder in placeholders {
+        if !PRINT_TEMPLATE_PLACEHOLDERS.contains(&placeholder.as_str()) {
+            result.add_error(ValidationError::UnknownPrintPlaceholder {
+                placeholder,
+                location: location.to_string(),
+            });
+        } else if !page_numbers && PAGE_NUMBER_PLACEHOLDERS.contains(&placeholder.as_str()) {
+            result.add_warning(ValidationError::PageNumbersDisabled {
+                placeholder,
+                location: location.to_string(),
+            });
+        }
+    }
+    
     result
 }
 
+/// Warn about condition filters that reference tags or slide ids the deck never uses
+fn validate_conditions(
+    conditions: &ConditionConfig,
+    slides: &[SlideDoc],
+    slide_ids: &HashSet<&String>,
+    result: &mut ValidationResult,
+) {
+    let tags: HashSet<&String> = slides.iter().flat_map(|slide| &slide.tags).collect();
+    for tag in conditions.include_tags.iter().flatten() {
+        if !tags.contains(tag) {
+            result.add_warning(ValidationError::UnknownConditionTag { tag: tag.clone() });
+        }
+    }
+    
+    for id in conditions.exclude_ids.iter().flatten() {
+        if !slide_ids.contains(id) {
+            result.add_warning(ValidationError::UnknownConditionId { id: id.clone() });
+        }
+    }
+}
+
+/// Apply the deck's `[lint]` severity overrides, if any
+pub(crate) fn apply_deck_lint(manifest: &DeckManifest, result: &mut ValidationResult) {
+    if let Some(lint) = &manifest.lint {
+        lint::apply_severities(&lint.rules, result);
+    }
+}
+
 /// Validate a single slide document
 pub fn validate_slide(slide: &SlideDoc) -> ValidationResult {
     validate_slide_with_registry(slide, None)
@@ -152,7 +701,7 @@ pub fn validate_slide_with_registry(
     
     // Validate component schema if registry is provided
     if let Some(registry) = registry {
-        validate_component_schema(slide, registry, &mut result);
+        validate_component_schema(slide, registry, None, &mut result);
     }
     
     result
@@ -170,33 +719,123 @@ fn validate_slide_internal(slide: &SlideDoc, result: &mut ValidationResult) {
     
     // Validate slots
     for (slot_name, slot) in &slide.slots {
-        validate_slot(slot, slot_name, result);
+        validate_slot(slot, slot_name, &slide.id, result);
+    }
+    
+    if let Some(narration) = &slide.narration {
+        let path = narration.src.split(['?', '#']).next().unwrap_or_default();
+        let extension = path.rsplit_once('.').map(|(_, ext)| ext.to_ascii_lowercase());
+        let reason = if narration.src.trim().is_empty() {
+            Some("src is empty".to_string())
+        } else if !(narration.duration.is_finite() && narration.duration > 0.0) {
+            Some(format!("duration must be a positive number of seconds, got {}", narration.duration))
+        } else if !extension.as_deref().is_some_and(|ext| NARRATION_EXTENSIONS.contains(&ext)) {
+            Some(format!("'{}' is not a supported audio file ({})", narration.src, NARRATION_EXTENSIONS.join(", ")))
+        } else {
+            None
+        };
+        if let Some(reason) = reason {
+            result.add_error(ValidationError::InvalidNarration { slide_id: slide.id.clone(), reason });
+        }
+    }
+}
+
+/// Audio formats browsers and ffmpeg both play
+const NARRATION_EXTENSIONS: &[&str] = &["mp3", "m4a", "aac", "wav", "ogg", "oga", "opus", "flac"];
+
+/// Check that each slide's narration file exists in the deck directory
+pub fn validate_narration(slides: &[SlideDoc], deck_root: &Path) -> ValidationResult {
+    let mut result = ValidationResult::new();
+    for slide in slides {
+        let Some(narration) = slide.narration.as_ref().filter(|n| !n.is_remote() && !n.src.trim().is_empty()) else {
+            continue;
+        };
+        if !deck_root.join(narration.src.trim_start_matches('/')).is_file() {
+            result.add_error(ValidationError::MissingNarrationFile {
+                src: narration.src.clone(),
+                slide_id: slide.id.clone(),
+            });
+        }
     }
+    result
 }
 
-fn validate_slot(slot: &Slot, slot_name: &str, result: &mut ValidationResult) {
+fn validate_slot(slot: &Slot, slot_name: &str, slide_id: &str, result: &mut ValidationResult) {
+    let empty_component = |field: &str| ValidationError::EmptySlotComponent {
+        field: field.to_string(),
+        slot: slot_name.to_string(),
+        slide_id: slide_id.to_string(),
+    };
+    
     match slot {
         Slot::Markdown { value } => {
             if value.is_empty() {
-                result.add_warning(format!("Empty markdown slot: {}", slot_name));
+                result.add_warning(ValidationError::EmptyMarkdownSlot {
+                    slot: slot_name.to_string(),
+                    slide_id: slide_id.to_string(),
+                });
+            } else {
+                markdown::lint_markdown(value, slot_name, slide_id, result);
             }
         }
         Slot::Component { tag, module, .. } => {
             if tag.is_empty() {
-                result.add_warning(format!("Empty component tag in slot: {}", slot_name));
+                result.add_warning(empty_component("tag"));
             }
             if module.is_empty() {
-                result.add_warning(format!("Empty component module in slot: {}", slot_name));
+                result.add_warning(empty_component("module"));
             }
         }
     }
 }
 
-/// Validate component props against JSON schema
-fn validate_component_schema(slide: &SlideDoc, registry: &ComponentRegistry, result: &mut ValidationResult) {
-    // Check if the component exists in the registry
-    let component = match registry.components.get(&slide.component.name) {
+/// Validate that no two components claim the same custom element tag
+fn validate_registry(registry: &ComponentRegistry, result: &mut ValidationResult) {
+    let source_of = |name: &str| {
+        registry
+            .sources
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| "unknown source".to_string())
+    };
+
+    for (tag, names) in registry.tag_collisions() {
+        // Report each additional claimant against the first so every component is named
+        for other in &names[1..] {
+            result.add_error(ValidationError::TagCollision {
+                tag: tag.clone(),
+                first: names[0].clone(),
+                first_source: source_of(&names[0]),
+                second: other.clone(),
+                second_source: source_of(other),
+            });
+        }
+    }
+}
+
+/// Props schema compiled with format checks, or the compile error
+type CompiledSchema = Result<jsonschema::JSONSchema, String>;
+
+/// Validate component props against JSON schema, compiling it unless `compiled` is given
+fn validate_component_schema(
+    slide: &SlideDoc,
+    registry: &ComponentRegistry,
+    compiled: Option<&CompiledSchema>,
+    result: &mut ValidationResult,
+) {
+    // Resolve the slide's versionReq to one of the registered versions
+    let component = match registry.resolve(&slide.component.name, &slide.component.version_req) {
         Some(component) => component,
+        None if registry.components.contains_key(&slide.component.name) => {
+            let available: Vec<&str> = registry.all_versions(&slide.component.name).map(|m| m.version.as_str()).collect();
+            result.add_error(ValidationError::UnsatisfiedVersionReq {
+                name: slide.component.name.clone(),
+                version_req: slide.component.version_req.clone(),
+                slide_id: slide.id.clone(),
+                available: available.join(", "),
+            });
+            return;
+        }
         None => {
             result.add_error(ValidationError::UnknownComponent {
                 name: slide.component.name.clone(),
@@ -206,8 +845,59 @@ fn validate_component_schema(slide: &SlideDoc, registry: &ComponentRegistry, res
         }
     };
     
-    // Compile the JSON schema
-    let schema = match jsonschema::JSONSchema::compile(&component.schema) {
+    if let Some(declared) = &component.slots {
+        validate_declared_slots(slide, &component.name, declared, result);
+    }
+    
+    if let Some(deprecation) = &component.deprecated {
+        result.add_warning(ValidationError::DeprecatedComponent {
+            name: component.name.clone(),
+            version: component.version.clone(),
+            slide_id: slide.id.clone(),
+            note: deprecation.to_string(),
+        });
+    }
+    let properties = component.schema.get("properties").and_then(|p| p.as_object());
+    let mut used: Vec<&String> = slide.props.as_object().map(|props| props.keys().collect()).unwrap_or_default();
+    used.sort();
+    for prop in used {
+        if let Some(deprecation) = properties.and_then(|p| p.get(prop)).and_then(Deprecation::of_prop) {
+            result.add_warning(ValidationError::DeprecatedProp {
+                prop: prop.clone(),
+                component: component.name.clone(),
+                slide_id: slide.id.clone(),
+                note: deprecation.to_string(),
+                replacement: deprecation.replacement,
+            });
+        }
+    }
+    
+    // Overrides only take effect through tokens the component reads; skip components
+    // that do not list their tokens
+    if !component.tokens_used.is_empty() {
+        let mut keys: Vec<&String> = slide.style_overrides.keys().collect();
+        keys.sort();
+        for key in keys {
+            if key.starts_with("--") && !component.tokens_used.contains(key) {
+                result.add_warning(ValidationError::UnusedStyleOverride {
+                    key: key.clone(),
+                    component: component.name.clone(),
+                    slide_id: slide.id.clone(),
+                });
+            }
+        }
+    }
+    
+    // Compile the JSON schema, checking `format` keywords (uri, date-time, color, ...)
+    let compiled_here;
+    let compiled = match compiled {
+        Some(compiled) => compiled,
+        None => {
+            compiled_here = formats::compile_schema(&component.schema);
+            &compiled_here
+        }
+    };
+    let schema = match compiled {
         Ok(schema) => schema,
         Err(e) => {
             result.add_error(ValidationError::InvalidComponentProps {
@@ -253,6 +943,56 @@ fn validate_component_schema(slide: &SlideDoc, registry: &ComponentRegistry, res
     }
 }
 
+/// Check a slide's slots against the slots its component declares
+fn validate_declared_slots(
+    slide: &SlideDoc,
+    component: &str,
+    declared: &[SlotDeclaration],
+    result: &mut ValidationResult,
+) {
+    let mut slot_names: Vec<&String> = slide.slots.keys().collect();
+    slot_names.sort();
+    for slot_name in slot_names {
+        let Some(declaration) = declared.iter().find(|d| d.name == *slot_name) else {
+            let names: Vec<&str> = declared.iter().map(|d| d.name.as_str()).collect();
+            result.add_error(ValidationError::UnknownSlot {
+                slot: slot_name.clone(),
+                component: component.to_string(),
+                slide_id: slide.id.clone(),
+                declared: if names.is_empty() { "none".to_string() } else { names.join(", ") },
+            });
+            continue;
+        };
+        
+        let kind = slide.slots[slot_name].kind();
+        if !declaration.accepts_kind(kind) {
+            let accepts: Vec<String> = declaration.accepts.iter().map(|k| k.to_string()).collect();
+            result.add_error(ValidationError::SlotKindNotAccepted {
+                slot: slot_name.clone(),
+                component: component.to_string(),
+                slide_id: slide.id.clone(),
+                kind: kind.to_string(),
+                accepts: accepts.join(" or "),
+            });
+        }
+    }
+    
+    for declaration in declared.iter().filter(|d| d.required) {
+        let filled = match slide.slots.get(&declaration.name) {
+            Some(Slot::Markdown { value }) => !value.trim().is_empty(),
+            Some(Slot::Component { tag, .. }) => !tag.is_empty(),
+            None => false,
+        };
+        if !filled {
+            result.add_warning(ValidationError::EmptyRequiredSlot {
+                slot: declaration.name.clone(),
+                component: component.to_string(),
+                slide_id: slide.id.clone(),
+            });
+        }
+    }
+}
+
 /// Format JSON path from instance path for better error messages
 fn format_json_path(instance_path: &str) -> String {
     if instance_path.is_empty() {
@@ -287,4 +1027,365 @@ pub fn validate_lockfile(lockfile: &Lockfile) -> ValidationResult {
     // Additional lockfile validation can be added here
     
     result
-}
\ No newline at end of file
+}
+
+/// Transitions the runtime provides without any plugin
+pub const BUILTIN_TRANSITIONS: &[&str] = &["none", "fade", "slide", "zoom"];
+
+/// Validate transition names in `[transitions]` and sequence groups against the built-in
+/// transitions plus those registered by plugins in the lockfile
+pub fn validate_transitions(manifest: &DeckManifest, lockfile: Option<&Lockfile>) -> ValidationResult {
+    let mut result = ValidationResult::new();
+    
+    let mut known: Vec<&str> = BUILTIN_TRANSITIONS.to_vec();
+    if let Some(lockfile) = lockfile {
+        known.extend(
+            lockfile
+                .resolved
+                .plugins
+                .values()
+                .flat_map(|plugin| plugin.transitions.iter().map(String::as_str)),
+        );
+    }
+    
+    let mut check = |name: &str, location: String| {
+        if !known.contains(&name) {
+            result.add_error(ValidationError::UnknownTransition {
+                name: name.to_string(),
+                location,
+                suggestion: closest_match(name, &known).map(str::to_string),
+            });
+        }
+    };
+    
+    check(&manifest.transitions.default, "transitions.default".to_string());
+    
+    let mut overrides: Vec<_> = manifest.transitions.overrides.iter().collect();
+    overrides.sort();
+    for (slide_id, name) in overrides {
+        check(name, format!("transitions.overrides.{}", slide_id));
+    }
+    
+    for item in &manifest.sequence {
+        if let DeckItem::Group { name: group, transition: Some(name), .. } = item {
+            check(name, format!("group '{}'", group));
+        }
+    }
+    
+    apply_deck_lint(manifest, &mut result);
+    result
+}
+
+/// Warn about slides, slots, assets, and groups that exceed the deck's performance budgets.
+///
+/// Assets are the theme and tokens stylesheets, images embedded in markdown slots, and
+/// string props naming a file in the deck; remote URLs are not measured.
+pub fn validate_budgets(manifest: &DeckManifest, slides: &[SlideDoc], deck_root: Option<&Path>) -> ValidationResult {
+    let mut result = ValidationResult::new();
+    let budgets = manifest.lint.as_ref().map(|l| l.budgets.clone()).unwrap_or_default();
+    let over = |value: u64, limit: u64| limit > 0 && value > limit;
+    
+    let mut assets: Vec<String> = std::iter::once(manifest.theme.clone())
+        .chain(manifest.tokens.clone())
+        .collect();
+    
+    for slide in slides {
+        let bytes = serde_json::to_vec(&slide.props).map(|v| v.len() as u64).unwrap_or(0);
+        if over(bytes, budgets.max_props_bytes) {
+            result.add_warning(ValidationError::PropsBudgetExceeded {
+                slide_id: slide.id.clone(),
+                bytes,
+                limit: budgets.max_props_bytes,
+            });
+        }
+        collect_prop_paths(&slide.props, &mut assets);
+        
+        let mut slot_names: Vec<&String> = slide.slots.keys().collect();
+        slot_names.sort();
+        for slot_name in slot_names {
+            if let Slot::Markdown { value } = &slide.slots[slot_name] {
+                let chars = value.chars().count() as u64;
+                if over(chars, budgets.max_markdown_chars) {
+                    result.add_warning(ValidationError::MarkdownBudgetExceeded {
+                        slot: slot_name.clone(),
+                        slide_id: slide.id.clone(),
+                        chars,
+                        limit: budgets.max_markdown_chars,
+                    });
+                }
+                assets.extend(markdown::image_sources(value));
+            }
+        }
+    }
+    
+    if let Some(deck_root) = deck_root.filter(|_| budgets.max_asset_bytes > 0) {
+        assets.sort();
+        assets.dedup();
+        for asset in assets.iter().filter(|a| !a.contains("://") && !a.starts_with("data:")) {
+            let path = deck_root.join(asset.trim_start_matches('/'));
+            let Some(bytes) = std::fs::metadata(&path).ok().filter(|m| m.is_file()).map(|m| m.len()) else {
+                continue;
+            };
+            if over(bytes, budgets.max_asset_bytes) {
+                result.add_warning(ValidationError::AssetBudgetExceeded {
+                    path: asset.clone(),
+                    bytes,
+                    limit: budgets.max_asset_bytes,
+                });
+            }
+        }
+    }
+    
+    for item in &manifest.sequence {
+        if let DeckItem::Group { name, slides, .. } = item {
+            let count = slides.len() as u64;
+            if over(count, budgets.max_slides_per_group) {
+                result.add_warning(ValidationError::GroupBudgetExceeded {
+                    group: name.clone(),
+                    slides: count,
+                    limit: budgets.max_slides_per_group,
+                });
+            }
+        }
+    }
+    
+    apply_deck_lint(manifest, &mut result);
+    result
+}
+
+/// Collect string props that look like relative file paths
+fn collect_prop_paths(value: &serde_json::Value, paths: &mut Vec<String>) {
+    match value {
+        serde_json::Value::String(s) => {
+            let looks_like_path = s.len() < 512
+                && !s.contains(char::is_whitespace)
+                && s.rsplit('/').next().is_some_and(|name| name.contains('.'));
+            if looks_like_path {
+                paths.push(s.clone());
+            }
+        }
+        serde_json::Value::Array(items) => items.iter().for_each(|v| collect_prop_paths(v, paths)),
+        serde_json::Value::Object(map) => map.values().for_each(|v| collect_prop_paths(v, paths)),
+        _ => {}
+    }
+}
+
+/// Closest candidate within a small edit distance, for "did you mean" hints
+fn closest_match<'a>(name: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    let max_distance = (name.chars().count() / 3).max(1);
+    candidates
+        .iter()
+        .map(|candidate| (edit_distance(name, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+/// Edit distance counting insertions, deletions, substitutions, and adjacent
+/// transpositions (optimal string alignment)
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1).min(d[i][j - 1] + 1).min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+/// Validate deck plugins against the deck's import map, lockfile, and local files
+pub fn validate_plugins(
+    manifest: &DeckManifest,
+    import_map: Option<&ImportMap>,
+    lockfile: Option<&Lockfile>,
+    deck_root: &Path,
+) -> ValidationResult {
+    let mut result = ValidationResult::new();
+    
+    for plugin in &manifest.plugins {
+        let resolved = if plugin.starts_with("http://") || plugin.starts_with("https://") {
+            true
+        } else if plugin.starts_with("./") || plugin.starts_with("../") {
+            deck_root.join(plugin).exists()
+        } else if plugin.starts_with('/') {
+            // Absolute URLs are served by the dev server, not resolved from the deck
+            true
+        } else {
+            import_map.and_then(|map| map.resolve(plugin)).is_some()
+                || lockfile.is_some_and(|lock| {
+                    lock.resolved.plugins.contains_key(plugin)
+                        || lock.import_map.resolve(plugin).is_some()
+                })
+        };
+        
+        if !resolved {
+            result.add_error(ValidationError::UnresolvedPlugin {
+                plugin: plugin.clone(),
+            });
+        }
+    }
+    
+    // Plugins mapped in importmap.json but never loaded by the deck. An entry is a plugin when
+    // the lockfile pins it as one or its package name says so, so decks without a lockfile are
+    // checked too.
+    if let Some(import_map) = import_map {
+        let pinned = |name: &str| lockfile.is_some_and(|lock| lock.resolved.plugins.contains_key(name));
+        let mut unused: Vec<&String> = import_map
+            .imports
+            .iter()
+            .filter(|(name, _)| pinned(name) || is_plugin_package(name))
+            .filter(|(name, url)| !manifest.plugins.iter().any(|plugin| loads_through(plugin, name, url)))
+            .map(|(name, _)| name)
+            .collect();
+        unused.sort();
+        for name in unused {
+            result.add_warning(ValidationError::UnusedPlugin { plugin: name.clone() });
+        }
+    }
+    
+    apply_deck_lint(manifest, &mut result);
+    result
+}
+
+/// Whether an import map specifier names a plugin package, like `@coolslides/plugins-stdlib`
+/// or `coolslides-plugin-confetti`
+fn is_plugin_package(specifier: &str) -> bool {
+    let mut segments = specifier.split('/');
+    let package = match segments.next() {
+        Some(scope) if scope.starts_with('@') => segments.next(),
+        first => first,
+    };
+    package.is_some_and(|name| name.contains("plugin"))
+}
+
+/// Whether the deck plugin `plugin` loads through the import map entry `specifier` -> `url`:
+/// by name, by a subpath of it, or by a path into the directory it maps to
+fn loads_through(plugin: &str, specifier: &str, url: &str) -> bool {
+    let directory = url.rfind('/').map_or("", |end| &url[..=end]);
+    plugin == specifier
+        || plugin
+            .strip_prefix(specifier)
+            .is_some_and(|rest| specifier.ends_with('/') || rest.starts_with('/'))
+        || (!directory.is_empty() && plugin.starts_with(directory))
+}
+
+/// Compare the lockfile against importmap.json, slides.toml plugins, and the components used by slides
+pub fn validate_lockfile_drift(
+    lockfile: &Lockfile,
+    import_map: Option<&ImportMap>,
+    manifest: &DeckManifest,
+    slides: &[SlideDoc],
+    registry: Option<&ComponentRegistry>,
+) -> ValidationResult {
+    let mut result = ValidationResult::new();
+    
+    // Unpinned: import map entries the lockfile doesn't know about (or pins to a different URL)
+    if let Some(import_map) = import_map {
+        let mut specifiers: Vec<&String> = import_map.imports.keys().collect();
+        specifiers.sort();
+        for specifier in specifiers {
+            if lockfile.import_map.imports.get(specifier) != import_map.imports.get(specifier) {
+                result.add_error(ValidationError::UnpinnedImport {
+                    specifier: specifier.clone(),
+                });
+            }
+        }
+        
+        let mut stale: Vec<&String> = lockfile
+            .import_map
+            .imports
+            .keys()
+            .filter(|specifier| !import_map.imports.contains_key(*specifier))
+            .collect();
+        stale.sort();
+        for specifier in stale {
+            result.add_warning(ValidationError::OrphanedLockEntry {
+                kind: "import".to_string(),
+                name: specifier.clone(),
+            });
+        }
+    }
+    
+    // Missing: bare-specifier plugins must be pinned; paths and URLs resolve on their own
+    for plugin in &manifest.plugins {
+        if is_package_specifier(plugin) && !lockfile.resolved.plugins.contains_key(plugin) {
+            result.add_error(ValidationError::MissingLockEntry {
+                kind: "Plugin".to_string(),
+                name: plugin.clone(),
+            });
+        }
+    }
+    
+    let used_components: HashSet<&str> = slides.iter().map(|s| s.component.name.as_str()).collect();
+    if let Some(registry) = registry {
+        let mut names: Vec<&&str> = used_components.iter().collect();
+        names.sort();
+        for name in names {
+            // Components served from local paths don't need pinning
+            let needs_pin = registry
+                .components
+                .get(*name)
+                .is_some_and(|m| is_package_specifier(&m.module) || m.module.starts_with("http"));
+            if needs_pin && !lockfile.resolved.components.contains_key(*name) {
+                result.add_error(ValidationError::MissingLockEntry {
+                    kind: "Component".to_string(),
+                    name: name.to_string(),
+                });
+            }
+        }
+    }
+    
+    // Orphaned: resolved entries nothing in the deck uses anymore. Plugins still mapped in
+    // importmap.json are reported by validate_plugins instead.
+    let mut orphaned_plugins: Vec<&String> = lockfile
+        .resolved
+        .plugins
+        .keys()
+        .filter(|name| !manifest.plugins.contains(name))
+        .filter(|name| import_map.is_none_or(|map| map.resolve(name).is_none()))
+        .collect();
+    orphaned_plugins.sort();
+    for name in orphaned_plugins {
+        result.add_warning(ValidationError::OrphanedLockEntry {
+            kind: "plugin".to_string(),
+            name: name.clone(),
+        });
+    }
+    
+    let mut orphaned_components: Vec<&String> = lockfile
+        .resolved
+        .components
+        .keys()
+        .filter(|name| !used_components.contains(name.as_str()))
+        .collect();
+    orphaned_components.sort();
+    for name in orphaned_components {
+        result.add_warning(ValidationError::OrphanedLockEntry {
+            kind: "component".to_string(),
+            name: name.clone(),
+        });
+    }
+    
+    apply_deck_lint(manifest, &mut result);
+    result
+}
+
+/// Whether a module reference is a bare package specifier rather than a path or URL
+fn is_package_specifier(spec: &str) -> bool {
+    !(spec.starts_with('/')
+        || spec.starts_with("./")
+        || spec.starts_with("../")
+        || spec.contains("://"))
+}
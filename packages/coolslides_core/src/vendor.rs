@@ -0,0 +1,212 @@
+//! Offline vendoring of remote import-map modules.
+//!
+//! Every `http(s)` module in a deck's import map is downloaded into `vendor/`,
+//! along with the modules it imports by URL or relative path. Imports inside
+//! vendored files are rewritten to relative paths, and the import map is
+//! rewritten to `./vendor/...`, so the deck runs without network access.
+
+use crate::integrity;
+use crate::ir::ImportMap;
+use anyhow::Result;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::path::Path;
+use std::sync::OnceLock;
+use url::Url;
+
+/// Directory under the deck that holds vendored modules
+pub const VENDOR_DIR: &str = "vendor";
+
+/// A downloaded module, as recorded in the lockfile
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct VendoredModule {
+    /// Path relative to the deck directory
+    pub path: String,
+    /// Subresource integrity hash of the file as written
+    pub integrity: String,
+}
+
+/// Outcome of vendoring a deck's import map
+#[derive(Debug, Clone, Default)]
+pub struct VendorResult {
+    /// Import map with remote entries pointing into `vendor/`
+    pub import_map: ImportMap,
+    /// Vendored modules by original URL
+    pub modules: BTreeMap<String, VendoredModule>,
+    /// Import map entries that were left as they are, with the reason
+    pub skipped: Vec<(String, String)>,
+}
+
+/// Download every remote module in `import_map`, and the modules they import, into
+/// `<deck_dir>/vendor`. `fetch` returns a URL's contents.
+pub fn vendor(import_map: &ImportMap, deck_dir: &Path, fetch: impl Fn(&str) -> Result<Vec<u8>>) -> Result<VendorResult> {
+    let mut result = VendorResult { import_map: import_map.clone(), ..Default::default() };
+    let mut paths: BTreeMap<String, String> = BTreeMap::new();
+    let mut queue: VecDeque<Url> = VecDeque::new();
+
+    let mut specifiers: Vec<&String> = import_map.imports.keys().collect();
+    specifiers.sort();
+    for specifier in specifiers {
+        let target = &import_map.imports[specifier];
+        let Some(url) = remote_url(target) else {
+            continue;
+        };
+        if specifier.ends_with('/') {
+            result.skipped.push((specifier.clone(), "prefix mappings cannot be downloaded as a whole".to_string()));
+            continue;
+        }
+        let path = enqueue(&url, &mut paths, &mut queue);
+        result.import_map.imports.insert(specifier.clone(), format!("./{}", path));
+    }
+
+    while let Some(url) = queue.pop_front() {
+        let path = paths[url.as_str()].clone();
+        let mut bytes = fetch(url.as_str())?;
+        if is_javascript(&path) {
+            if let Ok(source) = String::from_utf8(bytes.clone()) {
+                bytes = rewrite_imports(&source, &url, &path, &mut paths, &mut queue).into_bytes();
+            }
+        }
+        let file = deck_dir.join(&path);
+        if let Some(parent) = file.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&file, &bytes)?;
+        result.modules.insert(url.to_string(), VendoredModule { path, integrity: integrity::sri_hash(&bytes) });
+    }
+    Ok(result)
+}
+
+/// Point each URL or relative import at its vendored copy, queueing modules not seen yet;
+/// bare specifiers are left for the import map
+fn rewrite_imports(
+    source: &str,
+    url: &Url,
+    path: &str,
+    paths: &mut BTreeMap<String, String>,
+    queue: &mut VecDeque<Url>,
+) -> String {
+    static IMPORT: OnceLock<Regex> = OnceLock::new();
+    let import = IMPORT.get_or_init(|| {
+        Regex::new(r#"(?:\bfrom\s*|\bimport\s*\(\s*|\bimport\s*)(?:"([^"\n]+)"|'([^'\n]+)')"#).unwrap()
+    });
+
+    let mut output = String::with_capacity(source.len());
+    let mut cursor = 0;
+    for captures in import.captures_iter(source) {
+        let specifier = captures.get(1).or_else(|| captures.get(2)).unwrap();
+        let text = specifier.as_str();
+        let is_path = ["http://", "https://", "//", "/", "./", "../"].iter().any(|p| text.starts_with(p));
+        let Some(target) = is_path.then(|| url.join(text).ok()).flatten().filter(|u| u.scheme().starts_with("http")) else {
+            continue;
+        };
+        let target_path = enqueue(&target, paths, queue);
+        output.push_str(&source[cursor..specifier.start()]);
+        output.push_str(&relative_path(path, &target_path));
+        cursor = specifier.end();
+    }
+    output.push_str(&source[cursor..]);
+    output
+}
+
+/// Local path for a URL, assigning one and queueing the download the first time it is seen
+fn enqueue(url: &Url, paths: &mut BTreeMap<String, String>, queue: &mut VecDeque<Url>) -> String {
+    if let Some(path) = paths.get(url.as_str()) {
+        return path.clone();
+    }
+    let path = local_path(url);
+    paths.insert(url.to_string(), path.clone());
+    queue.push_back(url.clone());
+    path
+}
+
+/// `vendor/<host>/<path>`, with the query folded into the file name and `.js`
+/// added when the URL has no script or style extension (CDN entry points often have none)
+fn local_path(url: &Url) -> String {
+    let mut path = url.path().trim_start_matches('/').to_string();
+    if path.is_empty() || path.ends_with('/') {
+        path.push_str("index");
+    }
+    if let Some(query) = url.query() {
+        path.push('_');
+        path.push_str(query);
+    }
+    let mut path: String = path
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || "/.-_@+".contains(c) { c } else { '_' })
+        .collect::<String>()
+        .split('/')
+        .filter(|segment| !segment.is_empty() && *segment != "." && *segment != "..")
+        .collect::<Vec<_>>()
+        .join("/");
+    let has_extension = Path::new(&path)
+        .extension()
+        .is_some_and(|e| matches!(e.to_str(), Some("js" | "mjs" | "css" | "json" | "wasm")));
+    if !has_extension {
+        path.push_str(".js");
+    }
+    let host = match url.port() {
+        Some(port) => format!("{}_{}", url.host_str().unwrap_or("unknown"), port),
+        None => url.host_str().unwrap_or("unknown").to_string(),
+    };
+    format!("{}/{}/{}", VENDOR_DIR, host, path)
+}
+
+/// Relative import from one vendored file to another
+fn relative_path(from: &str, to: &str) -> String {
+    let from_dir: Vec<&str> = from.split('/').collect::<Vec<_>>().split_last().map(|(_, dir)| dir.to_vec()).unwrap_or_default();
+    let to_parts: Vec<&str> = to.split('/').collect();
+    let common = from_dir.iter().zip(&to_parts).take_while(|(a, b)| a == b).count();
+    let ups = from_dir.len() - common;
+    let rest = to_parts[common..].join("/");
+    if ups == 0 {
+        format!("./{}", rest)
+    } else {
+        format!("{}{}", "../".repeat(ups), rest)
+    }
+}
+
+fn remote_url(target: &str) -> Option<Url> {
+    Url::parse(target).ok().filter(|url| matches!(url.scheme(), "http" | "https"))
+}
+
+fn is_javascript(path: &str) -> bool {
+    path.ends_with(".js") || path.ends_with(".mjs")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_vendor_follows_and_rewrites_imports() {
+        let files: HashMap<&str, &str> = HashMap::from([
+            ("https://cdn.example.com/npm/chart@1/+esm", "import { a } from \"/npm/util@2/index.js\";\nimport 'lit';\nexport * from './extra.js';"),
+            ("https://cdn.example.com/npm/util@2/index.js", "export const a = import('https://other.example.com/lazy.mjs');"),
+            ("https://cdn.example.com/npm/chart@1/extra.js", "export const b = 2;"),
+            ("https://other.example.com/lazy.mjs", "export default 1;"),
+        ]);
+        let fetch = |url: &str| files.get(url).map(|f| f.as_bytes().to_vec()).ok_or_else(|| anyhow::anyhow!("no {}", url));
+        let mut import_map = ImportMap::default();
+        import_map.imports.insert("chart".into(), "https://cdn.example.com/npm/chart@1/+esm".into());
+        import_map.imports.insert("lit".into(), "/packages/lit/index.js".into());
+
+        let deck_dir = std::env::temp_dir().join(format!("coolslides-vendor-{}", std::process::id()));
+        let result = vendor(&import_map, &deck_dir, fetch).unwrap();
+        let entry = std::fs::read_to_string(deck_dir.join("vendor/cdn.example.com/npm/chart@1/+esm.js")).unwrap();
+        let util = std::fs::read_to_string(deck_dir.join("vendor/cdn.example.com/npm/util@2/index.js")).unwrap();
+        std::fs::remove_dir_all(&deck_dir).unwrap();
+
+        assert_eq!(result.import_map.imports["chart"], "./vendor/cdn.example.com/npm/chart@1/+esm.js");
+        assert_eq!(result.import_map.imports["lit"], "/packages/lit/index.js");
+        assert_eq!(result.modules.len(), 4);
+        assert_eq!(
+            entry,
+            "import { a } from \"../util@2/index.js\";\nimport 'lit';\nexport * from './extra.js';"
+        );
+        assert_eq!(util, "export const a = import('../../../other.example.com/lazy.mjs');");
+        assert!(integrity::matches(entry.as_bytes(), &result.modules["https://cdn.example.com/npm/chart@1/+esm"].integrity));
+    }
+}
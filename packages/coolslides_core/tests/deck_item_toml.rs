@@ -1,4 +1,5 @@
-use coolslides_core::DeckItem;
+use coolslides_core::{DeckItem, DeckManifest, ResolvedSlide, SlideDoc};
+use std::collections::HashMap;
 
 #[derive(serde::Deserialize, Debug)]
 struct Wrap {
@@ -70,3 +71,58 @@ fn group_missing_name_errors() {
     let msg = err.to_string();
     assert!(msg.contains("group item missing required field 'name'"), "unexpected error: {}", msg);
 }
+
+#[test]
+fn resolve_expands_groups_filters_and_settles_transitions() {
+    let deck: DeckManifest = toml::from_str(
+        r#"modelVersion = "1.0"
+title = "T"
+theme = "theme.css"
+sequence = [
+  "intro",
+  { type = "group", name = "Demo", transition = "fade", slides = ["setup", "live", "bonus"] },
+  "outro",
+]
+
+[transitions]
+default = "slide"
+
+[transitions.overrides]
+live = "zoom"
+
+[conditions]
+excludeIds = ["bonus"]
+
+[profiles.short]
+includeTags = ["core"]
+"#,
+    )
+    .expect("parse deck");
+    let slides: HashMap<String, SlideDoc> = [("intro", "core"), ("setup", "detail"), ("live", "core"), ("bonus", "core"), ("outro", "detail")]
+        .into_iter()
+        .map(|(id, tag)| {
+            let slide = format!("modelVersion = \"1.0\"\nid = \"{}\"\ntags = [\"{}\"]\n\n[component]\nname = \"TitleSlide\"\nversionReq = \"^1\"\n\n[props]\n", id, tag);
+            (id.to_string(), toml::from_str(&slide).expect("parse slide"))
+        })
+        .collect();
+    let slide = |id: &str, group: Option<&str>, transition: &str| ResolvedSlide {
+        id: id.to_string(),
+        group: group.map(str::to_string),
+        transition: transition.to_string(),
+    };
+
+    assert_eq!(
+        deck.resolve(&slides, deck.conditions.as_ref()),
+        [
+            slide("intro", None, "slide"),
+            slide("setup", Some("Demo"), "fade"),
+            slide("live", Some("Demo"), "zoom"),
+            slide("outro", None, "slide"),
+        ]
+    );
+    assert_eq!(
+        deck.resolve(&slides, deck.profiles.get("short")),
+        [slide("intro", None, "slide"), slide("live", Some("Demo"), "zoom"), slide("bonus", Some("Demo"), "fade")]
+    );
+    assert_eq!(deck.resolve(&slides, None).len(), 5);
+}
@@ -0,0 +1,796 @@
+use coolslides_core::validation::{
+    validate_deck_cached, validate_deck_with_registry, validate_lockfile_drift, validate_plugins,
+    validate_transitions, ValidationError,
+};
+use coolslides_core::{ComponentManifest, ComponentRegistry, DeckManifest, ImportMap, Lockfile, SlideDoc};
+use std::path::Path;
+
+fn manifest(toml_str: &str) -> DeckManifest {
+    toml::from_str(toml_str).expect("parse deck manifest")
+}
+
+fn slide(toml_str: &str) -> SlideDoc {
+    toml::from_str(toml_str).expect("parse slide")
+}
+
+fn component(name: &str, tag: &str) -> ComponentManifest {
+    ComponentManifest {
+        name: name.to_string(),
+        version: "1.0.0".to_string(),
+        tag: tag.to_string(),
+        module: format!("/components/{}.js", name),
+        schema: serde_json::json!({ "type": "object" }),
+        tokens_used: vec![],
+        capabilities: vec![],
+        suggested_transition: None,
+        slots: None,
+        deprecated: None,
+    }
+}
+
+const DECK: &str = r#"
+modelVersion = "1.0"
+title = "Test"
+theme = "theme.css"
+sequence = ["intro"]
+
+[transitions]
+default = "slide"
+"#;
+
+const INTRO: &str = r#"
+modelVersion = "1.0"
+id = "intro"
+
+[component]
+name = "TitleSlide"
+versionReq = "^1"
+
+[props]
+title = "Hello"
+"#;
+
+#[test]
+fn tag_collision_names_both_components_and_sources() {
+    let mut registry = ComponentRegistry::default();
+    registry.insert(component("TitleSlide", "cs-title"), Path::new("a/TitleSlide.json"));
+    registry.insert(component("FancyTitle", "cs-title"), Path::new("b/FancyTitle.json"));
+
+    let result = validate_deck_with_registry(&manifest(DECK), &[slide(INTRO)], Some(&registry));
+
+    let collisions: Vec<_> = result
+        .errors
+        .iter()
+        .filter(|e| matches!(e, ValidationError::TagCollision { .. }))
+        .collect();
+    assert_eq!(collisions.len(), 1);
+    let msg = collisions[0].to_string();
+    assert!(msg.starts_with("CS3004"), "unexpected message: {}", msg);
+    assert!(msg.contains("FancyTitle") && msg.contains("TitleSlide"), "unexpected message: {}", msg);
+    assert!(msg.contains("a/TitleSlide.json") && msg.contains("b/FancyTitle.json"), "unexpected message: {}", msg);
+}
+
+#[test]
+fn distinct_tags_do_not_collide() {
+    let mut registry = ComponentRegistry::default();
+    registry.insert(component("TitleSlide", "cs-title"), Path::new("TitleSlide.json"));
+    registry.insert(component("QuoteSlide", "cs-quote"), Path::new("QuoteSlide.json"));
+
+    let result = validate_deck_with_registry(&manifest(DECK), &[slide(INTRO)], Some(&registry));
+    assert!(result.is_valid(), "unexpected errors: {:?}", result.errors);
+}
+
+fn deck_with_plugins(plugins: &[&str]) -> DeckManifest {
+    let mut deck = manifest(DECK);
+    deck.plugins = plugins.iter().map(|p| p.to_string()).collect();
+    deck
+}
+
+fn import_map(entries: &[(&str, &str)]) -> ImportMap {
+    ImportMap {
+        imports: entries.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+    }
+}
+
+#[test]
+fn plugins_resolve_through_import_map_prefixes_and_urls() {
+    let deck = deck_with_plugins(&[
+        "@coolslides/plugins-stdlib/poll",
+        "https://cdn.example.com/plugin.js",
+        "/packages/plugins-stdlib/dist/notes/index.js",
+    ]);
+    let map = import_map(&[("@coolslides/plugins-stdlib/", "/packages/plugins-stdlib/dist/")]);
+
+    let result = validate_plugins(&deck, Some(&map), None, Path::new("."));
+    assert!(result.is_valid(), "unexpected errors: {:?}", result.errors);
+}
+
+#[test]
+fn unresolved_plugins_are_errors() {
+    let deck = deck_with_plugins(&["@acme/missing", "./plugins/does-not-exist.js"]);
+
+    let result = validate_plugins(&deck, Some(&import_map(&[])), None, Path::new("."));
+    let unresolved: Vec<_> = result
+        .errors
+        .iter()
+        .filter(|e| matches!(e, ValidationError::UnresolvedPlugin { .. }))
+        .collect();
+    assert_eq!(unresolved.len(), 2);
+}
+
+#[test]
+fn locked_but_unlisted_plugins_warn() {
+    let deck = deck_with_plugins(&[]);
+    let map = import_map(&[("@acme/confetti", "https://cdn.example.com/confetti.js")]);
+    let lockfile: Lockfile = serde_json::from_value(serde_json::json!({
+        "modelVersion": "1.0",
+        "timestamp": "2025-01-01T00:00:00Z",
+        "importMap": { "imports": {} },
+        "resolved": {
+            "plugins": {
+                "@acme/confetti": { "version": "1.0.0", "url": "https://cdn.example.com/confetti.js" }
+            }
+        }
+    }))
+    .unwrap();
+
+    let result = validate_plugins(&deck, Some(&map), Some(&lockfile), Path::new("."));
+    assert!(result.is_valid());
+    assert_eq!(result.warnings.len(), 1);
+    assert!(matches!(
+        &result.warnings[0],
+        ValidationError::UnusedPlugin { plugin } if plugin == "@acme/confetti"
+    ));
+}
+
+#[test]
+fn unused_plugin_packages_warn_without_a_lockfile() {
+    let deck = deck_with_plugins(&["/packages/plugins-stdlib/dist/poll/index.js"]);
+    let map = import_map(&[
+        ("@coolslides/runtime", "/packages/runtime/dist/index.js"),
+        ("@coolslides/plugins-stdlib", "/packages/plugins-stdlib/dist/index.js"),
+        ("coolslides-plugin-confetti", "https://cdn.example.com/confetti.js"),
+    ]);
+
+    let result = validate_plugins(&deck, Some(&map), None, Path::new("."));
+    let unused: Vec<_> = result
+        .warnings
+        .iter()
+        .filter_map(|w| match w {
+            ValidationError::UnusedPlugin { plugin } => Some(plugin.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(unused, ["coolslides-plugin-confetti"]);
+}
+
+#[test]
+fn lockfile_drift_reports_unpinned_missing_and_orphaned() {
+    let deck = deck_with_plugins(&["@acme/confetti"]);
+    let map = import_map(&[
+        ("@coolslides/runtime", "/packages/runtime/dist/index.js"),
+        ("@acme/confetti", "https://cdn.example.com/confetti.js"),
+    ]);
+    let lockfile: Lockfile = serde_json::from_value(serde_json::json!({
+        "modelVersion": "1.0",
+        "timestamp": "2025-01-01T00:00:00Z",
+        "importMap": { "imports": {
+            "@coolslides/runtime": "/packages/runtime/dist/index.js",
+            "@acme/old": "https://cdn.example.com/old.js"
+        } },
+        "resolved": {
+            "components": {
+                "OldSlide": { "version": "1.0.0", "url": "https://cdn.example.com/old-slide.js" }
+            }
+        }
+    }))
+    .unwrap();
+
+    let result = validate_lockfile_drift(&lockfile, Some(&map), &deck, &[slide(INTRO)], None);
+
+    assert!(result.errors.iter().any(|e| matches!(
+        e,
+        ValidationError::UnpinnedImport { specifier } if specifier == "@acme/confetti"
+    )));
+    assert!(result.errors.iter().any(|e| matches!(
+        e,
+        ValidationError::MissingLockEntry { name, .. } if name == "@acme/confetti"
+    )));
+    let orphaned: Vec<String> = result
+        .warnings
+        .iter()
+        .filter(|w| w.code() == "CS2006")
+        .map(|w| w.to_string())
+        .collect();
+    assert!(orphaned.iter().any(|w| w.contains("@acme/old")));
+    assert!(orphaned.iter().any(|w| w.contains("OldSlide")));
+}
+
+const LINTED_DECK: &str = r#"
+modelVersion = "1.0"
+title = "Test"
+theme = "theme.css"
+sequence = ["intro"]
+
+[transitions]
+default = "slide"
+
+[lint.rules]
+CS1005 = "off"
+CS1002 = "warn"
+
+[[lint.custom]]
+rule = "require-prop"
+component = "TitleSlide"
+prop = "subtitle"
+severity = "warn"
+
+[[lint.custom]]
+rule = "forbid-tag"
+tag = "marquee"
+message = "No marquees, please"
+"#;
+
+#[test]
+fn lint_rules_override_severities() {
+    let mut deck = manifest(LINTED_DECK);
+    deck.sequence.push(coolslides_core::DeckItem::Ref { slide_id: "missing".to_string() });
+    let mut intro = slide(INTRO);
+    intro.slots.insert(
+        "body".to_string(),
+        coolslides_core::Slot::Markdown { value: String::new() },
+    );
+
+    let result = validate_deck_with_registry(&deck, &[intro], None);
+
+    // CS1002 demoted to a warning, CS1005 silenced entirely
+    assert!(result.is_valid(), "unexpected errors: {:?}", result.errors);
+    assert!(result.warnings.iter().any(|w| w.code() == "CS1002"));
+    assert!(!result.warnings.iter().any(|w| w.code() == "CS1005"));
+}
+
+#[test]
+fn custom_lint_rules_report_violations() {
+    let deck = manifest(LINTED_DECK);
+    let mut intro = slide(INTRO);
+    intro.slots.insert(
+        "body".to_string(),
+        coolslides_core::Slot::Markdown { value: "<marquee>Breaking news</marquee>".to_string() },
+    );
+
+    let result = validate_deck_with_registry(&deck, &[intro], None);
+
+    let messages: Vec<String> = result.errors.iter().map(|e| e.to_string()).collect();
+    assert_eq!(messages, vec!["CS1901: No marquees, please in slide intro"]);
+    assert!(result
+        .warnings
+        .iter()
+        .any(|w| w.to_string().contains("TitleSlide requires prop 'subtitle'")));
+}
+
+#[test]
+fn strict_policy_promotes_allows_and_silences_warnings() {
+    let mut result = coolslides_core::validation::ValidationResult::new();
+    result.add_warning(ValidationError::UnusedPlugin { plugin: "@acme/a".to_string() });
+    result.add_warning(ValidationError::OrphanedLockEntry {
+        kind: "plugin".to_string(),
+        name: "@acme/b".to_string(),
+    });
+    result.add_warning(ValidationError::EmptyMarkdownSlot {
+        slot: "body".to_string(),
+        slide_id: "intro".to_string(),
+    });
+
+    let policy = coolslides_core::StrictPolicy {
+        allow: vec!["CS2005".to_string()],
+        silence: vec!["cs2006".to_string()],
+    };
+    let promoted = coolslides_core::lint::apply_strict(&policy, &mut result);
+
+    assert_eq!(promoted, 1);
+    assert_eq!(result.errors.iter().map(|e| e.code()).collect::<Vec<_>>(), vec!["CS1005"]);
+    assert_eq!(result.warnings.iter().map(|w| w.code()).collect::<Vec<_>>(), vec!["CS2005"]);
+}
+
+#[test]
+fn unknown_transitions_are_reported_with_suggestions() {
+    let mut deck = manifest(DECK);
+    deck.transitions.default = "fde".to_string();
+    deck.transitions.overrides.insert("intro".to_string(), "wipe".to_string());
+    deck.sequence.push(coolslides_core::DeckItem::Group {
+        name: "outro".to_string(),
+        transition: Some("sparkle".to_string()),
+        slides: vec![],
+    });
+
+    let result = validate_transitions(&deck, None);
+    let messages: Vec<String> = result.errors.iter().map(|e| e.to_string()).collect();
+    assert_eq!(
+        messages,
+        vec![
+            "CS1007: Unknown transition 'fde' in transitions.default (did you mean 'fade'?)",
+            "CS1007: Unknown transition 'wipe' in transitions.overrides.intro",
+            "CS1007: Unknown transition 'sparkle' in group 'outro'",
+        ]
+    );
+
+    // Plugins can register additional transitions through their lockfile entry
+    let lockfile: Lockfile = serde_json::from_value(serde_json::json!({
+        "modelVersion": "1.0",
+        "resolved": {
+            "components": {},
+            "plugins": {
+                "@acme/transitions": {
+                    "version": "1.0.0",
+                    "url": "https://cdn.example.com/transitions.js",
+                    "transitions": ["wipe", "sparkle"]
+                }
+            }
+        },
+        "importMap": { "imports": {} },
+        "timestamp": "2024-01-01T00:00:00Z"
+    }))
+    .unwrap();
+    let result = validate_transitions(&deck, Some(&lockfile));
+    assert_eq!(result.errors.len(), 1);
+    assert_eq!(result.errors[0].code(), "CS1007");
+}
+
+#[test]
+fn transition_typos_carry_safe_fixes() {
+    let source = "[transitions]\ndefault = \"fde\"\n";
+    let mut deck = manifest(DECK);
+    deck.transitions.default = "fde".to_string();
+
+    let result = validate_transitions(&deck, None);
+    let fixes = result.errors[0].suggested_fixes("slides.toml", source);
+    assert_eq!(fixes.len(), 1);
+    assert!(fixes[0].safe);
+    let (fixed, applied) = coolslides_core::fixes::apply(source, &fixes);
+    assert_eq!((fixed.as_str(), applied), ("[transitions]\ndefault = \"fade\"\n", 1));
+}
+
+#[test]
+fn transition_fixes_leave_matching_slide_ids_alone() {
+    let source = "[transitions]\ndefault = \"fde\"\n\n[transitions.overrides]\nfde = \"fde\"\n\n[[sequence]]\nref = \"fde\"\n";
+    let mut deck = manifest(DECK);
+    deck.transitions.default = "fade".to_string();
+    deck.transitions.overrides.insert("fde".to_string(), "fde".to_string());
+
+    let result = validate_transitions(&deck, None);
+    let fixes = result.errors[0].suggested_fixes("slides.toml", source);
+    let (fixed, applied) = coolslides_core::fixes::apply(source, &fixes);
+    assert_eq!(applied, 1);
+    assert_eq!(fixed, source.replacen("fde = \"fde\"", "fde = \"fade\"", 1));
+}
+
+#[test]
+fn slots_are_checked_against_component_declarations() {
+    let mut two_col = component("TitleSlide", "cs-title-slide");
+    two_col.slots = Some(vec![
+        coolslides_core::SlotDeclaration { name: "left".to_string(), required: true, description: None, accepts: vec![] },
+        coolslides_core::SlotDeclaration {
+            name: "right".to_string(),
+            required: false,
+            description: None,
+            accepts: vec![coolslides_core::SlotKind::Component],
+        },
+    ]);
+    let mut registry = ComponentRegistry::default();
+    registry.insert(two_col, Path::new("TitleSlide.component.json"));
+
+    let mut intro = slide(INTRO);
+    intro.slots.insert(
+        "middle".to_string(),
+        coolslides_core::Slot::Markdown { value: "Lost".to_string() },
+    );
+    intro.slots.insert(
+        "right".to_string(),
+        coolslides_core::Slot::Markdown { value: "Not a component".to_string() },
+    );
+
+    let result = validate_deck_with_registry(&manifest(DECK), &[intro.clone()], Some(&registry));
+    let errors: Vec<String> = result.errors.iter().map(|e| e.to_string()).collect();
+    assert_eq!(
+        errors,
+        vec![
+            "CS3005: Slot 'middle' is not declared by component TitleSlide in slide intro (declared: left, right)",
+            "CS3009: Slot 'right' of component TitleSlide in slide intro is markdown, but the slot accepts component",
+        ]
+    );
+    assert!(result.warnings.iter().any(|w| w.code() == "CS3006"));
+
+    // Components without declarations accept any slot
+    registry.components.get_mut("TitleSlide").unwrap().slots = None;
+    let result = validate_deck_with_registry(&manifest(DECK), &[intro], Some(&registry));
+    assert!(result.is_valid());
+    assert!(!result.warnings.iter().any(|w| w.code() == "CS3006"));
+}
+
+#[test]
+fn version_req_resolves_among_registered_versions() {
+    let mut v2 = component("TitleSlide", "cs-title-slide-2");
+    v2.version = "2.1.0".to_string();
+    v2.schema = serde_json::json!({ "type": "object", "required": ["subtitle"] });
+    let mut registry = ComponentRegistry::default();
+    registry.insert(component("TitleSlide", "cs-title-slide"), Path::new("v1/TitleSlide.component.json"));
+    registry.insert(v2, Path::new("v2/TitleSlide.component.json"));
+
+    // The newest version is the default, but ^1 still resolves to 1.0.0 and its schema
+    assert_eq!(registry.components["TitleSlide"].version, "2.1.0");
+    assert_eq!(registry.resolve("TitleSlide", "^1").unwrap().tag, "cs-title-slide");
+    let result = validate_deck_with_registry(&manifest(DECK), &[slide(INTRO)], Some(&registry));
+    assert!(result.is_valid(), "{:?}", result.errors);
+
+    let unsatisfied = slide(&INTRO.replace("^1", "^3"));
+    let result = validate_deck_with_registry(&manifest(DECK), &[unsatisfied], Some(&registry));
+    assert_eq!(
+        result.errors.iter().map(|e| e.to_string()).collect::<Vec<_>>(),
+        vec!["CS3010: No version of component TitleSlide satisfies versionReq '^3' in slide intro (available: 2.1.0, 1.0.0)"]
+    );
+}
+
+#[test]
+fn deprecated_components_and_props_warn() {
+    let mut title: ComponentManifest = serde_json::from_value(serde_json::json!({
+        "name": "TitleSlide", "version": "1.0.0", "tag": "cs-title-slide", "module": "/t.js",
+        "deprecated": { "message": "Replaced by HeroSlide", "replacement": "HeroSlide" },
+        "schema": { "type": "object", "properties": {
+            "color": { "type": "string", "deprecated": { "replacement": "tone" } },
+            "tone": { "type": "string" }
+        } }
+    }))
+    .unwrap();
+    let mut registry = ComponentRegistry::default();
+    registry.insert(title.clone(), Path::new("TitleSlide.component.json"));
+
+    let mut intro = slide(INTRO);
+    intro.props = serde_json::json!({ "color": "red", "tone": "warm" });
+    let result = validate_deck_with_registry(&manifest(DECK), &[intro.clone()], Some(&registry));
+    assert!(result.is_valid(), "{:?}", result.errors);
+    assert_eq!(
+        result.warnings.iter().map(|w| w.to_string()).collect::<Vec<_>>(),
+        vec![
+            "CS3011: Component TitleSlide 1.0.0 used in slide intro is deprecated: Replaced by HeroSlide; use HeroSlide instead",
+            "CS3012: Prop 'color' of component TitleSlide in slide intro is deprecated: use tone instead",
+        ]
+    );
+    assert_eq!(result.warnings[1].fix().unwrap().replacement, "tone");
+
+    title.deprecated = None;
+    let mut registry = ComponentRegistry::default();
+    registry.insert(title, Path::new("TitleSlide.component.json"));
+    intro.props = serde_json::json!({ "tone": "warm" });
+    let result = validate_deck_with_registry(&manifest(DECK), &[intro], Some(&registry));
+    assert!(result.warnings.is_empty(), "{:?}", result.warnings);
+}
+
+#[test]
+fn prop_formats_are_validated() {
+    let mut image = component("TitleSlide", "cs-title-slide");
+    image.schema = serde_json::json!({
+        "type": "object",
+        "properties": {
+            "imageUrl": { "type": "string", "format": "uri" },
+            "accent": { "type": "string", "format": "color" },
+            "date": { "type": "string", "format": "date-time" }
+        }
+    });
+    let mut registry = ComponentRegistry::default();
+    registry.insert(image, Path::new("TitleSlide.component.json"));
+
+    let mut intro = slide(INTRO);
+    intro.props = serde_json::json!({
+        "imageUrl": "htp://example.com/cat.png",
+        "accent": "bluish",
+        "date": "yesterday"
+    });
+    let result = validate_deck_with_registry(&manifest(DECK), &[intro.clone()], Some(&registry));
+    let mut paths: Vec<String> = result
+        .errors
+        .iter()
+        .filter_map(|e| match e {
+            ValidationError::InvalidComponentProps { json_path, .. } => json_path.clone(),
+            _ => None,
+        })
+        .collect();
+    paths.sort();
+    assert_eq!(paths, vec!["props/accent", "props/date", "props/imageUrl"]);
+
+    intro.props = serde_json::json!({
+        "imageUrl": "https://example.com/cat.png",
+        "accent": "#007acc",
+        "date": "2024-05-01T09:00:00Z"
+    });
+    let result = validate_deck_with_registry(&manifest(DECK), &[intro], Some(&registry));
+    assert!(result.is_valid(), "unexpected errors: {:?}", result.errors);
+}
+
+#[test]
+fn conditions_referencing_unknown_tags_and_ids_warn() {
+    let mut deck = manifest(DECK);
+    deck.conditions = Some(coolslides_core::ConditionConfig {
+        include_tags: Some(vec!["intro".to_string(), "advnaced".to_string()]),
+        exclude_ids: Some(vec!["intro".to_string(), "bonus".to_string()]),
+    });
+    let mut intro = slide(INTRO);
+    intro.tags = vec!["intro".to_string()];
+
+    let result = validate_deck_with_registry(&deck, &[intro], None);
+
+    assert!(result.is_valid());
+    let warnings: Vec<String> = result.warnings.iter().map(|w| w.to_string()).collect();
+    assert_eq!(
+        warnings,
+        vec![
+            "CS1008: Condition includeTags references tag 'advnaced' that no slide carries",
+            "CS1009: Condition excludeIds references unknown slide id 'bonus'",
+        ]
+    );
+}
+
+#[test]
+fn style_overrides_outside_tokens_used_warn() {
+    let mut title = component("TitleSlide", "cs-title-slide");
+    title.tokens_used = vec!["--title-color".to_string()];
+    let mut registry = ComponentRegistry::default();
+    registry.insert(title, Path::new("TitleSlide.component.json"));
+
+    let mut intro = slide(INTRO);
+    intro.style_overrides.insert("--title-color".to_string(), "red".to_string());
+    intro.style_overrides.insert("--heading-color".to_string(), "red".to_string());
+
+    let result = validate_deck_with_registry(&manifest(DECK), &[intro], Some(&registry));
+    let warnings: Vec<String> = result.warnings.iter().map(|w| w.to_string()).collect();
+    assert_eq!(
+        warnings,
+        vec!["CS3007: Style override '--heading-color' in slide intro is not a token TitleSlide reads"]
+    );
+}
+
+#[test]
+fn markdown_slots_are_linted() {
+    let mut intro = slide(INTRO);
+    intro.slots.insert(
+        "body".to_string(),
+        coolslides_core::Slot::Markdown {
+            value: "See [the docs][manual] and [x].\n\n![](diagram.png)\n\n<iframe src=\"https://example.com\"></iframe>\n\n<strong>ok</strong>\n\n```js\nconsole.log('never closed')\n".to_string(),
+        },
+    );
+
+    let result = validate_deck_with_registry(&manifest(DECK), &[intro], None);
+
+    assert!(result.is_valid());
+    let warnings: Vec<String> = result.warnings.iter().map(|w| w.to_string()).collect();
+    assert_eq!(
+        warnings,
+        vec![
+            "CS4001: Unclosed code fence in slot 'body' of slide intro",
+            "CS4002: Reference link [manual] has no definition in slot 'body' of slide intro",
+            "CS4003: Image 'diagram.png' has no alt text in slot 'body' of slide intro",
+            "CS4004: Raw HTML <iframe> in slot 'body' of slide intro is removed in strict mode",
+        ]
+    );
+}
+
+#[test]
+fn timed_validation_matches_sequential_order() {
+    let mut registry = ComponentRegistry::default();
+    registry.insert(component("TitleSlide", "cs-title-slide"), Path::new("TitleSlide.component.json"));
+
+    let mut deck = manifest(DECK);
+    let slides: Vec<SlideDoc> = (0..50)
+        .map(|i| {
+            let mut s = slide(INTRO);
+            s.id = format!("slide-{:02}", i);
+            s.component.name = if i % 2 == 0 { "TitleSlide" } else { "Missing" }.to_string();
+            deck.sequence.push(coolslides_core::DeckItem::Ref { slide_id: s.id.clone() });
+            s
+        })
+        .collect();
+
+    let (result, timings) = coolslides_core::validation::validate_deck_timed(&deck, &slides, Some(&registry));
+
+    // Diagnostics come back in slide order even though slides are validated in parallel
+    let unknown: Vec<String> = result
+        .errors
+        .iter()
+        .filter_map(|e| match e {
+            ValidationError::UnknownComponent { slide_id, .. } => Some(slide_id.clone()),
+            _ => None,
+        })
+        .collect();
+    let expected: Vec<String> = (0..50).filter(|i| i % 2 == 1).map(|i| format!("slide-{:02}", i)).collect();
+    assert_eq!(unknown, expected);
+
+    assert_eq!(timings.slides.len(), 50);
+    assert_eq!(timings.schemas.len(), 1);
+    assert_eq!(timings.schemas[0].slides, 25);
+}
+
+#[test]
+fn cached_validation_only_revalidates_changed_slides() {
+    let mut registry = ComponentRegistry::default();
+    registry.insert(component("TitleSlide", "cs-title-slide"), Path::new("TitleSlide.component.json"));
+    let deck = manifest(DECK);
+    let mut slides: Vec<SlideDoc> = ["intro", "outro"]
+        .iter()
+        .map(|id| {
+            let mut s = slide(INTRO);
+            s.id = id.to_string();
+            s
+        })
+        .collect();
+    let mut cache = coolslides_core::cache::ValidationCache::new();
+
+    let first = validate_deck_cached(&deck, &slides, Some(&registry), &mut cache);
+    assert_eq!((cache.hits(), cache.misses()), (0, 2));
+
+    let second = validate_deck_cached(&deck, &slides, Some(&registry), &mut cache);
+    assert_eq!((cache.hits(), cache.misses()), (2, 0));
+    assert_eq!(first.errors.len(), second.errors.len());
+
+    // Editing one slide only revalidates that slide, and its new diagnostics are reported
+    slides[1].component.name = "Missing".to_string();
+    let third = validate_deck_cached(&deck, &slides, Some(&registry), &mut cache);
+    assert_eq!((cache.hits(), cache.misses()), (1, 1));
+    assert!(third.errors.iter().any(|e| e.code() == "CS3001"));
+
+    // Changing the component manifest invalidates slides that use it
+    registry.components.get_mut("TitleSlide").unwrap().tokens_used = vec!["--x".to_string()];
+    validate_deck_cached(&deck, &slides, Some(&registry), &mut cache);
+    assert_eq!((cache.hits(), cache.misses()), (1, 1));
+}
+
+#[test]
+fn budgets_warn_when_exceeded() {
+    let deck_root = std::env::temp_dir().join(format!("coolslides-budgets-{}", std::process::id()));
+    std::fs::create_dir_all(&deck_root).unwrap();
+    std::fs::write(deck_root.join("theme.css"), "body {}").unwrap();
+    std::fs::write(deck_root.join("big.png"), vec![0u8; 2048]).unwrap();
+
+    let mut deck = manifest(DECK);
+    deck.lint = Some(toml::from_str(
+        "[budgets]\nmaxPropsBytes = 64\nmaxMarkdownChars = 10\nmaxAssetBytes = 1024\nmaxSlidesPerGroup = 1",
+    ).unwrap());
+    deck.sequence.push(coolslides_core::DeckItem::Group {
+        name: "appendix".to_string(),
+        transition: None,
+        slides: vec!["intro".to_string(), "intro".to_string()],
+    });
+    let mut intro = slide(INTRO);
+    intro.props = serde_json::json!({ "title": "A title that is long enough to blow the tiny props budget" });
+    intro.slots.insert(
+        "body".to_string(),
+        coolslides_core::Slot::Markdown { value: "![Big picture](big.png)".to_string() },
+    );
+
+    let result = coolslides_core::validation::validate_budgets(&deck, &[intro], Some(&deck_root));
+    std::fs::remove_dir_all(&deck_root).unwrap();
+
+    let codes: Vec<&str> = result.warnings.iter().map(|w| w.code()).collect();
+    assert_eq!(codes, vec!["CS4005", "CS4006", "CS4007", "CS4008"]);
+    assert!(result.warnings[2].to_string().contains("big.png"));
+}
+
+#[test]
+fn print_footer_templates_are_checked() {
+    let mut deck = manifest(DECK);
+    deck.print = Some(toml::from_str("pageNumbers = false\nfooterTemplate = \"{title} - Page {pageNumber} of {pages}\"").unwrap());
+
+    let result = validate_deck_with_registry(&deck, &[slide(INTRO)], None);
+    let errors: Vec<String> = result.errors.iter().map(|e| e.to_string()).collect();
+    assert_eq!(
+        errors,
+        vec!["CS1010: Unknown placeholder {pages} in print.footerTemplate (known: pageNumber, totalPages, title, date, url)"]
+    );
+    assert_eq!(
+        result.warnings.iter().map(|w| w.to_string()).collect::<Vec<_>>(),
+        vec!["CS1012: print.footerTemplate uses {pageNumber} but page numbers are disabled"]
+    );
+
+    assert!(coolslides_core::validation::template_placeholders("{{literal}} {date}").unwrap() == vec!["date"]);
+    let malformed = coolslides_core::validation::validate_print_template("Page {pageNumber", true, "footer");
+    assert_eq!(malformed.errors[0].code(), "CS1011");
+}
+
+#[test]
+fn deck_notes_must_reference_slides() {
+    let mut deck = manifest(DECK);
+    deck.notes.insert("intro".to_string(), "Welcome".to_string());
+    deck.notes.insert("intor".to_string(), "Typo".to_string());
+
+    let result = validate_deck_with_registry(&deck, &[slide(INTRO)], None);
+    let errors: Vec<String> = result.errors.iter().map(|e| e.to_string()).collect();
+    assert_eq!(errors, vec!["CS1013: Deck notes key 'intor' does not match any slide (did you mean 'intro'?)"]);
+}
+
+#[test]
+fn spelling_pass_uses_slide_locale_and_allow_list() {
+    let deck_root = std::env::temp_dir().join(format!("coolslides-spelling-{}", std::process::id()));
+    std::fs::create_dir_all(deck_root.join("dictionaries")).unwrap();
+    std::fs::write(deck_root.join("dictionaries/en_US.aff"), "SET UTF-8\nTRY esianrtolcdugmphbyfvkwz\n").unwrap();
+    std::fs::write(deck_root.join("dictionaries/en_US.dic"), "5\nthe\nquick\nbrown\nfox\nuses\n").unwrap();
+
+    let mut deck = manifest(DECK);
+    deck.lint = Some(toml::from_str("[spelling]\nenabled = true\nallow = [\"coolslides\"]").unwrap());
+    let mut intro = slide(INTRO);
+    intro.props = serde_json::json!({ "title": "The quick fox" });
+    intro.slots.insert(
+        "body".to_string(),
+        coolslides_core::Slot::Markdown { value: "The quikc brown fox uses Coolslides\n\n```\nnot checkd\n```".to_string() },
+    );
+    let mut german = slide(INTRO);
+    german.id = "german".to_string();
+    german.locale = Some("de-DE".to_string());
+
+    let result = coolslides_core::spelling::validate_spelling(&deck, &[intro, german], &deck_root);
+    std::fs::remove_dir_all(&deck_root).unwrap();
+
+    let warnings: Vec<String> = result.warnings.iter().map(|w| w.to_string()).collect();
+    assert_eq!(
+        warnings,
+        vec![
+            "CS4009: Possible misspelling 'quikc' in slot 'body' of slide intro (did you mean 'quick'?)",
+            "CS4010: No dictionary found for locale 'de-DE'; 1 slide(s) were not spell-checked",
+        ]
+    );
+}
+
+#[test]
+fn slot_ids_are_unique_across_the_deck() {
+    let component_slot = |slot_id: &str| coolslides_core::Slot::Component {
+        tag: "cs-chart".to_string(),
+        module: "/components/chart.js".to_string(),
+        props: serde_json::Value::Null,
+        defer: None,
+        slot_id: Some(slot_id.to_string()),
+        print_fallback: None,
+    };
+    let mut intro = slide(INTRO);
+    intro.slots.insert("chart".to_string(), component_slot("sales"));
+    let mut second = slide(INTRO);
+    second.id = "second".to_string();
+    second.slots.insert("left".to_string(), component_slot("sales"));
+    second.slots.insert("right".to_string(), component_slot("intro"));
+
+    let result = validate_deck_with_registry(&manifest(DECK), &[intro, second], None);
+    let errors: Vec<String> = result.errors.iter().filter(|e| e.code() == "CS1014").map(|e| e.to_string()).collect();
+    assert_eq!(
+        errors,
+        vec![
+            "CS1014: Slot id 'sales' in slot 'left' of slide second is already used by slot 'chart' of slide intro",
+            "CS1014: Slot id 'intro' in slot 'right' of slide second is already used by slide intro",
+        ]
+    );
+}
+
+#[test]
+fn narration_is_checked() {
+    let deck_root = std::env::temp_dir().join(format!("coolslides-narration-{}", std::process::id()));
+    std::fs::create_dir_all(deck_root.join("audio")).unwrap();
+    std::fs::write(deck_root.join("audio/intro.mp3"), b"ID3").unwrap();
+
+    let narrated = |src: &str, duration: f64| {
+        let mut slide = slide(INTRO);
+        slide.narration = Some(coolslides_core::Narration { src: src.to_string(), duration });
+        slide
+    };
+    let slides = vec![
+        narrated("audio/intro.mp3", 12.5),
+        narrated("audio/missing.ogg", 3.0),
+        narrated("https://media.example.com/intro.m4a?v=2", 8.0),
+    ];
+    let missing = coolslides_core::validation::validate_narration(&slides, &deck_root);
+    std::fs::remove_dir_all(&deck_root).unwrap();
+    assert_eq!(
+        missing.errors.iter().map(|e| e.to_string()).collect::<Vec<_>>(),
+        vec!["CS1016: Narration file 'audio/missing.ogg' of slide intro does not exist"]
+    );
+    assert!(coolslides_core::validation::validate_slide(&slides[2]).is_valid());
+
+    let invalid = coolslides_core::validation::validate_slide(&narrated("audio/intro.mp3", 0.0));
+    assert_eq!(invalid.errors[0].code(), "CS1015");
+    let not_audio = coolslides_core::validation::validate_slide(&narrated("slides/intro.pdf", 4.0));
+    assert!(not_audio.errors[0].to_string().contains("not a supported audio file"));
+}
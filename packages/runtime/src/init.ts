@@ -133,6 +133,21 @@ export async function init(deck?: DeckManifest, slides?: SlideDoc[]): Promise<Ru
     } catch {}
   });
   
+  // The dev server swaps an edited slide's element in place; bring its state back
+  document.addEventListener('coolslides:slide-updated', async (e: any) => {
+    const slideId = e.detail?.slideId;
+    if (typeof slideId !== 'string') return;
+    try {
+      const response = await fetch(`/api/slide/${encodeURIComponent(slideId)}`);
+      if (response.ok) {
+        context.slides.set(slideId, await response.json());
+      }
+    } catch {}
+    router.refresh();
+    propertyManager.applyPropsForSlide(slideId);
+    bus.emit('slide:updated', { slideId });
+  });
+
   // Rely on per-slide preloading; avoid global eager loading that breaks defer="visible"
   // await moduleLoader.preloadAllSlotComponents();
 
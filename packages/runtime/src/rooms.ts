@@ -7,13 +7,17 @@ import { EventBus } from './types.js';
 export interface RoomsClientOptions {
   roomId?: string;
   url?: string; // override ws url
+  token?: string; // presenter token; without it the server treats the page as audience
+  reconnect?: boolean; // reconnect and resume after the connection drops (default true)
 }
 
 export class RoomsClient {
   private bus: EventBus;
   private ws: WebSocket | null = null;
   private opts: RoomsClientOptions;
-  // Track state if needed later
+  // From the server's session message; resuming with it backfills what was missed
+  private resumeToken: string | null = null;
+  private lastSeq = 0;
 
   constructor(bus: EventBus, opts: RoomsClientOptions = {}) {
     this.bus = bus;
@@ -21,16 +25,36 @@ export class RoomsClient {
   }
 
   connect(): void {
-    const roomId = this.opts.roomId || this.getRoomIdFromURL() || 'default';
-    const url = this.opts.url || this.computeWsUrl(`/rooms/${encodeURIComponent(roomId)}`);
+    const roomId = this.opts.roomId || this.getURLParam('room') || 'default';
+    const token = this.opts.token || this.getURLParam('token');
+    // The token goes in the join message rather than the URL, which ends up in logs
+    let url = this.opts.url || this.computeWsUrl(`/rooms/${encodeURIComponent(roomId)}`);
+    if (this.resumeToken) {
+      const resume = new URL(url);
+      resume.searchParams.set('resume', this.resumeToken);
+      resume.searchParams.set('lastSeq', String(this.lastSeq));
+      url = resume.href;
+    }
     try {
       this.ws = new WebSocket(url);
-      this.ws.onopen = () => { this.bus.emit('rooms:open', { roomId }); };
-      this.ws.onclose = () => { this.bus.emit('rooms:close', { roomId }); };
+      this.ws.onopen = () => {
+        if (token) this.join('presenter', token);
+        this.bus.emit('rooms:open', { roomId });
+      };
+      this.ws.onclose = () => {
+        this.bus.emit('rooms:close', { roomId });
+        if (this.opts.reconnect !== false) setTimeout(() => this.connect(), 1000);
+      };
       this.ws.onerror = (e) => { this.bus.emit('rooms:error', e); };
       this.ws.onmessage = (evt) => {
         try {
           const msg = JSON.parse(evt.data);
+          if (typeof msg.seq === 'number') this.lastSeq = msg.seq;
+          if (msg.type === 'session') {
+            this.resumeToken = msg.resume_token;
+            // A fresh session starts from the room's state, not from what this page saw
+            if (!msg.resumed) this.lastSeq = msg.seq;
+          }
           this.bus.emit('rooms:message', msg);
           if (msg.type === 'event' && msg.event) {
             this.bus.emit(`rooms:event:${msg.event.name}`, msg.event);
@@ -42,6 +66,36 @@ export class RoomsClient {
     }
   }
 
+  /** Ask for a role; presenting needs the presenter token */
+  join(role: 'presenter' | 'audience', token?: string): void {
+    if (!this.ws || this.ws.readyState !== WebSocket.OPEN) return;
+    this.ws.send(JSON.stringify({ type: 'join', role, token }));
+  }
+
+  /** Vote in an open poll by option index; voting again replaces the vote */
+  vote(pollId: string, option: number): void {
+    if (!this.ws || this.ws.readyState !== WebSocket.OPEN) return;
+    this.ws.send(JSON.stringify({ type: 'vote', poll_id: pollId, option }));
+  }
+
+  /** Ask the presenter a question; it shows once they approve it */
+  ask(text: string): void {
+    if (!this.ws || this.ws.readyState !== WebSocket.OPEN) return;
+    this.ws.send(JSON.stringify({ type: 'ask', text }));
+  }
+
+  /** Upvote an approved question */
+  upvote(questionId: string): void {
+    if (!this.ws || this.ws.readyState !== WebSocket.OPEN) return;
+    this.ws.send(JSON.stringify({ type: 'upvote', question_id: questionId }));
+  }
+
+  /** Send a reaction such as 'clap' or 'heart'; the room broadcasts counts, not each one */
+  react(reaction: string): void {
+    if (!this.ws || this.ws.readyState !== WebSocket.OPEN) return;
+    this.ws.send(JSON.stringify({ type: 'react', reaction }));
+  }
+
   sendEvent(name: string, data: any = {}): void {
     if (!this.ws || this.ws.readyState !== WebSocket.OPEN) return;
     const payload = JSON.stringify({ type: 'event', event: { name, data, client_id: 'runtime' }, timestamp: Date.now() });
@@ -53,10 +107,10 @@ export class RoomsClient {
     return `${proto}://${location.host}${path}`;
   }
 
-  private getRoomIdFromURL(): string | null {
+  private getURLParam(name: string): string | null {
     try {
       const u = new URL(location.href);
-      return u.searchParams.get('room');
+      return u.searchParams.get(name);
     } catch { return null; }
   }
 }
@@ -97,13 +97,18 @@ export class SlideRouter implements Router {
   }
 
   private loadFromHash(): void {
-    const hash = window.location.hash.slice(1); // Remove #
+    // Both #slideId and #/slideId are accepted
+    const hash = window.location.hash.slice(1).replace(/^\//, '');
     if (!hash) {
-      this.navigate(this.slideSequence[0] || '', 0);
+      // Pages served at /slide/:id name the slide they open on
+      const initial = document.querySelector<HTMLMetaElement>('meta[name="coolslides:initial-slide"]')?.content;
+      const slideId = initial && this.context.slides.has(initial) ? initial : this.slideSequence[0];
+      this.navigate(slideId || '', 0);
       return;
     }
 
-    const [slideId, fragmentStr] = hash.split('/');
+    const [encodedId, fragmentStr] = hash.split('/');
+    const slideId = decodeURIComponent(encodedId);
     const fragment = fragmentStr ? parseInt(fragmentStr, 10) : 0;
     
     if (slideId && this.context.slides.has(slideId)) {
@@ -154,6 +159,13 @@ export class SlideRouter implements Router {
     this.updateSlideDisplay();
   }
 
+  /**
+   * Re-apply the active state and fragments, e.g. after a slide's element was replaced
+   */
+  refresh(): void {
+    this.updateSlideDisplay();
+  }
+
   getCurrentSlide(): string | null {
     return this.currentSlideId;
   }
@@ -103,6 +103,9 @@ export class DefaultSpeakerView implements SpeakerView {
     
     // Update progress
     this.updateProgress();
+    
+    // Update the revision on screen
+    this.updateRevision();
   }
 
   private initializeSpeakerWindow(): void {
@@ -178,6 +181,12 @@ export class DefaultSpeakerView implements SpeakerView {
           height: 200%;
           overflow: hidden;
         }
+
+        .slide-thumbnail {
+          display: block;
+          width: 100%;
+          height: auto;
+        }
         
         .notes {
           grid-area: notes;
@@ -236,6 +245,16 @@ export class DefaultSpeakerView implements SpeakerView {
           font-weight: 600;
         }
         
+        .revision {
+          font-family: ui-monospace, monospace;
+          font-size: 14px;
+          color: #bbb;
+        }
+        
+        .revision.dirty {
+          color: #ffb347;
+        }
+        
         .progress {
           flex: 1;
           margin: 0 20px;
@@ -270,6 +289,7 @@ export class DefaultSpeakerView implements SpeakerView {
     doc.body.innerHTML = `
       <div class="header">
         <h1>${this.context.deck.title}</h1>
+        <div class="revision" id="revision"></div>
         <div class="timer" id="timer">00:00:00</div>
       </div>
       
@@ -333,6 +353,20 @@ export class DefaultSpeakerView implements SpeakerView {
       return;
     }
 
+    if (type === 'next') {
+      // The dev server renders and caches thumbnails; static exports fall back to the cloned markup
+      const thumbnail = this.speakerWindow.document.createElement('img');
+      thumbnail.className = 'slide-thumbnail';
+      thumbnail.alt = `Next slide: ${slideId}`;
+      thumbnail.onerror = () => this.clonePreview(previewElement, slideId);
+      thumbnail.src = new URL(`api/thumbnails/${encodeURIComponent(slideId)}?w=640`, location.href).href;
+      previewElement.replaceChildren(thumbnail);
+      return;
+    }
+    this.clonePreview(previewElement, slideId);
+  }
+
+  private clonePreview(previewElement: HTMLElement, slideId: string): void {
     // Clone the slide from the main window for preview
     const mainSlideElement = document.querySelector(`[data-slide="${slideId}"]`);
     if (mainSlideElement) {
@@ -390,6 +424,24 @@ export class DefaultSpeakerView implements SpeakerView {
       .join('; ');
   }
 
+  private async updateRevision(): Promise<void> {
+    // Only the dev server knows the revision; static exports have no /api/version
+    let info: any;
+    try {
+      const response = await fetch('api/version');
+      if (!response.ok) return;
+      info = await response.json();
+    } catch {
+      return;
+    }
+    const element = this.speakerWindow?.document.getElementById('revision');
+    if (!element || !info.git) return;
+    const git = info.git;
+    element.textContent = `${git.branch ?? 'detached'} @ ${git.shortCommit}${git.dirty ? ' (uncommitted changes)' : ''}`;
+    element.title = git.subject;
+    element.classList.toggle('dirty', git.dirty);
+  }
+
   private escapeHtml(unsafe: string): string {
     return unsafe
       .replace(/&/g, '&amp;')
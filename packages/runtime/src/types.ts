@@ -37,6 +37,7 @@ export interface DeckManifest {
   transitions: TransitionConfig;
   sequence: DeckItem[];
   conditions?: ConditionConfig;
+  profiles?: Record<string, ConditionConfig>;
   print?: PrintConfig;
 }
 
@@ -121,6 +122,7 @@ export interface Router {
   lastSlide(): void;
   nextFragment(): boolean;
   prevFragment(): boolean;
+  refresh(): void;
 }
 
 export interface EventBus {